@@ -67,7 +67,8 @@ make_vendor "wasi-tls" "
 
 make_vendor "wasi-config" "config@f4d699b"
 
-make_vendor "wasi-keyvalue" "keyvalue@219ea36"
+make_vendor "wasi-keyvalue" "keyvalue@v0.2.0-draft2"
+make_vendor "wasi-keyvalue/src/draft" "keyvalue@219ea36"
 
 make_vendor "wasi/src/p3" "
     cli@v0.3.0-rc-2025-09-16@wit-0.3.0-draft
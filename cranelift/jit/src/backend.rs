@@ -10,9 +10,11 @@ use cranelift_codegen::settings::Configurable;
 use cranelift_codegen::{ir, settings};
 use cranelift_control::ControlPlane;
 use cranelift_entity::SecondaryMap;
+#[cfg(any(feature = "gdb-jit", feature = "perf-jitdump"))]
+use cranelift_module::DwarfFunction;
 use cranelift_module::{
-    DataDescription, DataId, FuncId, Init, Linkage, Module, ModuleDeclarations, ModuleError,
-    ModuleReloc, ModuleRelocTarget, ModuleResult,
+    DataDescription, DataId, FuncId, FunctionDebugInfo, Init, Linkage, Module, ModuleDeclarations,
+    ModuleError, ModuleReloc, ModuleRelocTarget, ModuleResult,
 };
 use log::info;
 use std::cell::RefCell;
@@ -20,10 +22,12 @@ use std::collections::HashMap;
 use std::ffi::CString;
 use std::io::Write;
 use std::ptr;
-use target_lexicon::PointerWidth;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use target_lexicon::{Architecture, PointerWidth};
 
 const WRITABLE_DATA_ALIGNMENT: u64 = 0x8;
 const READONLY_DATA_ALIGNMENT: u64 = 0x1;
+const PLT_ENTRY_SIZE: usize = 16;
 
 /// A builder for `JITModule`.
 pub struct JITBuilder {
@@ -32,6 +36,11 @@ pub struct JITBuilder {
     lookup_symbols: Vec<Box<dyn Fn(&str) -> Option<*const u8> + Send>>,
     libcall_names: Box<dyn Fn(ir::LibCall) -> String + Send + Sync>,
     memory: Option<Box<dyn JITMemoryProvider + Send>>,
+    hotswap_enabled: bool,
+    #[cfg(feature = "gdb-jit")]
+    gdb_jit: bool,
+    #[cfg(feature = "perf-jitdump")]
+    perf_jitdump: bool,
 }
 
 impl JITBuilder {
@@ -96,6 +105,11 @@ impl JITBuilder {
             lookup_symbols,
             libcall_names,
             memory: None,
+            hotswap_enabled: false,
+            #[cfg(feature = "gdb-jit")]
+            gdb_jit: false,
+            #[cfg(feature = "perf-jitdump")]
+            perf_jitdump: false,
         }
     }
 
@@ -154,6 +168,41 @@ impl JITBuilder {
         self.memory = Some(provider);
         self
     }
+
+    /// Enable or disable hotswap support.
+    ///
+    /// When enabled, functions are called through a GOT entry and a PLT stub,
+    /// which allows [`JITModule::prepare_for_function_redefine`] to replace
+    /// the body of an already finalized function. This requires an ISA built
+    /// with `is_pic=true` (see [`JITBuilder::with_isa`]) and is currently
+    /// only supported on x86_64 and aarch64.
+    pub fn hotswap(&mut self, enabled: bool) -> &mut Self {
+        self.hotswap_enabled = enabled;
+        self
+    }
+
+    /// Register finalized functions with debuggers through the GDB JIT
+    /// interface.
+    ///
+    /// Each function is described by an in-memory ELF image whose DWARF is
+    /// built from the [`FunctionDebugInfo`] attached through
+    /// [`JITModule::set_function_debug_info`], if any.
+    #[cfg(feature = "gdb-jit")]
+    pub fn gdb_jit(&mut self, enabled: bool) -> &mut Self {
+        self.gdb_jit = enabled;
+        self
+    }
+
+    /// Record finalized functions in a `jit-<pid>.dump` file in the current
+    /// directory, which `perf inject --jit` uses to symbolize samples.
+    ///
+    /// Line tables are included for functions that have
+    /// [`FunctionDebugInfo`] attached. This only has an effect on Linux.
+    #[cfg(feature = "perf-jitdump")]
+    pub fn perf_jitdump(&mut self, enabled: bool) -> &mut Self {
+        self.perf_jitdump = enabled;
+        self
+    }
 }
 
 /// A wrapper that impls Send for the contents.
@@ -179,6 +228,20 @@ pub struct JITModule {
     code_ranges: Vec<(usize, usize, FuncId)>,
     functions_to_finalize: Vec<FuncId>,
     data_objects_to_finalize: Vec<DataId>,
+    hotswap_enabled: bool,
+    function_got_entries: SecondaryMap<FuncId, Option<SendWrapper<*const AtomicPtr<u8>>>>,
+    function_plt_entries: SecondaryMap<FuncId, Option<SendWrapper<*const u8>>>,
+    data_object_got_entries: SecondaryMap<DataId, Option<SendWrapper<*const AtomicPtr<u8>>>>,
+    libcall_got_entries: HashMap<ir::LibCall, SendWrapper<*const AtomicPtr<u8>>>,
+    retired_functions: Vec<(FuncId, CompiledBlob)>,
+    function_debug_info: SecondaryMap<FuncId, Option<FunctionDebugInfo>>,
+    #[cfg(feature = "gdb-jit")]
+    gdb_jit: bool,
+    #[cfg(feature = "gdb-jit")]
+    gdb_registrations:
+        HashMap<FuncId, SendWrapper<wasmtime_jit_debug::gdb_jit_int::GdbJitImageRegistration>>,
+    #[cfg(feature = "perf-jitdump")]
+    perf_jitdump: bool,
 }
 
 impl JITModule {
@@ -211,7 +274,122 @@ impl JITModule {
         }
     }
 
+    fn get_got_entry(&self, name: &ModuleRelocTarget) -> *const u8 {
+        let got_entry = match name {
+            ModuleRelocTarget::User { .. } if ModuleDeclarations::is_function(name) => {
+                self.function_got_entries[FuncId::from_name(name)]
+            }
+            ModuleRelocTarget::User { .. } => self.data_object_got_entries[DataId::from_name(name)],
+            ModuleRelocTarget::LibCall(libcall) => self.libcall_got_entries.get(libcall).copied(),
+            name => panic!("invalid name {name:?}"),
+        };
+        got_entry
+            .unwrap_or_else(|| {
+                panic!("no GOT entry for {name}; GOT relocations require hotswapping to be enabled")
+            })
+            .0
+            .cast()
+    }
+
+    fn new_got_entry(&mut self, val: *const u8) -> ModuleResult<*const AtomicPtr<u8>> {
+        let got_entry = self
+            .memory
+            .allocate_readwrite(
+                size_of::<AtomicPtr<u8>>(),
+                align_of::<AtomicPtr<u8>>() as u64,
+            )
+            .map_err(|e| ModuleError::Allocation {
+                message: "unable to alloc GOT entry",
+                err: e,
+            })?
+            .cast::<AtomicPtr<u8>>();
+        unsafe { got_entry.write(AtomicPtr::new(val.cast_mut())) };
+        Ok(got_entry)
+    }
+
+    fn new_plt_entry(&mut self, got_entry: *const AtomicPtr<u8>) -> ModuleResult<*const u8> {
+        let plt_entry = self
+            .memory
+            .allocate_readexec(PLT_ENTRY_SIZE, self.isa.symbol_alignment().max(16))
+            .map_err(|e| ModuleError::Allocation {
+                message: "unable to alloc PLT entry",
+                err: e,
+            })?;
+        let bytes = match self.isa.triple().architecture {
+            Architecture::X86_64 => x86_64_plt_entry(plt_entry, got_entry),
+            Architecture::Aarch64(_) => aarch64_plt_entry(plt_entry, got_entry),
+            arch => unreachable!("hotswapping isn't supported on {arch}"),
+        };
+        unsafe { ptr::copy_nonoverlapping(bytes.as_ptr(), plt_entry, PLT_ENTRY_SIZE) };
+        Ok(plt_entry)
+    }
+
+    /// Creates the GOT and PLT entries of a function if they don't exist yet.
+    fn create_function_indirection(&mut self, func_id: FuncId) -> ModuleResult<()> {
+        if self.function_got_entries[func_id].is_some() {
+            return Ok(());
+        }
+        let decl = self.declarations.get_function_decl(func_id);
+        let plt_name = format!("{}@plt", decl.linkage_name(func_id));
+        // Definable functions get their GOT entry filled in once they are
+        // finalized; imports are resolved right away.
+        let val = if decl.linkage.is_definable() {
+            ptr::null()
+        } else {
+            self.get_definition_address(&ModuleRelocTarget::user(0, func_id.as_u32()))
+        };
+        let got_entry = self.new_got_entry(val)?;
+        let plt_entry = self.new_plt_entry(got_entry)?;
+        self.record_function_for_perf(plt_entry.cast_mut(), PLT_ENTRY_SIZE, &plt_name);
+        self.function_got_entries[func_id] = Some(SendWrapper(got_entry));
+        self.function_plt_entries[func_id] = Some(SendWrapper(plt_entry));
+        Ok(())
+    }
+
+    /// Creates the GOT and PLT entries needed to apply `reloc`.
+    fn create_reloc_indirection(&mut self, reloc: &ModuleReloc) -> ModuleResult<()> {
+        let needs_got = matches!(
+            reloc.kind,
+            Reloc::X86GOTPCRel4 | Reloc::Aarch64AdrGotPage21 | Reloc::Aarch64Ld64GotLo12Nc
+        );
+        match &reloc.name {
+            name @ ModuleRelocTarget::User { .. } if ModuleDeclarations::is_function(name) => {
+                self.create_function_indirection(FuncId::from_name(name))?;
+            }
+            name @ ModuleRelocTarget::User { .. } if needs_got => {
+                let data_id = DataId::from_name(name);
+                if self.data_object_got_entries[data_id].is_none() {
+                    let got_entry = self.new_got_entry(self.get_definition_address(name))?;
+                    self.data_object_got_entries[data_id] = Some(SendWrapper(got_entry));
+                }
+            }
+            name @ ModuleRelocTarget::LibCall(libcall) if needs_got => {
+                if !self.libcall_got_entries.contains_key(libcall) {
+                    let got_entry = self.new_got_entry(self.get_definition_address(name))?;
+                    self.libcall_got_entries
+                        .insert(*libcall, SendWrapper(got_entry));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
     fn get_address(&self, name: &ModuleRelocTarget) -> *const u8 {
+        // With hotswapping every reference to a function goes through its PLT
+        // entry, so that later redefinitions are picked up.
+        if self.hotswap_enabled
+            && matches!(name, ModuleRelocTarget::User { .. })
+            && ModuleDeclarations::is_function(name)
+        {
+            return self.function_plt_entries[FuncId::from_name(name)]
+                .expect("PLT entry must have been created")
+                .0;
+        }
+        self.get_definition_address(name)
+    }
+
+    fn get_definition_address(&self, name: &ModuleRelocTarget) -> *const u8 {
         match name {
             ModuleRelocTarget::User { .. } => {
                 let (name, linkage) = if ModuleDeclarations::is_function(name) {
@@ -261,17 +439,81 @@ impl JITModule {
 
     /// Returns the address of a finalized function.
     ///
-    /// The pointer remains valid until either [`JITModule::free_memory`] is called or in the future
-    /// some way of deallocating this individual function is used.
+    /// The pointer remains valid until [`JITModule::free_memory`] is called.
+    /// When hotswapping is enabled this is the address of the function's PLT
+    /// entry, which always forwards to the most recently finalized definition.
     pub fn get_finalized_function(&self, func_id: FuncId) -> *const u8 {
         let info = &self.compiled_functions[func_id];
         assert!(
             !self.functions_to_finalize.iter().any(|x| *x == func_id),
             "function not yet finalized"
         );
-        info.as_ref()
+        let ptr = info
+            .as_ref()
             .expect("function must be compiled before it can be finalized")
-            .ptr
+            .ptr;
+        if self.hotswap_enabled {
+            self.function_plt_entries[func_id].unwrap().0
+        } else {
+            ptr
+        }
+    }
+
+    /// Allow a finalized function to be defined again.
+    ///
+    /// Once the new definition is finalized, every call going through the
+    /// function's PLT entry, including calls from other functions and calls
+    /// through [`JITModule::get_finalized_function`], uses the new body. The
+    /// old body is retired rather than freed as it may still be executing; see
+    /// [`JITModule::free_retired_functions`].
+    ///
+    /// Panics if hotswapping isn't enabled.
+    pub fn prepare_for_function_redefine(&mut self, func_id: FuncId) -> ModuleResult<()> {
+        assert!(self.hotswap_enabled, "hotswap support is not enabled");
+        let decl = self.declarations.get_function_decl(func_id);
+        if !decl.linkage.is_definable() {
+            return Err(ModuleError::InvalidImportDefinition(
+                decl.linkage_name(func_id).into_owned(),
+            ));
+        }
+
+        let Some(compiled) = self.compiled_functions[func_id].take() else {
+            return Err(ModuleError::Backend(anyhow::anyhow!(
+                "tried to redefine not yet defined function {}",
+                decl.linkage_name(func_id)
+            )));
+        };
+        self.functions_to_finalize.retain(|&f| f != func_id);
+        self.code_ranges.retain(|&(_, _, f)| f != func_id);
+        self.retired_functions.push((func_id, compiled));
+        Ok(())
+    }
+
+    /// Free the memory of function bodies retired by
+    /// [`JITModule::prepare_for_function_redefine`] through
+    /// [`JITMemoryProvider::free_readexec`].
+    ///
+    /// Bodies whose replacement hasn't been finalized yet are kept.
+    ///
+    /// # Safety
+    ///
+    /// None of the retired function bodies may be executing, and no pointer
+    /// to them obtained before their redefinition may be called afterwards.
+    pub unsafe fn free_retired_functions(&mut self) {
+        let mut still_in_use = Vec::new();
+        for (func_id, compiled) in std::mem::take(&mut self.retired_functions) {
+            // Functions which were never finalized have no GOT entry, so
+            // nothing can have called them.
+            let in_use = self.function_got_entries[func_id].is_some_and(
+                |got_entry| unsafe { (*got_entry.0).load(Ordering::SeqCst) } == compiled.ptr,
+            );
+            if in_use {
+                still_in_use.push((func_id, compiled));
+            } else {
+                self.memory.free_readexec(compiled.ptr, compiled.size);
+            }
+        }
+        self.retired_functions = still_in_use;
     }
 
     /// Returns the address and size of a finalized data object.
@@ -317,13 +559,21 @@ impl JITModule {
     ///
     /// Returns ModuleError in case of allocation or syscall failure
     pub fn finalize_definitions(&mut self) -> ModuleResult<()> {
-        for func in std::mem::take(&mut self.functions_to_finalize) {
+        if self.hotswap_enabled {
+            self.create_pending_indirections()?;
+        }
+
+        let functions = std::mem::take(&mut self.functions_to_finalize);
+        for &func in &functions {
             let decl = self.declarations.get_function_decl(func);
             assert!(decl.linkage.is_definable());
             let func = self.compiled_functions[func]
                 .as_ref()
                 .expect("function must be compiled before it can be finalized");
-            func.perform_relocations(|name| self.get_address(name));
+            func.perform_relocations(
+                |name| self.get_address(name),
+                |name| self.get_got_entry(name),
+            );
         }
 
         for data in std::mem::take(&mut self.data_objects_to_finalize) {
@@ -332,7 +582,10 @@ impl JITModule {
             let data = self.compiled_data_objects[data]
                 .as_ref()
                 .expect("data object must be compiled before it can be finalized");
-            data.perform_relocations(|name| self.get_address(name));
+            data.perform_relocations(
+                |name| self.get_address(name),
+                |name| self.get_got_entry(name),
+            );
         }
 
         self.code_ranges
@@ -346,15 +599,125 @@ impl JITModule {
         };
         self.memory.finalize(branch_protection)?;
 
+        // Only publish new function bodies once they are executable.
+        if self.hotswap_enabled {
+            for &func in &functions {
+                let ptr = self.compiled_functions[func].as_ref().unwrap().ptr;
+                let got_entry = self.function_got_entries[func].unwrap().0;
+                unsafe { (*got_entry).store(ptr, Ordering::SeqCst) };
+            }
+        }
+
+        #[cfg(any(feature = "gdb-jit", feature = "perf-jitdump"))]
+        self.register_for_debugging(&functions)?;
+
+        Ok(())
+    }
+
+    /// Attach source-level debug information to a function.
+    ///
+    /// It is used to describe the function to debuggers and profilers when
+    /// they are enabled on the [`JITBuilder`], and must be attached before the
+    /// function is finalized.
+    pub fn set_function_debug_info(&mut self, func_id: FuncId, debug_info: FunctionDebugInfo) {
+        self.function_debug_info[func_id] = Some(debug_info);
+    }
+
+    /// Whether functions need to be described in DWARF once finalized.
+    #[cfg(any(feature = "gdb-jit", feature = "perf-jitdump"))]
+    fn wants_dwarf(&self) -> bool {
+        #[cfg(feature = "gdb-jit")]
+        if self.gdb_jit {
+            return true;
+        }
+        #[cfg(feature = "perf-jitdump")]
+        if self.perf_jitdump {
+            return true;
+        }
+        false
+    }
+
+    /// Registers newly finalized functions with the enabled debugger and
+    /// profiler interfaces.
+    #[cfg(any(feature = "gdb-jit", feature = "perf-jitdump"))]
+    fn register_for_debugging(&mut self, functions: &[FuncId]) -> ModuleResult<()> {
+        for &func in functions {
+            let compiled = self.compiled_functions[func].as_ref().unwrap();
+            let Some(dwarf) = &compiled.dwarf else {
+                continue;
+            };
+            let name = self.declarations.get_function_decl(func).linkage_name(func);
+            let debug_info = self.function_debug_info[func].as_ref();
+
+            #[cfg(feature = "perf-jitdump")]
+            if self.perf_jitdump {
+                let code = unsafe { std::slice::from_raw_parts(compiled.ptr, compiled.size) };
+                if let Err(err) =
+                    crate::debug::write_jitdump_record(&*self.isa, &name, code, dwarf, debug_info)
+                {
+                    log::warn!("failed to write jitdump record for {name}: {err}");
+                }
+            }
+
+            // Registering a redefined function replaces, and thereby
+            // unregisters, the image of its previous body.
+            #[cfg(feature = "gdb-jit")]
+            if self.gdb_jit {
+                if let Some(registration) = crate::debug::register_with_gdb(
+                    &*self.isa,
+                    &name,
+                    compiled.ptr,
+                    dwarf,
+                    debug_info,
+                )? {
+                    self.gdb_registrations
+                        .insert(func, SendWrapper(registration));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates the GOT and PLT entries for all functions and data objects that
+    /// are about to be finalized and for everything they reference.
+    fn create_pending_indirections(&mut self) -> ModuleResult<()> {
+        let mut relocs = Vec::new();
+        for &func in &self.functions_to_finalize {
+            relocs.extend_from_slice(&self.compiled_functions[func].as_ref().unwrap().relocs);
+        }
+        for &data in &self.data_objects_to_finalize {
+            relocs.extend_from_slice(&self.compiled_data_objects[data].as_ref().unwrap().relocs);
+        }
+
+        for func in self.functions_to_finalize.clone() {
+            self.create_function_indirection(func)?;
+        }
+        for reloc in &relocs {
+            self.create_reloc_indirection(reloc)?;
+        }
         Ok(())
     }
 
     /// Create a new `JITModule`.
     pub fn new(builder: JITBuilder) -> Self {
-        assert!(
-            !builder.isa.flags().is_pic(),
-            "cranelift-jit needs is_pic=false"
-        );
+        if builder.hotswap_enabled {
+            assert!(
+                builder.isa.flags().is_pic(),
+                "hotswapping requires is_pic=true"
+            );
+            assert!(
+                matches!(
+                    builder.isa.triple().architecture,
+                    Architecture::X86_64 | Architecture::Aarch64(_)
+                ),
+                "hotswapping is only supported on x86_64 and aarch64"
+            );
+        } else {
+            assert!(
+                !builder.isa.flags().is_pic(),
+                "cranelift-jit needs is_pic=false unless hotswapping is enabled"
+            );
+        }
 
         let memory = builder
             .memory
@@ -371,6 +734,19 @@ impl JITModule {
             code_ranges: Vec::new(),
             functions_to_finalize: Vec::new(),
             data_objects_to_finalize: Vec::new(),
+            hotswap_enabled: builder.hotswap_enabled,
+            function_got_entries: SecondaryMap::new(),
+            function_plt_entries: SecondaryMap::new(),
+            data_object_got_entries: SecondaryMap::new(),
+            libcall_got_entries: HashMap::new(),
+            retired_functions: Vec::new(),
+            function_debug_info: SecondaryMap::new(),
+            #[cfg(feature = "gdb-jit")]
+            gdb_jit: builder.gdb_jit,
+            #[cfg(feature = "gdb-jit")]
+            gdb_registrations: HashMap::new(),
+            #[cfg(feature = "perf-jitdump")]
+            perf_jitdump: builder.perf_jitdump,
         }
     }
 
@@ -509,6 +885,13 @@ impl Module for JITModule {
             .map(|reloc| ModuleReloc::from_mach_reloc(reloc, &ctx.func, id))
             .collect();
 
+        #[cfg(any(feature = "gdb-jit", feature = "perf-jitdump"))]
+        let dwarf = if self.wants_dwarf() {
+            Some(DwarfFunction::new(&*self.isa, compiled_code)?)
+        } else {
+            None
+        };
+
         self.record_function_for_perf(ptr, size, &decl.linkage_name(id));
         self.compiled_functions[id] = Some(CompiledBlob {
             ptr,
@@ -516,6 +899,8 @@ impl Module for JITModule {
             relocs,
             #[cfg(feature = "wasmtime-unwinder")]
             exception_data: None,
+            #[cfg(any(feature = "gdb-jit", feature = "perf-jitdump"))]
+            dwarf,
         });
 
         let range_start = ptr as usize;
@@ -579,6 +964,15 @@ impl Module for JITModule {
             ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, size);
         }
 
+        // Only the code range is known for raw bytes.
+        #[cfg(any(feature = "gdb-jit", feature = "perf-jitdump"))]
+        let dwarf = self.wants_dwarf().then(|| DwarfFunction {
+            size: u32::try_from(size).unwrap(),
+            srclocs: Vec::new(),
+            value_labels_ranges: Default::default(),
+            unwind_info: None,
+        });
+
         self.record_function_for_perf(ptr, size, &decl.linkage_name(id));
         self.compiled_functions[id] = Some(CompiledBlob {
             ptr,
@@ -586,6 +980,8 @@ impl Module for JITModule {
             relocs: relocs.to_owned(),
             #[cfg(feature = "wasmtime-unwinder")]
             exception_data: None,
+            #[cfg(any(feature = "gdb-jit", feature = "perf-jitdump"))]
+            dwarf,
         });
 
         self.functions_to_finalize.push(id);
@@ -679,6 +1075,8 @@ impl Module for JITModule {
             relocs,
             #[cfg(feature = "wasmtime-unwinder")]
             exception_data: None,
+            #[cfg(any(feature = "gdb-jit", feature = "perf-jitdump"))]
+            dwarf: None,
         });
         self.data_objects_to_finalize.push(id);
 
@@ -756,6 +1154,38 @@ fn lookup_with_dlsym(name: &str) -> Option<*const u8> {
     }
 }
 
+/// Encodes `jmp *got_entry(%rip)` followed by `ud2` padding.
+fn x86_64_plt_entry(plt_entry: *const u8, got_entry: *const AtomicPtr<u8>) -> [u8; PLT_ENTRY_SIZE] {
+    let mut bytes = [
+        0xff, 0x25, 0, 0, 0, 0, // jmp *rel32(%rip)
+        0x0f, 0x0b, 0x0f, 0x0b, 0x0f, 0x0b, 0x0f, 0x0b, 0x0f, 0x0b, // ud2 x5
+    ];
+    // The displacement is relative to the end of the `jmp` instruction.
+    let pcrel = i32::try_from((got_entry as isize) - (plt_entry as isize + 6)).unwrap();
+    bytes[2..6].copy_from_slice(&pcrel.to_le_bytes());
+    bytes
+}
+
+/// Encodes `bti c; adrp x16, got_entry; ldr x16, [x16, :lo12:got_entry]; br x16`.
+fn aarch64_plt_entry(
+    plt_entry: *const u8,
+    got_entry: *const AtomicPtr<u8>,
+) -> [u8; PLT_ENTRY_SIZE] {
+    let get_page = |x: isize| x & !0xfff;
+    let adrp_at = plt_entry as isize + 4;
+    let pcrel = i32::try_from(get_page(got_entry as isize) - get_page(adrp_at)).unwrap();
+    let hi21 = (pcrel >> 12).cast_unsigned();
+    let adrp = 0x9000_0010 | ((hi21 & 0x3) << 29) | ((hi21 & 0x1ffffc) << 3);
+    let ldr = 0xf940_0210 | (((got_entry as u32 & 0xfff) >> 3) << 10);
+    let insts: [u32; 4] = [0xd503_245f, adrp, ldr, 0xd61f_0200];
+
+    let mut bytes = [0; PLT_ENTRY_SIZE];
+    for (chunk, inst) in bytes.chunks_exact_mut(4).zip(insts) {
+        chunk.copy_from_slice(&inst.to_le_bytes());
+    }
+    bytes
+}
+
 fn use_bti(isa_flags: &Vec<settings::Value>) -> bool {
     isa_flags
         .iter()
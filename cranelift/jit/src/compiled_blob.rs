@@ -17,6 +17,8 @@ pub(crate) struct CompiledBlob {
     pub(crate) relocs: Vec<ModuleReloc>,
     #[cfg(feature = "wasmtime-unwinder")]
     pub(crate) exception_data: Option<Vec<u8>>,
+    #[cfg(any(feature = "gdb-jit", feature = "perf-jitdump"))]
+    pub(crate) dwarf: Option<cranelift_module::DwarfFunction>,
 }
 
 unsafe impl Send for CompiledBlob {}
@@ -25,6 +27,7 @@ impl CompiledBlob {
     pub(crate) fn perform_relocations(
         &self,
         get_address: impl Fn(&ModuleRelocTarget) -> *const u8,
+        get_got_entry: impl Fn(&ModuleRelocTarget) -> *const u8,
     ) {
         use std::ptr::write_unaligned;
 
@@ -55,17 +58,19 @@ impl CompiledBlob {
                         write_unaligned(at as *mut u64, u64::try_from(what as usize).unwrap())
                     };
                 }
-                Reloc::X86PCRel4 | Reloc::X86CallPCRel4 => {
+                // When hotswapping is enabled `get_address` already resolves
+                // functions to their PLT entry.
+                Reloc::X86PCRel4 | Reloc::X86CallPCRel4 | Reloc::X86CallPLTRel4 => {
                     let base = get_address(name);
                     let what = unsafe { base.offset(isize::try_from(addend).unwrap()) };
                     let pcrel = i32::try_from((what as isize) - (at as isize)).unwrap();
                     unsafe { write_unaligned(at as *mut i32, pcrel) };
                 }
                 Reloc::X86GOTPCRel4 => {
-                    panic!("GOT relocation shouldn't be generated when !is_pic");
-                }
-                Reloc::X86CallPLTRel4 => {
-                    panic!("PLT relocation shouldn't be generated when !is_pic");
+                    let base = get_got_entry(name);
+                    let what = unsafe { base.offset(isize::try_from(addend).unwrap()) };
+                    let pcrel = i32::try_from((what as isize) - (at as isize)).unwrap();
+                    unsafe { write_unaligned(at as *mut i32, pcrel) };
                 }
                 Reloc::S390xPCRel32Dbl | Reloc::S390xPLTRel32Dbl => {
                     let base = get_address(name);
@@ -91,14 +96,20 @@ impl CompiledBlob {
                     let imm26 = (diff as u32) << chop >> chop;
                     unsafe { modify_inst32(iptr, |inst| inst | imm26) };
                 }
-                Reloc::Aarch64AdrGotPage21 => {
-                    panic!("GOT relocation shouldn't be generated when !is_pic");
-                }
                 Reloc::Aarch64Ld64GotLo12Nc => {
-                    panic!("GOT relocation shouldn't be generated when !is_pic");
+                    let base = get_got_entry(name);
+                    let what = unsafe { base.offset(isize::try_from(addend).unwrap()) };
+                    let iptr = at as *mut u32;
+                    // The immediate of a 64-bit `ldr` is scaled by 8.
+                    let imm12 = ((what.addr() as u32 & 0xfff) >> 3) << 10;
+                    unsafe { modify_inst32(iptr, |inst| inst | imm12) };
                 }
-                Reloc::Aarch64AdrPrelPgHi21 => {
-                    let base = get_address(name);
+                Reloc::Aarch64AdrPrelPgHi21 | Reloc::Aarch64AdrGotPage21 => {
+                    let base = if kind == Reloc::Aarch64AdrGotPage21 {
+                        get_got_entry(name)
+                    } else {
+                        get_address(name)
+                    };
                     let what = unsafe { base.offset(isize::try_from(addend).unwrap()) };
                     let get_page = |x| x & (!0xfff);
                     // NOTE: This should technically be i33 given that this relocation type allows
@@ -0,0 +1,337 @@
+//! Registration of generated code with debuggers and profilers.
+
+use cranelift_codegen::isa::TargetIsa;
+use cranelift_module::{DwarfFunction, FunctionDebugInfo};
+#[cfg(feature = "gdb-jit")]
+use cranelift_module::{ModuleError, ModuleResult};
+use target_lexicon::Architecture;
+
+/// Returns the ELF machine of `isa`, if it's one debuggers and perf know.
+fn elf_machine(isa: &dyn TargetIsa) -> Option<u16> {
+    use object::elf;
+    match isa.triple().architecture {
+        Architecture::X86_64 => Some(elf::EM_X86_64),
+        Architecture::Aarch64(_) => Some(elf::EM_AARCH64),
+        Architecture::Riscv64(_) => Some(elf::EM_RISCV),
+        Architecture::S390x => Some(elf::EM_S390),
+        _ => None,
+    }
+}
+
+/// Builds an ELF image describing the function at `code` and registers it
+/// through the GDB JIT interface.
+///
+/// The image contains a symbol for the function and DWARF describing it with
+/// absolute addresses, so that no relocation is needed by the debugger.
+/// Returns `None` if the target isn't supported.
+#[cfg(feature = "gdb-jit")]
+pub(crate) fn register_with_gdb(
+    isa: &dyn TargetIsa,
+    name: &str,
+    code: *const u8,
+    function: &DwarfFunction,
+    debug_info: Option<&FunctionDebugInfo>,
+) -> ModuleResult<Option<wasmtime_jit_debug::gdb_jit_int::GdbJitImageRegistration>> {
+    let Some(e_machine) = elf_machine(isa) else {
+        return Ok(None);
+    };
+    let image = create_gdb_image(isa, e_machine, name, code as u64, function, debug_info)?;
+    Ok(Some(
+        wasmtime_jit_debug::gdb_jit_int::GdbJitImageRegistration::register(image),
+    ))
+}
+
+#[cfg(feature = "gdb-jit")]
+fn create_gdb_image(
+    isa: &dyn TargetIsa,
+    e_machine: u16,
+    name: &str,
+    address: u64,
+    function: &DwarfFunction,
+    debug_info: Option<&FunctionDebugInfo>,
+) -> ModuleResult<Vec<u8>> {
+    use cranelift_codegen::gimli::RunTimeEndian;
+    use cranelift_codegen::gimli::write::{Address, EndianVec, Sections};
+    use cranelift_codegen::ir::Endianness;
+    use cranelift_module::DwarfBuilder;
+    use object::elf;
+    use object::write::elf::{FileHeader, ProgramHeader, SectionHeader, Sym, Writer};
+
+    let (endian, dwarf_endian) = match isa.endianness() {
+        Endianness::Little => (object::Endianness::Little, RunTimeEndian::Little),
+        Endianness::Big => (object::Endianness::Big, RunTimeEndian::Big),
+    };
+
+    let mut dwarf = DwarfBuilder::new(isa, ".", name);
+    dwarf.add_function(name, Address::Constant(address), function, debug_info);
+    let mut sections = Sections::new(EndianVec::new(dwarf_endian));
+    dwarf.write(&mut sections)?;
+    let mut debug_sections = Vec::new();
+    sections
+        .for_each(|id, section| -> Result<(), ()> {
+            if !section.slice().is_empty() {
+                debug_sections.push((id.name(), section.slice()));
+            }
+            Ok(())
+        })
+        .unwrap();
+
+    let size = u64::from(function.size);
+    let mut image = Vec::new();
+    let mut writer = Writer::new(endian, isa.pointer_bytes() == 8, &mut image);
+
+    // Reserve the layout: headers, an empty `.text` section pointing at the
+    // code in memory, the DWARF sections and a symbol table.
+    writer.reserve_file_header();
+    writer.reserve_program_headers(1);
+    writer.reserve_null_section_index();
+    let text_name = writer.add_section_name(b".text");
+    let text_index = writer.reserve_section_index();
+    let debug_sections = debug_sections
+        .into_iter()
+        .map(|(name, data)| {
+            let name = writer.add_section_name(name.as_bytes());
+            writer.reserve_section_index();
+            let offset = writer.reserve(data.len(), 1);
+            (name, offset, data)
+        })
+        .collect::<Vec<_>>();
+    writer.reserve_null_symbol_index();
+    let symbol_name = writer.add_string(name.as_bytes());
+    writer.reserve_symbol_index(Some(text_index));
+    writer.reserve_symtab_section_index();
+    writer.reserve_symtab();
+    writer.reserve_strtab_section_index();
+    writer.reserve_strtab();
+    writer.reserve_shstrtab_section_index();
+    writer.reserve_shstrtab();
+    writer.reserve_section_headers();
+
+    writer
+        .write_file_header(&FileHeader {
+            os_abi: elf::ELFOSABI_NONE,
+            abi_version: 0,
+            e_type: elf::ET_EXEC,
+            e_machine,
+            e_entry: 0,
+            e_flags: 0,
+        })
+        .map_err(|e| {
+            ModuleError::Backend(anyhow::Error::new(e).context("unable to write GDB JIT image"))
+        })?;
+    writer.write_align_program_headers();
+    writer.write_program_header(&ProgramHeader {
+        p_type: elf::PT_LOAD,
+        p_flags: elf::PF_R | elf::PF_X,
+        p_offset: 0,
+        p_vaddr: address,
+        p_paddr: address,
+        p_filesz: 0,
+        p_memsz: size,
+        p_align: 1,
+    });
+    for (_, offset, data) in &debug_sections {
+        writer.pad_until(*offset);
+        writer.write(data);
+    }
+    writer.write_null_symbol();
+    writer.write_symbol(&Sym {
+        name: Some(symbol_name),
+        section: Some(text_index),
+        st_info: (elf::STB_GLOBAL << 4) | elf::STT_FUNC,
+        st_other: elf::STV_DEFAULT,
+        st_shndx: 0,
+        st_value: address,
+        st_size: size,
+    });
+    writer.write_strtab();
+    writer.write_shstrtab();
+
+    writer.write_null_section_header();
+    writer.write_section_header(&SectionHeader {
+        name: Some(text_name),
+        sh_type: elf::SHT_NOBITS,
+        sh_flags: u64::from(elf::SHF_ALLOC | elf::SHF_EXECINSTR),
+        sh_addr: address,
+        sh_offset: 0,
+        sh_size: size,
+        sh_link: 0,
+        sh_info: 0,
+        sh_addralign: 1,
+        sh_entsize: 0,
+    });
+    for (name, offset, data) in &debug_sections {
+        writer.write_section_header(&SectionHeader {
+            name: Some(*name),
+            sh_type: elf::SHT_PROGBITS,
+            sh_flags: 0,
+            sh_addr: 0,
+            sh_offset: *offset as u64,
+            sh_size: data.len() as u64,
+            sh_link: 0,
+            sh_info: 0,
+            sh_addralign: 1,
+            sh_entsize: 0,
+        });
+    }
+    writer.write_symtab_section_header(1);
+    writer.write_strtab_section_header();
+    writer.write_shstrtab_section_header();
+
+    Ok(image)
+}
+
+/// Process-wide jitdump file. Perf only accepts a single file per process.
+#[cfg(all(feature = "perf-jitdump", target_os = "linux"))]
+static JITDUMP_FILE: std::sync::Mutex<Option<wasmtime_jit_debug::perf_jitdump::JitDumpFile>> =
+    std::sync::Mutex::new(None);
+
+/// Records the function at `code` in `./jit-<pid>.dump`, along with its line
+/// table if `debug_info` is given, so that `perf inject --jit` can attribute
+/// samples to it.
+#[cfg(feature = "perf-jitdump")]
+pub(crate) fn write_jitdump_record(
+    isa: &dyn TargetIsa,
+    name: &str,
+    code: &[u8],
+    function: &DwarfFunction,
+    debug_info: Option<&FunctionDebugInfo>,
+) -> std::io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::mem::size_of;
+        use wasmtime_jit_debug::perf_jitdump::{
+            DebugEntry, DebugInfoRecord, JitDumpFile, RecordHeader, RecordId,
+        };
+
+        let Some(e_machine) = elf_machine(isa) else {
+            return Ok(());
+        };
+        let mut file = JITDUMP_FILE.lock().unwrap();
+        if file.is_none() {
+            let filename = format!("./jit-{}.dump", std::process::id());
+            *file = Some(JitDumpFile::new(filename, u32::from(e_machine))?);
+        }
+        let file = file.as_mut().unwrap();
+        let timestamp = file.get_time_stamp();
+
+        // Debug information must precede the code load record it describes.
+        if let Some(debug_info) = debug_info {
+            let entries = function
+                .srclocs
+                .iter()
+                .filter_map(|&(start, _end, srcloc)| {
+                    let position = debug_info.source_locs.get(&srcloc)?;
+                    Some(DebugEntry {
+                        address: code.as_ptr() as u64 + u64::from(start),
+                        line: position.line,
+                        discriminator: 0,
+                        filename: debug_info.file.clone(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            if !entries.is_empty() {
+                let entries_size: usize = entries
+                    .iter()
+                    .map(|e| size_of::<u64>() + 2 * size_of::<u32>() + e.filename.len() + 1)
+                    .sum();
+                file.write_debug_info_record(DebugInfoRecord {
+                    header: RecordHeader {
+                        id: RecordId::JitCodeDebugInfo as u32,
+                        record_size: u32::try_from(size_of::<DebugInfoRecord>() + entries_size)
+                            .unwrap(),
+                        timestamp,
+                    },
+                    address: code.as_ptr() as u64,
+                    count: entries.len() as u64,
+                })?;
+                file.write_debug_info_entries(entries)?;
+            }
+        }
+
+        let tid = unsafe { libc::gettid() }.cast_unsigned();
+        file.dump_code_load_record(name, code, timestamp, std::process::id(), tid)?;
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = (isa, name, code, function, debug_info);
+    Ok(())
+}
+
+#[cfg(all(test, feature = "gdb-jit"))]
+mod tests {
+    use super::*;
+    use cranelift_codegen::ir::{
+        AbiParam, Function, InstBuilder, Signature, SourceLoc, UserFuncName, types,
+    };
+    use cranelift_codegen::{Context, settings};
+    use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
+    use cranelift_module::SourcePosition;
+    use object::{Object, ObjectSection, ObjectSymbol};
+
+    #[test]
+    fn gdb_image_describes_function() {
+        let isa = match cranelift_native::builder() {
+            Ok(builder) => builder
+                .finish(settings::Flags::new(settings::builder()))
+                .unwrap(),
+            Err(_) => return,
+        };
+        let Some(e_machine) = elf_machine(&*isa) else {
+            return;
+        };
+
+        let mut func = Function::with_name_signature(UserFuncName::default(), {
+            let mut sig = Signature::new(isa.default_call_conv());
+            sig.params.push(AbiParam::new(types::I64));
+            sig.returns.push(AbiParam::new(types::I64));
+            sig
+        });
+        let mut func_ctx = FunctionBuilderContext::new();
+        {
+            let mut builder = FunctionBuilder::new(&mut func, &mut func_ctx);
+            let block = builder.create_block();
+            builder.append_block_params_for_function_params(block);
+            builder.switch_to_block(block);
+            builder.seal_block(block);
+            builder.set_srcloc(SourceLoc::new(1));
+            let x = builder.block_params(block)[0];
+            let y = builder.ins().iadd_imm(x, 1);
+            builder.ins().return_(&[y]);
+            builder.finalize();
+        }
+        let mut ctx = Context::for_function(func);
+        let compiled_code = ctx.compile(&*isa, &mut Default::default()).unwrap();
+        let function = DwarfFunction::new(&*isa, compiled_code).unwrap();
+
+        let mut debug_info = FunctionDebugInfo {
+            file: "test.src".into(),
+            line: 1,
+            ..Default::default()
+        };
+        debug_info
+            .source_locs
+            .insert(SourceLoc::new(1), SourcePosition { line: 2, column: 5 });
+
+        let address = 0x1234_0000;
+        let image = create_gdb_image(
+            &*isa,
+            e_machine,
+            "add_one",
+            address,
+            &function,
+            Some(&debug_info),
+        )
+        .unwrap();
+
+        let file = object::File::parse(&*image).unwrap();
+        let symbol = file.symbols().find(|s| s.name() == Ok("add_one")).unwrap();
+        assert_eq!(symbol.address(), address);
+        assert_eq!(symbol.size(), u64::from(function.size));
+        let text = file.section_by_name(".text").unwrap();
+        assert_eq!(text.address(), address);
+        for name in [".debug_info", ".debug_line", ".debug_abbrev"] {
+            let section = file.section_by_name(name).unwrap();
+            assert!(!section.data().unwrap().is_empty(), "{name} is empty");
+        }
+    }
+}
@@ -8,6 +8,8 @@
 
 mod backend;
 mod compiled_blob;
+#[cfg(any(feature = "gdb-jit", feature = "perf-jitdump"))]
+mod debug;
 mod memory;
 
 pub use crate::backend::{JITBuilder, JITModule};
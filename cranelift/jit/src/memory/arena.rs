@@ -18,6 +18,8 @@ struct Segment {
     position: usize,
     target_prot: region::Protection,
     finalized: bool,
+    /// Number of allocations in this segment that haven't been freed yet.
+    live: usize,
 }
 
 impl Segment {
@@ -31,6 +33,7 @@ impl Segment {
             target_prot,
             position: 0,
             finalized: false,
+            live: 0,
         };
         // Set segment to read-write for initialization. The target permissions
         // will be applied in `finalize`.
@@ -71,9 +74,27 @@ impl Segment {
         self.position = align_up(self.position, align);
         let ptr = unsafe { self.ptr.add(self.position) };
         self.position += size;
+        self.live += 1;
         ptr
     }
 
+    /// Release a single allocation. Once all allocations are released the
+    /// segment is made writable again and can be reused from the start.
+    fn free(&mut self) {
+        self.live -= 1;
+        if self.live == 0 {
+            if self.finalized {
+                self.set_rw();
+                self.finalized = false;
+            }
+            self.position = 0;
+        }
+    }
+
+    fn contains(&self, ptr: *mut u8) -> bool {
+        self.ptr <= ptr && ptr < self.ptr.wrapping_add(self.len)
+    }
+
     fn has_space_for(&self, size: usize, align: usize) -> bool {
         !self.finalized && align_up(self.position, align) + size <= self.len
     }
@@ -237,6 +258,12 @@ impl JITMemoryProvider for ArenaMemoryProvider {
         self.free_memory();
     }
 
+    unsafe fn free_readexec(&mut self, ptr: *mut u8, _size: usize) {
+        if let Some(segment) = self.segments.iter_mut().find(|seg| seg.contains(ptr)) {
+            segment.free();
+        }
+    }
+
     fn finalize(&mut self, branch_protection: BranchProtection) -> ModuleResult<()> {
         self.finalize(branch_protection);
         Ok(())
@@ -284,6 +311,24 @@ mod tests {
         let _ = arena.allocate_readwrite(200_000, 1).unwrap_err();
     }
 
+    #[test]
+    fn reuse_freed_segment() {
+        let mut arena = ArenaMemoryProvider::new_with_size(1 << 20).unwrap();
+
+        let a = arena.allocate_readexec(16, 16).unwrap();
+        let b = arena.allocate_readexec(16, 16).unwrap();
+        arena.finalize(BranchProtection::None);
+
+        unsafe { arena.free_readexec(a, 16) };
+        assert!(arena.segments[0].finalized);
+        unsafe { arena.free_readexec(b, 16) };
+        assert!(!arena.segments[0].finalized);
+
+        let c = arena.allocate_readexec(16, 16).unwrap();
+        assert_eq!(c, a);
+        assert_eq!(arena.segments.len(), 1);
+    }
+
     #[test]
     fn test_is_send() {
         fn assert_is_send<T: Send>() {}
@@ -27,6 +27,19 @@ pub trait JITMemoryProvider {
 
     /// Free the memory region.
     unsafe fn free_memory(&mut self);
+    /// Release a single allocation previously returned by
+    /// [`JITMemoryProvider::allocate_readexec`] so that its memory can be
+    /// reused or returned to the system.
+    ///
+    /// The default implementation leaks the allocation.
+    ///
+    /// # Safety
+    ///
+    /// The allocation must not be executing and must not be referenced by any
+    /// other code or data after this call.
+    unsafe fn free_readexec(&mut self, ptr: *mut u8, size: usize) {
+        let _ = (ptr, size);
+    }
     /// Finalize the memory region and apply memory protections.
     fn finalize(&mut self, branch_protection: BranchProtection) -> ModuleResult<()>;
 }
@@ -19,6 +19,8 @@ struct PtrLen {
 
     ptr: *mut u8,
     len: usize,
+    /// Number of allocations within this region that haven't been freed yet.
+    live: usize,
 }
 
 impl PtrLen {
@@ -30,9 +32,15 @@ impl PtrLen {
 
             ptr: ptr::null_mut(),
             len: 0,
+            live: 0,
         }
     }
 
+    /// Whether `ptr` points into this region.
+    fn contains(&self, ptr: *mut u8) -> bool {
+        self.ptr <= ptr && ptr < self.ptr.wrapping_add(self.len)
+    }
+
     /// Create a new `PtrLen` pointing to at least `size` bytes of memory,
     /// suitably sized and aligned for memory protection.
     #[cfg(all(not(target_os = "windows"), feature = "selinux-fix"))]
@@ -45,6 +53,7 @@ impl PtrLen {
                 ptr: mmap.as_mut_ptr(),
                 map: Some(mmap),
                 len: alloc_size,
+                live: 0,
             }
         })
     }
@@ -62,6 +71,7 @@ impl PtrLen {
             Ok(Self {
                 ptr,
                 len: alloc_size,
+                live: 0,
             })
         } else {
             Err(io::Error::from(io::ErrorKind::OutOfMemory))
@@ -87,6 +97,7 @@ impl PtrLen {
             Ok(Self {
                 ptr: ptr as *mut u8,
                 len: region::page::ceil(size as *const ()) as usize,
+                live: 0,
             })
         } else {
             Err(io::Error::last_os_error())
@@ -136,8 +147,12 @@ impl Memory {
     }
 
     fn finish_current(&mut self) {
-        self.allocations
-            .push(mem::replace(&mut self.current, PtrLen::new()));
+        let current = mem::replace(&mut self.current, PtrLen::new());
+        // Regions whose allocations have all been freed already are dropped
+        // right away instead of being kept around.
+        if current.live > 0 {
+            self.allocations.push(current);
+        }
         self.position = 0;
     }
 
@@ -152,6 +167,7 @@ impl Memory {
             // TODO: Ensure overflow is not possible.
             let ptr = unsafe { self.current.ptr.add(self.position) };
             self.position += size;
+            self.current.live += 1;
             return Ok(ptr);
         }
 
@@ -159,6 +175,7 @@ impl Memory {
 
         // TODO: Allocate more at a time.
         self.current = PtrLen::with_size(size)?;
+        self.current.live = 1;
         self.position = size;
 
         Ok(self.current.ptr)
@@ -211,6 +228,24 @@ impl Memory {
         return iter.filter(|&PtrLen { len, .. }| *len != 0);
     }
 
+    /// Releases a single allocation. Once every allocation of a region has
+    /// been released the region itself is freed.
+    pub(crate) unsafe fn free(&mut self, ptr: *mut u8) {
+        if self.current.contains(ptr) {
+            self.current.live -= 1;
+            return;
+        }
+        let Some(region) = self.allocations.iter_mut().rev().find(|r| r.contains(ptr)) else {
+            return;
+        };
+        region.live -= 1;
+        if region.live == 0 {
+            // Replace the region with an empty one rather than removing it so
+            // that `already_protected` stays a valid index.
+            drop(mem::replace(region, PtrLen::new()));
+        }
+    }
+
     /// Frees all allocated memory regions that would be leaked otherwise.
     /// Likely to invalidate existing function pointers, causing unsafety.
     pub(crate) unsafe fn free_memory(&mut self) {
@@ -258,6 +293,10 @@ impl JITMemoryProvider for SystemMemoryProvider {
         self.writable.free_memory();
     }
 
+    unsafe fn free_readexec(&mut self, ptr: *mut u8, _size: usize) {
+        self.code.free(ptr);
+    }
+
     fn finalize(&mut self, branch_protection: BranchProtection) -> ModuleResult<()> {
         self.readonly.set_readonly()?;
         self.code.set_readable_and_executable(branch_protection)
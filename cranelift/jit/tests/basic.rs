@@ -215,3 +215,130 @@ fn empty_data_object() {
     data.define(Box::new([]));
     module.define_data(data_id, &data).unwrap();
 }
+
+fn define_constant_function(
+    module: &mut JITModule,
+    func_id: FuncId,
+    value: i64,
+) -> Result<(), ModuleError> {
+    let mut ctx = module.make_context();
+    ctx.func.signature = module
+        .declarations()
+        .get_function_decl(func_id)
+        .signature
+        .clone();
+    ctx.func.name = UserFuncName::user(0, func_id.as_u32());
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.switch_to_block(block);
+        let value = bcx.ins().iconst(types::I64, value);
+        bcx.ins().return_(&[value]);
+        bcx.seal_all_blocks();
+    }
+    module.define_function(func_id, &mut ctx)
+}
+
+fn hotswap_module() -> Option<JITModule> {
+    if !cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        return None;
+    }
+    let mut flag_builder = settings::builder();
+    flag_builder.set("use_colocated_libcalls", "false").unwrap();
+    flag_builder.set("is_pic", "true").unwrap();
+    let isa_builder = cranelift_native::builder().ok()?;
+    let isa = isa_builder
+        .finish(settings::Flags::new(flag_builder))
+        .unwrap();
+    let mut builder = JITBuilder::with_isa(isa, default_libcall_names());
+    builder.hotswap(true);
+    Some(JITModule::new(builder))
+}
+
+#[test]
+fn hotswap_function() {
+    let Some(mut module) = hotswap_module() else {
+        return;
+    };
+
+    let mut sig = module.make_signature();
+    sig.returns.push(AbiParam::new(types::I64));
+    let callee = module
+        .declare_function("callee", Linkage::Local, &sig)
+        .unwrap();
+    let caller = module
+        .declare_function("caller", Linkage::Local, &sig)
+        .unwrap();
+
+    define_constant_function(&mut module, callee, 1).unwrap();
+
+    let mut ctx = module.make_context();
+    ctx.func.signature = sig.clone();
+    ctx.func.name = UserFuncName::user(0, caller.as_u32());
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.switch_to_block(block);
+        let local_callee = module.declare_func_in_func(callee, &mut bcx.func);
+        let call = bcx.ins().call(local_callee, &[]);
+        let result = bcx.inst_results(call)[0];
+        bcx.ins().return_(&[result]);
+        bcx.seal_all_blocks();
+    }
+    module.define_function(caller, &mut ctx).unwrap();
+    module.finalize_definitions().unwrap();
+
+    let caller_ptr = module.get_finalized_function(caller);
+    let caller_fn = unsafe { std::mem::transmute::<*const u8, extern "C" fn() -> i64>(caller_ptr) };
+    assert_eq!(caller_fn(), 1);
+
+    // Redefining requires `prepare_for_function_redefine` first.
+    define_constant_function(&mut module, callee, 2)
+        .err()
+        .unwrap();
+
+    module.prepare_for_function_redefine(callee).unwrap();
+    define_constant_function(&mut module, callee, 2).unwrap();
+    module.finalize_definitions().unwrap();
+    assert_eq!(caller_fn(), 2);
+
+    let callee_fn = unsafe {
+        std::mem::transmute::<*const u8, extern "C" fn() -> i64>(
+            module.get_finalized_function(callee),
+        )
+    };
+    assert_eq!(callee_fn(), 2);
+
+    unsafe { module.free_retired_functions() };
+    assert_eq!(caller_fn(), 2);
+}
+
+#[test]
+fn free_redefined_function_before_finalize() {
+    let Some(mut module) = hotswap_module() else {
+        return;
+    };
+
+    let mut sig = module.make_signature();
+    sig.returns.push(AbiParam::new(types::I64));
+    let func = module
+        .declare_function("func", Linkage::Local, &sig)
+        .unwrap();
+
+    // The first definition is never finalized, so it has no GOT entry yet and
+    // can be freed right away.
+    define_constant_function(&mut module, func, 1).unwrap();
+    module.prepare_for_function_redefine(func).unwrap();
+    unsafe { module.free_retired_functions() };
+
+    define_constant_function(&mut module, func, 2).unwrap();
+    module.finalize_definitions().unwrap();
+    let func_fn = unsafe {
+        std::mem::transmute::<*const u8, extern "C" fn() -> i64>(
+            module.get_finalized_function(func),
+        )
+    };
+    assert_eq!(func_fn(), 2);
+}
@@ -0,0 +1,46 @@
+//! Defines `FunctionDebugInfo`.
+
+use crate::HashMap;
+use cranelift_codegen::ir;
+use std::string::String;
+use std::vec::Vec;
+
+/// Source-level debug information a frontend can attach to a function.
+///
+/// Instructions are mapped back to the source through the `SourceLoc`s set on
+/// them while building the function, and variables are described through the
+/// `ValueLabel`s assigned to their values, which requires calling
+/// `Function::collect_debug_info` before building the function.
+#[derive(Clone, Debug, Default)]
+pub struct FunctionDebugInfo {
+    /// Path of the source file the function is defined in.
+    pub file: String,
+    /// Line the function is defined at.
+    pub line: u32,
+    /// Source positions, within `file`, of the instructions tagged with each
+    /// `SourceLoc`.
+    pub source_locs: HashMap<ir::SourceLoc, SourcePosition>,
+    /// Variables of the function.
+    pub variables: Vec<VariableDebugInfo>,
+}
+
+/// A position within a source file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SourcePosition {
+    /// Line number, starting at 1.
+    pub line: u32,
+    /// Column number, starting at 1, or 0 if unknown.
+    pub column: u32,
+}
+
+/// Describes a source-level variable whose location is tracked through a
+/// `ValueLabel`.
+#[derive(Clone, Debug)]
+pub struct VariableDebugInfo {
+    /// Name of the variable.
+    pub name: String,
+    /// Label of the values holding the variable.
+    pub label: ir::ValueLabel,
+    /// Type of the variable.
+    pub ty: ir::Type,
+}
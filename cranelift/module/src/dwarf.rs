@@ -0,0 +1,321 @@
+//! Defines `DwarfBuilder`, which describes compiled functions in DWARF.
+
+use crate::HashMap;
+use crate::debug::FunctionDebugInfo;
+use crate::{ModuleError, ModuleResult};
+use cranelift_codegen::binemit::CodeOffset;
+use cranelift_codegen::gimli::write::{
+    Address, AttributeValue, DwarfUnit, Expression, FileId, FrameTable, LineProgram, LineString,
+    Location, LocationList, Range, RangeList, Sections, UnitEntryId, Writer,
+};
+use cranelift_codegen::gimli::{self, Encoding, Format, LineEncoding, Register, constants};
+use cranelift_codegen::ir;
+use cranelift_codegen::isa::TargetIsa;
+use cranelift_codegen::isa::unwind::{UnwindInfo, UnwindInfoKind};
+use cranelift_codegen::{CompiledCode, LabelValueLoc, ValueLabelsRanges};
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+/// The parts of a compiled function that are needed to describe it in DWARF.
+///
+/// This is captured while the function is defined, as the `Context` it was
+/// compiled in is typically reused for the next function.
+#[derive(Clone, Debug)]
+pub struct DwarfFunction {
+    /// Size of the function's code in bytes.
+    pub size: u32,
+    /// Code ranges along with the source location they were generated for.
+    pub srclocs: Vec<(CodeOffset, CodeOffset, ir::SourceLoc)>,
+    /// Locations of the function's labeled values.
+    pub value_labels_ranges: ValueLabelsRanges,
+    /// System V unwind information, if the target supports it.
+    pub unwind_info: Option<UnwindInfo>,
+}
+
+impl DwarfFunction {
+    /// Capture the DWARF-relevant parts of `compiled_code`.
+    pub fn new(isa: &dyn TargetIsa, compiled_code: &CompiledCode) -> ModuleResult<Self> {
+        let unwind_info = match isa.emit_unwind_info(compiled_code, UnwindInfoKind::SystemV)? {
+            Some(info @ UnwindInfo::SystemV(_)) => Some(info),
+            _ => None,
+        };
+        Ok(Self {
+            size: compiled_code.code_info().total_size,
+            srclocs: compiled_code
+                .buffer
+                .get_srclocs_sorted()
+                .iter()
+                .map(|srcloc| (srcloc.start, srcloc.end, srcloc.loc))
+                .collect(),
+            value_labels_ranges: compiled_code.value_labels_ranges.clone(),
+            unwind_info,
+        })
+    }
+}
+
+/// Builds a DWARF compilation unit, along with a `.debug_frame` table,
+/// describing a set of compiled functions.
+///
+/// Functions are placed at a `gimli::write::Address`, which is either an
+/// absolute address or an offset from a symbol; the `Writer` the sections are
+/// written to is responsible for relocating the latter.
+pub struct DwarfBuilder<'a> {
+    isa: &'a dyn TargetIsa,
+    dwarf: DwarfUnit,
+    frames: Option<FrameTable>,
+    cie: Option<gimli::write::CieId>,
+    files: HashMap<String, FileId>,
+    base_types: HashMap<ir::Type, UnitEntryId>,
+    ranges: Vec<Range>,
+}
+
+impl<'a> DwarfBuilder<'a> {
+    /// Create a new builder for a compilation unit named `name`, compiled in
+    /// `comp_dir`.
+    pub fn new(isa: &'a dyn TargetIsa, comp_dir: &str, name: &str) -> Self {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: isa.pointer_bytes(),
+        };
+        let mut dwarf = DwarfUnit::new(encoding);
+        dwarf.unit.line_program = LineProgram::new(
+            encoding,
+            LineEncoding::default(),
+            LineString::String(comp_dir.into()),
+            None,
+            LineString::String(name.into()),
+            None,
+        );
+        let root = dwarf.unit.root();
+        let root = dwarf.unit.get_mut(root);
+        root.set(
+            constants::DW_AT_producer,
+            AttributeValue::String(format!("cranelift {}", cranelift_codegen::VERSION).into()),
+        );
+        root.set(constants::DW_AT_name, AttributeValue::String(name.into()));
+        root.set(
+            constants::DW_AT_comp_dir,
+            AttributeValue::String(comp_dir.into()),
+        );
+        root.set(
+            constants::DW_AT_low_pc,
+            AttributeValue::Address(Address::Constant(0)),
+        );
+
+        let mut frames = FrameTable::default();
+        let cie = isa.create_systemv_cie().map(|cie| frames.add_cie(cie));
+
+        Self {
+            isa,
+            dwarf,
+            frames: cie.map(|_| frames),
+            cie,
+            files: HashMap::new(),
+            base_types: HashMap::new(),
+            ranges: Vec::new(),
+        }
+    }
+
+    /// Describe a function named `name` located at `address`.
+    ///
+    /// Without `debug_info` only the function's name, code range and unwind
+    /// information are emitted.
+    pub fn add_function(
+        &mut self,
+        name: &str,
+        address: Address,
+        function: &DwarfFunction,
+        debug_info: Option<&FunctionDebugInfo>,
+    ) {
+        self.ranges.push(Range::StartLength {
+            begin: address,
+            length: u64::from(function.size),
+        });
+        if let (Some(frames), Some(cie), Some(UnwindInfo::SystemV(info))) =
+            (&mut self.frames, self.cie, &function.unwind_info)
+        {
+            frames.add_fde(cie, info.to_fde(address));
+        }
+
+        let file = debug_info.map(|info| self.file_id(&info.file));
+        self.add_line_sequence(address, function, debug_info.zip(file));
+
+        let root = self.dwarf.unit.root();
+        let subprogram = self.dwarf.unit.add(root, constants::DW_TAG_subprogram);
+        let entry = self.dwarf.unit.get_mut(subprogram);
+        entry.set(constants::DW_AT_name, AttributeValue::String(name.into()));
+        entry.set(constants::DW_AT_low_pc, AttributeValue::Address(address));
+        entry.set(
+            constants::DW_AT_high_pc,
+            AttributeValue::Udata(u64::from(function.size)),
+        );
+        let mut frame_base = Expression::new();
+        frame_base.op(constants::DW_OP_call_frame_cfa);
+        entry.set(
+            constants::DW_AT_frame_base,
+            AttributeValue::Exprloc(frame_base),
+        );
+
+        let Some(debug_info) = debug_info else {
+            return;
+        };
+        entry.set(constants::DW_AT_decl_file, AttributeValue::FileIndex(file));
+        entry.set(
+            constants::DW_AT_decl_line,
+            AttributeValue::Udata(u64::from(debug_info.line)),
+        );
+
+        for variable in &debug_info.variables {
+            let ty = self.base_type(variable.ty);
+            let locations = self.variable_locations(address, function, variable.label);
+            let locations =
+                (!locations.0.is_empty()).then(|| self.dwarf.unit.locations.add(locations));
+            let entry = self.dwarf.unit.add(subprogram, constants::DW_TAG_variable);
+            let entry = self.dwarf.unit.get_mut(entry);
+            entry.set(
+                constants::DW_AT_name,
+                AttributeValue::String(variable.name.as_str().into()),
+            );
+            entry.set(constants::DW_AT_type, AttributeValue::UnitRef(ty));
+            if let Some(locations) = locations {
+                entry.set(
+                    constants::DW_AT_location,
+                    AttributeValue::LocationListRef(locations),
+                );
+            }
+        }
+    }
+
+    /// Write the DWARF sections describing all added functions.
+    pub fn write<W: Writer>(mut self, sections: &mut Sections<W>) -> ModuleResult<()> {
+        let ranges = self.dwarf.unit.ranges.add(RangeList(self.ranges));
+        let root = self.dwarf.unit.root();
+        self.dwarf.unit.get_mut(root).set(
+            constants::DW_AT_ranges,
+            AttributeValue::RangeListRef(ranges),
+        );
+
+        self.dwarf.write(sections).map_err(dwarf_error)?;
+        if let Some(frames) = &self.frames {
+            frames
+                .write_debug_frame(&mut sections.debug_frame)
+                .map_err(dwarf_error)?;
+        }
+        Ok(())
+    }
+
+    fn file_id(&mut self, file: &str) -> FileId {
+        if let Some(id) = self.files.get(file) {
+            return *id;
+        }
+        let program = &mut self.dwarf.unit.line_program;
+        let dir = program.default_directory();
+        let id = program.add_file(LineString::String(file.into()), dir, None);
+        self.files.insert(file.to_string(), id);
+        id
+    }
+
+    fn add_line_sequence(
+        &mut self,
+        address: Address,
+        function: &DwarfFunction,
+        debug_info: Option<(&FunctionDebugInfo, FileId)>,
+    ) {
+        let Some((debug_info, file)) = debug_info else {
+            return;
+        };
+        let program = &mut self.dwarf.unit.line_program;
+        program.begin_sequence(Some(address));
+        for &(start, _end, srcloc) in &function.srclocs {
+            // Code without a known position is attributed to line 0 so that it
+            // isn't mistaken for part of the preceding line.
+            let position = debug_info
+                .source_locs
+                .get(&srcloc)
+                .copied()
+                .unwrap_or_default();
+            let row = program.row();
+            row.address_offset = u64::from(start);
+            row.file = file;
+            row.line = u64::from(position.line);
+            row.column = u64::from(position.column);
+            program.generate_row();
+        }
+        program.end_sequence(u64::from(function.size));
+    }
+
+    fn base_type(&mut self, ty: ir::Type) -> UnitEntryId {
+        if let Some(id) = self.base_types.get(&ty) {
+            return *id;
+        }
+        let encoding = if ty.is_float() {
+            constants::DW_ATE_float
+        } else if ty.is_int() {
+            constants::DW_ATE_signed
+        } else {
+            constants::DW_ATE_unsigned
+        };
+        let root = self.dwarf.unit.root();
+        let id = self.dwarf.unit.add(root, constants::DW_TAG_base_type);
+        let entry = self.dwarf.unit.get_mut(id);
+        entry.set(
+            constants::DW_AT_name,
+            AttributeValue::String(ty.to_string().into()),
+        );
+        entry.set(
+            constants::DW_AT_encoding,
+            AttributeValue::Encoding(encoding),
+        );
+        entry.set(
+            constants::DW_AT_byte_size,
+            AttributeValue::Udata(u64::from(ty.bytes())),
+        );
+        self.base_types.insert(ty, id);
+        id
+    }
+
+    fn variable_locations(
+        &self,
+        address: Address,
+        function: &DwarfFunction,
+        label: ir::ValueLabel,
+    ) -> LocationList {
+        let Some(ranges) = function.value_labels_ranges.get(&label) else {
+            return LocationList(Vec::new());
+        };
+        let locations = ranges
+            .iter()
+            .filter_map(|range| {
+                let mut data = Expression::new();
+                match range.loc {
+                    LabelValueLoc::Reg(reg) => {
+                        let reg = self.isa.map_regalloc_reg_to_dwarf(reg).ok()?;
+                        data.op_reg(Register(reg));
+                    }
+                    LabelValueLoc::CFAOffset(offset) => data.op_fbreg(offset),
+                }
+                Some(Location::StartEnd {
+                    begin: offset_address(address, range.start),
+                    end: offset_address(address, range.end),
+                    data,
+                })
+            })
+            .collect();
+        LocationList(locations)
+    }
+}
+
+fn offset_address(address: Address, offset: CodeOffset) -> Address {
+    match address {
+        Address::Constant(addr) => Address::Constant(addr + u64::from(offset)),
+        Address::Symbol { symbol, addend } => Address::Symbol {
+            symbol,
+            addend: addend + i64::from(offset),
+        },
+    }
+}
+
+fn dwarf_error(err: gimli::write::Error) -> ModuleError {
+    ModuleError::Backend(anyhow::Error::msg(err.to_string()).context("unable to write DWARF"))
+}
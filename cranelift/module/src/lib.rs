@@ -21,10 +21,16 @@ use std::string::String;
 use cranelift_codegen::ir;
 
 mod data_context;
+mod debug;
+#[cfg(feature = "dwarf")]
+mod dwarf;
 mod module;
 mod traps;
 
 pub use crate::data_context::{DataDescription, Init};
+pub use crate::debug::{FunctionDebugInfo, SourcePosition, VariableDebugInfo};
+#[cfg(feature = "dwarf")]
+pub use crate::dwarf::{DwarfBuilder, DwarfFunction};
 pub use crate::module::{
     DataDeclaration, DataId, FuncId, FuncOrDataId, FunctionDeclaration, Linkage, Module,
     ModuleDeclarations, ModuleError, ModuleReloc, ModuleRelocTarget, ModuleResult,
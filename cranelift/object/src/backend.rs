@@ -7,10 +7,10 @@ use cranelift_codegen::ir;
 use cranelift_codegen::isa::{OwnedTargetIsa, TargetIsa};
 use cranelift_control::ControlPlane;
 use cranelift_module::{
-    DataDescription, DataId, FuncId, Init, Linkage, Module, ModuleDeclarations, ModuleError,
-    ModuleReloc, ModuleRelocTarget, ModuleResult,
+    DataDescription, DataId, DwarfFunction, FuncId, FunctionDebugInfo, Init, Linkage, Module,
+    ModuleDeclarations, ModuleError, ModuleReloc, ModuleRelocTarget, ModuleResult,
 };
-use log::info;
+use log::{info, warn};
 use object::write::{
     Object, Relocation, SectionId, StandardSection, Symbol, SymbolId, SymbolSection,
 };
@@ -34,6 +34,7 @@ pub struct ObjectBuilder {
     libcall_names: Box<dyn Fn(ir::LibCall) -> String + Send + Sync>,
     per_function_section: bool,
     per_data_object_section: bool,
+    debug_info: bool,
 }
 
 impl ObjectBuilder {
@@ -124,6 +125,7 @@ impl ObjectBuilder {
             libcall_names,
             per_function_section: false,
             per_data_object_section: false,
+            debug_info: false,
         })
     }
 
@@ -138,6 +140,16 @@ impl ObjectBuilder {
         self.per_data_object_section = per_data_object_section;
         self
     }
+
+    /// Set if DWARF debug information describing the defined functions should
+    /// be emitted.
+    ///
+    /// Source locations and variables can be described for each function with
+    /// [`ObjectModule::set_function_debug_info`].
+    pub fn debug_info(&mut self, debug_info: bool) -> &mut Self {
+        self.debug_info = debug_info;
+        self
+    }
 }
 
 /// An `ObjectModule` implements `Module` and emits ".o" files using the `object` library.
@@ -156,6 +168,10 @@ pub struct ObjectModule {
     known_labels: HashMap<(FuncId, CodeOffset), SymbolId>,
     per_function_section: bool,
     per_data_object_section: bool,
+    name: String,
+    debug_info: bool,
+    dwarf_functions: SecondaryMap<FuncId, Option<DwarfFunction>>,
+    function_debug_info: SecondaryMap<FuncId, Option<FunctionDebugInfo>>,
 }
 
 impl ObjectModule {
@@ -164,6 +180,7 @@ impl ObjectModule {
         let mut object = Object::new(builder.binary_format, builder.architecture, builder.endian);
         object.flags = builder.flags;
         object.set_subsections_via_symbols();
+        let name = String::from_utf8_lossy(&builder.name).into_owned();
         object.add_file_symbol(builder.name);
         Self {
             isa: builder.isa,
@@ -178,8 +195,20 @@ impl ObjectModule {
             known_labels: HashMap::new(),
             per_function_section: builder.per_function_section,
             per_data_object_section: builder.per_data_object_section,
+            name,
+            debug_info: builder.debug_info,
+            dwarf_functions: SecondaryMap::new(),
+            function_debug_info: SecondaryMap::new(),
         }
     }
+
+    /// Attach source-level debug information to a function.
+    ///
+    /// It is only emitted when DWARF debug information is enabled on the
+    /// [`ObjectBuilder`].
+    pub fn set_function_debug_info(&mut self, func_id: FuncId, debug_info: FunctionDebugInfo) {
+        self.function_debug_info[func_id] = Some(debug_info);
+    }
 }
 
 fn validate_symbol(name: &str) -> ModuleResult<()> {
@@ -345,7 +374,8 @@ impl Module for ObjectModule {
         let res = ctx.compile(self.isa(), ctrl_plane)?;
         let alignment = res.buffer.alignment as u64;
 
-        let buffer = &ctx.compiled_code().unwrap().buffer;
+        let compiled_code = ctx.compiled_code().unwrap();
+        let buffer = &compiled_code.buffer;
         let relocs = buffer
             .relocs()
             .iter()
@@ -353,7 +383,12 @@ impl Module for ObjectModule {
                 self.process_reloc(&ModuleReloc::from_mach_reloc(&reloc, &ctx.func, func_id))
             })
             .collect::<Vec<_>>();
-        self.define_function_inner(func_id, alignment, buffer.data(), relocs)
+        self.define_function_inner(func_id, alignment, buffer.data(), relocs)?;
+
+        if self.debug_info {
+            self.dwarf_functions[func_id] = Some(DwarfFunction::new(self.isa(), compiled_code)?);
+        }
+        Ok(())
     }
 
     fn define_function_bytes(
@@ -367,7 +402,18 @@ impl Module for ObjectModule {
             .iter()
             .map(|reloc| self.process_reloc(reloc))
             .collect();
-        self.define_function_inner(func_id, alignment, bytes, relocs)
+        self.define_function_inner(func_id, alignment, bytes, relocs)?;
+
+        // Only the code range is known for raw bytes.
+        if self.debug_info {
+            self.dwarf_functions[func_id] = Some(DwarfFunction {
+                size: u32::try_from(bytes.len()).unwrap(),
+                srclocs: Vec::new(),
+                value_labels_ranges: Default::default(),
+                unwind_info: None,
+            });
+        }
+        Ok(())
     }
 
     fn define_data(&mut self, data_id: DataId, data: &DataDescription) -> ModuleResult<()> {
@@ -588,6 +634,10 @@ impl ObjectModule {
             }
         }
 
+        if self.debug_info {
+            self.write_dwarf();
+        }
+
         // Indicate that this object has a non-executable stack.
         if self.object.format() == object::BinaryFormat::Elf {
             self.object.add_section(
@@ -604,6 +654,35 @@ impl ObjectModule {
         }
     }
 
+    /// Emit DWARF sections describing all defined functions.
+    fn write_dwarf(&mut self) {
+        let names = self
+            .dwarf_functions
+            .iter()
+            .filter(|(_, dwarf)| dwarf.is_some())
+            .map(|(func_id, _)| {
+                let decl = self.declarations.get_function_decl(func_id);
+                (func_id, decl.linkage_name(func_id).into_owned())
+            })
+            .collect::<Vec<_>>();
+        let functions = names
+            .iter()
+            .map(|(func_id, name)| crate::debug::DebugFunction {
+                name,
+                symbol: self.functions[*func_id].unwrap().0,
+                dwarf: self.dwarf_functions[*func_id].as_ref().unwrap(),
+                debug_info: self.function_debug_info[*func_id].as_ref(),
+            })
+            .collect::<Vec<_>>();
+        // Debug information is best effort: failing to describe a function
+        // shouldn't prevent the object itself from being produced.
+        if let Err(err) =
+            crate::debug::write_dwarf(&mut self.object, &*self.isa, &self.name, &functions)
+        {
+            warn!("skipping DWARF debug information: {err}");
+        }
+    }
+
     /// This should only be called during finish because it creates
     /// symbols for missing libcalls.
     fn get_symbol(&mut self, name: &ModuleRelocTarget) -> SymbolId {
@@ -0,0 +1,271 @@
+//! Emission of DWARF debug information into an object file.
+
+use cranelift_codegen::gimli::write::{Address, EndianVec, Sections, Writer};
+use cranelift_codegen::gimli::{self, RunTimeEndian, SectionId};
+use cranelift_codegen::ir::Endianness;
+use cranelift_codegen::isa::TargetIsa;
+use cranelift_module::{DwarfBuilder, DwarfFunction, FunctionDebugInfo, ModuleError, ModuleResult};
+use object::write::{Object, Relocation, StandardSegment, SymbolId};
+use object::{RelocationEncoding, RelocationFlags, RelocationKind, SectionKind};
+use std::collections::HashMap;
+
+/// A function to describe in the DWARF sections.
+pub(crate) struct DebugFunction<'a> {
+    pub name: &'a str,
+    pub symbol: SymbolId,
+    pub dwarf: &'a DwarfFunction,
+    pub debug_info: Option<&'a FunctionDebugInfo>,
+}
+
+/// Add DWARF sections describing `functions` to `object`.
+///
+/// Addresses of functions and offsets into other DWARF sections are written
+/// as relocations, so that the sections remain valid once linked. The DWARF is
+/// fully generated, and every relocation checked, before anything is added to
+/// `object`, so `object` is left untouched if that fails.
+pub(crate) fn write_dwarf(
+    object: &mut Object<'static>,
+    isa: &dyn TargetIsa,
+    name: &str,
+    functions: &[DebugFunction<'_>],
+) -> ModuleResult<()> {
+    let mut dwarf = DwarfBuilder::new(isa, ".", name);
+    for (index, function) in functions.iter().enumerate() {
+        dwarf.add_function(
+            function.name,
+            Address::Symbol {
+                symbol: index,
+                addend: 0,
+            },
+            function.dwarf,
+            function.debug_info,
+        );
+    }
+
+    let (endian, object_endian) = match isa.endianness() {
+        Endianness::Little => (RunTimeEndian::Little, object::Endianness::Little),
+        Endianness::Big => (RunTimeEndian::Big, object::Endianness::Big),
+    };
+    // Offsets between DWARF sections are left unrelocated on Mach-O, as the
+    // linker doesn't merge debug sections there.
+    let relocate_offsets = object.format() != object::BinaryFormat::MachO;
+    let mut sections = Sections::new(WriterRelocate::new(endian, relocate_offsets));
+    dwarf.write(&mut sections)?;
+
+    let function_symbols = functions
+        .iter()
+        .map(|function| {
+            object
+                .symbol_section_and_offset(function.symbol)
+                .ok_or_else(|| {
+                    ModuleError::Backend(anyhow::anyhow!(
+                        "function {} is not defined",
+                        function.name
+                    ))
+                })
+        })
+        .collect::<ModuleResult<Vec<_>>>()?;
+    let section_offset_kind = match object.format() {
+        object::BinaryFormat::Coff => RelocationKind::SectionOffset,
+        _ => RelocationKind::Absolute,
+    };
+    let flags = |reloc: &DebugReloc| {
+        let kind = match reloc.target {
+            RelocTarget::Function(_) => RelocationKind::Absolute,
+            RelocTarget::Section(_) => section_offset_kind,
+        };
+        RelocationFlags::Generic {
+            kind,
+            encoding: RelocationEncoding::Generic,
+            size: reloc.size * 8,
+        }
+    };
+    let mut all_flags = Vec::new();
+    sections
+        .for_each(|_, section| -> Result<(), ()> {
+            for reloc in &section.relocs {
+                if !all_flags.contains(&flags(reloc)) {
+                    all_flags.push(flags(reloc));
+                }
+            }
+            Ok(())
+        })
+        .unwrap();
+    check_relocations(object, object_endian, &all_flags)?;
+
+    let mut section_ids = HashMap::new();
+    sections
+        .for_each(|id, section| -> Result<(), ()> {
+            if !section.writer.slice().is_empty() {
+                let segment = object.segment_name(StandardSegment::Debug).to_vec();
+                let name = match object.format() {
+                    // Mach-O uses `__debug_info` rather than `.debug_info`.
+                    object::BinaryFormat::MachO => format!("__{}", &id.name()[1..]),
+                    _ => id.name().to_string(),
+                };
+                let section_id = object.add_section(segment, name.into_bytes(), SectionKind::Debug);
+                object.append_section_data(section_id, section.writer.slice(), 1);
+                section_ids.insert(id, section_id);
+            }
+            Ok(())
+        })
+        .unwrap();
+
+    sections.for_each(|id, section| -> ModuleResult<()> {
+        for reloc in &section.relocs {
+            let (symbol, symbol_offset) = match reloc.target {
+                RelocTarget::Function(index) => function_symbols[index],
+                RelocTarget::Section(id) => (object.section_symbol(section_ids[&id]), 0),
+            };
+            object
+                .add_relocation(
+                    section_ids[&id],
+                    Relocation {
+                        offset: reloc.offset,
+                        symbol,
+                        addend: symbol_offset as i64 + reloc.addend,
+                        flags: flags(reloc),
+                    },
+                )
+                .map_err(relocation_error)?;
+        }
+        Ok(())
+    })
+}
+
+/// Checks that relocations with each of `flags` can be added to `object`,
+/// by adding them to an empty object of the same kind.
+fn check_relocations(
+    object: &Object<'_>,
+    endian: object::Endianness,
+    flags: &[RelocationFlags],
+) -> ModuleResult<()> {
+    let mut scratch = Object::new(object.format(), object.architecture(), endian);
+    scratch.flags = object.flags;
+    scratch.set_sub_architecture(object.sub_architecture());
+    let section = scratch.add_section(Vec::new(), b".debug".to_vec(), SectionKind::Debug);
+    scratch.append_section_data(section, &[0; 8], 1);
+    let symbol = scratch.section_symbol(section);
+    for &flags in flags {
+        scratch
+            .add_relocation(
+                section,
+                Relocation {
+                    offset: 0,
+                    symbol,
+                    addend: 0,
+                    flags,
+                },
+            )
+            .map_err(relocation_error)?;
+    }
+    Ok(())
+}
+
+fn relocation_error(err: object::write::Error) -> ModuleError {
+    ModuleError::Backend(anyhow::Error::new(err).context("unable to relocate DWARF"))
+}
+
+#[derive(Clone, Copy)]
+enum RelocTarget {
+    Function(usize),
+    Section(SectionId),
+}
+
+#[derive(Clone)]
+struct DebugReloc {
+    target: RelocTarget,
+    offset: u64,
+    size: u8,
+    addend: i64,
+}
+
+/// A `gimli` writer which records the relocations needed by the data written.
+#[derive(Clone)]
+struct WriterRelocate {
+    relocs: Vec<DebugReloc>,
+    writer: EndianVec<RunTimeEndian>,
+    relocate_offsets: bool,
+}
+
+impl WriterRelocate {
+    fn new(endian: RunTimeEndian, relocate_offsets: bool) -> Self {
+        Self {
+            relocs: Vec::new(),
+            writer: EndianVec::new(endian),
+            relocate_offsets,
+        }
+    }
+}
+
+impl Writer for WriterRelocate {
+    type Endian = RunTimeEndian;
+
+    fn endian(&self) -> Self::Endian {
+        self.writer.endian()
+    }
+
+    fn len(&self) -> usize {
+        self.writer.len()
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> gimli::write::Result<()> {
+        self.writer.write(bytes)
+    }
+
+    fn write_at(&mut self, offset: usize, bytes: &[u8]) -> gimli::write::Result<()> {
+        self.writer.write_at(offset, bytes)
+    }
+
+    fn write_address(&mut self, address: Address, size: u8) -> gimli::write::Result<()> {
+        match address {
+            Address::Constant(val) => self.write_udata(val, size),
+            Address::Symbol { symbol, addend } => {
+                self.relocs.push(DebugReloc {
+                    target: RelocTarget::Function(symbol),
+                    offset: self.len() as u64,
+                    size,
+                    addend,
+                });
+                self.write_udata(0, size)
+            }
+        }
+    }
+
+    fn write_offset(
+        &mut self,
+        val: usize,
+        section: SectionId,
+        size: u8,
+    ) -> gimli::write::Result<()> {
+        if !self.relocate_offsets {
+            return self.write_udata(val as u64, size);
+        }
+        self.relocs.push(DebugReloc {
+            target: RelocTarget::Section(section),
+            offset: self.len() as u64,
+            size,
+            addend: val as i64,
+        });
+        self.write_udata(0, size)
+    }
+
+    fn write_offset_at(
+        &mut self,
+        offset: usize,
+        val: usize,
+        section: SectionId,
+        size: u8,
+    ) -> gimli::write::Result<()> {
+        if !self.relocate_offsets {
+            return self.write_udata_at(offset, val as u64, size);
+        }
+        self.relocs.push(DebugReloc {
+            target: RelocTarget::Section(section),
+            offset: offset as u64,
+            size,
+            addend: val as i64,
+        });
+        self.write_udata_at(offset, 0, size)
+    }
+}
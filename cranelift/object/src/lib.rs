@@ -5,6 +5,7 @@
 #![deny(missing_docs)]
 
 mod backend;
+mod debug;
 
 pub use crate::backend::{ObjectBuilder, ObjectModule, ObjectProduct};
 
@@ -258,3 +258,87 @@ fn reject_nul_byte_symbol_for_data() {
         )
         .unwrap();
 }
+
+#[test]
+fn debug_info() {
+    use cranelift_object::object::{Object, ObjectSection};
+
+    let flag_builder = settings::builder();
+    let isa_builder = cranelift_codegen::isa::lookup_by_name("x86_64-unknown-linux-gnu").unwrap();
+    let isa = isa_builder
+        .finish(settings::Flags::new(flag_builder))
+        .unwrap();
+    let mut builder = ObjectBuilder::new(isa, "foo", default_libcall_names()).unwrap();
+    builder.debug_info(true);
+    let mut module = ObjectModule::new(builder);
+
+    let sig = Signature {
+        params: vec![AbiParam::new(types::I64)],
+        returns: vec![AbiParam::new(types::I64)],
+        call_conv: CallConv::SystemV,
+    };
+
+    let func_id = module
+        .declare_function("add_one", Linkage::Export, &sig)
+        .unwrap();
+
+    let mut ctx = Context::new();
+    ctx.func = Function::with_name_signature(UserFuncName::user(0, func_id.as_u32()), sig);
+    ctx.func.collect_debug_info();
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.append_block_params_for_function_params(block);
+        bcx.switch_to_block(block);
+        bcx.seal_block(block);
+
+        let x = bcx.block_params(block)[0];
+        bcx.set_val_label(x, ValueLabel::new(0));
+        bcx.set_srcloc(SourceLoc::new(1));
+        let y = bcx.ins().iadd_imm(x, 1);
+        bcx.set_srcloc(SourceLoc::new(2));
+        bcx.ins().return_(&[y]);
+        bcx.finalize();
+    }
+
+    module.define_function(func_id, &mut ctx).unwrap();
+
+    let mut debug_info = FunctionDebugInfo {
+        file: "add_one.src".into(),
+        line: 1,
+        ..Default::default()
+    };
+    debug_info
+        .source_locs
+        .insert(SourceLoc::new(1), SourcePosition { line: 2, column: 5 });
+    debug_info
+        .source_locs
+        .insert(SourceLoc::new(2), SourcePosition { line: 3, column: 5 });
+    debug_info.variables.push(VariableDebugInfo {
+        name: "x".into(),
+        label: ValueLabel::new(0),
+        ty: types::I64,
+    });
+    module.set_function_debug_info(func_id, debug_info);
+
+    let bytes = module.finish().emit().unwrap();
+    let file = cranelift_object::object::File::parse(&*bytes).unwrap();
+    for name in [
+        ".debug_info",
+        ".debug_abbrev",
+        ".debug_line",
+        ".debug_frame",
+    ] {
+        let section = file.section_by_name(name).unwrap();
+        assert!(!section.data().unwrap().is_empty(), "{name} is empty");
+    }
+    // Function addresses and offsets into other DWARF sections are relocated.
+    for name in [".debug_info", ".debug_line", ".debug_frame"] {
+        let section = file.section_by_name(name).unwrap();
+        assert!(
+            section.relocations().next().is_some(),
+            "{name} has no relocations"
+        );
+    }
+}
@@ -192,6 +192,7 @@
 #include <wasmtime/error.h>
 #include <wasmtime/extern.h>
 #include <wasmtime/func.h>
+#include <wasmtime/gc.h>
 #include <wasmtime/global.h>
 #include <wasmtime/instance.h>
 #include <wasmtime/linker.h>
@@ -40,6 +40,7 @@
 #include <wasmtime/error.hh>
 #include <wasmtime/extern.hh>
 #include <wasmtime/func.hh>
+#include <wasmtime/gc.hh>
 #include <wasmtime/global.hh>
 #include <wasmtime/instance.hh>
 #include <wasmtime/linker.hh>
@@ -5,6 +5,8 @@
 #include <wasmtime/component/func.h>
 #include <wasmtime/component/instance.h>
 #include <wasmtime/component/linker.h>
+#include <wasmtime/component/resource.h>
+#include <wasmtime/component/types.h>
 #include <wasmtime/component/val.h>
 
 #endif // WASMTIME_COMPONENT_H
@@ -5,6 +5,7 @@
 
 #include <wasmtime/component/component.h>
 #include <wasmtime/component/func.h>
+#include <wasmtime/component/resource.h>
 #include <wasmtime/conf.h>
 #include <wasmtime/store.h>
 
@@ -60,6 +61,26 @@ WASM_API_EXTERN bool wasmtime_component_instance_get_func(
     const wasmtime_component_export_index_t *export_index,
     wasmtime_component_func_t *func_out);
 
+/**
+ * \brief Looks up an exported resource type within this
+ * #wasmtime_component_instance_t.
+ *
+ * This is used to acquire the type of a resource defined by the guest, which
+ * can then be compared against #wasmtime_component_resource_any_type.
+ *
+ * \param instance the instance to look up this name in
+ * \param context the store that \p instance lives in
+ * \param export_index the export index of the resource type
+ * \param resource_type_out if found, the resource type corresponding to \p
+ * export_index, which must be deleted with
+ * #wasmtime_component_resource_type_delete
+ * \return boolean marking if a resource type was found
+ */
+WASM_API_EXTERN bool wasmtime_component_instance_get_resource(
+    const wasmtime_component_instance_t *instance, wasmtime_context_t *context,
+    const wasmtime_component_export_index_t *export_index,
+    wasmtime_component_resource_type_t **resource_type_out);
+
 #ifdef __cplusplus
 } // extern "C"
 #endif
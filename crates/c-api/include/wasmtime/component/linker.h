@@ -6,6 +6,7 @@
 #include <wasm.h>
 #include <wasmtime/component/component.h>
 #include <wasmtime/component/instance.h>
+#include <wasmtime/component/resource.h>
 #include <wasmtime/conf.h>
 #include <wasmtime/error.h>
 #include <wasmtime/store.h>
@@ -136,6 +137,33 @@ WASM_API_EXTERN wasmtime_error_t *wasmtime_component_linker_instance_add_func(
     size_t name_len, wasmtime_component_func_callback_t callback, void *data,
     void (*finalizer)(void *));
 
+/// Type of the callback used in
+/// #wasmtime_component_linker_instance_add_resource
+typedef wasmtime_error_t *(*wasmtime_component_resource_destructor_t)(
+    void *, wasmtime_context_t *, uint32_t);
+
+/**
+ * \brief Define a resource type within this instance.
+ *
+ * \param linker_instance the instance to define the resource in
+ * \param name the resource name
+ * \param name_len length of \p name in bytes
+ * \param ty the type of the resource, usually created with
+ * #wasmtime_component_resource_type_new_host
+ * \param destructor the callback invoked with the representation of an owned
+ * resource of type \p ty when it is destroyed
+ * \param data host-specific data passed to the callback invocation, can be
+ * `NULL`
+ * \param finalizer optional finalizer for \p data, can be `NULL`
+ * \return on success `NULL`, otherwise an error
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_component_linker_instance_add_resource(
+    wasmtime_component_linker_instance_t *linker_instance, const char *name,
+    size_t name_len, const wasmtime_component_resource_type_t *ty,
+    wasmtime_component_resource_destructor_t destructor, void *data,
+    void (*finalizer)(void *));
+
 #ifdef WASMTIME_FEATURE_WASI
 
 /**
@@ -0,0 +1,188 @@
+/// \file wasmtime/component/resource.h
+
+#ifndef WASMTIME_COMPONENT_RESOURCE_H
+#define WASMTIME_COMPONENT_RESOURCE_H
+
+#include <wasmtime/conf.h>
+#include <wasmtime/error.h>
+#include <wasmtime/store.h>
+
+#ifdef WASMTIME_FEATURE_COMPONENT_MODEL
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * \brief Representation of the type of a resource.
+ *
+ * Resource types are either defined by the host, with
+ * #wasmtime_component_resource_type_new_host, or by a guest in which case they
+ * can be acquired with #wasmtime_component_instance_get_resource.
+ */
+typedef struct wasmtime_component_resource_type_t
+    wasmtime_component_resource_type_t;
+
+/**
+ * \brief Creates a new host-defined resource type.
+ *
+ * Host resource types are identified by the integer \p ty, and two host types
+ * created with the same integer are the same type.
+ *
+ * \return a new resource type which must be deleted with
+ * #wasmtime_component_resource_type_delete
+ */
+WASM_API_EXTERN wasmtime_component_resource_type_t *
+wasmtime_component_resource_type_new_host(uint32_t ty);
+
+/// \brief Clones a #wasmtime_component_resource_type_t
+WASM_API_EXTERN wasmtime_component_resource_type_t *
+wasmtime_component_resource_type_clone(
+    const wasmtime_component_resource_type_t *ty);
+
+/// \brief Returns whether \p a and \p b are the same resource type
+WASM_API_EXTERN bool wasmtime_component_resource_type_equal(
+    const wasmtime_component_resource_type_t *a,
+    const wasmtime_component_resource_type_t *b);
+
+/// \brief Deletes a #wasmtime_component_resource_type_t
+WASM_API_EXTERN void
+wasmtime_component_resource_type_delete(wasmtime_component_resource_type_t *ty);
+
+/**
+ * \brief A resource defined by the host.
+ *
+ * Host resources are a pair of a 32-bit representation, chosen by the
+ * embedder, and a host type as passed to
+ * #wasmtime_component_resource_type_new_host. They can be converted to and
+ * from #wasmtime_component_resource_any_t to pass them to and from
+ * components.
+ */
+typedef struct wasmtime_component_resource_host_t
+    wasmtime_component_resource_host_t;
+
+/**
+ * \brief A resource of any type, either owned or borrowed.
+ *
+ * This is how resources are represented in #wasmtime_component_val_t. Such
+ * resources live in a #wasmtime_context_t and must be destroyed with
+ * #wasmtime_component_resource_any_drop once the embedder is done with them,
+ * which for example runs the destructor of owned resources. Deleting the
+ * #wasmtime_component_resource_any_t itself only releases its memory.
+ */
+typedef struct wasmtime_component_resource_any_t
+    wasmtime_component_resource_any_t;
+
+/**
+ * \brief Returns the type of \p resource.
+ *
+ * The returned type must be deleted with
+ * #wasmtime_component_resource_type_delete.
+ */
+WASM_API_EXTERN wasmtime_component_resource_type_t *
+wasmtime_component_resource_any_type(
+    const wasmtime_component_resource_any_t *resource);
+
+/// \brief Returns whether \p resource is owned, as opposed to borrowed
+WASM_API_EXTERN bool wasmtime_component_resource_any_owned(
+    const wasmtime_component_resource_any_t *resource);
+
+/**
+ * \brief Destroys \p resource within the \p context it lives in.
+ *
+ * For owned resources this runs the destructor of the resource, if any.
+ *
+ * \return on success `NULL`, otherwise an error
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_resource_any_drop(
+    wasmtime_context_t *context,
+    const wasmtime_component_resource_any_t *resource);
+
+/**
+ * \brief Converts \p resource into a host resource of host type \p ty.
+ *
+ * This fails if \p resource isn't of the host type \p ty. Owned resources are
+ * moved out of \p context, after which \p resource may no longer be used.
+ *
+ * \param context the context that \p resource lives in
+ * \param resource the resource to convert
+ * \param ty the host type, as passed to
+ * #wasmtime_component_resource_type_new_host
+ * \param host_out on success, the host resource which must be deleted with
+ * #wasmtime_component_resource_host_delete
+ * \return on success `NULL`, otherwise an error
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_resource_any_to_host(
+    wasmtime_context_t *context,
+    const wasmtime_component_resource_any_t *resource, uint32_t ty,
+    wasmtime_component_resource_host_t **host_out);
+
+/// \brief Clones a #wasmtime_component_resource_any_t
+///
+/// Both handles refer to the same resource, which must still only be dropped
+/// once with #wasmtime_component_resource_any_drop.
+WASM_API_EXTERN wasmtime_component_resource_any_t *
+wasmtime_component_resource_any_clone(
+    const wasmtime_component_resource_any_t *resource);
+
+/// \brief Deletes a #wasmtime_component_resource_any_t
+WASM_API_EXTERN void wasmtime_component_resource_any_delete(
+    wasmtime_component_resource_any_t *resource);
+
+/**
+ * \brief Creates a new host resource.
+ *
+ * \param owned whether the resource is owned or borrowed
+ * \param rep the representation of the resource
+ * \param ty the host type of the resource
+ * \return the new resource which must be deleted with
+ * #wasmtime_component_resource_host_delete
+ */
+WASM_API_EXTERN wasmtime_component_resource_host_t *
+wasmtime_component_resource_host_new(bool owned, uint32_t rep, uint32_t ty);
+
+/// \brief Returns the representation of \p resource
+WASM_API_EXTERN uint32_t wasmtime_component_resource_host_rep(
+    const wasmtime_component_resource_host_t *resource);
+
+/// \brief Returns the host type of \p resource
+WASM_API_EXTERN uint32_t wasmtime_component_resource_host_type(
+    const wasmtime_component_resource_host_t *resource);
+
+/// \brief Returns whether \p resource is owned, as opposed to borrowed
+WASM_API_EXTERN bool wasmtime_component_resource_host_owned(
+    const wasmtime_component_resource_host_t *resource);
+
+/**
+ * \brief Converts \p resource into a #wasmtime_component_resource_any_t.
+ *
+ * Borrowed resources may only be converted while a host function is being
+ * called.
+ *
+ * \param context the context that the new resource will live in
+ * \param resource the resource to convert
+ * \param any_out on success, the converted resource which must be deleted with
+ * #wasmtime_component_resource_any_delete
+ * \return on success `NULL`, otherwise an error
+ */
+WASM_API_EXTERN wasmtime_error_t *wasmtime_component_resource_host_to_any(
+    wasmtime_context_t *context,
+    const wasmtime_component_resource_host_t *resource,
+    wasmtime_component_resource_any_t **any_out);
+
+/// \brief Clones a #wasmtime_component_resource_host_t
+WASM_API_EXTERN wasmtime_component_resource_host_t *
+wasmtime_component_resource_host_clone(
+    const wasmtime_component_resource_host_t *resource);
+
+/// \brief Deletes a #wasmtime_component_resource_host_t
+WASM_API_EXTERN void wasmtime_component_resource_host_delete(
+    wasmtime_component_resource_host_t *resource);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif // WASMTIME_FEATURE_COMPONENT_MODEL
+
+#endif // WASMTIME_COMPONENT_RESOURCE_H
@@ -0,0 +1,257 @@
+/**
+ * \file wasmtime/component/resource.hh
+ */
+
+#ifndef WASMTIME_COMPONENT_RESOURCE_HH
+#define WASMTIME_COMPONENT_RESOURCE_HH
+
+#include <wasmtime/conf.h>
+
+#ifdef WASMTIME_FEATURE_COMPONENT_MODEL
+
+#include <memory>
+#include <string_view>
+#include <wasmtime/component/linker.h>
+#include <wasmtime/component/resource.h>
+#include <wasmtime/error.hh>
+#include <wasmtime/store.hh>
+
+namespace wasmtime {
+namespace component {
+
+/**
+ * \brief The type of a component resource.
+ *
+ * Resource types are either defined by the host with `ResourceType::host` or
+ * by a guest, in which case they're acquired with
+ * `wasmtime_component_instance_get_resource`.
+ */
+class ResourceType {
+  struct deleter {
+    void operator()(wasmtime_component_resource_type_t *p) const {
+      wasmtime_component_resource_type_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_component_resource_type_t, deleter> ptr;
+
+  template <typename F>
+  static wasmtime_error_t *raw_destructor(void *env, wasmtime_context_t *cx,
+                                          uint32_t rep) {
+    F *func = reinterpret_cast<F *>(env); // NOLINT
+    Result<std::monostate> result = (*func)(Store::Context(cx), rep);
+    if (!result) {
+      return result.err().release();
+    }
+    return nullptr;
+  }
+
+  template <typename F> static void raw_finalize(void *env) {
+    std::unique_ptr<F> ptr(reinterpret_cast<F *>(env)); // NOLINT
+  }
+
+public:
+  /// Takes ownership of the raw C API representation of a resource type.
+  explicit ResourceType(wasmtime_component_resource_type_t *ptr) : ptr(ptr) {}
+
+  /// Creates the host resource type identified by `ty`.
+  static ResourceType host(uint32_t ty) {
+    return ResourceType(wasmtime_component_resource_type_new_host(ty));
+  }
+
+  /// Copies another resource type into this one.
+  ResourceType(const ResourceType &other)
+      : ptr(wasmtime_component_resource_type_clone(other.ptr.get())) {}
+  /// Copies another resource type into this one.
+  ResourceType &operator=(const ResourceType &other) {
+    ptr.reset(wasmtime_component_resource_type_clone(other.ptr.get()));
+    return *this;
+  }
+  ~ResourceType() = default;
+  /// Moves another resource type into this one.
+  ResourceType(ResourceType &&other) = default;
+  /// Moves another resource type into this one.
+  ResourceType &operator=(ResourceType &&other) = default;
+
+  /// Returns whether both types are the same resource type.
+  bool operator==(const ResourceType &other) const {
+    return wasmtime_component_resource_type_equal(ptr.get(), other.ptr.get());
+  }
+  /// Returns whether the types are different resource types.
+  bool operator!=(const ResourceType &other) const {
+    return !(*this == other);
+  }
+
+  /// Returns the raw C API representation of this type.
+  const wasmtime_component_resource_type_t *capi() const { return ptr.get(); }
+
+  /**
+   * \brief Defines this resource type as `name` within `linker_instance`.
+   *
+   * The `destructor` is invoked with a `Store::Context` and the
+   * representation of an owned resource of this type when it's destroyed, and
+   * must return a `Result<std::monostate>`.
+   */
+  template <typename F>
+  Result<std::monostate>
+  define(wasmtime_component_linker_instance_t *linker_instance,
+         std::string_view name, F &&destructor) const {
+    auto *error = wasmtime_component_linker_instance_add_resource(
+        linker_instance, name.data(), name.length(), ptr.get(),
+        raw_destructor<std::remove_reference_t<F>>,
+        std::make_unique<std::remove_reference_t<F>>(std::forward<F>(destructor))
+            .release(),
+        raw_finalize<std::remove_reference_t<F>>);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return std::monostate();
+  }
+};
+
+class ResourceHost;
+
+/**
+ * \brief A resource of any type, owned or borrowed, as passed to and from
+ * components.
+ *
+ * Resources live within a store and must be destroyed with `drop` once the
+ * embedder is done with them. Destroying this object itself only releases the
+ * handle.
+ */
+class ResourceAny {
+  struct deleter {
+    void operator()(wasmtime_component_resource_any_t *p) const {
+      wasmtime_component_resource_any_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_component_resource_any_t, deleter> ptr;
+
+public:
+  /// Takes ownership of the raw C API representation of a resource.
+  explicit ResourceAny(wasmtime_component_resource_any_t *ptr) : ptr(ptr) {}
+
+  /// Copies another resource handle into this one.
+  ResourceAny(const ResourceAny &other)
+      : ptr(wasmtime_component_resource_any_clone(other.ptr.get())) {}
+  /// Copies another resource handle into this one.
+  ResourceAny &operator=(const ResourceAny &other) {
+    ptr.reset(wasmtime_component_resource_any_clone(other.ptr.get()));
+    return *this;
+  }
+  ~ResourceAny() = default;
+  /// Moves another resource handle into this one.
+  ResourceAny(ResourceAny &&other) = default;
+  /// Moves another resource handle into this one.
+  ResourceAny &operator=(ResourceAny &&other) = default;
+
+  /// Returns the type of this resource.
+  ResourceType type() const {
+    return ResourceType(wasmtime_component_resource_any_type(ptr.get()));
+  }
+
+  /// Returns whether this resource is owned, as opposed to borrowed.
+  bool owned() const { return wasmtime_component_resource_any_owned(ptr.get()); }
+
+  /// Destroys this resource within `cx`, running its destructor if it's owned.
+  Result<std::monostate> drop(Store::Context cx) const {
+    auto *error =
+        wasmtime_component_resource_any_drop(cx.raw_context(), ptr.get());
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return std::monostate();
+  }
+
+  /// Converts this resource into a host resource of host type `ty`.
+  Result<ResourceHost> to_host(Store::Context cx, uint32_t ty) const;
+
+  /// Returns the raw C API representation of this resource.
+  const wasmtime_component_resource_any_t *capi() const { return ptr.get(); }
+
+  /// Releases ownership of the raw C API representation of this resource.
+  wasmtime_component_resource_any_t *release() { return ptr.release(); }
+};
+
+/**
+ * \brief A resource defined by the host.
+ *
+ * Host resources pair a 32-bit representation chosen by the embedder with the
+ * host type passed to `ResourceType::host`.
+ */
+class ResourceHost {
+  struct deleter {
+    void operator()(wasmtime_component_resource_host_t *p) const {
+      wasmtime_component_resource_host_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_component_resource_host_t, deleter> ptr;
+
+public:
+  /// Takes ownership of the raw C API representation of a host resource.
+  explicit ResourceHost(wasmtime_component_resource_host_t *ptr) : ptr(ptr) {}
+
+  /// Creates a new owned or borrowed host resource.
+  ResourceHost(bool owned, uint32_t rep, uint32_t ty)
+      : ptr(wasmtime_component_resource_host_new(owned, rep, ty)) {}
+
+  /// Copies another host resource into this one.
+  ResourceHost(const ResourceHost &other)
+      : ptr(wasmtime_component_resource_host_clone(other.ptr.get())) {}
+  /// Copies another host resource into this one.
+  ResourceHost &operator=(const ResourceHost &other) {
+    ptr.reset(wasmtime_component_resource_host_clone(other.ptr.get()));
+    return *this;
+  }
+  ~ResourceHost() = default;
+  /// Moves another host resource into this one.
+  ResourceHost(ResourceHost &&other) = default;
+  /// Moves another host resource into this one.
+  ResourceHost &operator=(ResourceHost &&other) = default;
+
+  /// Returns the representation of this resource.
+  uint32_t rep() const { return wasmtime_component_resource_host_rep(ptr.get()); }
+
+  /// Returns the host type of this resource.
+  uint32_t type() const {
+    return wasmtime_component_resource_host_type(ptr.get());
+  }
+
+  /// Returns whether this resource is owned, as opposed to borrowed.
+  bool owned() const {
+    return wasmtime_component_resource_host_owned(ptr.get());
+  }
+
+  /// Converts this resource into a `ResourceAny` living in `cx`.
+  ///
+  /// Borrowed resources may only be converted during a host function call.
+  Result<ResourceAny> to_any(Store::Context cx) const {
+    wasmtime_component_resource_any_t *any = nullptr;
+    auto *error = wasmtime_component_resource_host_to_any(cx.raw_context(),
+                                                          ptr.get(), &any);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return ResourceAny(any);
+  }
+};
+
+inline Result<ResourceHost> ResourceAny::to_host(Store::Context cx,
+                                                 uint32_t ty) const {
+  wasmtime_component_resource_host_t *host = nullptr;
+  auto *error = wasmtime_component_resource_any_to_host(cx.raw_context(),
+                                                        ptr.get(), ty, &host);
+  if (error != nullptr) {
+    return Error(error);
+  }
+  return ResourceHost(host);
+}
+
+} // namespace component
+} // namespace wasmtime
+
+#endif // WASMTIME_FEATURE_COMPONENT_MODEL
+
+#endif // WASMTIME_COMPONENT_RESOURCE_HH
@@ -0,0 +1,478 @@
+/// \file wasmtime/component/types.h
+
+#ifndef WASMTIME_COMPONENT_TYPES_H
+#define WASMTIME_COMPONENT_TYPES_H
+
+#include <wasm.h>
+#include <wasmtime/component/component.h>
+#include <wasmtime/component/resource.h>
+#include <wasmtime/conf.h>
+
+#ifdef WASMTIME_FEATURE_COMPONENT_MODEL
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define DECLARE_TYPE(name)                                                     \
+  /** \brief An opaque type, see the functions taking it as a parameter */     \
+  typedef struct name##_t name##_t;                                            \
+                                                                               \
+  /** \brief Clones \p ty */                                                   \
+  WASM_API_EXTERN name##_t *name##_clone(const name##_t *ty);                  \
+  /** \brief Deletes \p ty */                                                  \
+  WASM_API_EXTERN void name##_delete(name##_t *ty);
+
+DECLARE_TYPE(wasmtime_component_type)
+DECLARE_TYPE(wasmtime_component_instance_type)
+DECLARE_TYPE(wasmtime_component_module_type)
+DECLARE_TYPE(wasmtime_component_func_type)
+DECLARE_TYPE(wasmtime_component_list_type)
+DECLARE_TYPE(wasmtime_component_record_type)
+DECLARE_TYPE(wasmtime_component_tuple_type)
+DECLARE_TYPE(wasmtime_component_variant_type)
+DECLARE_TYPE(wasmtime_component_enum_type)
+DECLARE_TYPE(wasmtime_component_option_type)
+DECLARE_TYPE(wasmtime_component_result_type)
+DECLARE_TYPE(wasmtime_component_flags_type)
+DECLARE_TYPE(wasmtime_component_future_type)
+DECLARE_TYPE(wasmtime_component_stream_type)
+
+#undef DECLARE_TYPE
+
+/// \brief Discriminant used in #wasmtime_component_valtype_t::kind
+typedef uint8_t wasmtime_component_valtype_kind_t;
+
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a bool
+#define WASMTIME_COMPONENT_VALTYPE_BOOL 0
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a s8
+#define WASMTIME_COMPONENT_VALTYPE_S8 1
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a u8
+#define WASMTIME_COMPONENT_VALTYPE_U8 2
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a s16
+#define WASMTIME_COMPONENT_VALTYPE_S16 3
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a u16
+#define WASMTIME_COMPONENT_VALTYPE_U16 4
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a s32
+#define WASMTIME_COMPONENT_VALTYPE_S32 5
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a u32
+#define WASMTIME_COMPONENT_VALTYPE_U32 6
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a s64
+#define WASMTIME_COMPONENT_VALTYPE_S64 7
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a u64
+#define WASMTIME_COMPONENT_VALTYPE_U64 8
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a f32
+#define WASMTIME_COMPONENT_VALTYPE_F32 9
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a f64
+#define WASMTIME_COMPONENT_VALTYPE_F64 10
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a char
+#define WASMTIME_COMPONENT_VALTYPE_CHAR 11
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a string
+#define WASMTIME_COMPONENT_VALTYPE_STRING 12
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a list
+#define WASMTIME_COMPONENT_VALTYPE_LIST 13
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a record
+#define WASMTIME_COMPONENT_VALTYPE_RECORD 14
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a tuple
+#define WASMTIME_COMPONENT_VALTYPE_TUPLE 15
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a variant
+#define WASMTIME_COMPONENT_VALTYPE_VARIANT 16
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is an enum
+#define WASMTIME_COMPONENT_VALTYPE_ENUM 17
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is an option
+#define WASMTIME_COMPONENT_VALTYPE_OPTION 18
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a result
+#define WASMTIME_COMPONENT_VALTYPE_RESULT 19
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is flags
+#define WASMTIME_COMPONENT_VALTYPE_FLAGS 20
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is an owned resource
+#define WASMTIME_COMPONENT_VALTYPE_OWN 21
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a borrowed resource
+#define WASMTIME_COMPONENT_VALTYPE_BORROW 22
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a future
+#define WASMTIME_COMPONENT_VALTYPE_FUTURE 23
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is a stream
+#define WASMTIME_COMPONENT_VALTYPE_STREAM 24
+/// \brief Value of #wasmtime_component_valtype_kind_t meaning that
+/// #wasmtime_component_valtype_t is an error context
+#define WASMTIME_COMPONENT_VALTYPE_ERROR_CONTEXT 25
+
+/// \brief Payload of #wasmtime_component_valtype_t, only present for
+/// compound types
+typedef union {
+  /// Field used if #wasmtime_component_valtype_t::kind is
+  /// #WASMTIME_COMPONENT_VALTYPE_LIST
+  wasmtime_component_list_type_t *list;
+  /// Field used if #wasmtime_component_valtype_t::kind is
+  /// #WASMTIME_COMPONENT_VALTYPE_RECORD
+  wasmtime_component_record_type_t *record;
+  /// Field used if #wasmtime_component_valtype_t::kind is
+  /// #WASMTIME_COMPONENT_VALTYPE_TUPLE
+  wasmtime_component_tuple_type_t *tuple;
+  /// Field used if #wasmtime_component_valtype_t::kind is
+  /// #WASMTIME_COMPONENT_VALTYPE_VARIANT
+  wasmtime_component_variant_type_t *variant;
+  /// Field used if #wasmtime_component_valtype_t::kind is
+  /// #WASMTIME_COMPONENT_VALTYPE_ENUM
+  wasmtime_component_enum_type_t *enumeration;
+  /// Field used if #wasmtime_component_valtype_t::kind is
+  /// #WASMTIME_COMPONENT_VALTYPE_OPTION
+  wasmtime_component_option_type_t *option;
+  /// Field used if #wasmtime_component_valtype_t::kind is
+  /// #WASMTIME_COMPONENT_VALTYPE_RESULT
+  wasmtime_component_result_type_t *result;
+  /// Field used if #wasmtime_component_valtype_t::kind is
+  /// #WASMTIME_COMPONENT_VALTYPE_FLAGS
+  wasmtime_component_flags_type_t *flags;
+  /// Field used if #wasmtime_component_valtype_t::kind is
+  /// #WASMTIME_COMPONENT_VALTYPE_OWN
+  wasmtime_component_resource_type_t *own;
+  /// Field used if #wasmtime_component_valtype_t::kind is
+  /// #WASMTIME_COMPONENT_VALTYPE_BORROW
+  wasmtime_component_resource_type_t *borrow;
+  /// Field used if #wasmtime_component_valtype_t::kind is
+  /// #WASMTIME_COMPONENT_VALTYPE_FUTURE
+  wasmtime_component_future_type_t *future;
+  /// Field used if #wasmtime_component_valtype_t::kind is
+  /// #WASMTIME_COMPONENT_VALTYPE_STREAM
+  wasmtime_component_stream_type_t *stream;
+} wasmtime_component_valtype_union_t;
+
+/// \brief Represents the type of a component model value.
+///
+/// Values of this type own their payload and must be deleted with
+/// #wasmtime_component_valtype_delete.
+typedef struct wasmtime_component_valtype_t {
+  /// The type discriminant
+  wasmtime_component_valtype_kind_t kind;
+  /// Payload of type \ref kind
+  wasmtime_component_valtype_union_t of;
+} wasmtime_component_valtype_t;
+
+/// \brief Clones \p ty into \p out
+WASM_API_EXTERN void
+wasmtime_component_valtype_clone(const wasmtime_component_valtype_t *ty,
+                                 wasmtime_component_valtype_t *out);
+
+/// \brief Returns whether \p a and \p b are the same type
+WASM_API_EXTERN bool
+wasmtime_component_valtype_equal(const wasmtime_component_valtype_t *a,
+                                 const wasmtime_component_valtype_t *b);
+
+/// \brief Deletes the payload of \p ty
+WASM_API_EXTERN void
+wasmtime_component_valtype_delete(wasmtime_component_valtype_t *ty);
+
+/// \brief Discriminant used in #wasmtime_component_item_t::kind
+typedef uint8_t wasmtime_component_item_kind_t;
+
+/// \brief Value of #wasmtime_component_item_kind_t meaning that
+/// #wasmtime_component_item_t is a component function
+#define WASMTIME_COMPONENT_ITEM_COMPONENT_FUNC 0
+/// \brief Value of #wasmtime_component_item_kind_t meaning that
+/// #wasmtime_component_item_t is a core function
+#define WASMTIME_COMPONENT_ITEM_CORE_FUNC 1
+/// \brief Value of #wasmtime_component_item_kind_t meaning that
+/// #wasmtime_component_item_t is a core module
+#define WASMTIME_COMPONENT_ITEM_MODULE 2
+/// \brief Value of #wasmtime_component_item_kind_t meaning that
+/// #wasmtime_component_item_t is a component
+#define WASMTIME_COMPONENT_ITEM_COMPONENT 3
+/// \brief Value of #wasmtime_component_item_kind_t meaning that
+/// #wasmtime_component_item_t is a component instance
+#define WASMTIME_COMPONENT_ITEM_COMPONENT_INSTANCE 4
+/// \brief Value of #wasmtime_component_item_kind_t meaning that
+/// #wasmtime_component_item_t is an interface type
+#define WASMTIME_COMPONENT_ITEM_TYPE 5
+/// \brief Value of #wasmtime_component_item_kind_t meaning that
+/// #wasmtime_component_item_t is a resource
+#define WASMTIME_COMPONENT_ITEM_RESOURCE 6
+
+/// \brief Payload of #wasmtime_component_item_t
+typedef union {
+  /// Field used if #wasmtime_component_item_t::kind is
+  /// #WASMTIME_COMPONENT_ITEM_COMPONENT_FUNC
+  wasmtime_component_func_type_t *component_func;
+  /// Field used if #wasmtime_component_item_t::kind is
+  /// #WASMTIME_COMPONENT_ITEM_CORE_FUNC
+  wasm_functype_t *core_func;
+  /// Field used if #wasmtime_component_item_t::kind is
+  /// #WASMTIME_COMPONENT_ITEM_MODULE
+  wasmtime_component_module_type_t *module;
+  /// Field used if #wasmtime_component_item_t::kind is
+  /// #WASMTIME_COMPONENT_ITEM_COMPONENT
+  wasmtime_component_type_t *component;
+  /// Field used if #wasmtime_component_item_t::kind is
+  /// #WASMTIME_COMPONENT_ITEM_COMPONENT_INSTANCE
+  wasmtime_component_instance_type_t *component_instance;
+  /// Field used if #wasmtime_component_item_t::kind is
+  /// #WASMTIME_COMPONENT_ITEM_TYPE
+  wasmtime_component_valtype_t type;
+  /// Field used if #wasmtime_component_item_t::kind is
+  /// #WASMTIME_COMPONENT_ITEM_RESOURCE
+  wasmtime_component_resource_type_t *resource;
+} wasmtime_component_item_union_t;
+
+/// \brief The type of an item imported or exported by a component.
+///
+/// Values of this type own their payload and must be deleted with
+/// #wasmtime_component_item_delete.
+typedef struct wasmtime_component_item_t {
+  /// The item discriminant
+  wasmtime_component_item_kind_t kind;
+  /// Payload of kind \ref kind
+  wasmtime_component_item_union_t of;
+} wasmtime_component_item_t;
+
+/// \brief Clones \p item into \p out
+WASM_API_EXTERN void
+wasmtime_component_item_clone(const wasmtime_component_item_t *item,
+                              wasmtime_component_item_t *out);
+
+/// \brief Deletes the payload of \p item
+WASM_API_EXTERN void
+wasmtime_component_item_delete(wasmtime_component_item_t *item);
+
+/**
+ * \brief Returns the type of \p component.
+ *
+ * The returned type must be deleted with #wasmtime_component_type_delete.
+ */
+WASM_API_EXTERN wasmtime_component_type_t *
+wasmtime_component_type(const wasmtime_component_t *component);
+
+/// \brief Returns the number of imports of \p ty
+WASM_API_EXTERN size_t wasmtime_component_type_import_count(
+    const wasmtime_component_type_t *ty, const wasm_engine_t *engine);
+
+/**
+ * \brief Looks up the import \p name of \p ty.
+ *
+ * \param ty the component type to look in
+ * \param engine the engine the component was compiled with
+ * \param name the name of the import
+ * \param name_len length of \p name in bytes
+ * \param item_out if found, the type of the import, which must be deleted with
+ * #wasmtime_component_item_delete
+ * \return whether the import was found
+ */
+WASM_API_EXTERN bool wasmtime_component_type_import_get(
+    const wasmtime_component_type_t *ty, const wasm_engine_t *engine,
+    const char *name, size_t name_len, wasmtime_component_item_t *item_out);
+
+/**
+ * \brief Returns the \p nth import of \p ty.
+ *
+ * \param ty the component type to look in
+ * \param engine the engine the component was compiled with
+ * \param nth the index of the import
+ * \param name_out if found, the name of the import, which is borrowed from \p
+ * ty
+ * \param name_len_out if found, the length of \p name_out in bytes
+ * \param item_out if found, the type of the import, which must be deleted with
+ * #wasmtime_component_item_delete
+ * \return whether \p nth is in bounds
+ */
+WASM_API_EXTERN bool wasmtime_component_type_import_nth(
+    const wasmtime_component_type_t *ty, const wasm_engine_t *engine,
+    size_t nth, const char **name_out, size_t *name_len_out,
+    wasmtime_component_item_t *item_out);
+
+/// \brief Returns the number of exports of \p ty
+WASM_API_EXTERN size_t wasmtime_component_type_export_count(
+    const wasmtime_component_type_t *ty, const wasm_engine_t *engine);
+
+/// \brief Same as #wasmtime_component_type_import_get, but for exports
+WASM_API_EXTERN bool wasmtime_component_type_export_get(
+    const wasmtime_component_type_t *ty, const wasm_engine_t *engine,
+    const char *name, size_t name_len, wasmtime_component_item_t *item_out);
+
+/// \brief Same as #wasmtime_component_type_import_nth, but for exports
+WASM_API_EXTERN bool wasmtime_component_type_export_nth(
+    const wasmtime_component_type_t *ty, const wasm_engine_t *engine,
+    size_t nth, const char **name_out, size_t *name_len_out,
+    wasmtime_component_item_t *item_out);
+
+/// \brief Returns the number of exports of \p ty
+WASM_API_EXTERN size_t wasmtime_component_instance_type_export_count(
+    const wasmtime_component_instance_type_t *ty, const wasm_engine_t *engine);
+
+/// \brief Same as #wasmtime_component_type_export_get, but for component
+/// instances
+WASM_API_EXTERN bool wasmtime_component_instance_type_export_get(
+    const wasmtime_component_instance_type_t *ty, const wasm_engine_t *engine,
+    const char *name, size_t name_len, wasmtime_component_item_t *item_out);
+
+/// \brief Same as #wasmtime_component_type_export_nth, but for component
+/// instances
+WASM_API_EXTERN bool wasmtime_component_instance_type_export_nth(
+    const wasmtime_component_instance_type_t *ty, const wasm_engine_t *engine,
+    size_t nth, const char **name_out, size_t *name_len_out,
+    wasmtime_component_item_t *item_out);
+
+/// \brief Returns the imports of the core module type \p ty in \p out
+WASM_API_EXTERN void
+wasmtime_component_module_type_imports(const wasmtime_component_module_type_t *ty,
+                                       const wasm_engine_t *engine,
+                                       wasm_importtype_vec_t *out);
+
+/// \brief Returns the exports of the core module type \p ty in \p out
+WASM_API_EXTERN void
+wasmtime_component_module_type_exports(const wasmtime_component_module_type_t *ty,
+                                       const wasm_engine_t *engine,
+                                       wasm_exporttype_vec_t *out);
+
+/// \brief Returns the number of parameters of \p ty
+WASM_API_EXTERN size_t
+wasmtime_component_func_type_param_count(const wasmtime_component_func_type_t *ty);
+
+/**
+ * \brief Returns the \p nth parameter of \p ty.
+ *
+ * \param ty the function type
+ * \param nth the index of the parameter
+ * \param name_out if found, the name of the parameter, which is borrowed from
+ * \p ty
+ * \param name_len_out if found, the length of \p name_out in bytes
+ * \param type_out if found, the type of the parameter, which must be deleted
+ * with #wasmtime_component_valtype_delete
+ * \return whether \p nth is in bounds
+ */
+WASM_API_EXTERN bool wasmtime_component_func_type_param_nth(
+    const wasmtime_component_func_type_t *ty, size_t nth,
+    const char **name_out, size_t *name_len_out,
+    wasmtime_component_valtype_t *type_out);
+
+/// \brief Returns the result type of \p ty in \p type_out, if it has one
+WASM_API_EXTERN bool
+wasmtime_component_func_type_result(const wasmtime_component_func_type_t *ty,
+                                    wasmtime_component_valtype_t *type_out);
+
+/// \brief Returns the element type of the list type \p ty in \p type_out
+WASM_API_EXTERN void
+wasmtime_component_list_type_element(const wasmtime_component_list_type_t *ty,
+                                     wasmtime_component_valtype_t *type_out);
+
+/// \brief Returns the number of fields of the record type \p ty
+WASM_API_EXTERN size_t wasmtime_component_record_type_field_count(
+    const wasmtime_component_record_type_t *ty);
+
+/// \brief Returns the name and type of the \p nth field of \p ty, with the
+/// name borrowed from \p ty
+WASM_API_EXTERN bool wasmtime_component_record_type_field_nth(
+    const wasmtime_component_record_type_t *ty, size_t nth,
+    const char **name_out, size_t *name_len_out,
+    wasmtime_component_valtype_t *type_out);
+
+/// \brief Returns the number of types of the tuple type \p ty
+WASM_API_EXTERN size_t wasmtime_component_tuple_type_types_count(
+    const wasmtime_component_tuple_type_t *ty);
+
+/// \brief Returns the \p nth type of the tuple type \p ty
+WASM_API_EXTERN bool
+wasmtime_component_tuple_type_types_nth(const wasmtime_component_tuple_type_t *ty,
+                                        size_t nth,
+                                        wasmtime_component_valtype_t *type_out);
+
+/// \brief Returns the number of cases of the variant type \p ty
+WASM_API_EXTERN size_t wasmtime_component_variant_type_case_count(
+    const wasmtime_component_variant_type_t *ty);
+
+/**
+ * \brief Returns the \p nth case of the variant type \p ty.
+ *
+ * \param ty the variant type
+ * \param nth the index of the case
+ * \param name_out if found, the name of the case, which is borrowed from \p ty
+ * \param name_len_out if found, the length of \p name_out in bytes
+ * \param has_payload_out if found, whether the case has a payload
+ * \param payload_out if found and \p has_payload_out is set, the type of the
+ * payload which must be deleted with #wasmtime_component_valtype_delete
+ * \return whether \p nth is in bounds
+ */
+WASM_API_EXTERN bool wasmtime_component_variant_type_case_nth(
+    const wasmtime_component_variant_type_t *ty, size_t nth,
+    const char **name_out, size_t *name_len_out, bool *has_payload_out,
+    wasmtime_component_valtype_t *payload_out);
+
+/// \brief Returns the number of names of the enum type \p ty
+WASM_API_EXTERN size_t wasmtime_component_enum_type_names_count(
+    const wasmtime_component_enum_type_t *ty);
+
+/// \brief Returns the \p nth name of the enum type \p ty, borrowed from \p ty
+WASM_API_EXTERN bool
+wasmtime_component_enum_type_names_nth(const wasmtime_component_enum_type_t *ty,
+                                       size_t nth, const char **name_out,
+                                       size_t *name_len_out);
+
+/// \brief Returns the payload type of the option type \p ty in \p type_out
+WASM_API_EXTERN void
+wasmtime_component_option_type_ty(const wasmtime_component_option_type_t *ty,
+                                  wasmtime_component_valtype_t *type_out);
+
+/// \brief Returns the `ok` type of the result type \p ty in \p type_out, if it
+/// has one
+WASM_API_EXTERN bool
+wasmtime_component_result_type_ok(const wasmtime_component_result_type_t *ty,
+                                  wasmtime_component_valtype_t *type_out);
+
+/// \brief Returns the `err` type of the result type \p ty in \p type_out, if
+/// it has one
+WASM_API_EXTERN bool
+wasmtime_component_result_type_err(const wasmtime_component_result_type_t *ty,
+                                   wasmtime_component_valtype_t *type_out);
+
+/// \brief Returns the number of names of the flags type \p ty
+WASM_API_EXTERN size_t wasmtime_component_flags_type_names_count(
+    const wasmtime_component_flags_type_t *ty);
+
+/// \brief Returns the \p nth name of the flags type \p ty, borrowed from \p ty
+WASM_API_EXTERN bool
+wasmtime_component_flags_type_names_nth(const wasmtime_component_flags_type_t *ty,
+                                        size_t nth, const char **name_out,
+                                        size_t *name_len_out);
+
+/// \brief Returns the payload type of the future type \p ty in \p type_out, if
+/// it has one
+WASM_API_EXTERN bool
+wasmtime_component_future_type_ty(const wasmtime_component_future_type_t *ty,
+                                  wasmtime_component_valtype_t *type_out);
+
+/// \brief Returns the payload type of the stream type \p ty in \p type_out, if
+/// it has one
+WASM_API_EXTERN bool
+wasmtime_component_stream_type_ty(const wasmtime_component_stream_type_t *ty,
+                                  wasmtime_component_valtype_t *type_out);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif // WASMTIME_FEATURE_COMPONENT_MODEL
+
+#endif // WASMTIME_COMPONENT_TYPES_H
@@ -0,0 +1,550 @@
+/**
+ * \file wasmtime/component/types.hh
+ */
+
+#ifndef WASMTIME_COMPONENT_TYPES_HH
+#define WASMTIME_COMPONENT_TYPES_HH
+
+#include <wasmtime/conf.h>
+
+#ifdef WASMTIME_FEATURE_COMPONENT_MODEL
+
+#include <memory>
+#include <optional>
+#include <string_view>
+#include <utility>
+#include <vector>
+#include <wasmtime/component/resource.hh>
+#include <wasmtime/component/types.h>
+#include <wasmtime/engine.hh>
+#include <wasmtime/types/export.hh>
+#include <wasmtime/types/func.hh>
+#include <wasmtime/types/import.hh>
+
+namespace wasmtime {
+namespace component {
+
+/**
+ * \brief The type of a component model value.
+ *
+ * Names returned by the accessors of this type are borrowed from it and must
+ * not be used after it's destroyed.
+ */
+class ValType {
+  wasmtime_component_valtype_t ty;
+
+public:
+  /// Takes ownership of the raw C API representation of a value type.
+  explicit ValType(wasmtime_component_valtype_t ty) : ty(ty) {}
+
+  /// Copies another value type into this one.
+  ValType(const ValType &other) {
+    wasmtime_component_valtype_clone(&other.ty, &ty);
+  }
+  /// Copies another value type into this one.
+  ValType &operator=(const ValType &other) {
+    wasmtime_component_valtype_delete(&ty);
+    wasmtime_component_valtype_clone(&other.ty, &ty);
+    return *this;
+  }
+  /// Moves another value type into this one.
+  ValType(ValType &&other) noexcept : ty(other.ty) {
+    other.ty.kind = WASMTIME_COMPONENT_VALTYPE_BOOL;
+  }
+  /// Moves another value type into this one.
+  ValType &operator=(ValType &&other) noexcept {
+    std::swap(ty, other.ty);
+    return *this;
+  }
+  ~ValType() { wasmtime_component_valtype_delete(&ty); }
+
+  /// Returns the kind of this type, a `WASMTIME_COMPONENT_VALTYPE_*` constant.
+  wasmtime_component_valtype_kind_t kind() const { return ty.kind; }
+
+  /// Returns whether both types are the same.
+  bool operator==(const ValType &other) const {
+    return wasmtime_component_valtype_equal(&ty, &other.ty);
+  }
+  /// Returns whether the types are different.
+  bool operator!=(const ValType &other) const { return !(*this == other); }
+
+  /// Returns the raw C API representation of this type.
+  const wasmtime_component_valtype_t *capi() const { return &ty; }
+
+  /// Returns the element type of a list type.
+  ValType list_element() const {
+    wasmtime_component_valtype_t ret;
+    wasmtime_component_list_type_element(ty.of.list, &ret);
+    return ValType(ret);
+  }
+
+  /// Returns the names and types of the fields of a record type.
+  std::vector<std::pair<std::string_view, ValType>> record_fields() const {
+    std::vector<std::pair<std::string_view, ValType>> ret;
+    size_t count = wasmtime_component_record_type_field_count(ty.of.record);
+    for (size_t i = 0; i < count; i++) {
+      const char *name = nullptr;
+      size_t name_len = 0;
+      wasmtime_component_valtype_t field;
+      wasmtime_component_record_type_field_nth(ty.of.record, i, &name,
+                                               &name_len, &field);
+      ret.emplace_back(std::string_view(name, name_len), ValType(field));
+    }
+    return ret;
+  }
+
+  /// Returns the types of a tuple type.
+  std::vector<ValType> tuple_types() const {
+    std::vector<ValType> ret;
+    size_t count = wasmtime_component_tuple_type_types_count(ty.of.tuple);
+    for (size_t i = 0; i < count; i++) {
+      wasmtime_component_valtype_t elem;
+      wasmtime_component_tuple_type_types_nth(ty.of.tuple, i, &elem);
+      ret.emplace_back(elem);
+    }
+    return ret;
+  }
+
+  /// Returns the names and optional payload types of the cases of a variant
+  /// type.
+  std::vector<std::pair<std::string_view, std::optional<ValType>>>
+  variant_cases() const {
+    std::vector<std::pair<std::string_view, std::optional<ValType>>> ret;
+    size_t count = wasmtime_component_variant_type_case_count(ty.of.variant);
+    for (size_t i = 0; i < count; i++) {
+      const char *name = nullptr;
+      size_t name_len = 0;
+      bool has_payload = false;
+      wasmtime_component_valtype_t payload;
+      wasmtime_component_variant_type_case_nth(ty.of.variant, i, &name,
+                                               &name_len, &has_payload,
+                                               &payload);
+      std::optional<ValType> payload_ty;
+      if (has_payload) {
+        payload_ty.emplace(payload);
+      }
+      ret.emplace_back(std::string_view(name, name_len),
+                       std::move(payload_ty));
+    }
+    return ret;
+  }
+
+  /// Returns the names of an enum type.
+  std::vector<std::string_view> enum_names() const {
+    std::vector<std::string_view> ret;
+    size_t count = wasmtime_component_enum_type_names_count(ty.of.enumeration);
+    for (size_t i = 0; i < count; i++) {
+      const char *name = nullptr;
+      size_t name_len = 0;
+      wasmtime_component_enum_type_names_nth(ty.of.enumeration, i, &name,
+                                             &name_len);
+      ret.emplace_back(name, name_len);
+    }
+    return ret;
+  }
+
+  /// Returns the names of a flags type.
+  std::vector<std::string_view> flags_names() const {
+    std::vector<std::string_view> ret;
+    size_t count = wasmtime_component_flags_type_names_count(ty.of.flags);
+    for (size_t i = 0; i < count; i++) {
+      const char *name = nullptr;
+      size_t name_len = 0;
+      wasmtime_component_flags_type_names_nth(ty.of.flags, i, &name,
+                                              &name_len);
+      ret.emplace_back(name, name_len);
+    }
+    return ret;
+  }
+
+  /// Returns the payload type of an option type.
+  ValType option_type() const {
+    wasmtime_component_valtype_t ret;
+    wasmtime_component_option_type_ty(ty.of.option, &ret);
+    return ValType(ret);
+  }
+
+  /// Returns the `ok` type of a result type, if any.
+  std::optional<ValType> result_ok() const {
+    wasmtime_component_valtype_t ret;
+    if (wasmtime_component_result_type_ok(ty.of.result, &ret)) {
+      return ValType(ret);
+    }
+    return std::nullopt;
+  }
+
+  /// Returns the `err` type of a result type, if any.
+  std::optional<ValType> result_err() const {
+    wasmtime_component_valtype_t ret;
+    if (wasmtime_component_result_type_err(ty.of.result, &ret)) {
+      return ValType(ret);
+    }
+    return std::nullopt;
+  }
+
+  /// Returns the resource type of an `own` or `borrow` type.
+  ResourceType resource_type() const {
+    const auto *resource =
+        ty.kind == WASMTIME_COMPONENT_VALTYPE_OWN ? ty.of.own : ty.of.borrow;
+    return ResourceType(wasmtime_component_resource_type_clone(resource));
+  }
+
+  /// Returns the payload type of a future type, if any.
+  std::optional<ValType> future_type() const {
+    wasmtime_component_valtype_t ret;
+    if (wasmtime_component_future_type_ty(ty.of.future, &ret)) {
+      return ValType(ret);
+    }
+    return std::nullopt;
+  }
+
+  /// Returns the payload type of a stream type, if any.
+  std::optional<ValType> stream_type() const {
+    wasmtime_component_valtype_t ret;
+    if (wasmtime_component_stream_type_ty(ty.of.stream, &ret)) {
+      return ValType(ret);
+    }
+    return std::nullopt;
+  }
+};
+
+/**
+ * \brief The type of a component function.
+ */
+class FuncType {
+  struct deleter {
+    void operator()(wasmtime_component_func_type_t *p) const {
+      wasmtime_component_func_type_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_component_func_type_t, deleter> ptr;
+
+public:
+  /// Takes ownership of the raw C API representation of a function type.
+  explicit FuncType(wasmtime_component_func_type_t *ptr) : ptr(ptr) {}
+
+  /// Copies another function type into this one.
+  FuncType(const FuncType &other)
+      : ptr(wasmtime_component_func_type_clone(other.ptr.get())) {}
+  /// Copies another function type into this one.
+  FuncType &operator=(const FuncType &other) {
+    ptr.reset(wasmtime_component_func_type_clone(other.ptr.get()));
+    return *this;
+  }
+  ~FuncType() = default;
+  /// Moves another function type into this one.
+  FuncType(FuncType &&other) = default;
+  /// Moves another function type into this one.
+  FuncType &operator=(FuncType &&other) = default;
+
+  /// Returns the names and types of the parameters of this function, with the
+  /// names borrowed from this type.
+  std::vector<std::pair<std::string_view, ValType>> params() const {
+    std::vector<std::pair<std::string_view, ValType>> ret;
+    size_t count = wasmtime_component_func_type_param_count(ptr.get());
+    for (size_t i = 0; i < count; i++) {
+      const char *name = nullptr;
+      size_t name_len = 0;
+      wasmtime_component_valtype_t param;
+      wasmtime_component_func_type_param_nth(ptr.get(), i, &name, &name_len,
+                                             &param);
+      ret.emplace_back(std::string_view(name, name_len), ValType(param));
+    }
+    return ret;
+  }
+
+  /// Returns the result type of this function, if any.
+  std::optional<ValType> result() const {
+    wasmtime_component_valtype_t ret;
+    if (wasmtime_component_func_type_result(ptr.get(), &ret)) {
+      return ValType(ret);
+    }
+    return std::nullopt;
+  }
+};
+
+/**
+ * \brief The type of a core module imported or exported by a component.
+ */
+class ModuleType {
+  struct deleter {
+    void operator()(wasmtime_component_module_type_t *p) const {
+      wasmtime_component_module_type_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_component_module_type_t, deleter> ptr;
+
+public:
+  /// Takes ownership of the raw C API representation of a module type.
+  explicit ModuleType(wasmtime_component_module_type_t *ptr) : ptr(ptr) {}
+
+  /// Copies another module type into this one.
+  ModuleType(const ModuleType &other)
+      : ptr(wasmtime_component_module_type_clone(other.ptr.get())) {}
+  /// Copies another module type into this one.
+  ModuleType &operator=(const ModuleType &other) {
+    ptr.reset(wasmtime_component_module_type_clone(other.ptr.get()));
+    return *this;
+  }
+  ~ModuleType() = default;
+  /// Moves another module type into this one.
+  ModuleType(ModuleType &&other) = default;
+  /// Moves another module type into this one.
+  ModuleType &operator=(ModuleType &&other) = default;
+
+  /// Returns the imports of this module.
+  ImportType::List imports(const Engine &engine) const {
+    ImportType::List list;
+    wasmtime_component_module_type_imports(ptr.get(), engine.capi(),
+                                           &list.list);
+    return list;
+  }
+
+  /// Returns the exports of this module.
+  ExportType::List exports(const Engine &engine) const {
+    ExportType::List list;
+    wasmtime_component_module_type_exports(ptr.get(), engine.capi(),
+                                           &list.list);
+    return list;
+  }
+};
+
+class ComponentType;
+class InstanceType;
+
+/**
+ * \brief The type of an item imported or exported by a component.
+ */
+class Item {
+  wasmtime_component_item_t item;
+
+public:
+  /// Takes ownership of the raw C API representation of an item.
+  explicit Item(wasmtime_component_item_t item) : item(item) {}
+
+  /// Copies another item into this one.
+  Item(const Item &other) { wasmtime_component_item_clone(&other.item, &item); }
+  /// Copies another item into this one.
+  Item &operator=(const Item &other) {
+    wasmtime_component_item_delete(&item);
+    wasmtime_component_item_clone(&other.item, &item);
+    return *this;
+  }
+  /// Moves another item into this one.
+  Item(Item &&other) noexcept : item(other.item) {
+    other.item.kind = WASMTIME_COMPONENT_ITEM_TYPE;
+    other.item.of.type.kind = WASMTIME_COMPONENT_VALTYPE_BOOL;
+  }
+  /// Moves another item into this one.
+  Item &operator=(Item &&other) noexcept {
+    std::swap(item, other.item);
+    return *this;
+  }
+  ~Item() { wasmtime_component_item_delete(&item); }
+
+  /// Returns the kind of this item, a `WASMTIME_COMPONENT_ITEM_*` constant.
+  wasmtime_component_item_kind_t kind() const { return item.kind; }
+
+  /// Returns the raw C API representation of this item.
+  const wasmtime_component_item_t *capi() const { return &item; }
+
+  /// Returns the type of a component function item.
+  FuncType component_func() const {
+    return FuncType(
+        wasmtime_component_func_type_clone(item.of.component_func));
+  }
+
+  /// Returns the type of a core function item, borrowed from this item.
+  wasmtime::FuncType::Ref core_func() const { return item.of.core_func; }
+
+  /// Returns the type of a core module item.
+  ModuleType module() const {
+    return ModuleType(wasmtime_component_module_type_clone(item.of.module));
+  }
+
+  /// Returns the type of a component item.
+  ComponentType component() const;
+
+  /// Returns the type of a component instance item.
+  InstanceType component_instance() const;
+
+  /// Returns the type of an interface type item.
+  ValType type() const {
+    wasmtime_component_valtype_t ret;
+    wasmtime_component_valtype_clone(&item.of.type, &ret);
+    return ValType(ret);
+  }
+
+  /// Returns the type of a resource item.
+  ResourceType resource() const {
+    return ResourceType(
+        wasmtime_component_resource_type_clone(item.of.resource));
+  }
+};
+
+/**
+ * \brief The type of a component instance.
+ */
+class InstanceType {
+  struct deleter {
+    void operator()(wasmtime_component_instance_type_t *p) const {
+      wasmtime_component_instance_type_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_component_instance_type_t, deleter> ptr;
+
+public:
+  /// Takes ownership of the raw C API representation of an instance type.
+  explicit InstanceType(wasmtime_component_instance_type_t *ptr) : ptr(ptr) {}
+
+  /// Copies another instance type into this one.
+  InstanceType(const InstanceType &other)
+      : ptr(wasmtime_component_instance_type_clone(other.ptr.get())) {}
+  /// Copies another instance type into this one.
+  InstanceType &operator=(const InstanceType &other) {
+    ptr.reset(wasmtime_component_instance_type_clone(other.ptr.get()));
+    return *this;
+  }
+  ~InstanceType() = default;
+  /// Moves another instance type into this one.
+  InstanceType(InstanceType &&other) = default;
+  /// Moves another instance type into this one.
+  InstanceType &operator=(InstanceType &&other) = default;
+
+  /// Looks up the export `name` of this instance.
+  std::optional<Item> get_export(const Engine &engine,
+                                 std::string_view name) const {
+    wasmtime_component_item_t item;
+    if (wasmtime_component_instance_type_export_get(
+            ptr.get(), engine.capi(), name.data(), name.length(), &item)) {
+      return Item(item);
+    }
+    return std::nullopt;
+  }
+
+  /// Returns the names and types of the exports of this instance, with the
+  /// names borrowed from this type.
+  std::vector<std::pair<std::string_view, Item>>
+  exports(const Engine &engine) const {
+    std::vector<std::pair<std::string_view, Item>> ret;
+    size_t count =
+        wasmtime_component_instance_type_export_count(ptr.get(), engine.capi());
+    for (size_t i = 0; i < count; i++) {
+      const char *name = nullptr;
+      size_t name_len = 0;
+      wasmtime_component_item_t item;
+      wasmtime_component_instance_type_export_nth(
+          ptr.get(), engine.capi(), i, &name, &name_len, &item);
+      ret.emplace_back(std::string_view(name, name_len), Item(item));
+    }
+    return ret;
+  }
+};
+
+/**
+ * \brief The type of a component, listing its imports and exports.
+ */
+class ComponentType {
+  struct deleter {
+    void operator()(wasmtime_component_type_t *p) const {
+      wasmtime_component_type_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_component_type_t, deleter> ptr;
+
+  template <typename Count, typename Nth>
+  std::vector<std::pair<std::string_view, Item>>
+  items(const Engine &engine, Count count_fn, Nth nth_fn) const {
+    std::vector<std::pair<std::string_view, Item>> ret;
+    size_t count = count_fn(ptr.get(), engine.capi());
+    for (size_t i = 0; i < count; i++) {
+      const char *name = nullptr;
+      size_t name_len = 0;
+      wasmtime_component_item_t item;
+      nth_fn(ptr.get(), engine.capi(), i, &name, &name_len, &item);
+      ret.emplace_back(std::string_view(name, name_len), Item(item));
+    }
+    return ret;
+  }
+
+public:
+  /// Takes ownership of the raw C API representation of a component type.
+  explicit ComponentType(wasmtime_component_type_t *ptr) : ptr(ptr) {}
+
+  /// Returns the type of `component`.
+  static ComponentType of(const wasmtime_component_t *component) {
+    return ComponentType(wasmtime_component_type(component));
+  }
+
+  /// Copies another component type into this one.
+  ComponentType(const ComponentType &other)
+      : ptr(wasmtime_component_type_clone(other.ptr.get())) {}
+  /// Copies another component type into this one.
+  ComponentType &operator=(const ComponentType &other) {
+    ptr.reset(wasmtime_component_type_clone(other.ptr.get()));
+    return *this;
+  }
+  ~ComponentType() = default;
+  /// Moves another component type into this one.
+  ComponentType(ComponentType &&other) = default;
+  /// Moves another component type into this one.
+  ComponentType &operator=(ComponentType &&other) = default;
+
+  /// Looks up the import `name` of this component.
+  std::optional<Item> get_import(const Engine &engine,
+                                 std::string_view name) const {
+    wasmtime_component_item_t item;
+    if (wasmtime_component_type_import_get(ptr.get(), engine.capi(),
+                                           name.data(), name.length(), &item)) {
+      return Item(item);
+    }
+    return std::nullopt;
+  }
+
+  /// Looks up the export `name` of this component.
+  std::optional<Item> get_export(const Engine &engine,
+                                 std::string_view name) const {
+    wasmtime_component_item_t item;
+    if (wasmtime_component_type_export_get(ptr.get(), engine.capi(),
+                                           name.data(), name.length(), &item)) {
+      return Item(item);
+    }
+    return std::nullopt;
+  }
+
+  /// Returns the names and types of the imports of this component, with the
+  /// names borrowed from this type.
+  std::vector<std::pair<std::string_view, Item>>
+  imports(const Engine &engine) const {
+    return items(engine, wasmtime_component_type_import_count,
+                 wasmtime_component_type_import_nth);
+  }
+
+  /// Returns the names and types of the exports of this component, with the
+  /// names borrowed from this type.
+  std::vector<std::pair<std::string_view, Item>>
+  exports(const Engine &engine) const {
+    return items(engine, wasmtime_component_type_export_count,
+                 wasmtime_component_type_export_nth);
+  }
+};
+
+inline ComponentType Item::component() const {
+  return ComponentType(wasmtime_component_type_clone(item.of.component));
+}
+
+inline InstanceType Item::component_instance() const {
+  return InstanceType(
+      wasmtime_component_instance_type_clone(item.of.component_instance));
+}
+
+} // namespace component
+} // namespace wasmtime
+
+#endif // WASMTIME_FEATURE_COMPONENT_MODEL
+
+#endif // WASMTIME_COMPONENT_TYPES_HH
@@ -3,6 +3,7 @@
 #ifndef WASMTIME_COMPONENT_VAL_H
 #define WASMTIME_COMPONENT_VAL_H
 
+#include <wasmtime/component/resource.h>
 #include <wasmtime/conf.h>
 
 #ifdef WASMTIME_FEATURE_COMPONENT_MODEL
@@ -77,6 +78,9 @@ typedef uint8_t wasmtime_component_valkind_t;
 /// \brief Value of #wasmtime_component_valkind_t meaning that
 /// #wasmtime_component_val_t is flags
 #define WASMTIME_COMPONENT_FLAGS 20
+/// \brief Value of #wasmtime_component_valkind_t meaning that
+/// #wasmtime_component_val_t is a resource
+#define WASMTIME_COMPONENT_RESOURCE 21
 
 struct wasmtime_component_val;
 struct wasmtime_component_valrecord_entry;
@@ -176,6 +180,9 @@ typedef union {
   wasmtime_component_valresult_t result;
   /// Field used if #wasmtime_component_val_t::kind is #WASMTIME_COMPONENT_FLAGS
   wasmtime_component_valflags_t flags;
+  /// Field used if #wasmtime_component_val_t::kind is
+  /// #WASMTIME_COMPONENT_RESOURCE
+  wasmtime_component_resource_any_t *resource;
 } wasmtime_component_valunion_t;
 
 /// \brief Represents possible runtime values which a component function can
@@ -184,6 +184,14 @@ WASMTIME_CONFIG_PROP(void, wasm_function_references, bool)
  */
 WASMTIME_CONFIG_PROP(void, wasm_gc, bool)
 
+/**
+ * \brief Configures whether the WebAssembly exception-handling proposal is
+ * enabled.
+ *
+ * This setting is `false` by default.
+ */
+WASMTIME_CONFIG_PROP(void, wasm_exceptions, bool)
+
 /**
  * \brief Configures whether the WebAssembly SIMD proposal is
  * enabled.
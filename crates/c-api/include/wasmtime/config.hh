@@ -374,6 +374,14 @@ public:
   /// https://docs.wasmtime.dev/api/wasmtime/struct.Config.html#method.wasm_gc
   void wasm_gc(bool enable) { wasmtime_config_wasm_gc_set(ptr.get(), enable); }
 
+  /// \brief Configures whether the WebAssembly exception-handling proposal
+  /// will be enabled
+  ///
+  /// https://docs.wasmtime.dev/api/wasmtime/struct.Config.html#method.wasm_exceptions
+  void wasm_exceptions(bool enable) {
+    wasmtime_config_wasm_exceptions_set(ptr.get(), enable);
+  }
+
   /// \brief Configures whether the WebAssembly function references proposal
   /// will be enabled
   ///
@@ -56,6 +56,9 @@ public:
 
   /// \brief Returns whether this engine is using Pulley for execution.
   void is_pulley() const { wasmtime_engine_is_pulley(ptr.get()); }
+
+  /// \brief Returns the raw C API representation of this engine.
+  const wasm_engine_t *capi() const { return ptr.get(); }
 };
 
 } // namespace wasmtime
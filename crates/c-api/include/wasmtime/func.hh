@@ -625,6 +625,16 @@ inline Val::Val(std::optional<Func> func) : val{} {
 inline Val::Val(Func func) : Val(std::optional(func)) {}
 inline Val::Val(ExternRef ptr) : Val(std::optional(ptr)) {}
 inline Val::Val(AnyRef ptr) : Val(std::optional(ptr)) {}
+inline Val::Val(ExnRef ptr) : Val(std::optional(ptr)) {}
+
+inline Result<Val> ExnRef::field(Store::Context cx, size_t index) const {
+  wasmtime_val_t ret;
+  auto *error = wasmtime_exnref_field(cx.raw_context(), &val, index, &ret);
+  if (error != nullptr) {
+    return Error(error);
+  }
+  return Val(ret);
+}
 
 inline std::optional<Func> Val::funcref() const {
   if (val.kind != WASMTIME_FUNCREF) {
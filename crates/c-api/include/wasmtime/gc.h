@@ -0,0 +1,393 @@
+/**
+ * \file wasmtime/gc.h
+ *
+ * APIs for allocating and inspecting WebAssembly GC objects, namely `struct`
+ * and `array` references, from the host.
+ */
+
+#ifndef WASMTIME_GC_H
+#define WASMTIME_GC_H
+
+#include <wasm.h>
+#include <wasmtime/error.h>
+#include <wasmtime/store.h>
+#include <wasmtime/val.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/// \brief Discriminant stored in #wasmtime_field_type::kind
+typedef uint8_t wasmtime_storage_kind_t;
+/// \brief Value of #wasmtime_storage_kind_t for a packed `i8` field
+#define WASMTIME_STORAGE_KIND_I8 0
+/// \brief Value of #wasmtime_storage_kind_t for a packed `i16` field
+#define WASMTIME_STORAGE_KIND_I16 1
+/// \brief Value of #wasmtime_storage_kind_t for an `i32` field
+#define WASMTIME_STORAGE_KIND_I32 2
+/// \brief Value of #wasmtime_storage_kind_t for an `i64` field
+#define WASMTIME_STORAGE_KIND_I64 3
+/// \brief Value of #wasmtime_storage_kind_t for an `f32` field
+#define WASMTIME_STORAGE_KIND_F32 4
+/// \brief Value of #wasmtime_storage_kind_t for an `f64` field
+#define WASMTIME_STORAGE_KIND_F64 5
+/// \brief Value of #wasmtime_storage_kind_t for a `v128` field
+#define WASMTIME_STORAGE_KIND_V128 6
+/// \brief Value of #wasmtime_storage_kind_t for a `(ref null func)` field
+#define WASMTIME_STORAGE_KIND_FUNCREF 7
+/// \brief Value of #wasmtime_storage_kind_t for a `(ref null extern)` field
+#define WASMTIME_STORAGE_KIND_EXTERNREF 8
+/// \brief Value of #wasmtime_storage_kind_t for a `(ref null any)` field
+#define WASMTIME_STORAGE_KIND_ANYREF 9
+/// \brief Value of #wasmtime_storage_kind_t for a `(ref null eq)` field
+#define WASMTIME_STORAGE_KIND_EQREF 10
+/// \brief Value of #wasmtime_storage_kind_t for a `(ref null i31)` field
+#define WASMTIME_STORAGE_KIND_I31REF 11
+/// \brief Value of #wasmtime_storage_kind_t for a `(ref null struct)` field
+#define WASMTIME_STORAGE_KIND_STRUCTREF 12
+/// \brief Value of #wasmtime_storage_kind_t for a `(ref null array)` field
+#define WASMTIME_STORAGE_KIND_ARRAYREF 13
+/// \brief Value of #wasmtime_storage_kind_t for a `(ref null exn)` field
+#define WASMTIME_STORAGE_KIND_EXNREF 14
+
+/**
+ * \typedef wasmtime_field_type_t
+ * \brief Convenience alias for #wasmtime_field_type
+ *
+ * \struct wasmtime_field_type
+ * \brief The type of a struct field or of an array's elements.
+ *
+ * Reference types described by this structure are always nullable abstract
+ * reference types. Values read from fields are represented with
+ * #wasmtime_val_t where packed `i8` and `i16` fields are zero-extended to
+ * #WASMTIME_I32 and `struct`, `array` and `eq` references use #WASMTIME_ANYREF.
+ */
+typedef struct wasmtime_field_type {
+  /// The storage type of this field.
+  wasmtime_storage_kind_t kind;
+  /// Whether this field is mutable, either `WASM_CONST` or `WASM_VAR`.
+  wasm_mutability_t mutability;
+} wasmtime_field_type_t;
+
+/// \brief An opaque type representing a WebAssembly `struct` type.
+typedef struct wasmtime_struct_type wasmtime_struct_type_t;
+
+/**
+ * \brief Creates a new `struct` type with the given fields.
+ *
+ * \param engine the engine the type is registered within
+ * \param fields the fields of the struct, in order
+ * \param nfields the number of elements in `fields`
+ * \param ret on success, the new type which must be deleted with
+ * #wasmtime_struct_type_delete
+ * \return on success `NULL`, otherwise an error
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_struct_type_new(const wasm_engine_t *engine,
+                         const wasmtime_field_type_t *fields, size_t nfields,
+                         wasmtime_struct_type_t **ret);
+
+/// \brief Deletes a #wasmtime_struct_type_t.
+WASM_API_EXTERN void wasmtime_struct_type_delete(wasmtime_struct_type_t *ty);
+
+/// \brief Returns the number of fields in the struct type `ty`.
+WASM_API_EXTERN size_t
+wasmtime_struct_type_field_count(const wasmtime_struct_type_t *ty);
+
+/**
+ * \brief Returns the type of the `nth` field of `ty`.
+ *
+ * Returns `false` if `nth` is out of bounds or if the field's type cannot be
+ * described by #wasmtime_field_type_t, for example a reference to a concrete
+ * type.
+ */
+WASM_API_EXTERN bool
+wasmtime_struct_type_field_nth(const wasmtime_struct_type_t *ty, size_t nth,
+                               wasmtime_field_type_t *ret);
+
+/// \brief An opaque type representing a WebAssembly `array` type.
+typedef struct wasmtime_array_type wasmtime_array_type_t;
+
+/**
+ * \brief Creates a new `array` type whose elements are of type `field`.
+ *
+ * \param engine the engine the type is registered within
+ * \param field the type of the array's elements
+ * \param ret on success, the new type which must be deleted with
+ * #wasmtime_array_type_delete
+ * \return on success `NULL`, otherwise an error
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_array_type_new(const wasm_engine_t *engine,
+                        const wasmtime_field_type_t *field,
+                        wasmtime_array_type_t **ret);
+
+/// \brief Deletes a #wasmtime_array_type_t.
+WASM_API_EXTERN void wasmtime_array_type_delete(wasmtime_array_type_t *ty);
+
+/**
+ * \brief Returns the type of the elements of `ty`.
+ *
+ * Returns `false` if the type cannot be described by #wasmtime_field_type_t.
+ */
+WASM_API_EXTERN bool
+wasmtime_array_type_field(const wasmtime_array_type_t *ty,
+                          wasmtime_field_type_t *ret);
+
+/**
+ * \brief An allocator for `struct`s of a particular type within a store.
+ *
+ * Creating an allocator performs the type registration work for allocation up
+ * front, so it should be reused for many allocations of the same type.
+ */
+typedef struct wasmtime_structref_pre wasmtime_structref_pre_t;
+
+/// \brief Creates a new allocator for `struct`s of type `ty` within `context`.
+WASM_API_EXTERN wasmtime_structref_pre_t *
+wasmtime_structref_pre_new(wasmtime_context_t *context,
+                           const wasmtime_struct_type_t *ty);
+
+/// \brief Deletes a #wasmtime_structref_pre_t.
+WASM_API_EXTERN void
+wasmtime_structref_pre_delete(wasmtime_structref_pre_t *pre);
+
+/**
+ * \brief An allocator for `array`s of a particular type within a store.
+ *
+ * See #wasmtime_structref_pre_t for more information.
+ */
+typedef struct wasmtime_arrayref_pre wasmtime_arrayref_pre_t;
+
+/// \brief Creates a new allocator for `array`s of type `ty` within `context`.
+WASM_API_EXTERN wasmtime_arrayref_pre_t *
+wasmtime_arrayref_pre_new(wasmtime_context_t *context,
+                          const wasmtime_array_type_t *ty);
+
+/// \brief Deletes a #wasmtime_arrayref_pre_t.
+WASM_API_EXTERN void wasmtime_arrayref_pre_delete(wasmtime_arrayref_pre_t *pre);
+
+/**
+ * \typedef wasmtime_structref_t
+ * \brief Convenience alias for #wasmtime_structref
+ *
+ * \struct wasmtime_structref
+ * \brief A reference to a WebAssembly `struct`.
+ *
+ * Like #wasmtime_anyref_t this points back into a #wasmtime_context_t and must
+ * be explicitly unrooted via #wasmtime_structref_unroot to enable garbage
+ * collection. Null is represented with this structure and created with
+ * `wasmtime_structref_set_null`.
+ */
+typedef struct wasmtime_structref {
+  /// Internal metadata tracking within the store, embedders should not
+  /// configure or modify these fields.
+  uint64_t store_id;
+  /// Internal to Wasmtime.
+  uint32_t __private1;
+  /// Internal to Wasmtime.
+  uint32_t __private2;
+  /// Internal to Wasmtime.
+  void *__private3;
+} wasmtime_structref_t;
+
+/// \brief Helper function to initialize the `ref` provided to a null
+/// structref value.
+static inline void wasmtime_structref_set_null(wasmtime_structref_t *ref) {
+  ref->store_id = 0;
+}
+
+/// \brief Helper function to return whether the provided `ref` points to a null
+/// `structref` value.
+static inline bool wasmtime_structref_is_null(const wasmtime_structref_t *ref) {
+  return ref->store_id == 0;
+}
+
+/**
+ * \brief Allocates a new `struct` with the allocator `pre`.
+ *
+ * \param context the store to allocate within, which must be the one `pre`
+ * was created with
+ * \param pre the allocator for the struct's type
+ * \param fields the initial values of the struct's fields, in order
+ * \param nfields the number of elements in `fields`
+ * \param out on success, the new struct which must be unrooted with
+ * #wasmtime_structref_unroot
+ * \return on success `NULL`, otherwise an error, for example if `fields` don't
+ * match the struct's type or the GC heap is out of memory
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_structref_new(wasmtime_context_t *context,
+                       const wasmtime_structref_pre_t *pre,
+                       const wasmtime_val_t *fields, size_t nfields,
+                       wasmtime_structref_t *out);
+
+/**
+ * \brief Creates a new reference pointing to the same struct that `ref`
+ * points to, which must be unrooted with #wasmtime_structref_unroot.
+ */
+WASM_API_EXTERN void wasmtime_structref_clone(wasmtime_context_t *context,
+                                              const wasmtime_structref_t *ref,
+                                              wasmtime_structref_t *out);
+
+/// \brief Unroots the `ref` provided within the `context`.
+WASM_API_EXTERN void wasmtime_structref_unroot(wasmtime_context_t *context,
+                                               wasmtime_structref_t *ref);
+
+/**
+ * \brief Upcasts `ref` into a new #wasmtime_anyref_t root, which must be
+ * unrooted with #wasmtime_anyref_unroot.
+ */
+WASM_API_EXTERN void wasmtime_structref_to_anyref(
+    wasmtime_context_t *context, const wasmtime_structref_t *ref,
+    wasmtime_anyref_t *out);
+
+/**
+ * \brief Downcasts `anyref` to a struct, if it is one.
+ *
+ * Returns `false` if `anyref` is null or not a struct. Otherwise `out` is
+ * filled in and must be unrooted with #wasmtime_structref_unroot.
+ */
+WASM_API_EXTERN bool
+wasmtime_anyref_as_structref(wasmtime_context_t *context,
+                             const wasmtime_anyref_t *anyref,
+                             wasmtime_structref_t *out);
+
+/**
+ * \brief Reads field `index` of the struct `ref`.
+ *
+ * On success `out` is filled in and must be unrooted with
+ * #wasmtime_val_unroot. Returns an error if `ref` is null or `index` is out of
+ * bounds.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_structref_field(wasmtime_context_t *context,
+                         const wasmtime_structref_t *ref, size_t index,
+                         wasmtime_val_t *out);
+
+/**
+ * \brief Writes `val` into field `index` of the struct `ref`.
+ *
+ * Returns an error if `ref` is null, `index` is out of bounds, the field is
+ * immutable or `val` doesn't match the field's type.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_structref_set_field(wasmtime_context_t *context,
+                             const wasmtime_structref_t *ref, size_t index,
+                             const wasmtime_val_t *val);
+
+/**
+ * \typedef wasmtime_arrayref_t
+ * \brief Convenience alias for #wasmtime_arrayref
+ *
+ * \struct wasmtime_arrayref
+ * \brief A reference to a WebAssembly `array`.
+ *
+ * See #wasmtime_structref_t for more information about rooting. Null is
+ * represented with this structure and created with
+ * `wasmtime_arrayref_set_null`.
+ */
+typedef struct wasmtime_arrayref {
+  /// Internal metadata tracking within the store, embedders should not
+  /// configure or modify these fields.
+  uint64_t store_id;
+  /// Internal to Wasmtime.
+  uint32_t __private1;
+  /// Internal to Wasmtime.
+  uint32_t __private2;
+  /// Internal to Wasmtime.
+  void *__private3;
+} wasmtime_arrayref_t;
+
+/// \brief Helper function to initialize the `ref` provided to a null arrayref
+/// value.
+static inline void wasmtime_arrayref_set_null(wasmtime_arrayref_t *ref) {
+  ref->store_id = 0;
+}
+
+/// \brief Helper function to return whether the provided `ref` points to a null
+/// `arrayref` value.
+static inline bool wasmtime_arrayref_is_null(const wasmtime_arrayref_t *ref) {
+  return ref->store_id == 0;
+}
+
+/**
+ * \brief Allocates a new `array` of `len` copies of `elem` with the allocator
+ * `pre`.
+ *
+ * On success `out` is filled in and must be unrooted with
+ * #wasmtime_arrayref_unroot. Returns an error if `elem` doesn't match the
+ * array's element type or the GC heap is out of memory.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_arrayref_new(wasmtime_context_t *context,
+                      const wasmtime_arrayref_pre_t *pre,
+                      const wasmtime_val_t *elem, uint32_t len,
+                      wasmtime_arrayref_t *out);
+
+/**
+ * \brief Creates a new reference pointing to the same array that `ref`
+ * points to, which must be unrooted with #wasmtime_arrayref_unroot.
+ */
+WASM_API_EXTERN void wasmtime_arrayref_clone(wasmtime_context_t *context,
+                                             const wasmtime_arrayref_t *ref,
+                                             wasmtime_arrayref_t *out);
+
+/// \brief Unroots the `ref` provided within the `context`.
+WASM_API_EXTERN void wasmtime_arrayref_unroot(wasmtime_context_t *context,
+                                              wasmtime_arrayref_t *ref);
+
+/**
+ * \brief Upcasts `ref` into a new #wasmtime_anyref_t root, which must be
+ * unrooted with #wasmtime_anyref_unroot.
+ */
+WASM_API_EXTERN void wasmtime_arrayref_to_anyref(wasmtime_context_t *context,
+                                                 const wasmtime_arrayref_t *ref,
+                                                 wasmtime_anyref_t *out);
+
+/**
+ * \brief Downcasts `anyref` to an array, if it is one.
+ *
+ * Returns `false` if `anyref` is null or not an array. Otherwise `out` is
+ * filled in and must be unrooted with #wasmtime_arrayref_unroot.
+ */
+WASM_API_EXTERN bool wasmtime_anyref_as_arrayref(wasmtime_context_t *context,
+                                                 const wasmtime_anyref_t *anyref,
+                                                 wasmtime_arrayref_t *out);
+
+/**
+ * \brief Returns the length of the array `ref`.
+ *
+ * Returns an error if `ref` is null.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_arrayref_len(wasmtime_context_t *context,
+                      const wasmtime_arrayref_t *ref, uint32_t *out);
+
+/**
+ * \brief Reads element `index` of the array `ref`.
+ *
+ * On success `out` is filled in and must be unrooted with
+ * #wasmtime_val_unroot. Returns an error if `ref` is null or `index` is out of
+ * bounds.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_arrayref_get(wasmtime_context_t *context,
+                      const wasmtime_arrayref_t *ref, uint32_t index,
+                      wasmtime_val_t *out);
+
+/**
+ * \brief Writes `val` into element `index` of the array `ref`.
+ *
+ * Returns an error if `ref` is null, `index` is out of bounds, the array is
+ * immutable or `val` doesn't match the array's element type.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_arrayref_set(wasmtime_context_t *context,
+                      const wasmtime_arrayref_t *ref, uint32_t index,
+                      const wasmtime_val_t *val);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif // WASMTIME_GC_H
@@ -0,0 +1,398 @@
+/**
+ * \file wasmtime/gc.hh
+ */
+
+#ifndef WASMTIME_GC_HH
+#define WASMTIME_GC_HH
+
+#include <memory>
+#include <optional>
+#include <vector>
+#include <wasmtime/engine.hh>
+#include <wasmtime/error.hh>
+#include <wasmtime/gc.h>
+#include <wasmtime/store.hh>
+#include <wasmtime/val.hh>
+
+namespace wasmtime {
+
+/// Different kinds of storage types of struct fields and array elements.
+enum class StorageKind {
+  /// A packed 8-bit integer
+  I8 = WASMTIME_STORAGE_KIND_I8,
+  /// A packed 16-bit integer
+  I16 = WASMTIME_STORAGE_KIND_I16,
+  /// WebAssembly's `i32` type
+  I32 = WASMTIME_STORAGE_KIND_I32,
+  /// WebAssembly's `i64` type
+  I64 = WASMTIME_STORAGE_KIND_I64,
+  /// WebAssembly's `f32` type
+  F32 = WASMTIME_STORAGE_KIND_F32,
+  /// WebAssembly's `f64` type
+  F64 = WASMTIME_STORAGE_KIND_F64,
+  /// WebAssembly's `v128` type
+  V128 = WASMTIME_STORAGE_KIND_V128,
+  /// WebAssembly's `(ref null func)` type
+  FuncRef = WASMTIME_STORAGE_KIND_FUNCREF,
+  /// WebAssembly's `(ref null extern)` type
+  ExternRef = WASMTIME_STORAGE_KIND_EXTERNREF,
+  /// WebAssembly's `(ref null any)` type
+  AnyRef = WASMTIME_STORAGE_KIND_ANYREF,
+  /// WebAssembly's `(ref null eq)` type
+  EqRef = WASMTIME_STORAGE_KIND_EQREF,
+  /// WebAssembly's `(ref null i31)` type
+  I31Ref = WASMTIME_STORAGE_KIND_I31REF,
+  /// WebAssembly's `(ref null struct)` type
+  StructRef = WASMTIME_STORAGE_KIND_STRUCTREF,
+  /// WebAssembly's `(ref null array)` type
+  ArrayRef = WASMTIME_STORAGE_KIND_ARRAYREF,
+  /// WebAssembly's `(ref null exn)` type
+  ExnRef = WASMTIME_STORAGE_KIND_EXNREF,
+};
+
+/**
+ * \brief The type of a struct field or of an array's elements.
+ */
+class FieldType {
+  wasmtime_field_type_t ty;
+
+public:
+  /// Creates a new field type from its C API representation.
+  FieldType(wasmtime_field_type_t ty) : ty(ty) {}
+
+  /// Creates a new field of the storage kind `kind`.
+  FieldType(StorageKind kind, bool mutable_ = false) : ty{} {
+    ty.kind = static_cast<wasmtime_storage_kind_t>(kind);
+    ty.mutability = mutable_ ? WASM_VAR : WASM_CONST;
+  }
+
+  /// Returns the storage kind of this field.
+  StorageKind kind() const { return static_cast<StorageKind>(ty.kind); }
+
+  /// Returns whether this field is mutable.
+  bool is_mutable() const { return ty.mutability == WASM_VAR; }
+
+  /// Returns the raw C API representation of this field type.
+  const wasmtime_field_type_t *capi() const { return &ty; }
+};
+
+/**
+ * \brief Type information about a WebAssembly `struct`.
+ */
+class StructType {
+  struct deleter {
+    void operator()(wasmtime_struct_type_t *p) const {
+      wasmtime_struct_type_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_struct_type_t, deleter> ptr;
+
+  StructType(wasmtime_struct_type_t *ptr) : ptr(ptr) {}
+
+public:
+  /// Creates a new struct type with the `fields` provided within `engine`.
+  static Result<StructType> create(const Engine &engine,
+                                   const std::vector<FieldType> &fields) {
+    std::vector<wasmtime_field_type_t> raw;
+    raw.reserve(fields.size());
+    for (const auto &field : fields) {
+      raw.push_back(*field.capi());
+    }
+    wasmtime_struct_type_t *ret = nullptr;
+    auto *error =
+        wasmtime_struct_type_new(engine.capi(), raw.data(), raw.size(), &ret);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return StructType(ret);
+  }
+
+  /// Returns the number of fields of this struct type.
+  size_t field_count() const {
+    return wasmtime_struct_type_field_count(ptr.get());
+  }
+
+  /// Returns the type of the `nth` field, if it's in bounds and representable
+  /// as a `FieldType`.
+  std::optional<FieldType> field(size_t nth) const {
+    wasmtime_field_type_t ret;
+    if (wasmtime_struct_type_field_nth(ptr.get(), nth, &ret)) {
+      return FieldType(ret);
+    }
+    return std::nullopt;
+  }
+
+  /// Returns the raw C API representation of this type.
+  const wasmtime_struct_type_t *capi() const { return ptr.get(); }
+};
+
+/**
+ * \brief Type information about a WebAssembly `array`.
+ */
+class ArrayType {
+  struct deleter {
+    void operator()(wasmtime_array_type_t *p) const {
+      wasmtime_array_type_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_array_type_t, deleter> ptr;
+
+  ArrayType(wasmtime_array_type_t *ptr) : ptr(ptr) {}
+
+public:
+  /// Creates a new array type with elements of type `field` within `engine`.
+  static Result<ArrayType> create(const Engine &engine,
+                                  const FieldType &field) {
+    wasmtime_array_type_t *ret = nullptr;
+    auto *error = wasmtime_array_type_new(engine.capi(), field.capi(), &ret);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return ArrayType(ret);
+  }
+
+  /// Returns the type of this array's elements, if representable as a
+  /// `FieldType`.
+  std::optional<FieldType> field() const {
+    wasmtime_field_type_t ret;
+    if (wasmtime_array_type_field(ptr.get(), &ret)) {
+      return FieldType(ret);
+    }
+    return std::nullopt;
+  }
+
+  /// Returns the raw C API representation of this type.
+  const wasmtime_array_type_t *capi() const { return ptr.get(); }
+};
+
+/**
+ * \brief An allocator for `struct`s of a particular type within a store.
+ */
+class StructRefPre {
+  struct deleter {
+    void operator()(wasmtime_structref_pre_t *p) const {
+      wasmtime_structref_pre_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_structref_pre_t, deleter> ptr;
+
+public:
+  /// Creates a new allocator for structs of type `ty` within `cx`.
+  StructRefPre(Store::Context cx, const StructType &ty)
+      : ptr(wasmtime_structref_pre_new(cx.raw_context(), ty.capi())) {}
+
+  /// Returns the raw C API representation of this allocator.
+  const wasmtime_structref_pre_t *capi() const { return ptr.get(); }
+};
+
+/**
+ * \brief An allocator for `array`s of a particular type within a store.
+ */
+class ArrayRefPre {
+  struct deleter {
+    void operator()(wasmtime_arrayref_pre_t *p) const {
+      wasmtime_arrayref_pre_delete(p);
+    }
+  };
+
+  std::unique_ptr<wasmtime_arrayref_pre_t, deleter> ptr;
+
+public:
+  /// Creates a new allocator for arrays of type `ty` within `cx`.
+  ArrayRefPre(Store::Context cx, const ArrayType &ty)
+      : ptr(wasmtime_arrayref_pre_new(cx.raw_context(), ty.capi())) {}
+
+  /// Returns the raw C API representation of this allocator.
+  const wasmtime_arrayref_pre_t *capi() const { return ptr.get(); }
+};
+
+/**
+ * \brief Representation of a WebAssembly `structref` value.
+ *
+ * Note that `StructRef` values are rooted within a `Store` and must be
+ * manually unrooted via the `unroot` function.
+ */
+class StructRef {
+  wasmtime_structref_t val;
+
+public:
+  /// Creates a new `StructRef` directly from its C-API representation.
+  explicit StructRef(wasmtime_structref_t val) : val(val) {}
+
+  /// Allocates a new struct with `pre` whose fields are initialized to
+  /// `fields`.
+  static Result<StructRef> create(Store::Context cx, const StructRefPre &pre,
+                                  const std::vector<Val> &fields) {
+    std::vector<wasmtime_val_t> raw;
+    raw.reserve(fields.size());
+    for (const auto &field : fields) {
+      raw.push_back(field.val);
+    }
+    wasmtime_structref_t ret;
+    auto *error = wasmtime_structref_new(cx.raw_context(), pre.capi(),
+                                         raw.data(), raw.size(), &ret);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return StructRef(ret);
+  }
+
+  /// Returns the struct that `anyref` points to, if it's a struct.
+  static std::optional<StructRef> from_anyref(Store::Context cx,
+                                              const AnyRef &anyref) {
+    wasmtime_structref_t ret;
+    if (wasmtime_anyref_as_structref(cx.raw_context(), anyref.raw(), &ret)) {
+      return StructRef(ret);
+    }
+    return std::nullopt;
+  }
+
+  /// Creates a new `StructRef` which is separately rooted from this one.
+  StructRef clone(Store::Context cx) {
+    wasmtime_structref_t other;
+    wasmtime_structref_clone(cx.raw_context(), &val, &other);
+    return StructRef(other);
+  }
+
+  /// Returns a new `AnyRef` root pointing to this struct.
+  AnyRef to_anyref(Store::Context cx) const {
+    wasmtime_anyref_t ret;
+    wasmtime_structref_to_anyref(cx.raw_context(), &val, &ret);
+    return AnyRef(ret);
+  }
+
+  /// Reads field `index` of this struct.
+  ///
+  /// The returned value must be unrooted with `Val::unroot`.
+  Result<Val> field(Store::Context cx, size_t index) const {
+    wasmtime_val_t ret;
+    auto *error = wasmtime_structref_field(cx.raw_context(), &val, index, &ret);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return Val(ret);
+  }
+
+  /// Writes `value` into field `index` of this struct.
+  Result<std::monostate> set_field(Store::Context cx, size_t index,
+                                   const Val &value) const {
+    auto *error = wasmtime_structref_set_field(cx.raw_context(), &val, index,
+                                               &value.val);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return std::monostate();
+  }
+
+  /// Unroots this value from the context provided, enabling a future GC to
+  /// collect the internal object if there are no more references.
+  void unroot(Store::Context cx) {
+    wasmtime_structref_unroot(cx.raw_context(), &val);
+  }
+
+  /// Returns the raw underlying C API value.
+  ///
+  /// This class still retains ownership of the pointer.
+  const wasmtime_structref_t *raw() const { return &val; }
+};
+
+/**
+ * \brief Representation of a WebAssembly `arrayref` value.
+ *
+ * Note that `ArrayRef` values are rooted within a `Store` and must be
+ * manually unrooted via the `unroot` function.
+ */
+class ArrayRef {
+  wasmtime_arrayref_t val;
+
+public:
+  /// Creates a new `ArrayRef` directly from its C-API representation.
+  explicit ArrayRef(wasmtime_arrayref_t val) : val(val) {}
+
+  /// Allocates a new array with `pre` containing `len` copies of `elem`.
+  static Result<ArrayRef> create(Store::Context cx, const ArrayRefPre &pre,
+                                 const Val &elem, uint32_t len) {
+    wasmtime_arrayref_t ret;
+    auto *error = wasmtime_arrayref_new(cx.raw_context(), pre.capi(),
+                                        &elem.val, len, &ret);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return ArrayRef(ret);
+  }
+
+  /// Returns the array that `anyref` points to, if it's an array.
+  static std::optional<ArrayRef> from_anyref(Store::Context cx,
+                                             const AnyRef &anyref) {
+    wasmtime_arrayref_t ret;
+    if (wasmtime_anyref_as_arrayref(cx.raw_context(), anyref.raw(), &ret)) {
+      return ArrayRef(ret);
+    }
+    return std::nullopt;
+  }
+
+  /// Creates a new `ArrayRef` which is separately rooted from this one.
+  ArrayRef clone(Store::Context cx) {
+    wasmtime_arrayref_t other;
+    wasmtime_arrayref_clone(cx.raw_context(), &val, &other);
+    return ArrayRef(other);
+  }
+
+  /// Returns a new `AnyRef` root pointing to this array.
+  AnyRef to_anyref(Store::Context cx) const {
+    wasmtime_anyref_t ret;
+    wasmtime_arrayref_to_anyref(cx.raw_context(), &val, &ret);
+    return AnyRef(ret);
+  }
+
+  /// Returns the length of this array.
+  Result<uint32_t> len(Store::Context cx) const {
+    uint32_t ret = 0;
+    auto *error = wasmtime_arrayref_len(cx.raw_context(), &val, &ret);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return ret;
+  }
+
+  /// Reads element `index` of this array.
+  ///
+  /// The returned value must be unrooted with `Val::unroot`.
+  Result<Val> get(Store::Context cx, uint32_t index) const {
+    wasmtime_val_t ret;
+    auto *error = wasmtime_arrayref_get(cx.raw_context(), &val, index, &ret);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return Val(ret);
+  }
+
+  /// Writes `value` into element `index` of this array.
+  Result<std::monostate> set(Store::Context cx, uint32_t index,
+                             const Val &value) const {
+    auto *error =
+        wasmtime_arrayref_set(cx.raw_context(), &val, index, &value.val);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return std::monostate();
+  }
+
+  /// Unroots this value from the context provided, enabling a future GC to
+  /// collect the internal object if there are no more references.
+  void unroot(Store::Context cx) {
+    wasmtime_arrayref_unroot(cx.raw_context(), &val);
+  }
+
+  /// Returns the raw underlying C API value.
+  ///
+  /// This class still retains ownership of the pointer.
+  const wasmtime_arrayref_t *raw() const { return &val; }
+};
+
+} // namespace wasmtime
+
+#endif // WASMTIME_GC_HH
@@ -18,6 +18,10 @@ namespace wasmtime {
 
 class Caller;
 
+namespace component {
+class ResourceType;
+} // namespace component
+
 /**
  * \brief Owner of all WebAssembly objects
  *
@@ -68,6 +72,7 @@ public:
     friend class AnyRef;
     friend class Val;
     friend class Store;
+    friend class component::ResourceType;
     wasmtime_context_t *ptr;
 
     Context(wasmtime_context_t *ptr) : ptr(ptr) {}
@@ -10,6 +10,10 @@
 
 namespace wasmtime {
 
+namespace component {
+class ModuleType;
+} // namespace component
+
 /**
  * \brief Type information about a WebAssembly export
  */
@@ -40,6 +44,7 @@ public:
   /// An owned list of `ExportType` instances.
   class List {
     friend class Module;
+    friend class component::ModuleType;
     wasm_exporttype_vec_t list;
 
   public:
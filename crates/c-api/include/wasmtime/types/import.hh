@@ -10,6 +10,10 @@
 
 namespace wasmtime {
 
+namespace component {
+class ModuleType;
+} // namespace component
+
 /**
  * \brief Type information about a WebAssembly import.
  */
@@ -45,6 +49,7 @@ public:
   /// An owned list of `ImportType` instances.
   class List {
     friend class Module;
+    friend class component::ModuleType;
     wasm_importtype_vec_t list;
 
   public:
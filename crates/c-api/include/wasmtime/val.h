@@ -292,6 +292,106 @@ WASM_API_EXTERN void wasmtime_externref_from_raw(wasmtime_context_t *context,
 WASM_API_EXTERN uint32_t wasmtime_externref_to_raw(
     wasmtime_context_t *context, const wasmtime_externref_t *ref);
 
+/**
+ * \typedef wasmtime_exnref_t
+ * \brief Convenience alias for #wasmtime_exnref
+ *
+ * \struct wasmtime_exnref
+ * \brief A reference to a WebAssembly exception object.
+ *
+ * This structure represents an `exnref` from the exceptions proposal, for
+ * example the payload of an exception thrown by WebAssembly. Like
+ * #wasmtime_anyref_t it points back into a #wasmtime_context_t and must be
+ * explicitly unrooted via #wasmtime_exnref_unroot to enable garbage
+ * collection.
+ *
+ * Note that null is represented with this structure and created with
+ * `wasmtime_exnref_set_null`. Null can be tested for with the
+ * `wasmtime_exnref_is_null` function.
+ */
+typedef struct wasmtime_exnref {
+  /// Internal metadata tracking within the store, embedders should not
+  /// configure or modify these fields.
+  uint64_t store_id;
+  /// Internal to Wasmtime.
+  uint32_t __private1;
+  /// Internal to Wasmtime.
+  uint32_t __private2;
+  /// Internal to Wasmtime.
+  void *__private3;
+} wasmtime_exnref_t;
+
+/// \brief Helper function to initialize the `ref` provided to a null exnref
+/// value.
+static inline void wasmtime_exnref_set_null(wasmtime_exnref_t *ref) {
+  ref->store_id = 0;
+}
+
+/// \brief Helper function to return whether the provided `ref` points to a null
+/// `exnref` value.
+///
+/// Note that `ref` itself should not be null as null is represented internally
+/// within a #wasmtime_exnref_t value.
+static inline bool wasmtime_exnref_is_null(const wasmtime_exnref_t *ref) {
+  return ref->store_id == 0;
+}
+
+/**
+ * \brief Creates a new reference pointing to the same exception that `ref`
+ * points to.
+ *
+ * The `out` parameter stores the result and must eventually be unrooted with
+ * #wasmtime_exnref_unroot.
+ */
+WASM_API_EXTERN void wasmtime_exnref_clone(wasmtime_context_t *context,
+                                           const wasmtime_exnref_t *ref,
+                                           wasmtime_exnref_t *out);
+
+/**
+ * \brief Unroots the `ref` provided within the `context`.
+ *
+ * This API is required to enable the `ref` value provided to be
+ * garbage-collected. This API itself does not necessarily garbage-collect the
+ * value, but it's possible to collect it in the future after this.
+ *
+ * This may be called on null exnref values, but it's not required.
+ */
+WASM_API_EXTERN void wasmtime_exnref_unroot(wasmtime_context_t *context,
+                                            wasmtime_exnref_t *ref);
+
+/**
+ * \brief Converts a raw `exnref` value coming from #wasmtime_val_raw_t into
+ * a #wasmtime_exnref_t.
+ *
+ * The `out` reference is filled in with the non-raw version of this exnref.
+ * It must eventually be unrooted with #wasmtime_exnref_unroot.
+ */
+WASM_API_EXTERN void wasmtime_exnref_from_raw(wasmtime_context_t *context,
+                                              uint32_t raw,
+                                              wasmtime_exnref_t *out);
+
+/**
+ * \brief Converts a #wasmtime_exnref_t to a raw value suitable for storing
+ * into a #wasmtime_val_raw_t.
+ *
+ * Note that the returned underlying value is not tracked by Wasmtime's garbage
+ * collector until it enters WebAssembly. This means that a GC may release the
+ * context's reference to the raw value, making the raw value invalid within
+ * the context of the store. Do not perform a GC between calling this function
+ * and passing it to WebAssembly.
+ */
+WASM_API_EXTERN uint32_t wasmtime_exnref_to_raw(wasmtime_context_t *context,
+                                                const wasmtime_exnref_t *ref);
+
+/**
+ * \brief Returns the number of payload fields of the exception `ref`.
+ *
+ * Returns an error if `ref` is null.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_exnref_field_count(wasmtime_context_t *context,
+                            const wasmtime_exnref_t *ref, size_t *out);
+
 /// \brief Discriminant stored in #wasmtime_val::kind
 typedef uint8_t wasmtime_valkind_t;
 /// \brief Value of #wasmtime_valkind_t meaning that #wasmtime_val_t is an i32
@@ -313,6 +413,9 @@ typedef uint8_t wasmtime_valkind_t;
 /// \brief Value of #wasmtime_valkind_t meaning that #wasmtime_val_t is an
 /// anyref
 #define WASMTIME_ANYREF 7
+/// \brief Value of #wasmtime_valkind_t meaning that #wasmtime_val_t is an
+/// exnref
+#define WASMTIME_EXNREF 8
 
 /// \brief A 128-bit value representing the WebAssembly `v128` type. Bytes are
 /// stored in little-endian order.
@@ -341,6 +444,8 @@ typedef union wasmtime_valunion {
   wasmtime_anyref_t anyref;
   /// Field used if #wasmtime_val_t::kind is #WASMTIME_EXTERNREF
   wasmtime_externref_t externref;
+  /// Field used if #wasmtime_val_t::kind is #WASMTIME_EXNREF
+  wasmtime_exnref_t exnref;
   /// Field used if #wasmtime_val_t::kind is #WASMTIME_FUNCREF
   ///
   /// Use `wasmtime_funcref_is_null` to test whether this is a null function
@@ -418,6 +523,14 @@ typedef union wasmtime_val_raw {
   ///
   /// Note that this field is always stored in a little-endian format.
   uint32_t externref;
+  /// Field for when this val is a WebAssembly `exnref` value.
+  ///
+  /// If this is set to 0 then it's a null exnref, otherwise this must be
+  /// passed to `wasmtime_exnref_from_raw` to determine the
+  /// `wasmtime_exnref_t`.
+  ///
+  /// Note that this field is always stored in a little-endian format.
+  uint32_t exnref;
   /// Field for when this val is a WebAssembly `funcref` value.
   ///
   /// If this is set to 0 then it's a null funcref, otherwise this must be
@@ -468,7 +581,8 @@ typedef struct wasmtime_val {
  * \brief Unroot the value contained by `val`.
  *
  * This function will unroot any GC references that `val` points to, for
- * example if it has the `WASMTIME_EXTERNREF` or `WASMTIME_ANYREF` kinds. This
+ * example if it has the `WASMTIME_EXTERNREF`, `WASMTIME_ANYREF` or
+ * `WASMTIME_EXNREF` kinds. This
  * function leaves `val` in an undefined state and it should not be used again
  * without re-initializing.
  *
@@ -489,6 +603,17 @@ WASM_API_EXTERN void wasmtime_val_clone(wasmtime_context_t *context,
                                         const wasmtime_val_t *src,
                                         wasmtime_val_t *dst);
 
+/**
+ * \brief Reads the payload field `index` of the exception `ref`.
+ *
+ * On success `out` is filled in and must be unrooted with
+ * #wasmtime_val_unroot. Returns an error if `ref` is null or `index` is out of
+ * bounds.
+ */
+WASM_API_EXTERN wasmtime_error_t *
+wasmtime_exnref_field(wasmtime_context_t *context, const wasmtime_exnref_t *ref,
+                      size_t index, wasmtime_val_t *out);
+
 #ifdef __cplusplus
 } // extern "C"
 #endif
@@ -127,6 +127,58 @@ public:
   }
 };
 
+class Val;
+
+/**
+ * \brief Representation of a WebAssembly `exnref` value.
+ *
+ * This is a reference to an exception object, for example the payload of an
+ * exception thrown by WebAssembly. Like `AnyRef` it must be manually unrooted
+ * via the `unroot` function.
+ */
+class ExnRef {
+  friend class Val;
+
+  wasmtime_exnref_t val;
+
+public:
+  /// Creates a new `ExnRef` directly from its C-API representation.
+  explicit ExnRef(wasmtime_exnref_t val) : val(val) {}
+
+  /// Creates a new `ExnRef` which is separately rooted from this one.
+  ExnRef clone(Store::Context cx) {
+    wasmtime_exnref_t other;
+    wasmtime_exnref_clone(cx.raw_context(), &val, &other);
+    return ExnRef(other);
+  }
+
+  /// Unroots this value from the context provided, enabling a future GC to
+  /// collect the internal object if there are no more references.
+  void unroot(Store::Context cx) {
+    wasmtime_exnref_unroot(cx.raw_context(), &val);
+  }
+
+  /// Returns the raw underlying C API value.
+  ///
+  /// This class still retains ownership of the pointer.
+  const wasmtime_exnref_t *raw() const { return &val; }
+
+  /// Returns the number of payload fields of this exception.
+  Result<size_t> field_count(Store::Context cx) const {
+    size_t ret = 0;
+    auto *error = wasmtime_exnref_field_count(cx.raw_context(), &val, &ret);
+    if (error != nullptr) {
+      return Error(error);
+    }
+    return ret;
+  }
+
+  /// Reads the payload field `index` of this exception.
+  ///
+  /// The returned value must be unrooted with `Val::unroot`.
+  Result<Val> field(Store::Context cx, size_t index) const;
+};
+
 /// \brief Container for the `v128` WebAssembly type.
 struct V128 {
   /// \brief The little-endian bytes of the `v128` value.
@@ -157,6 +209,9 @@ class Val {
   friend class Global;
   friend class Table;
   friend class Func;
+  friend class ExnRef;
+  friend class StructRef;
+  friend class ArrayRef;
 
   wasmtime_val_t val;
 
@@ -214,12 +269,23 @@ public:
       wasmtime_anyref_set_null(&val.of.anyref);
     }
   }
+  /// Creates a new `exnref` value.
+  Val(std::optional<ExnRef> ptr) : val{} {
+    val.kind = WASMTIME_EXNREF;
+    if (ptr) {
+      val.of.exnref = ptr->val;
+    } else {
+      wasmtime_exnref_set_null(&val.of.exnref);
+    }
+  }
   /// Creates a new `externref` WebAssembly value which is not `ref.null
   /// extern`.
   Val(ExternRef ptr);
   /// Creates a new `anyref` WebAssembly value which is not `ref.null
   /// any`.
   Val(AnyRef ptr);
+  /// Creates a new `exnref` WebAssembly value which is not `ref.null exn`.
+  Val(ExnRef ptr);
 
   /// Returns the kind of value that this value has.
   ValKind kind() const {
@@ -238,6 +304,8 @@ public:
       return ValKind::ExternRef;
     case WASMTIME_ANYREF:
       return ValKind::AnyRef;
+    case WASMTIME_EXNREF:
+      return ValKind::ExnRef;
     case WASMTIME_V128:
       return ValKind::V128;
     }
@@ -323,6 +391,23 @@ public:
     return AnyRef(other);
   }
 
+  /// Returns the underlying `exnref`, requires `kind() == KindExnRef` or
+  /// aborts the process.
+  ///
+  /// Note that `exnref` is a nullable reference, hence the `optional` return
+  /// value.
+  std::optional<ExnRef> exnref(Store::Context cx) const {
+    if (val.kind != WASMTIME_EXNREF) {
+      std::abort();
+    }
+    if (wasmtime_exnref_is_null(&val.of.exnref)) {
+      return std::nullopt;
+    }
+    wasmtime_exnref_t other;
+    wasmtime_exnref_clone(cx.raw_context(), &val.of.exnref, &other);
+    return ExnRef(other);
+  }
+
   /// Returns the underlying `funcref`, requires `kind() == KindFuncRef` or
   /// aborts the process.
   ///
@@ -13,8 +13,8 @@ pub unsafe extern "C" fn wasmtime_component_func_call(
     results: *mut wasmtime_component_val_t,
     results_len: usize,
 ) -> Option<Box<wasmtime_error_t>> {
-    let c_args = unsafe { std::slice::from_raw_parts(args, args_len) };
-    let c_results = unsafe { std::slice::from_raw_parts_mut(results, results_len) };
+    let c_args = unsafe { crate::slice_from_raw_parts(args, args_len) };
+    let c_results = unsafe { crate::slice_from_raw_parts_mut(results, results_len) };
 
     let args = c_args.iter().map(Val::from).collect::<Vec<_>>();
     let mut results = vec![Val::Bool(false); results_len];
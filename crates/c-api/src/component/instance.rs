@@ -2,7 +2,7 @@ use wasmtime::component::{Func, Instance};
 
 use crate::WasmtimeStoreContextMut;
 
-use super::wasmtime_component_export_index_t;
+use super::{wasmtime_component_export_index_t, wasmtime_component_resource_type_t};
 
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn wasmtime_component_instance_get_export_index(
@@ -42,3 +42,18 @@ pub unsafe extern "C" fn wasmtime_component_instance_get_func(
         false
     }
 }
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_instance_get_resource(
+    instance: &Instance,
+    context: WasmtimeStoreContextMut<'_>,
+    export_index: &wasmtime_component_export_index_t,
+    resource_type_out: &mut *mut wasmtime_component_resource_type_t,
+) -> bool {
+    if let Some(ty) = instance.get_resource(context, export_index.export_index) {
+        *resource_type_out = Box::into_raw(Box::new(wasmtime_component_resource_type_t { ty }));
+        true
+    } else {
+        false
+    }
+}
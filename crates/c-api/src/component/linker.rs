@@ -6,7 +6,7 @@ use crate::{
     WasmtimeStoreContextMut, WasmtimeStoreData, wasm_engine_t, wasmtime_error_t, wasmtime_module_t,
 };
 
-use super::{wasmtime_component_t, wasmtime_component_val_t};
+use super::{wasmtime_component_resource_type_t, wasmtime_component_t, wasmtime_component_val_t};
 
 #[repr(transparent)]
 pub struct wasmtime_component_linker_t {
@@ -152,6 +152,40 @@ pub unsafe extern "C" fn wasmtime_component_linker_instance_add_func(
     crate::handle_result(result, |_| ())
 }
 
+pub type wasmtime_component_resource_destructor_t =
+    extern "C" fn(*mut c_void, WasmtimeStoreContextMut<'_>, u32) -> Option<Box<wasmtime_error_t>>;
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_linker_instance_add_resource(
+    linker_instance: &mut wasmtime_component_linker_instance_t,
+    name: *const u8,
+    name_len: usize,
+    ty: &wasmtime_component_resource_type_t,
+    destructor: wasmtime_component_resource_destructor_t,
+    data: *mut c_void,
+    finalizer: Option<extern "C" fn(*mut c_void)>,
+) -> Option<Box<wasmtime_error_t>> {
+    let name = unsafe { std::slice::from_raw_parts(name, name_len) };
+    let Ok(name) = std::str::from_utf8(name) else {
+        return crate::bad_utf8();
+    };
+
+    let foreign = crate::ForeignData { data, finalizer };
+
+    let result = linker_instance
+        .linker_instance
+        .resource(&name, ty.ty, move |ctx, rep| {
+            let _ = &foreign;
+
+            match destructor(foreign.data, ctx, rep) {
+                Some(res) => Err((*res).into()),
+                None => Ok(()),
+            }
+        });
+
+    crate::handle_result(result, |_| ())
+}
+
 #[unsafe(no_mangle)]
 #[cfg(feature = "wasi")]
 pub unsafe extern "C" fn wasmtime_component_linker_add_wasip2(
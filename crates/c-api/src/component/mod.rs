@@ -2,10 +2,14 @@ mod component;
 mod func;
 mod instance;
 mod linker;
+mod resource;
+mod types;
 mod val;
 
 pub use component::*;
 pub use func::*;
 pub use instance::*;
 pub use linker::*;
+pub use resource::*;
+pub use types::*;
 pub use val::*;
@@ -0,0 +1,734 @@
+use std::mem::MaybeUninit;
+
+use wasmtime::component::Type;
+use wasmtime::component::types::{self, ComponentItem};
+
+use crate::{
+    CExternType, wasm_engine_t, wasm_exporttype_t, wasm_exporttype_vec_t, wasm_functype_t,
+    wasm_importtype_t, wasm_importtype_vec_t,
+};
+
+use super::{wasmtime_component_resource_type_t, wasmtime_component_t};
+
+macro_rules! type_wrappers {
+    ($(
+        (
+            name: $name:ident,
+            ty: $ty:ty,
+            clone: $clone:ident,
+            delete: $delete:ident,
+        )
+    )*) => {$(
+        #[derive(Clone)]
+        #[repr(transparent)]
+        pub struct $name {
+            pub(crate) ty: $ty,
+        }
+
+        #[unsafe(no_mangle)]
+        pub extern "C" fn $clone(ty: &$name) -> Box<$name> {
+            Box::new(ty.clone())
+        }
+
+        #[unsafe(no_mangle)]
+        pub extern "C" fn $delete(_ty: Box<$name>) {}
+    )*};
+}
+
+type_wrappers! {
+    (
+        name: wasmtime_component_type_t,
+        ty: types::Component,
+        clone: wasmtime_component_type_clone,
+        delete: wasmtime_component_type_delete,
+    )
+    (
+        name: wasmtime_component_instance_type_t,
+        ty: types::ComponentInstance,
+        clone: wasmtime_component_instance_type_clone,
+        delete: wasmtime_component_instance_type_delete,
+    )
+    (
+        name: wasmtime_component_module_type_t,
+        ty: types::Module,
+        clone: wasmtime_component_module_type_clone,
+        delete: wasmtime_component_module_type_delete,
+    )
+    (
+        name: wasmtime_component_func_type_t,
+        ty: types::ComponentFunc,
+        clone: wasmtime_component_func_type_clone,
+        delete: wasmtime_component_func_type_delete,
+    )
+    (
+        name: wasmtime_component_list_type_t,
+        ty: types::List,
+        clone: wasmtime_component_list_type_clone,
+        delete: wasmtime_component_list_type_delete,
+    )
+    (
+        name: wasmtime_component_record_type_t,
+        ty: types::Record,
+        clone: wasmtime_component_record_type_clone,
+        delete: wasmtime_component_record_type_delete,
+    )
+    (
+        name: wasmtime_component_tuple_type_t,
+        ty: types::Tuple,
+        clone: wasmtime_component_tuple_type_clone,
+        delete: wasmtime_component_tuple_type_delete,
+    )
+    (
+        name: wasmtime_component_variant_type_t,
+        ty: types::Variant,
+        clone: wasmtime_component_variant_type_clone,
+        delete: wasmtime_component_variant_type_delete,
+    )
+    (
+        name: wasmtime_component_enum_type_t,
+        ty: types::Enum,
+        clone: wasmtime_component_enum_type_clone,
+        delete: wasmtime_component_enum_type_delete,
+    )
+    (
+        name: wasmtime_component_option_type_t,
+        ty: types::OptionType,
+        clone: wasmtime_component_option_type_clone,
+        delete: wasmtime_component_option_type_delete,
+    )
+    (
+        name: wasmtime_component_result_type_t,
+        ty: types::ResultType,
+        clone: wasmtime_component_result_type_clone,
+        delete: wasmtime_component_result_type_delete,
+    )
+    (
+        name: wasmtime_component_flags_type_t,
+        ty: types::Flags,
+        clone: wasmtime_component_flags_type_clone,
+        delete: wasmtime_component_flags_type_delete,
+    )
+    (
+        name: wasmtime_component_future_type_t,
+        ty: types::FutureType,
+        clone: wasmtime_component_future_type_clone,
+        delete: wasmtime_component_future_type_delete,
+    )
+    (
+        name: wasmtime_component_stream_type_t,
+        ty: types::StreamType,
+        clone: wasmtime_component_stream_type_clone,
+        delete: wasmtime_component_stream_type_delete,
+    )
+}
+
+#[repr(C, u8)]
+#[derive(Clone)]
+pub enum wasmtime_component_valtype_t {
+    Bool,
+    S8,
+    U8,
+    S16,
+    U16,
+    S32,
+    U32,
+    S64,
+    U64,
+    F32,
+    F64,
+    Char,
+    String,
+    List(Box<wasmtime_component_list_type_t>),
+    Record(Box<wasmtime_component_record_type_t>),
+    Tuple(Box<wasmtime_component_tuple_type_t>),
+    Variant(Box<wasmtime_component_variant_type_t>),
+    Enum(Box<wasmtime_component_enum_type_t>),
+    Option(Box<wasmtime_component_option_type_t>),
+    Result(Box<wasmtime_component_result_type_t>),
+    Flags(Box<wasmtime_component_flags_type_t>),
+    Own(Box<wasmtime_component_resource_type_t>),
+    Borrow(Box<wasmtime_component_resource_type_t>),
+    Future(Box<wasmtime_component_future_type_t>),
+    Stream(Box<wasmtime_component_stream_type_t>),
+    ErrorContext,
+}
+
+impl From<Type> for wasmtime_component_valtype_t {
+    fn from(ty: Type) -> Self {
+        match ty {
+            Type::Bool => Self::Bool,
+            Type::S8 => Self::S8,
+            Type::U8 => Self::U8,
+            Type::S16 => Self::S16,
+            Type::U16 => Self::U16,
+            Type::S32 => Self::S32,
+            Type::U32 => Self::U32,
+            Type::S64 => Self::S64,
+            Type::U64 => Self::U64,
+            Type::Float32 => Self::F32,
+            Type::Float64 => Self::F64,
+            Type::Char => Self::Char,
+            Type::String => Self::String,
+            Type::List(ty) => Self::List(Box::new(wasmtime_component_list_type_t { ty })),
+            Type::Record(ty) => Self::Record(Box::new(wasmtime_component_record_type_t { ty })),
+            Type::Tuple(ty) => Self::Tuple(Box::new(wasmtime_component_tuple_type_t { ty })),
+            Type::Variant(ty) => Self::Variant(Box::new(wasmtime_component_variant_type_t { ty })),
+            Type::Enum(ty) => Self::Enum(Box::new(wasmtime_component_enum_type_t { ty })),
+            Type::Option(ty) => Self::Option(Box::new(wasmtime_component_option_type_t { ty })),
+            Type::Result(ty) => Self::Result(Box::new(wasmtime_component_result_type_t { ty })),
+            Type::Flags(ty) => Self::Flags(Box::new(wasmtime_component_flags_type_t { ty })),
+            Type::Own(ty) => Self::Own(Box::new(wasmtime_component_resource_type_t { ty })),
+            Type::Borrow(ty) => Self::Borrow(Box::new(wasmtime_component_resource_type_t { ty })),
+            Type::Future(ty) => Self::Future(Box::new(wasmtime_component_future_type_t { ty })),
+            Type::Stream(ty) => Self::Stream(Box::new(wasmtime_component_stream_type_t { ty })),
+            Type::ErrorContext => Self::ErrorContext,
+        }
+    }
+}
+
+impl From<&wasmtime_component_valtype_t> for Type {
+    fn from(ty: &wasmtime_component_valtype_t) -> Self {
+        match ty {
+            wasmtime_component_valtype_t::Bool => Type::Bool,
+            wasmtime_component_valtype_t::S8 => Type::S8,
+            wasmtime_component_valtype_t::U8 => Type::U8,
+            wasmtime_component_valtype_t::S16 => Type::S16,
+            wasmtime_component_valtype_t::U16 => Type::U16,
+            wasmtime_component_valtype_t::S32 => Type::S32,
+            wasmtime_component_valtype_t::U32 => Type::U32,
+            wasmtime_component_valtype_t::S64 => Type::S64,
+            wasmtime_component_valtype_t::U64 => Type::U64,
+            wasmtime_component_valtype_t::F32 => Type::Float32,
+            wasmtime_component_valtype_t::F64 => Type::Float64,
+            wasmtime_component_valtype_t::Char => Type::Char,
+            wasmtime_component_valtype_t::String => Type::String,
+            wasmtime_component_valtype_t::List(x) => Type::List(x.ty.clone()),
+            wasmtime_component_valtype_t::Record(x) => Type::Record(x.ty.clone()),
+            wasmtime_component_valtype_t::Tuple(x) => Type::Tuple(x.ty.clone()),
+            wasmtime_component_valtype_t::Variant(x) => Type::Variant(x.ty.clone()),
+            wasmtime_component_valtype_t::Enum(x) => Type::Enum(x.ty.clone()),
+            wasmtime_component_valtype_t::Option(x) => Type::Option(x.ty.clone()),
+            wasmtime_component_valtype_t::Result(x) => Type::Result(x.ty.clone()),
+            wasmtime_component_valtype_t::Flags(x) => Type::Flags(x.ty.clone()),
+            wasmtime_component_valtype_t::Own(x) => Type::Own(x.ty),
+            wasmtime_component_valtype_t::Borrow(x) => Type::Borrow(x.ty),
+            wasmtime_component_valtype_t::Future(x) => Type::Future(x.ty.clone()),
+            wasmtime_component_valtype_t::Stream(x) => Type::Stream(x.ty.clone()),
+            wasmtime_component_valtype_t::ErrorContext => Type::ErrorContext,
+        }
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_clone(
+    ty: &wasmtime_component_valtype_t,
+    ret: &mut MaybeUninit<wasmtime_component_valtype_t>,
+) {
+    crate::initialize(ret, ty.clone());
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_valtype_equal(
+    a: &wasmtime_component_valtype_t,
+    b: &wasmtime_component_valtype_t,
+) -> bool {
+    Type::from(a) == Type::from(b)
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_valtype_delete(ty: *mut wasmtime_component_valtype_t) {
+    unsafe {
+        std::ptr::drop_in_place(ty);
+    }
+}
+
+#[repr(C, u8)]
+#[derive(Clone)]
+pub enum wasmtime_component_item_t {
+    ComponentFunc(Box<wasmtime_component_func_type_t>),
+    CoreFunc(Box<wasm_functype_t>),
+    Module(Box<wasmtime_component_module_type_t>),
+    Component(Box<wasmtime_component_type_t>),
+    ComponentInstance(Box<wasmtime_component_instance_type_t>),
+    Type(wasmtime_component_valtype_t),
+    Resource(Box<wasmtime_component_resource_type_t>),
+}
+
+impl From<ComponentItem> for wasmtime_component_item_t {
+    fn from(item: ComponentItem) -> Self {
+        match item {
+            ComponentItem::ComponentFunc(ty) => {
+                Self::ComponentFunc(Box::new(wasmtime_component_func_type_t { ty }))
+            }
+            ComponentItem::CoreFunc(ty) => Self::CoreFunc(Box::new(wasm_functype_t::new(ty))),
+            ComponentItem::Module(ty) => {
+                Self::Module(Box::new(wasmtime_component_module_type_t { ty }))
+            }
+            ComponentItem::Component(ty) => {
+                Self::Component(Box::new(wasmtime_component_type_t { ty }))
+            }
+            ComponentItem::ComponentInstance(ty) => {
+                Self::ComponentInstance(Box::new(wasmtime_component_instance_type_t { ty }))
+            }
+            ComponentItem::Type(ty) => Self::Type(ty.into()),
+            ComponentItem::Resource(ty) => {
+                Self::Resource(Box::new(wasmtime_component_resource_type_t { ty }))
+            }
+        }
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_item_clone(
+    item: &wasmtime_component_item_t,
+    ret: &mut MaybeUninit<wasmtime_component_item_t>,
+) {
+    crate::initialize(ret, item.clone());
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_item_delete(item: *mut wasmtime_component_item_t) {
+    unsafe {
+        std::ptr::drop_in_place(item);
+    }
+}
+
+/// Writes `name` into the out-pointers used to return borrowed names to C.
+fn write_name(name: &str, name_out: &mut *const u8, name_len_out: &mut usize) {
+    *name_out = name.as_ptr();
+    *name_len_out = name.len();
+}
+
+/// Looks up `name`, coming from C, with `get`, returning `false` if the name
+/// isn't valid UTF-8 or nothing was found.
+unsafe fn get_item(
+    name: *const u8,
+    name_len: usize,
+    item_out: &mut MaybeUninit<wasmtime_component_item_t>,
+    get: impl FnOnce(&str) -> Option<ComponentItem>,
+) -> bool {
+    let name = unsafe { crate::slice_from_raw_parts(name, name_len) };
+    let Ok(name) = std::str::from_utf8(name) else {
+        return false;
+    };
+    match get(name) {
+        Some(item) => {
+            crate::initialize(item_out, item.into());
+            true
+        }
+        None => false,
+    }
+}
+
+/// Writes the `nth` named item of `items` into the out-pointers, returning
+/// `false` if `nth` is out of bounds.
+fn nth_item<'a>(
+    mut items: impl Iterator<Item = (&'a str, ComponentItem)>,
+    nth: usize,
+    name_out: &mut *const u8,
+    name_len_out: &mut usize,
+    item_out: &mut MaybeUninit<wasmtime_component_item_t>,
+) -> bool {
+    match items.nth(nth) {
+        Some((name, item)) => {
+            write_name(name, name_out, name_len_out);
+            crate::initialize(item_out, item.into());
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_type(
+    component: &wasmtime_component_t,
+) -> Box<wasmtime_component_type_t> {
+    Box::new(wasmtime_component_type_t {
+        ty: component.component.component_type(),
+    })
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_type_import_count(
+    ty: &wasmtime_component_type_t,
+    engine: &wasm_engine_t,
+) -> usize {
+    ty.ty.imports(&engine.engine).len()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_type_import_get(
+    ty: &wasmtime_component_type_t,
+    engine: &wasm_engine_t,
+    name: *const u8,
+    name_len: usize,
+    item_out: &mut MaybeUninit<wasmtime_component_item_t>,
+) -> bool {
+    unsafe {
+        get_item(name, name_len, item_out, |name| {
+            ty.ty.get_import(&engine.engine, name)
+        })
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_type_import_nth(
+    ty: &wasmtime_component_type_t,
+    engine: &wasm_engine_t,
+    nth: usize,
+    name_out: &mut *const u8,
+    name_len_out: &mut usize,
+    item_out: &mut MaybeUninit<wasmtime_component_item_t>,
+) -> bool {
+    nth_item(
+        ty.ty.imports(&engine.engine),
+        nth,
+        name_out,
+        name_len_out,
+        item_out,
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_type_export_count(
+    ty: &wasmtime_component_type_t,
+    engine: &wasm_engine_t,
+) -> usize {
+    ty.ty.exports(&engine.engine).len()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_type_export_get(
+    ty: &wasmtime_component_type_t,
+    engine: &wasm_engine_t,
+    name: *const u8,
+    name_len: usize,
+    item_out: &mut MaybeUninit<wasmtime_component_item_t>,
+) -> bool {
+    unsafe {
+        get_item(name, name_len, item_out, |name| {
+            ty.ty.get_export(&engine.engine, name)
+        })
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_type_export_nth(
+    ty: &wasmtime_component_type_t,
+    engine: &wasm_engine_t,
+    nth: usize,
+    name_out: &mut *const u8,
+    name_len_out: &mut usize,
+    item_out: &mut MaybeUninit<wasmtime_component_item_t>,
+) -> bool {
+    nth_item(
+        ty.ty.exports(&engine.engine),
+        nth,
+        name_out,
+        name_len_out,
+        item_out,
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_instance_type_export_count(
+    ty: &wasmtime_component_instance_type_t,
+    engine: &wasm_engine_t,
+) -> usize {
+    ty.ty.exports(&engine.engine).len()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_component_instance_type_export_get(
+    ty: &wasmtime_component_instance_type_t,
+    engine: &wasm_engine_t,
+    name: *const u8,
+    name_len: usize,
+    item_out: &mut MaybeUninit<wasmtime_component_item_t>,
+) -> bool {
+    unsafe {
+        get_item(name, name_len, item_out, |name| {
+            ty.ty.get_export(&engine.engine, name)
+        })
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_instance_type_export_nth(
+    ty: &wasmtime_component_instance_type_t,
+    engine: &wasm_engine_t,
+    nth: usize,
+    name_out: &mut *const u8,
+    name_len_out: &mut usize,
+    item_out: &mut MaybeUninit<wasmtime_component_item_t>,
+) -> bool {
+    nth_item(
+        ty.ty.exports(&engine.engine),
+        nth,
+        name_out,
+        name_len_out,
+        item_out,
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_module_type_imports(
+    ty: &wasmtime_component_module_type_t,
+    engine: &wasm_engine_t,
+    out: &mut wasm_importtype_vec_t,
+) {
+    let imports = ty
+        .ty
+        .imports(&engine.engine)
+        .map(|((module, name), ty)| {
+            Some(Box::new(wasm_importtype_t::new(
+                module.to_owned(),
+                name.to_owned(),
+                CExternType::new(ty),
+            )))
+        })
+        .collect::<Vec<_>>();
+    out.set_buffer(imports);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_module_type_exports(
+    ty: &wasmtime_component_module_type_t,
+    engine: &wasm_engine_t,
+    out: &mut wasm_exporttype_vec_t,
+) {
+    let exports = ty
+        .ty
+        .exports(&engine.engine)
+        .map(|(name, ty)| {
+            Some(Box::new(wasm_exporttype_t::new(
+                name.to_owned(),
+                CExternType::new(ty),
+            )))
+        })
+        .collect::<Vec<_>>();
+    out.set_buffer(exports);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_func_type_param_count(
+    ty: &wasmtime_component_func_type_t,
+) -> usize {
+    ty.ty.params().len()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_func_type_param_nth(
+    ty: &wasmtime_component_func_type_t,
+    nth: usize,
+    name_out: &mut *const u8,
+    name_len_out: &mut usize,
+    type_out: &mut MaybeUninit<wasmtime_component_valtype_t>,
+) -> bool {
+    match ty.ty.params().nth(nth) {
+        Some((name, ty)) => {
+            write_name(name, name_out, name_len_out);
+            crate::initialize(type_out, ty.into());
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_func_type_result(
+    ty: &wasmtime_component_func_type_t,
+    type_out: &mut MaybeUninit<wasmtime_component_valtype_t>,
+) -> bool {
+    match ty.ty.results().next() {
+        Some(ty) => {
+            crate::initialize(type_out, ty.into());
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_list_type_element(
+    ty: &wasmtime_component_list_type_t,
+    type_out: &mut MaybeUninit<wasmtime_component_valtype_t>,
+) {
+    crate::initialize(type_out, ty.ty.ty().into());
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_record_type_field_count(
+    ty: &wasmtime_component_record_type_t,
+) -> usize {
+    ty.ty.fields().len()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_record_type_field_nth(
+    ty: &wasmtime_component_record_type_t,
+    nth: usize,
+    name_out: &mut *const u8,
+    name_len_out: &mut usize,
+    type_out: &mut MaybeUninit<wasmtime_component_valtype_t>,
+) -> bool {
+    match ty.ty.fields().nth(nth) {
+        Some(field) => {
+            write_name(field.name, name_out, name_len_out);
+            crate::initialize(type_out, field.ty.into());
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_tuple_type_types_count(
+    ty: &wasmtime_component_tuple_type_t,
+) -> usize {
+    ty.ty.types().len()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_tuple_type_types_nth(
+    ty: &wasmtime_component_tuple_type_t,
+    nth: usize,
+    type_out: &mut MaybeUninit<wasmtime_component_valtype_t>,
+) -> bool {
+    match ty.ty.types().nth(nth) {
+        Some(ty) => {
+            crate::initialize(type_out, ty.into());
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_variant_type_case_count(
+    ty: &wasmtime_component_variant_type_t,
+) -> usize {
+    ty.ty.cases().len()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_variant_type_case_nth(
+    ty: &wasmtime_component_variant_type_t,
+    nth: usize,
+    name_out: &mut *const u8,
+    name_len_out: &mut usize,
+    has_payload_out: &mut bool,
+    payload_out: &mut MaybeUninit<wasmtime_component_valtype_t>,
+) -> bool {
+    match ty.ty.cases().nth(nth) {
+        Some(case) => {
+            write_name(case.name, name_out, name_len_out);
+            *has_payload_out = case.ty.is_some();
+            if let Some(ty) = case.ty {
+                crate::initialize(payload_out, ty.into());
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_enum_type_names_count(
+    ty: &wasmtime_component_enum_type_t,
+) -> usize {
+    ty.ty.names().len()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_enum_type_names_nth(
+    ty: &wasmtime_component_enum_type_t,
+    nth: usize,
+    name_out: &mut *const u8,
+    name_len_out: &mut usize,
+) -> bool {
+    match ty.ty.names().nth(nth) {
+        Some(name) => {
+            write_name(name, name_out, name_len_out);
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_option_type_ty(
+    ty: &wasmtime_component_option_type_t,
+    type_out: &mut MaybeUninit<wasmtime_component_valtype_t>,
+) {
+    crate::initialize(type_out, ty.ty.ty().into());
+}
+
+/// Writes `ty`, if any, into `type_out`, returning whether there was one.
+fn write_optional_type(
+    ty: Option<Type>,
+    type_out: &mut MaybeUninit<wasmtime_component_valtype_t>,
+) -> bool {
+    match ty {
+        Some(ty) => {
+            crate::initialize(type_out, ty.into());
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_result_type_ok(
+    ty: &wasmtime_component_result_type_t,
+    type_out: &mut MaybeUninit<wasmtime_component_valtype_t>,
+) -> bool {
+    write_optional_type(ty.ty.ok(), type_out)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_result_type_err(
+    ty: &wasmtime_component_result_type_t,
+    type_out: &mut MaybeUninit<wasmtime_component_valtype_t>,
+) -> bool {
+    write_optional_type(ty.ty.err(), type_out)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_flags_type_names_count(
+    ty: &wasmtime_component_flags_type_t,
+) -> usize {
+    ty.ty.names().len()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_flags_type_names_nth(
+    ty: &wasmtime_component_flags_type_t,
+    nth: usize,
+    name_out: &mut *const u8,
+    name_len_out: &mut usize,
+) -> bool {
+    match ty.ty.names().nth(nth) {
+        Some(name) => {
+            write_name(name, name_out, name_len_out);
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_future_type_ty(
+    ty: &wasmtime_component_future_type_t,
+    type_out: &mut MaybeUninit<wasmtime_component_valtype_t>,
+) -> bool {
+    write_optional_type(ty.ty.ty(), type_out)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_component_stream_type_ty(
+    ty: &wasmtime_component_stream_type_t,
+    type_out: &mut MaybeUninit<wasmtime_component_valtype_t>,
+) -> bool {
+    write_optional_type(ty.ty.ty(), type_out)
+}
@@ -1,6 +1,6 @@
 use wasmtime::component::Val;
 
-use crate::wasm_name_t;
+use crate::{wasm_name_t, wasmtime_component_resource_any_t};
 
 use std::mem;
 use std::mem::MaybeUninit;
@@ -232,6 +232,7 @@ pub enum wasmtime_component_val_t {
     Option(Option<Box<Self>>),
     Result(wasmtime_component_valresult_t),
     Flags(wasmtime_component_valflags_t),
+    Resource(Box<wasmtime_component_resource_any_t>),
 }
 
 impl Default for wasmtime_component_val_t {
@@ -273,6 +274,7 @@ impl From<&wasmtime_component_val_t> for Val {
             }
             wasmtime_component_val_t::Result(x) => Val::Result(x.into()),
             wasmtime_component_val_t::Flags(x) => Val::Flags(x.into()),
+            wasmtime_component_val_t::Resource(x) => Val::Resource(x.resource),
         }
     }
 }
@@ -306,7 +308,11 @@ impl From<&Val> for wasmtime_component_val_t {
             ),
             Val::Result(x) => wasmtime_component_val_t::Result(x.into()),
             Val::Flags(x) => wasmtime_component_val_t::Flags(x.as_slice().into()),
-            Val::Resource(_resource_any) => todo!(),
+            Val::Resource(x) => {
+                wasmtime_component_val_t::Resource(Box::new(wasmtime_component_resource_any_t {
+                    resource: *x,
+                }))
+            }
             Val::Future(_) => todo!(),
             Val::Stream(_) => todo!(),
             Val::ErrorContext(_) => todo!(),
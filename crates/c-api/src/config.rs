@@ -102,6 +102,11 @@ pub extern "C" fn wasmtime_config_wasm_gc_set(c: &mut wasm_config_t, enable: boo
     c.config.wasm_gc(enable);
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_config_wasm_exceptions_set(c: &mut wasm_config_t, enable: bool) {
+    c.config.wasm_exceptions(enable);
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn wasmtime_config_wasm_simd_set(c: &mut wasm_config_t, enable: bool) {
     c.config.wasm_simd(enable);
@@ -1,6 +1,14 @@
-use crate::{WasmtimeStoreContextMut, abort};
+use crate::{
+    WASM_CONST, WASM_VAR, WasmtimeStoreContextMut, abort, handle_result, wasm_engine_t,
+    wasm_mutability_t, wasmtime_error_t, wasmtime_val_t,
+};
+use anyhow::{Result, anyhow};
 use std::{mem::MaybeUninit, num::NonZeroU64, os::raw::c_void, ptr};
-use wasmtime::{AnyRef, ExternRef, I31, OwnedRooted, Ref, RootScope, Val};
+use wasmtime::{
+    AnyRef, ArrayRef, ArrayRefPre, ArrayType, ExnRef, ExternRef, FieldType, HeapType, I31,
+    Mutability, OwnedRooted, Ref, RootScope, StorageType, StructRef, StructRefPre, StructType, Val,
+    ValType,
+};
 
 /// `*mut wasm_ref_t` is a reference type (`externref` or `funcref`), as seen by
 /// the C API. Because we do not have a uniform representation for `funcref`s
@@ -245,6 +253,9 @@ macro_rules! ref_wrapper {
 
 ref_wrapper!(AnyRef => wasmtime_anyref_t);
 ref_wrapper!(ExternRef => wasmtime_externref_t);
+ref_wrapper!(StructRef => wasmtime_structref_t);
+ref_wrapper!(ArrayRef => wasmtime_arrayref_t);
+ref_wrapper!(ExnRef => wasmtime_exnref_t);
 
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn wasmtime_anyref_clone(
@@ -410,3 +421,495 @@ pub unsafe extern "C" fn wasmtime_externref_from_raw(
         .map(|e| e.to_owned_rooted(&mut scope).expect("in scope"));
     crate::initialize(val, rooted.into());
 }
+
+pub type wasmtime_storage_kind_t = u8;
+pub const WASMTIME_STORAGE_KIND_I8: wasmtime_storage_kind_t = 0;
+pub const WASMTIME_STORAGE_KIND_I16: wasmtime_storage_kind_t = 1;
+pub const WASMTIME_STORAGE_KIND_I32: wasmtime_storage_kind_t = 2;
+pub const WASMTIME_STORAGE_KIND_I64: wasmtime_storage_kind_t = 3;
+pub const WASMTIME_STORAGE_KIND_F32: wasmtime_storage_kind_t = 4;
+pub const WASMTIME_STORAGE_KIND_F64: wasmtime_storage_kind_t = 5;
+pub const WASMTIME_STORAGE_KIND_V128: wasmtime_storage_kind_t = 6;
+pub const WASMTIME_STORAGE_KIND_FUNCREF: wasmtime_storage_kind_t = 7;
+pub const WASMTIME_STORAGE_KIND_EXTERNREF: wasmtime_storage_kind_t = 8;
+pub const WASMTIME_STORAGE_KIND_ANYREF: wasmtime_storage_kind_t = 9;
+pub const WASMTIME_STORAGE_KIND_EQREF: wasmtime_storage_kind_t = 10;
+pub const WASMTIME_STORAGE_KIND_I31REF: wasmtime_storage_kind_t = 11;
+pub const WASMTIME_STORAGE_KIND_STRUCTREF: wasmtime_storage_kind_t = 12;
+pub const WASMTIME_STORAGE_KIND_ARRAYREF: wasmtime_storage_kind_t = 13;
+pub const WASMTIME_STORAGE_KIND_EXNREF: wasmtime_storage_kind_t = 14;
+
+/// C-API representation of the type of a struct field or array element.
+///
+/// Reference types are always nullable and abstract; fields whose type can't
+/// be described this way are reported as unrepresentable by the accessors
+/// below.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct wasmtime_field_type_t {
+    pub kind: wasmtime_storage_kind_t,
+    pub mutability: wasm_mutability_t,
+}
+
+impl wasmtime_field_type_t {
+    fn to_wasmtime(&self) -> Result<FieldType> {
+        let mutability = match self.mutability {
+            WASM_CONST => Mutability::Const,
+            WASM_VAR => Mutability::Var,
+            other => return Err(anyhow!("unknown mutability: {other}")),
+        };
+        let element_type = match self.kind {
+            WASMTIME_STORAGE_KIND_I8 => StorageType::I8,
+            WASMTIME_STORAGE_KIND_I16 => StorageType::I16,
+            WASMTIME_STORAGE_KIND_I32 => StorageType::ValType(ValType::I32),
+            WASMTIME_STORAGE_KIND_I64 => StorageType::ValType(ValType::I64),
+            WASMTIME_STORAGE_KIND_F32 => StorageType::ValType(ValType::F32),
+            WASMTIME_STORAGE_KIND_F64 => StorageType::ValType(ValType::F64),
+            WASMTIME_STORAGE_KIND_V128 => StorageType::ValType(ValType::V128),
+            WASMTIME_STORAGE_KIND_FUNCREF => StorageType::ValType(ValType::FUNCREF),
+            WASMTIME_STORAGE_KIND_EXTERNREF => StorageType::ValType(ValType::EXTERNREF),
+            WASMTIME_STORAGE_KIND_ANYREF => StorageType::ValType(ValType::ANYREF),
+            WASMTIME_STORAGE_KIND_EQREF => StorageType::ValType(ValType::EQREF),
+            WASMTIME_STORAGE_KIND_I31REF => StorageType::ValType(ValType::I31REF),
+            WASMTIME_STORAGE_KIND_STRUCTREF => StorageType::ValType(ValType::STRUCTREF),
+            WASMTIME_STORAGE_KIND_ARRAYREF => StorageType::ValType(ValType::ARRAYREF),
+            WASMTIME_STORAGE_KIND_EXNREF => StorageType::ValType(ValType::EXNREF),
+            other => return Err(anyhow!("unknown storage kind: {other}")),
+        };
+        Ok(FieldType::new(mutability, element_type))
+    }
+
+    fn from_wasmtime(ty: &FieldType) -> Option<wasmtime_field_type_t> {
+        let mutability = match ty.mutability() {
+            Mutability::Const => WASM_CONST,
+            Mutability::Var => WASM_VAR,
+        };
+        let kind = match ty.element_type() {
+            StorageType::I8 => WASMTIME_STORAGE_KIND_I8,
+            StorageType::I16 => WASMTIME_STORAGE_KIND_I16,
+            StorageType::ValType(ValType::I32) => WASMTIME_STORAGE_KIND_I32,
+            StorageType::ValType(ValType::I64) => WASMTIME_STORAGE_KIND_I64,
+            StorageType::ValType(ValType::F32) => WASMTIME_STORAGE_KIND_F32,
+            StorageType::ValType(ValType::F64) => WASMTIME_STORAGE_KIND_F64,
+            StorageType::ValType(ValType::V128) => WASMTIME_STORAGE_KIND_V128,
+            StorageType::ValType(ValType::Ref(r)) if r.is_nullable() => match r.heap_type() {
+                HeapType::Func => WASMTIME_STORAGE_KIND_FUNCREF,
+                HeapType::Extern => WASMTIME_STORAGE_KIND_EXTERNREF,
+                HeapType::Any => WASMTIME_STORAGE_KIND_ANYREF,
+                HeapType::Eq => WASMTIME_STORAGE_KIND_EQREF,
+                HeapType::I31 => WASMTIME_STORAGE_KIND_I31REF,
+                HeapType::Struct => WASMTIME_STORAGE_KIND_STRUCTREF,
+                HeapType::Array => WASMTIME_STORAGE_KIND_ARRAYREF,
+                HeapType::Exn => WASMTIME_STORAGE_KIND_EXNREF,
+                _ => return None,
+            },
+            StorageType::ValType(ValType::Ref(_)) => return None,
+        };
+        Some(wasmtime_field_type_t { kind, mutability })
+    }
+}
+
+pub struct wasmtime_struct_type_t {
+    pub(crate) ty: StructType,
+}
+
+wasmtime_c_api_macros::declare_own!(wasmtime_struct_type_t);
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_struct_type_new(
+    engine: &wasm_engine_t,
+    fields: *const wasmtime_field_type_t,
+    nfields: usize,
+    ret: &mut *mut wasmtime_struct_type_t,
+) -> Option<Box<wasmtime_error_t>> {
+    let result = crate::slice_from_raw_parts(fields, nfields)
+        .iter()
+        .map(|f| f.to_wasmtime())
+        .collect::<Result<Vec<_>>>()
+        .and_then(|fields| StructType::new(&engine.engine, fields));
+    handle_result(result, |ty| {
+        *ret = Box::into_raw(Box::new(wasmtime_struct_type_t { ty }));
+    })
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_struct_type_field_count(ty: &wasmtime_struct_type_t) -> usize {
+    ty.ty.fields().len()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_struct_type_field_nth(
+    ty: &wasmtime_struct_type_t,
+    nth: usize,
+    ret: &mut MaybeUninit<wasmtime_field_type_t>,
+) -> bool {
+    match ty
+        .ty
+        .field(nth)
+        .and_then(|f| wasmtime_field_type_t::from_wasmtime(&f))
+    {
+        Some(field) => {
+            crate::initialize(ret, field);
+            true
+        }
+        None => false,
+    }
+}
+
+pub struct wasmtime_array_type_t {
+    pub(crate) ty: ArrayType,
+}
+
+wasmtime_c_api_macros::declare_own!(wasmtime_array_type_t);
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_array_type_new(
+    engine: &wasm_engine_t,
+    field: &wasmtime_field_type_t,
+    ret: &mut *mut wasmtime_array_type_t,
+) -> Option<Box<wasmtime_error_t>> {
+    handle_result(field.to_wasmtime(), |field| {
+        let ty = ArrayType::new(&engine.engine, field);
+        *ret = Box::into_raw(Box::new(wasmtime_array_type_t { ty }));
+    })
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_array_type_field(
+    ty: &wasmtime_array_type_t,
+    ret: &mut MaybeUninit<wasmtime_field_type_t>,
+) -> bool {
+    match wasmtime_field_type_t::from_wasmtime(&ty.ty.field_type()) {
+        Some(field) => {
+            crate::initialize(ret, field);
+            true
+        }
+        None => false,
+    }
+}
+
+pub struct wasmtime_structref_pre_t {
+    pre: StructRefPre,
+}
+
+wasmtime_c_api_macros::declare_own!(wasmtime_structref_pre_t);
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_structref_pre_new(
+    cx: WasmtimeStoreContextMut<'_>,
+    ty: &wasmtime_struct_type_t,
+) -> Box<wasmtime_structref_pre_t> {
+    Box::new(wasmtime_structref_pre_t {
+        pre: StructRefPre::new(cx, ty.ty.clone()),
+    })
+}
+
+pub struct wasmtime_arrayref_pre_t {
+    pre: ArrayRefPre,
+}
+
+wasmtime_c_api_macros::declare_own!(wasmtime_arrayref_pre_t);
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_arrayref_pre_new(
+    cx: WasmtimeStoreContextMut<'_>,
+    ty: &wasmtime_array_type_t,
+) -> Box<wasmtime_arrayref_pre_t> {
+    Box::new(wasmtime_arrayref_pre_t {
+        pre: ArrayRefPre::new(cx, ty.ty.clone()),
+    })
+}
+
+fn null_ref() -> anyhow::Error {
+    anyhow!("null reference")
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_structref_new(
+    cx: WasmtimeStoreContextMut<'_>,
+    pre: &wasmtime_structref_pre_t,
+    fields: *const wasmtime_val_t,
+    nfields: usize,
+    out: &mut MaybeUninit<wasmtime_structref_t>,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let fields = crate::slice_from_raw_parts(fields, nfields)
+        .iter()
+        .map(|v| v.to_val(&mut scope))
+        .collect::<Vec<_>>();
+    let result =
+        StructRef::new(&mut scope, &pre.pre, &fields).and_then(|s| s.to_owned_rooted(&mut scope));
+    handle_result(result, |s| crate::initialize(out, Some(s).into()))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_structref_clone(
+    _cx: WasmtimeStoreContextMut<'_>,
+    structref: Option<&wasmtime_structref_t>,
+    out: &mut MaybeUninit<wasmtime_structref_t>,
+) {
+    let structref = structref.and_then(|s| s.as_wasmtime());
+    crate::initialize(out, structref.into());
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_structref_unroot(
+    _cx: WasmtimeStoreContextMut<'_>,
+    val: Option<&mut MaybeUninit<wasmtime_structref_t>>,
+) {
+    if let Some(val) = val.and_then(|v| v.assume_init_read().from_wasmtime()) {
+        drop(val);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_structref_to_anyref(
+    _cx: WasmtimeStoreContextMut<'_>,
+    structref: Option<&wasmtime_structref_t>,
+    out: &mut MaybeUninit<wasmtime_anyref_t>,
+) {
+    let anyref = structref
+        .and_then(|s| s.as_wasmtime())
+        .map(|s| s.to_anyref());
+    crate::initialize(out, anyref.into());
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_anyref_as_structref(
+    cx: WasmtimeStoreContextMut<'_>,
+    anyref: Option<&wasmtime_anyref_t>,
+    out: &mut MaybeUninit<wasmtime_structref_t>,
+) -> bool {
+    let mut scope = RootScope::new(cx);
+    let structref = anyref
+        .and_then(|a| a.as_wasmtime())
+        .map(|a| a.to_rooted(&mut scope))
+        .and_then(|a| a.as_struct(&scope).expect("in scope"))
+        .map(|s| s.to_owned_rooted(&mut scope).expect("in scope"));
+    match structref {
+        Some(s) => {
+            crate::initialize(out, Some(s).into());
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_structref_field(
+    cx: WasmtimeStoreContextMut<'_>,
+    structref: Option<&wasmtime_structref_t>,
+    index: usize,
+    out: &mut MaybeUninit<wasmtime_val_t>,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let result = structref
+        .and_then(|s| s.as_wasmtime())
+        .ok_or_else(null_ref)
+        .and_then(|s| s.field(&mut scope, index));
+    handle_result(result, |val| {
+        crate::initialize(out, wasmtime_val_t::from_val(&mut scope, val))
+    })
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_structref_set_field(
+    cx: WasmtimeStoreContextMut<'_>,
+    structref: Option<&wasmtime_structref_t>,
+    index: usize,
+    val: &wasmtime_val_t,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let val = val.to_val(&mut scope);
+    let result = structref
+        .and_then(|s| s.as_wasmtime())
+        .ok_or_else(null_ref)
+        .and_then(|s| s.set_field(&mut scope, index, val));
+    handle_result(result, |()| {})
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_arrayref_new(
+    cx: WasmtimeStoreContextMut<'_>,
+    pre: &wasmtime_arrayref_pre_t,
+    elem: &wasmtime_val_t,
+    len: u32,
+    out: &mut MaybeUninit<wasmtime_arrayref_t>,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let elem = elem.to_val(&mut scope);
+    let result =
+        ArrayRef::new(&mut scope, &pre.pre, &elem, len).and_then(|a| a.to_owned_rooted(&mut scope));
+    handle_result(result, |a| crate::initialize(out, Some(a).into()))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_arrayref_clone(
+    _cx: WasmtimeStoreContextMut<'_>,
+    arrayref: Option<&wasmtime_arrayref_t>,
+    out: &mut MaybeUninit<wasmtime_arrayref_t>,
+) {
+    let arrayref = arrayref.and_then(|a| a.as_wasmtime());
+    crate::initialize(out, arrayref.into());
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_arrayref_unroot(
+    _cx: WasmtimeStoreContextMut<'_>,
+    val: Option<&mut MaybeUninit<wasmtime_arrayref_t>>,
+) {
+    if let Some(val) = val.and_then(|v| v.assume_init_read().from_wasmtime()) {
+        drop(val);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_arrayref_to_anyref(
+    _cx: WasmtimeStoreContextMut<'_>,
+    arrayref: Option<&wasmtime_arrayref_t>,
+    out: &mut MaybeUninit<wasmtime_anyref_t>,
+) {
+    let anyref = arrayref
+        .and_then(|a| a.as_wasmtime())
+        .map(|a| a.to_anyref());
+    crate::initialize(out, anyref.into());
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_anyref_as_arrayref(
+    cx: WasmtimeStoreContextMut<'_>,
+    anyref: Option<&wasmtime_anyref_t>,
+    out: &mut MaybeUninit<wasmtime_arrayref_t>,
+) -> bool {
+    let mut scope = RootScope::new(cx);
+    let arrayref = anyref
+        .and_then(|a| a.as_wasmtime())
+        .map(|a| a.to_rooted(&mut scope))
+        .and_then(|a| a.as_array(&scope).expect("in scope"))
+        .map(|a| a.to_owned_rooted(&mut scope).expect("in scope"));
+    match arrayref {
+        Some(a) => {
+            crate::initialize(out, Some(a).into());
+            true
+        }
+        None => false,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_arrayref_len(
+    cx: WasmtimeStoreContextMut<'_>,
+    arrayref: Option<&wasmtime_arrayref_t>,
+    out: &mut MaybeUninit<u32>,
+) -> Option<Box<wasmtime_error_t>> {
+    let result = arrayref
+        .and_then(|a| a.as_wasmtime())
+        .ok_or_else(null_ref)
+        .and_then(|a| a.len(&cx));
+    handle_result(result, |len| crate::initialize(out, len))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_arrayref_get(
+    cx: WasmtimeStoreContextMut<'_>,
+    arrayref: Option<&wasmtime_arrayref_t>,
+    index: u32,
+    out: &mut MaybeUninit<wasmtime_val_t>,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let result = arrayref
+        .and_then(|a| a.as_wasmtime())
+        .ok_or_else(null_ref)
+        .and_then(|a| a.get(&mut scope, index));
+    handle_result(result, |val| {
+        crate::initialize(out, wasmtime_val_t::from_val(&mut scope, val))
+    })
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_arrayref_set(
+    cx: WasmtimeStoreContextMut<'_>,
+    arrayref: Option<&wasmtime_arrayref_t>,
+    index: u32,
+    val: &wasmtime_val_t,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let val = val.to_val(&mut scope);
+    let result = arrayref
+        .and_then(|a| a.as_wasmtime())
+        .ok_or_else(null_ref)
+        .and_then(|a| a.set(&mut scope, index, val));
+    handle_result(result, |()| {})
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_exnref_clone(
+    _cx: WasmtimeStoreContextMut<'_>,
+    exnref: Option<&wasmtime_exnref_t>,
+    out: &mut MaybeUninit<wasmtime_exnref_t>,
+) {
+    let exnref = exnref.and_then(|e| e.as_wasmtime());
+    crate::initialize(out, exnref.into());
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_exnref_unroot(
+    _cx: WasmtimeStoreContextMut<'_>,
+    val: Option<&mut MaybeUninit<wasmtime_exnref_t>>,
+) {
+    if let Some(val) = val.and_then(|v| v.assume_init_read().from_wasmtime()) {
+        drop(val);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_exnref_to_raw(
+    cx: WasmtimeStoreContextMut<'_>,
+    val: Option<&wasmtime_exnref_t>,
+) -> u32 {
+    val.and_then(|e| e.as_wasmtime())
+        .and_then(|e| e.to_raw(cx).ok())
+        .unwrap_or_default()
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_exnref_from_raw(
+    cx: WasmtimeStoreContextMut<'_>,
+    raw: u32,
+    val: &mut MaybeUninit<wasmtime_exnref_t>,
+) {
+    let mut scope = RootScope::new(cx);
+    let rooted =
+        ExnRef::from_raw(&mut scope, raw).map(|e| e.to_owned_rooted(&mut scope).expect("in scope"));
+    crate::initialize(val, rooted.into());
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_exnref_field_count(
+    cx: WasmtimeStoreContextMut<'_>,
+    exnref: Option<&wasmtime_exnref_t>,
+    out: &mut MaybeUninit<usize>,
+) -> Option<Box<wasmtime_error_t>> {
+    let result = exnref
+        .and_then(|e| e.as_wasmtime())
+        .ok_or_else(null_ref)
+        .and_then(|e| e.ty(&cx));
+    handle_result(result, |ty| crate::initialize(out, ty.fields().len()))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wasmtime_exnref_field(
+    cx: WasmtimeStoreContextMut<'_>,
+    exnref: Option<&wasmtime_exnref_t>,
+    index: usize,
+    out: &mut MaybeUninit<wasmtime_val_t>,
+) -> Option<Box<wasmtime_error_t>> {
+    let mut scope = RootScope::new(cx);
+    let result = exnref
+        .and_then(|e| e.as_wasmtime())
+        .ok_or_else(null_ref)
+        .and_then(|e| e.field(&mut scope, index));
+    handle_result(result, |val| {
+        crate::initialize(out, wasmtime_val_t::from_val(&mut scope, val))
+    })
+}
@@ -37,6 +37,8 @@ pub(crate) fn into_valtype(kind: wasm_valkind_t) -> ValType {
         WASM_EXTERNREF => ValType::EXTERNREF,
         WASM_FUNCREF => ValType::FUNCREF,
         WASMTIME_V128 => ValType::V128,
+        WASMTIME_ANYREF => ValType::ANYREF,
+        WASMTIME_EXNREF => ValType::EXNREF,
         _ => panic!("unexpected kind: {kind}"),
     }
 }
@@ -51,6 +53,8 @@ pub(crate) fn from_valtype(ty: &ValType) -> wasm_valkind_t {
         ValType::Ref(r) => match (r.is_nullable(), r.heap_type()) {
             (true, HeapType::Extern) => WASM_EXTERNREF,
             (true, HeapType::Func) => WASM_FUNCREF,
+            (true, HeapType::Any) => WASMTIME_ANYREF,
+            (true, HeapType::Exn) => WASMTIME_EXNREF,
             _ => crate::abort("support for non-externref and non-funcref references"),
         },
     }
@@ -65,3 +69,4 @@ pub const WASMTIME_V128: wasmtime_valkind_t = 4;
 pub const WASMTIME_FUNCREF: wasmtime_valkind_t = 5;
 pub const WASMTIME_EXTERNREF: wasmtime_valkind_t = 6;
 pub const WASMTIME_ANYREF: wasmtime_valkind_t = 7;
+pub const WASMTIME_EXNREF: wasmtime_valkind_t = 8;
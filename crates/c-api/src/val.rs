@@ -1,7 +1,7 @@
 use crate::r#ref::ref_to_val;
 use crate::{
     WASM_I32, WasmtimeStoreContextMut, from_valtype, into_valtype, wasm_ref_t, wasm_valkind_t,
-    wasmtime_anyref_t, wasmtime_externref_t, wasmtime_valkind_t,
+    wasmtime_anyref_t, wasmtime_exnref_t, wasmtime_externref_t, wasmtime_valkind_t,
 };
 use std::mem::{ManuallyDrop, MaybeUninit};
 use std::ptr;
@@ -148,12 +148,13 @@ pub union wasmtime_val_union {
     pub f64: u64,
     pub anyref: ManuallyDrop<wasmtime_anyref_t>,
     pub externref: ManuallyDrop<wasmtime_externref_t>,
+    pub exnref: ManuallyDrop<wasmtime_exnref_t>,
     pub funcref: wasmtime_func_t,
     pub v128: [u8; 16],
 }
 
 const _: () = {
-    // This is forced to 24 or 20 bytes by `anyref` and `externref`.
+    // This is forced to 24 or 20 bytes by `anyref`, `externref` and `exnref`.
     assert!(std::mem::size_of::<wasmtime_val_union>() <= 24);
     assert!(std::mem::align_of::<wasmtime_val_union>() == std::mem::align_of::<u64>());
 };
@@ -163,12 +164,14 @@ unsafe impl Send for wasmtime_val_union
 where
     Option<Box<wasmtime_anyref_t>>: Send,
     Option<Box<wasmtime_externref_t>>: Send,
+    Option<Box<wasmtime_exnref_t>>: Send,
 {
 }
 unsafe impl Sync for wasmtime_val_union
 where
     Option<Box<wasmtime_anyref_t>>: Sync,
     Option<Box<wasmtime_externref_t>>: Sync,
+    Option<Box<wasmtime_exnref_t>>: Sync,
 {
 }
 
@@ -252,7 +255,12 @@ impl wasmtime_val_t {
                     funcref: func.into(),
                 },
             },
-            Val::ExnRef(_) => crate::abort("exnrefs not yet supported in C API"),
+            Val::ExnRef(e) => wasmtime_val_t {
+                kind: crate::WASMTIME_EXNREF,
+                of: wasmtime_val_union {
+                    exnref: ManuallyDrop::new(e.and_then(|e| e.to_owned_rooted(cx).ok()).into()),
+                },
+            },
             Val::V128(val) => wasmtime_val_t {
                 kind: crate::WASMTIME_V128,
                 of: wasmtime_val_union {
@@ -291,6 +299,9 @@ impl wasmtime_val_t {
             crate::WASMTIME_EXTERNREF => {
                 Val::ExternRef(self.of.externref.as_wasmtime().map(|e| e.to_rooted(cx)))
             }
+            crate::WASMTIME_EXNREF => {
+                Val::ExnRef(self.of.exnref.as_wasmtime().map(|e| e.to_rooted(cx)))
+            }
             crate::WASMTIME_FUNCREF => Val::FuncRef(self.of.funcref.as_wasmtime()),
             other => panic!("unknown wasmtime_valkind_t: {other}"),
         }
@@ -314,6 +325,11 @@ pub unsafe extern "C" fn wasmtime_val_unroot(
                 drop(val);
             }
         }
+        crate::WASMTIME_EXNREF => {
+            if let Some(val) = ManuallyDrop::into_inner(val.of.exnref).from_wasmtime() {
+                drop(val);
+            }
+        }
         _ => {}
     }
 }
@@ -0,0 +1,208 @@
+#include "utils.h"
+
+#include <array>
+#include <gtest/gtest.h>
+#include <vector>
+#include <wasmtime.h>
+#include <wasmtime/component/resource.hh>
+
+static constexpr auto resources_component = std::string_view{
+    R"END(
+(component
+    (import "t" (type $t (sub resource)))
+    (core func $drop (canon resource.drop $t))
+
+    (core module $m
+        (import "" "drop" (func $drop (param i32)))
+        (func (export "pass") (param i32) (result i32)
+            (local.get 0))
+        (func (export "drop") (param i32)
+            (call $drop (local.get 0))))
+    (core instance $i (instantiate $m
+        (with "" (instance (export "drop" (func $drop))))
+    ))
+
+    (func (export "pass") (param "x" (own $t)) (result (own $t))
+        (canon lift (core func $i "pass")))
+    (func (export "drop") (param "x" (own $t))
+        (canon lift (core func $i "drop")))
+    (export "t" (type $t))
+)
+    )END",
+};
+
+static wasmtime_error_t *record_drop(void *data, wasmtime_context_t *context,
+                                     uint32_t rep) {
+  static_cast<std::vector<uint32_t> *>(data)->push_back(rep);
+  return nullptr;
+}
+
+TEST(component, resources) {
+  const auto engine = wasm_engine_new();
+  const auto store = wasmtime_store_new(engine, nullptr, nullptr);
+  const auto context = wasmtime_store_context(store);
+
+  wasmtime_component_t *component = nullptr;
+  auto err = wasmtime_component_new(
+      engine, reinterpret_cast<const uint8_t *>(resources_component.data()),
+      resources_component.size(), &component);
+  CHECK_ERR(err);
+
+  auto drops = std::vector<uint32_t>{};
+  const auto ty = wasmtime_component_resource_type_new_host(1);
+
+  const auto linker = wasmtime_component_linker_new(engine);
+  const auto root = wasmtime_component_linker_root(linker);
+  err = wasmtime_component_linker_instance_add_resource(
+      root, "t", 1, ty, record_drop, &drops, nullptr);
+  CHECK_ERR(err);
+  wasmtime_component_linker_instance_delete(root);
+
+  wasmtime_component_instance_t instance = {};
+  err = wasmtime_component_linker_instantiate(linker, context, component,
+                                              &instance);
+  CHECK_ERR(err);
+
+  const auto t_index = wasmtime_component_instance_get_export_index(
+      &instance, context, nullptr, "t", 1);
+  EXPECT_NE(t_index, nullptr);
+  wasmtime_component_resource_type_t *t_ty = nullptr;
+  EXPECT_TRUE(wasmtime_component_instance_get_resource(&instance, context,
+                                                       t_index, &t_ty));
+  EXPECT_TRUE(wasmtime_component_resource_type_equal(t_ty, ty));
+
+  const auto other_ty = wasmtime_component_resource_type_new_host(2);
+  EXPECT_FALSE(wasmtime_component_resource_type_equal(t_ty, other_ty));
+
+  const auto pass_index = wasmtime_component_instance_get_export_index(
+      &instance, context, nullptr, "pass", 4);
+  const auto drop_index = wasmtime_component_instance_get_export_index(
+      &instance, context, nullptr, "drop", 4);
+  wasmtime_component_func_t pass = {};
+  wasmtime_component_func_t drop = {};
+  EXPECT_TRUE(
+      wasmtime_component_instance_get_func(&instance, context, pass_index, &pass));
+  EXPECT_TRUE(
+      wasmtime_component_instance_get_func(&instance, context, drop_index, &drop));
+
+  // Round-trip an owned host resource through the guest.
+  const auto host = wasmtime_component_resource_host_new(true, 100, 1);
+  wasmtime_component_resource_any_t *any = nullptr;
+  err = wasmtime_component_resource_host_to_any(context, host, &any);
+  CHECK_ERR(err);
+
+  auto params = std::array<wasmtime_component_val_t, 1>{
+      wasmtime_component_val_t{
+          .kind = WASMTIME_COMPONENT_RESOURCE,
+          .of = {.resource = any},
+      },
+  };
+  auto results = std::array<wasmtime_component_val_t, 1>{};
+  err = wasmtime_component_func_call(&pass, context, params.data(),
+                                     params.size(), results.data(),
+                                     results.size());
+  CHECK_ERR(err);
+  err = wasmtime_component_func_post_return(&pass, context);
+  CHECK_ERR(err);
+
+  EXPECT_EQ(results[0].kind, WASMTIME_COMPONENT_RESOURCE);
+  const auto result = results[0].of.resource;
+  EXPECT_TRUE(wasmtime_component_resource_any_owned(result));
+  const auto result_ty = wasmtime_component_resource_any_type(result);
+  EXPECT_TRUE(wasmtime_component_resource_type_equal(result_ty, ty));
+
+  wasmtime_component_resource_host_t *result_host = nullptr;
+  err = wasmtime_component_resource_any_to_host(context, result, 1,
+                                                &result_host);
+  CHECK_ERR(err);
+  EXPECT_EQ(wasmtime_component_resource_host_rep(result_host), 100);
+  EXPECT_EQ(wasmtime_component_resource_host_type(result_host), 1);
+  EXPECT_TRUE(wasmtime_component_resource_host_owned(result_host));
+
+  // The guest dropping an owned resource runs the linker's destructor.
+  wasmtime_component_resource_any_t *drop_any = nullptr;
+  err = wasmtime_component_resource_host_to_any(context, result_host,
+                                                &drop_any);
+  CHECK_ERR(err);
+  auto drop_params = std::array<wasmtime_component_val_t, 1>{
+      wasmtime_component_val_t{
+          .kind = WASMTIME_COMPONENT_RESOURCE,
+          .of = {.resource = drop_any},
+      },
+  };
+  err = wasmtime_component_func_call(&drop, context, drop_params.data(),
+                                     drop_params.size(), nullptr, 0);
+  CHECK_ERR(err);
+  err = wasmtime_component_func_post_return(&drop, context);
+  CHECK_ERR(err);
+  EXPECT_EQ(drops, std::vector<uint32_t>{100});
+
+  // Owned host resources can also be destroyed by the embedder.
+  const auto dropped_host = wasmtime_component_resource_host_new(true, 200, 1);
+  wasmtime_component_resource_any_t *dropped_any = nullptr;
+  err = wasmtime_component_resource_host_to_any(context, dropped_host,
+                                                &dropped_any);
+  CHECK_ERR(err);
+  err = wasmtime_component_resource_any_drop(context, dropped_any);
+  CHECK_ERR(err);
+
+  wasmtime_component_resource_any_delete(dropped_any);
+  wasmtime_component_resource_host_delete(dropped_host);
+  wasmtime_component_val_delete(&drop_params[0]);
+  wasmtime_component_resource_host_delete(result_host);
+  wasmtime_component_resource_type_delete(result_ty);
+  wasmtime_component_val_delete(&results[0]);
+  wasmtime_component_val_delete(&params[0]);
+  wasmtime_component_resource_host_delete(host);
+  wasmtime_component_export_index_delete(drop_index);
+  wasmtime_component_export_index_delete(pass_index);
+  wasmtime_component_resource_type_delete(other_ty);
+  wasmtime_component_resource_type_delete(t_ty);
+  wasmtime_component_export_index_delete(t_index);
+  wasmtime_component_linker_delete(linker);
+  wasmtime_component_resource_type_delete(ty);
+  wasmtime_component_delete(component);
+  wasmtime_store_delete(store);
+  wasm_engine_delete(engine);
+}
+
+TEST(component, resources_cpp) {
+  using namespace wasmtime;
+  using namespace wasmtime::component;
+
+  const auto ty = ResourceType::host(1);
+  EXPECT_EQ(ty, ResourceType::host(1));
+  EXPECT_NE(ty, ResourceType::host(2));
+
+  Engine engine;
+  Store store(engine);
+
+  auto drops = std::vector<uint32_t>{};
+  const auto linker = wasmtime_component_linker_new(engine.capi());
+  const auto root = wasmtime_component_linker_root(linker);
+  ty.define(root, "t",
+            [&drops](Store::Context cx, uint32_t rep) -> Result<std::monostate> {
+              drops.push_back(rep);
+              return std::monostate();
+            })
+      .unwrap();
+  wasmtime_component_linker_instance_delete(root);
+  wasmtime_component_linker_delete(linker);
+
+  // Host resources round-trip through `ResourceAny` within a store.
+  ResourceHost host(true, 7, 1);
+  auto any = host.to_any(store).unwrap();
+  EXPECT_TRUE(any.owned());
+  EXPECT_EQ(any.type(), ty);
+  EXPECT_FALSE(any.to_host(store, 2));
+  auto back = any.to_host(store, 1).unwrap();
+  EXPECT_EQ(back.rep(), 7);
+  EXPECT_EQ(back.type(), 1);
+  EXPECT_TRUE(back.owned());
+
+
+  // Owned resources created by the host have no destructor attached.
+  auto owned_any = ResourceHost(true, 8, 1).to_any(store).unwrap();
+  owned_any.drop(store).unwrap();
+  EXPECT_TRUE(drops.empty());
+}
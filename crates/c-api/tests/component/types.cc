@@ -0,0 +1,169 @@
+#include "utils.h"
+
+#include <gtest/gtest.h>
+#include <wasmtime.h>
+#include <wasmtime/component/types.hh>
+
+static constexpr auto types_component = std::string_view{
+    R"END(
+(component
+    (import "r" (type $r (sub resource)))
+    (type $rec' (record (field "a" u8) (field "b" string)))
+    (import "rec" (type $rec (eq $rec')))
+    (type $e' (enum "x" "y"))
+    (import "e" (type $e (eq $e')))
+    (import "f" (func
+        (param "x" (borrow $r))
+        (param "y" (list string))
+        (param "z" $rec)
+        (result (result u32 (error $e)))))
+    (import "i" (instance
+        (export "g" (func (result (tuple u8 (option s64)))))))
+    (core module $m (func (export "h")))
+    (export "m" (core module $m))
+)
+    )END",
+};
+
+static std::string_view item_name(const char *name, size_t len) {
+  return std::string_view(name, len);
+}
+
+TEST(component, types) {
+  const auto engine = wasm_engine_new();
+
+  wasmtime_component_t *component = nullptr;
+  auto err = wasmtime_component_new(
+      engine, reinterpret_cast<const uint8_t *>(types_component.data()),
+      types_component.size(), &component);
+  CHECK_ERR(err);
+
+  const auto ty = wasmtime_component_type(component);
+  EXPECT_EQ(wasmtime_component_type_import_count(ty, engine), 3);
+  EXPECT_EQ(wasmtime_component_type_export_count(ty, engine), 1);
+
+  const char *name = nullptr;
+  size_t name_len = 0;
+  wasmtime_component_item_t item;
+  EXPECT_TRUE(wasmtime_component_type_import_nth(ty, engine, 0, &name,
+                                                 &name_len, &item));
+  EXPECT_EQ(item_name(name, name_len), "r");
+  EXPECT_EQ(item.kind, WASMTIME_COMPONENT_ITEM_RESOURCE);
+  wasmtime_component_item_delete(&item);
+  EXPECT_FALSE(wasmtime_component_type_import_nth(ty, engine, 3, &name,
+                                                  &name_len, &item));
+
+  EXPECT_TRUE(wasmtime_component_type_import_get(ty, engine, "f", 1, &item));
+  EXPECT_EQ(item.kind, WASMTIME_COMPONENT_ITEM_COMPONENT_FUNC);
+  const auto func = item.of.component_func;
+  EXPECT_EQ(wasmtime_component_func_type_param_count(func), 3);
+
+  wasmtime_component_valtype_t param;
+  EXPECT_TRUE(wasmtime_component_func_type_param_nth(func, 0, &name,
+                                                     &name_len, &param));
+  EXPECT_EQ(item_name(name, name_len), "x");
+  EXPECT_EQ(param.kind, WASMTIME_COMPONENT_VALTYPE_BORROW);
+  wasmtime_component_valtype_delete(&param);
+
+  EXPECT_TRUE(wasmtime_component_func_type_param_nth(func, 1, &name,
+                                                     &name_len, &param));
+  EXPECT_EQ(param.kind, WASMTIME_COMPONENT_VALTYPE_LIST);
+  wasmtime_component_valtype_t elem;
+  wasmtime_component_list_type_element(param.of.list, &elem);
+  EXPECT_EQ(elem.kind, WASMTIME_COMPONENT_VALTYPE_STRING);
+  wasmtime_component_valtype_delete(&elem);
+  wasmtime_component_valtype_delete(&param);
+
+  EXPECT_TRUE(wasmtime_component_func_type_param_nth(func, 2, &name,
+                                                     &name_len, &param));
+  EXPECT_EQ(param.kind, WASMTIME_COMPONENT_VALTYPE_RECORD);
+  EXPECT_EQ(wasmtime_component_record_type_field_count(param.of.record), 2);
+  wasmtime_component_valtype_t field;
+  EXPECT_TRUE(wasmtime_component_record_type_field_nth(param.of.record, 1,
+                                                       &name, &name_len, &field));
+  EXPECT_EQ(item_name(name, name_len), "b");
+  EXPECT_EQ(field.kind, WASMTIME_COMPONENT_VALTYPE_STRING);
+  wasmtime_component_valtype_delete(&field);
+  wasmtime_component_valtype_delete(&param);
+
+  wasmtime_component_valtype_t result;
+  EXPECT_TRUE(wasmtime_component_func_type_result(func, &result));
+  EXPECT_EQ(result.kind, WASMTIME_COMPONENT_VALTYPE_RESULT);
+  wasmtime_component_valtype_t err_ty;
+  EXPECT_TRUE(wasmtime_component_result_type_err(result.of.result, &err_ty));
+  EXPECT_EQ(err_ty.kind, WASMTIME_COMPONENT_VALTYPE_ENUM);
+  EXPECT_EQ(wasmtime_component_enum_type_names_count(err_ty.of.enumeration),
+            2);
+  wasmtime_component_valtype_delete(&err_ty);
+  wasmtime_component_valtype_delete(&result);
+  wasmtime_component_item_delete(&item);
+
+  EXPECT_TRUE(wasmtime_component_type_export_get(ty, engine, "m", 1, &item));
+  EXPECT_EQ(item.kind, WASMTIME_COMPONENT_ITEM_MODULE);
+  wasm_exporttype_vec_t exports;
+  wasmtime_component_module_type_exports(item.of.module, engine, &exports);
+  EXPECT_EQ(exports.size, 1);
+  wasm_exporttype_vec_delete(&exports);
+  wasmtime_component_item_delete(&item);
+
+  EXPECT_FALSE(wasmtime_component_type_export_get(ty, engine, "f", 1, &item));
+
+  wasmtime_component_type_delete(ty);
+  wasmtime_component_delete(component);
+  wasm_engine_delete(engine);
+}
+
+TEST(component, types_cpp) {
+  using namespace wasmtime::component;
+
+  wasmtime::Engine engine;
+  wasmtime_component_t *component = nullptr;
+  auto err = wasmtime_component_new(
+      engine.capi(), reinterpret_cast<const uint8_t *>(types_component.data()),
+      types_component.size(), &component);
+  CHECK_ERR(err);
+
+  const auto ty = ComponentType::of(component);
+  const auto imports = ty.imports(engine);
+  EXPECT_EQ(imports.size(), 3);
+  EXPECT_EQ(imports[0].first, "r");
+
+  const auto instance = ty.get_import(engine, "i");
+  EXPECT_TRUE(instance);
+  EXPECT_EQ(instance->kind(), WASMTIME_COMPONENT_ITEM_COMPONENT_INSTANCE);
+  const auto exports = instance->component_instance().exports(engine);
+  EXPECT_EQ(exports.size(), 1);
+  EXPECT_EQ(exports[0].first, "g");
+
+  const auto g = exports[0].second.component_func();
+  EXPECT_TRUE(g.params().empty());
+  const auto result = g.result();
+  EXPECT_TRUE(result);
+  EXPECT_EQ(result->kind(), WASMTIME_COMPONENT_VALTYPE_TUPLE);
+  const auto types = result->tuple_types();
+  EXPECT_EQ(types.size(), 2);
+  EXPECT_EQ(types[0].kind(), WASMTIME_COMPONENT_VALTYPE_U8);
+  EXPECT_EQ(types[1].kind(), WASMTIME_COMPONENT_VALTYPE_OPTION);
+  EXPECT_EQ(types[1].option_type().kind(), WASMTIME_COMPONENT_VALTYPE_S64);
+  const ValType copy = types[1];
+  EXPECT_EQ(copy, types[1]);
+  EXPECT_NE(copy, types[0]);
+
+  const auto f = ty.get_import(engine, "f")->component_func();
+  const auto params = f.params();
+  EXPECT_EQ(params.size(), 3);
+  EXPECT_EQ(params[0].second.resource_type(),
+            ty.get_import(engine, "r")->resource());
+
+  const auto f_result = f.result();
+  EXPECT_TRUE(f_result);
+  const auto err_ty = f_result->result_err();
+  EXPECT_TRUE(err_ty);
+  const auto names = err_ty->enum_names();
+  EXPECT_EQ(names.size(), 2);
+  EXPECT_EQ(names[1], "y");
+
+  EXPECT_FALSE(ty.get_export(engine, "missing"));
+
+  wasmtime_component_delete(component);
+}
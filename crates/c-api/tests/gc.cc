@@ -0,0 +1,122 @@
+#include <wasmtime/gc.hh>
+
+#include <gtest/gtest.h>
+#include <wasmtime.hh>
+
+using namespace wasmtime;
+
+static Engine gc_engine() {
+  Config config;
+  config.wasm_gc(true);
+  config.wasm_function_references(true);
+  config.wasm_exceptions(true);
+  return Engine(std::move(config));
+}
+
+TEST(GC, StructRef) {
+  Engine engine = gc_engine();
+  Store store(engine);
+
+  StructType ty = StructType::create(engine, {
+                                                 FieldType(StorageKind::I32, true),
+                                                 FieldType(StorageKind::I8),
+                                                 FieldType(StorageKind::AnyRef, true),
+                                             })
+                      .unwrap();
+  EXPECT_EQ(ty.field_count(), 3);
+  EXPECT_EQ(ty.field(0)->kind(), StorageKind::I32);
+  EXPECT_TRUE(ty.field(0)->is_mutable());
+  EXPECT_EQ(ty.field(1)->kind(), StorageKind::I8);
+  EXPECT_FALSE(ty.field(1)->is_mutable());
+  EXPECT_FALSE(ty.field(3));
+
+  StructRefPre pre(store, ty);
+  StructRef s = StructRef::create(store, pre,
+                                  {int32_t(1), int32_t(0x1ff),
+                                   std::optional<AnyRef>(std::nullopt)})
+                    .unwrap();
+
+  EXPECT_EQ(s.field(store, 0).unwrap().i32(), 1);
+  EXPECT_EQ(s.field(store, 1).unwrap().i32(), 0xff);
+  EXPECT_EQ(s.field(store, 2).unwrap().anyref(store), std::nullopt);
+
+  s.set_field(store, 0, int32_t(5)).unwrap();
+  EXPECT_EQ(s.field(store, 0).unwrap().i32(), 5);
+  s.set_field(store, 2, AnyRef::i31(store, 7)).unwrap();
+  EXPECT_EQ(s.field(store, 2).unwrap().anyref(store)->i31(store), 7);
+
+  // Immutable fields, mistyped values and out-of-bounds indices are errors.
+  EXPECT_FALSE(s.set_field(store, 1, int32_t(2)));
+  EXPECT_FALSE(s.set_field(store, 0, int64_t(2)));
+  EXPECT_FALSE(s.field(store, 3));
+  EXPECT_FALSE(StructRef::create(store, pre, {int32_t(1)}));
+
+  AnyRef any = s.to_anyref(store);
+  EXPECT_EQ(StructRef::from_anyref(store, any)->field(store, 0).unwrap().i32(),
+            5);
+  EXPECT_FALSE(ArrayRef::from_anyref(store, any));
+  EXPECT_FALSE(StructRef::from_anyref(store, AnyRef::i31(store, 1)));
+
+  any.unroot(store);
+  s.unroot(store);
+}
+
+TEST(GC, ArrayRef) {
+  Engine engine = gc_engine();
+  Store store(engine);
+
+  ArrayType ty =
+      ArrayType::create(engine, FieldType(StorageKind::I64, true)).unwrap();
+  EXPECT_EQ(ty.field()->kind(), StorageKind::I64);
+  EXPECT_TRUE(ty.field()->is_mutable());
+
+  ArrayRefPre pre(store, ty);
+  ArrayRef a = ArrayRef::create(store, pre, int64_t(7), 3).unwrap();
+  EXPECT_EQ(a.len(store).unwrap(), 3);
+  EXPECT_EQ(a.get(store, 2).unwrap().i64(), 7);
+
+  a.set(store, 1, int64_t(9)).unwrap();
+  EXPECT_EQ(a.get(store, 1).unwrap().i64(), 9);
+  EXPECT_FALSE(a.get(store, 3));
+  EXPECT_FALSE(a.set(store, 0, int32_t(1)));
+
+  AnyRef any = a.to_anyref(store);
+  EXPECT_EQ(ArrayRef::from_anyref(store, any)->len(store).unwrap(), 3);
+  EXPECT_FALSE(StructRef::from_anyref(store, any));
+
+  any.unroot(store);
+  a.unroot(store);
+}
+
+TEST(GC, ExnRef) {
+  Engine engine = gc_engine();
+  Module m = Module::compile(engine, "(module"
+                                     "  (tag $t (param i32 i64))"
+                                     "  (func (export \"f\") (result exnref)"
+                                     "    (block $b (result exnref)"
+                                     "      (try_table (catch_all_ref $b)"
+                                     "        (throw $t (i32.const 42)"
+                                     "                  (i64.const 7)))"
+                                     "      unreachable)))")
+                 .unwrap();
+
+  Store store(engine);
+  Instance i = Instance::create(store, m, {}).unwrap();
+  Func f = std::get<Func>(*i.get(store, "f"));
+  auto results = f.call(store, {}).unwrap();
+  EXPECT_EQ(results.size(), 1);
+  EXPECT_EQ(results[0].kind(), ValKind::ExnRef);
+
+  ExnRef exn = *results[0].exnref(store);
+  EXPECT_EQ(exn.field_count(store).unwrap(), 2);
+  EXPECT_EQ(exn.field(store, 0).unwrap().i32(), 42);
+  EXPECT_EQ(exn.field(store, 1).unwrap().i64(), 7);
+  EXPECT_FALSE(exn.field(store, 2));
+
+  Val null = std::optional<ExnRef>(std::nullopt);
+  EXPECT_EQ(null.kind(), ValKind::ExnRef);
+  EXPECT_EQ(null.exnref(store), std::nullopt);
+
+  exn.unroot(store);
+  results[0].unroot(store);
+}
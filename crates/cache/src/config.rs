@@ -1,5 +1,6 @@
 //! Module for configuring the cache system.
 
+use crate::ModuleCacheStore;
 use anyhow::{Context, Result, anyhow, bail};
 use directories_next::ProjectDirs;
 use log::{trace, warn};
@@ -10,6 +11,7 @@ use serde::{
 use std::fmt::Debug;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 
 // wrapped, so we have named section in config,
@@ -25,6 +27,10 @@ struct Config {
 #[serde(deny_unknown_fields)]
 pub struct CacheConfig {
     directory: Option<PathBuf>,
+    #[serde(default, rename = "read-only-directories")]
+    read_only_directories: Vec<PathBuf>,
+    #[serde(skip)]
+    store: Option<Arc<dyn ModuleCacheStore>>,
     #[serde(
         default = "default_worker_event_queue_size",
         rename = "worker-event-queue-size",
@@ -95,6 +101,8 @@ impl Default for CacheConfig {
     fn default() -> Self {
         Self {
             directory: None,
+            read_only_directories: Vec::new(),
+            store: None,
             worker_event_queue_size: default_worker_event_queue_size(),
             baseline_compression_level: default_baseline_compression_level(),
             optimized_compression_level: default_optimized_compression_level(),
@@ -391,6 +399,39 @@ impl CacheConfig {
         self
     }
 
+    /// Returns the read-only cache directories.
+    pub fn read_only_directories(&self) -> &[PathBuf] {
+        &self.read_only_directories
+    }
+
+    /// Adds a read-only cache directory, which is consulted, after any
+    /// previously added ones, when compiled code isn't found in the cache.
+    /// Must be an absolute path.
+    ///
+    /// Read-only directories have the same layout as the cache directory, so
+    /// a cache directory populated ahead of time, for example one baked into
+    /// a container image, can be used. They are never written to or cleaned
+    /// up.
+    pub fn with_read_only_directory(&mut self, directory: impl Into<PathBuf>) -> &mut Self {
+        self.read_only_directories.push(directory.into());
+        self
+    }
+
+    /// Returns the custom cache store if one is set.
+    pub fn store(&self) -> Option<&Arc<dyn ModuleCacheStore>> {
+        self.store.as_ref()
+    }
+
+    /// Stores compiled code in `store` instead of the cache directory.
+    ///
+    /// The cache worker doesn't recompress or clean up entries in custom
+    /// stores. Read-only directories are still consulted when the store
+    /// doesn't have an entry.
+    pub fn with_store(&mut self, store: Arc<dyn ModuleCacheStore>) -> &mut Self {
+        self.store = Some(store);
+        self
+    }
+
     /// Size of cache worker event queue. If the queue is full, incoming cache usage events will be
     /// dropped.
     pub fn with_worker_event_queue_size(&mut self, size: u64) -> &mut Self {
@@ -506,6 +547,7 @@ impl CacheConfig {
     /// validate values and fill in defaults
     pub(crate) fn validate(&mut self) -> Result<()> {
         self.validate_directory_or_default()?;
+        self.validate_read_only_directories()?;
         self.validate_worker_event_queue_size();
         self.validate_baseline_compression_level()?;
         self.validate_optimized_compression_level()?;
@@ -549,6 +591,18 @@ impl CacheConfig {
         Ok(())
     }
 
+    fn validate_read_only_directories(&self) -> Result<()> {
+        for dir in &self.read_only_directories {
+            if !dir.is_absolute() {
+                bail!(
+                    "Read-only cache directory path has to be absolute, path: {}",
+                    dir.display(),
+                );
+            }
+        }
+        Ok(())
+    }
+
     fn validate_worker_event_queue_size(&self) {
         if self.worker_event_queue_size < worker_event_queue_size_warning_threshold() {
             warn!("Detected small worker event queue size. Some messages might be lost.");
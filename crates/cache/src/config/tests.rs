@@ -513,6 +513,30 @@ fn test_builder_default() {
     );
 }
 
+#[test]
+fn test_read_only_directories_settings() {
+    let (_td, cd, cp) = test_prolog();
+    let conf = load_config!(
+        cp,
+        "[cache]\n\
+         directory = '{cache_dir}'\n\
+         read-only-directories = ['/opt/cache-a', '/opt/cache-b']",
+        cd
+    );
+    assert_eq!(
+        conf.read_only_directories(),
+        [PathBuf::from("/opt/cache-a"), PathBuf::from("/opt/cache-b")]
+    );
+
+    bad_config!(
+        cp,
+        "[cache]\n\
+         directory = '{cache_dir}'\n\
+         read-only-directories = ['relative/dir']",
+        cd
+    );
+}
+
 #[test]
 fn test_builder_all_settings() {
     let (_td, cd, _cp) = test_prolog();
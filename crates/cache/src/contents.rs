@@ -0,0 +1,168 @@
+//! Inspecting and managing the contents of the cache directory.
+
+use crate::worker::{self, CacheEntry};
+use crate::{Cache, CacheConfig};
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A compiled module stored in the cache directory, as returned by
+/// [`Cache::modules`].
+#[derive(Debug, Clone)]
+pub struct CachedModule {
+    path: PathBuf,
+    size: u64,
+    usages: u64,
+}
+
+impl CachedModule {
+    /// Returns the path of the file holding the compressed module.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the name and version of the compiler which produced the module.
+    pub fn compiler(&self) -> &str {
+        file_name(self.path.parent())
+    }
+
+    /// Returns the hash of the module's contents and compilation settings.
+    pub fn hash(&self) -> &str {
+        file_name(Some(&self.path))
+    }
+
+    /// Returns the size of the compressed module in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns how many times the module was written to or read from the
+    /// cache, as recorded by the cache worker.
+    pub fn usages(&self) -> u64 {
+        self.usages
+    }
+}
+
+fn file_name(path: Option<&Path>) -> &str {
+    path.and_then(Path::file_name)
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+}
+
+/// A summary of the contents of the cache directory, as returned by
+/// [`Cache::stats`].
+#[derive(Debug, Clone, Default)]
+pub struct CacheStats {
+    /// Number of compiled modules in the cache.
+    pub modules: u64,
+    /// Total size of the compiled modules in bytes.
+    pub modules_size: u64,
+    /// Number of other files and directories, such as locks, orphaned
+    /// statistics and files not created by Wasmtime.
+    pub other_files: u64,
+    /// Number of times a module was found in the cache, across all processes
+    /// using the cache directory.
+    pub hits: u64,
+    /// Number of times a module had to be compiled and written to the cache,
+    /// counting only modules which are still in the cache.
+    pub misses: u64,
+}
+
+impl Cache {
+    /// Lists the compiled modules in the cache directory.
+    ///
+    /// Read-only directories and custom stores are not included.
+    pub fn modules(&self) -> Vec<CachedModule> {
+        let mut modules = worker::list_cache_contents(&self.config)
+            .into_iter()
+            .filter_map(|entry| match entry {
+                CacheEntry::Recognized { path, size, .. } => {
+                    let usages = worker::read_usages(&path);
+                    Some(CachedModule { path, size, usages })
+                }
+                CacheEntry::Unrecognized { .. } => None,
+            })
+            .collect::<Vec<_>>();
+        modules.sort_by(|a, b| a.path.cmp(&b.path));
+        modules
+    }
+
+    /// Summarizes the contents of the cache directory.
+    ///
+    /// Hits and misses are derived from the usage counters kept by the cache
+    /// worker, so unlike [`Cache::cache_hits`] they cover all processes which
+    /// have used the cache directory.
+    pub fn stats(&self) -> CacheStats {
+        let mut stats = CacheStats::default();
+        for entry in worker::list_cache_contents(&self.config) {
+            match entry {
+                CacheEntry::Recognized { path, size, .. } => {
+                    let usages = worker::read_usages(&path);
+                    stats.modules += 1;
+                    stats.modules_size += size;
+                    stats.hits += usages.saturating_sub(1);
+                    stats.misses += 1;
+                }
+                CacheEntry::Unrecognized { .. } => stats.other_files += 1,
+            }
+        }
+        stats
+    }
+
+    /// Cleans up the cache directory now, rather than waiting for the cache
+    /// worker's next cleanup.
+    ///
+    /// Unrecognized files are deleted, as are the least recently used modules
+    /// if the `file-count-soft-limit` or `files-total-size-soft-limit` is
+    /// exceeded.
+    ///
+    /// This fails if a cleanup is already in progress, whether started by
+    /// the cache worker or by another call to this method, but otherwise
+    /// runs regardless of when the cache worker last cleaned up.
+    pub fn prune(&self) -> Result<()> {
+        if !worker::run_clean_up(&self.config) {
+            bail!(
+                "the cache in `{}` is already being cleaned up",
+                self.directory().display()
+            );
+        }
+        Ok(())
+    }
+
+    /// Deletes everything in the cache directory, apart from the locks of
+    /// tasks the cache worker is still running.
+    pub fn clear(&self) -> Result<()> {
+        clear_dir(self.directory(), 0, &self.config)
+    }
+}
+
+fn clear_dir(dir: &Path, level: u8, config: &CacheConfig) -> Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        // Nothing has been cached yet.
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => {
+            return Err(err).with_context(|| format!("failed to list `{}`", dir.display()));
+        }
+    };
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        if worker::is_active_lock(&entry, level, config) {
+            continue;
+        }
+        let result = if path.is_dir() {
+            clear_dir(&path, level + 1, config)?;
+            // Directories still holding a lock are left in place.
+            if fs::read_dir(&path)?.next().is_some() {
+                continue;
+            }
+            fs::remove_dir(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        result.with_context(|| format!("failed to delete `{}`", path.display()))?;
+    }
+    Ok(())
+}
@@ -21,9 +21,13 @@ use std::{fs, io};
 
 #[macro_use] // for tests
 mod config;
+mod contents;
+mod store;
 mod worker;
 
 pub use config::{CacheConfig, create_new_config};
+pub use contents::{CacheStats, CachedModule};
+pub use store::ModuleCacheStore;
 use worker::Worker;
 
 /// Global configuration for how the cache is managed
@@ -117,14 +121,22 @@ impl Cache {
         self.state.misses.load(SeqCst)
     }
 
-    pub(crate) fn on_cache_get_async(&self, path: impl AsRef<Path>) {
+    /// Records a cache hit, notifying the worker if the data came from the
+    /// cache directory.
+    pub(crate) fn on_cache_get_async(&self, path: Option<PathBuf>) {
         self.state.hits.fetch_add(1, SeqCst);
-        self.worker.on_cache_get_async(path)
+        if let Some(path) = path {
+            self.worker.on_cache_get_async(path)
+        }
     }
 
-    pub(crate) fn on_cache_update_async(&self, path: impl AsRef<Path>) {
+    /// Records a cache miss, notifying the worker if the computed data was
+    /// written to the cache directory.
+    pub(crate) fn on_cache_update_async(&self, path: Option<PathBuf>) {
         self.state.misses.fetch_add(1, SeqCst);
-        self.worker.on_cache_update_async(path)
+        if let Some(path) = path {
+            self.worker.on_cache_update_async(path)
+        }
     }
 }
 
@@ -138,10 +150,19 @@ struct CacheState {
 pub struct ModuleCacheEntry<'cache>(Option<ModuleCacheEntryInner<'cache>>);
 
 struct ModuleCacheEntryInner<'cache> {
+    compiler_dir: String,
     root_path: PathBuf,
     cache: &'cache Cache,
 }
 
+/// Where cached data was read from or written to.
+enum Location {
+    /// The cache directory, which is managed by the cache worker.
+    Directory,
+    /// A configured [`ModuleCacheStore`] or read-only directory.
+    Elsewhere,
+}
+
 struct Sha256Hasher(Sha256);
 
 impl<'cache> ModuleCacheEntry<'cache> {
@@ -201,18 +222,18 @@ impl<'cache> ModuleCacheEntry<'cache> {
         // standard encoding uses '/' which can't be used for filename
         let hash = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(&hash);
 
-        if let Some(cached_val) = inner.get_data(&hash) {
+        if let Some((cached_val, location)) = inner.get_data(&hash) {
             if let Some(val) = deserialize(state, cached_val) {
-                let mod_cache_path = inner.root_path.join(&hash);
-                inner.cache.on_cache_get_async(&mod_cache_path); // call on success
+                let mod_cache_path = inner.directory_path(&hash, location);
+                inner.cache.on_cache_get_async(mod_cache_path); // call on success
                 return Ok(val);
             }
         }
         let val_to_cache = compute(state)?;
         if let Some(bytes) = serialize(state, &val_to_cache) {
-            if inner.update_data(&hash, &bytes).is_some() {
-                let mod_cache_path = inner.root_path.join(&hash);
-                inner.cache.on_cache_update_async(&mod_cache_path); // call on success
+            if let Some(location) = inner.update_data(&hash, &bytes) {
+                let mod_cache_path = inner.directory_path(&hash, location);
+                inner.cache.on_cache_update_async(mod_cache_path); // call on success
             }
         }
         Ok(val_to_cache)
@@ -254,24 +275,60 @@ impl<'cache> ModuleCacheEntryInner<'cache> {
                 comp_ver = env!("GIT_REV"),
             )
         };
-        let root_path = cache.directory().join("modules").join(compiler_dir);
+        let root_path = cache.directory().join("modules").join(&compiler_dir);
 
-        Self { root_path, cache }
+        Self {
+            compiler_dir,
+            root_path,
+            cache,
+        }
     }
 
-    fn get_data(&self, hash: &str) -> Option<Vec<u8>> {
-        let mod_cache_path = self.root_path.join(hash);
-        trace!("get_data() for path: {}", mod_cache_path.display());
-        let compressed_cache_bytes = fs::read(&mod_cache_path).ok()?;
+    fn directory_path(&self, hash: &str, location: Location) -> Option<PathBuf> {
+        match location {
+            Location::Directory => Some(self.root_path.join(hash)),
+            Location::Elsewhere => None,
+        }
+    }
+
+    fn get_data(&self, hash: &str) -> Option<(Vec<u8>, Location)> {
+        let (compressed_cache_bytes, location) = self.read_compressed(hash)?;
         let cache_bytes = zstd::decode_all(&compressed_cache_bytes[..])
             .map_err(|err| warn!("Failed to decompress cached code: {err}"))
             .ok()?;
-        Some(cache_bytes)
+        Some((cache_bytes, location))
     }
 
-    fn update_data(&self, hash: &str, serialized_data: &[u8]) -> Option<()> {
-        let mod_cache_path = self.root_path.join(hash);
-        trace!("update_data() for path: {}", mod_cache_path.display());
+    /// Looks `hash` up in the configured store, or the cache directory if
+    /// there isn't one, and then in each read-only directory in turn.
+    fn read_compressed(&self, hash: &str) -> Option<(Vec<u8>, Location)> {
+        if let Some(store) = self.cache.config.store() {
+            let key = format!("{}/{hash}", self.compiler_dir);
+            trace!("get_data() for key: {key}");
+            if let Some(bytes) = store.get(&key) {
+                return Some((bytes.into_owned(), Location::Elsewhere));
+            }
+        } else {
+            let mod_cache_path = self.root_path.join(hash);
+            trace!("get_data() for path: {}", mod_cache_path.display());
+            if let Ok(bytes) = fs::read(&mod_cache_path) {
+                return Some((bytes, Location::Directory));
+            }
+        }
+
+        self.cache
+            .config
+            .read_only_directories()
+            .iter()
+            .find_map(|dir| {
+                let path = dir.join("modules").join(&self.compiler_dir).join(hash);
+                trace!("get_data() for read-only path: {}", path.display());
+                fs::read(path).ok()
+            })
+            .map(|bytes| (bytes, Location::Elsewhere))
+    }
+
+    fn update_data(&self, hash: &str, serialized_data: &[u8]) -> Option<Location> {
         let compressed_data = zstd::encode_all(
             &serialized_data[..],
             self.cache.baseline_compression_level(),
@@ -279,10 +336,23 @@ impl<'cache> ModuleCacheEntryInner<'cache> {
         .map_err(|err| warn!("Failed to compress cached code: {err}"))
         .ok()?;
 
+        if let Some(store) = self.cache.config.store() {
+            let key = format!("{}/{hash}", self.compiler_dir);
+            trace!("update_data() for key: {key}");
+            if store.insert(&key, compressed_data) {
+                return Some(Location::Elsewhere);
+            }
+            warn!("Failed to insert cached code into the cache store, key: {key}");
+            return None;
+        }
+
+        let mod_cache_path = self.root_path.join(hash);
+        trace!("update_data() for path: {}", mod_cache_path.display());
+
         // Optimize syscalls: first, try writing to disk. It should succeed in most cases.
         // Otherwise, try creating the cache directory and retry writing to the file.
         if fs_write_atomic(&mod_cache_path, "mod", &compressed_data).is_ok() {
-            return Some(());
+            return Some(Location::Directory);
         }
 
         debug!(
@@ -303,7 +373,7 @@ impl<'cache> ModuleCacheEntryInner<'cache> {
             .ok()?;
 
         match fs_write_atomic(&mod_cache_path, "mod", &compressed_data) {
-            Ok(_) => Some(()),
+            Ok(_) => Some(Location::Directory),
             Err(err) => {
                 warn!(
                     "Failed to write file with rename, target path: {}, err: {}",
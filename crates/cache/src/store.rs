@@ -0,0 +1,27 @@
+//! Pluggable storage for cached compilation artifacts.
+
+use std::borrow::Cow;
+use std::fmt;
+
+/// Storage for the compiled code cached by [`Cache`](crate::Cache), used in
+/// place of the cache directory when configured with
+/// [`CacheConfig::with_store`](crate::CacheConfig::with_store).
+///
+/// Keys are derived from a hash of everything that affects compilation, so
+/// the data stored under a key never changes and stores may be shared freely
+/// between processes and machines running the same Wasmtime build. Keys are
+/// made up of ASCII alphanumerics, `-`, `_`, `.` and `/`. Values are
+/// compressed by the cache before being inserted.
+///
+/// Stores are responsible for their own eviction, as the cache worker only
+/// manages the cache directory.
+pub trait ModuleCacheStore: Send + Sync + fmt::Debug {
+    /// Try to retrieve the bytes inserted under `key` by
+    /// [`ModuleCacheStore::insert`] before.
+    fn get(&self, key: &str) -> Option<Cow<'_, [u8]>>;
+
+    /// Given a key and bytes, stores them in the cache.
+    ///
+    /// Returns false when insertion in the cache failed.
+    fn insert(&self, key: &str, value: Vec<u8>) -> bool;
+}
@@ -88,3 +88,182 @@ fn test_write_read_cache() {
     entry1.get_data::<_, i32, i32>(4, |_| panic!()).unwrap();
     entry2.get_data::<_, i32, i32>(1, |_| panic!()).unwrap();
 }
+
+#[derive(Debug, Default)]
+struct MemoryStore(std::sync::Mutex<std::collections::HashMap<String, Vec<u8>>>);
+
+impl ModuleCacheStore for MemoryStore {
+    fn get(&self, key: &str) -> Option<std::borrow::Cow<'_, [u8]>> {
+        self.0.lock().unwrap().get(key).cloned().map(Into::into)
+    }
+
+    fn insert(&self, key: &str, value: Vec<u8>) -> bool {
+        self.0.lock().unwrap().insert(key.to_string(), value);
+        true
+    }
+}
+
+#[test]
+fn test_custom_store() {
+    let (_tempdir, cache_dir, config_path) = test_prolog();
+    let store = Arc::new(MemoryStore::default());
+    let mut cache_config = load_config!(
+        config_path,
+        "[cache]\n\
+         directory = '{cache_dir}'\n",
+        cache_dir
+    );
+    cache_config.with_store(store.clone());
+    let cache = Cache::new(cache_config).unwrap();
+
+    let entry = ModuleCacheEntry::from_inner(ModuleCacheEntryInner::new("test", &cache));
+    entry.get_data::<_, i32, i32>(1, |_| Ok(100)).unwrap();
+    assert_eq!(entry.get_data::<_, i32, i32>(1, |_| panic!()), Ok(100));
+    assert_eq!(cache.cache_hits(), 1);
+    assert_eq!(cache.cache_misses(), 1);
+
+    // Entries are keyed by compiler and written nowhere else.
+    let keys = store.0.lock().unwrap().keys().cloned().collect::<Vec<_>>();
+    assert_eq!(keys.len(), 1);
+    assert!(keys[0].starts_with("test-"), "{keys:?}");
+    assert!(!cache_dir.join("modules").exists());
+
+    // A cache sharing the store sees the entry.
+    let mut cache_config = CacheConfig::new();
+    cache_config.with_directory(&cache_dir).with_store(store);
+    let cache = Cache::new(cache_config).unwrap();
+    let entry = ModuleCacheEntry::from_inner(ModuleCacheEntryInner::new("test", &cache));
+    assert_eq!(entry.get_data::<_, i32, i32>(1, |_| panic!()), Ok(100));
+}
+
+#[test]
+fn test_read_only_directories() {
+    let (tempdir, cache_dir, config_path) = test_prolog();
+    let cache_config = load_config!(
+        config_path,
+        "[cache]\n\
+         directory = '{cache_dir}'\n",
+        cache_dir
+    );
+    let cache = Cache::new(cache_config).unwrap();
+    let entry = ModuleCacheEntry::from_inner(ModuleCacheEntryInner::new("test", &cache));
+    entry.get_data::<_, i32, i32>(1, |_| Ok(100)).unwrap();
+
+    // A cache with an empty directory falls back to the populated one.
+    let upper_dir = tempdir.path().join("upper");
+    let mut cache_config = CacheConfig::new();
+    cache_config
+        .with_directory(&upper_dir)
+        .with_read_only_directory(&cache_dir);
+    let cache = Cache::new(cache_config).unwrap();
+    let entry = ModuleCacheEntry::from_inner(ModuleCacheEntryInner::new("test", &cache));
+    assert_eq!(entry.get_data::<_, i32, i32>(1, |_| panic!()), Ok(100));
+
+    // New entries only go to the writable directory.
+    entry.get_data::<_, i32, i32>(2, |_| Ok(200)).unwrap();
+    let count = |dir: &Path| fs::read_dir(dir.join("modules")).unwrap().count();
+    assert_eq!(count(&cache_dir), 1);
+    assert_eq!(count(&upper_dir), 1);
+    let modules = |dir: &Path| {
+        let compiler_dir = fs::read_dir(dir.join("modules")).unwrap().next().unwrap();
+        fs::read_dir(compiler_dir.unwrap().path())
+            .unwrap()
+            .filter(|e| e.as_ref().unwrap().path().extension().is_none())
+            .count()
+    };
+    assert_eq!(modules(&cache_dir), 1);
+    assert_eq!(modules(&upper_dir), 1);
+}
+
+#[test]
+fn test_inspect_and_manage_contents() {
+    let (_tempdir, cache_dir, config_path) = test_prolog();
+    let cache_config = load_config!(
+        config_path,
+        "[cache]\n\
+         directory = '{cache_dir}'\n\
+         file-count-soft-limit = '2'\n\
+         file-count-limit-percent-if-deleting = '50%'\n",
+        cache_dir
+    );
+    let cache = Cache::new(cache_config).unwrap();
+
+    let compiler_dir = cache_dir.join("modules").join("test-1.0");
+    fs::create_dir_all(&compiler_dir).unwrap();
+    let now = filetime::FileTime::now();
+    for (i, hash) in ["aaa", "bbb", "ccc"].iter().enumerate() {
+        let path = compiler_dir.join(hash);
+        fs::write(&path, vec![0; 10 * (i + 1)]).unwrap();
+        let stats_path = compiler_dir.join(format!("{hash}.stats"));
+        fs::write(
+            &stats_path,
+            format!("usages = {}\noptimized-compression = 0\n", i + 1),
+        )
+        .unwrap();
+        let mtime = filetime::FileTime::from_unix_time(now.unix_seconds() - 100 * i as i64, 0);
+        filetime::set_file_mtime(&stats_path, mtime).unwrap();
+    }
+    fs::write(cache_dir.join("junk"), "").unwrap();
+
+    let modules = cache.modules();
+    let summary = modules
+        .iter()
+        .map(|m| (m.compiler(), m.hash(), m.size(), m.usages()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        [
+            ("test-1.0", "aaa", 10, 1),
+            ("test-1.0", "bbb", 20, 2),
+            ("test-1.0", "ccc", 30, 3),
+        ]
+    );
+
+    let stats = cache.stats();
+    assert_eq!(stats.modules, 3);
+    assert_eq!(stats.modules_size, 60);
+    assert_eq!(stats.other_files, 1);
+    assert_eq!(stats.hits, 3);
+    assert_eq!(stats.misses, 3);
+
+    // Pruning drops the unrecognized file and the least recently used modules.
+    cache.prune().unwrap();
+    let hashes = cache
+        .modules()
+        .iter()
+        .map(|m| m.hash().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(hashes, ["aaa"]);
+    assert!(!cache_dir.join("junk").exists());
+
+    // Pruning isn't held up by a cleanup the cache worker just finished.
+    cache
+        .worker()
+        .on_cache_update_async(compiler_dir.join("aaa"));
+    cache.worker().wait_for_all_events_handled();
+    let worker_lock = format!(".cleanup.wip-{}", std::process::id());
+    assert!(cache_dir.join(&worker_lock).exists());
+    fs::write(cache_dir.join("junk"), "").unwrap();
+    cache.prune().unwrap();
+    assert!(!cache_dir.join("junk").exists());
+
+    // But it fails while another cleanup is running, and clearing leaves the
+    // locks of both in place.
+    fs::write(cache_dir.join(".cleanup-running.wip-1"), "").unwrap();
+    assert!(cache.prune().is_err());
+    cache.clear().unwrap();
+    assert!(cache.modules().is_empty());
+    let mut remaining = fs::read_dir(&cache_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    remaining.sort();
+    assert_eq!(
+        remaining,
+        [".cleanup-running.wip-1".to_string(), worker_lock]
+    );
+
+    // Clearing a cache which was never written to does nothing.
+    fs::remove_dir_all(&cache_dir).unwrap();
+    cache.clear().unwrap();
+}
@@ -174,7 +174,7 @@ impl ModuleCacheStatistics {
     }
 }
 
-enum CacheEntry {
+pub(super) enum CacheEntry {
     Recognized {
         path: PathBuf,
         mtime: SystemTime,
@@ -393,12 +393,6 @@ impl WorkerThread {
         trace!("Task finished: recompress file: {}", path.display());
     }
 
-    fn directory(&self) -> &PathBuf {
-        self.cache_config
-            .directory()
-            .expect("CacheConfig should be validated before being passed to a WorkerThread")
-    }
-
     fn handle_on_cache_update(&self, path: PathBuf) {
         trace!("handle_on_cache_update() for path: {}", path.display());
 
@@ -419,318 +413,373 @@ impl WorkerThread {
 
         // ---------------------- step 2: perform cleanup task if needed
 
-        // acquire lock for cleanup task
-        // Lock is a proof of recent cleanup task, so we don't want to delete them.
-        // Expired locks will be deleted by the cleanup task.
-        let cleanup_file = self.directory().join(".cleanup"); // some non existing marker file
-        if acquire_task_fs_lock(
-            &cleanup_file,
-            self.cache_config.cleanup_interval(),
-            self.cache_config
-                .allowed_clock_drift_for_files_from_future(),
-        )
-        .is_none()
-        {
+        if !acquire_clean_up_lock(&self.cache_config) {
             return;
         }
 
-        trace!("Trying to clean up cache");
+        run_clean_up(&self.cache_config);
+    }
+}
 
-        let mut cache_index = self.list_cache_contents();
-        let future_tolerance = SystemTime::now()
-            .checked_add(
-                self.cache_config
-                    .allowed_clock_drift_for_files_from_future(),
-            )
-            .expect("Brace your cache, the next Big Bang is coming (time overflow)");
-        cache_index.sort_unstable_by(|lhs, rhs| {
-            // sort by age
-            use CacheEntry::*;
-            match (lhs, rhs) {
-                (Recognized { mtime: lhs_mt, .. }, Recognized { mtime: rhs_mt, .. }) => {
-                    match (*lhs_mt > future_tolerance, *rhs_mt > future_tolerance) {
-                        // later == younger
-                        (false, false) => rhs_mt.cmp(lhs_mt),
-                        // files from far future are treated as oldest recognized files
-                        // we want to delete them, so the cache keeps track of recent files
-                        // however, we don't delete them uncodintionally,
-                        // because .stats file can be overwritten with a meaningful mtime
-                        (true, false) => cmp::Ordering::Greater,
-                        (false, true) => cmp::Ordering::Less,
-                        (true, true) => cmp::Ordering::Equal,
-                    }
+/// How long a cleanup may hold its `.cleanup-running` lock, after which the
+/// process running it is assumed to have died.
+const CLEAN_UP_RUNNING_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Acquires the lock for the cleanup task, returning whether it was acquired.
+///
+/// The lock isn't released: it's a proof of recent cleanup task, so another
+/// cleanup isn't started until it expires after the cleanup interval.
+/// Expired locks will be deleted by the cleanup task.
+fn acquire_clean_up_lock(cache_config: &CacheConfig) -> bool {
+    let cleanup_file = cache_config
+        .directory()
+        .expect("CacheConfig should be validated before cleaning up the cache")
+        .join(".cleanup"); // some non existing marker file
+    acquire_task_fs_lock(
+        &cleanup_file,
+        cache_config.cleanup_interval(),
+        cache_config.allowed_clock_drift_for_files_from_future(),
+    )
+    .is_some()
+}
+
+/// Cleans up the cache unless another cleanup is running, returning whether
+/// it did.
+///
+/// Unlike the lock taken by [`acquire_clean_up_lock`], the lock taken here is
+/// released as soon as the cleanup is done.
+pub(super) fn run_clean_up(cache_config: &CacheConfig) -> bool {
+    let running_file = cache_config
+        .directory()
+        .expect("CacheConfig should be validated before cleaning up the cache")
+        .join(".cleanup-running");
+    let lock_path = match acquire_task_fs_lock(
+        &running_file,
+        CLEAN_UP_RUNNING_TIMEOUT,
+        cache_config.allowed_clock_drift_for_files_from_future(),
+    ) {
+        Some(p) => p,
+        None => return false,
+    };
+
+    clean_up(cache_config);
+
+    if let Err(error) = fs::remove_file(&lock_path) {
+        warn!(
+            "Failed to remove cleanup lock, path {}, err: {}",
+            lock_path.display(),
+            error
+        );
+    }
+    true
+}
+
+/// Checks whether `entry`, found `level` directories deep in the cache
+/// directory, is the lock of a task which hasn't expired yet.
+pub(super) fn is_active_lock(entry: &fs::DirEntry, level: u8, cache_config: &CacheConfig) -> bool {
+    let path = entry.path();
+    let timeout = match level {
+        0 if path.file_stem() == Some(OsStr::new(".cleanup")) => cache_config.cleanup_interval(),
+        0 if path.file_stem() == Some(OsStr::new(".cleanup-running")) => CLEAN_UP_RUNNING_TIMEOUT,
+        2 => cache_config.optimizing_compression_task_timeout(),
+        _ => return false,
+    };
+    path.extension()
+        .and_then(OsStr::to_str)
+        .is_some_and(|ext| ext.starts_with("wip-"))
+        && !is_fs_lock_expired(
+            Some(entry),
+            &path,
+            timeout,
+            cache_config.allowed_clock_drift_for_files_from_future(),
+        )
+}
+
+/// Deletes unrecognized files and, if the soft limits are exceeded, the least
+/// recently used cache files.
+fn clean_up(cache_config: &CacheConfig) {
+    trace!("Trying to clean up cache");
+
+    let mut cache_index = list_cache_contents(cache_config);
+    let future_tolerance = SystemTime::now()
+        .checked_add(cache_config.allowed_clock_drift_for_files_from_future())
+        .expect("Brace your cache, the next Big Bang is coming (time overflow)");
+    cache_index.sort_unstable_by(|lhs, rhs| {
+        // sort by age
+        use CacheEntry::*;
+        match (lhs, rhs) {
+            (Recognized { mtime: lhs_mt, .. }, Recognized { mtime: rhs_mt, .. }) => {
+                match (*lhs_mt > future_tolerance, *rhs_mt > future_tolerance) {
+                    // later == younger
+                    (false, false) => rhs_mt.cmp(lhs_mt),
+                    // files from far future are treated as oldest recognized files
+                    // we want to delete them, so the cache keeps track of recent files
+                    // however, we don't delete them uncodintionally,
+                    // because .stats file can be overwritten with a meaningful mtime
+                    (true, false) => cmp::Ordering::Greater,
+                    (false, true) => cmp::Ordering::Less,
+                    (true, true) => cmp::Ordering::Equal,
                 }
-                // unrecognized is kind of infinity
-                (Recognized { .. }, Unrecognized { .. }) => cmp::Ordering::Less,
-                (Unrecognized { .. }, Recognized { .. }) => cmp::Ordering::Greater,
-                (Unrecognized { .. }, Unrecognized { .. }) => cmp::Ordering::Equal,
             }
-        });
-
-        // find "cut" boundary:
-        // - remove unrecognized files anyway,
-        // - remove some cache files if some quota has been exceeded
-        let mut total_size = 0u64;
-        let mut start_delete_idx = None;
-        let mut start_delete_idx_if_deleting_recognized_items: Option<usize> = None;
-
-        let total_size_limit = self.cache_config.files_total_size_soft_limit();
-        let file_count_limit = self.cache_config.file_count_soft_limit();
-        let tsl_if_deleting = total_size_limit
-            .checked_mul(
-                self.cache_config
-                    .files_total_size_limit_percent_if_deleting() as u64,
-            )
-            .unwrap()
-            / 100;
-        let fcl_if_deleting = file_count_limit
-            .checked_mul(self.cache_config.file_count_limit_percent_if_deleting() as u64)
-            .unwrap()
-            / 100;
-
-        for (idx, item) in cache_index.iter().enumerate() {
-            let size = if let CacheEntry::Recognized { size, .. } = item {
-                size
-            } else {
-                start_delete_idx = Some(idx);
-                break;
-            };
+            // unrecognized is kind of infinity
+            (Recognized { .. }, Unrecognized { .. }) => cmp::Ordering::Less,
+            (Unrecognized { .. }, Recognized { .. }) => cmp::Ordering::Greater,
+            (Unrecognized { .. }, Unrecognized { .. }) => cmp::Ordering::Equal,
+        }
+    });
+
+    // find "cut" boundary:
+    // - remove unrecognized files anyway,
+    // - remove some cache files if some quota has been exceeded
+    let mut total_size = 0u64;
+    let mut start_delete_idx = None;
+    let mut start_delete_idx_if_deleting_recognized_items: Option<usize> = None;
+
+    let total_size_limit = cache_config.files_total_size_soft_limit();
+    let file_count_limit = cache_config.file_count_soft_limit();
+    let tsl_if_deleting = total_size_limit
+        .checked_mul(cache_config.files_total_size_limit_percent_if_deleting() as u64)
+        .unwrap()
+        / 100;
+    let fcl_if_deleting = file_count_limit
+        .checked_mul(cache_config.file_count_limit_percent_if_deleting() as u64)
+        .unwrap()
+        / 100;
+
+    for (idx, item) in cache_index.iter().enumerate() {
+        let size = if let CacheEntry::Recognized { size, .. } = item {
+            size
+        } else {
+            start_delete_idx = Some(idx);
+            break;
+        };
 
-            total_size += size;
-            if start_delete_idx_if_deleting_recognized_items.is_none()
-                && (total_size > tsl_if_deleting || (idx + 1) as u64 > fcl_if_deleting)
-            {
-                start_delete_idx_if_deleting_recognized_items = Some(idx);
-            }
+        total_size += size;
+        if start_delete_idx_if_deleting_recognized_items.is_none()
+            && (total_size > tsl_if_deleting || (idx + 1) as u64 > fcl_if_deleting)
+        {
+            start_delete_idx_if_deleting_recognized_items = Some(idx);
+        }
 
-            if total_size > total_size_limit || (idx + 1) as u64 > file_count_limit {
-                start_delete_idx = start_delete_idx_if_deleting_recognized_items;
-                break;
-            }
+        if total_size > total_size_limit || (idx + 1) as u64 > file_count_limit {
+            start_delete_idx = start_delete_idx_if_deleting_recognized_items;
+            break;
         }
+    }
 
-        if let Some(idx) = start_delete_idx {
-            for item in &cache_index[idx..] {
-                let (result, path, entity) = match item {
-                    CacheEntry::Recognized { path, .. }
-                    | CacheEntry::Unrecognized {
-                        path,
-                        is_dir: false,
-                    } => (fs::remove_file(path), path, "file"),
-                    CacheEntry::Unrecognized { path, is_dir: true } => {
-                        (fs::remove_dir_all(path), path, "directory")
-                    }
-                };
-                if let Err(err) = result {
-                    warn!(
-                        "Failed to remove {} during cleanup, path: {}, err: {}",
-                        entity,
-                        path.display(),
-                        err
-                    );
+    if let Some(idx) = start_delete_idx {
+        for item in &cache_index[idx..] {
+            let (result, path, entity) = match item {
+                CacheEntry::Recognized { path, .. }
+                | CacheEntry::Unrecognized {
+                    path,
+                    is_dir: false,
+                } => (fs::remove_file(path), path, "file"),
+                CacheEntry::Unrecognized { path, is_dir: true } => {
+                    (fs::remove_dir_all(path), path, "directory")
                 }
+            };
+            if let Err(err) = result {
+                warn!(
+                    "Failed to remove {} during cleanup, path: {}, err: {}",
+                    entity,
+                    path.display(),
+                    err
+                );
             }
         }
-
-        trace!("Task finished: clean up cache");
     }
 
-    // Be fault tolerant: list as much as you can, and ignore the rest
-    fn list_cache_contents(&self) -> Vec<CacheEntry> {
-        fn enter_dir(
-            vec: &mut Vec<CacheEntry>,
-            dir_path: &Path,
-            level: u8,
-            cache_config: &CacheConfig,
-        ) {
-            macro_rules! add_unrecognized {
-                (file: $path:expr) => {
-                    add_unrecognized!(false, $path)
-                };
-                (dir: $path:expr) => {
-                    add_unrecognized!(true, $path)
-                };
-                ($is_dir:expr, $path:expr) => {
-                    vec.push(CacheEntry::Unrecognized {
-                        path: $path.to_path_buf(),
-                        is_dir: $is_dir,
-                    })
-                };
-            }
-            macro_rules! add_unrecognized_and {
-                ([ $( $ty:ident: $path:expr ),* ], $cont:stmt) => {{
-                    $( add_unrecognized!($ty: $path); )*
-                        $cont
-                }};
-            }
+    trace!("Task finished: clean up cache");
+}
 
-            macro_rules! unwrap_or {
-                ($result:expr, $cont:stmt, $err_msg:expr) => {
-                    unwrap_or!($result, $cont, $err_msg, dir_path)
-                };
-                ($result:expr, $cont:stmt, $err_msg:expr, $path:expr) => {
-                    unwrap_or_warn!(
-                        $result,
-                        $cont,
-                        format!("{}, level: {}", $err_msg, level),
-                        $path
-                    )
-                };
-            }
+// Be fault tolerant: list as much as you can, and ignore the rest
+pub(super) fn list_cache_contents(cache_config: &CacheConfig) -> Vec<CacheEntry> {
+    fn enter_dir(
+        vec: &mut Vec<CacheEntry>,
+        dir_path: &Path,
+        level: u8,
+        cache_config: &CacheConfig,
+    ) {
+        macro_rules! add_unrecognized {
+            (file: $path:expr) => {
+                add_unrecognized!(false, $path)
+            };
+            (dir: $path:expr) => {
+                add_unrecognized!(true, $path)
+            };
+            ($is_dir:expr, $path:expr) => {
+                vec.push(CacheEntry::Unrecognized {
+                    path: $path.to_path_buf(),
+                    is_dir: $is_dir,
+                })
+            };
+        }
+        macro_rules! add_unrecognized_and {
+            ([ $( $ty:ident: $path:expr ),* ], $cont:stmt) => {{
+                $( add_unrecognized!($ty: $path); )*
+                    $cont
+            }};
+        }
 
-            // If we fail to list a directory, something bad is happening anyway
-            // (something touches our cache or we have disk failure)
-            // Try to delete it, so we can stay within soft limits of the cache size.
-            // This comment applies later in this function, too.
-            let it = unwrap_or!(
-                fs::read_dir(dir_path),
-                add_unrecognized_and!([dir: dir_path], return),
-                "Failed to list cache directory, deleting it"
-            );
+        macro_rules! unwrap_or {
+            ($result:expr, $cont:stmt, $err_msg:expr) => {
+                unwrap_or!($result, $cont, $err_msg, dir_path)
+            };
+            ($result:expr, $cont:stmt, $err_msg:expr, $path:expr) => {
+                unwrap_or_warn!(
+                    $result,
+                    $cont,
+                    format!("{}, level: {}", $err_msg, level),
+                    $path
+                )
+            };
+        }
 
-            let mut cache_files = HashMap::new();
-            for entry in it {
-                // read_dir() returns an iterator over results - in case some of them are errors
-                // we don't know their names, so we can't delete them. We don't want to delete
-                // the whole directory with good entries too, so we just ignore the erroneous entries.
-                let entry = unwrap_or!(
-                    entry,
-                    continue,
-                    "Failed to read a cache dir entry (NOT deleting it, it still occupies space)"
-                );
-                let path = entry.path();
-                match (level, path.is_dir()) {
-                    (0..=1, true) => enter_dir(vec, &path, level + 1, cache_config),
-                    (0..=1, false) => {
-                        if level == 0
-                            && path.file_stem() == Some(OsStr::new(".cleanup"))
-                                && path.extension().is_some()
-                                // assume it's cleanup lock
-                                && !is_fs_lock_expired(
-                                    Some(&entry),
-                                    &path,
-                                    cache_config.cleanup_interval(),
-                                    cache_config.allowed_clock_drift_for_files_from_future(),
-                                )
-                        {
-                            continue; // skip active lock
-                        }
-                        add_unrecognized!(file: path);
+        // If we fail to list a directory, something bad is happening anyway
+        // (something touches our cache or we have disk failure)
+        // Try to delete it, so we can stay within soft limits of the cache size.
+        // This comment applies later in this function, too.
+        let it = unwrap_or!(
+            fs::read_dir(dir_path),
+            add_unrecognized_and!([dir: dir_path], return),
+            "Failed to list cache directory, deleting it"
+        );
+
+        let mut cache_files = HashMap::new();
+        for entry in it {
+            // read_dir() returns an iterator over results - in case some of them are errors
+            // we don't know their names, so we can't delete them. We don't want to delete
+            // the whole directory with good entries too, so we just ignore the erroneous entries.
+            let entry = unwrap_or!(
+                entry,
+                continue,
+                "Failed to read a cache dir entry (NOT deleting it, it still occupies space)"
+            );
+            let path = entry.path();
+            match (level, path.is_dir()) {
+                (0..=1, true) => enter_dir(vec, &path, level + 1, cache_config),
+                (0..=1, false) => {
+                    if is_active_lock(&entry, level, cache_config) {
+                        continue; // skip active lock
                     }
-                    (2, false) => {
-                        match path.extension().and_then(OsStr::to_str) {
-                            // mod or stats file
-                            None | Some("stats") => {
-                                cache_files.insert(path, entry);
-                            }
+                    add_unrecognized!(file: path);
+                }
+                (2, false) => {
+                    match path.extension().and_then(OsStr::to_str) {
+                        // mod or stats file
+                        None | Some("stats") => {
+                            cache_files.insert(path, entry);
+                        }
 
-                            Some(ext) => {
-                                // check if valid lock
-                                let recognized = ext.starts_with("wip-")
-                                    && !is_fs_lock_expired(
-                                        Some(&entry),
-                                        &path,
-                                        cache_config.optimizing_compression_task_timeout(),
-                                        cache_config.allowed_clock_drift_for_files_from_future(),
-                                    );
-
-                                if !recognized {
-                                    add_unrecognized!(file: path);
-                                }
+                        Some(_) => {
+                            // check if valid lock
+                            if !is_active_lock(&entry, level, cache_config) {
+                                add_unrecognized!(file: path);
                             }
                         }
                     }
-                    (_, is_dir) => add_unrecognized!(is_dir, path),
                 }
+                (_, is_dir) => add_unrecognized!(is_dir, path),
             }
+        }
 
-            // associate module with its stats & handle them
-            // assumption: just mods and stats
-            for (path, entry) in cache_files.iter() {
-                let path_buf: PathBuf;
-                let (mod_, stats_, is_mod) = match path.extension() {
-                    Some(_) => {
-                        path_buf = path.with_extension("");
-                        (
-                            cache_files.get(&path_buf).map(|v| (&path_buf, v)),
-                            Some((path, entry)),
-                            false,
-                        )
-                    }
-                    None => {
-                        path_buf = path.with_extension("stats");
-                        (
-                            Some((path, entry)),
-                            cache_files.get(&path_buf).map(|v| (&path_buf, v)),
-                            true,
-                        )
-                    }
-                };
-
-                // construct a cache entry
-                match (mod_, stats_, is_mod) {
-                    (Some((mod_path, mod_entry)), Some((stats_path, stats_entry)), true) => {
-                        let mod_metadata = unwrap_or!(
-                            mod_entry.metadata(),
-                            add_unrecognized_and!([file: stats_path, file: mod_path], continue),
-                            "Failed to get metadata, deleting BOTH module cache and stats files",
-                            mod_path
-                        );
-                        let stats_mtime = unwrap_or!(
-                            stats_entry.metadata().and_then(|m| m.modified()),
-                            add_unrecognized_and!(
-                                [file: stats_path],
-                                unwrap_or!(
-                                    mod_metadata.modified(),
-                                    add_unrecognized_and!(
-                                        [file: stats_path, file: mod_path],
-                                        continue
-                                    ),
-                                    "Failed to get mtime, deleting BOTH module cache and stats \
-                                     files",
-                                    mod_path
-                                )
-                            ),
-                            "Failed to get metadata/mtime, deleting the file",
-                            stats_path
-                        );
-                        // .into() called for the SystemTimeStub if cfg(test)
-                        vec.push(CacheEntry::Recognized {
-                            path: mod_path.to_path_buf(),
-                            mtime: stats_mtime.into(),
-                            size: mod_metadata.len(),
-                        })
-                    }
-                    (Some(_), Some(_), false) => (), // was or will be handled by previous branch
-                    (Some((mod_path, mod_entry)), None, _) => {
-                        let (mod_metadata, mod_mtime) = unwrap_or!(
-                            mod_entry
-                                .metadata()
-                                .and_then(|md| md.modified().map(|mt| (md, mt))),
-                            add_unrecognized_and!([file: mod_path], continue),
-                            "Failed to get metadata/mtime, deleting the file",
-                            mod_path
-                        );
-                        // .into() called for the SystemTimeStub if cfg(test)
-                        vec.push(CacheEntry::Recognized {
-                            path: mod_path.to_path_buf(),
-                            mtime: mod_mtime.into(),
-                            size: mod_metadata.len(),
-                        })
-                    }
-                    (None, Some((stats_path, _stats_entry)), _) => {
-                        debug!("Found orphaned stats file: {}", stats_path.display());
-                        add_unrecognized!(file: stats_path);
-                    }
-                    _ => unreachable!(),
+        // associate module with its stats & handle them
+        // assumption: just mods and stats
+        for (path, entry) in cache_files.iter() {
+            let path_buf: PathBuf;
+            let (mod_, stats_, is_mod) = match path.extension() {
+                Some(_) => {
+                    path_buf = path.with_extension("");
+                    (
+                        cache_files.get(&path_buf).map(|v| (&path_buf, v)),
+                        Some((path, entry)),
+                        false,
+                    )
+                }
+                None => {
+                    path_buf = path.with_extension("stats");
+                    (
+                        Some((path, entry)),
+                        cache_files.get(&path_buf).map(|v| (&path_buf, v)),
+                        true,
+                    )
+                }
+            };
+
+            // construct a cache entry
+            match (mod_, stats_, is_mod) {
+                (Some((mod_path, mod_entry)), Some((stats_path, stats_entry)), true) => {
+                    let mod_metadata = unwrap_or!(
+                        mod_entry.metadata(),
+                        add_unrecognized_and!([file: stats_path, file: mod_path], continue),
+                        "Failed to get metadata, deleting BOTH module cache and stats files",
+                        mod_path
+                    );
+                    let stats_mtime = unwrap_or!(
+                        stats_entry.metadata().and_then(|m| m.modified()),
+                        add_unrecognized_and!(
+                            [file: stats_path],
+                            unwrap_or!(
+                                mod_metadata.modified(),
+                                add_unrecognized_and!(
+                                    [file: stats_path, file: mod_path],
+                                    continue
+                                ),
+                                "Failed to get mtime, deleting BOTH module cache and stats \
+                                 files",
+                                mod_path
+                            )
+                        ),
+                        "Failed to get metadata/mtime, deleting the file",
+                        stats_path
+                    );
+                    // .into() called for the SystemTimeStub if cfg(test)
+                    vec.push(CacheEntry::Recognized {
+                        path: mod_path.to_path_buf(),
+                        mtime: stats_mtime.into(),
+                        size: mod_metadata.len(),
+                    })
+                }
+                (Some(_), Some(_), false) => (), // was or will be handled by previous branch
+                (Some((mod_path, mod_entry)), None, _) => {
+                    let (mod_metadata, mod_mtime) = unwrap_or!(
+                        mod_entry
+                            .metadata()
+                            .and_then(|md| md.modified().map(|mt| (md, mt))),
+                        add_unrecognized_and!([file: mod_path], continue),
+                        "Failed to get metadata/mtime, deleting the file",
+                        mod_path
+                    );
+                    // .into() called for the SystemTimeStub if cfg(test)
+                    vec.push(CacheEntry::Recognized {
+                        path: mod_path.to_path_buf(),
+                        mtime: mod_mtime.into(),
+                        size: mod_metadata.len(),
+                    })
+                }
+                (None, Some((stats_path, _stats_entry)), _) => {
+                    debug!("Found orphaned stats file: {}", stats_path.display());
+                    add_unrecognized!(file: stats_path);
                 }
+                _ => unreachable!(),
             }
         }
-
-        let mut vec = Vec::new();
-        enter_dir(&mut vec, self.directory(), 0, &self.cache_config);
-        vec
     }
+
+    let directory = cache_config
+        .directory()
+        .expect("CacheConfig should be validated before its contents are listed");
+    let mut vec = Vec::new();
+    enter_dir(&mut vec, directory, 0, cache_config);
+    vec
+}
+
+/// Returns the usage counter recorded for the module cache file at `path`.
+pub(super) fn read_usages(path: &Path) -> u64 {
+    let mut stats_path = path.as_os_str().to_owned();
+    stats_path.push(".stats");
+    read_stats_file(Path::new(&stats_path)).map_or(0, |stats| stats.usages)
 }
 
 fn read_stats_file(path: &Path) -> Option<ModuleCacheStatistics> {
@@ -210,20 +210,25 @@ wasmtime_option_group! {
     #[derive(PartialEq, Clone, Deserialize)]
     #[serde(rename_all = "kebab-case", deny_unknown_fields)]
     pub struct CodegenOptions {
-        /// Either `cranelift` or `winch`.
+        /// Either `cranelift`, `winch` or `tiered`.
         ///
         /// Currently only `cranelift` and `winch` are supported, but not all
-        /// builds of Wasmtime have both built in.
+        /// builds of Wasmtime have both built in. `tiered` starts out with
+        /// `winch` and recompiles hot functions with `cranelift`, and requires
+        /// both.
         #[serde(default)]
         #[serde(deserialize_with = "crate::opt::cli_parse_wrapper")]
         pub compiler: Option<wasmtime::Strategy>,
-        /// Which garbage collector to use: `drc` or `null`.
+        /// Which garbage collector to use: `drc`, `null`, or `copying`.
         ///
         /// `drc` is the deferred reference-counting collector.
         ///
         /// `null` is the null garbage collector, which does not collect any
         /// garbage.
         ///
+        /// `copying` is the copying garbage collector, which can also collect
+        /// cycles.
+        ///
         /// Note that not all builds of Wasmtime will have support for garbage
         /// collection included.
         #[serde(default)]
@@ -246,6 +251,10 @@ wasmtime_option_group! {
         /// Whether to perform function inlining during compilation.
         pub inlining: Option<bool>,
 
+        /// Number of calls and loop iterations after which a function is
+        /// recompiled with Cranelift when using `-C compiler=tiered`.
+        pub tier_up_threshold: Option<u32>,
+
         #[prefixed = "cranelift"]
         #[serde(default)]
         /// Set a cranelift-specific option. Use `wasmtime settings` to see
@@ -272,6 +281,9 @@ wasmtime_option_group! {
         pub log_to_files: Option<bool>,
         /// Enable coredump generation to this file after a WebAssembly trap.
         pub coredump: Option<String>,
+        /// Preserve the values of locals and the operand stack of each frame
+        /// in coredumps.
+        pub coredump_frame_state: Option<bool>,
     }
 
     enum Debug {
@@ -318,6 +330,30 @@ wasmtime_option_group! {
         pub unknown_imports_default: Option<bool>,
         /// Enables memory error checking. (see wmemcheck.md for more info)
         pub wmemcheck: Option<bool>,
+        /// Name of a guest function that wmemcheck treats as
+        /// `malloc(size) -> ptr`, may be repeated. (default: malloc)
+        #[serde(default)]
+        pub wmemcheck_malloc: Vec<String>,
+        /// Name of a guest function that wmemcheck treats as `free(ptr)`, may
+        /// be repeated. (default: free)
+        #[serde(default)]
+        pub wmemcheck_free: Vec<String>,
+        /// Name of a guest function that wmemcheck treats as
+        /// `realloc(ptr, size) -> ptr`, may be repeated. (default: realloc)
+        #[serde(default)]
+        pub wmemcheck_realloc: Vec<String>,
+        /// Name of a guest function that wmemcheck treats as
+        /// `calloc(count, size) -> ptr`, may be repeated. (default: calloc)
+        #[serde(default)]
+        pub wmemcheck_calloc: Vec<String>,
+        /// Name of a guest function that wmemcheck treats as
+        /// `aligned_alloc(align, size) -> ptr`, may be repeated.
+        /// (default: aligned_alloc)
+        #[serde(default)]
+        pub wmemcheck_aligned_alloc: Vec<String>,
+        /// Index of the linear memory managed by the allocator that wmemcheck
+        /// checks. (default: 0)
+        pub wmemcheck_memory: Option<u32>,
         /// Maximum size, in bytes, that a linear memory is allowed to reach.
         ///
         /// Growth beyond this limit will cause `memory.grow` instructions in
@@ -433,6 +469,31 @@ wasmtime_option_group! {
         /// Maximum size allowed in a write call to the outgoing body's output-stream.
         /// Default: 1024 * 1024.
         pub http_outgoing_body_chunk_size: Option<usize>,
+        /// Only allow outgoing HTTP requests to hosts matching a pattern.
+        ///
+        /// Patterns are of the form `[scheme://]host[:port]`, where `host` is
+        /// either `*` or a domain name optionally starting with `*.` to match
+        /// its subdomains.
+        #[serde(default)]
+        pub http_outgoing_allow: Vec<String>,
+        /// Deny outgoing HTTP requests to hosts matching a pattern, in the
+        /// same form as `http-outgoing-allow`.
+        #[serde(default)]
+        pub http_outgoing_deny: Vec<String>,
+        /// Set a header on all outgoing HTTP requests, as `NAME=VALUE`.
+        #[serde(skip)]
+        pub http_outgoing_header: Vec<KeyValuePair>,
+        /// Remove a header from all outgoing HTTP requests.
+        #[serde(default)]
+        pub http_outgoing_strip_header: Vec<String>,
+        /// Maximum size, in bytes, of the body of an outgoing HTTP request.
+        pub http_outgoing_max_body_size: Option<u64>,
+        /// Maximum number of outgoing HTTP requests an instance may send.
+        pub http_outgoing_max_requests: Option<u64>,
+        /// Maximum number of redirects to follow for each outgoing HTTP
+        /// request.
+        /// Default: 0.
+        pub http_outgoing_max_redirects: Option<u32>,
         /// Enable support for WASI config imports (experimental)
         pub config: Option<bool>,
         /// Enable support for WASI key-value imports (experimental)
@@ -483,6 +544,13 @@ wasmtime_option_group! {
         /// Preset data for the In-Memory provider of WASI key-value API.
         #[serde(skip)]
         pub keyvalue_in_memory_data: Vec<KeyValuePair>,
+        /// Persist a WASI key-value store to a file, as `NAME=PATH`.
+        ///
+        /// `NAME` is the identifier guests pass to `open`, and an empty name
+        /// replaces the default In-Memory store. With `wasmtime serve`, the
+        /// file is shared by all requests while In-Memory stores are not.
+        #[serde(skip)]
+        pub keyvalue_file: Vec<KeyValuePair>,
         /// Enable support for WASIp3 APIs.
         pub p3: Option<bool>,
     }
@@ -625,6 +693,36 @@ impl CommonOptions {
         }
     }
 
+    /// Parses `args` as additional command-line options which take precedence
+    /// over the options already specified.
+    ///
+    /// This must be called before these options are used to create a
+    /// [`Config`].
+    pub fn push_args<I, T>(&mut self, args: I) -> Result<()>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString>,
+    {
+        if self.configured {
+            anyhow::bail!("cannot add options after they've already been configured");
+        }
+        let args = std::iter::once(std::ffi::OsString::from("wasmtime"))
+            .chain(args.into_iter().map(Into::into));
+        let extra = CommonOptions::try_parse_from(args)?;
+        self.opts_raw.extend(extra.opts_raw);
+        self.codegen_raw.extend(extra.codegen_raw);
+        self.debug_raw.extend(extra.debug_raw);
+        self.wasm_raw.extend(extra.wasm_raw);
+        self.wasi_raw.extend(extra.wasi_raw);
+        if extra.target.is_some() {
+            self.target = extra.target;
+        }
+        if extra.config.is_some() {
+            self.config = extra.config;
+        }
+        Ok(())
+    }
+
     fn configure(&mut self) -> Result<()> {
         if self.configured {
             return Ok(());
@@ -688,6 +786,11 @@ impl CommonOptions {
             strategy => config.strategy(strategy),
             _ => err,
         }
+        match_feature! {
+            ["cranelift" : self.codegen.tier_up_threshold]
+            threshold => config.tier_up_threshold(threshold),
+            _ => err,
+        }
         match_feature! {
             ["gc" : self.codegen.collector]
             collector => config.collector(collector),
@@ -710,6 +813,11 @@ impl CommonOptions {
             #[cfg(not(feature = "coredump"))]
             anyhow::bail!("support for coredumps disabled at compile time");
         }
+        match_feature! {
+            ["coredump" : self.debug.coredump_frame_state]
+            enable => config.coredump_frame_state(enable),
+            true => err,
+        }
         match_feature! {
             ["cranelift" : self.opts.opt_level]
             level => config.cranelift_opt_level(level),
@@ -989,6 +1097,7 @@ impl CommonOptions {
             enable => config.wmemcheck(enable),
             true => err,
         }
+        self.configure_wmemcheck_allocator(&mut config)?;
 
         if let Some(enable) = self.wasm.gc_support {
             config.gc_support(enable);
@@ -997,6 +1106,44 @@ impl CommonOptions {
         Ok(config)
     }
 
+    fn configure_wmemcheck_allocator(&self, config: &mut Config) -> Result<()> {
+        let wasm = &self.wasm;
+        let customized = !wasm.wmemcheck_malloc.is_empty()
+            || !wasm.wmemcheck_free.is_empty()
+            || !wasm.wmemcheck_realloc.is_empty()
+            || !wasm.wmemcheck_calloc.is_empty()
+            || !wasm.wmemcheck_aligned_alloc.is_empty()
+            || wasm.wmemcheck_memory.is_some();
+        if !customized {
+            return Ok(());
+        }
+        #[cfg(feature = "cranelift")]
+        {
+            let mut allocator = wasmtime::WmemcheckAllocator::default();
+            for (names, functions) in [
+                (&wasm.wmemcheck_malloc, &mut allocator.malloc),
+                (&wasm.wmemcheck_free, &mut allocator.free),
+                (&wasm.wmemcheck_realloc, &mut allocator.realloc),
+                (&wasm.wmemcheck_calloc, &mut allocator.calloc),
+                (&wasm.wmemcheck_aligned_alloc, &mut allocator.aligned_alloc),
+            ] {
+                if !names.is_empty() {
+                    *functions = names.clone();
+                }
+            }
+            if let Some(memory) = wasm.wmemcheck_memory {
+                allocator.memory = memory;
+            }
+            config.wmemcheck_allocator(allocator);
+            Ok(())
+        }
+        #[cfg(not(feature = "cranelift"))]
+        {
+            let _ = config;
+            anyhow::bail!("support for cranelift disabled at compile time")
+        }
+    }
+
     pub fn enable_wasm_features(&self, config: &mut Config) -> Result<()> {
         let all = self.wasm.all_proposals;
 
@@ -1158,6 +1305,7 @@ mod tests {
         for (strategy_value, expected) in [
             ("\"cranelift\"", Some(wasmtime::Strategy::Cranelift)),
             ("\"winch\"", Some(wasmtime::Strategy::Winch)),
+            ("\"tiered\"", Some(wasmtime::Strategy::Tiered)),
             ("\"hello\"", None), // should fail
             ("5", None),         // should fail
             ("true", None),      // should fail
@@ -1184,6 +1332,7 @@ mod tests {
                 Some(wasmtime::Collector::DeferredReferenceCounting),
             ),
             ("\"null\"", Some(wasmtime::Collector::Null)),
+            ("\"copying\"", Some(wasmtime::Collector::Copying)),
             ("\"hello\"", None), // should fail
             ("5", None),         // should fail
             ("true", None),      // should fail
@@ -1203,6 +1352,21 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn push_args() -> Result<()> {
+        let mut options =
+            CommonOptions::try_parse_from(["wasmtime", "-O", "opt-level=2", "-Ccache=n"])?;
+        options.push_args(["-O", "opt-level=0", "--target", "aarch64-unknown-linux-gnu"])?;
+        options.configure()?;
+        assert_eq!(options.opts.opt_level, Some(OptLevel::None));
+        assert_eq!(options.codegen.cache, Some(false));
+        assert_eq!(options.target.as_deref(), Some("aarch64-unknown-linux-gnu"));
+
+        // Options can't be added once they're in use.
+        assert!(options.push_args(["-Ccache=y"]).is_err());
+        Ok(())
+    }
 }
 
 impl Default for CommonOptions {
@@ -503,12 +503,15 @@ impl WasmtimeOptionValue for wasmtime::RegallocAlgorithm {
 }
 
 impl WasmtimeOptionValue for wasmtime::Strategy {
-    const VAL_HELP: &'static str = "=winch|cranelift";
+    const VAL_HELP: &'static str = "=winch|cranelift|tiered";
     fn parse(val: Option<&str>) -> Result<Self> {
         match String::parse(val)?.as_str() {
             "cranelift" => Ok(wasmtime::Strategy::Cranelift),
             "winch" => Ok(wasmtime::Strategy::Winch),
-            other => bail!("unknown compiler `{other}` only `cranelift` and `winch` accepted",),
+            "tiered" => Ok(wasmtime::Strategy::Tiered),
+            other => {
+                bail!("unknown compiler `{other}` only `cranelift`, `winch` and `tiered` accepted",)
+            }
         }
     }
 
@@ -516,18 +519,22 @@ impl WasmtimeOptionValue for wasmtime::Strategy {
         match *self {
             wasmtime::Strategy::Cranelift => f.write_str("cranelift"),
             wasmtime::Strategy::Winch => f.write_str("winch"),
+            wasmtime::Strategy::Tiered => f.write_str("tiered"),
             _ => unreachable!(),
         }
     }
 }
 
 impl WasmtimeOptionValue for wasmtime::Collector {
-    const VAL_HELP: &'static str = "=drc|null";
+    const VAL_HELP: &'static str = "=drc|null|copying";
     fn parse(val: Option<&str>) -> Result<Self> {
         match String::parse(val)?.as_str() {
             "drc" => Ok(wasmtime::Collector::DeferredReferenceCounting),
             "null" => Ok(wasmtime::Collector::Null),
-            other => bail!("unknown collector `{other}` only `drc` and `null` accepted",),
+            "copying" => Ok(wasmtime::Collector::Copying),
+            other => {
+                bail!("unknown collector `{other}` only `drc`, `null`, and `copying` accepted",)
+            }
         }
     }
 
@@ -535,6 +542,7 @@ impl WasmtimeOptionValue for wasmtime::Collector {
         match *self {
             wasmtime::Collector::DeferredReferenceCounting => f.write_str("drc"),
             wasmtime::Collector::Null => f.write_str("null"),
+            wasmtime::Collector::Copying => f.write_str("copying"),
             _ => unreachable!(),
         }
     }
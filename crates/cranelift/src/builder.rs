@@ -13,7 +13,7 @@ use std::fmt;
 use std::path;
 use std::sync::Arc;
 use target_lexicon::Triple;
-use wasmtime_environ::{CacheStore, CompilerBuilder, Setting, Tunables};
+use wasmtime_environ::{CacheStore, CompilerBuilder, Setting, Tunables, WmemcheckAllocator};
 
 struct Builder {
     tunables: Option<Tunables>,
@@ -23,6 +23,7 @@ struct Builder {
     cache_store: Option<Arc<dyn CacheStore>>,
     clif_dir: Option<path::PathBuf>,
     wmemcheck: bool,
+    wmemcheck_allocator: WmemcheckAllocator,
 }
 
 #[derive(Clone, Default)]
@@ -46,6 +47,7 @@ pub fn builder(triple: Option<Triple>) -> Result<Box<dyn CompilerBuilder>> {
         cache_store: None,
         clif_dir: None,
         wmemcheck: false,
+        wmemcheck_allocator: WmemcheckAllocator::default(),
         emit_debug_checks: false,
     }))
 }
@@ -115,6 +117,7 @@ impl CompilerBuilder for Builder {
             self.linkopts.clone(),
             self.clif_dir.clone(),
             self.wmemcheck,
+            self.wmemcheck_allocator.clone(),
         )))
     }
 
@@ -133,6 +136,10 @@ impl CompilerBuilder for Builder {
     fn wmemcheck(&mut self, enable: bool) {
         self.wmemcheck = enable;
     }
+
+    fn wmemcheck_allocator(&mut self, allocator: WmemcheckAllocator) {
+        self.wmemcheck_allocator = allocator;
+    }
 }
 
 impl fmt::Debug for Builder {
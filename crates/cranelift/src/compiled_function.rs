@@ -3,7 +3,9 @@ use cranelift_codegen::{
     Final, MachBufferFinalized, MachSrcLoc, ValueLabelsRanges, ir, isa::unwind::CfaUnwindInfo,
     isa::unwind::UnwindInfo,
 };
-use wasmtime_environ::{FilePos, InstructionAddressMap, PrimaryMap, TrapInformation};
+use wasmtime_environ::{
+    FilePos, FunctionFrameState, InstructionAddressMap, PrimaryMap, TrapInformation,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 /// Metadata to translate from binary offsets back to the original
@@ -50,6 +52,8 @@ pub struct CompiledFunctionMetadata {
     pub start_srcloc: FilePos,
     /// End source location.
     pub end_srcloc: FilePos,
+    /// The preserved Wasm locals and operand stack, if any.
+    pub frame_state: Option<FunctionFrameState>,
 }
 
 /// Compiled function: machine code body, jump table offsets, and unwind information.
@@ -159,6 +163,16 @@ impl CompiledFunction {
     pub fn set_sized_stack_slots(&mut self, slots: ir::StackSlots) {
         self.metadata.sized_stack_slots = slots;
     }
+
+    /// Get the preserved frame state from the function's metadata.
+    pub fn frame_state(&self) -> Option<&FunctionFrameState> {
+        self.metadata.frame_state.as_ref()
+    }
+
+    /// Set the preserved frame state in the function's metadata.
+    pub fn set_frame_state(&mut self, frame_state: FunctionFrameState) {
+        self.metadata.frame_state = Some(frame_state);
+    }
 }
 
 // Collects an iterator of `InstructionAddressMap` into a `Vec` for insertion
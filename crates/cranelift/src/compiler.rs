@@ -1,6 +1,7 @@
 use crate::TRAP_INTERNAL_ASSERT;
 use crate::debug::DwarfSectionRelocTarget;
 use crate::func_environ::FuncEnvironment;
+use crate::func_environ::frame_state::FrameStateBuilder;
 use crate::translate::FuncTranslator;
 use crate::{BuiltinFunctionSignatures, builder::LinkOptions, wasm_call_signature};
 use crate::{CompiledFunction, ModuleTextBuilder, array_call_signature};
@@ -31,10 +32,10 @@ use wasmparser::{FuncValidatorAllocations, FunctionBody};
 use wasmtime_environ::obj::ELF_WASMTIME_EXCEPTIONS;
 use wasmtime_environ::{
     Abi, AddressMapSection, BuiltinFunctionIndex, CacheStore, CompileError, CompiledFunctionBody,
-    DefinedFuncIndex, FlagValue, FuncKey, FunctionBodyData, FunctionLoc, HostCall,
-    InliningCompiler, ModuleTranslation, ModuleTypesBuilder, PtrSize, StackMapSection,
+    DefinedFuncIndex, FlagValue, FrameStateSection, FuncKey, FunctionBodyData, FunctionLoc,
+    HostCall, InliningCompiler, ModuleTranslation, ModuleTypesBuilder, PtrSize, StackMapSection,
     StaticModuleIndex, TrapEncodingBuilder, TrapSentinel, TripleExt, Tunables, VMOffsets,
-    WasmFuncType, WasmValType,
+    WasmFuncType, WasmValType, WmemcheckAllocator,
 };
 use wasmtime_unwinder::ExceptionTableBuilder;
 
@@ -54,6 +55,7 @@ struct CompilerContext {
     incremental_cache_ctx: Option<IncrementalCacheContext>,
     validator_allocations: FuncValidatorAllocations,
     abi: Option<Abi>,
+    frame_state: Option<FrameStateBuilder>,
 }
 
 impl Default for CompilerContext {
@@ -64,6 +66,7 @@ impl Default for CompilerContext {
             incremental_cache_ctx: None,
             validator_allocations: Default::default(),
             abi: None,
+            frame_state: None,
         }
     }
 }
@@ -80,6 +83,8 @@ pub struct Compiler {
     clif_dir: Option<path::PathBuf>,
     #[cfg(feature = "wmemcheck")]
     pub(crate) wmemcheck: bool,
+    #[cfg(feature = "wmemcheck")]
+    pub(crate) wmemcheck_allocator: WmemcheckAllocator,
 }
 
 impl Drop for Compiler {
@@ -119,8 +124,9 @@ impl Compiler {
         linkopts: LinkOptions,
         clif_dir: Option<path::PathBuf>,
         wmemcheck: bool,
+        wmemcheck_allocator: WmemcheckAllocator,
     ) -> Compiler {
-        let _ = wmemcheck;
+        let _ = (wmemcheck, &wmemcheck_allocator);
         Compiler {
             contexts: Default::default(),
             tunables,
@@ -131,6 +137,8 @@ impl Compiler {
             clif_dir,
             #[cfg(feature = "wmemcheck")]
             wmemcheck,
+            #[cfg(feature = "wmemcheck")]
+            wmemcheck_allocator,
         }
     }
 
@@ -317,6 +325,7 @@ impl wasmtime_environ::Compiler for Compiler {
             &mut context.func,
             &mut func_env,
         )?;
+        compiler.cx.frame_state = func_env.take_frame_state();
 
         if self.tunables.inlining {
             compiler
@@ -574,6 +583,7 @@ impl wasmtime_environ::Compiler for Compiler {
         let mut addrs = AddressMapSection::default();
         let mut traps = TrapEncodingBuilder::default();
         let mut stack_maps = StackMapSection::default();
+        let mut frame_states = FrameStateSection::default();
         let mut exception_tables = ExceptionTableBuilder::default();
 
         let mut ret = Vec::with_capacity(funcs.len());
@@ -596,6 +606,10 @@ impl wasmtime_environ::Compiler for Compiler {
                 func.buffer.user_stack_maps(),
             );
 
+            if let Some(frame_state) = func.frame_state() {
+                frame_states.push(range.clone(), frame_state);
+            }
+
             traps.push(range.clone(), &func.traps().collect::<Vec<_>>());
             clif_to_env_exception_tables(
                 &mut exception_tables,
@@ -617,6 +631,7 @@ impl wasmtime_environ::Compiler for Compiler {
             addrs.append_to(obj);
         }
         stack_maps.append_to(obj);
+        frame_states.append_to(obj);
         traps.append_to(obj);
 
         let exception_section = obj.add_section(
@@ -1401,6 +1416,12 @@ impl FunctionCompiler<'_> {
             }
         }
 
+        if let Some(frame_state) = self.cx.frame_state.take() {
+            let slot_offset = compiled_code.sized_stackslot_offsets[frame_state.slot()];
+            compiled_function
+                .set_frame_state(frame_state.finish(compiled_code.frame_size - slot_offset));
+        }
+
         compiled_function
             .set_sized_stack_slots(std::mem::take(&mut context.func.sized_stack_slots));
         self.compiler.contexts.lock().unwrap().push(self.cx);
@@ -1,3 +1,4 @@
+pub(crate) mod frame_state;
 mod gc;
 pub(crate) mod stack_switching;
 
@@ -29,6 +30,8 @@ use wasmtime_environ::{
     TagIndex, TripleExt, Tunables, TypeConvert, TypeIndex, VMOffsets, WasmCompositeInnerType,
     WasmFuncType, WasmHeapTopType, WasmHeapType, WasmRefType, WasmResult, WasmValType,
 };
+#[cfg(feature = "wmemcheck")]
+use wasmtime_environ::{EntityIndex, WmemcheckAllocatorFunc};
 use wasmtime_environ::{FUNCREF_INIT_BIT, FUNCREF_MASK};
 use wasmtime_math::f64_cvt_to_int_bounds;
 
@@ -182,6 +185,10 @@ pub struct FuncEnvironment<'module_environment> {
     /// slot on this function's stack to be used for the
     /// current continuation's `values` field.
     stack_switching_values_buffer: Option<ir::StackSlot>,
+
+    /// The preserved Wasm locals and operand stack of this function, if
+    /// `Tunables::coredump_frame_state` is enabled.
+    frame_state: Option<frame_state::FrameStateBuilder>,
 }
 
 impl<'module_environment> FuncEnvironment<'module_environment> {
@@ -238,6 +245,8 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
 
             stack_switching_handler_list_buffer: None,
             stack_switching_values_buffer: None,
+
+            frame_state: None,
         }
     }
 
@@ -603,44 +612,69 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
     }
 
     #[cfg(feature = "wmemcheck")]
-    fn hook_malloc_exit(&mut self, builder: &mut FunctionBuilder, retvals: &[ir::Value]) {
-        let check_malloc = self.builtin_functions.check_malloc(builder.func);
+    fn hook_allocator_exit(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        func: WmemcheckAllocatorFunc,
+        retvals: &[ir::Value],
+    ) {
         let vmctx = self.vmctx_val(&mut builder.cursor());
-        let func_args = builder
+        // Skip the callee and caller vmctx parameters; the remaining ones are
+        // the wasm parameters, whose arity was checked by
+        // `wmemcheck_allocator_func`.
+        let params = builder
             .func
             .dfg
-            .block_params(builder.func.layout.entry_block().unwrap());
-        let len = if func_args.len() < 3 {
-            return;
-        } else {
-            // If a function named `malloc` has at least one argument, we assume the
-            // first argument is the requested allocation size.
-            func_args[2]
-        };
-        let retval = if retvals.len() < 1 {
-            return;
-        } else {
-            retvals[0]
-        };
-        builder.ins().call(check_malloc, &[vmctx, retval, len]);
+            .block_params(builder.func.layout.entry_block().unwrap())[2..]
+            .to_vec();
+        let args: SmallVec<[ir::Value; 2]> = params
+            .iter()
+            .take(2)
+            .map(|v| Self::wmemcheck_u64(builder, *v))
+            .collect();
+        let ret = retvals.first().map(|v| Self::wmemcheck_u64(builder, *v));
+        match func {
+            WmemcheckAllocatorFunc::Malloc => {
+                let check_malloc = self.builtin_functions.check_malloc(builder.func);
+                builder
+                    .ins()
+                    .call(check_malloc, &[vmctx, ret.unwrap(), args[0]]);
+            }
+            WmemcheckAllocatorFunc::AlignedAlloc => {
+                // The first argument is the alignment.
+                let check_malloc = self.builtin_functions.check_malloc(builder.func);
+                builder
+                    .ins()
+                    .call(check_malloc, &[vmctx, ret.unwrap(), args[1]]);
+            }
+            WmemcheckAllocatorFunc::Free => {
+                let check_free = self.builtin_functions.check_free(builder.func);
+                builder.ins().call(check_free, &[vmctx, args[0]]);
+            }
+            WmemcheckAllocatorFunc::Realloc => {
+                let check_realloc = self.builtin_functions.check_realloc(builder.func);
+                builder
+                    .ins()
+                    .call(check_realloc, &[vmctx, args[0], ret.unwrap(), args[1]]);
+            }
+            WmemcheckAllocatorFunc::Calloc => {
+                let check_calloc = self.builtin_functions.check_calloc(builder.func);
+                builder
+                    .ins()
+                    .call(check_calloc, &[vmctx, ret.unwrap(), args[0], args[1]]);
+            }
+        }
     }
 
+    /// Zero-extends a 32-bit address or length to the 64 bits that wmemcheck
+    /// builtins take, so that both 32- and 64-bit memories are supported.
     #[cfg(feature = "wmemcheck")]
-    fn hook_free_exit(&mut self, builder: &mut FunctionBuilder) {
-        let check_free = self.builtin_functions.check_free(builder.func);
-        let vmctx = self.vmctx_val(&mut builder.cursor());
-        let func_args = builder
-            .func
-            .dfg
-            .block_params(builder.func.layout.entry_block().unwrap());
-        let ptr = if func_args.len() < 3 {
-            return;
+    fn wmemcheck_u64(builder: &mut FunctionBuilder, val: ir::Value) -> ir::Value {
+        if builder.func.dfg.value_type(val) == I64 {
+            val
         } else {
-            // If a function named `free` has at least one argument, we assume the
-            // first argument is a pointer to memory.
-            func_args[2]
-        };
-        builder.ins().call(check_free, &[vmctx, ptr]);
+            builder.ins().uextend(I64, val)
+        }
     }
 
     fn epoch_ptr(&mut self, builder: &mut FunctionBuilder<'_>) -> ir::Value {
@@ -886,34 +920,69 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         result_param
     }
 
+    /// Whether accesses to `mem_index` are checked by wmemcheck.
     #[cfg(feature = "wmemcheck")]
-    fn check_malloc_start(&mut self, builder: &mut FunctionBuilder) {
-        let malloc_start = self.builtin_functions.malloc_start(builder.func);
-        let vmctx = self.vmctx_val(&mut builder.cursor());
-        builder.ins().call(malloc_start, &[vmctx]);
+    fn wmemcheck_memory(&self, mem_index: MemoryIndex) -> bool {
+        self.compiler.wmemcheck && mem_index.as_u32() == self.compiler.wmemcheck_allocator.memory
     }
 
     #[cfg(feature = "wmemcheck")]
-    fn check_free_start(&mut self, builder: &mut FunctionBuilder) {
-        let free_start = self.builtin_functions.free_start(builder.func);
+    fn hook_allocator_start(&mut self, builder: &mut FunctionBuilder) {
+        let allocator_start = self.builtin_functions.allocator_start(builder.func);
         let vmctx = self.vmctx_val(&mut builder.cursor());
-        builder.ins().call(free_start, &[vmctx]);
+        builder.ins().call(allocator_start, &[vmctx]);
     }
 
+    /// Returns which of the guest's allocator functions, if any, is being
+    /// compiled.
+    ///
+    /// Functions are recognized by their name in the name section or any of
+    /// their export names, and must take and return integers as the function
+    /// they're treated as would.
     #[cfg(feature = "wmemcheck")]
-    fn current_func_name(&self, builder: &mut FunctionBuilder) -> Option<&str> {
+    fn wmemcheck_allocator_func(
+        &self,
+        builder: &mut FunctionBuilder,
+    ) -> Option<WmemcheckAllocatorFunc> {
         let func_index = match &builder.func.name {
             ir::UserFuncName::User(user) => FuncIndex::from_u32(user.index),
             _ => {
                 panic!("function name not a UserFuncName::User as expected")
             }
         };
-        self.translation
+        let allocator = &self.compiler.wmemcheck_allocator;
+        let func = self
+            .translation
             .debuginfo
             .name_section
             .func_names
             .get(&func_index)
-            .copied()
+            .and_then(|name| allocator.classify(name))
+            .or_else(|| {
+                self.module
+                    .exports
+                    .iter()
+                    .filter(|(_, e)| **e == EntityIndex::Function(func_index))
+                    .find_map(|(name, _)| allocator.classify(name))
+            })?;
+
+        let sig = &builder.func.signature;
+        let is_int = |p: &ir::AbiParam| p.value_type == I32 || p.value_type == I64;
+        let params = &sig.params[2..];
+        let (num_params, has_result) = match func {
+            WmemcheckAllocatorFunc::Malloc => (1, true),
+            WmemcheckAllocatorFunc::Free => (1, false),
+            WmemcheckAllocatorFunc::Realloc
+            | WmemcheckAllocatorFunc::Calloc
+            | WmemcheckAllocatorFunc::AlignedAlloc => (2, true),
+        };
+        let params_ok = params.len() >= num_params && params[..num_params].iter().all(is_int);
+        let result_ok = !has_result || sig.returns.first().is_some_and(is_int);
+        if params_ok && result_ok {
+            Some(func)
+        } else {
+            None
+        }
     }
 
     /// Proof-carrying code: create a memtype describing an empty
@@ -2672,7 +2741,9 @@ impl FuncEnvironment<'_> {
             libcall,
             &[vmctx, interned_type_index, data_index, data_offset, len],
         );
-        Ok(builder.func.dfg.first_result(call_inst))
+        let array_ref = builder.func.dfg.first_result(call_inst);
+        builder.declare_value_needs_stack_map(array_ref);
+        Ok(array_ref)
     }
 
     pub fn translate_array_new_elem(
@@ -2694,7 +2765,9 @@ impl FuncEnvironment<'_> {
             libcall,
             &[vmctx, interned_type_index, elem_index, elem_offset, len],
         );
-        Ok(builder.func.dfg.first_result(call_inst))
+        let array_ref = builder.func.dfg.first_result(call_inst);
+        builder.declare_value_needs_stack_map(array_ref);
+        Ok(array_ref)
     }
 
     pub fn translate_array_copy(
@@ -3547,6 +3620,7 @@ impl FuncEnvironment<'_> {
         if self.tunables.consume_fuel && state.reachable() {
             self.fuel_after_op(op, builder);
         }
+        self.frame_state_after_op(op, builder, state);
         Ok(())
     }
 
@@ -3582,13 +3656,8 @@ impl FuncEnvironment<'_> {
         }
 
         #[cfg(feature = "wmemcheck")]
-        if self.compiler.wmemcheck {
-            let func_name = self.current_func_name(builder);
-            if func_name == Some("malloc") {
-                self.check_malloc_start(builder);
-            } else if func_name == Some("free") {
-                self.check_free_start(builder);
-            }
+        if self.compiler.wmemcheck && self.wmemcheck_allocator_func(builder).is_some() {
+            self.hook_allocator_start(builder);
         }
 
         Ok(())
@@ -3744,11 +3813,8 @@ impl FuncEnvironment<'_> {
     pub fn handle_before_return(&mut self, retvals: &[ir::Value], builder: &mut FunctionBuilder) {
         #[cfg(feature = "wmemcheck")]
         if self.compiler.wmemcheck {
-            let func_name = self.current_func_name(builder);
-            if func_name == Some("malloc") {
-                self.hook_malloc_exit(builder, retvals);
-            } else if func_name == Some("free") {
-                self.hook_free_exit(builder);
+            if let Some(func) = self.wmemcheck_allocator_func(builder) {
+                self.hook_allocator_exit(builder, func, retvals);
             }
         }
         #[cfg(not(feature = "wmemcheck"))]
@@ -3759,42 +3825,46 @@ impl FuncEnvironment<'_> {
         &mut self,
         builder: &mut FunctionBuilder,
         val_size: u8,
+        mem_index: MemoryIndex,
         addr: ir::Value,
         offset: u64,
     ) {
         #[cfg(feature = "wmemcheck")]
-        if self.compiler.wmemcheck {
+        if self.wmemcheck_memory(mem_index) {
             let check_load = self.builtin_functions.check_load(builder.func);
             let vmctx = self.vmctx_val(&mut builder.cursor());
             let num_bytes = builder.ins().iconst(I32, val_size as i64);
+            let addr = Self::wmemcheck_u64(builder, addr);
             let offset_val = builder.ins().iconst(I64, offset as i64);
             builder
                 .ins()
                 .call(check_load, &[vmctx, num_bytes, addr, offset_val]);
         }
         #[cfg(not(feature = "wmemcheck"))]
-        let _ = (builder, val_size, addr, offset);
+        let _ = (builder, val_size, mem_index, addr, offset);
     }
 
     pub fn before_store(
         &mut self,
         builder: &mut FunctionBuilder,
         val_size: u8,
+        mem_index: MemoryIndex,
         addr: ir::Value,
         offset: u64,
     ) {
         #[cfg(feature = "wmemcheck")]
-        if self.compiler.wmemcheck {
+        if self.wmemcheck_memory(mem_index) {
             let check_store = self.builtin_functions.check_store(builder.func);
             let vmctx = self.vmctx_val(&mut builder.cursor());
             let num_bytes = builder.ins().iconst(I32, val_size as i64);
+            let addr = Self::wmemcheck_u64(builder, addr);
             let offset_val = builder.ins().iconst(I64, offset as i64);
             builder
                 .ins()
                 .call(check_store, &[vmctx, num_bytes, addr, offset_val]);
         }
         #[cfg(not(feature = "wmemcheck"))]
-        let _ = (builder, val_size, addr, offset);
+        let _ = (builder, val_size, mem_index, addr, offset);
     }
 
     pub fn update_global(
@@ -3805,11 +3875,13 @@ impl FuncEnvironment<'_> {
     ) {
         #[cfg(feature = "wmemcheck")]
         if self.compiler.wmemcheck {
-            if global_index.index() == 0 {
+            let ty = builder.func.dfg.value_type(value);
+            if global_index.index() == 0 && (ty == I32 || ty == I64) {
                 // We are making the assumption that global 0 is the auxiliary stack pointer.
                 let update_stack_pointer =
                     self.builtin_functions.update_stack_pointer(builder.func);
                 let vmctx = self.vmctx_val(&mut builder.cursor());
+                let value = Self::wmemcheck_u64(builder, value);
                 builder.ins().call(update_stack_pointer, &[vmctx, value]);
             }
         }
@@ -3824,9 +3896,10 @@ impl FuncEnvironment<'_> {
         mem_index: MemoryIndex,
     ) {
         #[cfg(feature = "wmemcheck")]
-        if self.compiler.wmemcheck && mem_index.as_u32() == 0 {
+        if self.wmemcheck_memory(mem_index) {
             let update_mem_size = self.builtin_functions.update_mem_size(builder.func);
             let vmctx = self.vmctx_val(&mut builder.cursor());
+            let num_pages = Self::wmemcheck_u64(builder, num_pages);
             builder.ins().call(update_mem_size, &[vmctx, num_pages]);
         }
         #[cfg(not(feature = "wmemcheck"))]
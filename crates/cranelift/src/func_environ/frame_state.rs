@@ -0,0 +1,202 @@
+//! Preservation of Wasm locals and the operand stack for core dumps.
+//!
+//! When `Tunables::coredump_frame_state` is enabled every compiled function
+//! reserves a stack slot which mirrors the Wasm-level state of its frame. The
+//! `VMContext` and all locals are stored to the slot on entry, locals are
+//! additionally stored whenever they're written, and the operand stack is
+//! stored before each instruction that may trap or call. The runtime reads
+//! this slot back when capturing a core dump.
+
+use crate::func_environ::FuncEnvironment;
+use crate::translate::FuncTranslationStacks;
+use cranelift_codegen::ir::{self, InstBuilder};
+use cranelift_frontend::{FunctionBuilder, Variable};
+use wasmparser::{FuncValidator, Operator, ValType, WasmModuleResources};
+use wasmtime_environ::{FRAME_STATE_VALUE_SIZE, FrameValueType, FunctionFrameState};
+
+/// The frame state of the function currently being translated.
+pub(crate) struct FrameStateBuilder {
+    /// The stack slot holding the values of locals and operands.
+    slot: ir::StackSlot,
+    /// The types of the function's locals.
+    locals: Vec<FrameValueType>,
+    /// The operand stack types at each preserved Wasm offset.
+    points: Vec<(u32, Vec<FrameValueType>)>,
+}
+
+impl FrameStateBuilder {
+    /// Returns the stack slot used by this frame state, to be resolved to a
+    /// frame offset once the function has been compiled.
+    pub(crate) fn slot(&self) -> ir::StackSlot {
+        self.slot
+    }
+
+    /// Finishes this frame state now that the offset of its stack slot from
+    /// the frame pointer is known.
+    pub(crate) fn finish(self, fp_offset: u32) -> FunctionFrameState {
+        FunctionFrameState {
+            fp_offset,
+            locals: self.locals,
+            points: self.points,
+        }
+    }
+}
+
+fn frame_value_type(ty: Option<ValType>) -> FrameValueType {
+    match ty {
+        Some(ValType::I32) => FrameValueType::I32,
+        Some(ValType::I64) => FrameValueType::I64,
+        Some(ValType::F32) => FrameValueType::F32,
+        Some(ValType::F64) => FrameValueType::F64,
+        Some(ValType::V128 | ValType::Ref(_)) | None => FrameValueType::Unknown,
+    }
+}
+
+fn value_offset(index: usize) -> i32 {
+    i32::try_from(index).unwrap() * i32::try_from(FRAME_STATE_VALUE_SIZE).unwrap()
+}
+
+/// Returns whether the operand stack must be preserved before `op`.
+///
+/// This is the case for any instruction which may trap or call, and so appear
+/// in a backtrace. Instructions which only shuffle values around or transfer
+/// control within the function are skipped.
+fn is_sequence_point(op: &Operator<'_>) -> bool {
+    !matches!(
+        op,
+        Operator::Nop
+            | Operator::Drop
+            | Operator::Block { .. }
+            | Operator::If { .. }
+            | Operator::Else
+            | Operator::End
+            | Operator::Br { .. }
+            | Operator::BrIf { .. }
+            | Operator::BrTable { .. }
+            | Operator::Return
+            | Operator::Select
+            | Operator::TypedSelect { .. }
+            | Operator::LocalGet { .. }
+            | Operator::LocalSet { .. }
+            | Operator::LocalTee { .. }
+            | Operator::I32Const { .. }
+            | Operator::I64Const { .. }
+            | Operator::F32Const { .. }
+            | Operator::F64Const { .. }
+            | Operator::V128Const { .. }
+            | Operator::RefNull { .. }
+    )
+}
+
+impl FuncEnvironment<'_> {
+    /// Creates the frame state's stack slot and stores the `VMContext` and the
+    /// initial value of every local into it.
+    pub(crate) fn frame_state_function_entry(
+        &mut self,
+        validator: &FuncValidator<impl WasmModuleResources>,
+        builder: &mut FunctionBuilder,
+    ) {
+        if !self.tunables.coredump_frame_state {
+            return;
+        }
+        let locals = (0..validator.len_locals())
+            .map(|i| frame_value_type(validator.get_local_type(i)))
+            .collect::<Vec<_>>();
+        let slot = builder.create_sized_stack_slot(ir::StackSlotData::new(
+            ir::StackSlotKind::ExplicitSlot,
+            FRAME_STATE_VALUE_SIZE * u32::try_from(1 + locals.len()).unwrap(),
+            3,
+        ));
+        let state = FrameStateBuilder {
+            slot,
+            locals,
+            points: Vec::new(),
+        };
+        let vmctx = self.vmctx_val(&mut builder.cursor());
+        builder.ins().stack_store(vmctx, slot, 0);
+        for i in 0..state.locals.len() {
+            store_local(&state, builder, i);
+        }
+        self.frame_state = Some(state);
+    }
+
+    /// Preserves the operand stack before `op` if it's an instruction which
+    /// may trap or call.
+    ///
+    /// This must be called before `op` is validated so that the validator
+    /// reflects the operand stack's types prior to `op`.
+    pub(crate) fn frame_state_before_op(
+        &mut self,
+        validator: &FuncValidator<impl WasmModuleResources>,
+        op: &Operator<'_>,
+        pos: usize,
+        builder: &mut FunctionBuilder,
+        stacks: &FuncTranslationStacks,
+    ) {
+        let Some(state) = &mut self.frame_state else {
+            return;
+        };
+        if !stacks.reachable() || !is_sequence_point(op) {
+            return;
+        }
+        let height = usize::try_from(validator.operand_stack_height()).unwrap();
+        debug_assert_eq!(height, stacks.stack.len());
+        if height != stacks.stack.len() {
+            return;
+        }
+
+        let operands = (0..height)
+            .rev()
+            .map(|depth| frame_value_type(validator.get_operand_type(depth).flatten()))
+            .collect::<Vec<_>>();
+        let base = 1 + state.locals.len();
+        for (i, (ty, val)) in operands.iter().zip(&stacks.stack).enumerate() {
+            if *ty != FrameValueType::Unknown {
+                builder
+                    .ins()
+                    .stack_store(*val, state.slot, value_offset(base + i));
+            }
+        }
+
+        let size = FRAME_STATE_VALUE_SIZE * u32::try_from(base + height).unwrap();
+        let slot = &mut builder.func.sized_stack_slots[state.slot];
+        slot.size = slot.size.max(size);
+        state.points.push((u32::try_from(pos).unwrap(), operands));
+    }
+
+    /// Updates the preserved value of a local after `op` has written to it.
+    pub(crate) fn frame_state_after_op(
+        &mut self,
+        op: &Operator<'_>,
+        builder: &mut FunctionBuilder,
+        stacks: &FuncTranslationStacks,
+    ) {
+        let Some(state) = &self.frame_state else {
+            return;
+        };
+        if !stacks.reachable() {
+            return;
+        }
+        match op {
+            Operator::LocalSet { local_index } | Operator::LocalTee { local_index } => {
+                store_local(state, builder, usize::try_from(*local_index).unwrap());
+            }
+            _ => {}
+        }
+    }
+
+    /// Takes the frame state of the function which was just translated.
+    pub(crate) fn take_frame_state(&mut self) -> Option<FrameStateBuilder> {
+        self.frame_state.take()
+    }
+}
+
+fn store_local(state: &FrameStateBuilder, builder: &mut FunctionBuilder, index: usize) {
+    if state.locals[index] == FrameValueType::Unknown {
+        return;
+    }
+    let val = builder.use_var(Variable::from_u32(u32::try_from(index).unwrap()));
+    builder
+        .ins()
+        .stack_store(val, state.slot, value_offset(1 + index));
+}
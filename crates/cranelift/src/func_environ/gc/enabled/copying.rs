@@ -0,0 +1,387 @@
+//! Compiler for the copying collector.
+//!
+//! The copying collector moves objects during collection, so every GC
+//! reference that is live across a safepoint must be included in stack maps:
+//! the collector rewrites the stack slots to point to the objects' new
+//! locations, and the code we emit here reloads them after each call. There are
+//! no other read or write barriers.
+//!
+//! Allocation is a bump-pointer fast path, which is emitted inline, and a
+//! `gc_alloc_raw` libcall slow path for when the current free space is
+//! exhausted, which may trigger a collection.
+
+use super::*;
+use crate::func_environ::FuncEnvironment;
+use cranelift_codegen::ir::{self, InstBuilder};
+use cranelift_frontend::FunctionBuilder;
+use wasmtime_environ::copying::{
+    EXCEPTION_TAG_DEFINED_OFFSET, EXCEPTION_TAG_INSTANCE_OFFSET, OBJECT_ALIGN,
+};
+use wasmtime_environ::{
+    GcTypeLayouts, ModuleInternedTypeIndex, PtrSize, TypeIndex, VMGcKind, WasmRefType, WasmResult,
+    copying::CopyingTypeLayouts,
+};
+
+#[derive(Default)]
+pub struct CopyingCompiler {
+    layouts: CopyingTypeLayouts,
+}
+
+impl CopyingCompiler {
+    /// Emit code to allocate a new object of the given kind and type.
+    ///
+    /// `size` must be greater than or equal to `size_of(VMGcHeader)`.
+    ///
+    /// The resulting values are
+    ///
+    /// 1. The `VMGcRef` indexing into the GC heap.
+    ///
+    /// 2. The raw pointer to the start of the object inside the GC heap. This
+    ///    may be used to access up to `size` bytes.
+    fn emit_alloc(
+        &mut self,
+        func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder,
+        kind: VMGcKind,
+        ty: ModuleInternedTypeIndex,
+        size: ir::Value,
+    ) -> (ir::Value, ir::Value) {
+        log::trace!("emit_alloc(kind={kind:?}, ty={ty:?}, size={size})");
+
+        assert_eq!(builder.func.dfg.value_type(size), ir::types::I32);
+
+        let current_block = builder.current_block().unwrap();
+        let bump_block = builder.create_block();
+        let slow_block = builder.create_block();
+        let continue_block = builder.create_block();
+        let gc_ref = builder.append_block_param(continue_block, ir::types::I32);
+
+        builder.ensure_inserted_block();
+        builder.insert_block_after(bump_block, current_block);
+        builder.insert_block_after(continue_block, bump_block);
+        builder.insert_block_after(slow_block, continue_block);
+
+        // Round the size up to the object alignment. Because every object's
+        // size is a multiple of that alignment, and the bump "pointer" starts
+        // out aligned, it is always aligned and we don't need to align it
+        // ourselves here.
+        let align_minus_one = i64::from(OBJECT_ALIGN - 1);
+        let align_minus_one = builder.ins().iconst(ir::types::I32, align_minus_one);
+        let size = func_env.uadd_overflow_trap(
+            builder,
+            size,
+            align_minus_one,
+            crate::TRAP_ALLOCATION_TOO_LARGE,
+        );
+        let size = builder.ins().band_imm(size, !i64::from(OBJECT_ALIGN - 1));
+
+        // Check that the size fits in the unused bits of a `VMGcKind`, since
+        // the copying collector stores the object's size there.
+        let mask = builder
+            .ins()
+            .iconst(ir::types::I32, i64::from(VMGcKind::MASK));
+        let masked = builder.ins().band(size, mask);
+        func_env.trapnz(builder, masked, crate::TRAP_ALLOCATION_TOO_LARGE);
+
+        // Load the bump "pointer" (it is actually an index into the GC heap,
+        // not a raw pointer) and check whether the allocation fits in the heap
+        // space we have left.
+        let pointer_type = func_env.pointer_type();
+        let vmctx = func_env.vmctx_val(&mut builder.cursor());
+        let ptr_to_next = builder.ins().load(
+            pointer_type,
+            ir::MemFlags::trusted().with_readonly(),
+            vmctx,
+            i32::from(func_env.offsets.ptr.vmctx_gc_heap_data()),
+        );
+        let next = builder
+            .ins()
+            .load(ir::types::I32, ir::MemFlags::trusted(), ptr_to_next, 0);
+        let end_of_object =
+            func_env.uadd_overflow_trap(builder, next, size, crate::TRAP_ALLOCATION_TOO_LARGE);
+        let uext_end_of_object = uextend_i32_to_pointer_type(builder, pointer_type, end_of_object);
+        let bound = func_env.get_gc_heap_bound(builder);
+        let is_in_bounds = builder.ins().icmp(
+            ir::condcodes::IntCC::UnsignedLessThanOrEqual,
+            uext_end_of_object,
+            bound,
+        );
+        builder
+            .ins()
+            .brif(is_in_bounds, bump_block, &[], slow_block, &[]);
+
+        // The fast path: write the header and update the bump "pointer".
+        //
+        // TODO: Ideally we would use a single `i64` store to write both the
+        // header and the type index, but that requires generating different
+        // code for big-endian architectures, and I haven't bothered doing that
+        // yet.
+        log::trace!("emit_alloc: bump_block");
+        builder.switch_to_block(bump_block);
+        builder.seal_block(bump_block);
+        let base = func_env.get_gc_heap_base(builder);
+        let uext_next = uextend_i32_to_pointer_type(builder, pointer_type, next);
+        let ptr_to_object = builder.ins().iadd(base, uext_next);
+        let kind_val = builder
+            .ins()
+            .iconst(ir::types::I32, i64::from(kind.as_u32()));
+        let kind_and_size = builder.ins().bor(kind_val, size);
+        let shared_ty = func_env.module_interned_to_shared_ty(&mut builder.cursor(), ty);
+        builder.ins().store(
+            ir::MemFlags::trusted(),
+            kind_and_size,
+            ptr_to_object,
+            i32::try_from(wasmtime_environ::VM_GC_HEADER_KIND_OFFSET).unwrap(),
+        );
+        builder.ins().store(
+            ir::MemFlags::trusted(),
+            shared_ty,
+            ptr_to_object,
+            i32::try_from(wasmtime_environ::VM_GC_HEADER_TYPE_INDEX_OFFSET).unwrap(),
+        );
+        builder
+            .ins()
+            .store(ir::MemFlags::trusted(), end_of_object, ptr_to_next, 0);
+        builder
+            .ins()
+            .jump(continue_block, &[ir::BlockArg::Value(next)]);
+
+        // The slow path: call out to the runtime, which will collect garbage
+        // and/or grow the GC heap as necessary.
+        log::trace!("emit_alloc: slow_block");
+        builder.switch_to_block(slow_block);
+        builder.seal_block(slow_block);
+        builder.set_cold_block(slow_block);
+        let gc_alloc_raw_builtin = func_env.builtin_functions.gc_alloc_raw(builder.func);
+        let vmctx = func_env.vmctx_val(&mut builder.cursor());
+        let kind_val = builder
+            .ins()
+            .iconst(ir::types::I32, i64::from(kind.as_u32()));
+        let ty_val = builder.ins().iconst(ir::types::I32, i64::from(ty.as_u32()));
+        let align = builder
+            .ins()
+            .iconst(ir::types::I32, i64::from(OBJECT_ALIGN));
+        let call_inst = builder.ins().call(
+            gc_alloc_raw_builtin,
+            &[vmctx, kind_val, ty_val, size, align],
+        );
+        let slow_gc_ref = builder.func.dfg.first_result(call_inst);
+        builder
+            .ins()
+            .jump(continue_block, &[ir::BlockArg::Value(slow_gc_ref)]);
+
+        // Join the two paths and compute the raw pointer to the new object.
+        // Note that the GC heap's base may have changed if the slow path grew
+        // the heap, so we must recompute it here.
+        log::trace!("emit_alloc: continue_block");
+        builder.switch_to_block(continue_block);
+        builder.seal_block(continue_block);
+        builder.declare_value_needs_stack_map(gc_ref);
+        let base = func_env.get_gc_heap_base(builder);
+        let uext_gc_ref = uextend_i32_to_pointer_type(builder, pointer_type, gc_ref);
+        let ptr_to_object = builder.ins().iadd(base, uext_gc_ref);
+
+        log::trace!("emit_alloc(..) -> ({gc_ref}, {ptr_to_object})");
+        (gc_ref, ptr_to_object)
+    }
+}
+
+impl GcCompiler for CopyingCompiler {
+    fn layouts(&self) -> &dyn GcTypeLayouts {
+        &self.layouts
+    }
+
+    fn alloc_array(
+        &mut self,
+        func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder<'_>,
+        array_type_index: TypeIndex,
+        init: super::ArrayInit<'_>,
+    ) -> WasmResult<ir::Value> {
+        let interned_type_index =
+            func_env.module.types[array_type_index].unwrap_module_type_index();
+        let ptr_ty = func_env.pointer_type();
+
+        let len_offset = gc_compiler(func_env)?.layouts().array_length_field_offset();
+        let array_layout = func_env.array_layout(interned_type_index).clone();
+        let base_size = array_layout.base_size;
+        let len_to_elems_delta = base_size.checked_sub(len_offset).unwrap();
+
+        // First, compute the array's total size from its base size, element
+        // size, and length.
+        let len = init.len(&mut builder.cursor());
+        let size = emit_array_size(func_env, builder, &array_layout, len);
+
+        // Next, allocate the array.
+        assert!(array_layout.align <= OBJECT_ALIGN);
+        let (gc_ref, ptr_to_object) = self.emit_alloc(
+            func_env,
+            builder,
+            VMGcKind::ArrayRef,
+            interned_type_index,
+            size,
+        );
+
+        // Write the array's length into its field.
+        //
+        // Note: we don't need to bounds-check the GC ref access here, because
+        // the result of the allocation is trusted and we aren't reading any
+        // pointers or offsets out from the (untrusted) GC heap.
+        let len_addr = builder.ins().iadd_imm(ptr_to_object, i64::from(len_offset));
+        let len = init.len(&mut builder.cursor());
+        builder
+            .ins()
+            .store(ir::MemFlags::trusted(), len, len_addr, 0);
+
+        // Finally, initialize the elements.
+        let len_to_elems_delta = builder.ins().iconst(ptr_ty, i64::from(len_to_elems_delta));
+        let elems_addr = builder.ins().iadd(len_addr, len_to_elems_delta);
+        init.initialize(
+            func_env,
+            builder,
+            interned_type_index,
+            base_size,
+            size,
+            elems_addr,
+            |func_env, builder, elem_ty, elem_addr, val| {
+                write_field_at_addr(func_env, builder, elem_ty, elem_addr, val)
+            },
+        )?;
+
+        Ok(gc_ref)
+    }
+
+    fn alloc_struct(
+        &mut self,
+        func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder<'_>,
+        struct_type_index: TypeIndex,
+        field_vals: &[ir::Value],
+    ) -> WasmResult<ir::Value> {
+        let interned_type_index =
+            func_env.module.types[struct_type_index].unwrap_module_type_index();
+        let struct_layout = func_env.struct_or_exn_layout(interned_type_index);
+
+        // Copy some stuff out of the struct layout to avoid borrowing issues.
+        let struct_size = struct_layout.size;
+        assert!(struct_layout.align <= OBJECT_ALIGN);
+
+        let struct_size_val = builder.ins().iconst(ir::types::I32, i64::from(struct_size));
+        let (struct_ref, raw_struct_pointer) = self.emit_alloc(
+            func_env,
+            builder,
+            VMGcKind::StructRef,
+            interned_type_index,
+            struct_size_val,
+        );
+
+        // Initialize the struct's fields.
+        //
+        // Note: we don't need to bounds-check the GC ref access here, because
+        // the result of the allocation is trusted and we aren't reading any
+        // pointers or offsets out from the (untrusted) GC heap.
+        initialize_struct_fields(
+            func_env,
+            builder,
+            interned_type_index,
+            raw_struct_pointer,
+            field_vals,
+            |func_env, builder, ty, field_addr, val| {
+                write_field_at_addr(func_env, builder, ty, field_addr, val)
+            },
+        )?;
+
+        Ok(struct_ref)
+    }
+
+    fn alloc_exn(
+        &mut self,
+        func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder<'_>,
+        tag_index: TagIndex,
+        field_vals: &[ir::Value],
+        instance_id: ir::Value,
+        tag: ir::Value,
+    ) -> WasmResult<ir::Value> {
+        let interned_type_index = func_env.module.tags[tag_index]
+            .exception
+            .unwrap_module_type_index();
+        let exn_layout = func_env.struct_or_exn_layout(interned_type_index);
+
+        // Copy some stuff out of the exception layout to avoid borrowing issues.
+        let exn_size = exn_layout.size;
+        assert!(exn_layout.align <= OBJECT_ALIGN);
+
+        let exn_size_val = builder.ins().iconst(ir::types::I32, i64::from(exn_size));
+        let (exn_ref, raw_exn_pointer) = self.emit_alloc(
+            func_env,
+            builder,
+            VMGcKind::ExnRef,
+            interned_type_index,
+            exn_size_val,
+        );
+
+        // Initialize the exception object's fields.
+        //
+        // Note: we don't need to bounds-check the GC ref access here, because
+        // the result of the allocation is trusted and we aren't reading any
+        // pointers or offsets out from the (untrusted) GC heap.
+        initialize_struct_fields(
+            func_env,
+            builder,
+            interned_type_index,
+            raw_exn_pointer,
+            field_vals,
+            |func_env, builder, ty, field_addr, val| {
+                write_field_at_addr(func_env, builder, ty, field_addr, val)
+            },
+        )?;
+
+        // Initialize the tag fields.
+        let instance_id_addr = builder
+            .ins()
+            .iadd_imm(raw_exn_pointer, i64::from(EXCEPTION_TAG_INSTANCE_OFFSET));
+        write_field_at_addr(
+            func_env,
+            builder,
+            WasmStorageType::Val(WasmValType::I32),
+            instance_id_addr,
+            instance_id,
+        )?;
+        let tag_addr = builder
+            .ins()
+            .iadd_imm(raw_exn_pointer, i64::from(EXCEPTION_TAG_DEFINED_OFFSET));
+        write_field_at_addr(
+            func_env,
+            builder,
+            WasmStorageType::Val(WasmValType::I32),
+            tag_addr,
+            tag,
+        )?;
+
+        Ok(exn_ref)
+    }
+
+    fn translate_read_gc_reference(
+        &mut self,
+        _func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder,
+        ty: WasmRefType,
+        src: ir::Value,
+        flags: ir::MemFlags,
+    ) -> WasmResult<ir::Value> {
+        unbarriered_load_gc_ref(builder, ty.heap_type, src, flags)
+    }
+
+    fn translate_write_gc_reference(
+        &mut self,
+        _func_env: &mut FuncEnvironment<'_>,
+        builder: &mut FunctionBuilder,
+        ty: WasmRefType,
+        dst: ir::Value,
+        new_val: ir::Value,
+        flags: ir::MemFlags,
+    ) -> WasmResult<()> {
+        unbarriered_store_gc_ref(builder, ty.heap_type, dst, new_val, flags)
+    }
+}
@@ -221,7 +221,16 @@ pub fn translate_operator(
             if builder.func.dfg.value_type(arg2).is_vector() {
                 arg2 = optionally_bitcast_vector(arg2, I8X16, builder);
             }
-            stack.push1(builder.ins().select(cond, arg1, arg2));
+            let result = builder.ins().select(cond, arg1, arg2);
+            // Selecting between two GC references produces a new value, which
+            // must be in stack maps just like its operands.
+            if let [.., WasmValType::Ref(r_ty), _] = operand_types {
+                let (_, needs_stack_map) = environ.reference_type(r_ty.heap_type);
+                if needs_stack_map {
+                    builder.declare_value_needs_stack_map(result);
+                }
+            }
+            stack.push1(result);
         }
         Operator::Nop => {
             // We do nothing
@@ -3570,7 +3579,13 @@ fn translate_load(
             Reachability::Reachable((f, i, b)) => (f, i, b),
         };
 
-    environ.before_load(builder, mem_op_size, wasm_index, memarg.offset);
+    environ.before_load(
+        builder,
+        mem_op_size,
+        MemoryIndex::from_u32(memarg.memory),
+        wasm_index,
+        memarg.offset,
+    );
 
     let (load, dfg) = builder
         .ins()
@@ -3596,7 +3611,13 @@ fn translate_store(
         prepare_addr(memarg, mem_op_size, builder, stack, environ)?
     );
 
-    environ.before_store(builder, mem_op_size, wasm_index, memarg.offset);
+    environ.before_store(
+        builder,
+        mem_op_size,
+        MemoryIndex::from_u32(memarg.memory),
+        wasm_index,
+        memarg.offset,
+    );
 
     builder
         .ins()
@@ -241,6 +241,7 @@ fn parse_function_body(
     debug_assert_eq!(stack.control_stack.len(), 1, "State not initialized");
 
     environ.before_translate_function(builder, stack)?;
+    environ.frame_state_function_entry(validator, builder);
 
     let mut reader = OperatorsReader::new(reader);
     let mut operand_types = vec![];
@@ -250,6 +251,7 @@ fn parse_function_body(
         builder.set_srcloc(cur_srcloc(&reader.get_binary_reader()));
 
         let op = reader.read()?;
+        environ.frame_state_before_op(validator, &op, pos, builder, stack);
         let operand_types =
             validate_op_and_get_operand_types(validator, environ, &mut operand_types, &op, pos)?;
 
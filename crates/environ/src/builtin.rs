@@ -42,30 +42,37 @@ macro_rules! foreach_builtin_function {
             // Invoked when we reach a new epoch.
             #[cfg(target_has_atomic = "64")]
             new_epoch(vmctx: vmctx) -> u64;
+            // Invoked when the tier-up counter of a baseline-compiled function
+            // reaches zero.
+            #[allow(dead_code, reason = "only called from Winch-compiled code")]
+            tier_up(vmctx: vmctx, func: u32);
             // Invoked before malloc returns.
             #[cfg(feature = "wmemcheck")]
-            check_malloc(vmctx: vmctx, addr: u32, len: u32) -> bool;
+            check_malloc(vmctx: vmctx, addr: u64, len: u64) -> bool;
             // Invoked before the free returns.
             #[cfg(feature = "wmemcheck")]
-            check_free(vmctx: vmctx, addr: u32) -> bool;
+            check_free(vmctx: vmctx, addr: u64) -> bool;
+            // Invoked before realloc returns.
+            #[cfg(feature = "wmemcheck")]
+            check_realloc(vmctx: vmctx, old_addr: u64, new_addr: u64, len: u64) -> bool;
+            // Invoked before calloc returns.
+            #[cfg(feature = "wmemcheck")]
+            check_calloc(vmctx: vmctx, addr: u64, count: u64, size: u64) -> bool;
             // Invoked before a load is executed.
             #[cfg(feature = "wmemcheck")]
-            check_load(vmctx: vmctx, num_bytes: u32, addr: u32, offset: u32) -> bool;
+            check_load(vmctx: vmctx, num_bytes: u32, addr: u64, offset: u64) -> bool;
             // Invoked before a store is executed.
             #[cfg(feature = "wmemcheck")]
-            check_store(vmctx: vmctx, num_bytes: u32, addr: u32, offset: u32) -> bool;
-            // Invoked after malloc is called.
+            check_store(vmctx: vmctx, num_bytes: u32, addr: u64, offset: u64) -> bool;
+            // Invoked after one of the allocator functions is called.
             #[cfg(feature = "wmemcheck")]
-            malloc_start(vmctx: vmctx);
-            // Invoked after free is called.
-            #[cfg(feature = "wmemcheck")]
-            free_start(vmctx: vmctx);
+            allocator_start(vmctx: vmctx);
             // Invoked when wasm stack pointer is updated.
             #[cfg(feature = "wmemcheck")]
-            update_stack_pointer(vmctx: vmctx, value: u32);
+            update_stack_pointer(vmctx: vmctx, value: u64);
             // Invoked before memory.grow is called.
             #[cfg(feature = "wmemcheck")]
-            update_mem_size(vmctx: vmctx, num_bytes: u32);
+            update_mem_size(vmctx: vmctx, num_pages: u64);
 
             // Drop a non-stack GC reference (eg an overwritten table entry)
             // once it will no longer be used again. (Note: `val` is not of type
@@ -82,7 +89,7 @@ macro_rules! foreach_builtin_function {
 
             // Allocate a new, uninitialized GC object and return a reference to
             // it.
-            #[cfg(feature = "gc-drc")]
+            #[cfg(any(feature = "gc-drc", feature = "gc-copying"))]
             gc_alloc_raw(
                 vmctx: vmctx,
                 kind: u32,
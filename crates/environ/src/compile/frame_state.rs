@@ -0,0 +1,226 @@
+use crate::obj::ELF_WASMTIME_FRAME_STATE;
+use crate::prelude::*;
+use crate::{FRAME_STATE_VALUE_SIZE, FrameValueType};
+use core::ops::Range;
+use object::write::{Object, StandardSegment};
+use object::{LittleEndian, SectionKind, U32Bytes};
+
+/// The frame state of a single compiled function.
+///
+/// Functions compiled with `Tunables::coredump_frame_state` reserve a region
+/// of their stack frame which holds the frame's `VMContext`, the values of all
+/// locals and, at each instruction which may trap or call, the operand stack.
+/// Values are stored in consecutive slots of `FRAME_STATE_VALUE_SIZE` bytes:
+/// the `VMContext` first, then locals, and then operands from the bottom of the
+/// stack to the top.
+#[derive(Debug, Default)]
+pub struct FunctionFrameState {
+    /// The distance, in bytes, from the start of the frame state region up to
+    /// the frame pointer.
+    pub fp_offset: u32,
+    /// The types of the function's locals, including parameters.
+    pub locals: Vec<FrameValueType>,
+    /// The Wasm offset of each instruction where the operand stack is
+    /// preserved, along with the types of the stack's values at that point.
+    ///
+    /// This must be sorted by offset.
+    pub points: Vec<(u32, Vec<FrameValueType>)>,
+}
+
+/// Builder for the `ELF_WASMTIME_FRAME_STATE` section in compiled executables.
+///
+/// This format is parsed by `crate::frame_state`.
+///
+/// The current layout of the format is:
+///
+/// ```text
+/// ┌─────────────────────┬───── 0x00 (relative, not necessarily aligned)
+/// │ count: 4-byte LE    │
+/// ├─────────────────────┼───── 0x04
+/// │ start1: 4-byte LE   │
+/// │ ...                 │
+/// │ startN: 4-byte LE   │
+/// ├─────────────────────┼───── 0x04 + 4 * count
+/// │ end1: 4-byte LE     │
+/// │ ...                 │
+/// │ endN: 4-byte LE     │
+/// ├─────────────────────┼───── 0x04 + 8 * count
+/// │ offset1: 4-byte LE  │
+/// │ ...                 │
+/// │ offsetN: 4-byte LE  │
+/// ├─────────────────────┼───── 0x04 + 12 * count
+/// │ data[0]: 4-byte LE  │
+/// │ ...                 │
+/// │ data[M]: 4-byte LE  │
+/// └─────────────────────┴───── 0x04 + 12 * count + 4 * M
+/// ```
+///
+/// Here `startN` and `endN` are the range of a function in the text section,
+/// sorted by `startN`, and `offsetN` is the index in `data` of its frame
+/// state, which is encoded as:
+///
+/// ```text
+/// ┌─────────────────────────────────────────────┐
+/// │ fp_offset                                   │
+/// │ num_locals                                  │
+/// │ local_type[0] ... local_type[num_locals-1]  │
+/// │ num_points                                  │
+/// │ wasm_offset[0] ... wasm_offset[num_points-1]│
+/// │ operands[0] ... operands[num_points-1]      │
+/// └─────────────────────────────────────────────┘
+/// ```
+///
+/// Each `operands` entry is the index in `data` of a length-prefixed list of
+/// operand stack types. Types are encoded with `FrameValueType::to_u32`.
+#[derive(Default)]
+pub struct FrameStateSection {
+    starts: Vec<U32Bytes<LittleEndian>>,
+    ends: Vec<U32Bytes<LittleEndian>>,
+    pointers: Vec<U32Bytes<LittleEndian>>,
+    data: Vec<U32Bytes<LittleEndian>>,
+}
+
+impl FrameStateSection {
+    /// Appends the frame state of the function located at `range` in the text
+    /// section.
+    pub fn push(&mut self, range: Range<u64>, frame_state: &FunctionFrameState) {
+        // NB: for now this only supports <=4GB text sections in object files.
+        let start = u32::try_from(range.start).unwrap();
+        let end = u32::try_from(range.end).unwrap();
+
+        // Sanity-check to ensure that functions are pushed in-order, otherwise
+        // the `starts` array won't be sorted which is our goal.
+        assert!(
+            self.ends
+                .last()
+                .map_or(true, |prev| prev.get(LittleEndian) <= start)
+        );
+        debug_assert!(frame_state.points.is_sorted_by_key(|(offset, _)| *offset));
+        debug_assert!(
+            u32::try_from(1 + frame_state.locals.len()).unwrap() * FRAME_STATE_VALUE_SIZE
+                <= frame_state.fp_offset
+        );
+
+        self.starts.push(U32Bytes::new(LittleEndian, start));
+        self.ends.push(U32Bytes::new(LittleEndian, end));
+        self.pointers.push(self.next_pointer());
+
+        let header = [
+            frame_state.fp_offset,
+            u32::try_from(frame_state.locals.len()).unwrap(),
+        ];
+        self.push_words(header);
+        self.push_words(frame_state.locals.iter().map(|ty| ty.to_u32()));
+        self.push_words([u32::try_from(frame_state.points.len()).unwrap()]);
+        self.push_words(frame_state.points.iter().map(|(offset, _)| *offset));
+
+        // Reserve space for the pointers to each point's operand types, then
+        // fill them in as the operand types are appended after them.
+        let pointers = self.data.len();
+        self.push_words(frame_state.points.iter().map(|_| 0));
+        for (i, (_, operands)) in frame_state.points.iter().enumerate() {
+            self.data[pointers + i] = self.next_pointer();
+            self.push_words([u32::try_from(operands.len()).unwrap()]);
+            self.push_words(operands.iter().map(|ty| ty.to_u32()));
+        }
+    }
+
+    fn next_pointer(&self) -> U32Bytes<LittleEndian> {
+        U32Bytes::new(LittleEndian, u32::try_from(self.data.len()).unwrap())
+    }
+
+    fn push_words(&mut self, words: impl IntoIterator<Item = u32>) {
+        self.data
+            .extend(words.into_iter().map(|w| U32Bytes::new(LittleEndian, w)));
+    }
+
+    /// Finishes encoding this section into the `Object` provided.
+    pub fn append_to(self, obj: &mut Object) {
+        // Don't append anything for this section if no functions preserve
+        // their frame state.
+        if self.starts.is_empty() {
+            return;
+        }
+        let section = obj.add_section(
+            obj.segment_name(StandardSegment::Data).to_vec(),
+            ELF_WASMTIME_FRAME_STATE.as_bytes().to_vec(),
+            SectionKind::ReadOnlyData,
+        );
+
+        // NB: this matches the encoding expected by `lookup` in the
+        // `crate::frame_state` module.
+        let amt = u32::try_from(self.starts.len()).unwrap();
+        obj.append_section_data(section, &amt.to_le_bytes(), 1);
+        obj.append_section_data(section, object::bytes_of_slice(&self.starts), 1);
+        obj.append_section_data(section, object::bytes_of_slice(&self.ends), 1);
+        obj.append_section_data(section, object::bytes_of_slice(&self.pointers), 1);
+        obj.append_section_data(section, object::bytes_of_slice(&self.data), 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FrameState;
+    use object::{Object, ObjectSection};
+
+    fn types(state: &FrameState<'_>) -> (Vec<(FrameValueType, u32)>, Vec<(FrameValueType, u32)>) {
+        (state.locals().collect(), state.operands().collect())
+    }
+
+    #[test]
+    fn roundtrip() {
+        use FrameValueType::*;
+
+        let mut section = FrameStateSection::default();
+        section.push(
+            0x10..0x40,
+            &FunctionFrameState {
+                fp_offset: 64,
+                locals: vec![I32, Unknown],
+                points: vec![(5, vec![]), (9, vec![F64, I64])],
+            },
+        );
+        section.push(
+            0x40..0x80,
+            &FunctionFrameState {
+                fp_offset: 16,
+                locals: vec![],
+                points: vec![(100, vec![F32])],
+            },
+        );
+
+        let mut obj = object::write::Object::new(
+            object::BinaryFormat::Elf,
+            object::Architecture::X86_64,
+            object::Endianness::Little,
+        );
+        section.append_to(&mut obj);
+        let elf = obj.write().unwrap();
+        let image = object::File::parse(&elf[..]).unwrap();
+        let data = image
+            .sections()
+            .find(|s| s.name().ok() == Some(ELF_WASMTIME_FRAME_STATE))
+            .unwrap()
+            .data()
+            .unwrap();
+
+        let state = FrameState::lookup(data, 0x20, 9).unwrap();
+        assert_eq!(state.vmctx_offset(), 64);
+        assert_eq!(
+            types(&state),
+            (vec![(I32, 56), (Unknown, 48)], vec![(F64, 40), (I64, 32)])
+        );
+        let state = FrameState::lookup(data, 0x10, 5).unwrap();
+        assert_eq!(types(&state), (vec![(I32, 56), (Unknown, 48)], vec![]));
+        let state = FrameState::lookup(data, 0x7f, 100).unwrap();
+        assert_eq!(state.vmctx_offset(), 16);
+        assert_eq!(types(&state), (vec![], vec![(F32, 8)]));
+
+        // Offsets without preserved state and pcs outside of any function
+        // have no frame state.
+        assert!(FrameState::lookup(data, 0x20, 6).is_none());
+        assert!(FrameState::lookup(data, 0x0, 5).is_none());
+        assert!(FrameState::lookup(data, 0x80, 100).is_none());
+    }
+}
@@ -4,7 +4,7 @@
 use crate::prelude::*;
 use crate::{
     DefinedFuncIndex, FlagValue, FuncKey, FunctionLoc, ObjectKind, PrimaryMap, StaticModuleIndex,
-    TripleExt, Tunables, WasmError, WasmFuncType, obj,
+    TripleExt, Tunables, WasmError, WasmFuncType, WmemcheckAllocator, obj,
 };
 use anyhow::Result;
 use object::write::{Object, SymbolId};
@@ -16,6 +16,7 @@ use std::path;
 use std::sync::Arc;
 
 mod address_map;
+mod frame_state;
 mod module_artifacts;
 mod module_environ;
 mod module_types;
@@ -23,6 +24,7 @@ mod stack_maps;
 mod trap_encoding;
 
 pub use self::address_map::*;
+pub use self::frame_state::*;
 pub use self::module_artifacts::*;
 pub use self::module_environ::*;
 pub use self::module_types::*;
@@ -133,6 +135,9 @@ pub trait CompilerBuilder: Send + Sync + fmt::Debug {
 
     /// Enables or disables wmemcheck during runtime according to the wmemcheck CLI flag.
     fn wmemcheck(&mut self, _enable: bool) {}
+
+    /// Configures which guest functions wmemcheck treats as the allocator.
+    fn wmemcheck_allocator(&mut self, _allocator: WmemcheckAllocator) {}
 }
 
 /// Description of compiler settings returned by [`CompilerBuilder::settings`].
@@ -237,6 +242,17 @@ pub trait Compiler: Send + Sync {
     /// `InliningCompiler::finish_compiling`.
     fn inlining_compiler(&self) -> Option<&dyn InliningCompiler>;
 
+    /// Returns the optimizing compiler used to recompile hot code when
+    /// `Tunables::tier_up` is enabled.
+    ///
+    /// Code produced by the returned compiler must be callable with the same
+    /// calling convention, and use the same `VMContext` layout, as code
+    /// produced by `self`. Returns `None` if this compiler doesn't support
+    /// tiered compilation.
+    fn tier_up_compiler(&self) -> Option<&dyn Compiler> {
+        None
+    }
+
     /// Compiles the function `index` within `translation`.
     ///
     /// The body of the function is available in `data` and configuration
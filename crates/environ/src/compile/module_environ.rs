@@ -380,7 +380,17 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
                     let sigindex = entry?;
                     let ty = TypeIndex::from_u32(sigindex);
                     let interned_index = self.result.module.types[ty];
-                    self.result.module.push_function(interned_index);
+                    let func_index = self.result.module.push_function(interned_index);
+
+                    // Tiered compilation calls all defined functions through
+                    // their `VMFuncRef` so they can be swapped for optimized
+                    // code at runtime, so they all need one.
+                    if self.tunables.tier_up {
+                        self.flag_func_escaped(func_index);
+                    }
+                }
+                if self.tunables.tier_up {
+                    self.result.module.num_tier_up_counters = cnt;
                 }
             }
 
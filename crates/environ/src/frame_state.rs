@@ -0,0 +1,179 @@
+use object::{Bytes, LittleEndian, U32Bytes};
+
+/// The number of bytes each value occupies in a function's frame state.
+///
+/// Every local and operand stack entry is given a slot of this size regardless
+/// of its type, so the `i`th value lives at `FRAME_STATE_VALUE_SIZE * i`
+/// bytes from the start of the frame state. The first slot holds the frame's
+/// `VMContext` pointer, followed by the locals and then the operand stack.
+pub const FRAME_STATE_VALUE_SIZE: u32 = 8;
+
+/// The type of a value preserved in a function's frame state.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FrameValueType {
+    /// A 32-bit integer.
+    I32,
+    /// A 64-bit integer.
+    I64,
+    /// A 32-bit float.
+    F32,
+    /// A 64-bit float.
+    F64,
+    /// A value which isn't preserved, such as a `v128` or a reference.
+    Unknown,
+}
+
+impl FrameValueType {
+    /// Returns the encoding of this type in the frame state section.
+    pub fn to_u32(self) -> u32 {
+        match self {
+            FrameValueType::Unknown => 0,
+            FrameValueType::I32 => 1,
+            FrameValueType::I64 => 2,
+            FrameValueType::F32 => 3,
+            FrameValueType::F64 => 4,
+        }
+    }
+
+    /// Decodes a type encoded with [`FrameValueType::to_u32`].
+    pub fn from_u32(ty: u32) -> FrameValueType {
+        match ty {
+            1 => FrameValueType::I32,
+            2 => FrameValueType::I64,
+            3 => FrameValueType::F32,
+            4 => FrameValueType::F64,
+            _ => FrameValueType::Unknown,
+        }
+    }
+}
+
+struct FrameStateSection<'a> {
+    starts: &'a [U32Bytes<LittleEndian>],
+    ends: &'a [U32Bytes<LittleEndian>],
+    pointers: &'a [U32Bytes<LittleEndian>],
+    data: &'a [U32Bytes<LittleEndian>],
+}
+
+impl<'a> FrameStateSection<'a> {
+    fn parse(section: &'a [u8]) -> Option<FrameStateSection<'a>> {
+        let mut section = Bytes(section);
+        // NB: this matches the encoding written by `append_to` in the
+        // `compile::frame_state` module.
+        let count = section.read::<U32Bytes<LittleEndian>>().ok()?;
+        let count = usize::try_from(count.get(LittleEndian)).ok()?;
+        let (starts, section) =
+            object::slice_from_bytes::<U32Bytes<LittleEndian>>(section.0, count).ok()?;
+        let (ends, section) =
+            object::slice_from_bytes::<U32Bytes<LittleEndian>>(section, count).ok()?;
+        let (pointers, section) =
+            object::slice_from_bytes::<U32Bytes<LittleEndian>>(section, count).ok()?;
+        let data = object::slice_from_all_bytes::<U32Bytes<LittleEndian>>(section).ok()?;
+        Some(FrameStateSection {
+            starts,
+            ends,
+            pointers,
+            data,
+        })
+    }
+
+    /// Returns the data for the function containing `text_offset`.
+    fn function(&self, text_offset: u32) -> Option<&'a [U32Bytes<LittleEndian>]> {
+        let index = match self
+            .starts
+            .binary_search_by_key(&text_offset, |v| v.get(LittleEndian))
+        {
+            Ok(i) => i,
+            Err(0) => return None,
+            Err(i) => i - 1,
+        };
+        if text_offset >= self.ends[index].get(LittleEndian) {
+            return None;
+        }
+        let pointer = usize::try_from(self.pointers[index].get(LittleEndian)).ok()?;
+        self.data.get(pointer..)
+    }
+
+    fn slice(&self, pointer: u32) -> Option<&'a [U32Bytes<LittleEndian>]> {
+        let data = self.data.get(usize::try_from(pointer).ok()?..)?;
+        let (len, data) = data.split_first()?;
+        data.get(..usize::try_from(len.get(LittleEndian)).ok()?)
+    }
+}
+
+/// Describes where the values of Wasm locals and the operand stack are
+/// preserved in a frame at a particular instruction.
+pub struct FrameState<'a> {
+    fp_offset: u32,
+    locals: &'a [U32Bytes<LittleEndian>],
+    operands: &'a [U32Bytes<LittleEndian>],
+}
+
+impl<'a> FrameState<'a> {
+    /// Looks up the frame state of the function containing `text_offset`, for
+    /// the instruction located at `wasm_offset` in the original Wasm module.
+    ///
+    /// The `section` should be produced by `FrameStateSection` in the
+    /// `compile::frame_state` module. The `text_offset` should be relative to
+    /// the start of the `.text` section in the final executable.
+    ///
+    /// Returns `None` if the function has no frame state, or if the state
+    /// isn't preserved at `wasm_offset`.
+    pub fn lookup(section: &'a [u8], text_offset: u32, wasm_offset: u32) -> Option<FrameState<'a>> {
+        let section = FrameStateSection::parse(section)?;
+        let data = section.function(text_offset)?;
+
+        // See `FrameStateSection::push` for the layout of this data.
+        let (fp_offset, data) = data.split_first()?;
+        let (num_locals, data) = data.split_first()?;
+        let num_locals = usize::try_from(num_locals.get(LittleEndian)).ok()?;
+        let (locals, data) = (data.get(..num_locals)?, data.get(num_locals..)?);
+        let (num_points, data) = data.split_first()?;
+        let num_points = usize::try_from(num_points.get(LittleEndian)).ok()?;
+        let offsets = data.get(..num_points)?;
+        let pointers = data.get(num_points..2 * num_points)?;
+
+        let index = offsets
+            .binary_search_by_key(&wasm_offset, |v| v.get(LittleEndian))
+            .ok()?;
+        let operands = section.slice(pointers[index].get(LittleEndian))?;
+        Some(FrameState {
+            fp_offset: fp_offset.get(LittleEndian),
+            locals,
+            operands,
+        })
+    }
+
+    /// Returns the offset below the frame pointer at which the frame's
+    /// `VMContext` pointer is stored.
+    pub fn vmctx_offset(&self) -> u32 {
+        self.fp_offset
+    }
+
+    /// Returns the type of each local along with the offset of its value below
+    /// the frame pointer.
+    pub fn locals(&self) -> impl ExactSizeIterator<Item = (FrameValueType, u32)> + 'a {
+        self.values(self.locals, 1)
+    }
+
+    /// Returns the type of each operand stack entry, from the bottom of the
+    /// stack to the top, along with the offset of its value below the frame
+    /// pointer.
+    pub fn operands(&self) -> impl ExactSizeIterator<Item = (FrameValueType, u32)> + 'a {
+        self.values(self.operands, 1 + self.locals.len())
+    }
+
+    fn values(
+        &self,
+        types: &'a [U32Bytes<LittleEndian>],
+        start: usize,
+    ) -> impl ExactSizeIterator<Item = (FrameValueType, u32)> + 'a {
+        let fp_offset = self.fp_offset;
+        types.iter().enumerate().map(move |(i, ty)| {
+            let index = u32::try_from(start + i).unwrap();
+            (
+                FrameValueType::from_u32(ty.get(LittleEndian)),
+                fp_offset - index * FRAME_STATE_VALUE_SIZE,
+            )
+        })
+    }
+}
@@ -24,6 +24,7 @@ mod builtin;
 mod demangling;
 mod error;
 mod ext;
+mod frame_state;
 mod gc;
 mod hostcall;
 mod key;
@@ -39,12 +40,14 @@ mod trap_encoding;
 mod tunables;
 mod types;
 mod vmoffsets;
+mod wmemcheck;
 
 pub use self::ext::*;
 pub use crate::address_map::*;
 pub use crate::builtin::*;
 pub use crate::demangling::*;
 pub use crate::error::*;
+pub use crate::frame_state::*;
 pub use crate::gc::*;
 pub use crate::hostcall::*;
 pub use crate::key::*;
@@ -59,6 +62,7 @@ pub use crate::trap_encoding::*;
 pub use crate::tunables::*;
 pub use crate::types::*;
 pub use crate::vmoffsets::*;
+pub use crate::wmemcheck::*;
 pub use object;
 
 pub use wasmparser;
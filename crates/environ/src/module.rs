@@ -349,6 +349,13 @@ pub struct Module {
     /// an `func_ref` index (and is the maximum func_ref index).
     pub num_escaped_funcs: usize,
 
+    /// Number of counters used to decide when a defined function should be
+    /// recompiled with an optimizing compiler.
+    ///
+    /// This is either zero, if tiered compilation is disabled, or the number
+    /// of defined functions, indexed by `DefinedFuncIndex`.
+    pub num_tier_up_counters: usize,
+
     /// Types of functions, imported and local.
     pub functions: PrimaryMap<FuncIndex, FunctionType>,
 
@@ -406,6 +413,7 @@ impl Module {
             num_imported_tags: Default::default(),
             needs_gc_heap: Default::default(),
             num_escaped_funcs: Default::default(),
+            num_tier_up_counters: Default::default(),
             functions: Default::default(),
             tables: Default::default(),
             memories: Default::default(),
@@ -675,6 +683,7 @@ impl TypeTrace for Module {
             num_imported_globals: _,
             num_imported_tags: _,
             num_escaped_funcs: _,
+            num_tier_up_counters: _,
             needs_gc_heap: _,
             functions,
             tables,
@@ -726,6 +735,7 @@ impl TypeTrace for Module {
             num_imported_globals: _,
             num_imported_tags: _,
             num_escaped_funcs: _,
+            num_tier_up_counters: _,
             needs_gc_heap: _,
             functions,
             tables,
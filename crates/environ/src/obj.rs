@@ -71,6 +71,15 @@ pub const ELF_WASMTIME_ADDRMAP: &str = ".wasmtime.addrmap";
 /// >=4gb text sections.
 pub const ELF_WASMTIME_STACK_MAP: &str = ".wasmtime.stackmap";
 
+/// A custom Wasmtime-specific section of compilation which describes where the
+/// values of Wasm locals and the operand stack are preserved in each frame.
+///
+/// This section is only present when compiling with
+/// `Tunables::coredump_frame_state` and has a custom binary encoding described
+/// in `frame_state.rs`. It's used to recover the state of Wasm frames when
+/// capturing core dumps.
+pub const ELF_WASMTIME_FRAME_STATE: &str = ".wasmtime.framestate";
+
 /// A custom binary-encoded section of wasmtime compilation artifacts which
 /// encodes the ability to map an offset in the text section to the trap code
 /// that it corresponds to.
@@ -104,6 +104,11 @@ define_tunables! {
         /// offsets in the original file is generated.
         pub generate_address_map: bool,
 
+        /// Whether or not compiled code preserves the values of Wasm locals
+        /// and the operand stack in each frame so they can be recovered in
+        /// core dumps.
+        pub coredump_frame_state: bool,
+
         /// Flag for the component module whether adapter modules have debug
         /// assertions baked into them.
         pub debug_adapter_modules: bool,
@@ -115,6 +120,11 @@ define_tunables! {
         /// Whether or not Wasm functions target the winch abi.
         pub winch_callable: bool,
 
+        /// Whether or not baseline-compiled functions count their calls and
+        /// loop iterations so that hot functions can be recompiled with an
+        /// optimizing compiler at runtime.
+        pub tier_up: bool,
+
         /// Whether or not the host will be using native signals (e.g. SIGILL,
         /// SIGSEGV, etc) to implement traps.
         pub signals_based_traps: bool,
@@ -201,9 +211,11 @@ impl Tunables {
             guard_before_linear_memory: true,
             table_lazy_init: true,
             generate_address_map: true,
+            coredump_frame_state: false,
             debug_adapter_modules: false,
             relaxed_simd_deterministic: false,
             winch_callable: false,
+            tier_up: false,
             signals_based_traps: false,
             memory_init_cow: true,
             inlining: false,
@@ -276,6 +288,8 @@ pub enum Collector {
     DeferredReferenceCounting,
     /// The null collector.
     Null,
+    /// The copying collector.
+    Copying,
 }
 
 impl fmt::Display for Collector {
@@ -283,6 +297,7 @@ impl fmt::Display for Collector {
         match self {
             Collector::DeferredReferenceCounting => write!(f, "deferred reference-counting"),
             Collector::Null => write!(f, "null"),
+            Collector::Copying => write!(f, "copying"),
         }
     }
 }
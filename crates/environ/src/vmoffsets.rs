@@ -31,11 +31,13 @@
 //      globals: [VMGlobalDefinition; module.num_defined_globals],
 //      tags: [VMTagDefinition; module.num_defined_tags],
 //      func_refs: [VMFuncRef; module.num_escaped_funcs],
+//      tier_up_counters: [u32; module.num_tier_up_counters],
 // }
 
 use crate::{
-    DefinedGlobalIndex, DefinedMemoryIndex, DefinedTableIndex, DefinedTagIndex, FuncIndex,
-    FuncRefIndex, GlobalIndex, MemoryIndex, Module, OwnedMemoryIndex, TableIndex, TagIndex,
+    DefinedFuncIndex, DefinedGlobalIndex, DefinedMemoryIndex, DefinedTableIndex, DefinedTagIndex,
+    FuncIndex, FuncRefIndex, GlobalIndex, MemoryIndex, Module, OwnedMemoryIndex, TableIndex,
+    TagIndex,
 };
 use cranelift_entity::packed_option::ReservedValue;
 
@@ -56,7 +58,7 @@ fn align(offset: u32, width: u32) -> u32 {
 
 /// This class computes offsets to fields within `VMContext` and other
 /// related structs that JIT code accesses directly.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct VMOffsets<P> {
     /// The size in bytes of a pointer on the target.
     pub ptr: P,
@@ -83,6 +85,8 @@ pub struct VMOffsets<P> {
     /// The number of escaped functions in the module, the size of the func_refs
     /// array.
     pub num_escaped_funcs: u32,
+    /// The number of tier-up counters in the module.
+    pub num_tier_up_counters: u32,
 
     // precalculated offsets of various member fields
     imported_functions: u32,
@@ -96,6 +100,7 @@ pub struct VMOffsets<P> {
     defined_globals: u32,
     defined_tags: u32,
     defined_func_refs: u32,
+    tier_up_counters: u32,
     size: u32,
 }
 
@@ -466,7 +471,7 @@ pub trait PtrSize {
 }
 
 /// Type representing the size of a pointer for the current compilation host
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct HostPtr;
 
 impl PtrSize for HostPtr {
@@ -511,6 +516,8 @@ pub struct VMOffsetsFields<P> {
     /// The number of escaped functions in the module, the size of the function
     /// references array.
     pub num_escaped_funcs: u32,
+    /// The number of tier-up counters in the module.
+    pub num_tier_up_counters: u32,
 }
 
 impl<P: PtrSize> VMOffsets<P> {
@@ -537,6 +544,7 @@ impl<P: PtrSize> VMOffsets<P> {
             num_defined_globals: cast_to_u32(module.globals.len() - module.num_imported_globals),
             num_defined_tags: cast_to_u32(module.tags.len() - module.num_imported_tags),
             num_escaped_funcs: cast_to_u32(module.num_escaped_funcs),
+            num_tier_up_counters: cast_to_u32(module.num_tier_up_counters),
         })
     }
 
@@ -568,6 +576,7 @@ impl<P: PtrSize> VMOffsets<P> {
                     num_defined_tags: _,
                     num_owned_memories: _,
                     num_escaped_funcs: _,
+                    num_tier_up_counters: _,
 
                     // used as the initial size below
                     size,
@@ -596,6 +605,7 @@ impl<P: PtrSize> VMOffsets<P> {
         }
 
         calculate_sizes! {
+            tier_up_counters: "tier-up counters",
             defined_func_refs: "module functions",
             defined_tags: "defined tags",
             defined_globals: "defined globals",
@@ -626,6 +636,7 @@ impl<P: PtrSize> From<VMOffsetsFields<P>> for VMOffsets<P> {
             num_defined_globals: fields.num_defined_globals,
             num_defined_tags: fields.num_defined_tags,
             num_escaped_funcs: fields.num_escaped_funcs,
+            num_tier_up_counters: fields.num_tier_up_counters,
             imported_functions: 0,
             imported_tables: 0,
             imported_memories: 0,
@@ -637,6 +648,7 @@ impl<P: PtrSize> From<VMOffsetsFields<P>> for VMOffsets<P> {
             defined_globals: 0,
             defined_tags: 0,
             defined_func_refs: 0,
+            tier_up_counters: 0,
             size: 0,
         };
 
@@ -695,6 +707,7 @@ impl<P: PtrSize> From<VMOffsetsFields<P>> for VMOffsets<P> {
                 ret.num_escaped_funcs,
                 ret.ptr.size_of_vm_func_ref(),
             ),
+            size(tier_up_counters) = cmul(ret.num_tier_up_counters, 4),
         }
 
         ret.size = next_field_offset;
@@ -937,6 +950,12 @@ impl<P: PtrSize> VMOffsets<P> {
         self.defined_func_refs
     }
 
+    /// The offset of the `tier_up_counters` array.
+    #[inline]
+    pub fn vmctx_tier_up_counters_begin(&self) -> u32 {
+        self.tier_up_counters
+    }
+
     /// Return the size of the `VMContext` allocation.
     #[inline]
     pub fn size_of_vmctx(&self) -> u32 {
@@ -1029,6 +1048,13 @@ impl<P: PtrSize> VMOffsets<P> {
         self.vmctx_func_refs_begin() + index.as_u32() * u32::from(self.ptr.size_of_vm_func_ref())
     }
 
+    /// Return the offset to the tier-up counter of the given defined function.
+    #[inline]
+    pub fn vmctx_tier_up_counter(&self, index: DefinedFuncIndex) -> u32 {
+        assert!(index.as_u32() < self.num_tier_up_counters);
+        self.vmctx_tier_up_counters_begin() + index.as_u32() * 4
+    }
+
     /// Return the offset to the `wasm_call` field in `*const VMFunctionBody` index `index`.
     #[inline]
     pub fn vmctx_vmfunction_import_wasm_call(&self, index: FuncIndex) -> u32 {
@@ -0,0 +1,70 @@
+use crate::prelude::*;
+
+/// Describes the functions of a guest's allocator for the wmemcheck memory
+/// checker.
+///
+/// Functions are matched by their name in the `name` section or by the name
+/// they're exported under. Each list may contain any number of names so that
+/// guests using a custom allocator, such as `dlmalloc`, can be checked too.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct WmemcheckAllocator {
+    /// Functions with the signature `malloc(size) -> ptr`.
+    pub malloc: Vec<String>,
+    /// Functions with the signature `free(ptr)`.
+    pub free: Vec<String>,
+    /// Functions with the signature `realloc(ptr, size) -> ptr`.
+    pub realloc: Vec<String>,
+    /// Functions with the signature `calloc(count, size) -> ptr`.
+    pub calloc: Vec<String>,
+    /// Functions with the signature `aligned_alloc(align, size) -> ptr`.
+    pub aligned_alloc: Vec<String>,
+    /// The index of the linear memory that the allocator manages.
+    ///
+    /// Only loads and stores to this memory are checked.
+    pub memory: u32,
+}
+
+/// The kind of an allocator function recognized by wmemcheck.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WmemcheckAllocatorFunc {
+    /// See [`WmemcheckAllocator::malloc`].
+    Malloc,
+    /// See [`WmemcheckAllocator::free`].
+    Free,
+    /// See [`WmemcheckAllocator::realloc`].
+    Realloc,
+    /// See [`WmemcheckAllocator::calloc`].
+    Calloc,
+    /// See [`WmemcheckAllocator::aligned_alloc`].
+    AlignedAlloc,
+}
+
+impl Default for WmemcheckAllocator {
+    fn default() -> WmemcheckAllocator {
+        WmemcheckAllocator {
+            malloc: vec!["malloc".to_string()],
+            free: vec!["free".to_string()],
+            realloc: vec!["realloc".to_string()],
+            calloc: vec!["calloc".to_string()],
+            aligned_alloc: vec!["aligned_alloc".to_string()],
+            memory: 0,
+        }
+    }
+}
+
+impl WmemcheckAllocator {
+    /// Returns which kind of allocator function, if any, `name` refers to.
+    pub fn classify(&self, name: &str) -> Option<WmemcheckAllocatorFunc> {
+        let lists = [
+            (&self.malloc, WmemcheckAllocatorFunc::Malloc),
+            (&self.free, WmemcheckAllocatorFunc::Free),
+            (&self.realloc, WmemcheckAllocatorFunc::Realloc),
+            (&self.calloc, WmemcheckAllocatorFunc::Calloc),
+            (&self.aligned_alloc, WmemcheckAllocatorFunc::AlignedAlloc),
+        ];
+        lists
+            .into_iter()
+            .find(|(names, _)| names.iter().any(|n| n == name))
+            .map(|(_, kind)| kind)
+    }
+}
@@ -0,0 +1,312 @@
+//! Exploring the compilation of components.
+//!
+//! A component is compiled to a single object containing the functions of all
+//! of its nested core modules, the fused adapter modules that Wasmtime
+//! generates to pass values between components, and trampolines such as the
+//! ones lowering host imports. Each of these is disassembled from the symbols
+//! of the precompiled component and linked back to the WIT function it
+//! implements.
+
+use crate::{
+    AddressMap, AnnotatedAsm, AnnotatedFunction, AnnotatedWat, FunctionKind, WasmOffset,
+    annotate_wat, capstone, disassemble,
+};
+use anyhow::{Context, Result};
+use object::read::elf::ElfFile64;
+use object::{Endianness, Object, ObjectSection, ObjectSymbol, SymbolKind};
+use std::collections::HashMap;
+use wasmtime_environ::component::{
+    ComponentTranslation, ComponentTypesBuilder, CoreDef, Export, ExportItem, GlobalInitializer,
+    InstantiateModule, RuntimeImportIndex, Translator,
+};
+use wasmtime_environ::wasmparser::{Validator, WasmFeatures};
+use wasmtime_environ::{
+    EntityIndex, ModuleTranslation, PrimaryMap, ScopeVec, StaticModuleIndex, Tunables, obj,
+};
+
+pub(crate) fn annotate(
+    config: &wasmtime::Config,
+    target: &target_lexicon::Triple,
+    wasm: &[u8],
+) -> Result<(AnnotatedWat, AnnotatedAsm)> {
+    // Translate the component on the side to learn about its structure, which
+    // isn't recorded in the compiled artifact, as well as to get the fused
+    // adapter modules generated during translation.
+    let tunables = Tunables::default_for_target(target)?;
+    let mut validator = Validator::new_with_features(WasmFeatures::all());
+    let mut types = ComponentTypesBuilder::new(&validator);
+    let scope = ScopeVec::new();
+    let (translation, modules) =
+        Translator::new(&tunables, &mut validator, &mut types, &scope).translate(wasm)?;
+
+    // Adapter modules are the only ones whose bytes aren't a part of the
+    // component itself. Their WAT is appended to the component's.
+    let mut wat = annotate_wat(wasm)?;
+    let mut offset_bases = HashMap::new();
+    let mut next_base = u32::try_from(wasm.len()).unwrap();
+    for (index, module) in modules.iter() {
+        if wasm.as_ptr_range().contains(&module.wasm.as_ptr()) {
+            continue;
+        }
+        offset_bases.insert(index, next_base);
+        let mut adapter_wat = annotate_wat(module.wasm)?;
+        for chunk in adapter_wat.chunks.iter_mut() {
+            chunk.wasm_offset = chunk.wasm_offset.map(|o| WasmOffset(o.0 + next_base));
+        }
+        if let Some(first) = adapter_wat.chunks.first_mut() {
+            first.wat = format!(
+                ";; fused adapters generated by Wasmtime in wasm[{}]\n{}",
+                index.as_u32(),
+                first.wat
+            );
+        }
+        wat.chunks.extend(adapter_wat.chunks);
+        next_base += u32::try_from(module.wasm.len()).unwrap();
+    }
+    let names = WitNames::new(&translation, &modules, |module| {
+        offset_bases.contains_key(&module)
+    });
+
+    let engine = wasmtime::Engine::new(config)?;
+    let compiled = engine.precompile_component(wasm)?;
+    let elf = ElfFile64::<Endianness>::parse(&compiled[..])?;
+    let text = elf
+        .section_by_name(".text")
+        .context("missing .text section")?
+        .data()?;
+    let mut address_map = AddressMap::new(
+        elf.section_by_name(obj::ELF_WASMTIME_ADDRMAP)
+            .and_then(|section| section.data().ok())
+            .and_then(wasmtime_environ::iterate_address_map)
+            .ok_or_else(|| anyhow::anyhow!("address maps must be enabled in the config"))?
+            .map(|(offset, pos)| (offset as usize, pos.file_offset())),
+    );
+
+    let mut symbols = elf
+        .symbols()
+        .filter(|sym| sym.kind() == SymbolKind::Text)
+        .map(|sym| Ok((sym.name()?, sym.address() as usize, sym.size() as usize)))
+        .collect::<Result<Vec<_>>>()?;
+    symbols.sort_by_key(|(_, offset, _)| *offset);
+
+    let cs = capstone(target)?;
+    let functions = symbols
+        .into_iter()
+        .map(|(symbol, offset, len)| {
+            let (kind, func, wit_name) = match parse_symbol(symbol) {
+                Symbol::Function(module, index) => {
+                    let kind = if offset_bases.contains_key(&module) {
+                        FunctionKind::Adapter
+                    } else {
+                        FunctionKind::Function
+                    };
+                    (
+                        kind,
+                        Some((module, index)),
+                        names.funcs.get(&(module, index)),
+                    )
+                }
+                Symbol::ArrayToWasmTrampoline(module, index) => (
+                    FunctionKind::Trampoline,
+                    None,
+                    names.funcs.get(&(module, index)),
+                ),
+                Symbol::LowerImport(index) => {
+                    (FunctionKind::Trampoline, None, names.lowerings.get(&index))
+                }
+                Symbol::Other => (FunctionKind::Trampoline, None, None),
+            };
+            let base = func
+                .and_then(|(module, _)| offset_bases.get(&module).copied())
+                .unwrap_or(0);
+
+            let body = &text[offset..][..len];
+            let instructions = disassemble(&cs, body, offset, |address| {
+                address_map
+                    .wasm_offset(offset, address)
+                    .map(|o| WasmOffset(o + base))
+            })?;
+
+            Ok(AnnotatedFunction {
+                func_index: func.map(|(_, index)| index),
+                name: Some(symbol.to_string()),
+                demangled_name: None,
+                kind,
+                wit_name: wit_name.cloned(),
+                instructions,
+                clif_name: match func {
+                    Some((module, index)) => {
+                        format!("wasm[{}]--function[{index}]", module.as_u32())
+                    }
+                    None => symbol.replace(':', "-"),
+                },
+                clif_offset_base: base,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok((wat, AnnotatedAsm { functions }))
+}
+
+/// The functions of a compiled component that the explorer knows about, as
+/// identified by their symbol.
+enum Symbol {
+    /// `wasm[M]::function[F]`, optionally followed by the function's name.
+    Function(StaticModuleIndex, u32),
+    /// `wasm[M]::array_to_wasm_trampoline[F]`
+    ArrayToWasmTrampoline(StaticModuleIndex, u32),
+    /// `component-lower-import[L]_wasm_call` and `..._array_call`
+    LowerImport(u32),
+    Other,
+}
+
+fn parse_symbol(symbol: &str) -> Symbol {
+    fn index<'a>(s: &'a str, prefix: &str) -> Option<(u32, &'a str)> {
+        let (index, rest) = s.strip_prefix(prefix)?.split_once(']')?;
+        Some((index.parse().ok()?, rest))
+    }
+
+    if let Some((module, rest)) = index(symbol, "wasm[") {
+        let module = StaticModuleIndex::from_u32(module);
+        if let Some((func, _)) = index(rest, "::function[") {
+            return Symbol::Function(module, func);
+        }
+        if let Some((func, _)) = index(rest, "::array_to_wasm_trampoline[") {
+            return Symbol::ArrayToWasmTrampoline(module, func);
+        }
+    } else if let Some((lowering, _)) = index(symbol, "component-lower-import[") {
+        return Symbol::LowerImport(lowering);
+    }
+    Symbol::Other
+}
+
+/// The WIT functions implemented by the functions of a component.
+///
+/// A function is named after the WIT function that it's lifted as or lowered
+/// from. Fused adapters are named after the core wasm import that they're
+/// instantiated as, which are named after the WIT interface and function by
+/// convention.
+#[derive(Default)]
+struct WitNames {
+    /// WIT names of core wasm functions, including fused adapters, keyed by
+    /// their module and function index.
+    funcs: HashMap<(StaticModuleIndex, u32), String>,
+    /// WIT names of lowered host imports, keyed by their lowering index.
+    lowerings: HashMap<u32, String>,
+}
+
+impl WitNames {
+    fn new(
+        translation: &ComponentTranslation,
+        modules: &PrimaryMap<StaticModuleIndex, ModuleTranslation<'_>>,
+        is_adapter_module: impl Fn(StaticModuleIndex) -> bool,
+    ) -> WitNames {
+        let component = &translation.component;
+        let mut names = WitNames::default();
+        let mut instances = Vec::new();
+        let mut callees = Vec::new();
+
+        // Resolves a core wasm function to its module and index, if it's
+        // defined within this component.
+        let resolve = |instances: &[Option<StaticModuleIndex>], def: &CoreDef| {
+            let CoreDef::Export(export) = def else {
+                return None;
+            };
+            let module = instances[export.instance.as_u32() as usize]?;
+            let index = match &export.item {
+                ExportItem::Index(index) => *index,
+                ExportItem::Name(name) => *modules[module].module.exports.get(name)?,
+            };
+            match index {
+                EntityIndex::Function(index) => Some((module, index.as_u32())),
+                _ => None,
+            }
+        };
+
+        for init in component.initializers.iter() {
+            match init {
+                GlobalInitializer::InstantiateModule(InstantiateModule::Static(module, args)) => {
+                    let imports = modules[*module].module.imports();
+                    for ((import_module, field, _), arg) in imports.zip(args.iter()) {
+                        // Fused adapter modules import the function that they
+                        // call as `callee` under the adapter's name. Their
+                        // other imports are named after the canonical ABI
+                        // options and not WIT.
+                        if is_adapter_module(*module) {
+                            if import_module != "callee" {
+                                continue;
+                            }
+                            let adapter = match modules[*module].module.exports.get(field) {
+                                Some(EntityIndex::Function(i)) => (*module, i.as_u32()),
+                                _ => continue,
+                            };
+                            callees.push((adapter, resolve(&instances, arg)));
+                        } else if let Some(func) = resolve(&instances, arg) {
+                            names
+                                .funcs
+                                .entry(func)
+                                .or_insert_with(|| format!("{import_module}#{field}"));
+                        }
+                    }
+                    instances.push(Some(*module));
+                }
+                GlobalInitializer::InstantiateModule(InstantiateModule::Import(..)) => {
+                    instances.push(None);
+                }
+                GlobalInitializer::LowerImport { index, import } => {
+                    names
+                        .lowerings
+                        .insert(index.as_u32(), import_name(translation, *import));
+                }
+                _ => {}
+            }
+        }
+
+        // The function called by a fused adapter is the other half of the
+        // same WIT function.
+        for (adapter, callee) in callees {
+            if let (Some(name), Some(callee)) = (names.funcs.get(&adapter), callee) {
+                let name = name.clone();
+                names.funcs.entry(callee).or_insert(name);
+            }
+        }
+
+        let mut exports = component
+            .exports
+            .raw_iter()
+            .map(|(name, index)| (name.clone(), *index))
+            .collect::<Vec<_>>();
+        while let Some((name, index)) = exports.pop() {
+            match &component.export_items[index] {
+                Export::LiftedFunction { func, .. } => {
+                    if let Some(func) = resolve(&instances, func) {
+                        names.funcs.insert(func, name);
+                    }
+                }
+                Export::Instance { exports: items, .. } => {
+                    exports.extend(
+                        items
+                            .raw_iter()
+                            .map(|(item, index)| (format!("{name}#{item}"), *index)),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        names
+    }
+}
+
+/// Returns the name of the imported WIT function `import`, such as
+/// `wasi:cli/stdout#get-stdout`.
+fn import_name(translation: &ComponentTranslation, import: RuntimeImportIndex) -> String {
+    let component = &translation.component;
+    let (index, path) = &component.imports[import];
+    let mut name = component.import_types[*index].0.clone();
+    for item in path {
+        name.push('#');
+        name.push_str(item);
+    }
+    name
+}
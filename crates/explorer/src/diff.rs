@@ -0,0 +1,170 @@
+//! Comparing two compilations of the same Wasm.
+//!
+//! The instructions of each function are grouped into blocks compiled from
+//! the same Wasm offset, and the blocks of both compilations are aligned on
+//! their offsets with a longest common subsequence. Blocks only present in one
+//! compilation, such as prologues or code that was optimized away, get a row of
+//! their own.
+
+use crate::{AnnotatedAsm, AnnotatedFunction, AnnotatedInstruction, FunctionKind, WasmOffset};
+use serde_derive::Serialize;
+
+/// Above this many pairs of blocks the alignment of a function isn't computed
+/// and its blocks are shown next to each other in order.
+const MAX_ALIGNMENT_CELLS: usize = 1 << 22;
+
+#[derive(Serialize, Debug)]
+pub(crate) struct AnnotatedDiff {
+    labels: [String; 2],
+    functions: Vec<DiffFunction>,
+}
+
+#[derive(Serialize, Debug)]
+struct DiffFunction {
+    func_index: Option<u32>,
+    name: Option<String>,
+    demangled_name: Option<String>,
+    kind: FunctionKind,
+    wit_name: Option<String>,
+    rows: Vec<DiffRow>,
+}
+
+#[derive(Serialize, Debug)]
+struct DiffRow {
+    wasm_offset: Option<WasmOffset>,
+    instructions: [Vec<AnnotatedInstruction>; 2],
+}
+
+pub(crate) fn diff(labels: [&str; 2], [left, right]: [AnnotatedAsm; 2]) -> AnnotatedDiff {
+    let mut right = right.functions.into_iter().map(Some).collect::<Vec<_>>();
+    let mut functions = Vec::new();
+    for left in left.functions {
+        let matching = right.iter_mut().find(|r| {
+            r.as_ref()
+                .is_some_and(|r| r.func_index == left.func_index && r.name == left.name)
+        });
+        let right = matching.and_then(|r| r.take());
+        functions.push(diff_function(Some(left), right));
+    }
+    // Functions only present in the second compilation, for example
+    // trampolines that the first one didn't need.
+    for right in right.into_iter().flatten() {
+        functions.push(diff_function(None, Some(right)));
+    }
+
+    AnnotatedDiff {
+        labels: labels.map(|label| label.to_string()),
+        functions,
+    }
+}
+
+fn diff_function(
+    left: Option<AnnotatedFunction>,
+    right: Option<AnnotatedFunction>,
+) -> DiffFunction {
+    let func = left.as_ref().or(right.as_ref()).unwrap();
+    let mut diff = DiffFunction {
+        func_index: func.func_index,
+        name: func.name.clone(),
+        demangled_name: func.demangled_name.clone(),
+        kind: func.kind,
+        wit_name: func.wit_name.clone(),
+        rows: Vec::new(),
+    };
+    let left = blocks(left.map(|f| f.instructions).unwrap_or_default());
+    let right = blocks(right.map(|f| f.instructions).unwrap_or_default());
+    diff.rows = align(left, right);
+    diff
+}
+
+type Block = (Option<WasmOffset>, Vec<AnnotatedInstruction>);
+
+/// Splits `instructions` into runs of consecutive instructions compiled from
+/// the same Wasm offset.
+fn blocks(instructions: Vec<AnnotatedInstruction>) -> Vec<Block> {
+    let mut blocks: Vec<Block> = Vec::new();
+    for inst in instructions {
+        match blocks.last_mut() {
+            Some((offset, block)) if *offset == inst.wasm_offset => block.push(inst),
+            _ => blocks.push((inst.wasm_offset, vec![inst])),
+        }
+    }
+    blocks
+}
+
+fn align(left: Vec<Block>, right: Vec<Block>) -> Vec<DiffRow> {
+    let n = left.len();
+    let m = right.len();
+    if n.saturating_mul(m) > MAX_ALIGNMENT_CELLS {
+        let mut right = right.into_iter();
+        let mut rows = left
+            .into_iter()
+            .map(|(offset, insts)| {
+                let other = right.next().map(|(_, insts)| insts).unwrap_or_default();
+                DiffRow {
+                    wasm_offset: offset,
+                    instructions: [insts, other],
+                }
+            })
+            .collect::<Vec<_>>();
+        rows.extend(right.map(|(offset, insts)| DiffRow {
+            wasm_offset: offset,
+            instructions: [Vec::new(), insts],
+        }));
+        return rows;
+    }
+
+    // `lcs[i][j]` is the length of the longest common subsequence of the
+    // offsets of `left[i..]` and `right[j..]`. Blocks without an offset are
+    // never aligned with each other.
+    let offsets = [&left, &right].map(|blocks| blocks.iter().map(|b| b.0).collect::<Vec<_>>());
+    let same = |i: usize, j: usize| offsets[0][i].is_some() && offsets[0][i] == offsets[1][j];
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if same(i, j) {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut rows = Vec::new();
+    let mut left = left.into_iter();
+    let mut right = right.into_iter();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        // Blocks without an offset, such as prologues and epilogues, are
+        // shown next to each other when that doesn't break the alignment of
+        // any other blocks.
+        let unknown = |i: usize, j: usize| {
+            offsets[0][i].is_none() && offsets[1][j].is_none() && lcs[i + 1][j + 1] == lcs[i][j]
+        };
+        if i < n && j < m && (same(i, j) || unknown(i, j)) {
+            let (offset, l) = left.next().unwrap();
+            let (_, r) = right.next().unwrap();
+            rows.push(DiffRow {
+                wasm_offset: offset,
+                instructions: [l, r],
+            });
+            i += 1;
+            j += 1;
+        } else if j == m || (i < n && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            let (offset, l) = left.next().unwrap();
+            rows.push(DiffRow {
+                wasm_offset: offset,
+                instructions: [l, Vec::new()],
+            });
+            i += 1;
+        } else {
+            let (offset, r) = right.next().unwrap();
+            rows.push(DiffRow {
+                wasm_offset: offset,
+                instructions: [Vec::new(), r],
+            });
+            j += 1;
+        }
+    }
+    rows
+}
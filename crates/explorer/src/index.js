@@ -1,12 +1,18 @@
 class State {
-  constructor(wat, clif, asm) {
+  constructor(wat, clif, asm, diff) {
     this.wat = wat;
     this.clif = clif;
     this.asm = asm;
+    this.diff = diff;
   }
 }
 
-const state = (window.STATE = new State(window.WAT, window.CLIF, window.ASM));
+const state = (window.STATE = new State(
+  window.WAT,
+  window.CLIF,
+  window.ASM,
+  window.DIFF,
+));
 
 /*** LRU Cache *****************************************************************/
 
@@ -212,12 +218,42 @@ const renderInst = (mnemonic, operands) => {
   }
 };
 
+const renderInstruction = inst =>
+  `${renderAddress(inst.address)}    ${renderBytes(inst.bytes)}    ${renderInst(inst.mnemonic, inst.operands)}`;
+
 const createDivForCode = () => {
   let div = document.createElement("div");
   div.classList.add("highlight");
   return div;
 };
 
+const functionName = func =>
+  func.name === null ? `function[${func.func_index}]` : func.name;
+
+const functionKinds = {
+  function: "function",
+  adapter: "fused adapter",
+  trampoline: "trampoline",
+};
+
+// Describe a function for its header, e.g. `function <foo>`. Functions of
+// components additionally name the WIT function that they implement, e.g.
+// `fused adapter <wasm[2]::function[1]> of foo:bar/baz#add`.
+const describeFunction = func => {
+  let name =
+    func.demangled_name !== null ? func.demangled_name : functionName(func);
+  let description = `${functionKinds[func.kind]} <${name}>`;
+  if (func.wit_name !== null) {
+    description += ` of ${func.wit_name}`;
+  }
+  return description;
+};
+
+const titleForFunction = func =>
+  func.func_index === null
+    ? functionName(func)
+    : `Function ${func.func_index}: ${functionName(func)}`;
+
 // Render the CLIF (if any).
 const clifElem = document.getElementById("clif");
 if (clifElem) {
@@ -225,12 +261,8 @@ if (clifElem) {
     const funcElem = document.createElement("div");
 
     const funcHeader = document.createElement("h3");
-    let func_name =
-      func.name === null ? `function[${func.func_index}]` : func.name;
-    let demangled_name =
-      func.demangled_name !== null ? func.demangled_name : func_name;
-    funcHeader.textContent = `Intermediate Representation of function <${demangled_name}>:`;
-    funcHeader.title = `Function ${func.func_index}: ${func_name}`;
+    funcHeader.textContent = `Intermediate Representation of ${describeFunction(func)}:`;
+    funcHeader.title = titleForFunction(func);
     funcElem.appendChild(funcHeader);
 
     for (const inst of func.instructions) {
@@ -250,48 +282,88 @@ if (clifElem) {
 
 // Render the ASM.
 const asmElem = document.getElementById("asm");
-for (const func of state.asm.functions) {
-  const funcElem = document.createElement("div");
-
-  const funcHeader = document.createElement("h3");
-  let functionName =
-    func.name === null ? `function[${func.func_index}]` : func.name;
-  let demangledName =
-    func.demangled_name !== null ? func.demangled_name : functionName;
-  funcHeader.textContent = `Disassembly of function <${demangledName}>:`;
-  funcHeader.title = `Function ${func.func_index}: ${functionName}`;
-  funcElem.appendChild(funcHeader);
-
-  let currentBlock = createDivForCode();
-  let disasmBuffer = [];
-  let lastOffset = null;
-
-  const addCurrentBlock = offset => {
-    currentBlock.dataset.wasmOffset = offset;
-
-    if (offset !== null) {
-      adjustColorForOffset(currentBlock, offset);
-      linkElements(currentBlock);
-    }
+if (asmElem) {
+  for (const func of state.asm.functions) {
+    const funcElem = document.createElement("div");
 
-    currentBlock.innerText = disasmBuffer.join("\n");
-    funcElem.appendChild(currentBlock);
-    disasmBuffer = [];
-  };
+    const funcHeader = document.createElement("h3");
+    funcHeader.textContent = `Disassembly of ${describeFunction(func)}:`;
+    funcHeader.title = titleForFunction(func);
+    funcElem.appendChild(funcHeader);
+
+    let currentBlock = createDivForCode();
+    let disasmBuffer = [];
+    let lastOffset = null;
+
+    const addCurrentBlock = offset => {
+      currentBlock.dataset.wasmOffset = offset;
+
+      if (offset !== null) {
+        adjustColorForOffset(currentBlock, offset);
+        linkElements(currentBlock);
+      }
 
-  for (const inst of func.instructions) {
-    if (lastOffset !== inst.wasm_offset) {
-      addCurrentBlock(lastOffset);
-      currentBlock = createDivForCode();
-      lastOffset = inst.wasm_offset;
+      currentBlock.innerText = disasmBuffer.join("\n");
+      funcElem.appendChild(currentBlock);
+      disasmBuffer = [];
+    };
+
+    for (const inst of func.instructions) {
+      if (lastOffset !== inst.wasm_offset) {
+        addCurrentBlock(lastOffset);
+        currentBlock = createDivForCode();
+        lastOffset = inst.wasm_offset;
+      }
+      disasmBuffer.push(renderInstruction(inst));
     }
-    disasmBuffer.push(
-      `${renderAddress(inst.address)}    ${renderBytes(inst.bytes)}    ${renderInst(inst.mnemonic, inst.operands)}`,
-    );
+    addCurrentBlock(lastOffset);
+
+    asmElem.appendChild(funcElem);
   }
-  addCurrentBlock(lastOffset);
+}
+
+// Render the ASM of two compilations side by side (if any). Each row holds the
+// instructions that both compilations generated for the same Wasm offset, or
+// that only one of them generated.
+const diffElem = document.getElementById("diff");
+if (diffElem) {
+  const labelsElem = document.createElement("div");
+  labelsElem.classList.add("diff-row");
+  for (const label of state.diff.labels) {
+    const labelElem = document.createElement("h2");
+    labelElem.textContent = label;
+    labelsElem.appendChild(labelElem);
+  }
+  diffElem.appendChild(labelsElem);
+
+  for (const func of state.diff.functions) {
+    const funcElem = document.createElement("div");
+
+    const funcHeader = document.createElement("h3");
+    funcHeader.textContent = `Disassembly of ${describeFunction(func)}:`;
+    funcHeader.title = titleForFunction(func);
+    funcElem.appendChild(funcHeader);
 
-  asmElem.appendChild(funcElem);
+    for (const row of func.rows) {
+      const rowElem = document.createElement("div");
+      rowElem.classList.add("diff-row");
+      for (const instructions of row.instructions) {
+        const block = createDivForCode();
+        if (instructions.length > 0) {
+          block.innerText = instructions.map(renderInstruction).join("\n");
+          if (row.wasm_offset !== null) {
+            block.dataset.wasmOffset = row.wasm_offset;
+            adjustColorForOffset(block, row.wasm_offset);
+            linkElements(block);
+          }
+        }
+        rowElem.appendChild(block);
+      }
+      funcElem.appendChild(rowElem);
+    }
+
+    diffElem.appendChild(funcElem);
+  }
 }
 
 // Render the WAT.
@@ -9,12 +9,17 @@ use anyhow::Result;
 use capstone::arch::BuildsCapstone;
 use serde_derive::Serialize;
 use std::{
+    collections::HashMap,
     fs::File,
     io::{Write, read_to_string},
+    iter::Peekable,
     path::Path,
     str::FromStr,
 };
-use wasmtime_environ::demangle_function_name;
+use wasmtime_environ::{demangle_function_name, wasmparser};
+
+mod component;
+mod diff;
 
 pub fn generate(
     config: &wasmtime::Config,
@@ -23,14 +28,8 @@ pub fn generate(
     wasm: &[u8],
     dest: &mut dyn Write,
 ) -> Result<()> {
-    let target = match target {
-        None => target_lexicon::Triple::host(),
-        Some(target) => target_lexicon::Triple::from_str(target)?,
-    };
-
-    let wat = annotate_wat(wasm)?;
+    let (wat, asm) = annotate(config, target, wasm)?;
     let wat_json = serde_json::to_string(&wat)?;
-    let asm = annotate_asm(config, &target, wasm)?;
     let asm_json = serde_json::to_string(&asm)?;
     let clif_json = clif_dir
         .map::<anyhow::Result<String>, _>(|clif_dir| {
@@ -91,7 +90,82 @@ pub fn generate(
     Ok(())
 }
 
-#[derive(Serialize, Clone, Copy, Debug)]
+/// One of the two compilations compared by [`generate_diff`].
+pub struct DiffSide<'a> {
+    /// A short description of this compilation, such as the options it was
+    /// compiled with.
+    pub label: &'a str,
+    pub config: &'a wasmtime::Config,
+    pub target: Option<&'a str>,
+}
+
+/// Like [`generate`] but compiles `wasm` twice, once for each of `sides`, and
+/// shows the assembly of both compilations side by side.
+///
+/// The blocks of instructions compiled from each Wasm offset are aligned with
+/// each other so that, for example, the code generated by two compilers or at
+/// two optimization levels can be compared.
+pub fn generate_diff(sides: [DiffSide<'_>; 2], wasm: &[u8], dest: &mut dyn Write) -> Result<()> {
+    let (wat, left) = annotate(sides[0].config, sides[0].target, wasm)?;
+    let (_, right) = annotate(sides[1].config, sides[1].target, wasm)?;
+    let diff = diff::diff([sides[0].label, sides[1].label], [left, right]);
+    let wat_json = serde_json::to_string(&wat)?;
+    let diff_json = serde_json::to_string(&diff)?;
+
+    let index_css = include_str!("./index.css");
+    let index_js = include_str!("./index.js");
+
+    write!(
+        dest,
+        r#"
+<!DOCTYPE html>
+<html>
+  <head>
+    <title>Wasmtime Compiler Explorer</title>
+    <style>
+      {index_css}
+    </style>
+  </head>
+  <body class="hbox">
+    <pre id="wat"></pre>
+    <div id="diff"></div>
+    <script>
+      window.WAT = {wat_json};
+      window.DIFF = {diff_json};
+    </script>
+    <script>
+      {index_js}
+    </script>
+  </body>
+</html>
+        "#
+    )?;
+    Ok(())
+}
+
+fn annotate(
+    config: &wasmtime::Config,
+    target: Option<&str>,
+    wasm: &[u8],
+) -> Result<(AnnotatedWat, AnnotatedAsm)> {
+    let target = match target {
+        None => target_lexicon::Triple::host(),
+        Some(target) => target_lexicon::Triple::from_str(target)?,
+    };
+
+    if wasmparser::Parser::is_component(wasm) {
+        component::annotate(config, &target, wasm)
+    } else {
+        Ok((annotate_wat(wasm)?, annotate_asm(config, &target, wasm)?))
+    }
+}
+
+/// An offset into the Wasm binary being explored.
+///
+/// For components, the fused adapter modules generated by Wasmtime are
+/// explored too. Offsets into those are placed after the end of the component,
+/// one adapter module after another, so they don't collide with its offsets.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
 struct WasmOffset(u32);
 
 #[derive(Serialize, Debug)]
@@ -125,10 +199,33 @@ struct AnnotatedAsm {
 
 #[derive(Serialize, Debug)]
 struct AnnotatedFunction {
-    func_index: u32,
+    func_index: Option<u32>,
     name: Option<String>,
     demangled_name: Option<String>,
+    kind: FunctionKind,
+    /// The WIT function that this function implements or is part of the
+    /// lifting or lowering of, for functions within a component.
+    wit_name: Option<String>,
     instructions: Vec<AnnotatedInstruction>,
+    /// The name of the file, without extension, that the function's CLIF is
+    /// written to, or a prefix of it followed by `--`.
+    #[serde(skip)]
+    clif_name: String,
+    /// Added to the Wasm offsets of the function's CLIF, see `WasmOffset`.
+    #[serde(skip)]
+    clif_offset_base: u32,
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "kebab-case")]
+enum FunctionKind {
+    /// A function defined in a core wasm module.
+    Function,
+    /// A function of a fused adapter module generated by Wasmtime to connect
+    /// the lifting and lowering of a function between two components.
+    Adapter,
+    /// A trampoline generated by Wasmtime, for example to call into the host.
+    Trampoline,
 }
 
 #[derive(Serialize, Debug)]
@@ -149,82 +246,22 @@ fn annotate_asm(
     let module = wasmtime::Module::new(&engine, wasm)?;
 
     let text = module.text();
-    let address_map: Vec<_> = module
-        .address_map()
-        .ok_or_else(|| anyhow::anyhow!("address maps must be enabled in the config"))?
-        .collect();
-
-    let mut address_map_iter = address_map.into_iter().peekable();
-    let mut current_entry = address_map_iter.next();
-    let mut wasm_offset_for_address = |start: usize, address: u32| -> Option<WasmOffset> {
-        // Consume any entries that happened before the current function for the
-        // first instruction.
-        while current_entry.map_or(false, |cur| cur.0 < start) {
-            current_entry = address_map_iter.next();
-        }
-
-        // Next advance the address map up to the current `address` specified,
-        // including it.
-        while address_map_iter.peek().map_or(false, |next_entry| {
-            u32::try_from(next_entry.0).unwrap() <= address
-        }) {
-            current_entry = address_map_iter.next();
-        }
-        current_entry.and_then(|entry| entry.1.map(WasmOffset))
-    };
+    let mut address_map = AddressMap::new(
+        module
+            .address_map()
+            .ok_or_else(|| anyhow::anyhow!("address maps must be enabled in the config"))?,
+    );
 
+    let cs = capstone(target)?;
     let functions = module
         .functions()
         .map(|function| {
             let body = &text[function.offset..][..function.len];
-
-            let mut cs = match target.architecture {
-                target_lexicon::Architecture::Aarch64(_) => capstone::Capstone::new()
-                    .arm64()
-                    .mode(capstone::arch::arm64::ArchMode::Arm)
-                    .build()
-                    .map_err(|e| anyhow::anyhow!("{e}"))?,
-                target_lexicon::Architecture::Riscv64(_) => capstone::Capstone::new()
-                    .riscv()
-                    .mode(capstone::arch::riscv::ArchMode::RiscV64)
-                    .build()
-                    .map_err(|e| anyhow::anyhow!("{e}"))?,
-                target_lexicon::Architecture::S390x => capstone::Capstone::new()
-                    .sysz()
-                    .mode(capstone::arch::sysz::ArchMode::Default)
-                    .build()
-                    .map_err(|e| anyhow::anyhow!("{e}"))?,
-                target_lexicon::Architecture::X86_64 => capstone::Capstone::new()
-                    .x86()
-                    .mode(capstone::arch::x86::ArchMode::Mode64)
-                    .build()
-                    .map_err(|e| anyhow::anyhow!("{e}"))?,
-                _ => anyhow::bail!("Unsupported target: {target}"),
-            };
-
-            // This tells capstone to skip over anything that looks like data,
-            // such as inline constant pools and things like that. This also
-            // additionally is required to skip over trapping instructions on
-            // AArch64.
-            cs.set_skipdata(true).unwrap();
-
-            let instructions = cs
-                .disasm_all(body, function.offset as u64)
-                .map_err(|e| anyhow::anyhow!("{e}"))?;
-            let instructions = instructions
-                .iter()
-                .map(|inst| {
-                    let address = u32::try_from(inst.address()).unwrap();
-                    let wasm_offset = wasm_offset_for_address(function.offset, address);
-                    Ok(AnnotatedInstruction {
-                        wasm_offset,
-                        address,
-                        bytes: inst.bytes().to_vec(),
-                        mnemonic: inst.mnemonic().map(ToString::to_string),
-                        operands: inst.op_str().map(ToString::to_string),
-                    })
-                })
-                .collect::<Result<Vec<_>>>()?;
+            let instructions = disassemble(&cs, body, function.offset, |address| {
+                address_map
+                    .wasm_offset(function.offset, address)
+                    .map(WasmOffset)
+            })?;
 
             let demangled_name = if let Some(name) = &function.name {
                 let mut demangled = String::new();
@@ -238,10 +275,14 @@ fn annotate_asm(
             };
 
             Ok(AnnotatedFunction {
-                func_index: function.index.as_u32(),
+                func_index: Some(function.index.as_u32()),
                 name: function.name,
                 demangled_name,
+                kind: FunctionKind::Function,
+                wit_name: None,
                 instructions,
+                clif_name: format!("wasm[0]--function[{}]", function.index.as_u32()),
+                clif_offset_base: 0,
             })
         })
         .collect::<Result<Vec<_>>>()?;
@@ -249,6 +290,97 @@ fn annotate_asm(
     Ok(AnnotatedAsm { functions })
 }
 
+/// A cursor over an address map, yielding the Wasm offset of each native
+/// instruction. Functions and their instructions must be visited in increasing
+/// address order.
+struct AddressMap<I: Iterator<Item = (usize, Option<u32>)>> {
+    iter: Peekable<I>,
+    current: Option<(usize, Option<u32>)>,
+}
+
+impl<I: Iterator<Item = (usize, Option<u32>)>> AddressMap<I> {
+    fn new(iter: I) -> Self {
+        let mut iter = iter.peekable();
+        let current = iter.next();
+        AddressMap { iter, current }
+    }
+
+    fn wasm_offset(&mut self, start: usize, address: u32) -> Option<u32> {
+        // Consume any entries that happened before the current function for the
+        // first instruction.
+        while self.current.map_or(false, |cur| cur.0 < start) {
+            self.current = self.iter.next();
+        }
+
+        // Next advance the address map up to the current `address` specified,
+        // including it.
+        while self.iter.peek().map_or(false, |next_entry| {
+            u32::try_from(next_entry.0).unwrap() <= address
+        }) {
+            self.current = self.iter.next();
+        }
+        self.current.and_then(|entry| entry.1)
+    }
+}
+
+fn capstone(target: &target_lexicon::Triple) -> Result<capstone::Capstone> {
+    let mut cs = match target.architecture {
+        target_lexicon::Architecture::Aarch64(_) => capstone::Capstone::new()
+            .arm64()
+            .mode(capstone::arch::arm64::ArchMode::Arm)
+            .build()
+            .map_err(|e| anyhow::anyhow!("{e}"))?,
+        target_lexicon::Architecture::Riscv64(_) => capstone::Capstone::new()
+            .riscv()
+            .mode(capstone::arch::riscv::ArchMode::RiscV64)
+            .build()
+            .map_err(|e| anyhow::anyhow!("{e}"))?,
+        target_lexicon::Architecture::S390x => capstone::Capstone::new()
+            .sysz()
+            .mode(capstone::arch::sysz::ArchMode::Default)
+            .build()
+            .map_err(|e| anyhow::anyhow!("{e}"))?,
+        target_lexicon::Architecture::X86_64 => capstone::Capstone::new()
+            .x86()
+            .mode(capstone::arch::x86::ArchMode::Mode64)
+            .build()
+            .map_err(|e| anyhow::anyhow!("{e}"))?,
+        _ => anyhow::bail!("Unsupported target: {target}"),
+    };
+
+    // This tells capstone to skip over anything that looks like data,
+    // such as inline constant pools and things like that. This also
+    // additionally is required to skip over trapping instructions on
+    // AArch64.
+    cs.set_skipdata(true).unwrap();
+    Ok(cs)
+}
+
+/// Disassembles the function `body` found at `offset` in the text section.
+fn disassemble(
+    cs: &capstone::Capstone,
+    body: &[u8],
+    offset: usize,
+    mut wasm_offset_for_address: impl FnMut(u32) -> Option<WasmOffset>,
+) -> Result<Vec<AnnotatedInstruction>> {
+    let instructions = cs
+        .disasm_all(body, offset as u64)
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    Ok(instructions
+        .iter()
+        .map(|inst| {
+            let address = u32::try_from(inst.address()).unwrap();
+            AnnotatedInstruction {
+                wasm_offset: wasm_offset_for_address(address),
+                address,
+                bytes: inst.bytes().to_vec(),
+                mnemonic: inst.mnemonic().map(ToString::to_string),
+                operands: inst.op_str().map(ToString::to_string),
+            }
+        })
+        .collect())
+}
+
 #[derive(Serialize, Debug)]
 struct AnnotatedClif {
     functions: Vec<AnnotatedClifFunction>,
@@ -256,9 +388,11 @@ struct AnnotatedClif {
 
 #[derive(Serialize, Debug)]
 struct AnnotatedClifFunction {
-    func_index: u32,
+    func_index: Option<u32>,
     name: Option<String>,
     demangled_name: Option<String>,
+    kind: FunctionKind,
+    wit_name: Option<String>,
     instructions: Vec<AnnotatedClifInstruction>,
 }
 
@@ -272,25 +406,48 @@ fn annotate_clif(clif_dir: &Path, asm: &AnnotatedAsm) -> Result<AnnotatedClif> {
     let mut clif = AnnotatedClif {
         functions: Vec::new(),
     };
+
+    // CLIF files are named after the symbol of their function, with `:`
+    // replaced by `-`, and the symbols of Wasm functions additionally include
+    // their name from the name section, if any.
+    let mut clif_files = HashMap::new();
+    for entry in std::fs::read_dir(clif_dir)? {
+        let path = entry?.path();
+        if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+            let key = match stem.find("--function[") {
+                Some(i) => match stem[i..].find(']') {
+                    Some(j) => &stem[..i + j + 1],
+                    None => stem,
+                },
+                None => stem,
+            };
+            clif_files.insert(key.to_string(), path.clone());
+        }
+    }
+
     for function in &asm.functions {
-        let function_path = clif_dir.join(format!("wasm_func_{}.clif", function.func_index));
-        if !function_path.exists() {
+        let Some(function_path) = clif_files.get(&function.clif_name) else {
             continue;
-        }
+        };
         let mut clif_function = AnnotatedClifFunction {
             func_index: function.func_index,
             name: function.name.clone(),
             demangled_name: function.demangled_name.clone(),
+            kind: function.kind,
+            wit_name: function.wit_name.clone(),
             instructions: Vec::new(),
         };
-        let file = File::open(&function_path)?;
+        let file = File::open(function_path)?;
         for mut line in read_to_string(file)?.lines() {
-            if line.is_empty() {
+            if line.is_empty() || line.starts_with(";;") {
                 continue;
             }
             let mut wasm_offset = None;
-            if line.starts_with('@') {
-                wasm_offset = Some(WasmOffset(u32::from_str_radix(&line[1..5], 16)?));
+            if let Some(rest) = line.strip_prefix('@') {
+                let end = rest.find(' ').unwrap_or(rest.len());
+                wasm_offset = Some(WasmOffset(
+                    u32::from_str_radix(&rest[..end], 16)? + function.clif_offset_base,
+                ));
                 line = &line[28..];
             } else if line.starts_with("     ") {
                 line = &line[28..];
@@ -9,6 +9,26 @@ fn main() {
     let resp = bucket.list_keys(None).unwrap();
     assert_eq!(resp.keys, vec!["atomics_key".to_string()]);
 
+    // a swap succeeds if the value hasn't changed since the cas was created
+    let cas = atomics::Cas::new(&bucket, "cas_key").unwrap();
+    assert_eq!(cas.current().unwrap(), None);
+    atomics::swap(cas, "1".as_bytes()).unwrap();
+
+    // and otherwise fails, handing back a cas for the latest value to retry with
+    let cas = atomics::Cas::new(&bucket, "cas_key").unwrap();
+    bucket.set("cas_key", "2".as_bytes()).unwrap();
+    let cas = match atomics::swap(cas, "3".as_bytes()) {
+        Err(atomics::CasError::CasFailed(cas)) => cas,
+        _ => panic!("swap of a changed value should fail"),
+    };
+    assert_eq!(cas.current().unwrap(), Some("2".as_bytes().to_vec()));
+    atomics::swap(cas, "3".as_bytes()).unwrap();
+    assert_eq!(
+        bucket.get("cas_key").unwrap(),
+        Some("3".as_bytes().to_vec())
+    );
+    bucket.delete("cas_key").unwrap();
+
     bucket.set("hello", "world".as_bytes()).unwrap();
 
     let v = bucket.get("hello").unwrap();
@@ -36,9 +56,9 @@ fn main() {
     assert_eq!(
         values,
         vec![
-            None,
-            Some(("b1".to_string(), "v1".as_bytes().to_vec())),
-            None
+            ("a1".to_string(), None),
+            ("b1".to_string(), Some("v1".as_bytes().to_vec())),
+            ("c1".to_string(), None)
         ]
     );
 }
@@ -14,7 +14,7 @@ wit_bindgen::generate!({
             include wasi:cli/imports@0.2.6;
             include wasi:http/imports@0.2.6;
             include wasi:config/imports@0.2.0-draft;
-            include wasi:keyvalue/imports@0.2.0-draft;
+            include wasi:keyvalue/imports@0.2.0-draft2;
             include wasi:tls/imports@0.2.0-draft;
         }
     ",
@@ -55,7 +55,7 @@ pub unsafe trait Unwind {
 }
 
 /// A stack frame within a Wasm stack trace.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Frame {
     /// The program counter in this frame. Because every frame in the
     /// stack-walk is paused at a call (as we are in host code called
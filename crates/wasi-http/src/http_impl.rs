@@ -93,6 +93,21 @@ where
             .body(body)
             .map_err(|err| internal_error(err.to_string()))?;
 
+        let ctx = self.ctx();
+        let policy = ctx.outgoing_policy.clone();
+        if !policy.is_allowed(request.uri()) {
+            return Err(types::ErrorCode::HttpRequestDenied.into());
+        }
+        let (mut parts, body) = request.into_parts();
+        policy.apply_headers(&parts.uri, &mut parts.headers);
+        let body =
+            policy.limit_body(&parts.headers, body, types::ErrorCode::HttpRequestBodySize)?;
+        let request = hyper::Request::from_parts(parts, body);
+        if !policy.allows_another_request(ctx.outgoing_requests) {
+            return Err(types::ErrorCode::HttpRequestDenied.into());
+        }
+        ctx.outgoing_requests += 1;
+
         let future = self.send_request(
             request,
             OutgoingRequestConfig {
@@ -100,6 +115,7 @@ where
                 connect_timeout,
                 first_byte_timeout,
                 between_bytes_timeout,
+                policy,
             },
         )?;
 
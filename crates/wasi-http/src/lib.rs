@@ -222,6 +222,7 @@
 
 mod error;
 mod http_impl;
+mod policy;
 mod types_impl;
 
 pub mod body;
@@ -236,6 +237,7 @@ pub mod p3;
 pub use crate::error::{
     HttpError, HttpResult, http_request_error, hyper_request_error, hyper_response_error,
 };
+pub use crate::policy::OutgoingPolicy;
 #[doc(inline)]
 pub use crate::types::{
     DEFAULT_OUTGOING_BODY_BUFFER_CHUNKS, DEFAULT_OUTGOING_BODY_CHUNK_SIZE, WasiHttpCtx,
@@ -297,7 +297,25 @@ impl HostWithStore for WasiHttp {
                 .uri(uri)
                 .body(body)
                 .map_err(|err| ErrorCode::InternalError(Some(err.to_string())))?;
-            HttpResult::Ok(store.get().ctx.send_request(
+            let WasiHttpCtxView { ctx, .. } = store.get();
+            let req = match ctx.outgoing_policy() {
+                Some((policy, sent)) => {
+                    if !policy.is_allowed(req.uri()) {
+                        return Err(ErrorCode::HttpRequestDenied.into());
+                    }
+                    let (mut parts, body) = req.into_parts();
+                    policy.apply_headers(&parts.uri, &mut parts.headers);
+                    let body =
+                        policy.limit_body(&parts.headers, body, ErrorCode::HttpRequestBodySize)?;
+                    if !policy.allows_another_request(*sent) {
+                        return Err(ErrorCode::HttpRequestDenied.into());
+                    }
+                    *sent += 1;
+                    http::Request::from_parts(parts, body)
+                }
+                None => req,
+            };
+            HttpResult::Ok(ctx.send_request(
                 req,
                 options.as_deref().copied(),
                 Box::new(async {
@@ -21,6 +21,7 @@ pub use request::default_send_request;
 pub use request::{Request, RequestOptions};
 pub use response::Response;
 
+use crate::OutgoingPolicy;
 use crate::p3::bindings::http::types::ErrorCode;
 use crate::types::DEFAULT_FORBIDDEN_HEADERS;
 use bindings::http::{handler, types};
@@ -76,6 +77,17 @@ pub trait WasiHttpCtx: Send {
         Some(Scheme::HTTPS)
     }
 
+    /// The policy applied to outgoing requests before they're passed to
+    /// `send_request`, along with the number of requests it has permitted so
+    /// far, which `handle` increments.
+    ///
+    /// If [None], all requests are permitted. [`crate::WasiHttpCtx`] returns
+    /// its [outgoing policy](crate::WasiHttpCtx::set_outgoing_policy), so the
+    /// same policy and request count can apply to WASIp2 and WASIp3.
+    fn outgoing_policy(&mut self) -> Option<(&OutgoingPolicy, &mut u64)> {
+        None
+    }
+
     /// Send an outgoing request.
     ///
     /// This function will be used by the `wasi:http/handler#handle` implementation.
@@ -158,6 +170,13 @@ pub struct DefaultWasiHttpCtx;
 #[cfg(feature = "default-send-request")]
 impl WasiHttpCtx for DefaultWasiHttpCtx {}
 
+#[cfg(feature = "default-send-request")]
+impl WasiHttpCtx for crate::WasiHttpCtx {
+    fn outgoing_policy(&mut self) -> Option<(&OutgoingPolicy, &mut u64)> {
+        Some((&self.outgoing_policy, &mut self.outgoing_requests))
+    }
+}
+
 /// View into [WasiHttpCtx] implementation and [ResourceTable].
 pub struct WasiHttpCtxView<'a> {
     /// Mutable reference to the WASI HTTP context.
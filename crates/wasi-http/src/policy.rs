@@ -0,0 +1,373 @@
+//! Implements [`OutgoingPolicy`], the built-in filter for outgoing requests.
+
+use anyhow::{Context as _, bail};
+use bytes::Bytes;
+use http_body::{Body, Frame, SizeHint};
+use http_body_util::BodyExt;
+use http_body_util::combinators::BoxBody;
+use hyper::header::{HeaderMap, HeaderName, HeaderValue};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A policy applied to every request sent through
+/// `wasi:http/outgoing-handler`, or WASIp3's `wasi:http/handler`.
+///
+/// The policy is installed with [`WasiHttpCtx::set_outgoing_policy`] and is
+/// enforced before [`WasiHttpView::send_request`] is called, so it also
+/// applies to embedders which override how requests are sent. WASIp3 requests
+/// are checked against the policy returned by
+/// `p3::WasiHttpCtx::outgoing_policy`, which [`WasiHttpCtx`] implements. The
+/// default policy permits everything, matching the behavior without a policy.
+///
+/// Hosts are matched with patterns of the form `[scheme://]host[:port]`:
+///
+/// * `example.com` matches only that host, on any scheme and port.
+/// * `*.example.com` matches subdomains of `example.com`, but not
+///   `example.com` itself.
+/// * `*` matches any host, so `http://*` matches any plaintext request.
+///
+/// A request is denied if it matches any [`deny`](OutgoingPolicy::deny)
+/// pattern, or if any [`allow`](OutgoingPolicy::allow) patterns were added and
+/// it matches none of them.
+///
+/// [`WasiHttpCtx`]: crate::WasiHttpCtx
+/// [`WasiHttpCtx::set_outgoing_policy`]: crate::WasiHttpCtx::set_outgoing_policy
+/// [`WasiHttpView::send_request`]: crate::WasiHttpView::send_request
+///
+/// # Example
+///
+/// ```
+/// use wasmtime_wasi_http::{OutgoingPolicy, WasiHttpCtx};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut policy = OutgoingPolicy::new();
+/// policy
+///     .allow("https://api.example.com")?
+///     .allow("https://*.cdn.example.com")?
+///     .inject_header("x-tenant".parse()?, "acme".parse()?)
+///     .strip_header("cookie".parse()?)
+///     .max_body_size(1 << 20)
+///     .max_requests(100)
+///     .max_redirects(5);
+///
+/// let mut ctx = WasiHttpCtx::new();
+/// ctx.set_outgoing_policy(policy);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct OutgoingPolicy {
+    allow: Vec<HostPattern>,
+    deny: Vec<HostPattern>,
+    /// Headers to inject, along with the hosts to inject them for, or `None`
+    /// for all hosts.
+    inject_headers: Vec<(Option<HostPattern>, HeaderName, HeaderValue)>,
+    strip_headers: Vec<HeaderName>,
+    max_body_size: Option<u64>,
+    max_requests: Option<u64>,
+    max_redirects: u32,
+}
+
+impl OutgoingPolicy {
+    /// Creates a policy which permits all requests.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Permits requests to hosts matching `pattern`.
+    ///
+    /// Once any pattern is allowed, requests which match no allowed pattern
+    /// are denied.
+    pub fn allow(&mut self, pattern: &str) -> anyhow::Result<&mut Self> {
+        self.allow.push(pattern.parse()?);
+        Ok(self)
+    }
+
+    /// Denies requests to hosts matching `pattern`, even if they're allowed.
+    pub fn deny(&mut self, pattern: &str) -> anyhow::Result<&mut Self> {
+        self.deny.push(pattern.parse()?);
+        Ok(self)
+    }
+
+    /// Sets the header `name` to `value` on every request, replacing any
+    /// values set by the guest.
+    pub fn inject_header(&mut self, name: HeaderName, value: HeaderValue) -> &mut Self {
+        self.inject_headers.push((None, name, value));
+        self
+    }
+
+    /// Like [`inject_header`](OutgoingPolicy::inject_header), but only for
+    /// requests to hosts matching `pattern`.
+    ///
+    /// This is meant for credentials: when a redirect is followed to a host
+    /// which doesn't match `pattern` the header is removed again.
+    pub fn inject_header_for(
+        &mut self,
+        pattern: &str,
+        name: HeaderName,
+        value: HeaderValue,
+    ) -> anyhow::Result<&mut Self> {
+        self.inject_headers
+            .push((Some(pattern.parse()?), name, value));
+        Ok(self)
+    }
+
+    /// Removes the header `name` from every request.
+    ///
+    /// This is applied when requests are sent, in addition to the headers
+    /// which [`WasiHttpView::is_forbidden_header`] prevents guests from
+    /// setting at all. Injected headers are never stripped.
+    ///
+    /// [`WasiHttpView::is_forbidden_header`]: crate::WasiHttpView::is_forbidden_header
+    pub fn strip_header(&mut self, name: HeaderName) -> &mut Self {
+        self.strip_headers.push(name);
+        self
+    }
+
+    /// Limits the body of each request to `bytes` bytes.
+    ///
+    /// Requests with a larger `content-length` fail immediately with
+    /// `HTTP-request-body-size`, and other requests fail once their body
+    /// grows past the limit.
+    pub fn max_body_size(&mut self, bytes: u64) -> &mut Self {
+        self.max_body_size = Some(bytes);
+        self
+    }
+
+    /// Limits the number of requests a store may send, after which requests
+    /// are denied.
+    pub fn max_requests(&mut self, requests: u64) -> &mut Self {
+        self.max_requests = Some(requests);
+        self
+    }
+
+    /// Follows up to `redirects` redirects for each request.
+    ///
+    /// By default redirects aren't followed and the redirect response is
+    /// returned to the guest. Redirect targets must be permitted by this
+    /// policy, otherwise the request fails with `HTTP-request-denied`. A
+    /// redirect response is also returned as-is if following it would require
+    /// resending a request body. Redirects are only followed by
+    /// [`default_send_request`](crate::types::default_send_request).
+    pub fn max_redirects(&mut self, redirects: u32) -> &mut Self {
+        self.max_redirects = redirects;
+        self
+    }
+
+    /// Returns the configured limit on redirects to follow per request.
+    pub fn redirect_limit(&self) -> u32 {
+        self.max_redirects
+    }
+
+    /// Returns whether requests to `uri` are permitted by this policy.
+    pub fn is_allowed(&self, uri: &http::Uri) -> bool {
+        if uri.scheme().is_none() || uri.authority().is_none() {
+            return false;
+        }
+        let matches = |pattern: &HostPattern| pattern.matches_uri(uri);
+        if self.deny.iter().any(matches) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(matches)
+    }
+
+    /// Applies the header rules of this policy to `headers` of a request to
+    /// `uri`.
+    pub(crate) fn apply_headers(&self, uri: &http::Uri, headers: &mut HeaderMap) {
+        for name in self.strip_headers.iter() {
+            headers.remove(name);
+        }
+        for (name, value) in self.injected_headers(uri) {
+            headers.insert(name.clone(), value.clone());
+        }
+    }
+
+    /// Removes the headers [`apply_headers`](Self::apply_headers) injected
+    /// into `headers` of a request to `uri`.
+    #[cfg(feature = "default-send-request")]
+    pub(crate) fn remove_injected_headers(&self, uri: &http::Uri, headers: &mut HeaderMap) {
+        for (name, _) in self.injected_headers(uri) {
+            headers.remove(name);
+        }
+    }
+
+    fn injected_headers(
+        &self,
+        uri: &http::Uri,
+    ) -> impl Iterator<Item = (&HeaderName, &HeaderValue)> {
+        self.inject_headers
+            .iter()
+            .filter(|(hosts, ..)| hosts.as_ref().is_none_or(|p| p.matches_uri(uri)))
+            .map(|(_, name, value)| (name, value))
+    }
+
+    /// Returns whether another request may be sent after `sent` requests.
+    pub(crate) fn allows_another_request(&self, sent: u64) -> bool {
+        self.max_requests.is_none_or(|max| sent < max)
+    }
+
+    /// Applies the body size limit of this policy to `body`, given the
+    /// request's headers, failing with `make_error` once it's exceeded.
+    pub(crate) fn limit_body<B, E>(
+        &self,
+        headers: &HeaderMap,
+        body: B,
+        make_error: fn(Option<u64>) -> E,
+    ) -> Result<BoxBody<Bytes, E>, E>
+    where
+        B: Body<Data = Bytes, Error = E> + Send + Sync + Unpin + 'static,
+        E: 'static,
+    {
+        let Some(limit) = self.max_body_size else {
+            return Ok(body.boxed());
+        };
+        let content_length = headers
+            .get(hyper::header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        if let Some(len) = content_length.filter(|len| *len > limit) {
+            return Err(make_error(Some(len)));
+        }
+        Ok(LimitedBody {
+            body,
+            limit,
+            written: 0,
+            make_error,
+        }
+        .boxed())
+    }
+}
+
+/// A pattern matching the scheme, host and port of a request.
+#[derive(Clone, Debug)]
+struct HostPattern {
+    scheme: Option<String>,
+    host: HostMatch,
+    port: Option<u16>,
+}
+
+#[derive(Clone, Debug)]
+enum HostMatch {
+    Any,
+    Exact(String),
+    Subdomain(String),
+}
+
+impl std::str::FromStr for HostPattern {
+    type Err = anyhow::Error;
+
+    fn from_str(pattern: &str) -> anyhow::Result<Self> {
+        let (scheme, rest) = match pattern.split_once("://") {
+            Some((scheme, rest)) => (Some(scheme.to_ascii_lowercase()), rest),
+            None => (None, pattern),
+        };
+        if let Some(scheme) = &scheme {
+            if scheme != "http" && scheme != "https" {
+                bail!("unsupported scheme `{scheme}` in host pattern `{pattern}`");
+            }
+        }
+        let (host, port) = match rest.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => {
+                let port = port
+                    .parse()
+                    .with_context(|| format!("invalid port in host pattern `{pattern}`"))?;
+                (host, Some(port))
+            }
+            _ => (rest, None),
+        };
+        let host = host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_ascii_lowercase();
+        let host = if host == "*" {
+            HostMatch::Any
+        } else if let Some(domain) = host.strip_prefix("*.") {
+            HostMatch::Subdomain(domain.to_string())
+        } else {
+            HostMatch::Exact(host)
+        };
+        match &host {
+            HostMatch::Exact(h) | HostMatch::Subdomain(h)
+                if h.is_empty() || h.contains(['*', '/']) =>
+            {
+                bail!("invalid host pattern `{pattern}`")
+            }
+            _ => {}
+        }
+        Ok(HostPattern { scheme, host, port })
+    }
+}
+
+impl HostPattern {
+    fn matches_uri(&self, uri: &http::Uri) -> bool {
+        let (Some(scheme), Some(authority)) = (uri.scheme_str(), uri.authority()) else {
+            return false;
+        };
+        let host = authority
+            .host()
+            .trim_start_matches('[')
+            .trim_end_matches(']');
+        self.matches(scheme, host, authority.port_u16())
+    }
+
+    fn matches(&self, scheme: &str, host: &str, port: Option<u16>) -> bool {
+        if let Some(s) = &self.scheme {
+            if !s.eq_ignore_ascii_case(scheme) {
+                return false;
+            }
+        }
+        if self.port.is_some() && self.port != port {
+            return false;
+        }
+        match &self.host {
+            HostMatch::Any => true,
+            HostMatch::Exact(h) => h.eq_ignore_ascii_case(host),
+            HostMatch::Subdomain(domain) => {
+                host.len().checked_sub(domain.len() + 1).is_some_and(|dot| {
+                    host.as_bytes()[dot] == b'.' && host[dot + 1..].eq_ignore_ascii_case(domain)
+                })
+            }
+        }
+    }
+}
+
+/// A request body which fails once more than `limit` bytes are written.
+struct LimitedBody<B, E> {
+    body: B,
+    limit: u64,
+    written: u64,
+    make_error: fn(Option<u64>) -> E,
+}
+
+impl<B, E> Body for LimitedBody<B, E>
+where
+    B: Body<Data = Bytes, Error = E> + Unpin,
+{
+    type Data = Bytes;
+    type Error = E;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, E>>> {
+        let frame = match Pin::new(&mut self.body).poll_frame(cx) {
+            Poll::Ready(Some(Ok(frame))) => frame,
+            other => return other,
+        };
+        if let Some(data) = frame.data_ref() {
+            self.written = self.written.saturating_add(data.len() as u64);
+            if self.written > self.limit {
+                return Poll::Ready(Some(Err((self.make_error)(Some(self.written)))));
+            }
+        }
+        Poll::Ready(Some(Ok(frame)))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.body.size_hint()
+    }
+}
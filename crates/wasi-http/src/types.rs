@@ -2,6 +2,7 @@
 //! implementation of the wasi-http API.
 
 use crate::{
+    OutgoingPolicy,
     bindings::http::types::{self, Method, Scheme},
     body::{HostIncomingBody, HyperIncomingBody, HyperOutgoingBody},
 };
@@ -11,6 +12,7 @@ use http_body_util::BodyExt;
 use hyper::body::Body;
 use hyper::header::HeaderName;
 use std::any::Any;
+use std::sync::Arc;
 use std::time::Duration;
 use wasmtime::component::{Resource, ResourceTable};
 use wasmtime_wasi::p2::Pollable;
@@ -27,13 +29,37 @@ use {
 /// Capture the state necessary for use in the wasi-http API implementation.
 #[derive(Debug)]
 pub struct WasiHttpCtx {
-    _priv: (),
+    pub(crate) outgoing_policy: Arc<OutgoingPolicy>,
+    pub(crate) outgoing_requests: u64,
 }
 
 impl WasiHttpCtx {
     /// Create a new context.
     pub fn new() -> Self {
-        Self { _priv: () }
+        Self {
+            outgoing_policy: Arc::new(OutgoingPolicy::new()),
+            outgoing_requests: 0,
+        }
+    }
+
+    /// Sets the policy applied to outgoing requests.
+    ///
+    /// The policy is enforced for requests made through WASIp2's
+    /// `wasi:http/outgoing-handler`, and for WASIp3's `wasi:http/handler` when
+    /// this context is also used as the `p3::WasiHttpCtx`.
+    pub fn set_outgoing_policy(&mut self, policy: OutgoingPolicy) {
+        self.outgoing_policy = Arc::new(policy);
+    }
+
+    /// Returns the policy applied to outgoing requests.
+    pub fn outgoing_policy(&self) -> &OutgoingPolicy {
+        &self.outgoing_policy
+    }
+
+    /// Returns the number of outgoing requests the policy has permitted so
+    /// far.
+    pub fn outgoing_requests(&self) -> u64 {
+        self.outgoing_requests
     }
 }
 
@@ -333,6 +359,35 @@ pub struct OutgoingRequestConfig {
     pub first_byte_timeout: Duration,
     /// The timeout between chunks of a streaming body
     pub between_bytes_timeout: Duration,
+    /// The policy the request was checked against, which is also consulted
+    /// when following redirects.
+    pub(crate) policy: Arc<OutgoingPolicy>,
+}
+
+impl Default for OutgoingRequestConfig {
+    fn default() -> Self {
+        Self {
+            use_tls: true,
+            connect_timeout: Duration::from_secs(600),
+            first_byte_timeout: Duration::from_secs(600),
+            between_bytes_timeout: Duration::from_secs(600),
+            policy: Arc::new(OutgoingPolicy::new()),
+        }
+    }
+}
+
+impl OutgoingRequestConfig {
+    /// Sets the policy the request was checked against, which is also
+    /// consulted when following redirects.
+    pub fn with_policy(mut self, policy: Arc<OutgoingPolicy>) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the policy the request was checked against.
+    pub fn policy(&self) -> &OutgoingPolicy {
+        &self.policy
+    }
 }
 
 /// The default implementation of how an outgoing request is sent.
@@ -354,15 +409,129 @@ pub fn default_send_request(
 /// in a task.
 ///
 /// This is called from [default_send_request] to actually send the request.
+/// Redirects are followed as configured by [`OutgoingPolicy::max_redirects`].
 #[cfg(feature = "default-send-request")]
 pub async fn default_send_request_handler(
     mut request: hyper::Request<HyperOutgoingBody>,
-    OutgoingRequestConfig {
-        use_tls,
+    config: OutgoingRequestConfig,
+) -> Result<IncomingResponse, types::ErrorCode> {
+    let mut use_tls = config.use_tls;
+    for _ in 0..config.policy.redirect_limit() {
+        let method = request.method().clone();
+        let uri = request.uri().clone();
+        let headers = request.headers().clone();
+        let has_body = !request.body().is_end_stream();
+
+        let response = send_request_once(request, use_tls, &config).await?;
+        match redirect_request(
+            &config.policy,
+            method,
+            uri,
+            headers,
+            has_body,
+            &response.resp,
+        ) {
+            Some(next) => {
+                request = next?;
+                use_tls = request.uri().scheme() == Some(&http::uri::Scheme::HTTPS);
+            }
+            None => return Ok(response),
+        }
+    }
+    send_request_once(request, use_tls, &config).await
+}
+
+/// Returns the request to send to follow `response` to a request with
+/// `method`, `uri`, `headers`, or `None` if it isn't a redirect which can be
+/// followed.
+#[cfg(feature = "default-send-request")]
+fn redirect_request(
+    policy: &OutgoingPolicy,
+    method: http::Method,
+    uri: http::Uri,
+    mut headers: http::HeaderMap,
+    has_body: bool,
+    response: &hyper::Response<HyperIncomingBody>,
+) -> Option<Result<hyper::Request<HyperOutgoingBody>, types::ErrorCode>> {
+    use http::{Method, StatusCode, header};
+
+    let status = response.status();
+    let method = match status {
+        StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND if method == Method::POST => Method::GET,
+        StatusCode::SEE_OTHER if method != Method::HEAD => Method::GET,
+        StatusCode::MOVED_PERMANENTLY
+        | StatusCode::FOUND
+        | StatusCode::SEE_OTHER
+        | StatusCode::TEMPORARY_REDIRECT
+        | StatusCode::PERMANENT_REDIRECT => method,
+        _ => return None,
+    };
+    let keep_body = method != Method::GET || !matches!(status.as_u16(), 301..=303);
+    // The body has been consumed by now, so it can't be sent again.
+    if keep_body && has_body {
+        return None;
+    }
+
+    let location = response.headers().get(header::LOCATION)?.to_str().ok()?;
+    let mut next = url::Url::parse(&uri.to_string())
+        .ok()?
+        .join(location)
+        .ok()?;
+    next.set_fragment(None);
+    let next = next.as_str().parse::<http::Uri>().ok()?;
+    if !policy.is_allowed(&next) {
+        return Some(Err(types::ErrorCode::HttpRequestDenied));
+    }
+
+    if !keep_body {
+        for name in [
+            header::CONTENT_LENGTH,
+            header::CONTENT_TYPE,
+            header::CONTENT_ENCODING,
+            header::TRANSFER_ENCODING,
+        ] {
+            headers.remove(name);
+        }
+    }
+    let authority = next.authority()?;
+    if Some(authority) != uri.authority() {
+        // Don't leak credentials to other hosts.
+        for name in [
+            header::AUTHORIZATION,
+            header::COOKIE,
+            header::PROXY_AUTHORIZATION,
+        ] {
+            headers.remove(name);
+        }
+    }
+    // Headers injected by the policy depend on the target, so swap those
+    // injected for the previous one for those of the next.
+    policy.remove_injected_headers(&uri, &mut headers);
+    policy.apply_headers(&next, &mut headers);
+    headers.insert(header::HOST, authority.as_str().parse().ok()?);
+
+    let mut request = hyper::Request::new(
+        http_body_util::Empty::<Bytes>::new()
+            .map_err(|_| unreachable!("Infallible error"))
+            .boxed(),
+    );
+    *request.method_mut() = method;
+    *request.uri_mut() = next;
+    *request.headers_mut() = headers;
+    Some(Ok(request))
+}
+
+/// Sends `request` without following redirects.
+#[cfg(feature = "default-send-request")]
+async fn send_request_once(
+    mut request: hyper::Request<HyperOutgoingBody>,
+    use_tls: bool,
+    &OutgoingRequestConfig {
         connect_timeout,
         first_byte_timeout,
         between_bytes_timeout,
-    }: OutgoingRequestConfig,
+        ..
+    }: &OutgoingRequestConfig,
 ) -> Result<IncomingResponse, types::ErrorCode> {
     let authority = if let Some(authority) = request.uri().authority() {
         if authority.port().is_some() {
@@ -115,6 +115,7 @@ impl Drop for Ctx {
 }
 
 mod async_;
+mod policy;
 mod sync;
 
 async fn run_wasi_http(
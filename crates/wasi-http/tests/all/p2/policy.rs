@@ -0,0 +1,219 @@
+use super::*;
+use hyper::header::{self, HeaderName, HeaderValue};
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use wasmtime_wasi::p2::Pollable as _;
+use wasmtime_wasi_http::bindings::http::outgoing_handler::Host as _;
+use wasmtime_wasi_http::bindings::http::types::Method;
+use wasmtime_wasi_http::types::HostOutgoingRequest;
+use wasmtime_wasi_http::{OutgoingPolicy, WasiHttpImpl};
+
+fn new_ctx(policy: OutgoingPolicy, send_request: Option<RequestSender>) -> Ctx {
+    let mut http = WasiHttpCtx::new();
+    http.set_outgoing_policy(policy);
+    Ctx {
+        table: ResourceTable::new(),
+        wasi: WasiCtx::builder().build(),
+        http,
+        stdout: MemoryOutputPipe::new(4096),
+        stderr: MemoryOutputPipe::new(4096),
+        send_request,
+        rejected_authority: None,
+    }
+}
+
+async fn get(
+    ctx: &mut Ctx,
+    authority: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+) -> Result<Result<IncomingResponse, ErrorCode>> {
+    let mut fields = http::HeaderMap::new();
+    for (name, value) in headers {
+        fields.append(HeaderName::from_bytes(name.as_bytes())?, value.parse()?);
+    }
+    let request = ctx.table.push(HostOutgoingRequest {
+        method: Method::Get,
+        scheme: Some(Scheme::Http),
+        authority: Some(authority.to_string()),
+        path_with_query: Some(path.to_string()),
+        headers: fields,
+        body: None,
+    })?;
+    let future = match WasiHttpImpl(&mut *ctx).handle(request, None) {
+        Ok(future) => future,
+        Err(e) => return Ok(Err(e.downcast()?)),
+    };
+    let mut future = ctx.table.delete(future)?;
+    future.ready().await;
+    future.unwrap_ready()
+}
+
+fn denied(result: Result<IncomingResponse, ErrorCode>) -> bool {
+    matches!(result, Err(ErrorCode::HttpRequestDenied))
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn outgoing_policy_filters_requests() -> Result<()> {
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let send_request = {
+        let sent = sent.clone();
+        Arc::new(
+            move |request: hyper::Request<HyperOutgoingBody>,
+                  OutgoingRequestConfig {
+                      between_bytes_timeout,
+                      ..
+                  }| {
+                sent.lock().unwrap().push(request.into_parts().0);
+                HostFutureIncomingResponse::ready(Ok(Ok(IncomingResponse {
+                    resp: hyper::Response::new(Empty::new().map_err(|x| match x {}).boxed()),
+                    worker: None,
+                    between_bytes_timeout,
+                })))
+            },
+        ) as RequestSender
+    };
+
+    let mut policy = OutgoingPolicy::new();
+    policy
+        .allow("http://*.example.com")?
+        .deny("blocked.example.com")?
+        .inject_header(
+            HeaderName::from_static("x-tenant"),
+            HeaderValue::from_static("acme"),
+        )
+        .strip_header(HeaderName::from_static("x-secret"))
+        .max_body_size(4)
+        .max_requests(2);
+    let mut ctx = new_ctx(policy, Some(send_request));
+
+    let headers = [("x-secret", "hunter2"), ("x-tenant", "evil")];
+    get(&mut ctx, "api.example.com", "/", &headers).await??;
+    {
+        let sent = sent.lock().unwrap();
+        let headers = &sent[0].headers;
+        assert_eq!(sent[0].uri.host(), Some("api.example.com"));
+        assert!(!headers.contains_key("x-secret"));
+        let tenant = headers.get_all("x-tenant").iter().collect::<Vec<_>>();
+        assert_eq!(tenant, ["acme"]);
+    }
+
+    // Denied hosts, including the parent of an allowed wildcard, don't count
+    // towards the request limit.
+    assert!(denied(
+        get(&mut ctx, "blocked.example.com", "/", &[]).await?
+    ));
+    assert!(denied(get(&mut ctx, "example.com", "/", &[]).await?));
+    assert!(denied(get(&mut ctx, "example.org", "/", &[]).await?));
+
+    let result = get(
+        &mut ctx,
+        "api.example.com",
+        "/",
+        &[("content-length", "10")],
+    )
+    .await?;
+    assert!(
+        matches!(result, Err(ErrorCode::HttpRequestBodySize(Some(10)))),
+        "{result:?}"
+    );
+
+    get(&mut ctx, "api.example.com", "/", &[]).await??;
+    assert!(denied(get(&mut ctx, "api.example.com", "/", &[]).await?));
+    assert_eq!(sent.lock().unwrap().len(), 2);
+    Ok(())
+}
+
+/// Starts a server which redirects `/start` to `/middle` to `/end`, and
+/// `/away` and `/cross` to other hosts. `/echo` responds with the request's
+/// `x-api-key` header.
+async fn redirect_server() -> Result<SocketAddr> {
+    let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+    let addr = listener.local_addr()?;
+    task::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let service = service_fn(move |req: hyper::Request<hyper::body::Incoming>| {
+                let (status, location) = match req.uri().path() {
+                    "/start" => (StatusCode::FOUND, Some("/middle".to_string())),
+                    "/middle" => (
+                        StatusCode::TEMPORARY_REDIRECT,
+                        Some(format!("http://{addr}/end")),
+                    ),
+                    "/away" => (
+                        StatusCode::FOUND,
+                        Some("http://denied.example.com/".to_string()),
+                    ),
+                    "/cross" => (
+                        StatusCode::FOUND,
+                        Some(format!("http://localhost:{}/echo", addr.port())),
+                    ),
+                    _ => (StatusCode::OK, None),
+                };
+                let body = match req.uri().path() {
+                    "/echo" => Bytes::copy_from_slice(
+                        req.headers()
+                            .get("x-api-key")
+                            .map_or(&b""[..], |v| v.as_bytes()),
+                    ),
+                    _ => Bytes::from_static(b"done"),
+                };
+                let mut response = hyper::Response::builder().status(status);
+                if let Some(location) = location {
+                    response = response.header(header::LOCATION, location);
+                }
+                future::ready(response.body(body::full(body)))
+            });
+            task::spawn(http1::Builder::new().serve_connection(TokioIo::new(stream), service));
+        }
+    });
+    Ok(addr)
+}
+
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn outgoing_policy_follows_redirects() -> Result<()> {
+    let addr = redirect_server().await?;
+    let authority = addr.to_string();
+    let status = |result: Result<IncomingResponse, ErrorCode>| result.unwrap().resp.status();
+
+    // Redirects aren't followed by default.
+    let mut ctx = new_ctx(OutgoingPolicy::new(), None);
+    let result = get(&mut ctx, &authority, "/start", &[]).await?;
+    assert_eq!(status(result), StatusCode::FOUND);
+
+    let mut policy = OutgoingPolicy::new();
+    policy.allow("http://127.0.0.1")?.max_redirects(1);
+    let mut ctx = new_ctx(policy.clone(), None);
+    let result = get(&mut ctx, &authority, "/start", &[]).await?;
+    assert_eq!(status(result), StatusCode::TEMPORARY_REDIRECT);
+
+    policy.max_redirects(2);
+    let mut ctx = new_ctx(policy, None);
+    let response = get(&mut ctx, &authority, "/start", &[]).await??;
+    assert_eq!(response.resp.status(), StatusCode::OK);
+    let body = response.resp.into_body().collect().await?.to_bytes();
+    assert_eq!(body, "done");
+
+    // Redirect targets are subject to the policy too.
+    assert!(denied(get(&mut ctx, &authority, "/away", &[]).await?));
+
+    // Headers injected for one host don't follow redirects to another.
+    let mut policy = OutgoingPolicy::new();
+    policy
+        .allow("http://127.0.0.1")?
+        .allow("http://localhost")?
+        .inject_header_for(
+            "127.0.0.1",
+            HeaderName::from_static("x-api-key"),
+            HeaderValue::from_static("secret"),
+        )?
+        .max_redirects(1);
+    let mut ctx = new_ctx(policy, None);
+    let response = get(&mut ctx, &authority, "/echo", &[]).await??;
+    let body = response.resp.into_body().collect().await?.to_bytes();
+    assert_eq!(body, "secret");
+    let response = get(&mut ctx, &authority, "/cross", &[]).await??;
+    assert_eq!(response.resp.status(), StatusCode::OK);
+    let body = response.resp.into_body().collect().await?.to_bytes();
+    assert_eq!(body, "");
+    Ok(())
+}
@@ -0,0 +1,126 @@
+//! Implementation of `wasi:keyvalue@0.2.0-draft`, the version of the
+//! interfaces preceding `wasi:keyvalue@0.2.0-draft2`, so that components built
+//! against it keep working.
+//!
+//! Buckets opened through either version are backed by the same
+//! [`KeyValueStore`](crate::KeyValueStore)s, and each method here forwards to
+//! its `draft2` counterpart.
+
+use crate::keyvalue as draft2;
+use crate::{Bucket, Error, HasWasiKeyValue, WasiKeyValue};
+use anyhow::Result;
+use wasmtime::component::Resource;
+
+mod generated {
+    wasmtime::component::bindgen!({
+        path: "src/draft/wit",
+        world: "wasi:keyvalue/imports",
+        imports: { default: trappable },
+        with: {
+            "wasi:keyvalue/store/bucket": crate::Bucket,
+        },
+        trappable_error_type: {
+            "wasi:keyvalue/store/error" => crate::Error,
+        },
+    });
+}
+
+use self::generated::wasi::keyvalue;
+
+impl keyvalue::store::Host for WasiKeyValue<'_> {
+    fn open(&mut self, identifier: String) -> Result<Resource<Bucket>, Error> {
+        draft2::store::Host::open(self, identifier)
+    }
+
+    fn convert_error(&mut self, err: Error) -> Result<keyvalue::store::Error> {
+        Ok(match err {
+            Error::NoSuchStore => keyvalue::store::Error::NoSuchStore,
+            Error::AccessDenied => keyvalue::store::Error::AccessDenied,
+            Error::Other(e) => keyvalue::store::Error::Other(e),
+        })
+    }
+}
+
+impl keyvalue::store::HostBucket for WasiKeyValue<'_> {
+    fn get(&mut self, bucket: Resource<Bucket>, key: String) -> Result<Option<Vec<u8>>, Error> {
+        draft2::store::HostBucket::get(self, bucket, key)
+    }
+
+    fn set(&mut self, bucket: Resource<Bucket>, key: String, value: Vec<u8>) -> Result<(), Error> {
+        draft2::store::HostBucket::set(self, bucket, key, value)
+    }
+
+    fn delete(&mut self, bucket: Resource<Bucket>, key: String) -> Result<(), Error> {
+        draft2::store::HostBucket::delete(self, bucket, key)
+    }
+
+    fn exists(&mut self, bucket: Resource<Bucket>, key: String) -> Result<bool, Error> {
+        draft2::store::HostBucket::exists(self, bucket, key)
+    }
+
+    fn list_keys(
+        &mut self,
+        bucket: Resource<Bucket>,
+        cursor: Option<u64>,
+    ) -> Result<keyvalue::store::KeyResponse, Error> {
+        // All keys are returned at once, so a cursor is only ever one the
+        // guest made up. Treat it as the number of keys already seen.
+        let mut keys = draft2::store::HostBucket::list_keys(self, bucket, None)?.keys;
+        let seen = cursor.map_or(0, |c| usize::try_from(c).unwrap_or(usize::MAX));
+        keys.drain(..seen.min(keys.len()));
+        Ok(keyvalue::store::KeyResponse { keys, cursor: None })
+    }
+
+    fn drop(&mut self, bucket: Resource<Bucket>) -> Result<()> {
+        draft2::store::HostBucket::drop(self, bucket)
+    }
+}
+
+impl keyvalue::atomics::Host for WasiKeyValue<'_> {
+    fn increment(
+        &mut self,
+        bucket: Resource<Bucket>,
+        key: String,
+        delta: u64,
+    ) -> Result<u64, Error> {
+        let delta =
+            i64::try_from(delta).map_err(|_| Error::Other("integer overflow".to_string()))?;
+        let value = draft2::atomics::Host::increment(self, bucket, key, delta)?;
+        u64::try_from(value).map_err(|_| Error::Other("value is negative".to_string()))
+    }
+}
+
+impl keyvalue::batch::Host for WasiKeyValue<'_> {
+    fn get_many(
+        &mut self,
+        bucket: Resource<Bucket>,
+        keys: Vec<String>,
+    ) -> Result<Vec<Option<(String, Vec<u8>)>>, Error> {
+        Ok(draft2::batch::Host::get_many(self, bucket, keys)?
+            .into_iter()
+            .map(|(key, value)| Some((key, value?)))
+            .collect())
+    }
+
+    fn set_many(
+        &mut self,
+        bucket: Resource<Bucket>,
+        key_values: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), Error> {
+        draft2::batch::Host::set_many(self, bucket, key_values)
+    }
+
+    fn delete_many(&mut self, bucket: Resource<Bucket>, keys: Vec<String>) -> Result<(), Error> {
+        draft2::batch::Host::delete_many(self, bucket, keys)
+    }
+}
+
+pub(crate) fn add_to_linker<T: Send + 'static>(
+    l: &mut wasmtime::component::Linker<T>,
+    f: fn(&mut T) -> WasiKeyValue<'_>,
+) -> Result<()> {
+    keyvalue::store::add_to_linker::<_, HasWasiKeyValue>(l, f)?;
+    keyvalue::atomics::add_to_linker::<_, HasWasiKeyValue>(l, f)?;
+    keyvalue::batch::add_to_linker::<_, HasWasiKeyValue>(l, f)?;
+    Ok(())
+}
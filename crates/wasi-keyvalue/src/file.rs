@@ -0,0 +1,343 @@
+//! A [`KeyValueStore`] persisted to a file on the host.
+
+use crate::{Error, KeyValueStore};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Header written at the start of every store file, including a version byte.
+const MAGIC: &[u8; 8] = b"wkvlog\0\x01";
+
+const RECORD_SET: u8 = 0;
+const RECORD_DELETE: u8 = 1;
+/// A count followed by that many set or delete records, applied atomically.
+const RECORD_BATCH: u8 = 2;
+
+/// A [`KeyValueStore`] persisted to a single file on the host.
+///
+/// Every modification, or batch of modifications, is appended to the file as a
+/// single record and synced to disk before being acknowledged, and the file is
+/// replayed into memory when the store is opened, so reads never touch the
+/// file.
+///
+/// A record that fails to be written, for example because the disk is full, is
+/// truncated away again so that later records aren't lost behind it; if even
+/// that fails, the store refuses further modifications. A trailing record that
+/// was only partially written because the process was killed mid-write is
+/// discarded when the store is next opened, but any other invalid record makes
+/// opening the store fail rather than losing the records after it.
+/// Opening the store also compacts the file if most of it is taken up by
+/// overwritten or deleted entries.
+///
+/// No locking is performed on the file itself, so only one `FileStore`, in one
+/// process, should have a given file open at a time.
+pub struct FileStore {
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    data: BTreeMap<String, Vec<u8>>,
+    file: File,
+    /// Length of the file up to the end of the last complete record.
+    len: u64,
+    /// Set if a failed write couldn't be rolled back, leaving a partial
+    /// record at the end of the file.
+    poisoned: bool,
+}
+
+impl FileStore {
+    /// Opens the store persisted at `path`, creating an empty one if the file
+    /// doesn't exist.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<FileStore> {
+        let path = path.as_ref();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+
+        if contents.is_empty() {
+            file.write_all(MAGIC)?;
+            file.sync_data()?;
+            return FileStore::new(BTreeMap::new(), file);
+        }
+        if !contents.starts_with(MAGIC) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("`{}` is not a key-value store file", path.display()),
+            ));
+        }
+
+        let (data, len) = replay(&contents[MAGIC.len()..]).map_err(|offset| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "`{}` has an invalid record at offset {}",
+                    path.display(),
+                    MAGIC.len() + offset
+                ),
+            )
+        })?;
+        let len = MAGIC.len() + len;
+        let live = MAGIC.len()
+            + data
+                .iter()
+                .map(|(k, v)| record_len(k, Some(v)))
+                .sum::<usize>();
+        if len > 2 * live {
+            let file = compact(path, &data)?;
+            return FileStore::new(data, file);
+        }
+        if len < contents.len() {
+            file.set_len(len as u64)?;
+        }
+        FileStore::new(data, file)
+    }
+
+    fn new(data: BTreeMap<String, Vec<u8>>, file: File) -> io::Result<FileStore> {
+        let len = file.metadata()?.len();
+        Ok(FileStore {
+            inner: Mutex::new(Inner {
+                data,
+                file,
+                len,
+                poisoned: false,
+            }),
+        })
+    }
+
+    fn append(&self, key: &str, value: Option<Vec<u8>>) -> Result<(), Error> {
+        self.inner
+            .lock()
+            .unwrap()
+            .write(vec![(key.to_string(), value)])
+    }
+}
+
+impl Inner {
+    /// Appends `writes` to the file as a single record, then applies them.
+    fn write(&mut self, mut writes: Vec<(String, Option<Vec<u8>>)>) -> Result<(), Error> {
+        writes.retain(|(key, value)| value.is_some() || self.data.contains_key(key));
+        let mut record = Vec::new();
+        match &writes[..] {
+            [] => return Ok(()),
+            [(key, value)] => encode(&mut record, key, value.as_deref())?,
+            _ => {
+                let count = u32::try_from(writes.len())
+                    .map_err(|_| Error::Other("too many entries in batch".to_string()))?;
+                record.push(RECORD_BATCH);
+                record.extend_from_slice(&count.to_le_bytes());
+                for (key, value) in &writes {
+                    encode(&mut record, key, value.as_deref())?;
+                }
+            }
+        }
+        if self.poisoned {
+            return Err(Error::Other(
+                "store is unusable after a failed write".to_string(),
+            ));
+        }
+        if let Err(err) = self
+            .file
+            .write_all(&record)
+            .and_then(|()| self.file.sync_data())
+        {
+            // Drop whatever part of the record made it to the file, as replay
+            // stops at the first invalid record and would discard every record
+            // appended after it.
+            if self.file.set_len(self.len).is_err() {
+                self.poisoned = true;
+            }
+            return Err(err.into());
+        }
+        self.len += record.len() as u64;
+        for (key, value) in writes {
+            match value {
+                Some(value) => self.data.insert(key, value),
+                None => self.data.remove(&key),
+            };
+        }
+        Ok(())
+    }
+}
+
+impl KeyValueStore for FileStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.inner.lock().unwrap().data.get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: Vec<u8>) -> Result<(), Error> {
+        self.append(key, Some(value))
+    }
+
+    fn delete(&self, key: &str) -> Result<(), Error> {
+        self.append(key, None)
+    }
+
+    fn exists(&self, key: &str) -> Result<bool, Error> {
+        Ok(self.inner.lock().unwrap().data.contains_key(key))
+    }
+
+    fn list_keys(&self) -> Result<Vec<String>, Error> {
+        Ok(self.inner.lock().unwrap().data.keys().cloned().collect())
+    }
+
+    fn get_many(&self, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>, Error> {
+        let inner = self.inner.lock().unwrap();
+        Ok(keys
+            .iter()
+            .map(|key| inner.data.get(key).cloned())
+            .collect())
+    }
+
+    fn write_many(&self, writes: Vec<(String, Option<Vec<u8>>)>) -> Result<(), Error> {
+        self.inner.lock().unwrap().write(writes)
+    }
+
+    fn compare_and_swap(
+        &self,
+        key: &str,
+        current: Option<&[u8]>,
+        value: Vec<u8>,
+    ) -> Result<bool, Error> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.data.get(key).map(|v| &v[..]) != current {
+            return Ok(false);
+        }
+        inner.write(vec![(key.to_string(), Some(value))])?;
+        Ok(true)
+    }
+}
+
+/// Replays the records in `log`, returning the resulting data and the length
+/// of the prefix of `log` made up of complete records.
+///
+/// Only the last record may be incomplete. If any record is invalid, the
+/// offset of that record is returned as the error instead.
+fn replay(mut log: &[u8]) -> Result<(BTreeMap<String, Vec<u8>>, usize), usize> {
+    let mut data = BTreeMap::new();
+    let mut valid = 0;
+    while !log.is_empty() {
+        let (entries, rest) = match record(log) {
+            Ok(record) => record,
+            Err(BadRecord::Truncated) => break,
+            Err(BadRecord::Invalid) => return Err(valid),
+        };
+        for (key, value) in entries {
+            match value {
+                Some(value) => data.insert(key.to_string(), value.to_vec()),
+                None => data.remove(key),
+            };
+        }
+        valid += log.len() - rest.len();
+        log = rest;
+    }
+    Ok((data, valid))
+}
+
+/// A single set, with a value, or delete, without one, parsed from the log.
+type Entry<'a> = (&'a str, Option<&'a [u8]>);
+
+/// Why a record couldn't be parsed.
+enum BadRecord {
+    /// The log ends part way through the record, as happens when the process
+    /// is killed while writing it.
+    Truncated,
+    /// The record is malformed.
+    Invalid,
+}
+
+/// Splits a complete record off the front of `bytes`.
+fn record(bytes: &[u8]) -> Result<(Vec<Entry<'_>>, &[u8]), BadRecord> {
+    let (op, rest) = bytes.split_first().ok_or(BadRecord::Truncated)?;
+    if *op != RECORD_BATCH {
+        let (entry, rest) = entry(*op, rest)?;
+        return Ok((vec![entry], rest));
+    }
+    let (count, mut rest) = rest.split_first_chunk::<4>().ok_or(BadRecord::Truncated)?;
+    let mut entries = Vec::new();
+    for _ in 0..u32::from_le_bytes(*count) {
+        let (op, tail) = rest.split_first().ok_or(BadRecord::Truncated)?;
+        let (entry, tail) = entry(*op, tail)?;
+        entries.push(entry);
+        rest = tail;
+    }
+    Ok((entries, rest))
+}
+
+fn entry(op: u8, bytes: &[u8]) -> Result<(Entry<'_>, &[u8]), BadRecord> {
+    if op != RECORD_SET && op != RECORD_DELETE {
+        return Err(BadRecord::Invalid);
+    }
+    let (key, rest) = field(bytes)?;
+    let key = std::str::from_utf8(key).map_err(|_| BadRecord::Invalid)?;
+    if op == RECORD_DELETE {
+        return Ok(((key, None), rest));
+    }
+    let (value, rest) = field(rest)?;
+    Ok(((key, Some(value)), rest))
+}
+
+/// Splits a length-prefixed field off the front of `bytes`.
+fn field(bytes: &[u8]) -> Result<(&[u8], &[u8]), BadRecord> {
+    let (len, rest) = bytes.split_first_chunk::<4>().ok_or(BadRecord::Truncated)?;
+    let len = usize::try_from(u32::from_le_bytes(*len)).map_err(|_| BadRecord::Invalid)?;
+    if rest.len() < len {
+        return Err(BadRecord::Truncated);
+    }
+    Ok(rest.split_at(len))
+}
+
+fn record_len(key: &str, value: Option<&Vec<u8>>) -> usize {
+    1 + 4 + key.len() + value.map_or(0, |v| 4 + v.len())
+}
+
+fn encode(dst: &mut Vec<u8>, key: &str, value: Option<&[u8]>) -> Result<(), Error> {
+    fn push_field(dst: &mut Vec<u8>, field: &[u8]) -> Result<(), Error> {
+        let len = u32::try_from(field.len())
+            .map_err(|_| Error::Other("key or value is too large".to_string()))?;
+        dst.extend_from_slice(&len.to_le_bytes());
+        dst.extend_from_slice(field);
+        Ok(())
+    }
+
+    match value {
+        Some(value) => {
+            dst.push(RECORD_SET);
+            push_field(dst, key.as_bytes())?;
+            push_field(dst, value)?;
+        }
+        None => {
+            dst.push(RECORD_DELETE);
+            push_field(dst, key.as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Rewrites the file at `path` to contain only `data`, returning it opened for
+/// appending.
+///
+/// The new contents are written to a temporary file which is then renamed over
+/// the original, so a failure part way through leaves the original intact.
+fn compact(path: &Path, data: &BTreeMap<String, Vec<u8>>) -> io::Result<File> {
+    let mut contents = MAGIC.to_vec();
+    for (key, value) in data {
+        encode(&mut contents, key, Some(value))
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "entry is too large"))?;
+    }
+
+    let mut tmp = PathBuf::from(path);
+    tmp.as_mut_os_string().push(".compact");
+    let mut file = File::create(&tmp)?;
+    file.write_all(&contents)?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&tmp, path)?;
+
+    OpenOptions::new().append(true).open(path)
+}
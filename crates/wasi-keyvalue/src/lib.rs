@@ -4,8 +4,16 @@
 //! API. With this crate, the runtime can run components that call APIs in
 //! [wasi-keyvalue] and provide components with access to key-value storages.
 //!
-//! Currently supported storage backends:
-//! * In-Memory (empty identifier)
+//! Both `wasi:keyvalue@0.2.0-draft2` and the preceding
+//! `wasi:keyvalue@0.2.0-draft` are implemented.
+//!
+//! Guests select a store by the identifier passed to `wasi:keyvalue/store.open`,
+//! and each identifier is backed by a [`KeyValueStore`] registered with
+//! [`WasiKeyValueCtxBuilder::store`]. Provided storage backends are:
+//! * [`InMemoryStore`], a new one of which, holding the data set with
+//!   [`WasiKeyValueCtxBuilder::in_memory_data`], is opened each time the
+//!   empty identifier is opened, unless another store is registered for it.
+//! * [`FileStore`], which persists data to a file on the host.
 //!
 //! # Examples
 //!
@@ -73,6 +81,7 @@ mod generated {
         imports: { default: trappable },
         with: {
             "wasi:keyvalue/store/bucket": crate::Bucket,
+            "wasi:keyvalue/atomics/cas": crate::Cas,
         },
         trappable_error_type: {
             "wasi:keyvalue/store/error" => crate::Error,
@@ -82,31 +91,248 @@ mod generated {
 
 use self::generated::wasi::keyvalue;
 use anyhow::Result;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
 use wasmtime::component::{HasData, Resource, ResourceTable, ResourceTableError};
 
-#[doc(hidden)]
+mod draft;
+mod file;
+
+pub use self::file::FileStore;
+
+/// Errors returned by a [`KeyValueStore`], which are reported to the guest.
+#[derive(Debug)]
 pub enum Error {
+    /// The host does not recognize the store identifier requested.
     NoSuchStore,
+    /// The requesting component does not have access to the specified store.
     AccessDenied,
+    /// Some implementation-specific error has occurred.
     Other(String),
 }
 
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoSuchStore => f.write_str("no such store"),
+            Error::AccessDenied => f.write_str("access denied"),
+            Error::Other(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 impl From<ResourceTableError> for Error {
     fn from(err: ResourceTableError) -> Self {
         Self::Other(err.to_string())
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Other(err.to_string())
+    }
+}
+
+impl From<Error> for keyvalue::store::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::NoSuchStore => Self::NoSuchStore,
+            Error::AccessDenied => Self::AccessDenied,
+            Error::Other(e) => Self::Other(e),
+        }
+    }
+}
+
+/// Storage backing the buckets guests open with `wasi:keyvalue/store.open`.
+///
+/// A store is shared by every bucket opened with its identifier, including
+/// buckets in different [`wasmtime::Store`]s using clones of the same
+/// [`WasiKeyValueCtx`], so implementations must synchronize internally. In
+/// particular the batch and compare-and-swap methods must be atomic with
+/// respect to all other operations on the store, as guests build counters and
+/// locks on top of them.
+pub trait KeyValueStore: Send + Sync + 'static {
+    /// Returns the value associated with `key`, if any.
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error>;
+
+    /// Associates `value` with `key`, replacing any existing value.
+    fn set(&self, key: &str, value: Vec<u8>) -> Result<(), Error>;
+
+    /// Removes `key` and its value, doing nothing if `key` doesn't exist.
+    fn delete(&self, key: &str) -> Result<(), Error>;
+
+    /// Returns whether `key` has a value associated with it.
+    fn exists(&self, key: &str) -> Result<bool, Error> {
+        Ok(self.get(key)?.is_some())
+    }
+
+    /// Returns all keys in the store, in any order.
+    fn list_keys(&self) -> Result<Vec<String>, Error>;
+
+    /// Returns the values associated with each of `keys`, read atomically.
+    fn get_many(&self, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>, Error>;
+
+    /// Atomically applies all of `writes`, where a value of `None` deletes
+    /// the key.
+    fn write_many(&self, writes: Vec<(String, Option<Vec<u8>>)>) -> Result<(), Error>;
+
+    /// Atomically associates `value` with `key` if the key's value is still
+    /// `current`, where `None` means the key doesn't exist.
+    ///
+    /// Returns whether the value was replaced.
+    fn compare_and_swap(
+        &self,
+        key: &str,
+        current: Option<&[u8]>,
+        value: Vec<u8>,
+    ) -> Result<bool, Error>;
+
+    /// Atomically adds `delta` to the value associated with `key`, returning
+    /// the new value.
+    ///
+    /// Values are stored as decimal strings, and a key which doesn't exist is
+    /// treated as having a value of 0. The default implementation retries
+    /// [`KeyValueStore::compare_and_swap`] until it succeeds.
+    fn increment(&self, key: &str, delta: i64) -> Result<i64, Error> {
+        loop {
+            let current = self.get(key)?;
+            let new = add_delta(current.as_deref(), delta)?;
+            if self.compare_and_swap(key, current.as_deref(), new.to_string().into_bytes())? {
+                return Ok(new);
+            }
+        }
+    }
+}
+
+fn add_delta(current: Option<&[u8]>, delta: i64) -> Result<i64, Error> {
+    let current = match current {
+        Some(value) => std::str::from_utf8(value)
+            .map_err(|e| Error::Other(e.to_string()))?
+            .parse::<i64>()
+            .map_err(|e| Error::Other(e.to_string()))?,
+        None => 0,
+    };
+    current
+        .checked_add(delta)
+        .ok_or_else(|| Error::Other("integer overflow".to_string()))
+}
+
+/// A [`KeyValueStore`] which keeps its data in memory.
+#[derive(Default)]
+pub struct InMemoryStore {
+    data: Mutex<BTreeMap<String, Vec<u8>>>,
+}
+
+impl InMemoryStore {
+    /// Creates a new, empty store.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for InMemoryStore
+where
+    K: Into<String>,
+    V: Into<Vec<u8>>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            data: Mutex::new(
+                iter.into_iter()
+                    .map(|(k, v)| (k.into(), v.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl KeyValueStore for InMemoryStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.data.lock().unwrap().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: Vec<u8>) -> Result<(), Error> {
+        self.data.lock().unwrap().insert(key.to_string(), value);
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), Error> {
+        self.data.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    fn exists(&self, key: &str) -> Result<bool, Error> {
+        Ok(self.data.lock().unwrap().contains_key(key))
+    }
+
+    fn list_keys(&self) -> Result<Vec<String>, Error> {
+        Ok(self.data.lock().unwrap().keys().cloned().collect())
+    }
+
+    fn get_many(&self, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>, Error> {
+        let data = self.data.lock().unwrap();
+        Ok(keys.iter().map(|key| data.get(key).cloned()).collect())
+    }
+
+    fn write_many(&self, writes: Vec<(String, Option<Vec<u8>>)>) -> Result<(), Error> {
+        let mut data = self.data.lock().unwrap();
+        for (key, value) in writes {
+            match value {
+                Some(value) => data.insert(key, value),
+                None => data.remove(&key),
+            };
+        }
+        Ok(())
+    }
+
+    fn compare_and_swap(
+        &self,
+        key: &str,
+        current: Option<&[u8]>,
+        value: Vec<u8>,
+    ) -> Result<bool, Error> {
+        let mut data = self.data.lock().unwrap();
+        if data.get(key).map(|v| &v[..]) != current {
+            return Ok(false);
+        }
+        data.insert(key.to_string(), value);
+        Ok(true)
+    }
+
+    fn increment(&self, key: &str, delta: i64) -> Result<i64, Error> {
+        let mut data = self.data.lock().unwrap();
+        let new = add_delta(data.get(key).map(|v| &v[..]), delta)?;
+        data.insert(key.to_string(), new.to_string().into_bytes());
+        Ok(new)
+    }
+}
+
 #[doc(hidden)]
 pub struct Bucket {
-    in_memory_data: HashMap<String, Vec<u8>>,
+    store: Arc<dyn KeyValueStore>,
+}
+
+/// A compare-and-swap operation started by a guest.
+///
+/// This records the value of the key when the operation was started, which
+/// the key must still have for the swap to succeed. Values rather than
+/// versions are compared, so a swap succeeds if the key was changed and then
+/// changed back in the meantime.
+#[doc(hidden)]
+pub struct Cas {
+    store: Arc<dyn KeyValueStore>,
+    key: String,
+    current: Option<Vec<u8>>,
 }
 
 /// Builder-style structure used to create a [`WasiKeyValueCtx`].
 #[derive(Default)]
 pub struct WasiKeyValueCtxBuilder {
     in_memory_data: HashMap<String, Vec<u8>>,
+    stores: HashMap<String, Arc<dyn KeyValueStore>>,
 }
 
 impl WasiKeyValueCtxBuilder {
@@ -116,6 +342,12 @@ impl WasiKeyValueCtxBuilder {
     }
 
     /// Preset data for the In-Memory provider.
+    ///
+    /// Each bucket opened with the empty identifier gets its own copy of this
+    /// data, so changes made through one aren't seen by others. This is
+    /// ignored if another store is registered for the empty identifier with
+    /// [`WasiKeyValueCtxBuilder::store`], which could be an [`InMemoryStore`]
+    /// for the data to be shared instead.
     pub fn in_memory_data<I, K, V>(mut self, data: I) -> Self
     where
         I: IntoIterator<Item = (K, V)>,
@@ -129,17 +361,39 @@ impl WasiKeyValueCtxBuilder {
         self
     }
 
+    /// Makes `store` available to guests opening `identifier`, replacing any
+    /// store previously registered for it.
+    pub fn store(self, identifier: impl Into<String>, store: impl KeyValueStore) -> Self {
+        self.shared_store(identifier, Arc::new(store))
+    }
+
+    /// Like [`WasiKeyValueCtxBuilder::store`], but for a store which may also
+    /// be used by other contexts.
+    pub fn shared_store(
+        mut self,
+        identifier: impl Into<String>,
+        store: Arc<dyn KeyValueStore>,
+    ) -> Self {
+        self.stores.insert(identifier.into(), store);
+        self
+    }
+
     /// Uses the configured context so far to construct the final [`WasiKeyValueCtx`].
     pub fn build(self) -> WasiKeyValueCtx {
         WasiKeyValueCtx {
-            in_memory_data: self.in_memory_data,
+            in_memory_data: Arc::new(self.in_memory_data),
+            stores: self.stores,
         }
     }
 }
 
 /// Capture the state necessary for use in the `wasi-keyvalue` API implementation.
+///
+/// Clones of a context share the same stores.
+#[derive(Clone)]
 pub struct WasiKeyValueCtx {
-    in_memory_data: HashMap<String, Vec<u8>>,
+    in_memory_data: Arc<HashMap<String, Vec<u8>>>,
+    stores: HashMap<String, Arc<dyn KeyValueStore>>,
 }
 
 impl WasiKeyValueCtx {
@@ -147,6 +401,20 @@ impl WasiKeyValueCtx {
     pub fn builder() -> WasiKeyValueCtxBuilder {
         WasiKeyValueCtxBuilder::new()
     }
+
+    fn open(&self, identifier: &str) -> Result<Arc<dyn KeyValueStore>, Error> {
+        if let Some(store) = self.stores.get(identifier) {
+            return Ok(store.clone());
+        }
+        if !identifier.is_empty() {
+            return Err(Error::NoSuchStore);
+        }
+        Ok(Arc::new(InMemoryStore::from_iter(
+            self.in_memory_data
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone())),
+        )))
+    }
 }
 
 /// A wrapper capturing the needed internal `wasi-keyvalue` state.
@@ -164,59 +432,50 @@ impl<'a> WasiKeyValue<'a> {
 
 impl keyvalue::store::Host for WasiKeyValue<'_> {
     fn open(&mut self, identifier: String) -> Result<Resource<Bucket>, Error> {
-        match identifier.as_str() {
-            "" => Ok(self.table.push(Bucket {
-                in_memory_data: self.ctx.in_memory_data.clone(),
-            })?),
-            _ => Err(Error::NoSuchStore),
-        }
+        let store = self.ctx.open(&identifier)?;
+        Ok(self.table.push(Bucket { store })?)
     }
 
     fn convert_error(&mut self, err: Error) -> Result<keyvalue::store::Error> {
-        match err {
-            Error::NoSuchStore => Ok(keyvalue::store::Error::NoSuchStore),
-            Error::AccessDenied => Ok(keyvalue::store::Error::AccessDenied),
-            Error::Other(e) => Ok(keyvalue::store::Error::Other(e)),
-        }
+        Ok(err.into())
     }
 }
 
 impl keyvalue::store::HostBucket for WasiKeyValue<'_> {
     fn get(&mut self, bucket: Resource<Bucket>, key: String) -> Result<Option<Vec<u8>>, Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        Ok(bucket.in_memory_data.get(&key).cloned())
+        let bucket = self.table.get(&bucket)?;
+        bucket.store.get(&key)
     }
 
     fn set(&mut self, bucket: Resource<Bucket>, key: String, value: Vec<u8>) -> Result<(), Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        bucket.in_memory_data.insert(key, value);
-        Ok(())
+        let bucket = self.table.get(&bucket)?;
+        bucket.store.set(&key, value)
     }
 
     fn delete(&mut self, bucket: Resource<Bucket>, key: String) -> Result<(), Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        bucket.in_memory_data.remove(&key);
-        Ok(())
+        let bucket = self.table.get(&bucket)?;
+        bucket.store.delete(&key)
     }
 
     fn exists(&mut self, bucket: Resource<Bucket>, key: String) -> Result<bool, Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        Ok(bucket.in_memory_data.contains_key(&key))
+        let bucket = self.table.get(&bucket)?;
+        bucket.store.exists(&key)
     }
 
     fn list_keys(
         &mut self,
         bucket: Resource<Bucket>,
-        cursor: Option<u64>,
+        cursor: Option<String>,
     ) -> Result<keyvalue::store::KeyResponse, Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        let keys: Vec<String> = bucket.in_memory_data.keys().cloned().collect();
-        let cursor = cursor.unwrap_or(0) as usize;
-        let keys_slice = &keys[cursor..];
-        Ok(keyvalue::store::KeyResponse {
-            keys: keys_slice.to_vec(),
-            cursor: None,
-        })
+        let bucket = self.table.get(&bucket)?;
+        let mut keys = bucket.store.list_keys()?;
+        keys.sort_unstable();
+        // All keys are returned at once, so a cursor is only ever one the
+        // guest made up. Treat it as the last key already seen.
+        if let Some(cursor) = cursor {
+            keys.retain(|key| *key > cursor);
+        }
+        Ok(keyvalue::store::KeyResponse { keys, cursor: None })
     }
 
     fn drop(&mut self, bucket: Resource<Bucket>) -> Result<()> {
@@ -230,20 +489,58 @@ impl keyvalue::atomics::Host for WasiKeyValue<'_> {
         &mut self,
         bucket: Resource<Bucket>,
         key: String,
-        delta: u64,
-    ) -> Result<u64, Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        let value = bucket
-            .in_memory_data
-            .entry(key.clone())
-            .or_insert("0".to_string().into_bytes());
-        let current_value = String::from_utf8(value.clone())
-            .map_err(|e| Error::Other(e.to_string()))?
-            .parse::<u64>()
-            .map_err(|e| Error::Other(e.to_string()))?;
-        let new_value = current_value + delta;
-        *value = new_value.to_string().into_bytes();
-        Ok(new_value)
+        delta: i64,
+    ) -> Result<i64, Error> {
+        let bucket = self.table.get(&bucket)?;
+        bucket.store.increment(&key, delta)
+    }
+
+    fn swap(
+        &mut self,
+        cas: Resource<Cas>,
+        value: Vec<u8>,
+    ) -> Result<Result<(), keyvalue::atomics::CasError>> {
+        let cas = self.table.delete(cas)?;
+        let result = cas
+            .store
+            .compare_and_swap(&cas.key, cas.current.as_deref(), value)
+            .and_then(|swapped| {
+                if swapped {
+                    return Ok(None);
+                }
+                let current = cas.store.get(&cas.key)?;
+                Ok(Some(Cas { current, ..cas }))
+            });
+        match result {
+            Ok(None) => Ok(Ok(())),
+            Ok(Some(cas)) => Ok(Err(keyvalue::atomics::CasError::CasFailed(
+                self.table.push(cas)?,
+            ))),
+            Err(e) => Ok(Err(keyvalue::atomics::CasError::StoreError(e.into()))),
+        }
+    }
+}
+
+impl keyvalue::atomics::HostCas for WasiKeyValue<'_> {
+    fn new(&mut self, bucket: Resource<Bucket>, key: String) -> Result<Resource<Cas>, Error> {
+        let bucket = self.table.get(&bucket)?;
+        let store = bucket.store.clone();
+        let current = store.get(&key)?;
+        Ok(self.table.push(Cas {
+            store,
+            key,
+            current,
+        })?)
+    }
+
+    fn current(&mut self, cas: Resource<Cas>) -> Result<Option<Vec<u8>>, Error> {
+        let cas = self.table.get(&cas)?;
+        Ok(cas.current.clone())
+    }
+
+    fn drop(&mut self, cas: Resource<Cas>) -> Result<()> {
+        self.table.delete(cas)?;
+        Ok(())
     }
 }
 
@@ -252,17 +549,10 @@ impl keyvalue::batch::Host for WasiKeyValue<'_> {
         &mut self,
         bucket: Resource<Bucket>,
         keys: Vec<String>,
-    ) -> Result<Vec<Option<(String, Vec<u8>)>>, Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        Ok(keys
-            .into_iter()
-            .map(|key| {
-                bucket
-                    .in_memory_data
-                    .get(&key)
-                    .map(|value| (key.clone(), value.clone()))
-            })
-            .collect())
+    ) -> Result<Vec<(String, Option<Vec<u8>>)>, Error> {
+        let bucket = self.table.get(&bucket)?;
+        let values = bucket.store.get_many(&keys)?;
+        Ok(keys.into_iter().zip(values).collect())
     }
 
     fn set_many(
@@ -270,23 +560,27 @@ impl keyvalue::batch::Host for WasiKeyValue<'_> {
         bucket: Resource<Bucket>,
         key_values: Vec<(String, Vec<u8>)>,
     ) -> Result<(), Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        for (key, value) in key_values {
-            bucket.in_memory_data.insert(key, value);
-        }
-        Ok(())
+        let bucket = self.table.get(&bucket)?;
+        bucket.store.write_many(
+            key_values
+                .into_iter()
+                .map(|(key, value)| (key, Some(value)))
+                .collect(),
+        )
     }
 
     fn delete_many(&mut self, bucket: Resource<Bucket>, keys: Vec<String>) -> Result<(), Error> {
-        let bucket = self.table.get_mut(&bucket)?;
-        for key in keys {
-            bucket.in_memory_data.remove(&key);
-        }
-        Ok(())
+        let bucket = self.table.get(&bucket)?;
+        bucket
+            .store
+            .write_many(keys.into_iter().map(|key| (key, None)).collect())
     }
 }
 
 /// Add all the `wasi-keyvalue` world's interfaces to a [`wasmtime::component::Linker`].
+///
+/// This adds the interfaces of both `wasi:keyvalue@0.2.0-draft2` and
+/// `wasi:keyvalue@0.2.0-draft`.
 pub fn add_to_linker<T: Send + 'static>(
     l: &mut wasmtime::component::Linker<T>,
     f: fn(&mut T) -> WasiKeyValue<'_>,
@@ -294,6 +588,7 @@ pub fn add_to_linker<T: Send + 'static>(
     keyvalue::store::add_to_linker::<_, HasWasiKeyValue>(l, f)?;
     keyvalue::atomics::add_to_linker::<_, HasWasiKeyValue>(l, f)?;
     keyvalue::batch::add_to_linker::<_, HasWasiKeyValue>(l, f)?;
+    draft::add_to_linker(l, f)?;
     Ok(())
 }
 
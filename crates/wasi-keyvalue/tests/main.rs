@@ -5,7 +5,9 @@ use wasmtime::{
     component::{Component, Linker, ResourceTable},
 };
 use wasmtime_wasi::{WasiCtx, WasiCtxView, WasiView, p2::bindings::Command};
-use wasmtime_wasi_keyvalue::{WasiKeyValue, WasiKeyValueCtx, WasiKeyValueCtxBuilder};
+use wasmtime_wasi_keyvalue::{
+    FileStore, InMemoryStore, KeyValueStore, WasiKeyValue, WasiKeyValueCtx, WasiKeyValueCtxBuilder,
+};
 
 struct Ctx {
     table: ResourceTable,
@@ -66,3 +68,162 @@ async fn keyvalue_main() -> Result<()> {
     )
     .await
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn keyvalue_main_file_store() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("store");
+
+    let store = FileStore::open(&path)?;
+    store.set("atomics_key", b"5".to_vec())?;
+    run_wasi(
+        KEYVALUE_MAIN_COMPONENT,
+        Ctx {
+            table: ResourceTable::new(),
+            wasi_ctx: WasiCtx::builder().inherit_stderr().build(),
+            wasi_keyvalue_ctx: WasiKeyValueCtxBuilder::new().store("", store).build(),
+        },
+    )
+    .await?;
+
+    let store = FileStore::open(&path)?;
+    assert_eq!(store.list_keys()?, ["atomics_key", "b1"]);
+    assert_eq!(store.get("atomics_key")?.as_deref(), Some(&b"6"[..]));
+    assert_eq!(store.get("b1")?.as_deref(), Some(&b"v1"[..]));
+    Ok(())
+}
+
+#[test]
+fn previous_draft_is_linked() -> Result<()> {
+    let engine = wasmtime::Engine::default();
+    let mut linker = Linker::<Ctx>::new(&engine);
+    wasmtime_wasi_keyvalue::add_to_linker(&mut linker, |h: &mut Ctx| {
+        WasiKeyValue::new(&h.wasi_keyvalue_ctx, &mut h.table)
+    })?;
+
+    let component = Component::new(
+        &engine,
+        r#"(component
+            (import "wasi:keyvalue/store@0.2.0-draft" (instance
+                (type $error' (variant
+                    (case "no-such-store")
+                    (case "access-denied")
+                    (case "other" string)))
+                (export "error" (type $error (eq $error')))
+                (type $key-response' (record
+                    (field "keys" (list string))
+                    (field "cursor" (option u64))))
+                (export "key-response" (type $key-response (eq $key-response')))
+                (export "bucket" (type $bucket (sub resource)))
+                (export "open" (func
+                    (param "identifier" string)
+                    (result (result (own $bucket) (error $error)))))
+                (export "[method]bucket.list-keys" (func
+                    (param "self" (borrow $bucket))
+                    (param "cursor" (option u64))
+                    (result (result $key-response (error $error)))))
+            ))
+        )"#,
+    )?;
+    linker.instantiate_pre(&component)?;
+    Ok(())
+}
+
+#[test]
+fn file_store_recovery_and_compaction() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("store");
+
+    let store = FileStore::open(&path)?;
+    store.set("a", b"1".to_vec())?;
+    store.set("b", b"2".to_vec())?;
+    drop(store);
+
+    // A partially-written trailing record is discarded on open.
+    let len = std::fs::metadata(&path)?.len();
+    let mut contents = std::fs::read(&path)?;
+    contents.extend_from_slice(&[0, 1, 0, 0, 0, b'c', 10, 0]);
+    std::fs::write(&path, &contents)?;
+    let store = FileStore::open(&path)?;
+    assert_eq!(store.list_keys()?, ["a", "b"]);
+    assert_eq!(std::fs::metadata(&path)?.len(), len);
+
+    // Overwritten entries are compacted away on open.
+    for i in 0..100 {
+        store.set("a", i.to_string().into_bytes())?;
+    }
+    store.delete("b")?;
+    drop(store);
+    let store = FileStore::open(&path)?;
+    assert_eq!(store.list_keys()?, ["a"]);
+    assert_eq!(store.get("a")?.as_deref(), Some(&b"99"[..]));
+    assert!(std::fs::metadata(&path)?.len() < len);
+
+    // An invalid record anywhere else is an error rather than dropping the
+    // records after it. This overwrites the first record's type, which comes
+    // after the 8-byte header.
+    store.set("b", b"2".to_vec())?;
+    drop(store);
+    let mut contents = std::fs::read(&path)?;
+    contents[8] = 0xff;
+    std::fs::write(&path, &contents)?;
+    assert!(FileStore::open(&path).is_err());
+
+    // Files which aren't stores are rejected.
+    std::fs::write(&path, "not a store")?;
+    assert!(FileStore::open(&path).is_err());
+    Ok(())
+}
+
+#[test]
+fn atomics_are_atomic() -> Result<()> {
+    fn hammer(store: &dyn KeyValueStore) -> Result<()> {
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..100 {
+                        store.increment("counter", 1).unwrap();
+                        loop {
+                            let current = store.get("cas").unwrap();
+                            let next = current.as_deref().map_or(0, |v| v.len()) + 1;
+                            if store
+                                .compare_and_swap("cas", current.as_deref(), vec![0; next])
+                                .unwrap()
+                            {
+                                break;
+                            }
+                        }
+                        store
+                            .write_many(vec![
+                                ("a".to_string(), Some(b"x".to_vec())),
+                                ("b".to_string(), Some(b"x".to_vec())),
+                            ])
+                            .unwrap();
+                        store
+                            .write_many(vec![("a".to_string(), None), ("b".to_string(), None)])
+                            .unwrap();
+                    }
+                });
+                s.spawn(|| {
+                    for _ in 0..100 {
+                        let values = store.get_many(&["a".to_string(), "b".to_string()]).unwrap();
+                        assert_eq!(values[0], values[1]);
+                    }
+                });
+            }
+        });
+        assert_eq!(store.get("counter")?.as_deref(), Some(&b"400"[..]));
+        assert_eq!(store.get("cas")?.map(|v| v.len()), Some(400));
+        Ok(())
+    }
+
+    hammer(&InMemoryStore::new())?;
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("store");
+    hammer(&FileStore::open(&path)?)?;
+    let store = FileStore::open(&path)?;
+    assert_eq!(store.get("counter")?.as_deref(), Some(&b"400"[..]));
+    assert_eq!(store.list_keys()?, ["cas", "counter"]);
+    Ok(())
+}
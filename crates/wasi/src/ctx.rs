@@ -1,8 +1,10 @@
 use crate::cli::{StdinStream, StdoutStream, WasiCliCtx};
 use crate::clocks::{HostMonotonicClock, HostWallClock, WasiClocksCtx};
-use crate::filesystem::{Dir, WasiFilesystemCtx};
+use crate::filesystem::{Dir, WasiDir, WasiFilesystemCtx};
 use crate::random::WasiRandomCtx;
-use crate::sockets::{SocketAddrCheck, SocketAddrUse, WasiSocketsCtx};
+use crate::sockets::{
+    HostNameResolver, SocketAddrCheck, SocketAddrUse, VirtualNetwork, WasiSocketsCtx,
+};
 use crate::{DirPerms, FilePerms, OpenMode};
 use anyhow::Result;
 use cap_rand::RngCore;
@@ -12,6 +14,7 @@ use std::mem;
 use std::net::SocketAddr;
 use std::path::Path;
 use std::pin::Pin;
+use std::sync::Arc;
 use tokio::io::{stderr, stdin, stdout};
 
 /// Builder-style structure used to create a [`WasiCtx`].
@@ -297,6 +300,27 @@ impl WasiCtxBuilder {
         file_perms: FilePerms,
     ) -> Result<&mut Self> {
         let dir = cap_std::fs::Dir::open_ambient_dir(host_path.as_ref(), ambient_authority())?;
+        Ok(self.preopened_virtual_dir(dir, guest_path, dir_perms, file_perms))
+    }
+
+    /// Provides access to a directory implemented by `dir` to the guest.
+    ///
+    /// This is similar to [`WasiCtxBuilder::preopened_dir`] except that rather
+    /// than opening a directory on the host the guest is given access to an
+    /// arbitrary [`WasiDir`] implementation. This can be used, for example, to
+    /// give each guest its own in-memory filesystem or to expose the contents
+    /// of an archive without extracting it to the host filesystem.
+    ///
+    /// The `guest_path`, `dir_perms`, and `file_perms` arguments have the same
+    /// meaning as in [`WasiCtxBuilder::preopened_dir`], and permissions are
+    /// enforced before any method of `dir` is invoked.
+    pub fn preopened_virtual_dir(
+        &mut self,
+        dir: impl WasiDir,
+        guest_path: impl AsRef<str>,
+        dir_perms: DirPerms,
+        file_perms: FilePerms,
+    ) -> &mut Self {
         let mut open_mode = OpenMode::empty();
         if dir_perms.contains(DirPerms::READ) {
             open_mode |= OpenMode::READ;
@@ -314,7 +338,7 @@ impl WasiCtxBuilder {
             ),
             guest_path.as_ref().to_owned(),
         ));
-        Ok(self)
+        self
     }
 
     /// Set the generator for the `wasi:random/random` number generator to the
@@ -400,6 +424,16 @@ impl WasiCtxBuilder {
         self
     }
 
+    /// Configures `wasi:sockets/ip-name-lookup` to resolve domain names with
+    /// `resolver`.
+    ///
+    /// By default the host's system resolver is used. Name lookups must still
+    /// be enabled with [`WasiCtxBuilder::allow_ip_name_lookup`].
+    pub fn ip_name_resolver(&mut self, resolver: impl HostNameResolver + 'static) -> &mut Self {
+        self.sockets.resolver = Arc::new(resolver);
+        self
+    }
+
     /// Allow usage of UDP.
     ///
     /// This is enabled by default, but can be disabled if UDP should be blanket
@@ -418,6 +452,19 @@ impl WasiCtxBuilder {
         self
     }
 
+    /// Connects `wasi:sockets` to `network`, an in-process network, instead
+    /// of the host's network.
+    ///
+    /// Guests configured with the same [`VirtualNetwork`] can communicate
+    /// with each other over TCP and UDP, but can't reach the host's network.
+    /// Addresses must still be permitted with
+    /// [`WasiCtxBuilder::socket_addr_check`] or
+    /// [`WasiCtxBuilder::inherit_network`].
+    pub fn virtual_network(&mut self, network: VirtualNetwork) -> &mut Self {
+        self.sockets.virtual_network = Some(network);
+        self
+    }
+
     /// Uses the configured context so far to construct the final [`WasiCtx`].
     ///
     /// Note that each `WasiCtxBuilder` can only be used to "build" once, and
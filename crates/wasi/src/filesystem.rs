@@ -1,13 +1,25 @@
+use crate::SystemTimeSpec;
 use crate::clocks::Datetime;
 use crate::runtime::{AbortOnDropJoinHandle, spawn_blocking};
 use anyhow::Context as _;
 use cap_fs_ext::{FileTypeExt as _, MetadataExt as _};
-use fs_set_times::SystemTimeSpec;
+use std::any::Any;
 use std::collections::hash_map;
+use std::io;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::SystemTime;
+use system_interface::fs::FileIoExt;
 use tracing::debug;
 use wasmtime::component::{HasData, Resource, ResourceTable};
 
+mod memory;
+
+pub use self::memory::MemoryDir;
+
+#[doc(no_inline)]
+pub use system_interface::fs::{Advice, FdFlags};
+
 /// A helper struct which implements [`HasData`] for the `wasi:filesystem` APIs.
 ///
 /// This can be useful when directly calling `add_to_linker` functions directly,
@@ -184,6 +196,8 @@ pub(crate) enum ErrorCode {
     Access,
     /// Connection already in progress, similar to `EALREADY` in POSIX.
     Already,
+    /// Cross-device link, similar to `EXDEV` in POSIX.
+    CrossDevice,
     /// Bad descriptor, similar to `EBADF` in POSIX.
     BadDescriptor,
     /// Device or resource busy, similar to `EBUSY` in POSIX.
@@ -240,7 +254,8 @@ fn datetime_from(t: std::time::SystemTime) -> Datetime {
 /// The type of a filesystem object referenced by a descriptor.
 ///
 /// Note: This was called `filetype` in earlier versions of WASI.
-pub(crate) enum DescriptorType {
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DescriptorType {
     /// The type of the descriptor or file is unknown or is different from
     /// any of the other types specified.
     Unknown,
@@ -302,15 +317,15 @@ pub(crate) struct DescriptorStat {
     pub status_change_timestamp: Option<Datetime>,
 }
 
-impl From<cap_std::fs::Metadata> for DescriptorStat {
-    fn from(meta: cap_std::fs::Metadata) -> Self {
+impl From<Metadata> for DescriptorStat {
+    fn from(meta: Metadata) -> Self {
         Self {
-            type_: meta.file_type().into(),
-            link_count: meta.nlink(),
-            size: meta.len(),
-            data_access_timestamp: meta.accessed().map(|t| datetime_from(t.into_std())).ok(),
-            data_modification_timestamp: meta.modified().map(|t| datetime_from(t.into_std())).ok(),
-            status_change_timestamp: meta.created().map(|t| datetime_from(t.into_std())).ok(),
+            type_: meta.file_type,
+            link_count: meta.link_count,
+            size: meta.size,
+            data_access_timestamp: meta.accessed.map(datetime_from),
+            data_modification_timestamp: meta.modified.map(datetime_from),
+            status_change_timestamp: meta.created.map(datetime_from),
         }
     }
 }
@@ -324,17 +339,16 @@ pub(crate) struct MetadataHashValue {
     pub upper: u64,
 }
 
-impl From<&cap_std::fs::Metadata> for MetadataHashValue {
-    fn from(meta: &cap_std::fs::Metadata) -> Self {
-        use cap_fs_ext::MetadataExt;
+impl From<&Metadata> for MetadataHashValue {
+    fn from(meta: &Metadata) -> Self {
         // Without incurring any deps, std provides us with a 64 bit hash
         // function:
         use std::hash::Hasher;
         // Note that this means that the metadata hash (which becomes a preview1 ino) may
         // change when a different rustc release is used to build this host implementation:
         let mut hasher = hash_map::DefaultHasher::new();
-        hasher.write_u64(meta.dev());
-        hasher.write_u64(meta.ino());
+        hasher.write_u64(meta.device);
+        hasher.write_u64(meta.inode);
         let lower = hasher.finish();
         // MetadataHashValue has a pair of 64-bit members for representing a
         // single 128-bit number. However, we only have 64 bits of entropy. To
@@ -425,6 +439,7 @@ impl<'a> From<&'a std::io::Error> for ErrorCode {
     fn from(err: &'a std::io::Error) -> ErrorCode {
         match from_raw_os_error(err.raw_os_error()) {
             Some(errno) => errno,
+            None if err.get_ref().is_some_and(|e| e.is::<SymlinkLoop>()) => ErrorCode::Loop,
             None => {
                 debug!("unknown raw os error: {err}");
                 match err.kind() {
@@ -432,6 +447,15 @@ impl<'a> From<&'a std::io::Error> for ErrorCode {
                     std::io::ErrorKind::PermissionDenied => ErrorCode::NotPermitted,
                     std::io::ErrorKind::AlreadyExists => ErrorCode::Exist,
                     std::io::ErrorKind::InvalidInput => ErrorCode::Invalid,
+                    std::io::ErrorKind::InvalidData => ErrorCode::IllegalByteSequence,
+                    std::io::ErrorKind::NotADirectory => ErrorCode::NotDirectory,
+                    std::io::ErrorKind::IsADirectory => ErrorCode::IsDirectory,
+                    std::io::ErrorKind::DirectoryNotEmpty => ErrorCode::NotEmpty,
+                    std::io::ErrorKind::StorageFull => ErrorCode::InsufficientSpace,
+                    std::io::ErrorKind::FileTooLarge => ErrorCode::FileTooLarge,
+                    std::io::ErrorKind::OutOfMemory => ErrorCode::InsufficientMemory,
+                    std::io::ErrorKind::Unsupported => ErrorCode::Unsupported,
+                    std::io::ErrorKind::CrossesDevices => ErrorCode::CrossDevice,
                     _ => ErrorCode::Io,
                 }
             }
@@ -445,6 +469,531 @@ impl From<std::io::Error> for ErrorCode {
     }
 }
 
+/// Metadata about a filesystem object as reported by a [`WasiDir`] or
+/// [`WasiFile`] implementation.
+#[derive(Clone, Debug)]
+pub struct Metadata {
+    /// The type of the object.
+    pub file_type: DescriptorType,
+    /// Number of hard links to the object.
+    pub link_count: u64,
+    /// For regular files, the file size in bytes. For symbolic links, the
+    /// length in bytes of the pathname contained in the symbolic link.
+    pub size: u64,
+    /// Last data access timestamp, if maintained.
+    pub accessed: Option<SystemTime>,
+    /// Last data modification timestamp, if maintained.
+    pub modified: Option<SystemTime>,
+    /// Creation or last status-change timestamp, if maintained.
+    pub created: Option<SystemTime>,
+    /// Identifier of the device, or filesystem, containing this object.
+    ///
+    /// Together with `inode` this is used to determine whether two
+    /// descriptors refer to the same object and to derive the metadata hash
+    /// reported to the guest.
+    pub device: u64,
+    /// Identifier of this object within `device`.
+    pub inode: u64,
+}
+
+impl From<cap_std::fs::Metadata> for Metadata {
+    fn from(meta: cap_std::fs::Metadata) -> Self {
+        Self {
+            file_type: meta.file_type().into(),
+            link_count: meta.nlink(),
+            size: meta.len(),
+            accessed: meta.accessed().map(|t| t.into_std()).ok(),
+            modified: meta.modified().map(|t| t.into_std()).ok(),
+            created: meta.created().map(|t| t.into_std()).ok(),
+            device: meta.dev(),
+            inode: meta.ino(),
+        }
+    }
+}
+
+/// A single entry yielded by [`WasiDir::read_dir`].
+#[derive(Clone, Debug)]
+pub struct DirEntry {
+    /// The name of the entry within its directory.
+    pub name: String,
+    /// The type of the entry.
+    pub file_type: DescriptorType,
+}
+
+/// Iterator over the entries of a directory, see [`WasiDir::read_dir`].
+pub type ReadDir = Box<dyn Iterator<Item = io::Result<DirEntry>> + Send>;
+
+/// Options describing how [`WasiDir::open_at`] should open a path.
+///
+/// Permission checks configured through [`DirPerms`] and [`FilePerms`] have
+/// already been performed by the time an implementation sees these options.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpenOptions {
+    /// Follow a symbolic link if it is the final component of the path.
+    pub follow_symlinks: bool,
+    /// Create the file if it does not exist.
+    pub create: bool,
+    /// Fail if the file already exists. Only meaningful along with `create`.
+    pub exclusive: bool,
+    /// Truncate the file to zero length after opening it.
+    pub truncate: bool,
+    /// Fail if the path does not refer to a directory.
+    pub directory: bool,
+    /// Open the object for reading.
+    pub read: bool,
+    /// Open the object for writing.
+    pub write: bool,
+}
+
+/// The object opened by [`WasiDir::open_at`].
+pub enum OpenResult {
+    /// A directory was opened.
+    Dir(Box<dyn WasiDir>),
+    /// A non-directory object was opened.
+    File(Box<dyn WasiFile>),
+}
+
+fn unsupported<T>() -> io::Result<T> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// Error used by virtual filesystems for `ELOOP`, which has no stable
+/// [`io::ErrorKind`].
+#[derive(Debug)]
+struct SymlinkLoop;
+
+impl std::fmt::Display for SymlinkLoop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("too many levels of symbolic links")
+    }
+}
+
+impl std::error::Error for SymlinkLoop {}
+
+fn symlink_loop() -> io::Error {
+    io::Error::other(SymlinkLoop)
+}
+
+/// The implementation of a directory exposed to WASI guests.
+///
+/// Every [`Dir`] descriptor, including preopens, is backed by a trait object of
+/// this type. This crate implements it for [`cap_std::fs::Dir`] to provide
+/// access to directories on the host, and embedders may implement it to
+/// provide virtual filesystems instead, for example an in-memory tree or a
+/// read-only archive. Such directories are made available to guests with
+/// [`WasiCtxBuilder::preopened_virtual_dir`](crate::WasiCtxBuilder::preopened_virtual_dir).
+///
+/// All methods are synchronous and are invoked either on a blocking thread or,
+/// if [`WasiCtxBuilder::allow_blocking_current_thread`] is enabled, directly on
+/// the current thread, so implementations are free to block.
+///
+/// Paths passed to these methods are relative to this directory and come
+/// straight from the guest. Implementations are responsible for ensuring that
+/// they cannot be used to escape this directory, for example through `..` or
+/// absolute paths.
+///
+/// Mutating operations default to returning [`io::ErrorKind::Unsupported`]
+/// which is suitable for read-only implementations.
+///
+/// [`WasiCtxBuilder::allow_blocking_current_thread`]: crate::WasiCtxBuilder::allow_blocking_current_thread
+pub trait WasiDir: Send + Sync + 'static {
+    /// Returns `self` as [`Any`], used to identify the concrete type of the
+    /// destination directory in [`WasiDir::rename`] and
+    /// [`WasiDir::hard_link`].
+    fn as_any(&self) -> &dyn Any;
+
+    /// Opens `path` with the provided `options`.
+    fn open_at(&self, path: &str, options: &OpenOptions) -> io::Result<OpenResult>;
+
+    /// Returns the metadata of this directory.
+    fn metadata(&self) -> io::Result<Metadata>;
+
+    /// Returns the metadata of `path`, following a final symbolic link only if
+    /// `follow_symlinks` is set.
+    fn metadata_at(&self, path: &str, follow_symlinks: bool) -> io::Result<Metadata>;
+
+    /// Returns an iterator over the entries of this directory, not including
+    /// `.` and `..`.
+    fn read_dir(&self) -> io::Result<ReadDir>;
+
+    /// Reads the target of the symbolic link at `path`.
+    fn read_link(&self, path: &str) -> io::Result<PathBuf>;
+
+    /// Creates a new directory at `path`.
+    fn create_dir(&self, path: &str) -> io::Result<()> {
+        let _ = path;
+        unsupported()
+    }
+
+    /// Removes the empty directory at `path`.
+    fn remove_dir(&self, path: &str) -> io::Result<()> {
+        let _ = path;
+        unsupported()
+    }
+
+    /// Removes the file or symbolic link at `path`.
+    fn unlink_file(&self, path: &str) -> io::Result<()> {
+        let _ = path;
+        unsupported()
+    }
+
+    /// Creates a symbolic link at `dest_path` pointing to `src_path`.
+    fn symlink(&self, src_path: &str, dest_path: &str) -> io::Result<()> {
+        let _ = (src_path, dest_path);
+        unsupported()
+    }
+
+    /// Moves `old_path` to `new_path` within `new_dir`.
+    ///
+    /// Implementations are expected to use [`WasiDir::as_any`] to downcast
+    /// `new_dir` and should fail if it is of a type they cannot move objects
+    /// into.
+    fn rename(&self, old_path: &str, new_dir: &dyn WasiDir, new_path: &str) -> io::Result<()> {
+        let _ = (old_path, new_dir, new_path);
+        unsupported()
+    }
+
+    /// Creates a hard link at `new_path` within `new_dir` to `old_path`.
+    ///
+    /// See [`WasiDir::rename`] for how `new_dir` is handled.
+    fn hard_link(&self, old_path: &str, new_dir: &dyn WasiDir, new_path: &str) -> io::Result<()> {
+        let _ = (old_path, new_dir, new_path);
+        unsupported()
+    }
+
+    /// Sets the timestamps of this directory.
+    fn set_times(
+        &self,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        let _ = (atim, mtim);
+        unsupported()
+    }
+
+    /// Sets the timestamps of `path`, following a final symbolic link only if
+    /// `follow_symlinks` is set.
+    fn set_times_at(
+        &self,
+        path: &str,
+        follow_symlinks: bool,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        let _ = (path, follow_symlinks, atim, mtim);
+        unsupported()
+    }
+
+    /// Flushes the metadata of this directory to storage.
+    fn sync_all(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Flushes the contents of this directory to storage.
+    fn sync_data(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Returns the synchronization flags this directory was opened with.
+    fn fd_flags(&self) -> io::Result<FdFlags> {
+        Ok(FdFlags::empty())
+    }
+}
+
+/// The implementation of a non-directory object exposed to WASI guests.
+///
+/// Every [`File`] descriptor is backed by a trait object of this type. This
+/// crate implements it for [`cap_std::fs::File`], and embedders may implement
+/// it alongside [`WasiDir`] to provide virtual files.
+///
+/// As with [`WasiDir`] all methods are synchronous and may block. Mutating
+/// operations default to returning [`io::ErrorKind::Unsupported`].
+pub trait WasiFile: Send + Sync + 'static {
+    /// Returns `self` as [`Any`] to allow downcasting to a concrete type.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Reads bytes starting at `offset` into `buf`, returning how many bytes
+    /// were read. Returns `0` at the end of the file.
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize>;
+
+    /// Returns the metadata of this file.
+    fn metadata(&self) -> io::Result<Metadata>;
+
+    /// Writes bytes from `buf` starting at `offset`, returning how many bytes
+    /// were written.
+    fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        let _ = (buf, offset);
+        unsupported()
+    }
+
+    /// Writes bytes from `buf` to the end of the file, returning how many
+    /// bytes were written.
+    fn append(&self, buf: &[u8]) -> io::Result<usize> {
+        let _ = buf;
+        unsupported()
+    }
+
+    /// Truncates or extends this file to `size` bytes.
+    fn set_len(&self, size: u64) -> io::Result<()> {
+        let _ = size;
+        unsupported()
+    }
+
+    /// Sets the timestamps of this file.
+    fn set_times(
+        &self,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        let _ = (atim, mtim);
+        unsupported()
+    }
+
+    /// Flushes the contents and metadata of this file to storage.
+    fn sync_all(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Flushes the contents of this file to storage.
+    fn sync_data(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Returns the synchronization flags this file was opened with.
+    fn fd_flags(&self) -> io::Result<FdFlags> {
+        Ok(FdFlags::empty())
+    }
+
+    /// Provides a hint about how the given range of the file will be accessed.
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        let _ = (offset, len, advice);
+        Ok(())
+    }
+}
+
+impl WasiDir for cap_std::fs::Dir {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn open_at(&self, path: &str, options: &OpenOptions) -> io::Result<OpenResult> {
+        use cap_fs_ext::{FollowSymlinks, OpenOptionsFollowExt, OpenOptionsMaybeDirExt};
+        use system_interface::fs::GetSetFdFlags;
+
+        let mut opts = cap_std::fs::OpenOptions::new();
+        opts.maybe_dir(true);
+        if options.create {
+            if options.exclusive {
+                opts.create_new(true);
+            } else {
+                opts.create(true);
+            }
+        }
+        opts.truncate(options.truncate)
+            .read(options.read)
+            .write(options.write);
+        if options.follow_symlinks {
+            opts.follow(FollowSymlinks::Yes);
+        } else {
+            opts.follow(FollowSymlinks::No);
+        }
+
+        let mut opened = self.open_with(path, &opts)?;
+        if opened.metadata()?.is_dir() {
+            Ok(OpenResult::Dir(Box::new(cap_std::fs::Dir::from_std_file(
+                opened.into_std(),
+            ))))
+        } else if options.directory {
+            Err(io::ErrorKind::NotADirectory.into())
+        } else {
+            // FIXME cap-std needs a nonblocking open option so that files reads and writes
+            // are nonblocking. Instead we set it after opening here:
+            let set_fd_flags = opened.new_set_fd_flags(FdFlags::NONBLOCK)?;
+            opened.set_fd_flags(set_fd_flags)?;
+            Ok(OpenResult::File(Box::new(opened)))
+        }
+    }
+
+    fn metadata(&self) -> io::Result<Metadata> {
+        Ok(self.dir_metadata()?.into())
+    }
+
+    fn metadata_at(&self, path: &str, follow_symlinks: bool) -> io::Result<Metadata> {
+        let meta = if follow_symlinks {
+            cap_std::fs::Dir::metadata(self, path)?
+        } else {
+            self.symlink_metadata(path)?
+        };
+        Ok(meta.into())
+    }
+
+    fn read_dir(&self) -> io::Result<ReadDir> {
+        // Both `entries` and `metadata` perform syscalls, which is why this
+        // is expected to be called on a thread that can block.
+        let entries = self.entries()?.filter_map(|entry| {
+            let result = entry.and_then(|entry| {
+                let meta = entry.metadata()?;
+                let name = entry
+                    .file_name()
+                    .into_string()
+                    .map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+                Ok(DirEntry {
+                    name,
+                    file_type: meta.file_type().into(),
+                })
+            });
+            // On windows, filter out files like `C:\DumpStack.log.tmp` which we
+            // can't get full metadata for.
+            #[cfg(windows)]
+            if let Err(err) = &result {
+                use windows_sys::Win32::Foundation::{
+                    ERROR_ACCESS_DENIED, ERROR_SHARING_VIOLATION,
+                };
+                if err.raw_os_error() == Some(ERROR_SHARING_VIOLATION as i32)
+                    || err.raw_os_error() == Some(ERROR_ACCESS_DENIED as i32)
+                {
+                    return None;
+                }
+            }
+            Some(result)
+        });
+        Ok(Box::new(entries))
+    }
+
+    fn read_link(&self, path: &str) -> io::Result<PathBuf> {
+        cap_std::fs::Dir::read_link(self, path)
+    }
+
+    fn create_dir(&self, path: &str) -> io::Result<()> {
+        cap_std::fs::Dir::create_dir(self, path)
+    }
+
+    fn remove_dir(&self, path: &str) -> io::Result<()> {
+        cap_std::fs::Dir::remove_dir(self, path)
+    }
+
+    fn unlink_file(&self, path: &str) -> io::Result<()> {
+        use cap_fs_ext::DirExt;
+        self.remove_file_or_symlink(path)
+    }
+
+    fn symlink(&self, src_path: &str, dest_path: &str) -> io::Result<()> {
+        // `Dir::symlink` is only available on unix, so use `DirExt` instead
+        cap_fs_ext::DirExt::symlink(self, src_path, dest_path)
+    }
+
+    fn rename(&self, old_path: &str, new_dir: &dyn WasiDir, new_path: &str) -> io::Result<()> {
+        let new_dir = downcast_host_dir(new_dir)?;
+        cap_std::fs::Dir::rename(self, old_path, new_dir, new_path)
+    }
+
+    fn hard_link(&self, old_path: &str, new_dir: &dyn WasiDir, new_path: &str) -> io::Result<()> {
+        let new_dir = downcast_host_dir(new_dir)?;
+        cap_std::fs::Dir::hard_link(self, old_path, new_dir, new_path)
+    }
+
+    fn set_times(
+        &self,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        fs_set_times::SetTimes::set_times(
+            self,
+            atim.map(SystemTimeSpec::into_std),
+            mtim.map(SystemTimeSpec::into_std),
+        )
+    }
+
+    fn set_times_at(
+        &self,
+        path: &str,
+        follow_symlinks: bool,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        use cap_fs_ext::DirExt;
+        if follow_symlinks {
+            DirExt::set_times(self, path, atim, mtim)
+        } else {
+            self.set_symlink_times(path, atim, mtim)
+        }
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        self.open(std::path::Component::CurDir)?.sync_all()
+    }
+
+    fn sync_data(&self) -> io::Result<()> {
+        self.open(std::path::Component::CurDir)?.sync_data()
+    }
+
+    fn fd_flags(&self) -> io::Result<FdFlags> {
+        system_interface::fs::GetSetFdFlags::get_fd_flags(self)
+    }
+}
+
+/// Host directories can only be linked or renamed into other host
+/// directories.
+fn downcast_host_dir(dir: &dyn WasiDir) -> io::Result<&cap_std::fs::Dir> {
+    dir.as_any()
+        .downcast_ref()
+        .ok_or_else(|| io::ErrorKind::CrossesDevices.into())
+}
+
+impl WasiFile for cap_std::fs::File {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        FileIoExt::read_at(self, buf, offset)
+    }
+
+    fn metadata(&self) -> io::Result<Metadata> {
+        Ok(cap_std::fs::File::metadata(self)?.into())
+    }
+
+    fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        FileIoExt::write_at(self, buf, offset)
+    }
+
+    fn append(&self, buf: &[u8]) -> io::Result<usize> {
+        FileIoExt::append(self, buf)
+    }
+
+    fn set_len(&self, size: u64) -> io::Result<()> {
+        cap_std::fs::File::set_len(self, size)
+    }
+
+    fn set_times(
+        &self,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        fs_set_times::SetTimes::set_times(
+            self,
+            atim.map(SystemTimeSpec::into_std),
+            mtim.map(SystemTimeSpec::into_std),
+        )
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        cap_std::fs::File::sync_all(self)
+    }
+
+    fn sync_data(&self) -> io::Result<()> {
+        cap_std::fs::File::sync_data(self)
+    }
+
+    fn fd_flags(&self) -> io::Result<FdFlags> {
+        system_interface::fs::GetSetFdFlags::get_fd_flags(self)
+    }
+
+    fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        FileIoExt::advise(self, offset, len, advice)
+    }
+}
+
 #[derive(Clone)]
 pub enum Descriptor {
     File(File),
@@ -466,7 +1015,7 @@ impl Descriptor {
         }
     }
 
-    async fn get_metadata(&self) -> std::io::Result<cap_std::fs::Metadata> {
+    async fn get_metadata(&self) -> io::Result<Metadata> {
         match self {
             Self::File(f) => {
                 // No permissions check on metadata: if opened, allowed to stat it
@@ -474,7 +1023,7 @@ impl Descriptor {
             }
             Self::Dir(d) => {
                 // No permissions check on metadata: if opened, allowed to stat it
-                d.run_blocking(|d| d.dir_metadata()).await
+                d.run_blocking(|d| d.metadata()).await
             }
         }
     }
@@ -498,19 +1047,13 @@ impl Descriptor {
                 }
             }
             Self::Dir(d) => {
-                d.run_blocking(|d| {
-                    let d = d.open(std::path::Component::CurDir)?;
-                    d.sync_data()?;
-                    Ok(())
-                })
-                .await
+                d.run_blocking(|d| d.sync_data()).await?;
+                Ok(())
             }
         }
     }
 
     pub(crate) async fn get_flags(&self) -> Result<DescriptorFlags, ErrorCode> {
-        use system_interface::fs::{FdFlags, GetSetFdFlags};
-
         fn get_from_fdflags(flags: FdFlags) -> DescriptorFlags {
             let mut out = DescriptorFlags::empty();
             if flags.contains(FdFlags::DSYNC) {
@@ -526,7 +1069,7 @@ impl Descriptor {
         }
         match self {
             Self::File(f) => {
-                let flags = f.run_blocking(|f| f.get_fd_flags()).await?;
+                let flags = f.run_blocking(|f| f.fd_flags()).await?;
                 let mut flags = get_from_fdflags(flags);
                 if f.open_mode.contains(OpenMode::READ) {
                     flags |= DescriptorFlags::READ;
@@ -537,7 +1080,7 @@ impl Descriptor {
                 Ok(flags)
             }
             Self::Dir(d) => {
-                let flags = d.run_blocking(|d| d.get_fd_flags()).await?;
+                let flags = d.run_blocking(|d| d.fd_flags()).await?;
                 let mut flags = get_from_fdflags(flags);
                 if d.open_mode.contains(OpenMode::READ) {
                     flags |= DescriptorFlags::READ;
@@ -554,7 +1097,7 @@ impl Descriptor {
         match self {
             Self::File(f) => {
                 let meta = f.run_blocking(|f| f.metadata()).await?;
-                Ok(meta.file_type().into())
+                Ok(meta.file_type)
             }
             Self::Dir(_) => Ok(DescriptorType::Directory),
         }
@@ -562,10 +1105,11 @@ impl Descriptor {
 
     pub(crate) async fn set_times(
         &self,
-        atim: Option<SystemTimeSpec>,
-        mtim: Option<SystemTimeSpec>,
+        atim: Option<fs_set_times::SystemTimeSpec>,
+        mtim: Option<fs_set_times::SystemTimeSpec>,
     ) -> Result<(), ErrorCode> {
-        use fs_set_times::SetTimes as _;
+        let atim = atim.map(SystemTimeSpec::from_std);
+        let mtim = mtim.map(SystemTimeSpec::from_std);
         match self {
             Self::File(f) => {
                 if !f.perms.contains(FilePerms::WRITE) {
@@ -603,36 +1147,22 @@ impl Descriptor {
                 }
             }
             Self::Dir(d) => {
-                d.run_blocking(|d| {
-                    let d = d.open(std::path::Component::CurDir)?;
-                    d.sync_all()?;
-                    Ok(())
-                })
-                .await
+                d.run_blocking(|d| d.sync_all()).await?;
+                Ok(())
             }
         }
     }
 
     pub(crate) async fn stat(&self) -> Result<DescriptorStat, ErrorCode> {
-        match self {
-            Self::File(f) => {
-                // No permissions check on stat: if opened, allowed to stat it
-                let meta = f.run_blocking(|f| f.metadata()).await?;
-                Ok(meta.into())
-            }
-            Self::Dir(d) => {
-                // No permissions check on stat: if opened, allowed to stat it
-                let meta = d.run_blocking(|d| d.dir_metadata()).await?;
-                Ok(meta.into())
-            }
-        }
+        // No permissions check on stat: if opened, allowed to stat it
+        let meta = self.get_metadata().await?;
+        Ok(meta.into())
     }
 
     pub(crate) async fn is_same_object(&self, other: &Self) -> wasmtime::Result<bool> {
-        use cap_fs_ext::MetadataExt;
         let meta_a = self.get_metadata().await?;
         let meta_b = other.get_metadata().await?;
-        if meta_a.dev() == meta_b.dev() && meta_a.ino() == meta_b.ino() {
+        if meta_a.device == meta_b.device && meta_a.inode == meta_b.inode {
             // MetadataHashValue does not derive eq, so use a pair of
             // comparisons to check equality:
             debug_assert_eq!(
@@ -658,14 +1188,15 @@ impl Descriptor {
 
 #[derive(Clone)]
 pub struct File {
-    /// The operating system File this struct is mediating access to.
+    /// The implementation of the file this struct is mediating access to,
+    /// typically an operating system file.
     ///
     /// Wrapped in an Arc because the same underlying file is used for
     /// implementing the stream types. A copy is also needed for
     /// [`spawn_blocking`].
     ///
     /// [`spawn_blocking`]: Self::spawn_blocking
-    pub file: Arc<cap_std::fs::File>,
+    pub file: Arc<dyn WasiFile>,
     /// Permissions to enforce on access to the file. These permissions are
     /// specified by a user of the `crate::WasiCtxBuilder`, and are
     /// enforced prior to any enforced by the underlying operating system.
@@ -681,7 +1212,7 @@ pub struct File {
 
 impl File {
     pub fn new(
-        file: cap_std::fs::File,
+        file: impl WasiFile,
         perms: FilePerms,
         open_mode: OpenMode,
         allow_blocking_current_thread: bool,
@@ -710,7 +1241,7 @@ impl File {
     /// - [Implement opt-in for enabling WASI to block the current thread](https://github.com/bytecodealliance/wasmtime/pull/8190)
     pub(crate) async fn run_blocking<F, R>(&self, body: F) -> R
     where
-        F: FnOnce(&cap_std::fs::File) -> R + Send + 'static,
+        F: FnOnce(&dyn WasiFile) -> R + Send + 'static,
         R: Send + 'static,
     {
         match self.as_blocking_file() {
@@ -721,27 +1252,27 @@ impl File {
 
     pub(crate) fn spawn_blocking<F, R>(&self, body: F) -> AbortOnDropJoinHandle<R>
     where
-        F: FnOnce(&cap_std::fs::File) -> R + Send + 'static,
+        F: FnOnce(&dyn WasiFile) -> R + Send + 'static,
         R: Send + 'static,
     {
         let f = self.file.clone();
-        spawn_blocking(move || body(&f))
+        spawn_blocking(move || body(&*f))
     }
 
     /// Returns `Some` when the current thread is allowed to block in filesystem
     /// operations, and otherwise returns `None` to indicate that
     /// `spawn_blocking` must be used.
-    pub(crate) fn as_blocking_file(&self) -> Option<&cap_std::fs::File> {
+    pub(crate) fn as_blocking_file(&self) -> Option<&dyn WasiFile> {
         if self.allow_blocking_current_thread {
-            Some(&self.file)
+            Some(&*self.file)
         } else {
             None
         }
     }
 
-    /// Returns reference to the underlying [`cap_std::fs::File`]
+    /// Returns reference to the underlying [`WasiFile`]
     #[cfg(feature = "p3")]
-    pub(crate) fn as_file(&self) -> &Arc<cap_std::fs::File> {
+    pub(crate) fn as_file(&self) -> &Arc<dyn WasiFile> {
         &self.file
     }
 
@@ -749,9 +1280,8 @@ impl File {
         &self,
         offset: u64,
         len: u64,
-        advice: system_interface::fs::Advice,
+        advice: Advice,
     ) -> Result<(), ErrorCode> {
-        use system_interface::fs::FileIoExt as _;
         self.run_blocking(move |f| f.advise(offset, len, advice))
             .await?;
         Ok(())
@@ -768,13 +1298,13 @@ impl File {
 
 #[derive(Clone)]
 pub struct Dir {
-    /// The operating system file descriptor this struct is mediating access
-    /// to.
+    /// The implementation of the directory this struct is mediating access
+    /// to, typically an operating system file descriptor.
     ///
     /// Wrapped in an Arc because a copy is needed for [`spawn_blocking`].
     ///
     /// [`spawn_blocking`]: Self::spawn_blocking
-    pub dir: Arc<cap_std::fs::Dir>,
+    pub dir: Arc<dyn WasiDir>,
     /// Permissions to enforce on access to this directory. These permissions
     /// are specified by a user of the `crate::WasiCtxBuilder`, and
     /// are enforced prior to any enforced by the underlying operating system.
@@ -795,7 +1325,7 @@ pub struct Dir {
 
 impl Dir {
     pub fn new(
-        dir: cap_std::fs::Dir,
+        dir: impl WasiDir,
         perms: DirPerms,
         file_perms: FilePerms,
         open_mode: OpenMode,
@@ -826,20 +1356,20 @@ impl Dir {
     /// - [Implement opt-in for enabling WASI to block the current thread](https://github.com/bytecodealliance/wasmtime/pull/8190)
     pub(crate) async fn run_blocking<F, R>(&self, body: F) -> R
     where
-        F: FnOnce(&cap_std::fs::Dir) -> R + Send + 'static,
+        F: FnOnce(&dyn WasiDir) -> R + Send + 'static,
         R: Send + 'static,
     {
         if self.allow_blocking_current_thread {
-            body(&self.dir)
+            body(&*self.dir)
         } else {
             let d = self.dir.clone();
-            spawn_blocking(move || body(&d)).await
+            spawn_blocking(move || body(&*d)).await
         }
     }
 
-    /// Returns reference to the underlying [`cap_std::fs::Dir`]
+    /// Returns reference to the underlying [`WasiDir`]
     #[cfg(feature = "p3")]
-    pub(crate) fn as_dir(&self) -> &Arc<cap_std::fs::Dir> {
+    pub(crate) fn as_dir(&self) -> &Arc<dyn WasiDir> {
         &self.dir
     }
 
@@ -860,12 +1390,10 @@ impl Dir {
             return Err(ErrorCode::NotPermitted);
         }
 
-        let meta = if path_flags.contains(PathFlags::SYMLINK_FOLLOW) {
-            self.run_blocking(move |d| d.metadata(&path)).await?
-        } else {
-            self.run_blocking(move |d| d.symlink_metadata(&path))
-                .await?
-        };
+        let follow = path_flags.contains(PathFlags::SYMLINK_FOLLOW);
+        let meta = self
+            .run_blocking(move |d| d.metadata_at(&path, follow))
+            .await?;
         Ok(meta.into())
     }
 
@@ -873,33 +1401,22 @@ impl Dir {
         &self,
         path_flags: PathFlags,
         path: String,
-        atim: Option<SystemTimeSpec>,
-        mtim: Option<SystemTimeSpec>,
+        atim: Option<fs_set_times::SystemTimeSpec>,
+        mtim: Option<fs_set_times::SystemTimeSpec>,
     ) -> Result<(), ErrorCode> {
-        use cap_fs_ext::DirExt as _;
-
         if !self.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted);
         }
-        if path_flags.contains(PathFlags::SYMLINK_FOLLOW) {
-            self.run_blocking(move |d| {
-                d.set_times(
-                    &path,
-                    atim.map(cap_fs_ext::SystemTimeSpec::from_std),
-                    mtim.map(cap_fs_ext::SystemTimeSpec::from_std),
-                )
-            })
-            .await?;
-        } else {
-            self.run_blocking(move |d| {
-                d.set_symlink_times(
-                    &path,
-                    atim.map(cap_fs_ext::SystemTimeSpec::from_std),
-                    mtim.map(cap_fs_ext::SystemTimeSpec::from_std),
-                )
-            })
-            .await?;
-        }
+        let follow = path_flags.contains(PathFlags::SYMLINK_FOLLOW);
+        self.run_blocking(move |d| {
+            d.set_times_at(
+                &path,
+                follow,
+                atim.map(SystemTimeSpec::from_std),
+                mtim.map(SystemTimeSpec::from_std),
+            )
+        })
+        .await?;
         Ok(())
     }
 
@@ -920,7 +1437,7 @@ impl Dir {
             return Err(ErrorCode::Invalid);
         }
         let new_dir_handle = Arc::clone(&new_dir.dir);
-        self.run_blocking(move |d| d.hard_link(&old_path, &new_dir_handle, &new_path))
+        self.run_blocking(move |d| d.hard_link(&old_path, &*new_dir_handle, &new_path))
             .await?;
         Ok(())
     }
@@ -933,9 +1450,6 @@ impl Dir {
         flags: DescriptorFlags,
         allow_blocking_current_thread: bool,
     ) -> Result<Descriptor, ErrorCode> {
-        use cap_fs_ext::{FollowSymlinks, OpenOptionsFollowExt, OpenOptionsMaybeDirExt};
-        use system_interface::fs::{FdFlags, GetSetFdFlags};
-
         if !self.perms.contains(DirPerms::READ) {
             return Err(ErrorCode::NotPermitted);
         }
@@ -949,46 +1463,36 @@ impl Dir {
             }
         }
 
-        // Track whether we are creating file, for permission check:
-        let mut create = false;
         // Track open mode, for permission check and recording in created descriptor:
         let mut open_mode = OpenMode::empty();
-        // Construct the OpenOptions to give the OS:
-        let mut opts = cap_std::fs::OpenOptions::new();
-        opts.maybe_dir(true);
+        // Construct the options to give to the directory implementation:
+        let mut opts = OpenOptions::default();
 
         if oflags.contains(OpenFlags::CREATE) {
-            if oflags.contains(OpenFlags::EXCLUSIVE) {
-                opts.create_new(true);
-            } else {
-                opts.create(true);
-            }
-            create = true;
-            opts.write(true);
+            opts.create = true;
+            opts.exclusive = oflags.contains(OpenFlags::EXCLUSIVE);
+            opts.write = true;
             open_mode |= OpenMode::WRITE;
         }
 
         if oflags.contains(OpenFlags::TRUNCATE) {
-            opts.truncate(true).write(true);
+            opts.truncate = true;
+            opts.write = true;
         }
         if flags.contains(DescriptorFlags::READ) {
-            opts.read(true);
+            opts.read = true;
             open_mode |= OpenMode::READ;
         }
         if flags.contains(DescriptorFlags::WRITE) {
-            opts.write(true);
+            opts.write = true;
             open_mode |= OpenMode::WRITE;
         } else {
             // If not opened write, open read. This way the OS lets us open
             // the file, but we can use perms to reject use of the file later.
-            opts.read(true);
+            opts.read = true;
             open_mode |= OpenMode::READ;
         }
-        if path_flags.contains(PathFlags::SYMLINK_FOLLOW) {
-            opts.follow(FollowSymlinks::Yes);
-        } else {
-            opts.follow(FollowSymlinks::No);
-        }
+        opts.follow_symlinks = path_flags.contains(PathFlags::SYMLINK_FOLLOW);
 
         // These flags are not yet supported in cap-std:
         if flags.contains(DescriptorFlags::FILE_INTEGRITY_SYNC)
@@ -1005,62 +1509,35 @@ impl Dir {
             {
                 return Err(ErrorCode::Invalid);
             }
+            opts.directory = true;
         }
 
         // Now enforce this WasiCtx's permissions before letting the OS have
         // its shot:
-        if !self.perms.contains(DirPerms::MUTATE) && create {
+        if !self.perms.contains(DirPerms::MUTATE) && opts.create {
             return Err(ErrorCode::NotPermitted);
         }
         if !self.file_perms.contains(FilePerms::WRITE) && open_mode.contains(OpenMode::WRITE) {
             return Err(ErrorCode::NotPermitted);
         }
 
-        // Represents each possible outcome from the spawn_blocking operation.
-        // This makes sure we don't have to give spawn_blocking any way to
-        // manipulate the table.
-        enum OpenResult {
-            Dir(cap_std::fs::Dir),
-            File(cap_std::fs::File),
-            NotDir,
-        }
-
-        let opened = self
-            .run_blocking::<_, std::io::Result<OpenResult>>(move |d| {
-                let mut opened = d.open_with(&path, &opts)?;
-                if opened.metadata()?.is_dir() {
-                    Ok(OpenResult::Dir(cap_std::fs::Dir::from_std_file(
-                        opened.into_std(),
-                    )))
-                } else if oflags.contains(OpenFlags::DIRECTORY) {
-                    Ok(OpenResult::NotDir)
-                } else {
-                    // FIXME cap-std needs a nonblocking open option so that files reads and writes
-                    // are nonblocking. Instead we set it after opening here:
-                    let set_fd_flags = opened.new_set_fd_flags(FdFlags::NONBLOCK)?;
-                    opened.set_fd_flags(set_fd_flags)?;
-                    Ok(OpenResult::File(opened))
-                }
-            })
-            .await?;
+        let opened = self.run_blocking(move |d| d.open_at(&path, &opts)).await?;
 
         match opened {
-            OpenResult::Dir(dir) => Ok(Descriptor::Dir(Dir::new(
-                dir,
-                self.perms,
-                self.file_perms,
+            OpenResult::Dir(dir) => Ok(Descriptor::Dir(Dir {
+                dir: Arc::from(dir),
+                perms: self.perms,
+                file_perms: self.file_perms,
                 open_mode,
                 allow_blocking_current_thread,
-            ))),
+            })),
 
-            OpenResult::File(file) => Ok(Descriptor::File(File::new(
-                file,
-                self.file_perms,
+            OpenResult::File(file) => Ok(Descriptor::File(File {
+                file: Arc::from(file),
+                perms: self.file_perms,
                 open_mode,
                 allow_blocking_current_thread,
-            ))),
-
-            OpenResult::NotDir => Err(ErrorCode::NotDirectory),
+            })),
         }
     }
 
@@ -1095,7 +1572,7 @@ impl Dir {
             return Err(ErrorCode::NotPermitted);
         }
         let new_dir_handle = Arc::clone(&new_dir.dir);
-        self.run_blocking(move |d| d.rename(&old_path, &new_dir_handle, &new_path))
+        self.run_blocking(move |d| d.rename(&old_path, &*new_dir_handle, &new_path))
             .await?;
         Ok(())
     }
@@ -1105,10 +1582,6 @@ impl Dir {
         src_path: String,
         dest_path: String,
     ) -> Result<(), ErrorCode> {
-        // On windows, Dir.symlink is provided by DirExt
-        #[cfg(windows)]
-        use cap_fs_ext::DirExt;
-
         if !self.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted);
         }
@@ -1118,13 +1591,10 @@ impl Dir {
     }
 
     pub(crate) async fn unlink_file_at(&self, path: String) -> Result<(), ErrorCode> {
-        use cap_fs_ext::DirExt;
-
         if !self.perms.contains(DirPerms::MUTATE) {
             return Err(ErrorCode::NotPermitted);
         }
-        self.run_blocking(move |d| d.remove_file_or_symlink(&path))
-            .await?;
+        self.run_blocking(move |d| d.unlink_file(&path)).await?;
         Ok(())
     }
 
@@ -1134,14 +1604,9 @@ impl Dir {
         path: String,
     ) -> Result<MetadataHashValue, ErrorCode> {
         // No permissions check on metadata: if dir opened, allowed to stat it
+        let follow = path_flags.contains(PathFlags::SYMLINK_FOLLOW);
         let meta = self
-            .run_blocking(move |d| {
-                if path_flags.contains(PathFlags::SYMLINK_FOLLOW) {
-                    d.metadata(path)
-                } else {
-                    d.symlink_metadata(path)
-                }
-            })
+            .run_blocking(move |d| d.metadata_at(&path, follow))
             .await?;
         Ok(MetadataHashValue::from(&meta))
     }
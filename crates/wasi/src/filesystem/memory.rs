@@ -0,0 +1,1219 @@
+//! In-memory implementation of [`WasiDir`] and [`WasiFile`], optionally
+//! layered on top of a host directory.
+
+use crate::SystemTimeSpec;
+use crate::filesystem::{
+    DescriptorType, DirEntry, Metadata, OpenOptions, OpenResult, ReadDir, WasiDir, WasiFile,
+    symlink_loop,
+};
+use cap_fs_ext::DirExt as _;
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+use system_interface::fs::FileIoExt;
+
+/// Maximum number of symbolic links expanded while resolving a single path,
+/// matching Linux's limit.
+const MAX_SYMLINK_EXPANSIONS: usize = 40;
+
+/// Inode number of the root directory of every filesystem.
+const ROOT: u64 = 1;
+
+/// A directory whose contents live entirely in host memory.
+///
+/// This implements [`WasiDir`] and can be handed to guests with
+/// [`WasiCtxBuilder::preopened_virtual_dir`]. Nothing written by the guest
+/// ever reaches the host filesystem, and all of it is discarded once the last
+/// handle to the directory is dropped.
+///
+/// Two flavors are available:
+///
+/// * [`MemoryDir::new`] creates an empty tree.
+/// * [`MemoryDir::overlay`] creates a copy-on-write layer on top of a host
+///   directory. The guest sees the host directory's contents and may modify
+///   them freely, but modifications are only recorded in memory. Host
+///   directories are read lazily as they are visited and files are copied
+///   into memory the first time they're written to.
+///
+/// The amount of memory the guest may consume can be limited with
+/// [`MemoryDir::size_limit`].
+///
+/// Cloning a `MemoryDir` yields another handle to the same directory, which
+/// is useful to inspect what a guest has left behind, for example with
+/// [`MemoryDir::read`].
+///
+/// # Examples
+///
+/// ```
+/// use wasmtime_wasi::{DirPerms, FilePerms, WasiCtx};
+/// use wasmtime_wasi::filesystem::MemoryDir;
+///
+/// let scratch = MemoryDir::new().size_limit(16 << 20);
+/// scratch.write("input.txt", "hello")?;
+///
+/// let mut builder = WasiCtx::builder();
+/// builder.preopened_virtual_dir(scratch.clone(), "/tmp", DirPerms::all(), FilePerms::all());
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`WasiCtxBuilder::preopened_virtual_dir`]: crate::WasiCtxBuilder::preopened_virtual_dir
+pub struct MemoryDir {
+    fs: Arc<Fs>,
+    inode: u64,
+}
+
+struct MemoryFile {
+    fs: Arc<Fs>,
+    inode: u64,
+    readable: bool,
+    writable: bool,
+}
+
+struct Fs {
+    /// Synthetic device number used to distinguish objects in this
+    /// filesystem from those in others.
+    device: u64,
+    /// Directory underneath an overlay.
+    lower: Option<cap_std::fs::Dir>,
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    nodes: HashMap<u64, Node>,
+    next_inode: u64,
+    /// Number of bytes currently counted against `size_limit`.
+    used: u64,
+    size_limit: Option<u64>,
+    /// Part of the lower directory which the operation in progress needs
+    /// copied into memory, see [`Fs::run`].
+    pending: Option<Load>,
+}
+
+struct Node {
+    kind: NodeKind,
+    /// Number of directory entries referring to this node.
+    links: u64,
+    /// Number of open `MemoryDir`s and `MemoryFile`s referring to this node.
+    ///
+    /// Nodes are kept alive while either this or `links` is nonzero.
+    handles: u64,
+    /// Bytes of file contents or symlink target counted against the size
+    /// limit on behalf of this node.
+    charged: u64,
+    accessed: SystemTime,
+    modified: SystemTime,
+    changed: SystemTime,
+}
+
+enum NodeKind {
+    File(Contents),
+    Dir(DirNode),
+    Symlink(String),
+}
+
+#[derive(Clone)]
+enum Contents {
+    Owned(Vec<u8>),
+    /// A file of an overlay's lower directory which has not been copied into
+    /// memory yet.
+    Lower {
+        path: PathBuf,
+        len: u64,
+    },
+}
+
+struct DirNode {
+    entries: BTreeMap<String, Link>,
+    /// Inode of the directory containing this one, or itself for the root.
+    parent: u64,
+    /// Path of the lower directory of an overlay whose entries have not been
+    /// copied into `entries` yet.
+    lower: Option<PathBuf>,
+}
+
+/// Part of an overlay's lower directory to copy into memory.
+enum Load {
+    /// The entries of the lower directory at `path` for the directory `inode`.
+    Dir { inode: u64, path: PathBuf },
+    /// The contents of the lower file at `path` for the file `inode`.
+    File { inode: u64, path: PathBuf },
+}
+
+struct Link {
+    inode: u64,
+    /// Bytes counted against the size limit for the name of this entry.
+    /// Entries from an overlay's lower directory are free.
+    size: u64,
+}
+
+/// How a symbolic link in the final component of a path is treated during
+/// lookup.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Follow {
+    /// The link is always followed.
+    Yes,
+    /// The link is followed only if the path has a trailing slash.
+    No,
+    /// The link is never followed, used for operations which act on
+    /// directory entries themselves such as `unlink` or `rename`.
+    Never,
+}
+
+/// The result of resolving a path with [`Inner::lookup`].
+struct Lookup {
+    /// The directory containing the final component of the path along with
+    /// its name. This is `None` if the path ended in `.` or `..`.
+    parent: Option<(u64, String)>,
+    /// The object the path refers to, if it exists.
+    inode: Option<u64>,
+    /// Whether the path ended in `/`, requiring it to refer to a directory.
+    trailing_slash: bool,
+}
+
+fn next_device() -> u64 {
+    // Start from a value unlikely to collide with the device numbers of host
+    // filesystems.
+    static NEXT: AtomicU64 = AtomicU64::new(0xffff_0000_0000_0000);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+fn to_std_time(spec: SystemTimeSpec) -> SystemTime {
+    match spec {
+        SystemTimeSpec::SymbolicNow => SystemTime::now(),
+        SystemTimeSpec::Absolute(time) => time.into_std(),
+    }
+}
+
+impl MemoryDir {
+    /// Creates a new, empty, in-memory directory.
+    pub fn new() -> MemoryDir {
+        MemoryDir::with_root(None, DirNode::new(ROOT, None))
+    }
+
+    /// Creates a copy-on-write overlay on top of the host directory `lower`.
+    ///
+    /// The host directory is never modified through the returned directory.
+    /// Changes made to the host directory after the overlay is created may
+    /// or may not be visible, depending on whether the overlay has already
+    /// read the affected parts of it.
+    pub fn overlay(lower: cap_std::fs::Dir) -> MemoryDir {
+        MemoryDir::with_root(Some(lower), DirNode::new(ROOT, Some(PathBuf::new())))
+    }
+
+    fn with_root(lower: Option<cap_std::fs::Dir>, root: DirNode) -> MemoryDir {
+        let mut root = Node::new(NodeKind::Dir(root));
+        root.handles = 1;
+        let inner = Inner {
+            nodes: HashMap::from([(ROOT, root)]),
+            next_inode: ROOT + 1,
+            used: 0,
+            size_limit: None,
+            pending: None,
+        };
+        MemoryDir {
+            fs: Arc::new(Fs {
+                device: next_device(),
+                lower,
+                inner: Mutex::new(inner),
+            }),
+            inode: ROOT,
+        }
+    }
+
+    /// Limits the number of bytes this filesystem may hold.
+    ///
+    /// The limit covers the contents of files, the targets of symbolic links
+    /// and the names of directory entries. Operations which would exceed it
+    /// fail with [`io::ErrorKind::StorageFull`], which guests observe as
+    /// `insufficient-space`. Data read from the lower directory of an
+    /// overlay is only counted once it has been copied into memory.
+    ///
+    /// The limit applies to the whole filesystem, including through other
+    /// handles to it.
+    pub fn size_limit(self, bytes: u64) -> MemoryDir {
+        self.fs.lock().size_limit = Some(bytes);
+        self
+    }
+
+    /// Returns the number of bytes currently counted against the limit
+    /// configured with [`MemoryDir::size_limit`].
+    pub fn size(&self) -> u64 {
+        self.fs.lock().used
+    }
+
+    /// Reads the entire contents of the file at `path`, relative to this
+    /// directory.
+    pub fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        let contents = self.fs.run(|inner| {
+            let inode = inner.lookup(self.inode, path, Follow::Yes)?.existing()?;
+            match &inner.node(inode).kind {
+                NodeKind::File(contents) => Ok(contents.clone()),
+                NodeKind::Dir(_) => Err(io::ErrorKind::IsADirectory.into()),
+                NodeKind::Symlink(_) => Err(io::ErrorKind::InvalidInput.into()),
+            }
+        })?;
+        match contents {
+            Contents::Owned(data) => Ok(data),
+            Contents::Lower { path, .. } => self.fs.lower().read(path),
+        }
+    }
+
+    /// Writes `contents` to the file at `path`, relative to this directory,
+    /// creating it if necessary and replacing any previous contents.
+    pub fn write(&self, path: &str, contents: impl AsRef<[u8]>) -> io::Result<()> {
+        let options = OpenOptions {
+            follow_symlinks: true,
+            create: true,
+            truncate: true,
+            write: true,
+            ..OpenOptions::default()
+        };
+        self.fs.run(|inner| {
+            let inode = inner.open(self.inode, path, &options)?;
+            inner.write_at(inode, contents.as_ref(), 0)
+        })?;
+        Ok(())
+    }
+
+    fn handle(&self, inner: &mut Inner, inode: u64) -> MemoryDir {
+        inner.node_mut(inode).handles += 1;
+        MemoryDir {
+            fs: self.fs.clone(),
+            inode,
+        }
+    }
+
+    /// Returns `dir` as a `MemoryDir` if it belongs to the same filesystem as
+    /// `self`.
+    fn same_fs<'a>(&self, dir: &'a dyn WasiDir) -> io::Result<&'a MemoryDir> {
+        dir.as_any()
+            .downcast_ref::<MemoryDir>()
+            .filter(|dir| Arc::ptr_eq(&dir.fs, &self.fs))
+            .ok_or_else(|| io::ErrorKind::CrossesDevices.into())
+    }
+}
+
+impl Default for MemoryDir {
+    fn default() -> MemoryDir {
+        MemoryDir::new()
+    }
+}
+
+impl Clone for MemoryDir {
+    fn clone(&self) -> MemoryDir {
+        self.handle(&mut self.fs.lock(), self.inode)
+    }
+}
+
+impl Drop for MemoryDir {
+    fn drop(&mut self) {
+        self.fs.close(self.inode);
+    }
+}
+
+impl Drop for MemoryFile {
+    fn drop(&mut self) {
+        self.fs.close(self.inode);
+    }
+}
+
+impl Fs {
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap()
+    }
+
+    fn lower(&self) -> &cap_std::fs::Dir {
+        self.lower
+            .as_ref()
+            .expect("only overlays have lower directories")
+    }
+
+    /// Runs `op` with the lock held.
+    ///
+    /// Operations on an overlay stop when they need part of the lower
+    /// directory which hasn't been copied into memory yet. That part is then
+    /// read from the host with the lock released, so that a slow host
+    /// filesystem doesn't hold up every other operation, and `op` is retried.
+    /// Operations must therefore not modify anything before they can fail
+    /// this way.
+    fn run<T>(&self, mut op: impl FnMut(&mut Inner) -> io::Result<T>) -> io::Result<T> {
+        loop {
+            let mut inner = self.lock();
+            let result = op(&mut inner);
+            let Some(load) = inner.pending.take() else {
+                return result;
+            };
+            drop(inner);
+            match load {
+                Load::Dir { inode, path } => {
+                    let children = self.read_lower_dir(inode, &path)?;
+                    self.lock().add_lower_entries(inode, &path, children);
+                }
+                Load::File { inode, path } => {
+                    let data = self.lower().read(&path)?;
+                    self.lock().copy_up(inode, &path, data)?;
+                }
+            }
+        }
+    }
+
+    /// Reads the entries of the lower directory at `path` into nodes for the
+    /// directory `inode`.
+    fn read_lower_dir(&self, inode: u64, path: &Path) -> io::Result<Vec<(String, Node)>> {
+        let lower = self.lower();
+        let opened;
+        let dir = if path.as_os_str().is_empty() {
+            lower
+        } else {
+            opened = lower.open_dir_nofollow(path)?;
+            &opened
+        };
+
+        let mut children = Vec::new();
+        for entry in dir.entries()? {
+            let entry = entry?;
+            // Names which aren't valid UTF-8 can't be named by guests anyway.
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            let meta = entry.metadata()?;
+            let kind = if meta.is_dir() {
+                NodeKind::Dir(DirNode::new(inode, Some(path.join(&name))))
+            } else if meta.file_type().is_symlink() {
+                // Links which can't be read through `cap_std`, such as
+                // absolute ones, couldn't be followed by the guest either.
+                match dir
+                    .read_link(&name)
+                    .map(|t| t.into_os_string().into_string())
+                {
+                    Ok(Ok(target)) => NodeKind::Symlink(target),
+                    _ => continue,
+                }
+            } else {
+                NodeKind::File(Contents::Lower {
+                    path: path.join(&name),
+                    len: meta.len(),
+                })
+            };
+            let mut node = Node::new(kind);
+            if let Ok(time) = meta.accessed() {
+                node.accessed = time.into_std();
+            }
+            if let Ok(time) = meta.modified() {
+                node.modified = time.into_std();
+                node.changed = node.modified;
+            }
+            children.push((name, node));
+        }
+        Ok(children)
+    }
+
+    fn read_at(&self, inode: u64, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let path = match &self.lock().node(inode).kind {
+            NodeKind::File(Contents::Owned(data)) => {
+                let start = usize::try_from(offset)
+                    .unwrap_or(usize::MAX)
+                    .min(data.len());
+                let n = buf.len().min(data.len() - start);
+                buf[..n].copy_from_slice(&data[start..][..n]);
+                return Ok(n);
+            }
+            NodeKind::File(Contents::Lower { path, .. }) => path.clone(),
+            NodeKind::Dir(_) => return Err(io::ErrorKind::IsADirectory.into()),
+            NodeKind::Symlink(_) => return Err(io::ErrorKind::InvalidInput.into()),
+        };
+        // Like other reads of the lower directory, this happens without the
+        // lock held.
+        FileIoExt::read_at(&self.lower().open(path)?, buf, offset)
+    }
+
+    fn close(&self, inode: u64) {
+        // Avoid a double panic if a panic happened while the lock was held.
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        let node = inner.node_mut(inode);
+        node.handles -= 1;
+        if node.handles == 0 && node.links == 0 {
+            inner.free(inode);
+        }
+    }
+
+    fn metadata(&self, inner: &Inner, inode: u64) -> Metadata {
+        let node = inner.node(inode);
+        let (file_type, size) = match &node.kind {
+            NodeKind::File(Contents::Owned(data)) => {
+                (DescriptorType::RegularFile, data.len() as u64)
+            }
+            NodeKind::File(Contents::Lower { len, .. }) => (DescriptorType::RegularFile, *len),
+            NodeKind::Dir(_) => (DescriptorType::Directory, 0),
+            NodeKind::Symlink(target) => (DescriptorType::SymbolicLink, target.len() as u64),
+        };
+        Metadata {
+            file_type,
+            link_count: node.links,
+            size,
+            accessed: Some(node.accessed),
+            modified: Some(node.modified),
+            created: Some(node.changed),
+            device: self.device,
+            inode,
+        }
+    }
+}
+
+impl Node {
+    fn new(kind: NodeKind) -> Node {
+        let now = SystemTime::now();
+        Node {
+            kind,
+            links: 1,
+            handles: 0,
+            charged: 0,
+            accessed: now,
+            modified: now,
+            changed: now,
+        }
+    }
+
+    fn file_type(&self) -> DescriptorType {
+        match self.kind {
+            NodeKind::File(_) => DescriptorType::RegularFile,
+            NodeKind::Dir(_) => DescriptorType::Directory,
+            NodeKind::Symlink(_) => DescriptorType::SymbolicLink,
+        }
+    }
+
+    fn is_dir(&self) -> bool {
+        matches!(self.kind, NodeKind::Dir(_))
+    }
+
+    fn touch(&mut self) {
+        let now = SystemTime::now();
+        self.modified = now;
+        self.changed = now;
+    }
+}
+
+impl DirNode {
+    fn new(parent: u64, lower: Option<PathBuf>) -> DirNode {
+        DirNode {
+            entries: BTreeMap::new(),
+            parent,
+            lower,
+        }
+    }
+}
+
+impl Lookup {
+    fn existing(&self) -> io::Result<u64> {
+        self.inode.ok_or_else(|| io::ErrorKind::NotFound.into())
+    }
+
+    /// Returns the parent directory and name of a path which is about to be
+    /// created.
+    fn new_entry(self) -> io::Result<(u64, String)> {
+        if self.inode.is_some() {
+            return Err(io::ErrorKind::AlreadyExists.into());
+        }
+        if self.trailing_slash {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        Ok(self.parent.expect("missing entries always have a parent"))
+    }
+}
+
+impl Inner {
+    fn node(&self, inode: u64) -> &Node {
+        &self.nodes[&inode]
+    }
+
+    fn node_mut(&mut self, inode: u64) -> &mut Node {
+        self.nodes.get_mut(&inode).unwrap()
+    }
+
+    fn insert(&mut self, node: Node) -> u64 {
+        let inode = self.next_inode;
+        self.next_inode += 1;
+        self.nodes.insert(inode, node);
+        inode
+    }
+
+    /// Replaces `old` bytes counted against the size limit with `new` bytes.
+    fn reserve(&mut self, old: u64, new: u64) -> io::Result<()> {
+        let used = self.used - old;
+        let used = used.checked_add(new).ok_or(io::ErrorKind::StorageFull)?;
+        if new > old && self.size_limit.is_some_and(|limit| used > limit) {
+            return Err(io::ErrorKind::StorageFull.into());
+        }
+        self.used = used;
+        Ok(())
+    }
+
+    /// Returns the directory `inode`, first copying in the entries of its
+    /// lower directory if it hasn't been visited yet.
+    fn dir_mut(&mut self, inode: u64) -> io::Result<&mut DirNode> {
+        self.populate(inode)?;
+        match &mut self.node_mut(inode).kind {
+            NodeKind::Dir(dir) => Ok(dir),
+            _ => Err(io::ErrorKind::NotADirectory.into()),
+        }
+    }
+
+    fn populate(&mut self, inode: u64) -> io::Result<()> {
+        if let NodeKind::Dir(DirNode {
+            lower: Some(path), ..
+        }) = &self.node(inode).kind
+        {
+            let path = path.clone();
+            return Err(self.load(Load::Dir { inode, path }));
+        }
+        Ok(())
+    }
+
+    /// Records that `load` has to happen before the operation in progress
+    /// can continue, returning the error which aborts it until then.
+    fn load(&mut self, load: Load) -> io::Error {
+        self.pending = Some(load);
+        io::ErrorKind::WouldBlock.into()
+    }
+
+    /// Adds the entries read from the lower directory at `path` to the
+    /// directory `inode`, unless that already happened while they were
+    /// being read.
+    fn add_lower_entries(&mut self, inode: u64, path: &Path, children: Vec<(String, Node)>) {
+        let unpopulated = self.nodes.get(&inode).is_some_and(
+            |node| matches!(&node.kind, NodeKind::Dir(DirNode { lower: Some(p), .. }) if p == path),
+        );
+        if !unpopulated {
+            return;
+        }
+        let mut links = Vec::new();
+        for (name, node) in children {
+            let inode = self.insert(node);
+            links.push((name, Link { inode, size: 0 }));
+        }
+        if let NodeKind::Dir(dir) = &mut self.node_mut(inode).kind {
+            dir.entries.extend(links);
+            dir.lower = None;
+        }
+    }
+
+    /// Replaces the file `inode`, backed by the lower file at `path`, with
+    /// `data` read from it, unless that already happened while it was being
+    /// read.
+    fn copy_up(&mut self, inode: u64, path: &Path, data: Vec<u8>) -> io::Result<()> {
+        let lower = self.nodes.get(&inode).is_some_and(|node| {
+            matches!(&node.kind, NodeKind::File(Contents::Lower { path: p, .. }) if p == path)
+        });
+        if !lower {
+            return Ok(());
+        }
+        self.reserve(0, data.len() as u64)?;
+        let node = self.node_mut(inode);
+        node.charged = data.len() as u64;
+        node.kind = NodeKind::File(Contents::Owned(data));
+        Ok(())
+    }
+
+    /// Resolves `path` relative to the directory `start`.
+    ///
+    /// Like `cap_std` this refuses to resolve absolute paths or paths which
+    /// would leave `start` through `..`, including via symbolic links.
+    fn lookup(&mut self, start: u64, path: &str, follow: Follow) -> io::Result<Lookup> {
+        if path.is_empty() {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        if path.contains('\0') {
+            return Err(io::ErrorKind::InvalidInput.into());
+        }
+        if path.starts_with('/') {
+            return Err(io::ErrorKind::PermissionDenied.into());
+        }
+
+        let mut trailing_slash = path.ends_with('/');
+        // Directories traversed so far, used to implement `..`.
+        let mut stack = vec![start];
+        // Components left to resolve, in reverse order.
+        let mut todo = components(path);
+        let mut parent = None;
+        let mut expansions = 0;
+
+        while let Some(name) = todo.pop() {
+            let dir = *stack.last().unwrap();
+            match name.as_str() {
+                "." => {
+                    parent = None;
+                    continue;
+                }
+                ".." => {
+                    if stack.len() == 1 {
+                        return Err(io::ErrorKind::PermissionDenied.into());
+                    }
+                    stack.pop();
+                    parent = None;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let last = todo.is_empty();
+            let Some(child) = self.dir_mut(dir)?.entries.get(&name).map(|l| l.inode) else {
+                if !last {
+                    return Err(io::ErrorKind::NotFound.into());
+                }
+                return Ok(Lookup {
+                    parent: Some((dir, name)),
+                    inode: None,
+                    trailing_slash,
+                });
+            };
+
+            match &self.node(child).kind {
+                NodeKind::Dir(_) => {
+                    stack.push(child);
+                    parent = Some((dir, name));
+                }
+                NodeKind::Symlink(target)
+                    if !last
+                        || follow == Follow::Yes
+                        || (follow == Follow::No && trailing_slash) =>
+                {
+                    expansions += 1;
+                    if expansions > MAX_SYMLINK_EXPANSIONS {
+                        return Err(symlink_loop());
+                    }
+                    if target.starts_with('/') {
+                        return Err(io::ErrorKind::PermissionDenied.into());
+                    }
+                    if target.is_empty() {
+                        return Err(io::ErrorKind::NotFound.into());
+                    }
+                    if last && target.ends_with('/') {
+                        trailing_slash = true;
+                    }
+                    todo.extend(components(target));
+                }
+                _ => {
+                    if !last || trailing_slash {
+                        return Err(io::ErrorKind::NotADirectory.into());
+                    }
+                    return Ok(Lookup {
+                        parent: Some((dir, name)),
+                        inode: Some(child),
+                        trailing_slash,
+                    });
+                }
+            }
+        }
+
+        Ok(Lookup {
+            parent,
+            inode: Some(*stack.last().unwrap()),
+            trailing_slash,
+        })
+    }
+
+    /// Creates a new entry `name` in the directory `parent`.
+    fn create(&mut self, parent: u64, name: String, kind: NodeKind) -> io::Result<u64> {
+        // Entries can't be added to directories which have been removed.
+        if self.node(parent).links == 0 {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        let charged = match &kind {
+            NodeKind::Symlink(target) => target.len() as u64,
+            _ => 0,
+        };
+        let size = name.len() as u64;
+        self.reserve(0, size + charged)?;
+        let mut node = Node::new(kind);
+        node.charged = charged;
+        let inode = self.insert(node);
+        self.dir_mut(parent)?
+            .entries
+            .insert(name, Link { inode, size });
+        self.node_mut(parent).touch();
+        Ok(inode)
+    }
+
+    /// Removes the entry `name` from the directory `parent`.
+    fn remove_entry(&mut self, parent: u64, name: &str) -> io::Result<()> {
+        let link = self
+            .dir_mut(parent)?
+            .entries
+            .remove(name)
+            .ok_or(io::ErrorKind::NotFound)?;
+        self.used -= link.size;
+        self.node_mut(parent).touch();
+        let node = self.node_mut(link.inode);
+        node.links -= 1;
+        node.changed = SystemTime::now();
+        if node.links == 0 && node.handles == 0 {
+            self.free(link.inode);
+        }
+        Ok(())
+    }
+
+    fn free(&mut self, inode: u64) {
+        let node = self.nodes.remove(&inode).unwrap();
+        self.used -= node.charged;
+    }
+
+    fn open(&mut self, dir: u64, path: &str, options: &OpenOptions) -> io::Result<u64> {
+        // Exclusive creation fails on any existing entry, including symbolic
+        // links, so don't follow them in that case.
+        let follow = if options.follow_symlinks && !(options.create && options.exclusive) {
+            Follow::Yes
+        } else {
+            Follow::No
+        };
+        let found = self.lookup(dir, path, follow)?;
+        let inode = match found.inode {
+            Some(_) if options.create && options.exclusive => {
+                return Err(io::ErrorKind::AlreadyExists.into());
+            }
+            Some(inode) => inode,
+            None if !options.create => return Err(io::ErrorKind::NotFound.into()),
+            None if found.trailing_slash => return Err(io::ErrorKind::IsADirectory.into()),
+            None => {
+                let (parent, name) = found.parent.unwrap();
+                self.create(parent, name, NodeKind::File(Contents::Owned(Vec::new())))?
+            }
+        };
+        match &self.node(inode).kind {
+            // Opening a symbolic link itself is `O_NOFOLLOW`, which fails.
+            NodeKind::Symlink(_) => Err(symlink_loop()),
+            NodeKind::Dir(_) if options.write || options.truncate => {
+                Err(io::ErrorKind::IsADirectory.into())
+            }
+            NodeKind::File(_) if options.directory => Err(io::ErrorKind::NotADirectory.into()),
+            NodeKind::File(_) if options.truncate => {
+                self.set_len(inode, 0)?;
+                Ok(inode)
+            }
+            _ => Ok(inode),
+        }
+    }
+
+    fn file_len(&self, inode: u64) -> io::Result<u64> {
+        match &self.node(inode).kind {
+            NodeKind::File(Contents::Owned(data)) => Ok(data.len() as u64),
+            NodeKind::File(Contents::Lower { len, .. }) => Ok(*len),
+            NodeKind::Dir(_) => Err(io::ErrorKind::IsADirectory.into()),
+            NodeKind::Symlink(_) => Err(io::ErrorKind::InvalidInput.into()),
+        }
+    }
+
+    /// Returns the contents of the file `inode`, copying them up from the
+    /// lower directory of an overlay if necessary.
+    fn contents_mut(&mut self, inode: u64) -> io::Result<&mut Vec<u8>> {
+        if let NodeKind::File(Contents::Lower { path, .. }) = &self.node(inode).kind {
+            let path = path.clone();
+            return Err(self.load(Load::File { inode, path }));
+        }
+        match &mut self.node_mut(inode).kind {
+            NodeKind::File(Contents::Owned(data)) => Ok(data),
+            NodeKind::Dir(_) => Err(io::ErrorKind::IsADirectory.into()),
+            _ => Err(io::ErrorKind::InvalidInput.into()),
+        }
+    }
+
+    fn set_len(&mut self, inode: u64, size: u64) -> io::Result<()> {
+        let new_len = usize::try_from(size).map_err(|_| io::ErrorKind::FileTooLarge)?;
+        if size == 0 {
+            // Don't bother copying up files which are about to be truncated.
+            if let NodeKind::File(contents @ Contents::Lower { .. }) =
+                &mut self.node_mut(inode).kind
+            {
+                *contents = Contents::Owned(Vec::new());
+            }
+        }
+        let old = self.contents_mut(inode)?.len() as u64;
+        self.reserve(old, size)?;
+        let data = self.contents_mut(inode)?;
+        let reserved = data.try_reserve(new_len.saturating_sub(data.len())).is_ok();
+        if !reserved {
+            self.reserve(size, old)?;
+            return Err(io::ErrorKind::OutOfMemory.into());
+        }
+        data.resize(new_len, 0);
+        let node = self.node_mut(inode);
+        node.charged = size;
+        node.touch();
+        Ok(())
+    }
+
+    fn write_at(&mut self, inode: u64, buf: &[u8], offset: u64) -> io::Result<usize> {
+        // Empty writes don't extend the file, even past its end.
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let end = offset
+            .checked_add(buf.len() as u64)
+            .ok_or(io::ErrorKind::FileTooLarge)?;
+        if end > self.file_len(inode)? {
+            self.set_len(inode, end)?;
+        }
+        let data = self.contents_mut(inode)?;
+        // Both of these fit in a `usize` as the file has been extended to
+        // `end` above.
+        data[offset as usize..end as usize].copy_from_slice(buf);
+        self.node_mut(inode).touch();
+        Ok(buf.len())
+    }
+
+    fn set_times(
+        &mut self,
+        inode: u64,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) {
+        let node = self.node_mut(inode);
+        if let Some(atim) = atim {
+            node.accessed = to_std_time(atim);
+        }
+        if let Some(mtim) = mtim {
+            node.modified = to_std_time(mtim);
+        }
+        node.changed = SystemTime::now();
+    }
+
+    /// Returns whether `inode` is `ancestor` or contained within it.
+    fn is_within(&self, mut inode: u64, ancestor: u64) -> bool {
+        loop {
+            if inode == ancestor {
+                return true;
+            }
+            let parent = match &self.node(inode).kind {
+                NodeKind::Dir(dir) => dir.parent,
+                _ => return false,
+            };
+            if parent == inode {
+                return false;
+            }
+            inode = parent;
+        }
+    }
+}
+
+/// Splits `path` into its components in reverse order, ready to be popped off
+/// the end.
+fn components(path: &str) -> Vec<String> {
+    path.split('/')
+        .filter(|c| !c.is_empty())
+        .rev()
+        .map(String::from)
+        .collect()
+}
+
+impl WasiDir for MemoryDir {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn open_at(&self, path: &str, options: &OpenOptions) -> io::Result<OpenResult> {
+        self.fs.run(|inner| {
+            let inode = inner.open(self.inode, path, options)?;
+            if inner.node(inode).is_dir() {
+                Ok(OpenResult::Dir(Box::new(self.handle(inner, inode))))
+            } else {
+                inner.node_mut(inode).handles += 1;
+                Ok(OpenResult::File(Box::new(MemoryFile {
+                    fs: self.fs.clone(),
+                    inode,
+                    readable: options.read,
+                    writable: options.write,
+                })))
+            }
+        })
+    }
+
+    fn metadata(&self) -> io::Result<Metadata> {
+        Ok(self.fs.metadata(&self.fs.lock(), self.inode))
+    }
+
+    fn metadata_at(&self, path: &str, follow_symlinks: bool) -> io::Result<Metadata> {
+        let follow = if follow_symlinks {
+            Follow::Yes
+        } else {
+            Follow::No
+        };
+        self.fs.run(|inner| {
+            let inode = inner.lookup(self.inode, path, follow)?.existing()?;
+            Ok(self.fs.metadata(inner, inode))
+        })
+    }
+
+    fn read_dir(&self) -> io::Result<ReadDir> {
+        self.fs.run(|inner| {
+            let inodes = inner
+                .dir_mut(self.inode)?
+                .entries
+                .iter()
+                .map(|(name, link)| (name.clone(), link.inode))
+                .collect::<Vec<_>>();
+            let entries = inodes
+                .into_iter()
+                .map(|(name, inode)| {
+                    Ok(DirEntry {
+                        name,
+                        file_type: inner.node(inode).file_type(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            Ok(Box::new(entries.into_iter()) as ReadDir)
+        })
+    }
+
+    fn read_link(&self, path: &str) -> io::Result<PathBuf> {
+        self.fs.run(|inner| {
+            let inode = inner.lookup(self.inode, path, Follow::Never)?.existing()?;
+            match &inner.node(inode).kind {
+                NodeKind::Symlink(target) => Ok(target.into()),
+                _ => Err(io::ErrorKind::InvalidInput.into()),
+            }
+        })
+    }
+
+    fn create_dir(&self, path: &str) -> io::Result<()> {
+        self.fs.run(|inner| {
+            let found = inner.lookup(self.inode, path, Follow::No)?;
+            if found.inode.is_some() {
+                return Err(io::ErrorKind::AlreadyExists.into());
+            }
+            let (parent, name) = found.parent.unwrap();
+            inner.create(parent, name, NodeKind::Dir(DirNode::new(parent, None)))?;
+            Ok(())
+        })
+    }
+
+    fn remove_dir(&self, path: &str) -> io::Result<()> {
+        self.fs.run(|inner| {
+            let found = inner.lookup(self.inode, path, Follow::Never)?;
+            let inode = found.existing()?;
+            // `rmdir` of a path ending in `.` or `..` is invalid.
+            let (parent, name) = found.parent.ok_or(io::ErrorKind::InvalidInput)?;
+            if !inner.dir_mut(inode)?.entries.is_empty() {
+                return Err(io::ErrorKind::DirectoryNotEmpty.into());
+            }
+            inner.remove_entry(parent, &name)
+        })
+    }
+
+    fn unlink_file(&self, path: &str) -> io::Result<()> {
+        self.fs.run(|inner| {
+            let found = inner.lookup(self.inode, path, Follow::Never)?;
+            let inode = found.existing()?;
+            if inner.node(inode).is_dir() {
+                return Err(io::ErrorKind::IsADirectory.into());
+            }
+            let (parent, name) = found.parent.unwrap();
+            inner.remove_entry(parent, &name)
+        })
+    }
+
+    fn symlink(&self, src_path: &str, dest_path: &str) -> io::Result<()> {
+        // Like `cap_std` refuse to create links which could only ever point
+        // outside of this filesystem.
+        if src_path.starts_with('/') {
+            return Err(io::ErrorKind::PermissionDenied.into());
+        }
+        self.fs.run(|inner| {
+            let (parent, name) = inner
+                .lookup(self.inode, dest_path, Follow::Never)?
+                .new_entry()?;
+            inner.create(parent, name, NodeKind::Symlink(src_path.to_string()))?;
+            Ok(())
+        })
+    }
+
+    fn rename(&self, old_path: &str, new_dir: &dyn WasiDir, new_path: &str) -> io::Result<()> {
+        let new_dir = self.same_fs(new_dir)?;
+        self.fs.run(|inner| {
+            let src = inner.lookup(self.inode, old_path, Follow::Never)?;
+            let src_inode = src.existing()?;
+            let (src_parent, src_name) = src.parent.ok_or(io::ErrorKind::InvalidInput)?;
+            let src_is_dir = inner.node(src_inode).is_dir();
+
+            let dst = inner.lookup(new_dir.inode, new_path, Follow::Never)?;
+            if dst.trailing_slash && !src_is_dir {
+                return Err(io::ErrorKind::NotADirectory.into());
+            }
+            let (dst_parent, dst_name) = dst.parent.ok_or(io::ErrorKind::InvalidInput)?;
+            if inner.node(dst_parent).links == 0 {
+                return Err(io::ErrorKind::NotFound.into());
+            }
+            let mut replaced = 0;
+            if let Some(dst_inode) = dst.inode {
+                if dst_inode == src_inode {
+                    return Ok(());
+                }
+                match (src_is_dir, inner.node(dst_inode).is_dir()) {
+                    (true, false) => return Err(io::ErrorKind::NotADirectory.into()),
+                    (false, true) => return Err(io::ErrorKind::IsADirectory.into()),
+                    (true, true) => {
+                        if !inner.dir_mut(dst_inode)?.entries.is_empty() {
+                            return Err(io::ErrorKind::DirectoryNotEmpty.into());
+                        }
+                    }
+                    (false, false) => {}
+                }
+                replaced = inner.dir_mut(dst_parent)?.entries[&dst_name].size;
+            }
+            // A directory can't be moved inside of itself.
+            if src_is_dir && inner.is_within(dst_parent, src_inode) {
+                return Err(io::ErrorKind::InvalidInput.into());
+            }
+
+            // Check the size limit against the final state up front, the names of
+            // the entries removed below are released along with them.
+            let size = dst_name.len() as u64;
+            inner.reserve(replaced, size)?;
+            inner.used += replaced;
+            if dst.inode.is_some() {
+                inner.remove_entry(dst_parent, &dst_name)?;
+            }
+            let link = inner
+                .dir_mut(src_parent)?
+                .entries
+                .remove(&src_name)
+                .unwrap();
+            inner.used -= link.size;
+            inner.node_mut(src_parent).touch();
+            inner.dir_mut(dst_parent)?.entries.insert(
+                dst_name,
+                Link {
+                    inode: src_inode,
+                    size,
+                },
+            );
+            inner.node_mut(dst_parent).touch();
+            let node = inner.node_mut(src_inode);
+            node.changed = SystemTime::now();
+            if let NodeKind::Dir(dir) = &mut node.kind {
+                dir.parent = dst_parent;
+            }
+            Ok(())
+        })
+    }
+
+    fn hard_link(&self, old_path: &str, new_dir: &dyn WasiDir, new_path: &str) -> io::Result<()> {
+        let new_dir = self.same_fs(new_dir)?;
+        self.fs.run(|inner| {
+            let inode = inner
+                .lookup(self.inode, old_path, Follow::Never)?
+                .existing()?;
+            if inner.node(inode).is_dir() {
+                return Err(io::ErrorKind::PermissionDenied.into());
+            }
+            let (parent, name) = inner
+                .lookup(new_dir.inode, new_path, Follow::Never)?
+                .new_entry()?;
+            if inner.node(parent).links == 0 {
+                return Err(io::ErrorKind::NotFound.into());
+            }
+            let size = name.len() as u64;
+            inner.reserve(0, size)?;
+            inner
+                .dir_mut(parent)?
+                .entries
+                .insert(name, Link { inode, size });
+            inner.node_mut(parent).touch();
+            let node = inner.node_mut(inode);
+            node.links += 1;
+            node.changed = SystemTime::now();
+            Ok(())
+        })
+    }
+
+    fn set_times(
+        &self,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        self.fs.lock().set_times(self.inode, atim, mtim);
+        Ok(())
+    }
+
+    fn set_times_at(
+        &self,
+        path: &str,
+        follow_symlinks: bool,
+        mut atim: Option<SystemTimeSpec>,
+        mut mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        let follow = if follow_symlinks {
+            Follow::Yes
+        } else {
+            Follow::No
+        };
+        self.fs.run(|inner| {
+            let inode = inner.lookup(self.inode, path, follow)?.existing()?;
+            // Only the lookup may be retried, so this is reached once.
+            inner.set_times(inode, atim.take(), mtim.take());
+            Ok(())
+        })
+    }
+}
+
+impl MemoryFile {
+    /// Fails unless the file was opened in the mode required for an
+    /// operation, like the host does for regular files.
+    fn check(&self, mode: bool) -> io::Result<()> {
+        if mode {
+            Ok(())
+        } else {
+            Err(io::ErrorKind::PermissionDenied.into())
+        }
+    }
+}
+
+impl WasiFile for MemoryFile {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.check(self.readable)?;
+        self.fs.read_at(self.inode, buf, offset)
+    }
+
+    fn metadata(&self) -> io::Result<Metadata> {
+        Ok(self.fs.metadata(&self.fs.lock(), self.inode))
+    }
+
+    fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        self.check(self.writable)?;
+        self.fs.run(|inner| inner.write_at(self.inode, buf, offset))
+    }
+
+    fn append(&self, buf: &[u8]) -> io::Result<usize> {
+        self.check(self.writable)?;
+        self.fs.run(|inner| {
+            let len = inner.file_len(self.inode)?;
+            inner.write_at(self.inode, buf, len)
+        })
+    }
+
+    fn set_len(&self, size: u64) -> io::Result<()> {
+        // Like `ftruncate` this is invalid, rather than forbidden, on files
+        // which aren't open for writing.
+        if !self.writable {
+            return Err(io::ErrorKind::InvalidInput.into());
+        }
+        self.fs.run(|inner| inner.set_len(self.inode, size))
+    }
+
+    fn set_times(
+        &self,
+        atim: Option<SystemTimeSpec>,
+        mtim: Option<SystemTimeSpec>,
+    ) -> io::Result<()> {
+        self.fs.lock().set_times(self.inode, atim, mtim);
+        Ok(())
+    }
+}
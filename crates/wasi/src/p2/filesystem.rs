@@ -1,5 +1,5 @@
 use crate::TrappableError;
-use crate::filesystem::File;
+use crate::filesystem::{File, WasiFile};
 use crate::p2::bindings::filesystem::types;
 use crate::p2::{InputStream, OutputStream, Pollable, StreamError, StreamResult};
 use crate::runtime::AbortOnDropJoinHandle;
@@ -17,6 +17,7 @@ impl From<crate::filesystem::ErrorCode> for types::ErrorCode {
         match error {
             crate::filesystem::ErrorCode::Access => Self::Access,
             crate::filesystem::ErrorCode::Already => Self::Already,
+            crate::filesystem::ErrorCode::CrossDevice => Self::CrossDevice,
             crate::filesystem::ErrorCode::BadDescriptor => Self::BadDescriptor,
             crate::filesystem::ErrorCode::Busy => Self::Busy,
             crate::filesystem::ErrorCode::Exist => Self::Exist,
@@ -83,9 +84,7 @@ impl FileInputStream {
         }
     }
 
-    fn blocking_read(file: &cap_std::fs::File, offset: u64, size: usize) -> ReadState {
-        use system_interface::fs::FileIoExt;
-
+    fn blocking_read(file: &dyn WasiFile, offset: u64, size: usize) -> ReadState {
         let mut buf = BytesMut::zeroed(size);
         loop {
             match file.read_at(&mut buf, offset) {
@@ -239,12 +238,10 @@ impl FileOutputStream {
     }
 
     fn blocking_write(
-        file: &cap_std::fs::File,
+        file: &dyn WasiFile,
         mut buf: Bytes,
         mode: FileOutputMode,
     ) -> io::Result<usize> {
-        use system_interface::fs::FileIoExt;
-
         match mode {
             FileOutputMode::Position(mut p) => {
                 let mut total = 0;
@@ -1,7 +1,8 @@
 use crate::TrappableError;
 use crate::p2::bindings::sockets::network::ErrorCode;
-use crate::sockets::{SocketAddrCheck, SocketAddrUse};
+use crate::sockets::{HostNameResolver, ResolveError, SocketAddrCheck, SocketAddrUse};
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 pub type SocketResult<T> = Result<T, SocketError>;
 
@@ -31,6 +32,23 @@ impl From<crate::sockets::util::ErrorCode> for SocketError {
     }
 }
 
+impl From<ResolveError> for SocketError {
+    fn from(error: ResolveError) -> Self {
+        ErrorCode::from(error).into()
+    }
+}
+
+impl From<ResolveError> for ErrorCode {
+    fn from(error: ResolveError) -> Self {
+        match error {
+            ResolveError::AccessDenied => Self::AccessDenied,
+            ResolveError::NameUnresolvable => Self::NameUnresolvable,
+            ResolveError::TemporaryResolverFailure => Self::TemporaryResolverFailure,
+            ResolveError::PermanentResolverFailure => Self::PermanentResolverFailure,
+        }
+    }
+}
+
 impl From<crate::sockets::util::ErrorCode> for ErrorCode {
     fn from(error: crate::sockets::util::ErrorCode) -> Self {
         match error {
@@ -57,6 +75,7 @@ impl From<crate::sockets::util::ErrorCode> for ErrorCode {
 pub struct Network {
     pub(crate) socket_addr_check: SocketAddrCheck,
     pub(crate) allow_ip_name_lookup: bool,
+    pub(crate) resolver: Arc<dyn HostNameResolver>,
 }
 
 impl Network {
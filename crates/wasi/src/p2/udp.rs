@@ -1,16 +1,16 @@
-use crate::sockets::{SocketAddrCheck, SocketAddressFamily};
+use crate::sockets::{SocketAddrCheck, SocketAddressFamily, UdpEndpoint};
 use std::net::SocketAddr;
 use std::sync::Arc;
 
 pub struct IncomingDatagramStream {
-    pub(crate) inner: Arc<tokio::net::UdpSocket>,
+    pub(crate) inner: Arc<UdpEndpoint>,
 
     /// If this has a value, the stream is "connected".
     pub(crate) remote_address: Option<SocketAddr>,
 }
 
 pub struct OutgoingDatagramStream {
-    pub(crate) inner: Arc<tokio::net::UdpSocket>,
+    pub(crate) inner: Arc<UdpEndpoint>,
 
     /// If this has a value, the stream is "connected".
     pub(crate) remote_address: Option<SocketAddr>,
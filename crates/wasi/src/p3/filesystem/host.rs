@@ -1,4 +1,6 @@
-use crate::filesystem::{Descriptor, Dir, File, WasiFilesystem, WasiFilesystemCtxView};
+use crate::filesystem::{
+    Descriptor, Dir, File, WasiDir, WasiFile, WasiFilesystem, WasiFilesystemCtxView,
+};
 use crate::p3::bindings::clocks::wall_clock;
 use crate::p3::bindings::filesystem::types::{
     self, Advice, DescriptorFlags, DescriptorStat, DescriptorType, DirectoryEntry, ErrorCode,
@@ -14,7 +16,6 @@ use core::task::{Context, Poll, ready};
 use core::{iter, mem};
 use std::io::{self, Cursor};
 use std::sync::Arc;
-use system_interface::fs::FileIoExt as _;
 use tokio::sync::{mpsc, oneshot};
 use tokio::task::{JoinHandle, spawn_blocking};
 use wasmtime::StoreContextMut;
@@ -224,36 +225,10 @@ impl<D> StreamProducer<D> for ReadStreamProducer {
     }
 }
 
-fn map_dir_entry(
-    entry: std::io::Result<cap_std::fs::DirEntry>,
-) -> Result<Option<DirectoryEntry>, ErrorCode> {
-    match entry {
-        Ok(entry) => {
-            let meta = entry.metadata()?;
-            let Ok(name) = entry.file_name().into_string() else {
-                return Err(ErrorCode::IllegalByteSequence);
-            };
-            Ok(Some(DirectoryEntry {
-                type_: meta.file_type().into(),
-                name,
-            }))
-        }
-        Err(err) => {
-            // On windows, filter out files like `C:\DumpStack.log.tmp` which we
-            // can't get full metadata for.
-            #[cfg(windows)]
-            {
-                use windows_sys::Win32::Foundation::{
-                    ERROR_ACCESS_DENIED, ERROR_SHARING_VIOLATION,
-                };
-                if err.raw_os_error() == Some(ERROR_SHARING_VIOLATION as i32)
-                    || err.raw_os_error() == Some(ERROR_ACCESS_DENIED as i32)
-                {
-                    return Ok(None);
-                }
-            }
-            Err(err.into())
-        }
+fn map_dir_entry(entry: crate::filesystem::DirEntry) -> DirectoryEntry {
+    DirectoryEntry {
+        type_: entry.file_type.into(),
+        name: entry.name,
     }
 }
 
@@ -264,19 +239,14 @@ struct ReadDirStream {
 }
 
 impl ReadDirStream {
-    fn new(
-        dir: Arc<cap_std::fs::Dir>,
-        result: oneshot::Sender<Result<(), ErrorCode>>,
-    ) -> ReadDirStream {
+    fn new(dir: Arc<dyn WasiDir>, result: oneshot::Sender<Result<(), ErrorCode>>) -> ReadDirStream {
         let (tx, rx) = mpsc::channel(1);
         ReadDirStream {
             task: spawn_blocking(move || {
-                let entries = dir.entries()?;
+                let entries = dir.read_dir()?;
                 for entry in entries {
-                    if let Some(entry) = map_dir_entry(entry)? {
-                        if let Err(_) = tx.blocking_send(entry) {
-                            break;
-                        }
+                    if let Err(_) = tx.blocking_send(map_dir_entry(entry?)) {
+                        break;
                     }
                 }
                 Ok(())
@@ -409,7 +379,7 @@ impl WriteStreamConsumer {
 }
 
 impl WriteLocation {
-    fn write(&self, file: &cap_std::fs::File, bytes: &[u8]) -> io::Result<usize> {
+    fn write(&self, file: &dyn WasiFile, bytes: &[u8]) -> io::Result<usize> {
         match *self {
             WriteLocation::End => file.append(bytes),
             WriteLocation::Offset(at) => file.write_at(bytes, at),
@@ -451,7 +421,7 @@ impl<D> StreamConsumer<D> for WriteStreamConsumer {
             let buf = mem::take(&mut me.buffer);
             let file = Arc::clone(me.file.as_file());
             let location = me.location;
-            spawn_blocking(move || location.write(&file, &buf).map(|n| (buf, n)))
+            spawn_blocking(move || location.write(&*file, &buf).map(|n| (buf, n)))
         });
         let res = ready!(Pin::new(task).poll(cx)).expect("I/O task should not panic");
         self.task = None;
@@ -649,12 +619,12 @@ impl types::HostDescriptorWithStore for WasiFilesystem {
             let dir = Arc::clone(dir.as_dir());
             let (result_tx, result_rx) = oneshot::channel();
             let stream = if allow_blocking_current_thread {
-                match dir.entries() {
+                match dir.read_dir() {
                     Ok(readdir) => StreamReader::new(
                         instance,
                         &mut store,
                         FallibleIteratorProducer::new(
-                            readdir.filter_map(|e| map_dir_entry(e).transpose()),
+                            readdir.map(|e| e.map(map_dir_entry).map_err(ErrorCode::from)),
                             result_tx,
                         ),
                     ),
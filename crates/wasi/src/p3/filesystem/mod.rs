@@ -92,6 +92,7 @@ impl From<crate::filesystem::ErrorCode> for types::ErrorCode {
         match error {
             crate::filesystem::ErrorCode::Access => Self::Access,
             crate::filesystem::ErrorCode::Already => Self::Already,
+            crate::filesystem::ErrorCode::CrossDevice => Self::CrossDevice,
             crate::filesystem::ErrorCode::BadDescriptor => Self::BadDescriptor,
             crate::filesystem::ErrorCode::Busy => Self::Busy,
             crate::filesystem::ErrorCode::Exist => Self::Exist,
@@ -265,22 +266,3 @@ impl From<crate::filesystem::DescriptorType> for types::DescriptorType {
         }
     }
 }
-
-impl From<cap_std::fs::FileType> for types::DescriptorType {
-    fn from(ft: cap_std::fs::FileType) -> Self {
-        use cap_fs_ext::FileTypeExt as _;
-        if ft.is_dir() {
-            Self::Directory
-        } else if ft.is_symlink() {
-            Self::SymbolicLink
-        } else if ft.is_block_device() {
-            Self::BlockDevice
-        } else if ft.is_char_device() {
-            Self::CharacterDevice
-        } else if ft.is_file() {
-            Self::RegularFile
-        } else {
-            Self::Unknown
-        }
-    }
-}
@@ -1,11 +1,11 @@
-use tokio::net::lookup_host;
 use wasmtime::component::Accessor;
 
 use crate::p3::bindings::sockets::ip_name_lookup::{ErrorCode, Host, HostWithStore};
 use crate::p3::bindings::sockets::types;
 use crate::p3::sockets::WasiSockets;
-use crate::sockets::WasiSocketsCtxView;
-use crate::sockets::util::{from_ipv4_addr, from_ipv6_addr, parse_host};
+use crate::sockets::ip_name_lookup::resolve;
+use crate::sockets::util::parse_host;
+use crate::sockets::{ResolveError, WasiSocketsCtxView};
 
 impl HostWithStore for WasiSockets {
     async fn resolve_addresses<U>(
@@ -15,25 +15,30 @@ impl HostWithStore for WasiSockets {
         let Ok(host) = parse_host(&name) else {
             return Ok(Err(ErrorCode::InvalidArgument));
         };
-        if !store.with(|mut view| view.get().ctx.allowed_network_uses.ip_name_lookup) {
+        let Some(resolver) = store.with(|mut view| {
+            let ctx = view.get().ctx;
+            ctx.allowed_network_uses
+                .ip_name_lookup
+                .then(|| ctx.resolver.clone())
+        }) else {
             return Ok(Err(ErrorCode::PermanentResolverFailure));
-        }
-        match host {
-            url::Host::Ipv4(addr) => Ok(Ok(vec![types::IpAddress::Ipv4(from_ipv4_addr(addr))])),
-            url::Host::Ipv6(addr) => Ok(Ok(vec![types::IpAddress::Ipv6(from_ipv6_addr(addr))])),
-            url::Host::Domain(domain) => {
-                // This is only resolving names, not ports, so force the port to be 0.
-                if let Ok(addrs) = lookup_host((domain.as_str(), 0)).await {
-                    Ok(Ok(addrs
-                        .map(|addr| addr.ip().to_canonical().into())
-                        .collect()))
-                } else {
-                    // If/when we use `getaddrinfo` directly, map the error properly.
-                    Ok(Err(ErrorCode::NameUnresolvable))
-                }
-            }
+        };
+        match resolve(&*resolver, host).await {
+            Ok(addrs) => Ok(Ok(addrs.into_iter().map(Into::into).collect())),
+            Err(err) => Ok(Err(err.into())),
         }
     }
 }
 
 impl Host for WasiSocketsCtxView<'_> {}
+
+impl From<ResolveError> for ErrorCode {
+    fn from(error: ResolveError) -> Self {
+        match error {
+            ResolveError::AccessDenied => Self::AccessDenied,
+            ResolveError::NameUnresolvable => Self::NameUnresolvable,
+            ResolveError::TemporaryResolverFailure => Self::TemporaryResolverFailure,
+            ResolveError::PermanentResolverFailure => Self::PermanentResolverFailure,
+        }
+    }
+}
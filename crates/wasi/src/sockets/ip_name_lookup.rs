@@ -0,0 +1,84 @@
+use std::fmt;
+use std::net::IpAddr;
+
+/// Errors which a [`HostNameResolver`] can report to the guest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResolveError {
+    /// The guest is not permitted to look up the name.
+    AccessDenied,
+    /// The name does not exist or has no suitable associated IP addresses.
+    NameUnresolvable,
+    /// A temporary failure in name resolution occurred.
+    TemporaryResolverFailure,
+    /// A permanent failure in name resolution occurred.
+    PermanentResolverFailure,
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+/// A resolver used to implement `wasi:sockets/ip-name-lookup`.
+///
+/// The resolver is installed with
+/// [`WasiCtxBuilder::ip_name_resolver`](crate::WasiCtxBuilder::ip_name_resolver)
+/// and defaults to [`SystemResolver`]. It is only consulted for domain names:
+/// IP address literals are returned to the guest as-is, and names which aren't
+/// syntactically valid are rejected beforehand.
+///
+/// Addresses returned by the resolver are still subject to
+/// [`WasiCtxBuilder::socket_addr_check`](crate::WasiCtxBuilder::socket_addr_check)
+/// when the guest uses them.
+#[async_trait::async_trait]
+pub trait HostNameResolver: Send + Sync {
+    /// Resolves `name`, an ASCII domain name, to a list of IP addresses in
+    /// connection order preference.
+    ///
+    /// Returning an empty list is equivalent to returning
+    /// [`ResolveError::NameUnresolvable`].
+    async fn resolve(&self, name: &str) -> Result<Vec<IpAddr>, ResolveError>;
+}
+
+/// A [`HostNameResolver`] which uses the host's system resolver.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemResolver;
+
+#[async_trait::async_trait]
+impl HostNameResolver for SystemResolver {
+    async fn resolve(&self, name: &str) -> Result<Vec<IpAddr>, ResolveError> {
+        // This is only resolving names, not ports, so force the port to be 0.
+        let addrs = tokio::net::lookup_host((name, 0))
+            .await
+            // If/when we use `getaddrinfo` directly, map the error properly.
+            .map_err(|_| ResolveError::NameUnresolvable)?;
+        Ok(addrs.map(|addr| addr.ip()).collect())
+    }
+}
+
+/// Resolves `host` with `resolver` if it's a domain name, enforcing the
+/// guarantees `wasi:sockets` makes about the results.
+pub(crate) async fn resolve(
+    resolver: &dyn HostNameResolver,
+    host: url::Host,
+) -> Result<Vec<IpAddr>, ResolveError> {
+    match host {
+        url::Host::Ipv4(addr) => Ok(vec![addr.into()]),
+        url::Host::Ipv6(addr) => Ok(vec![addr.into()]),
+        url::Host::Domain(domain) => {
+            let addrs = resolver
+                .resolve(&domain)
+                .await?
+                .into_iter()
+                .map(|addr| addr.to_canonical())
+                .collect::<Vec<_>>();
+            if addrs.is_empty() {
+                return Err(ResolveError::NameUnresolvable);
+            }
+            Ok(addrs)
+        }
+    }
+}
@@ -5,14 +5,21 @@ use std::pin::Pin;
 use std::sync::Arc;
 use wasmtime::component::{HasData, ResourceTable};
 
+pub(crate) mod ip_name_lookup;
 mod tcp;
 mod udp;
 pub(crate) mod util;
+mod virt;
+
+pub use ip_name_lookup::{HostNameResolver, ResolveError, SystemResolver};
 
-#[cfg(feature = "p3")]
-pub(crate) use tcp::NonInheritedOptions;
 pub use tcp::TcpSocket;
+pub(crate) use tcp::TcpStream;
+#[cfg(feature = "p3")]
+pub(crate) use tcp::{NonInheritedOptions, TcpListener};
+pub(crate) use udp::UdpEndpoint;
 pub use udp::UdpSocket;
+pub use virt::VirtualNetwork;
 
 /// A helper struct which implements [`HasData`] for the `wasi:sockets` APIs.
 ///
@@ -66,10 +73,23 @@ pub(crate) const DEFAULT_TCP_BACKLOG: u32 = 128;
 /// In practice, datagrams are typically less than 1500 bytes.
 pub(crate) const MAX_UDP_DATAGRAM_SIZE: usize = u16::MAX as usize;
 
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct WasiSocketsCtx {
     pub(crate) socket_addr_check: SocketAddrCheck,
     pub(crate) allowed_network_uses: AllowedNetworkUses,
+    pub(crate) resolver: Arc<dyn HostNameResolver>,
+    pub(crate) virtual_network: Option<VirtualNetwork>,
+}
+
+impl Default for WasiSocketsCtx {
+    fn default() -> Self {
+        Self {
+            socket_addr_check: SocketAddrCheck::default(),
+            allowed_network_uses: AllowedNetworkUses::default(),
+            resolver: Arc::new(SystemResolver),
+            virtual_network: None,
+        }
+    }
 }
 
 pub struct WasiSocketsCtxView<'a> {
@@ -6,6 +6,9 @@ use crate::sockets::util::{
     set_keep_alive_idle_time, set_keep_alive_interval, set_receive_buffer_size,
     set_send_buffer_size, set_unicast_hop_limit, tcp_bind,
 };
+use crate::sockets::virt::{
+    VirtualSocketOptions, VirtualTcpListener, VirtualTcpSocket, VirtualTcpStream,
+};
 use crate::sockets::{DEFAULT_TCP_BACKLOG, SocketAddressFamily, WasiSocketsCtx};
 use io_lifetimes::AsSocketlike as _;
 use io_lifetimes::views::SocketlikeView;
@@ -14,9 +17,9 @@ use rustix::net::sockopt;
 use std::fmt::Debug;
 use std::io;
 use std::mem;
-use std::net::SocketAddr;
+use std::net::{Shutdown, SocketAddr};
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, MutexGuard};
 use std::task::{Context, Poll, Waker};
 use std::time::Duration;
 
@@ -30,37 +33,37 @@ enum TcpState {
     ///
     /// From here a socket can transition to `BindStarted`, `ListenStarted`, or
     /// `Connecting`.
-    Default(tokio::net::TcpSocket),
+    Default(UnconnectedTcpSocket),
 
     /// A state indicating that a bind has been started and must be finished
     /// subsequently with `finish_bind`.
     ///
     /// From here a socket can transition to `Bound`.
-    BindStarted(tokio::net::TcpSocket),
+    BindStarted(UnconnectedTcpSocket),
 
     /// Binding finished. The socket has an address but is not yet listening for
     /// connections.
     ///
     /// From here a socket can transition to `ListenStarted`, or `Connecting`.
-    Bound(tokio::net::TcpSocket),
+    Bound(UnconnectedTcpSocket),
 
     /// Listening on a socket has started and must be completed with
     /// `finish_listen`.
     ///
     /// From here a socket can transition to `Listening`.
-    ListenStarted(tokio::net::TcpSocket),
+    ListenStarted(UnconnectedTcpSocket),
 
     /// The socket is now listening and waiting for an incoming connection.
     ///
     /// Sockets will not leave this state.
     Listening {
-        /// The raw tokio-basd TCP listener managing the underyling socket.
-        listener: Arc<tokio::net::TcpListener>,
+        /// The raw TCP listener managing the underyling socket.
+        listener: Arc<TcpListener>,
 
         /// The last-accepted connection, set during the `ready` method and read
         /// during the `accept` method. Note that this is only used for WASIp2
         /// at this time.
-        pending_accept: Option<io::Result<tokio::net::TcpStream>>,
+        pending_accept: Option<io::Result<TcpStream>>,
     },
 
     /// An outgoing connection is started.
@@ -71,7 +74,7 @@ enum TcpState {
     /// so this is `None`.
     ///
     /// From here a socket can transition to `ConnectReady` or `Connected`.
-    Connecting(Option<Pin<Box<dyn Future<Output = io::Result<tokio::net::TcpStream>> + Send>>>),
+    Connecting(Option<Pin<Box<dyn Future<Output = io::Result<TcpStream>> + Send>>>),
 
     /// A connection via `Connecting` has completed.
     ///
@@ -80,7 +83,7 @@ enum TcpState {
     /// finishes as part of the `ready` method.
     ///
     /// From here a socket can transition to `Connected`.
-    ConnectReady(io::Result<tokio::net::TcpStream>),
+    ConnectReady(io::Result<TcpStream>),
 
     /// A connection has been established.
     ///
@@ -88,13 +91,13 @@ enum TcpState {
     /// sockets from a TCP listener.
     ///
     /// From here a socket can transition to `Receiving` or `P2Streaming`.
-    Connected(Arc<tokio::net::TcpStream>),
+    Connected(Arc<TcpStream>),
 
     /// A connection has been established and `receive` has been called.
     ///
     /// A socket will not transition out of this state.
     #[cfg(feature = "p3")]
-    Receiving(Arc<tokio::net::TcpStream>),
+    Receiving(Arc<TcpStream>),
 
     /// This is a WASIp2-bound socket which stores some extra state for
     /// read/write streams to handle TCP shutdown.
@@ -155,6 +158,14 @@ impl TcpSocket {
     ) -> Result<Self, ErrorCode> {
         ctx.allowed_network_uses.check_allowed_tcp()?;
 
+        if let Some(network) = &ctx.virtual_network {
+            let socket = VirtualTcpSocket::new(network, family);
+            return Ok(Self::from_state(
+                TcpState::Default(UnconnectedTcpSocket::Virtual(socket)),
+                family,
+            ));
+        }
+
         with_ambient_tokio_runtime(|| {
             let socket = match family {
                 SocketAddressFamily::Ipv4 => tokio::net::TcpSocket::new_v4()?,
@@ -165,7 +176,10 @@ impl TcpSocket {
                 }
             };
 
-            Ok(Self::from_state(TcpState::Default(socket), family))
+            Ok(Self::from_state(
+                TcpState::Default(UnconnectedTcpSocket::Host(socket)),
+                family,
+            ))
         })
     }
 
@@ -180,7 +194,7 @@ impl TcpSocket {
     /// This will handle the `result` internally and `result` should be the raw
     /// result from a TCP listen operation.
     pub(crate) fn new_accept(
-        result: io::Result<tokio::net::TcpStream>,
+        result: io::Result<TcpStream>,
         options: &NonInheritedOptions,
         family: SocketAddressFamily,
     ) -> io::Result<Self> {
@@ -217,7 +231,9 @@ impl TcpSocket {
 
             _ => err,
         })?;
-        options.apply(family, &client);
+        if let TcpStream::Host(client) = &client {
+            options.apply(family, client);
+        }
         Ok(Self::from_state(
             TcpState::Connected(Arc::new(client)),
             family,
@@ -234,17 +250,26 @@ impl TcpSocket {
         }
     }
 
-    pub(crate) fn as_std_view(&self) -> Result<SocketlikeView<'_, std::net::TcpStream>, ErrorCode> {
+    /// Returns the target of socket options for the current state.
+    fn socket_options(&self) -> Result<SocketOptions<'_>, ErrorCode> {
         match &self.tcp_state {
             TcpState::Default(socket)
             | TcpState::BindStarted(socket)
             | TcpState::Bound(socket)
-            | TcpState::ListenStarted(socket) => Ok(socket.as_socketlike_view()),
-            TcpState::Connected(stream) => Ok(stream.as_socketlike_view()),
+            | TcpState::ListenStarted(socket) => Ok(match socket {
+                UnconnectedTcpSocket::Host(socket) => {
+                    SocketOptions::Host(socket.as_socketlike_view())
+                }
+                UnconnectedTcpSocket::Virtual(socket) => SocketOptions::Virtual(socket.options()),
+            }),
+            TcpState::Connected(stream) => Ok(stream.socket_options()),
             #[cfg(feature = "p3")]
-            TcpState::Receiving(stream) => Ok(stream.as_socketlike_view()),
-            TcpState::Listening { listener, .. } => Ok(listener.as_socketlike_view()),
-            TcpState::P2Streaming(state) => Ok(state.stream.as_socketlike_view()),
+            TcpState::Receiving(stream) => Ok(stream.socket_options()),
+            TcpState::Listening { listener, .. } => Ok(match &**listener {
+                TcpListener::Host(listener) => SocketOptions::Host(listener.as_socketlike_view()),
+                TcpListener::Virtual(listener) => SocketOptions::Virtual(listener.options()),
+            }),
+            TcpState::P2Streaming(state) => Ok(state.stream.socket_options()),
             TcpState::Connecting(..) | TcpState::ConnectReady(_) | TcpState::Closed => {
                 Err(ErrorCode::InvalidState)
             }
@@ -259,8 +284,8 @@ impl TcpSocket {
             return Err(ErrorCode::InvalidArgument);
         }
         match mem::replace(&mut self.tcp_state, TcpState::Closed) {
-            TcpState::Default(sock) => {
-                if let Err(err) = tcp_bind(&sock, addr) {
+            TcpState::Default(mut sock) => {
+                if let Err(err) = sock.bind(addr) {
                     self.tcp_state = TcpState::Default(sock);
                     Err(err)
                 } else {
@@ -292,7 +317,7 @@ impl TcpSocket {
     pub(crate) fn start_connect(
         &mut self,
         addr: &SocketAddr,
-    ) -> Result<tokio::net::TcpSocket, ErrorCode> {
+    ) -> Result<UnconnectedTcpSocket, ErrorCode> {
         match self.tcp_state {
             TcpState::Default(..) | TcpState::Bound(..) => {}
             TcpState::Connecting(..) => {
@@ -308,20 +333,20 @@ impl TcpSocket {
             return Err(ErrorCode::InvalidArgument);
         };
 
-        let (TcpState::Default(tokio_socket) | TcpState::Bound(tokio_socket)) =
+        let (TcpState::Default(socket) | TcpState::Bound(socket)) =
             mem::replace(&mut self.tcp_state, TcpState::Connecting(None))
         else {
             unreachable!();
         };
 
-        Ok(tokio_socket)
+        Ok(socket)
     }
 
     /// For WASIp2 this is used to record the actual connection future as part
     /// of `start_connect` within this socket state.
     pub(crate) fn set_pending_connect(
         &mut self,
-        future: impl Future<Output = io::Result<tokio::net::TcpStream>> + Send + 'static,
+        future: impl Future<Output = io::Result<TcpStream>> + Send + 'static,
     ) -> Result<(), ErrorCode> {
         match &mut self.tcp_state {
             TcpState::Connecting(slot @ None) => {
@@ -342,7 +367,7 @@ impl TcpSocket {
     /// * `Err(e)` - a connect operation is not in progress.
     pub(crate) fn take_pending_connect(
         &mut self,
-    ) -> Result<Option<io::Result<tokio::net::TcpStream>>, ErrorCode> {
+    ) -> Result<Option<io::Result<TcpStream>>, ErrorCode> {
         match mem::replace(&mut self.tcp_state, TcpState::Connecting(None)) {
             TcpState::ConnectReady(result) => Ok(Some(result)),
             TcpState::Connecting(Some(mut future)) => {
@@ -364,7 +389,7 @@ impl TcpSocket {
 
     pub(crate) fn finish_connect(
         &mut self,
-        result: io::Result<tokio::net::TcpStream>,
+        result: io::Result<TcpStream>,
     ) -> Result<(), ErrorCode> {
         if !matches!(self.tcp_state, TcpState::Connecting(None)) {
             return Err(ErrorCode::InvalidState);
@@ -395,15 +420,15 @@ impl TcpSocket {
     }
 
     pub(crate) fn finish_listen(&mut self) -> Result<(), ErrorCode> {
-        let tokio_socket = match mem::replace(&mut self.tcp_state, TcpState::Closed) {
-            TcpState::ListenStarted(tokio_socket) => tokio_socket,
+        let socket = match mem::replace(&mut self.tcp_state, TcpState::Closed) {
+            TcpState::ListenStarted(socket) => socket,
             previous_state => {
                 self.tcp_state = previous_state;
                 return Err(ErrorCode::NotInProgress);
             }
         };
 
-        match with_ambient_tokio_runtime(|| tokio_socket.listen(self.listen_backlog_size)) {
+        match socket.listen(self.listen_backlog_size) {
             Ok(listener) => {
                 self.tcp_state = TcpState::Listening {
                     listener: Arc::new(listener),
@@ -446,9 +471,7 @@ impl TcpSocket {
             Some(result) => result,
             None => {
                 let mut cx = std::task::Context::from_waker(Waker::noop());
-                match with_ambient_tokio_runtime(|| listener.poll_accept(&mut cx))
-                    .map_ok(|(stream, _)| stream)
-                {
+                match with_ambient_tokio_runtime(|| listener.poll_accept(&mut cx)) {
                     Poll::Ready(result) => result,
                     Poll::Pending => return Ok(None),
                 }
@@ -459,7 +482,7 @@ impl TcpSocket {
     }
 
     #[cfg(feature = "p3")]
-    pub(crate) fn start_receive(&mut self) -> Option<&Arc<tokio::net::TcpStream>> {
+    pub(crate) fn start_receive(&mut self) -> Option<&Arc<TcpStream>> {
         match mem::replace(&mut self.tcp_state, TcpState::Closed) {
             TcpState::Connected(stream) => {
                 self.tcp_state = TcpState::Receiving(stream);
@@ -519,10 +542,17 @@ impl TcpSocket {
                 Ok(())
             }
             TcpState::Listening { listener, .. } => {
-                // Try to update the backlog by calling `listen` again.
-                // Not all platforms support this. We'll only update our own value if the OS supports changing the backlog size after the fact.
-                if rustix::net::listen(&listener, value.try_into().unwrap_or(i32::MAX)).is_err() {
-                    return Err(ErrorCode::NotSupported);
+                match &**listener {
+                    TcpListener::Host(listener) => {
+                        // Try to update the backlog by calling `listen` again.
+                        // Not all platforms support this. We'll only update our own value if the OS supports changing the backlog size after the fact.
+                        if rustix::net::listen(listener, value.try_into().unwrap_or(i32::MAX))
+                            .is_err()
+                        {
+                            return Err(ErrorCode::NotSupported);
+                        }
+                    }
+                    TcpListener::Virtual(listener) => listener.set_backlog(value),
                 }
                 self.listen_backlog_size = value;
                 Ok(())
@@ -534,96 +564,121 @@ impl TcpSocket {
     }
 
     pub(crate) fn keep_alive_enabled(&self) -> Result<bool, ErrorCode> {
-        let fd = &*self.as_std_view()?;
-        let v = sockopt::socket_keepalive(fd)?;
+        let fd = match self.socket_options()? {
+            SocketOptions::Host(fd) => fd,
+            SocketOptions::Virtual(options) => return Ok(options.keep_alive_enabled()),
+        };
+        let v = sockopt::socket_keepalive(&*fd)?;
         Ok(v)
     }
 
     pub(crate) fn set_keep_alive_enabled(&self, value: bool) -> Result<(), ErrorCode> {
-        let fd = &*self.as_std_view()?;
-        sockopt::set_socket_keepalive(fd, value)?;
+        match self.socket_options()? {
+            SocketOptions::Host(fd) => sockopt::set_socket_keepalive(&*fd, value)?,
+            SocketOptions::Virtual(mut options) => options.set_keep_alive_enabled(value),
+        }
         Ok(())
     }
 
     pub(crate) fn keep_alive_idle_time(&self) -> Result<u64, ErrorCode> {
-        let fd = &*self.as_std_view()?;
-        let v = sockopt::tcp_keepidle(fd)?;
+        let fd = match self.socket_options()? {
+            SocketOptions::Host(fd) => fd,
+            SocketOptions::Virtual(options) => return Ok(options.keep_alive_idle_time()),
+        };
+        let v = sockopt::tcp_keepidle(&*fd)?;
         Ok(v.as_nanos().try_into().unwrap_or(u64::MAX))
     }
 
     pub(crate) fn set_keep_alive_idle_time(&mut self, value: u64) -> Result<(), ErrorCode> {
-        let value = {
-            let fd = self.as_std_view()?;
-            set_keep_alive_idle_time(&*fd, value)?
+        let value = match self.socket_options()? {
+            SocketOptions::Host(fd) => set_keep_alive_idle_time(&*fd, value)?,
+            SocketOptions::Virtual(mut options) => return options.set_keep_alive_idle_time(value),
         };
         self.options.set_keep_alive_idle_time(value);
         Ok(())
     }
 
     pub(crate) fn keep_alive_interval(&self) -> Result<u64, ErrorCode> {
-        let fd = &*self.as_std_view()?;
-        let v = sockopt::tcp_keepintvl(fd)?;
+        let fd = match self.socket_options()? {
+            SocketOptions::Host(fd) => fd,
+            SocketOptions::Virtual(options) => return Ok(options.keep_alive_interval()),
+        };
+        let v = sockopt::tcp_keepintvl(&*fd)?;
         Ok(v.as_nanos().try_into().unwrap_or(u64::MAX))
     }
 
     pub(crate) fn set_keep_alive_interval(&self, value: u64) -> Result<(), ErrorCode> {
-        let fd = &*self.as_std_view()?;
-        set_keep_alive_interval(fd, Duration::from_nanos(value))?;
-        Ok(())
+        match self.socket_options()? {
+            SocketOptions::Host(fd) => set_keep_alive_interval(&*fd, Duration::from_nanos(value)),
+            SocketOptions::Virtual(mut options) => options.set_keep_alive_interval(value),
+        }
     }
 
     pub(crate) fn keep_alive_count(&self) -> Result<u32, ErrorCode> {
-        let fd = &*self.as_std_view()?;
-        let v = sockopt::tcp_keepcnt(fd)?;
+        let fd = match self.socket_options()? {
+            SocketOptions::Host(fd) => fd,
+            SocketOptions::Virtual(options) => return Ok(options.keep_alive_count()),
+        };
+        let v = sockopt::tcp_keepcnt(&*fd)?;
         Ok(v)
     }
 
     pub(crate) fn set_keep_alive_count(&self, value: u32) -> Result<(), ErrorCode> {
-        let fd = &*self.as_std_view()?;
-        set_keep_alive_count(fd, value)?;
-        Ok(())
+        match self.socket_options()? {
+            SocketOptions::Host(fd) => set_keep_alive_count(&*fd, value),
+            SocketOptions::Virtual(mut options) => options.set_keep_alive_count(value),
+        }
     }
 
     pub(crate) fn hop_limit(&self) -> Result<u8, ErrorCode> {
-        let fd = &*self.as_std_view()?;
-        let n = get_unicast_hop_limit(fd, self.family)?;
+        let fd = match self.socket_options()? {
+            SocketOptions::Host(fd) => fd,
+            SocketOptions::Virtual(options) => return Ok(options.hop_limit()),
+        };
+        let n = get_unicast_hop_limit(&*fd, self.family)?;
         Ok(n)
     }
 
     pub(crate) fn set_hop_limit(&mut self, value: u8) -> Result<(), ErrorCode> {
-        {
-            let fd = &*self.as_std_view()?;
-            set_unicast_hop_limit(fd, self.family, value)?;
+        match self.socket_options()? {
+            SocketOptions::Host(fd) => set_unicast_hop_limit(&*fd, self.family, value)?,
+            SocketOptions::Virtual(mut options) => return options.set_hop_limit(value),
         }
         self.options.set_hop_limit(value);
         Ok(())
     }
 
     pub(crate) fn receive_buffer_size(&self) -> Result<u64, ErrorCode> {
-        let fd = &*self.as_std_view()?;
-        let n = receive_buffer_size(fd)?;
+        let fd = match self.socket_options()? {
+            SocketOptions::Host(fd) => fd,
+            SocketOptions::Virtual(options) => return Ok(options.receive_buffer_size()),
+        };
+        let n = receive_buffer_size(&*fd)?;
         Ok(n)
     }
 
     pub(crate) fn set_receive_buffer_size(&mut self, value: u64) -> Result<(), ErrorCode> {
-        let res = {
-            let fd = &*self.as_std_view()?;
-            set_receive_buffer_size(fd, value)?
+        let res = match self.socket_options()? {
+            SocketOptions::Host(fd) => set_receive_buffer_size(&*fd, value)?,
+            SocketOptions::Virtual(mut options) => return options.set_receive_buffer_size(value),
         };
         self.options.set_receive_buffer_size(res);
         Ok(())
     }
 
     pub(crate) fn send_buffer_size(&self) -> Result<u64, ErrorCode> {
-        let fd = &*self.as_std_view()?;
-        let n = send_buffer_size(fd)?;
+        let fd = match self.socket_options()? {
+            SocketOptions::Host(fd) => fd,
+            SocketOptions::Virtual(options) => return Ok(options.send_buffer_size()),
+        };
+        let n = send_buffer_size(&*fd)?;
         Ok(n)
     }
 
     pub(crate) fn set_send_buffer_size(&mut self, value: u64) -> Result<(), ErrorCode> {
-        let res = {
-            let fd = &*self.as_std_view()?;
-            set_send_buffer_size(fd, value)?
+        let res = match self.socket_options()? {
+            SocketOptions::Host(fd) => set_send_buffer_size(&*fd, value)?,
+            SocketOptions::Virtual(mut options) => return options.set_send_buffer_size(value),
         };
         self.options.set_send_buffer_size(res);
         Ok(())
@@ -635,7 +690,7 @@ impl TcpSocket {
     }
 
     #[cfg(feature = "p3")]
-    pub(crate) fn tcp_listener_arc(&self) -> Result<&Arc<tokio::net::TcpListener>, ErrorCode> {
+    pub(crate) fn tcp_listener_arc(&self) -> Result<&Arc<TcpListener>, ErrorCode> {
         match &self.tcp_state {
             TcpState::Listening { listener, .. } => Ok(listener),
             #[cfg(feature = "p3")]
@@ -644,7 +699,7 @@ impl TcpSocket {
         }
     }
 
-    pub(crate) fn tcp_stream_arc(&self) -> Result<&Arc<tokio::net::TcpStream>, ErrorCode> {
+    pub(crate) fn tcp_stream_arc(&self) -> Result<&Arc<TcpStream>, ErrorCode> {
         match &self.tcp_state {
             TcpState::Connected(socket) => Ok(socket),
             #[cfg(feature = "p3")]
@@ -708,16 +763,188 @@ impl TcpSocket {
                 listener,
                 pending_accept: slot @ None,
             } => {
-                let result = futures::future::poll_fn(|cx| {
-                    listener.poll_accept(cx).map_ok(|(stream, _)| stream)
-                })
-                .await;
+                let result = futures::future::poll_fn(|cx| listener.poll_accept(cx)).await;
                 *slot = Some(result);
             }
         }
     }
 }
 
+/// The target of socket options: either a host socket, or the options
+/// remembered for a socket on a virtual network.
+enum SocketOptions<'a> {
+    Host(SocketlikeView<'a, std::net::TcpStream>),
+    Virtual(MutexGuard<'a, VirtualSocketOptions>),
+}
+
+/// A TCP socket which is neither listening nor connected, either on the
+/// host's network or on a virtual one.
+pub(crate) enum UnconnectedTcpSocket {
+    Host(tokio::net::TcpSocket),
+    Virtual(VirtualTcpSocket),
+}
+
+impl UnconnectedTcpSocket {
+    fn bind(&mut self, addr: SocketAddr) -> Result<(), ErrorCode> {
+        match self {
+            Self::Host(socket) => tcp_bind(socket, addr),
+            Self::Virtual(socket) => Ok(socket.bind(addr)?),
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            Self::Host(socket) => socket.local_addr(),
+            Self::Virtual(socket) => socket.local_addr(),
+        }
+    }
+
+    fn listen(self, backlog: u32) -> io::Result<TcpListener> {
+        match self {
+            Self::Host(socket) => {
+                with_ambient_tokio_runtime(|| socket.listen(backlog)).map(TcpListener::Host)
+            }
+            Self::Virtual(socket) => socket.listen(backlog).map(TcpListener::Virtual),
+        }
+    }
+
+    pub(crate) async fn connect(self, addr: SocketAddr) -> io::Result<TcpStream> {
+        match self {
+            Self::Host(socket) => socket.connect(addr).await.map(TcpStream::Host),
+            Self::Virtual(socket) => socket.connect(addr).map(TcpStream::Virtual),
+        }
+    }
+}
+
+/// A listening TCP socket, either on the host's network or on a virtual one.
+pub(crate) enum TcpListener {
+    Host(tokio::net::TcpListener),
+    Virtual(VirtualTcpListener),
+}
+
+impl TcpListener {
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            Self::Host(listener) => listener.local_addr(),
+            Self::Virtual(listener) => Ok(listener.local_addr()),
+        }
+    }
+
+    pub(crate) fn poll_accept(&self, cx: &mut Context<'_>) -> Poll<io::Result<TcpStream>> {
+        match self {
+            Self::Host(listener) => listener
+                .poll_accept(cx)
+                .map_ok(|(stream, _)| TcpStream::Host(stream)),
+            Self::Virtual(listener) => listener.poll_accept(cx).map(|s| Ok(TcpStream::Virtual(s))),
+        }
+    }
+}
+
+/// A connected TCP socket, either on the host's network or on a virtual one.
+///
+/// The methods here mirror those of `tokio::net::TcpStream`.
+pub(crate) enum TcpStream {
+    Host(tokio::net::TcpStream),
+    Virtual(VirtualTcpStream),
+}
+
+impl TcpStream {
+    fn socket_options(&self) -> SocketOptions<'_> {
+        match self {
+            Self::Host(stream) => SocketOptions::Host(stream.as_socketlike_view()),
+            Self::Virtual(stream) => SocketOptions::Virtual(stream.options()),
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            Self::Host(stream) => stream.local_addr(),
+            Self::Virtual(stream) => Ok(stream.local_addr()),
+        }
+    }
+
+    fn peer_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            Self::Host(stream) => stream.peer_addr(),
+            Self::Virtual(stream) => Ok(stream.peer_addr()),
+        }
+    }
+
+    #[cfg(feature = "p3")]
+    pub(crate) fn try_read(&self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Host(stream) => stream.try_read(buf),
+            Self::Virtual(stream) => stream.try_read(buf),
+        }
+    }
+
+    pub(crate) fn try_read_buf(&self, buf: &mut bytes::BytesMut) -> io::Result<usize> {
+        match self {
+            Self::Host(stream) => stream.try_read_buf(buf),
+            Self::Virtual(stream) => {
+                let len = buf.len();
+                buf.resize(buf.capacity(), 0);
+                let result = stream.try_read(&mut buf[len..]);
+                buf.truncate(len + *result.as_ref().unwrap_or(&0));
+                result
+            }
+        }
+    }
+
+    pub(crate) fn try_write(&self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Host(stream) => stream.try_write(buf),
+            Self::Virtual(stream) => stream.try_write(buf),
+        }
+    }
+
+    #[cfg(feature = "p3")]
+    pub(crate) fn poll_read_ready(&self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self {
+            Self::Host(stream) => stream.poll_read_ready(cx),
+            Self::Virtual(stream) => stream.poll_read_ready(cx),
+        }
+    }
+
+    #[cfg(feature = "p3")]
+    pub(crate) fn poll_write_ready(&self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self {
+            Self::Host(stream) => stream.poll_write_ready(cx),
+            Self::Virtual(stream) => stream.poll_write_ready(cx),
+        }
+    }
+
+    pub(crate) async fn readable(&self) -> io::Result<()> {
+        match self {
+            Self::Host(stream) => stream.readable().await,
+            Self::Virtual(stream) => {
+                futures::future::poll_fn(|cx| stream.poll_read_ready(cx)).await
+            }
+        }
+    }
+
+    pub(crate) async fn writable(&self) -> io::Result<()> {
+        match self {
+            Self::Host(stream) => stream.writable().await,
+            Self::Virtual(stream) => {
+                futures::future::poll_fn(|cx| stream.poll_write_ready(cx)).await
+            }
+        }
+    }
+
+    /// Shuts down part or all of the connection, ignoring any errors.
+    pub(crate) fn shutdown(&self, how: Shutdown) {
+        match self {
+            Self::Host(stream) => {
+                _ = stream
+                    .as_socketlike_view::<std::net::TcpStream>()
+                    .shutdown(how);
+            }
+            Self::Virtual(stream) => stream.shutdown(how),
+        }
+    }
+}
+
 #[cfg(not(target_os = "macos"))]
 pub use inherits_option::*;
 #[cfg(not(target_os = "macos"))]
@@ -4,14 +4,16 @@ use crate::sockets::util::{
     receive_buffer_size, send_buffer_size, set_receive_buffer_size, set_send_buffer_size,
     set_unicast_hop_limit, udp_bind, udp_disconnect, udp_socket,
 };
+use crate::sockets::virt::VirtualUdpSocket;
 use crate::sockets::{SocketAddrCheck, SocketAddressFamily, WasiSocketsCtx};
 use cap_net_ext::AddressFamily;
-use io_lifetimes::AsSocketlike as _;
 use io_lifetimes::raw::{FromRawSocketlike as _, IntoRawSocketlike as _};
 use rustix::io::Errno;
 use rustix::net::connect;
+use std::io;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use tokio::io::Interest;
 use tracing::debug;
 
 /// The state of a UDP socket.
@@ -43,7 +45,7 @@ enum UdpState {
 /// The inner state is wrapped in an Arc because the same underlying socket is
 /// used for implementing the stream types.
 pub struct UdpSocket {
-    socket: Arc<tokio::net::UdpSocket>,
+    socket: Arc<UdpEndpoint>,
 
     /// The current state in the bind/connect progression.
     udp_state: UdpState,
@@ -61,6 +63,15 @@ impl UdpSocket {
     pub(crate) fn new(cx: &WasiSocketsCtx, family: AddressFamily) -> Result<Self, ErrorCode> {
         cx.allowed_network_uses.check_allowed_udp()?;
 
+        if let Some(network) = &cx.virtual_network {
+            let family = match family {
+                AddressFamily::Ipv4 => SocketAddressFamily::Ipv4,
+                AddressFamily::Ipv6 => SocketAddressFamily::Ipv6,
+            };
+            let socket = VirtualUdpSocket::new(network, family);
+            return Ok(Self::from_endpoint(UdpEndpoint::Virtual(socket), family));
+        }
+
         // Delegate socket creation to cap_net_ext. They handle a couple of things for us:
         // - On Windows: call WSAStartup if not done before.
         // - Set the NONBLOCK and CLOEXEC flags. Either immediately during socket creation,
@@ -82,12 +93,19 @@ impl UdpSocket {
             })
         })?;
 
-        Ok(Self {
+        Ok(Self::from_endpoint(
+            UdpEndpoint::Host(socket),
+            socket_address_family,
+        ))
+    }
+
+    fn from_endpoint(socket: UdpEndpoint, family: SocketAddressFamily) -> Self {
+        Self {
             socket: Arc::new(socket),
             udp_state: UdpState::Default,
-            family: socket_address_family,
+            family,
             socket_addr_check: None,
-        })
+        }
     }
 
     pub(crate) fn bind(&mut self, addr: SocketAddr) -> Result<(), ErrorCode> {
@@ -97,7 +115,7 @@ impl UdpSocket {
         if !is_valid_address_family(addr.ip(), self.family) {
             return Err(ErrorCode::InvalidArgument);
         }
-        udp_bind(&self.socket, addr)?;
+        self.socket.bind(addr)?;
         self.udp_state = UdpState::BindStarted;
         Ok(())
     }
@@ -124,7 +142,7 @@ impl UdpSocket {
         if !self.is_connected() {
             return Err(ErrorCode::InvalidState);
         }
-        udp_disconnect(&self.socket)?;
+        self.socket.disconnect()?;
         self.udp_state = UdpState::Bound;
         Ok(())
     }
@@ -147,18 +165,11 @@ impl UdpSocket {
 
         // Step #1: Disconnect
         if let UdpState::Connected(..) = self.udp_state {
-            udp_disconnect(&self.socket)?;
+            self.socket.disconnect()?;
             self.udp_state = UdpState::Bound;
         }
         // Step #2: (Re)connect
-        connect(&self.socket, &addr).map_err(|error| match error {
-            Errno::AFNOSUPPORT => ErrorCode::InvalidArgument, // See `udp_bind` implementation.
-            Errno::INPROGRESS => {
-                debug!("UDP connect returned EINPROGRESS, which should never happen");
-                ErrorCode::Unknown
-            }
-            err => err.into(),
-        })?;
+        self.socket.connect(addr)?;
         self.udp_state = UdpState::Connected(addr);
         Ok(())
     }
@@ -183,8 +194,8 @@ impl UdpSocket {
         addr: SocketAddr,
     ) -> impl Future<Output = Result<(), ErrorCode>> + use<> {
         enum Mode {
-            Send(Arc<tokio::net::UdpSocket>),
-            SendTo(Arc<tokio::net::UdpSocket>, SocketAddr),
+            Send(Arc<UdpEndpoint>),
+            SendTo(Arc<UdpEndpoint>, SocketAddr),
         }
         let socket = match &self.udp_state {
             UdpState::BindStarted => Err(ErrorCode::InvalidState),
@@ -207,8 +218,8 @@ impl UdpSocket {
         &self,
     ) -> impl Future<Output = Result<(Vec<u8>, SocketAddr), ErrorCode>> + use<> {
         enum Mode {
-            Recv(Arc<tokio::net::UdpSocket>, SocketAddr),
-            RecvFrom(Arc<tokio::net::UdpSocket>),
+            Recv(Arc<UdpEndpoint>, SocketAddr),
+            RecvFrom(Arc<UdpEndpoint>),
         }
         let socket = match self.udp_state {
             UdpState::Default | UdpState::BindStarted => Err(ErrorCode::InvalidState),
@@ -237,10 +248,7 @@ impl UdpSocket {
         if matches!(self.udp_state, UdpState::Default | UdpState::BindStarted) {
             return Err(ErrorCode::InvalidState);
         }
-        let addr = self
-            .socket
-            .as_socketlike_view::<std::net::UdpSocket>()
-            .local_addr()?;
+        let addr = self.socket.local_addr()?;
         Ok(addr)
     }
 
@@ -248,10 +256,7 @@ impl UdpSocket {
         if !matches!(self.udp_state, UdpState::Connected(..)) {
             return Err(ErrorCode::InvalidState);
         }
-        let addr = self
-            .socket
-            .as_socketlike_view::<std::net::UdpSocket>()
-            .peer_addr()?;
+        let addr = self.socket.peer_addr()?;
         Ok(addr)
     }
 
@@ -260,36 +265,54 @@ impl UdpSocket {
     }
 
     pub(crate) fn unicast_hop_limit(&self) -> Result<u8, ErrorCode> {
-        let n = get_unicast_hop_limit(&self.socket, self.family)?;
-        Ok(n)
+        match &*self.socket {
+            UdpEndpoint::Host(socket) => get_unicast_hop_limit(socket, self.family),
+            UdpEndpoint::Virtual(socket) => Ok(socket.options().hop_limit()),
+        }
     }
 
     pub(crate) fn set_unicast_hop_limit(&self, value: u8) -> Result<(), ErrorCode> {
-        set_unicast_hop_limit(&self.socket, self.family, value)?;
-        Ok(())
+        match &*self.socket {
+            UdpEndpoint::Host(socket) => set_unicast_hop_limit(socket, self.family, value),
+            UdpEndpoint::Virtual(socket) => socket.options().set_hop_limit(value),
+        }
     }
 
     pub(crate) fn receive_buffer_size(&self) -> Result<u64, ErrorCode> {
-        let n = receive_buffer_size(&self.socket)?;
-        Ok(n)
+        match &*self.socket {
+            UdpEndpoint::Host(socket) => receive_buffer_size(socket),
+            UdpEndpoint::Virtual(socket) => Ok(socket.options().receive_buffer_size()),
+        }
     }
 
     pub(crate) fn set_receive_buffer_size(&self, value: u64) -> Result<(), ErrorCode> {
-        set_receive_buffer_size(&self.socket, value)?;
-        Ok(())
+        match &*self.socket {
+            UdpEndpoint::Host(socket) => {
+                set_receive_buffer_size(socket, value)?;
+                Ok(())
+            }
+            UdpEndpoint::Virtual(socket) => socket.options().set_receive_buffer_size(value),
+        }
     }
 
     pub(crate) fn send_buffer_size(&self) -> Result<u64, ErrorCode> {
-        let n = send_buffer_size(&self.socket)?;
-        Ok(n)
+        match &*self.socket {
+            UdpEndpoint::Host(socket) => send_buffer_size(socket),
+            UdpEndpoint::Virtual(socket) => Ok(socket.options().send_buffer_size()),
+        }
     }
 
     pub(crate) fn set_send_buffer_size(&self, value: u64) -> Result<(), ErrorCode> {
-        set_send_buffer_size(&self.socket, value)?;
-        Ok(())
+        match &*self.socket {
+            UdpEndpoint::Host(socket) => {
+                set_send_buffer_size(socket, value)?;
+                Ok(())
+            }
+            UdpEndpoint::Virtual(socket) => socket.options().set_send_buffer_size(value),
+        }
     }
 
-    pub(crate) fn socket(&self) -> &Arc<tokio::net::UdpSocket> {
+    pub(crate) fn socket(&self) -> &Arc<UdpEndpoint> {
         &self.socket
     }
 
@@ -303,7 +326,7 @@ impl UdpSocket {
 }
 
 #[cfg(feature = "p3")]
-async fn send(socket: &tokio::net::UdpSocket, buf: &[u8]) -> Result<(), ErrorCode> {
+async fn send(socket: &UdpEndpoint, buf: &[u8]) -> Result<(), ErrorCode> {
     let n = socket.send(buf).await?;
     // From Rust stdlib docs:
     // > Note that the operating system may refuse buffers larger than 65507.
@@ -318,11 +341,7 @@ async fn send(socket: &tokio::net::UdpSocket, buf: &[u8]) -> Result<(), ErrorCod
 }
 
 #[cfg(feature = "p3")]
-async fn send_to(
-    socket: &tokio::net::UdpSocket,
-    buf: &[u8],
-    addr: SocketAddr,
-) -> Result<(), ErrorCode> {
+async fn send_to(socket: &UdpEndpoint, buf: &[u8], addr: SocketAddr) -> Result<(), ErrorCode> {
     let n = socket.send_to(buf, addr).await?;
     // See [`send`] documentation
     if n != buf.len() {
@@ -331,3 +350,130 @@ async fn send_to(
         Ok(())
     }
 }
+
+/// A UDP socket, either on the host's network or on a virtual one.
+///
+/// The methods here mirror those of `tokio::net::UdpSocket`.
+pub(crate) enum UdpEndpoint {
+    Host(tokio::net::UdpSocket),
+    Virtual(VirtualUdpSocket),
+}
+
+impl UdpEndpoint {
+    fn bind(&self, addr: SocketAddr) -> Result<(), ErrorCode> {
+        match self {
+            Self::Host(socket) => udp_bind(socket, addr),
+            Self::Virtual(socket) => Ok(socket.bind(addr)?),
+        }
+    }
+
+    fn connect(&self, addr: SocketAddr) -> Result<(), ErrorCode> {
+        match self {
+            Self::Host(socket) => connect(socket, &addr).map_err(|error| match error {
+                Errno::AFNOSUPPORT => ErrorCode::InvalidArgument, // See `udp_bind` implementation.
+                Errno::INPROGRESS => {
+                    debug!("UDP connect returned EINPROGRESS, which should never happen");
+                    ErrorCode::Unknown
+                }
+                err => err.into(),
+            }),
+            Self::Virtual(socket) => Ok(socket.connect(addr)?),
+        }
+    }
+
+    fn disconnect(&self) -> Result<(), ErrorCode> {
+        match self {
+            Self::Host(socket) => udp_disconnect(socket),
+            Self::Virtual(socket) => {
+                socket.disconnect();
+                Ok(())
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            Self::Host(socket) => socket.local_addr(),
+            Self::Virtual(socket) => socket.local_addr(),
+        }
+    }
+
+    fn peer_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            Self::Host(socket) => socket.peer_addr(),
+            Self::Virtual(socket) => socket.peer_addr(),
+        }
+    }
+
+    pub(crate) async fn ready(&self, interest: Interest) -> io::Result<()> {
+        match self {
+            Self::Host(socket) => socket.ready(interest).await.map(drop),
+            // Datagrams are delivered as soon as they're sent, so a virtual
+            // socket is always writable.
+            Self::Virtual(_) if !interest.is_readable() => Ok(()),
+            Self::Virtual(socket) => {
+                futures::future::poll_fn(|cx| socket.poll_recv_ready(cx)).await
+            }
+        }
+    }
+
+    pub(crate) fn try_send(&self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Host(socket) => socket.try_send(buf),
+            Self::Virtual(socket) => socket.try_send(buf),
+        }
+    }
+
+    pub(crate) fn try_send_to(&self, buf: &[u8], addr: SocketAddr) -> io::Result<usize> {
+        match self {
+            Self::Host(socket) => socket.try_send_to(buf, addr),
+            Self::Virtual(socket) => socket.try_send_to(buf, addr),
+        }
+    }
+
+    pub(crate) fn try_recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        match self {
+            Self::Host(socket) => socket.try_recv_from(buf),
+            Self::Virtual(socket) => socket.try_recv_from(buf),
+        }
+    }
+
+    #[cfg(feature = "p3")]
+    async fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Host(socket) => socket.send(buf).await,
+            Self::Virtual(socket) => socket.try_send(buf),
+        }
+    }
+
+    #[cfg(feature = "p3")]
+    async fn send_to(&self, buf: &[u8], addr: SocketAddr) -> io::Result<usize> {
+        match self {
+            Self::Host(socket) => socket.send_to(buf, addr).await,
+            Self::Virtual(socket) => socket.try_send_to(buf, addr),
+        }
+    }
+
+    #[cfg(feature = "p3")]
+    async fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Host(socket) => socket.recv(buf).await,
+            Self::Virtual(_) => Ok(self.recv_from(buf).await?.0),
+        }
+    }
+
+    #[cfg(feature = "p3")]
+    async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        match self {
+            Self::Host(socket) => socket.recv_from(buf).await,
+            Self::Virtual(socket) => loop {
+                match socket.try_recv_from(buf) {
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                        futures::future::poll_fn(|cx| socket.poll_recv_ready(cx)).await?;
+                    }
+                    result => break result,
+                }
+            },
+        }
+    }
+}
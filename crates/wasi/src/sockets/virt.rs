@@ -0,0 +1,750 @@
+//! An in-process network which guests can use in place of the host's, see
+//! [`VirtualNetwork`].
+
+use crate::sockets::SocketAddressFamily;
+use crate::sockets::util::ErrorCode;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+/// The first port handed out when binding to port 0, as suggested by IANA.
+const EPHEMERAL_PORTS: std::ops::RangeInclusive<u16> = 49152..=65535;
+
+/// How many bytes a TCP connection buffers in each direction before writes
+/// start to block.
+const TCP_BUFFER_SIZE: usize = 64 * 1024;
+
+/// How many datagrams a UDP socket buffers before dropping new ones.
+const UDP_QUEUE_LENGTH: usize = 64;
+
+/// An in-process network for `wasi:sockets`.
+///
+/// Guests whose [`WasiCtx`](crate::WasiCtx) is configured with the same
+/// network, via [`WasiCtxBuilder::virtual_network`], can listen on, connect
+/// to, and send datagrams to each other on any IP address without touching
+/// the host's network stack. The host's network is entirely unreachable from
+/// such guests.
+///
+/// Addresses behave much as they would on a host with every address assigned
+/// to it: sockets bound to an unspecified address receive connections and
+/// datagrams sent to any address with the same port and family, and sockets
+/// which are connected without being bound, or are bound to an unspecified
+/// address, use the loopback address of their family as their local address.
+///
+/// [`WasiCtxBuilder::socket_addr_check`] still applies to virtual addresses.
+/// Socket options, such as keep-alive and buffer sizes, are validated and
+/// remembered like on the host but otherwise have no effect.
+///
+/// Cloning a `VirtualNetwork` returns a handle to the same network.
+///
+/// [`WasiCtxBuilder::virtual_network`]: crate::WasiCtxBuilder::virtual_network
+/// [`WasiCtxBuilder::socket_addr_check`]: crate::WasiCtxBuilder::socket_addr_check
+#[derive(Clone, Default)]
+pub struct VirtualNetwork {
+    switch: Arc<Mutex<Switch>>,
+}
+
+#[derive(Default)]
+struct Switch {
+    tcp_ports: Ports,
+    udp_ports: Ports,
+    listeners: HashMap<SocketAddr, Arc<Backlog>>,
+    inboxes: HashMap<SocketAddr, Arc<Inbox>>,
+}
+
+/// The addresses bound for a single protocol.
+#[derive(Default)]
+struct Ports {
+    bound: HashSet<SocketAddr>,
+    next_ephemeral: u16,
+}
+
+impl Ports {
+    fn conflicts(&self, addr: SocketAddr) -> bool {
+        self.bound.iter().any(|bound| {
+            bound.port() == addr.port()
+                && bound.is_ipv4() == addr.is_ipv4()
+                && (bound.ip() == addr.ip()
+                    || bound.ip().is_unspecified()
+                    || addr.ip().is_unspecified())
+        })
+    }
+
+    fn bind(&mut self, mut addr: SocketAddr) -> io::Result<SocketAddr> {
+        if addr.port() == 0 {
+            let start = *EPHEMERAL_PORTS.start();
+            let count = EPHEMERAL_PORTS.len() as u16;
+            let port = (0..count)
+                .map(|i| start + self.next_ephemeral.wrapping_add(i) % count)
+                .find(|port| !self.conflicts(SocketAddr::new(addr.ip(), *port)))
+                .ok_or_else(|| io::Error::from(io::ErrorKind::AddrInUse))?;
+            self.next_ephemeral = port - start + 1;
+            addr.set_port(port);
+        } else if self.conflicts(addr) {
+            return Err(io::ErrorKind::AddrInUse.into());
+        }
+        self.bound.insert(addr);
+        Ok(addr)
+    }
+}
+
+impl VirtualNetwork {
+    /// Creates a new network with nothing bound on it.
+    pub fn new() -> VirtualNetwork {
+        VirtualNetwork::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Switch> {
+        self.switch.lock().unwrap()
+    }
+
+    fn bind(&self, addr: SocketAddr, protocol: Protocol) -> io::Result<Binding> {
+        let mut switch = self.lock();
+        let ports = match protocol {
+            Protocol::Tcp => &mut switch.tcp_ports,
+            Protocol::Udp => &mut switch.udp_ports,
+        };
+        let addr = ports.bind(addr)?;
+        Ok(Binding {
+            network: self.clone(),
+            protocol,
+            addr,
+        })
+    }
+}
+
+/// Finds the endpoint which `addr` is delivered to in `endpoints`.
+fn route<T: Clone>(endpoints: &HashMap<SocketAddr, T>, addr: SocketAddr) -> Option<T> {
+    let wildcard = SocketAddr::new(unspecified(addr.is_ipv4()), addr.port());
+    endpoints
+        .get(&addr)
+        .or_else(|| endpoints.get(&wildcard))
+        .cloned()
+}
+
+fn unspecified(ipv4: bool) -> std::net::IpAddr {
+    if ipv4 {
+        Ipv4Addr::UNSPECIFIED.into()
+    } else {
+        Ipv6Addr::UNSPECIFIED.into()
+    }
+}
+
+fn loopback(ipv4: bool) -> std::net::IpAddr {
+    if ipv4 {
+        Ipv4Addr::LOCALHOST.into()
+    } else {
+        Ipv6Addr::LOCALHOST.into()
+    }
+}
+
+fn family_is_ipv4(family: SocketAddressFamily) -> bool {
+    family == SocketAddressFamily::Ipv4
+}
+
+#[derive(Clone, Copy)]
+enum Protocol {
+    Tcp,
+    Udp,
+}
+
+/// An address bound on a [`VirtualNetwork`], released when dropped along
+/// with any listener or inbox registered at it.
+struct Binding {
+    network: VirtualNetwork,
+    protocol: Protocol,
+    addr: SocketAddr,
+}
+
+impl Binding {
+    /// The address this binding sends from.
+    fn source(&self) -> SocketAddr {
+        if self.addr.ip().is_unspecified() {
+            SocketAddr::new(loopback(self.addr.is_ipv4()), self.addr.port())
+        } else {
+            self.addr
+        }
+    }
+}
+
+impl Drop for Binding {
+    fn drop(&mut self) {
+        let mut switch = self.network.lock();
+        match self.protocol {
+            Protocol::Tcp => {
+                switch.tcp_ports.bound.remove(&self.addr);
+                switch.listeners.remove(&self.addr);
+            }
+            Protocol::Udp => {
+                switch.udp_ports.bound.remove(&self.addr);
+                switch.inboxes.remove(&self.addr);
+            }
+        }
+    }
+}
+
+/// The tasks waiting for an endpoint to become ready.
+///
+/// Unlike the host's sockets, which tokio tracks readiness for, several tasks
+/// may wait on the same endpoint at once, so every waker is kept.
+#[derive(Default)]
+struct Wakers(Vec<Waker>);
+
+impl Wakers {
+    fn register(&mut self, cx: &Context<'_>) {
+        if !self.0.iter().any(|waker| waker.will_wake(cx.waker())) {
+            self.0.push(cx.waker().clone());
+        }
+    }
+
+    fn wake(&mut self) {
+        for waker in self.0.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+/// The socket options of a socket on a [`VirtualNetwork`].
+///
+/// Values are validated and clamped the same way as for the host's sockets,
+/// with defaults matching Linux.
+#[derive(Clone)]
+pub(crate) struct VirtualSocketOptions {
+    keep_alive_enabled: bool,
+    keep_alive_idle_time: Duration,
+    keep_alive_interval: Duration,
+    keep_alive_count: u32,
+    hop_limit: u8,
+    receive_buffer_size: u64,
+    send_buffer_size: u64,
+}
+
+impl Default for VirtualSocketOptions {
+    fn default() -> Self {
+        Self {
+            keep_alive_enabled: false,
+            keep_alive_idle_time: Duration::from_secs(7200),
+            keep_alive_interval: Duration::from_secs(75),
+            keep_alive_count: 9,
+            hop_limit: 64,
+            receive_buffer_size: TCP_BUFFER_SIZE as u64,
+            send_buffer_size: TCP_BUFFER_SIZE as u64,
+        }
+    }
+}
+
+/// Cap keep-alive durations at Linux' maximum, as the host does.
+const MAX_KEEP_ALIVE: Duration = Duration::from_secs(i16::MAX as u64);
+
+impl VirtualSocketOptions {
+    pub(crate) fn keep_alive_enabled(&self) -> bool {
+        self.keep_alive_enabled
+    }
+
+    pub(crate) fn set_keep_alive_enabled(&mut self, value: bool) {
+        self.keep_alive_enabled = value;
+    }
+
+    pub(crate) fn keep_alive_idle_time(&self) -> u64 {
+        self.keep_alive_idle_time.as_nanos() as u64
+    }
+
+    pub(crate) fn set_keep_alive_idle_time(&mut self, value: u64) -> Result<(), ErrorCode> {
+        self.keep_alive_idle_time = keep_alive_duration(value)?;
+        Ok(())
+    }
+
+    pub(crate) fn keep_alive_interval(&self) -> u64 {
+        self.keep_alive_interval.as_nanos() as u64
+    }
+
+    pub(crate) fn set_keep_alive_interval(&mut self, value: u64) -> Result<(), ErrorCode> {
+        self.keep_alive_interval = keep_alive_duration(value)?;
+        Ok(())
+    }
+
+    pub(crate) fn keep_alive_count(&self) -> u32 {
+        self.keep_alive_count
+    }
+
+    pub(crate) fn set_keep_alive_count(&mut self, value: u32) -> Result<(), ErrorCode> {
+        if value == 0 {
+            return Err(ErrorCode::InvalidArgument);
+        }
+        self.keep_alive_count = value.min(i8::MAX as u32);
+        Ok(())
+    }
+
+    pub(crate) fn hop_limit(&self) -> u8 {
+        self.hop_limit
+    }
+
+    pub(crate) fn set_hop_limit(&mut self, value: u8) -> Result<(), ErrorCode> {
+        if value == 0 {
+            return Err(ErrorCode::InvalidArgument);
+        }
+        self.hop_limit = value;
+        Ok(())
+    }
+
+    pub(crate) fn receive_buffer_size(&self) -> u64 {
+        self.receive_buffer_size
+    }
+
+    pub(crate) fn set_receive_buffer_size(&mut self, value: u64) -> Result<(), ErrorCode> {
+        self.receive_buffer_size = buffer_size(value)?;
+        Ok(())
+    }
+
+    pub(crate) fn send_buffer_size(&self) -> u64 {
+        self.send_buffer_size
+    }
+
+    pub(crate) fn set_send_buffer_size(&mut self, value: u64) -> Result<(), ErrorCode> {
+        self.send_buffer_size = buffer_size(value)?;
+        Ok(())
+    }
+}
+
+fn keep_alive_duration(nanos: u64) -> Result<Duration, ErrorCode> {
+    if nanos == 0 {
+        return Err(ErrorCode::InvalidArgument);
+    }
+    Ok(Duration::from_nanos(nanos).clamp(Duration::from_secs(1), MAX_KEEP_ALIVE))
+}
+
+fn buffer_size(value: u64) -> Result<u64, ErrorCode> {
+    if value == 0 {
+        return Err(ErrorCode::InvalidArgument);
+    }
+    Ok(value.min(i32::MAX as u64))
+}
+
+/// A TCP socket on a [`VirtualNetwork`] which isn't listening or connected.
+pub(crate) struct VirtualTcpSocket {
+    network: VirtualNetwork,
+    family: SocketAddressFamily,
+    binding: Option<Binding>,
+    options: Mutex<VirtualSocketOptions>,
+}
+
+impl VirtualTcpSocket {
+    pub(crate) fn new(network: &VirtualNetwork, family: SocketAddressFamily) -> Self {
+        Self {
+            network: network.clone(),
+            family,
+            binding: None,
+            options: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn options(&self) -> MutexGuard<'_, VirtualSocketOptions> {
+        self.options.lock().unwrap()
+    }
+
+    pub(crate) fn bind(&mut self, addr: SocketAddr) -> io::Result<()> {
+        if self.binding.is_some() {
+            return Err(io::ErrorKind::InvalidInput.into());
+        }
+        self.binding = Some(self.network.bind(addr, Protocol::Tcp)?);
+        Ok(())
+    }
+
+    pub(crate) fn local_addr(&self) -> io::Result<SocketAddr> {
+        match &self.binding {
+            Some(binding) => Ok(binding.addr),
+            None => Err(io::ErrorKind::InvalidInput.into()),
+        }
+    }
+
+    pub(crate) fn connect(self, addr: SocketAddr) -> io::Result<VirtualTcpStream> {
+        let binding = match self.binding {
+            Some(binding) => binding,
+            None => {
+                let ipv4 = family_is_ipv4(self.family);
+                let addr = SocketAddr::new(unspecified(ipv4), 0);
+                self.network.bind(addr, Protocol::Tcp)?
+            }
+        };
+        let backlog = route(&self.network.lock().listeners, addr)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::ConnectionRefused))?;
+
+        let to_server = Arc::new(Pipe::default());
+        let to_client = Arc::new(Pipe::default());
+        let local = binding.source();
+        let server = VirtualTcpStream {
+            rx: to_server.clone(),
+            tx: to_client.clone(),
+            local: addr,
+            peer: local,
+            _binding: None,
+            options: Mutex::default(),
+        };
+        backlog.push(server)?;
+        Ok(VirtualTcpStream {
+            rx: to_client,
+            tx: to_server,
+            local,
+            peer: addr,
+            _binding: Some(binding),
+            options: self.options,
+        })
+    }
+
+    pub(crate) fn listen(self, backlog: u32) -> io::Result<VirtualTcpListener> {
+        let binding = self
+            .binding
+            .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))?;
+        let queue = Arc::new(Backlog {
+            state: Mutex::new(BacklogState {
+                pending: VecDeque::new(),
+                limit: backlog,
+                wakers: Wakers::default(),
+            }),
+        });
+        self.network
+            .lock()
+            .listeners
+            .insert(binding.addr, queue.clone());
+        Ok(VirtualTcpListener {
+            queue,
+            binding,
+            options: self.options,
+        })
+    }
+}
+
+/// A listening TCP socket on a [`VirtualNetwork`].
+pub(crate) struct VirtualTcpListener {
+    queue: Arc<Backlog>,
+    binding: Binding,
+    options: Mutex<VirtualSocketOptions>,
+}
+
+struct Backlog {
+    state: Mutex<BacklogState>,
+}
+
+struct BacklogState {
+    pending: VecDeque<VirtualTcpStream>,
+    limit: u32,
+    wakers: Wakers,
+}
+
+impl Backlog {
+    fn push(&self, stream: VirtualTcpStream) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.pending.len() >= state.limit as usize {
+            return Err(io::ErrorKind::ConnectionRefused.into());
+        }
+        state.pending.push_back(stream);
+        state.wakers.wake();
+        Ok(())
+    }
+}
+
+impl VirtualTcpListener {
+    pub(crate) fn local_addr(&self) -> SocketAddr {
+        self.binding.addr
+    }
+
+    pub(crate) fn set_backlog(&self, backlog: u32) {
+        self.queue.state.lock().unwrap().limit = backlog;
+    }
+
+    pub(crate) fn options(&self) -> MutexGuard<'_, VirtualSocketOptions> {
+        self.options.lock().unwrap()
+    }
+
+    pub(crate) fn poll_accept(&self, cx: &mut Context<'_>) -> Poll<VirtualTcpStream> {
+        let mut state = self.queue.state.lock().unwrap();
+        match state.pending.pop_front() {
+            Some(stream) => {
+                // Like on Linux, accepted sockets inherit the listener's options.
+                *stream.options() = self.options().clone();
+                Poll::Ready(stream)
+            }
+            None => {
+                state.wakers.register(cx);
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// A connected TCP socket on a [`VirtualNetwork`].
+pub(crate) struct VirtualTcpStream {
+    rx: Arc<Pipe>,
+    tx: Arc<Pipe>,
+    local: SocketAddr,
+    peer: SocketAddr,
+    /// The port used by the connecting side, released once it is closed.
+    _binding: Option<Binding>,
+    options: Mutex<VirtualSocketOptions>,
+}
+
+/// One direction of a [`VirtualTcpStream`].
+#[derive(Default)]
+struct Pipe {
+    state: Mutex<PipeState>,
+}
+
+#[derive(Default)]
+struct PipeState {
+    buf: VecDeque<u8>,
+    /// No more data will be written.
+    write_closed: bool,
+    /// No more data will be read, so writes fail.
+    read_closed: bool,
+    readers: Wakers,
+    writers: Wakers,
+}
+
+impl VirtualTcpStream {
+    pub(crate) fn options(&self) -> MutexGuard<'_, VirtualSocketOptions> {
+        self.options.lock().unwrap()
+    }
+
+    pub(crate) fn local_addr(&self) -> SocketAddr {
+        self.local
+    }
+
+    pub(crate) fn peer_addr(&self) -> SocketAddr {
+        self.peer
+    }
+
+    pub(crate) fn try_read(&self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut state = self.rx.state.lock().unwrap();
+        if state.buf.is_empty() {
+            if state.write_closed || state.read_closed || buf.is_empty() {
+                return Ok(0);
+            }
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        let n = buf.len().min(state.buf.len());
+        for (dst, src) in buf.iter_mut().zip(state.buf.drain(..n)) {
+            *dst = src;
+        }
+        state.writers.wake();
+        Ok(n)
+    }
+
+    pub(crate) fn poll_read_ready(&self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let mut state = self.rx.state.lock().unwrap();
+        if !state.buf.is_empty() || state.write_closed || state.read_closed {
+            return Poll::Ready(Ok(()));
+        }
+        state.readers.register(cx);
+        Poll::Pending
+    }
+
+    pub(crate) fn try_write(&self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.tx.state.lock().unwrap();
+        if state.read_closed || state.write_closed {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        let n = buf.len().min(TCP_BUFFER_SIZE - state.buf.len());
+        if n == 0 && !buf.is_empty() {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        state.buf.extend(&buf[..n]);
+        state.readers.wake();
+        Ok(n)
+    }
+
+    pub(crate) fn poll_write_ready(&self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let mut state = self.tx.state.lock().unwrap();
+        if state.buf.len() < TCP_BUFFER_SIZE || state.read_closed || state.write_closed {
+            return Poll::Ready(Ok(()));
+        }
+        state.writers.register(cx);
+        Poll::Pending
+    }
+
+    pub(crate) fn shutdown(&self, how: Shutdown) {
+        if let Shutdown::Read | Shutdown::Both = how {
+            let mut state = self.rx.state.lock().unwrap();
+            state.read_closed = true;
+            state.buf.clear();
+            state.writers.wake();
+            state.readers.wake();
+        }
+        if let Shutdown::Write | Shutdown::Both = how {
+            let mut state = self.tx.state.lock().unwrap();
+            state.write_closed = true;
+            state.readers.wake();
+            state.writers.wake();
+        }
+    }
+}
+
+impl Drop for VirtualTcpStream {
+    fn drop(&mut self) {
+        self.shutdown(Shutdown::Both);
+    }
+}
+
+/// A UDP socket on a [`VirtualNetwork`].
+///
+/// All methods take `&self` as, like the host's sockets, this is shared with
+/// the streams used to send and receive datagrams.
+pub(crate) struct VirtualUdpSocket {
+    network: VirtualNetwork,
+    family: SocketAddressFamily,
+    inbox: Arc<Inbox>,
+    state: Mutex<UdpState>,
+    options: Mutex<VirtualSocketOptions>,
+}
+
+#[derive(Default)]
+struct UdpState {
+    binding: Option<Binding>,
+    peer: Option<SocketAddr>,
+}
+
+#[derive(Default)]
+struct Inbox {
+    state: Mutex<InboxState>,
+}
+
+#[derive(Default)]
+struct InboxState {
+    datagrams: VecDeque<(Vec<u8>, SocketAddr)>,
+    wakers: Wakers,
+    /// The address each sender last sent to, which replies to it are sent
+    /// from if this socket is bound to the wildcard address, like the host
+    /// does. Senders are themselves bound on the network, so this is bounded
+    /// by the number of addresses there.
+    reached_at: HashMap<SocketAddr, std::net::IpAddr>,
+}
+
+impl VirtualUdpSocket {
+    pub(crate) fn new(network: &VirtualNetwork, family: SocketAddressFamily) -> Self {
+        Self {
+            network: network.clone(),
+            family,
+            inbox: Arc::default(),
+            state: Mutex::default(),
+            options: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn options(&self) -> MutexGuard<'_, VirtualSocketOptions> {
+        self.options.lock().unwrap()
+    }
+
+    fn state(&self) -> MutexGuard<'_, UdpState> {
+        self.state.lock().unwrap()
+    }
+
+    pub(crate) fn bind(&self, addr: SocketAddr) -> io::Result<()> {
+        let mut state = self.state();
+        if state.binding.is_some() {
+            return Err(io::ErrorKind::InvalidInput.into());
+        }
+        let binding = self.network.bind(addr, Protocol::Udp)?;
+        self.network
+            .lock()
+            .inboxes
+            .insert(binding.addr, self.inbox.clone());
+        state.binding = Some(binding);
+        Ok(())
+    }
+
+    /// Binds to an ephemeral port if not yet bound, returning the address
+    /// datagrams to `remote` are sent from.
+    fn source(&self, remote: SocketAddr) -> io::Result<SocketAddr> {
+        if self.state().binding.is_none() {
+            let ipv4 = family_is_ipv4(self.family);
+            self.bind(SocketAddr::new(unspecified(ipv4), 0))?;
+        }
+        let state = self.state();
+        Ok(self.source_bound(state.binding.as_ref().unwrap(), remote))
+    }
+
+    fn source_bound(&self, binding: &Binding, remote: SocketAddr) -> SocketAddr {
+        if binding.addr.ip().is_unspecified() {
+            let inbox = self.inbox.state.lock().unwrap();
+            if let Some(ip) = inbox.reached_at.get(&remote) {
+                return SocketAddr::new(*ip, binding.addr.port());
+            }
+        }
+        binding.source()
+    }
+
+    pub(crate) fn connect(&self, addr: SocketAddr) -> io::Result<()> {
+        self.source(addr)?;
+        self.state().peer = Some(addr);
+        Ok(())
+    }
+
+    pub(crate) fn disconnect(&self) {
+        self.state().peer = None;
+    }
+
+    pub(crate) fn local_addr(&self) -> io::Result<SocketAddr> {
+        let state = self.state();
+        match (&state.binding, state.peer) {
+            // Like on the host, connecting picks a specific local address.
+            (Some(binding), Some(peer)) => Ok(self.source_bound(binding, peer)),
+            (Some(binding), None) => Ok(binding.addr),
+            (None, _) => Err(io::ErrorKind::InvalidInput.into()),
+        }
+    }
+
+    pub(crate) fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.state()
+            .peer
+            .ok_or_else(|| io::ErrorKind::NotConnected.into())
+    }
+
+    pub(crate) fn try_send(&self, buf: &[u8]) -> io::Result<usize> {
+        let peer = self.peer_addr()?;
+        self.try_send_to(buf, peer)
+    }
+
+    /// Delivers a datagram to `addr`, silently dropping it if nothing is
+    /// bound there or the receiver's queue is full.
+    pub(crate) fn try_send_to(&self, buf: &[u8], addr: SocketAddr) -> io::Result<usize> {
+        let source = self.source(addr)?;
+        let Some(inbox) = route(&self.network.lock().inboxes, addr) else {
+            return Ok(buf.len());
+        };
+        let mut state = inbox.state.lock().unwrap();
+        if !addr.ip().is_unspecified() {
+            state.reached_at.insert(source, addr.ip());
+        }
+        if state.datagrams.len() < UDP_QUEUE_LENGTH {
+            state.datagrams.push_back((buf.to_vec(), source));
+            state.wakers.wake();
+        }
+        Ok(buf.len())
+    }
+
+    pub(crate) fn try_recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        let peer = self.state().peer;
+        let mut state = self.inbox.state.lock().unwrap();
+        while let Some((data, source)) = state.datagrams.pop_front() {
+            // Like the host, connected sockets only receive from their peer.
+            if peer.is_some_and(|peer| peer != source) {
+                continue;
+            }
+            let n = buf.len().min(data.len());
+            buf[..n].copy_from_slice(&data[..n]);
+            return Ok((n, source));
+        }
+        Err(io::ErrorKind::WouldBlock.into())
+    }
+
+    pub(crate) fn poll_recv_ready(&self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let mut state = self.inbox.state.lock().unwrap();
+        if !state.datagrams.is_empty() {
+            return Poll::Ready(Ok(()));
+        }
+        state.wakers.register(cx);
+        Poll::Pending
+    }
+}
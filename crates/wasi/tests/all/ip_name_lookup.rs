@@ -0,0 +1,94 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Duration;
+use wasmtime::component::{Resource, ResourceTable};
+use wasmtime_wasi::p2::bindings::sockets::instance_network::Host as _;
+use wasmtime_wasi::p2::bindings::sockets::ip_name_lookup::{Host as _, HostResolveAddressStream};
+use wasmtime_wasi::p2::bindings::sockets::network::{ErrorCode, IpAddress};
+use wasmtime_wasi::sockets::{HostNameResolver, ResolveError, WasiSocketsView};
+use wasmtime_wasi::{WasiCtx, WasiCtxView, WasiView};
+
+/// A static hosts map which denies any name under `blocked.test`.
+struct Hosts(HashMap<&'static str, Vec<IpAddr>>);
+
+#[wasmtime_wasi::async_trait]
+impl HostNameResolver for Hosts {
+    async fn resolve(&self, name: &str) -> Result<Vec<IpAddr>, ResolveError> {
+        if name == "blocked.test" || name.ends_with(".blocked.test") {
+            return Err(ResolveError::AccessDenied);
+        }
+        Ok(self.0.get(name).cloned().unwrap_or_default())
+    }
+}
+
+struct Ctx {
+    wasi: WasiCtx,
+    table: ResourceTable,
+}
+
+impl WasiView for Ctx {
+    fn ctx(&mut self) -> WasiCtxView<'_> {
+        WasiCtxView {
+            ctx: &mut self.wasi,
+            table: &mut self.table,
+        }
+    }
+}
+
+async fn resolve(ctx: &mut Ctx, name: &str) -> Result<Vec<IpAddress>, ErrorCode> {
+    let mut view = ctx.sockets();
+    let network = view.instance_network().unwrap();
+    let stream = view
+        .resolve_addresses(network, name.to_string())
+        .map_err(|e| e.downcast().unwrap())?;
+    let rep = stream.rep();
+    let mut addrs = Vec::new();
+    loop {
+        match view.resolve_next_address(Resource::new_borrow(rep)) {
+            Ok(Some(addr)) => addrs.push(addr),
+            Ok(None) => break,
+            Err(e) => match e.downcast().unwrap() {
+                ErrorCode::WouldBlock => tokio::time::sleep(Duration::from_millis(1)).await,
+                code => return Err(code),
+            },
+        }
+    }
+    HostResolveAddressStream::drop(&mut view, stream).unwrap();
+    Ok(addrs)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn custom_resolver() -> Result<()> {
+    let hosts = Hosts(HashMap::from([(
+        "example.test",
+        vec![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))],
+    )]));
+    let mut ctx = Ctx {
+        wasi: WasiCtx::builder()
+            .allow_ip_name_lookup(true)
+            .ip_name_resolver(hosts)
+            .build(),
+        table: ResourceTable::new(),
+    };
+
+    let addrs = resolve(&mut ctx, "example.test").await;
+    assert!(
+        matches!(addrs.as_deref(), Ok([IpAddress::Ipv4((10, 0, 0, 1))])),
+        "{addrs:?}"
+    );
+    let addrs = resolve(&mut ctx, "www.blocked.test").await;
+    assert!(matches!(addrs, Err(ErrorCode::AccessDenied)), "{addrs:?}");
+    let addrs = resolve(&mut ctx, "missing.test").await;
+    assert!(
+        matches!(addrs, Err(ErrorCode::NameUnresolvable)),
+        "{addrs:?}"
+    );
+    // Literals never reach the resolver.
+    let addrs = resolve(&mut ctx, "127.0.0.1").await;
+    assert!(
+        matches!(addrs.as_deref(), Ok([IpAddress::Ipv4((127, 0, 0, 1))])),
+        "{addrs:?}"
+    );
+    Ok(())
+}
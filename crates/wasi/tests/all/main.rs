@@ -5,7 +5,12 @@ macro_rules! assert_test_exists {
     };
 }
 
+#[cfg(feature = "p2")]
+mod ip_name_lookup;
+mod memory_dir;
 mod store;
+#[cfg(feature = "p2")]
+mod virtual_network;
 
 #[cfg(feature = "p1")]
 mod p1;
@@ -0,0 +1,77 @@
+use anyhow::Result;
+use std::io;
+use wasmtime_wasi::filesystem::{MemoryDir, OpenOptions, OpenResult, WasiDir};
+
+#[test]
+fn size_limit() -> Result<()> {
+    let dir = MemoryDir::new().size_limit(100);
+    dir.write("a", [0; 50])?;
+    assert_eq!(dir.size(), 51);
+
+    let err = dir.write("b", [0; 50]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::StorageFull);
+
+    dir.unlink_file("a")?;
+    dir.write("b", [0; 50])?;
+    assert_eq!(dir.read("b")?, [0; 50]);
+    assert_eq!(dir.size(), 51);
+    Ok(())
+}
+
+#[test]
+fn overlay_is_copy_on_write() -> Result<()> {
+    let tmp = tempfile::tempdir()?;
+    std::fs::write(tmp.path().join("file"), "lower")?;
+    std::fs::create_dir(tmp.path().join("sub"))?;
+    std::fs::write(tmp.path().join("sub/nested"), "nested")?;
+
+    let lower = cap_std::fs::Dir::open_ambient_dir(tmp.path(), cap_std::ambient_authority())?;
+    let dir = MemoryDir::overlay(lower);
+    assert_eq!(dir.read("file")?, b"lower");
+    assert_eq!(dir.read("sub/nested")?, b"nested");
+
+    dir.write("file", "upper")?;
+    dir.unlink_file("sub/nested")?;
+    dir.create_dir("new")?;
+    assert_eq!(dir.read("file")?, b"upper");
+    assert_eq!(
+        dir.read("sub/nested").unwrap_err().kind(),
+        io::ErrorKind::NotFound
+    );
+
+    // The host directory is left untouched.
+    assert_eq!(std::fs::read(tmp.path().join("file"))?, b"lower");
+    assert!(tmp.path().join("sub/nested").exists());
+    assert!(!tmp.path().join("new").exists());
+
+    // Only what was written to the overlay counts against the size limit.
+    assert_eq!(dir.size(), "upper".len() as u64 + "new".len() as u64);
+    Ok(())
+}
+
+#[test]
+fn overlay_copies_up_partial_writes() -> Result<()> {
+    let tmp = tempfile::tempdir()?;
+    std::fs::create_dir(tmp.path().join("sub"))?;
+    std::fs::write(tmp.path().join("sub/file"), "lower")?;
+
+    let lower = cap_std::fs::Dir::open_ambient_dir(tmp.path(), cap_std::ambient_authority())?;
+    let dir = MemoryDir::overlay(lower);
+    let options = OpenOptions {
+        read: true,
+        write: true,
+        ..OpenOptions::default()
+    };
+    let OpenResult::File(file) = dir.open_at("sub/file", &options)? else {
+        panic!("expected a file");
+    };
+    let mut buf = [0; 5];
+    assert_eq!(file.read_at(&mut buf, 0)?, 5);
+    assert_eq!(&buf, b"lower");
+
+    file.write_at(b"L", 0)?;
+    assert_eq!(dir.read("sub/file")?, b"Lower");
+    assert_eq!(std::fs::read(tmp.path().join("sub/file"))?, b"lower");
+    assert_eq!(dir.size(), 5);
+    Ok(())
+}
@@ -2,28 +2,45 @@ use crate::store::Ctx;
 use anyhow::Result;
 use std::path::Path;
 use test_programs_artifacts::*;
-use wasmtime::{Linker, Module};
+use wasmtime::{Engine, Linker, Module, Store};
 use wasmtime_wasi::p1::{WasiP1Ctx, add_to_linker_async};
 
 async fn run(path: &str, inherit_stdio: bool) -> Result<()> {
     let path = Path::new(path);
     let name = path.file_stem().unwrap().to_str().unwrap();
-    let engine = test_programs_artifacts::engine(|config| {
-        config.async_support(true);
-    });
-    let mut linker = Linker::<Ctx<WasiP1Ctx>>::new(&engine);
-    add_to_linker_async(&mut linker, |t| &mut t.wasi)?;
-
-    let module = Module::from_file(&engine, path)?;
+    let engine = engine();
     let (mut store, _td) = Ctx::new(&engine, name, |builder| {
         if inherit_stdio {
             builder.inherit_stdio();
         }
         builder.build_p1()
     })?;
-    let instance = linker.instantiate_async(&mut store, &module).await?;
-    let start = instance.get_typed_func::<(), ()>(&mut store, "_start")?;
-    start.call_async(&mut store, ()).await?;
+    start(&engine, path, &mut store).await
+}
+
+/// Same as `run`, except that the guest's scratch directory is a `MemoryDir`.
+async fn run_in_memory(path: &str) -> Result<()> {
+    let path = Path::new(path);
+    let name = path.file_stem().unwrap().to_str().unwrap();
+    let engine = engine();
+    let (mut store, _workspace) = Ctx::new_in_memory(&engine, name, |builder| builder.build_p1())?;
+    start(&engine, path, &mut store).await
+}
+
+fn engine() -> Engine {
+    test_programs_artifacts::engine(|config| {
+        config.async_support(true);
+    })
+}
+
+async fn start(engine: &Engine, path: &Path, store: &mut Store<Ctx<WasiP1Ctx>>) -> Result<()> {
+    let mut linker = Linker::<Ctx<WasiP1Ctx>>::new(engine);
+    add_to_linker_async(&mut linker, |t| &mut t.wasi)?;
+
+    let module = Module::from_file(engine, path)?;
+    let instance = linker.instantiate_async(&mut *store, &module).await?;
+    let start = instance.get_typed_func::<(), ()>(&mut *store, "_start")?;
+    start.call_async(&mut *store, ()).await?;
     Ok(())
 }
 
@@ -251,3 +268,58 @@ async fn preview1_file_write() {
 async fn preview1_path_open_lots() {
     run(PREVIEW1_PATH_OPEN_LOTS, true).await.unwrap()
 }
+
+// Filesystem tests are additionally run against an in-memory scratch
+// directory to exercise `MemoryDir`.
+macro_rules! in_memory_tests {
+    ($($name:ident: $path:ident,)*) => {
+        mod in_memory {
+            use super::*;
+            $(
+                #[test_log::test(tokio::test(flavor = "multi_thread"))]
+                async fn $name() {
+                    run_in_memory($path).await.unwrap()
+                }
+            )*
+        }
+    };
+}
+
+in_memory_tests! {
+    preview1_close_preopen: PREVIEW1_CLOSE_PREOPEN,
+    preview1_dangling_fd: PREVIEW1_DANGLING_FD,
+    preview1_dangling_symlink: PREVIEW1_DANGLING_SYMLINK,
+    preview1_directory_seek: PREVIEW1_DIRECTORY_SEEK,
+    preview1_dir_fd_op_failures: PREVIEW1_DIR_FD_OP_FAILURES,
+    preview1_fd_advise: PREVIEW1_FD_ADVISE,
+    preview1_fd_filestat_get: PREVIEW1_FD_FILESTAT_GET,
+    preview1_fd_filestat_set: PREVIEW1_FD_FILESTAT_SET,
+    preview1_fd_flags_set: PREVIEW1_FD_FLAGS_SET,
+    preview1_fd_readdir: PREVIEW1_FD_READDIR,
+    preview1_file_allocate: PREVIEW1_FILE_ALLOCATE,
+    preview1_file_pread_pwrite: PREVIEW1_FILE_PREAD_PWRITE,
+    preview1_file_read_write: PREVIEW1_FILE_READ_WRITE,
+    preview1_file_seek_tell: PREVIEW1_FILE_SEEK_TELL,
+    preview1_file_truncation: PREVIEW1_FILE_TRUNCATION,
+    preview1_file_unbuffered_write: PREVIEW1_FILE_UNBUFFERED_WRITE,
+    preview1_interesting_paths: PREVIEW1_INTERESTING_PATHS,
+    preview1_nofollow_errors: PREVIEW1_NOFOLLOW_ERRORS,
+    preview1_overwrite_preopen: PREVIEW1_OVERWRITE_PREOPEN,
+    preview1_path_exists: PREVIEW1_PATH_EXISTS,
+    preview1_path_filestat: PREVIEW1_PATH_FILESTAT,
+    preview1_path_link: PREVIEW1_PATH_LINK,
+    preview1_path_open_create_existing: PREVIEW1_PATH_OPEN_CREATE_EXISTING,
+    preview1_path_open_dirfd_not_dir: PREVIEW1_PATH_OPEN_DIRFD_NOT_DIR,
+    preview1_path_open_missing: PREVIEW1_PATH_OPEN_MISSING,
+    preview1_path_open_read_write: PREVIEW1_PATH_OPEN_READ_WRITE,
+    preview1_path_rename: PREVIEW1_PATH_RENAME,
+    preview1_path_rename_dir_trailing_slashes: PREVIEW1_PATH_RENAME_DIR_TRAILING_SLASHES,
+    preview1_path_symlink_trailing_slashes: PREVIEW1_PATH_SYMLINK_TRAILING_SLASHES,
+    preview1_readlink: PREVIEW1_READLINK,
+    preview1_remove_directory: PREVIEW1_REMOVE_DIRECTORY,
+    preview1_remove_nonempty_directory: PREVIEW1_REMOVE_NONEMPTY_DIRECTORY,
+    preview1_symlink_create: PREVIEW1_SYMLINK_CREATE,
+    preview1_symlink_filestat: PREVIEW1_SYMLINK_FILESTAT,
+    preview1_symlink_loop: PREVIEW1_SYMLINK_LOOP,
+    preview1_unlink_file_trailing_slashes: PREVIEW1_UNLINK_FILE_TRAILING_SLASHES,
+}
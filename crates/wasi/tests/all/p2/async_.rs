@@ -5,8 +5,19 @@ use test_programs_artifacts::*;
 use wasmtime::component::{Component, Linker};
 use wasmtime_wasi::p2::add_to_linker_async;
 use wasmtime_wasi::p2::bindings::Command;
+use wasmtime_wasi::sockets::VirtualNetwork;
 
 async fn run(path: &str, inherit_stdio: bool) -> Result<()> {
+    run_with_network(path, inherit_stdio, None).await
+}
+
+/// Same as `run`, except that the guest's sockets are on `network` rather
+/// than the host's network, if specified.
+async fn run_with_network(
+    path: &str,
+    inherit_stdio: bool,
+    network: Option<VirtualNetwork>,
+) -> Result<()> {
     let path = Path::new(path);
     let name = path.file_stem().unwrap().to_str().unwrap();
     let engine = test_programs_artifacts::engine(|config| {
@@ -19,6 +30,9 @@ async fn run(path: &str, inherit_stdio: bool) -> Result<()> {
         if inherit_stdio {
             builder.inherit_stdio();
         }
+        if let Some(network) = network {
+            builder.virtual_network(network);
+        }
         MyWasiCtx {
             wasi: builder.build(),
             table: Default::default(),
@@ -405,3 +419,34 @@ async fn preview2_file_read_write() {
         .await
         .unwrap()
 }
+
+// Socket tests are additionally run on a virtual network to exercise
+// `VirtualNetwork`. The bind tests are skipped as every address can be bound
+// on a virtual network.
+macro_rules! virtual_network_tests {
+    ($($name:ident: $path:ident,)*) => {
+        mod virtual_network {
+            use super::*;
+            $(
+                #[test_log::test(tokio::test(flavor = "multi_thread"))]
+                async fn $name() {
+                    run_with_network($path, false, Some(VirtualNetwork::new()))
+                        .await
+                        .unwrap()
+                }
+            )*
+        }
+    };
+}
+
+virtual_network_tests! {
+    preview2_tcp_sample_application: PREVIEW2_TCP_SAMPLE_APPLICATION_COMPONENT,
+    preview2_tcp_states: PREVIEW2_TCP_STATES_COMPONENT,
+    preview2_tcp_streams: PREVIEW2_TCP_STREAMS_COMPONENT,
+    preview2_tcp_connect: PREVIEW2_TCP_CONNECT_COMPONENT,
+    preview2_tcp_sockopts: PREVIEW2_TCP_SOCKOPTS_COMPONENT,
+    preview2_udp_sample_application: PREVIEW2_UDP_SAMPLE_APPLICATION_COMPONENT,
+    preview2_udp_states: PREVIEW2_UDP_STATES_COMPONENT,
+    preview2_udp_connect: PREVIEW2_UDP_CONNECT_COMPONENT,
+    preview2_udp_sockopts: PREVIEW2_UDP_SOCKOPTS_COMPONENT,
+}
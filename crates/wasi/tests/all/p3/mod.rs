@@ -5,6 +5,7 @@ use test_programs_artifacts::*;
 use wasmtime::Result;
 use wasmtime::component::{Component, Linker};
 use wasmtime_wasi::p3::bindings::Command;
+use wasmtime_wasi::sockets::VirtualNetwork;
 
 async fn run(path: &str) -> Result<()> {
     run_allow_blocking_current_thread(path, false).await
@@ -13,6 +14,14 @@ async fn run(path: &str) -> Result<()> {
 async fn run_allow_blocking_current_thread(
     path: &str,
     allow_blocking_current_thread: bool,
+) -> Result<()> {
+    run_with(path, allow_blocking_current_thread, None).await
+}
+
+async fn run_with(
+    path: &str,
+    allow_blocking_current_thread: bool,
+    network: Option<VirtualNetwork>,
 ) -> Result<()> {
     let path = Path::new(path);
     let name = path.file_stem().unwrap().to_str().unwrap();
@@ -26,11 +35,16 @@ async fn run_allow_blocking_current_thread(
         .context("failed to link `wasi:cli@0.2.x`")?;
     wasmtime_wasi::p3::add_to_linker(&mut linker).context("failed to link `wasi:cli@0.3.x`")?;
 
-    let (mut store, _td) = Ctx::new(&engine, name, |builder| MyWasiCtx {
-        wasi: builder
-            .allow_blocking_current_thread(allow_blocking_current_thread)
-            .build(),
-        table: Default::default(),
+    let (mut store, _td) = Ctx::new(&engine, name, |builder| {
+        if let Some(network) = network {
+            builder.virtual_network(network);
+        }
+        MyWasiCtx {
+            wasi: builder
+                .allow_blocking_current_thread(allow_blocking_current_thread)
+                .build(),
+            table: Default::default(),
+        }
     })?;
     let component = Component::from_file(&engine, path)?;
     let instance = linker.instantiate_async(&mut store, &component).await?;
@@ -152,3 +166,32 @@ async fn p3_file_write() -> anyhow::Result<()> {
 async fn p3_file_write_blocking() -> anyhow::Result<()> {
     run_allow_blocking_current_thread(P3_FILE_WRITE_COMPONENT, true).await
 }
+
+// Socket tests are additionally run on a virtual network to exercise
+// `VirtualNetwork`. The bind tests are skipped as every address can be bound
+// on a virtual network.
+macro_rules! virtual_network_tests {
+    ($($name:ident: $path:ident,)*) => {
+        mod virtual_network {
+            use super::*;
+            $(
+                #[test_log::test(tokio::test(flavor = "multi_thread"))]
+                async fn $name() -> anyhow::Result<()> {
+                    run_with($path, false, Some(VirtualNetwork::new())).await
+                }
+            )*
+        }
+    };
+}
+
+virtual_network_tests! {
+    p3_sockets_tcp_connect: P3_SOCKETS_TCP_CONNECT_COMPONENT,
+    p3_sockets_tcp_sample_application: P3_SOCKETS_TCP_SAMPLE_APPLICATION_COMPONENT,
+    p3_sockets_tcp_sockopts: P3_SOCKETS_TCP_SOCKOPTS_COMPONENT,
+    p3_sockets_tcp_states: P3_SOCKETS_TCP_STATES_COMPONENT,
+    p3_sockets_tcp_streams: P3_SOCKETS_TCP_STREAMS_COMPONENT,
+    p3_sockets_udp_connect: P3_SOCKETS_UDP_CONNECT_COMPONENT,
+    p3_sockets_udp_sample_application: P3_SOCKETS_UDP_SAMPLE_APPLICATION_COMPONENT,
+    p3_sockets_udp_sockopts: P3_SOCKETS_UDP_SOCKOPTS_COMPONENT,
+    p3_sockets_udp_states: P3_SOCKETS_UDP_STATES_COMPONENT,
+}
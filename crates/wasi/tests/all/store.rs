@@ -2,6 +2,7 @@ use anyhow::Result;
 use tempfile::TempDir;
 use wasmtime::component::ResourceTable;
 use wasmtime::{Engine, Store};
+use wasmtime_wasi::filesystem::MemoryDir;
 use wasmtime_wasi::{
     DirPerms, FilePerms, WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView, p2::pipe::MemoryOutputPipe,
 };
@@ -24,10 +25,59 @@ impl<T> Ctx<T> {
         name: &str,
         configure: impl FnOnce(&mut WasiCtxBuilder) -> T,
     ) -> Result<(Store<Ctx<T>>, TempDir)> {
+        let workspace = prepare_workspace(name)?;
+        let store = Ctx::with_preopen(
+            engine,
+            name,
+            |builder| {
+                println!("preopen: {workspace:?}");
+                builder.preopened_dir(workspace.path(), ".", DirPerms::all(), FilePerms::all())?;
+                Ok(())
+            },
+            test_programs_artifacts::wasi_tests_environment(),
+            configure,
+        )?;
+        Ok((store, workspace))
+    }
+
+    /// Same as [`Ctx::new`] except that the guest's scratch directory is a
+    /// [`MemoryDir`] instead of a temporary directory on the host.
+    pub fn new_in_memory(
+        engine: &Engine,
+        name: &str,
+        configure: impl FnOnce(&mut WasiCtxBuilder) -> T,
+    ) -> Result<(Store<Ctx<T>>, MemoryDir)> {
+        let workspace = MemoryDir::new();
+        let store = Ctx::with_preopen(
+            engine,
+            name,
+            |builder| {
+                builder.preopened_virtual_dir(
+                    workspace.clone(),
+                    ".",
+                    DirPerms::all(),
+                    FilePerms::all(),
+                );
+                Ok(())
+            },
+            // `MemoryDir` behaves the same on all platforms, following Linux
+            // in the details.
+            &[("ERRNO_MODE_UNIX", "1")],
+            configure,
+        )?;
+        Ok((store, workspace))
+    }
+
+    fn with_preopen(
+        engine: &Engine,
+        name: &str,
+        preopen: impl FnOnce(&mut WasiCtxBuilder) -> Result<()>,
+        env: &[(&str, &str)],
+        configure: impl FnOnce(&mut WasiCtxBuilder) -> T,
+    ) -> Result<Store<Ctx<T>>> {
         const MAX_OUTPUT_SIZE: usize = 10 << 20;
         let stdout = MemoryOutputPipe::new(MAX_OUTPUT_SIZE);
         let stderr = MemoryOutputPipe::new(MAX_OUTPUT_SIZE);
-        let workspace = prepare_workspace(name)?;
 
         // Create our wasi context.
         let mut builder = WasiCtxBuilder::new();
@@ -37,9 +87,8 @@ impl<T> Ctx<T> {
             .args(&[name, "."])
             .inherit_network()
             .allow_ip_name_lookup(true);
-        println!("preopen: {workspace:?}");
-        builder.preopened_dir(workspace.path(), ".", DirPerms::all(), FilePerms::all())?;
-        for (var, val) in test_programs_artifacts::wasi_tests_environment() {
+        preopen(&mut builder)?;
+        for (var, val) in env {
             builder.env(var, val);
         }
 
@@ -54,7 +103,7 @@ impl<T> Ctx<T> {
             stdout,
         };
 
-        Ok((Store::new(&engine, ctx), workspace))
+        Ok(Store::new(&engine, ctx))
     }
 }
 
@@ -0,0 +1,216 @@
+use anyhow::Result;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use wasmtime::component::{Resource, ResourceTable};
+use wasmtime_wasi::p2::bindings::sockets::instance_network::Host as _;
+use wasmtime_wasi::p2::bindings::sockets::network::{
+    ErrorCode, IpAddressFamily, IpSocketAddress, Ipv4SocketAddress,
+};
+use wasmtime_wasi::p2::bindings::sockets::tcp::HostTcpSocket;
+use wasmtime_wasi::p2::bindings::sockets::tcp_create_socket::Host as _;
+use wasmtime_wasi::p2::bindings::sockets::udp;
+use wasmtime_wasi::p2::bindings::sockets::udp_create_socket::Host as _;
+use wasmtime_wasi::sockets::{TcpSocket, VirtualNetwork, WasiSocketsView};
+use wasmtime_wasi::{WasiCtx, WasiCtxView, WasiView};
+
+struct Ctx {
+    wasi: WasiCtx,
+    table: ResourceTable,
+}
+
+impl Ctx {
+    fn new(network: &VirtualNetwork) -> Ctx {
+        Ctx {
+            wasi: WasiCtx::builder()
+                .inherit_network()
+                .virtual_network(network.clone())
+                .build(),
+            table: ResourceTable::new(),
+        }
+    }
+}
+
+impl WasiView for Ctx {
+    fn ctx(&mut self) -> WasiCtxView<'_> {
+        WasiCtxView {
+            ctx: &mut self.wasi,
+            table: &mut self.table,
+        }
+    }
+}
+
+fn addr(ip: (u8, u8, u8, u8), port: u16) -> IpSocketAddress {
+    IpSocketAddress::Ipv4(Ipv4SocketAddress { port, address: ip })
+}
+
+fn borrow(socket: &Resource<TcpSocket>) -> Resource<TcpSocket> {
+    Resource::new_borrow(socket.rep())
+}
+
+async fn listen(ctx: &mut Ctx, local: IpSocketAddress) -> Result<Resource<TcpSocket>, ErrorCode> {
+    let mut view = ctx.sockets();
+    let network = view.instance_network().unwrap();
+    let socket = view.create_tcp_socket(IpAddressFamily::Ipv4).unwrap();
+    view.start_bind(borrow(&socket), network, local)
+        .await
+        .map_err(|e| e.downcast().unwrap())?;
+    view.finish_bind(borrow(&socket)).unwrap();
+    view.start_listen(borrow(&socket)).unwrap();
+    view.finish_listen(borrow(&socket)).unwrap();
+    Ok(socket)
+}
+
+async fn connect(ctx: &mut Ctx, remote: IpSocketAddress) -> Result<Resource<TcpSocket>, ErrorCode> {
+    let mut view = ctx.sockets();
+    let network = view.instance_network().unwrap();
+    let socket = view.create_tcp_socket(IpAddressFamily::Ipv4).unwrap();
+    view.start_connect(borrow(&socket), network, remote)
+        .await
+        .unwrap();
+    loop {
+        match view.finish_connect(borrow(&socket)) {
+            Ok(_) => return Ok(socket),
+            Err(e) => match e.downcast().unwrap() {
+                ErrorCode::WouldBlock => tokio::time::sleep(Duration::from_millis(1)).await,
+                code => return Err(code),
+            },
+        }
+    }
+}
+
+/// Binds a UDP socket to `local` and opens its streams, sending only to
+/// `remote` if it's given.
+async fn udp_stream(
+    ctx: &mut Ctx,
+    local: IpSocketAddress,
+    remote: Option<IpSocketAddress>,
+) -> (
+    Resource<udp::UdpSocket>,
+    Resource<udp::IncomingDatagramStream>,
+    Resource<udp::OutgoingDatagramStream>,
+) {
+    let mut view = ctx.sockets();
+    let network = view.instance_network().unwrap();
+    let socket = view.create_udp_socket(IpAddressFamily::Ipv4).unwrap();
+    let this = || Resource::new_borrow(socket.rep());
+    udp::HostUdpSocket::start_bind(&mut view, this(), network, local)
+        .await
+        .unwrap();
+    udp::HostUdpSocket::finish_bind(&mut view, this()).unwrap();
+    let (incoming, outgoing) = udp::HostUdpSocket::stream(&mut view, this(), remote)
+        .await
+        .unwrap();
+    (socket, incoming, outgoing)
+}
+
+async fn udp_send(
+    ctx: &mut Ctx,
+    outgoing: &Resource<udp::OutgoingDatagramStream>,
+    data: &[u8],
+    remote: Option<IpSocketAddress>,
+) {
+    let mut view = ctx.sockets();
+    let this = || Resource::new_borrow(outgoing.rep());
+    udp::HostOutgoingDatagramStream::check_send(&mut view, this()).unwrap();
+    let datagram = udp::OutgoingDatagram {
+        data: data.to_vec(),
+        remote_address: remote,
+    };
+    let sent = udp::HostOutgoingDatagramStream::send(&mut view, this(), vec![datagram])
+        .await
+        .unwrap();
+    assert_eq!(sent, 1);
+}
+
+async fn udp_receive(
+    ctx: &mut Ctx,
+    incoming: &Resource<udp::IncomingDatagramStream>,
+) -> udp::IncomingDatagram {
+    let mut view = ctx.sockets();
+    loop {
+        let this = Resource::new_borrow(incoming.rep());
+        let datagrams = udp::HostIncomingDatagramStream::receive(&mut view, this, 1).unwrap();
+        if let Some(datagram) = datagrams.into_iter().next() {
+            return datagram;
+        }
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn connect_between_contexts() -> Result<()> {
+    let network = VirtualNetwork::new();
+    let mut server = Ctx::new(&network);
+    let mut client = Ctx::new(&network);
+    let mut outsider = Ctx::new(&VirtualNetwork::new());
+
+    let listener = listen(&mut server, addr((10, 0, 0, 1), 80)).await.unwrap();
+    let result = listen(&mut client, addr((10, 0, 0, 1), 80)).await;
+    assert!(matches!(result, Err(ErrorCode::AddressInUse)), "{result:?}");
+
+    let connection = connect(&mut client, addr((10, 0, 0, 1), 80)).await.unwrap();
+    let local = client.sockets().local_address(borrow(&connection)).unwrap();
+    let local = SocketAddr::from(local);
+    // Unbound sockets connect from the loopback address.
+    assert_eq!(local.ip(), Ipv4Addr::LOCALHOST);
+    assert_ne!(local.port(), 0);
+
+    let (accepted, _, _) = server.sockets().accept(borrow(&listener)).unwrap();
+    let remote = server.sockets().remote_address(accepted).unwrap();
+    assert_eq!(SocketAddr::from(remote), local);
+
+    // Separate networks, like the host's network, can't be reached.
+    let result = connect(&mut outsider, addr((10, 0, 0, 1), 80)).await;
+    assert!(
+        matches!(result, Err(ErrorCode::ConnectionRefused)),
+        "{result:?}"
+    );
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn udp_replies_from_wildcard_address() -> Result<()> {
+    let network = VirtualNetwork::new();
+    let mut server = Ctx::new(&network);
+    let mut client = Ctx::new(&network);
+
+    let (_server, server_in, server_out) =
+        udp_stream(&mut server, addr((0, 0, 0, 0), 53), None).await;
+    let (client_socket, client_in, client_out) = udp_stream(
+        &mut client,
+        addr((0, 0, 0, 0), 0),
+        Some(addr((10, 0, 0, 1), 53)),
+    )
+    .await;
+
+    udp_send(&mut client, &client_out, b"query", None).await;
+    let query = udp_receive(&mut server, &server_in).await;
+    assert_eq!(query.data, b"query");
+    let local = udp::HostUdpSocket::local_address(
+        &mut client.sockets(),
+        Resource::new_borrow(client_socket.rep()),
+    )
+    .unwrap();
+    assert_eq!(
+        SocketAddr::from(query.remote_address),
+        SocketAddr::from(local)
+    );
+
+    // The reply comes from the address the client sent to, rather than the
+    // wildcard address the server is bound to, so the connected client
+    // accepts it.
+    udp_send(
+        &mut server,
+        &server_out,
+        b"reply",
+        Some(query.remote_address),
+    )
+    .await;
+    let reply = udp_receive(&mut client, &client_in).await;
+    assert_eq!(reply.data, b"reply");
+    assert_eq!(
+        SocketAddr::from(reply.remote_address),
+        SocketAddr::from(addr((10, 0, 0, 1), 53))
+    );
+    Ok(())
+}
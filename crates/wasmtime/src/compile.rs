@@ -48,6 +48,8 @@ pub use self::code_builder::{CodeBuilder, CodeHint, HashedEngineCompileEnv};
 
 #[cfg(feature = "runtime")]
 mod runtime;
+#[cfg(all(feature = "runtime", feature = "winch"))]
+pub(crate) use self::runtime::compile_tier_up_module;
 
 /// Converts an input binary-encoded WebAssembly module to compilation
 /// artifacts and type information.
@@ -69,6 +71,24 @@ pub(crate) fn build_artifacts<T: FinishedObject>(
 ) -> Result<(
     T,
     Option<(CompiledModuleInfo, CompiledFunctionsTable, ModuleTypes)>,
+)> {
+    build_artifacts_with_compiler(engine, engine.compiler(), wasm, dwarf_package, obj_state)
+}
+
+/// Same as [`build_artifacts`] except that `compiler` is used instead of the
+/// engine's compiler.
+///
+/// This is used by tiered compilation to recompile a module with the
+/// optimizing compiler of [`Compiler::tier_up_compiler`].
+pub(crate) fn build_artifacts_with_compiler<T: FinishedObject>(
+    engine: &Engine,
+    compiler: &dyn Compiler,
+    wasm: &[u8],
+    dwarf_package: Option<&[u8]>,
+    obj_state: &T::State,
+) -> Result<(
+    T,
+    Option<(CompiledModuleInfo, CompiledFunctionsTable, ModuleTypes)>,
 )> {
     let tunables = engine.tunables();
 
@@ -91,7 +111,7 @@ pub(crate) fn build_artifacts<T: FinishedObject>(
     let functions = mem::take(&mut translation.function_body_inputs);
 
     let compile_inputs = CompileInputs::for_module(&types, &translation, functions);
-    let unlinked_compile_outputs = compile_inputs.compile(engine)?;
+    let unlinked_compile_outputs = compile_inputs.compile(engine, compiler)?;
     let PreLinkOutput {
         needs_gc_heap,
         compiled_funcs,
@@ -101,7 +121,7 @@ pub(crate) fn build_artifacts<T: FinishedObject>(
 
     // Emplace all compiled functions into the object file with any other
     // sections associated with code as well.
-    let mut object = engine.compiler().object(ObjectKind::Module)?;
+    let mut object = compiler.object(ObjectKind::Module)?;
     // Insert `Engine` and type-level information into the compiled
     // artifact so if this module is deserialized later it contains all
     // information necessary.
@@ -117,6 +137,7 @@ pub(crate) fn build_artifacts<T: FinishedObject>(
     let (mut object, compilation_artifacts) = indices.link_and_append_code(
         object,
         engine,
+        compiler,
         compiled_funcs,
         std::iter::once(translation).collect(),
         dwarf_package,
@@ -169,7 +190,7 @@ pub(crate) fn build_component_artifacts<T: FinishedObject>(
             (i, &*translation, functions)
         }),
     );
-    let unlinked_compile_outputs = compile_inputs.compile(&engine)?;
+    let unlinked_compile_outputs = compile_inputs.compile(&engine, compiler)?;
 
     let PreLinkOutput {
         needs_gc_heap,
@@ -187,6 +208,7 @@ pub(crate) fn build_component_artifacts<T: FinishedObject>(
     let (mut object, compilation_artifacts) = indices.link_and_append_code(
         object,
         engine,
+        compiler,
         compiled_funcs,
         module_translations,
         None, // TODO: Support dwarf packages for components.
@@ -478,9 +500,11 @@ impl<'a> CompileInputs<'a> {
 
     /// Compile these `CompileInput`s (maybe in parallel) and return the
     /// resulting `UnlinkedCompileOutput`s.
-    fn compile(self, engine: &Engine) -> Result<UnlinkedCompileOutputs<'a>> {
-        let compiler = engine.compiler();
-
+    fn compile(
+        self,
+        engine: &Engine,
+        compiler: &dyn Compiler,
+    ) -> Result<UnlinkedCompileOutputs<'a>> {
         if self.inputs.len() > 0 && cfg!(miri) {
             bail!(
                 "\
@@ -525,7 +549,7 @@ the use case.
         // wasmtime-builtin functions are necessary. If so those need to be
         // collected and then those trampolines additionally need to be
         // compiled.
-        compile_required_builtins(engine, &mut raw_outputs)?;
+        compile_required_builtins(engine, compiler, &mut raw_outputs)?;
 
         // Bucket the outputs by kind.
         let mut outputs: BTreeMap<FuncKey, CompileOutput> = BTreeMap::new();
@@ -818,8 +842,11 @@ the use case.
     }
 }
 
-fn compile_required_builtins(engine: &Engine, raw_outputs: &mut Vec<CompileOutput>) -> Result<()> {
-    let compiler = engine.compiler();
+fn compile_required_builtins(
+    engine: &Engine,
+    compiler: &dyn Compiler,
+    raw_outputs: &mut Vec<CompileOutput>,
+) -> Result<()> {
     let mut builtins = HashSet::new();
     let mut new_inputs: Vec<CompileInput<'_>> = Vec::new();
 
@@ -937,6 +964,7 @@ impl FunctionIndices {
         self,
         mut obj: object::write::Object<'static>,
         engine: &'a Engine,
+        compiler: &dyn Compiler,
         compiled_funcs: Vec<(String, Box<dyn Any + Send + Sync>)>,
         translations: PrimaryMap<StaticModuleIndex, ModuleTranslation<'_>>,
         dwarf_package_bytes: Option<&[u8]>,
@@ -946,7 +974,6 @@ impl FunctionIndices {
         // The result is a vector parallel to `compiled_funcs` where
         // `symbol_ids_and_locs[i]` is the symbol ID and function location of
         // `compiled_funcs[i]`.
-        let compiler = engine.compiler();
         let tunables = engine.tunables();
         let symbol_ids_and_locs = compiler.append_code(
             &mut obj,
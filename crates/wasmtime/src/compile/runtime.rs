@@ -95,7 +95,10 @@ impl<'a> CodeBuilder<'a> {
         let custom_alignment = self.custom_alignment();
         let (code, info_and_types) =
             self.compile_cached(super::build_artifacts, &custom_alignment)?;
-        Module::from_parts(self.engine, code, info_and_types)
+        let module = Module::from_parts(self.engine, code, info_and_types)?;
+        #[cfg(feature = "winch")]
+        let module = module.with_tier_up(self.get_wasm()?);
+        Ok(module)
     }
 
     /// Same as [`CodeBuilder::compile_module`] except that it compiles a
@@ -109,16 +112,30 @@ impl<'a> CodeBuilder<'a> {
     }
 
     fn custom_alignment(&self) -> CustomAlignment {
-        CustomAlignment {
-            alignment: self
-                .engine
-                .custom_code_memory()
-                .map(|c| c.required_alignment())
-                .unwrap_or(1),
-        }
+        CustomAlignment::new(self.engine)
     }
 }
 
+/// Compiles `wasm` with the optimizing compiler used for tiered compilation,
+/// producing the module that hot functions of Winch-compiled code are
+/// replaced with.
+#[cfg(feature = "winch")]
+pub(crate) fn compile_tier_up_module(engine: &Engine, wasm: &[u8]) -> Result<Module> {
+    let compiler = engine
+        .compiler()
+        .tier_up_compiler()
+        .ok_or_else(|| anyhow!("the configured compiler does not support tiered compilation"))?;
+    let (mmap, info_and_types) = super::build_artifacts_with_compiler::<MmapVecWrapper>(
+        engine,
+        compiler,
+        wasm,
+        None,
+        &CustomAlignment::new(engine),
+    )?;
+    let code = publish_mmap(engine, mmap.0)?;
+    Module::from_parts(engine, code, info_and_types)
+}
+
 fn publish_mmap(engine: &Engine, mmap: MmapVec) -> Result<Arc<CodeMemory>> {
     let mut code = CodeMemory::new(engine, mmap)?;
     code.publish()?;
@@ -133,6 +150,17 @@ pub(crate) struct CustomAlignment {
     alignment: usize,
 }
 
+impl CustomAlignment {
+    fn new(engine: &Engine) -> CustomAlignment {
+        CustomAlignment {
+            alignment: engine
+                .custom_code_memory()
+                .map(|c| c.required_alignment())
+                .unwrap_or(1),
+        }
+    }
+}
+
 impl FinishedObject for MmapVecWrapper {
     type State = CustomAlignment;
     fn finish_object(obj: ObjectBuilder<'_>, align: &CustomAlignment) -> Result<Self> {
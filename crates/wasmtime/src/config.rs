@@ -27,9 +27,10 @@ use wasmtime_fiber::RuntimeFiberStackCreator;
 #[cfg(feature = "runtime")]
 pub use crate::runtime::code_memory::CustomCodeMemory;
 #[cfg(feature = "cache")]
-pub use wasmtime_cache::{Cache, CacheConfig};
+pub use wasmtime_cache::{Cache, CacheConfig, ModuleCacheStore};
 #[cfg(all(feature = "incremental-cache", feature = "cranelift"))]
 pub use wasmtime_environ::CacheStore;
+pub use wasmtime_environ::WmemcheckAllocator;
 
 /// Represents the module instance allocation strategy to use.
 #[derive(Clone)]
@@ -159,8 +160,11 @@ pub struct Config {
     pub(crate) memory_guaranteed_dense_image_size: u64,
     pub(crate) force_memory_init_memfd: bool,
     pub(crate) wmemcheck: bool,
+    #[cfg(any(feature = "cranelift", feature = "winch", feature = "wmemcheck"))]
+    pub(crate) wmemcheck_allocator: WmemcheckAllocator,
     #[cfg(feature = "coredump")]
     pub(crate) coredump_on_trap: bool,
+    pub(crate) tier_up_threshold: u32,
     pub(crate) macos_use_mach_ports: bool,
     pub(crate) detect_host_feature: Option<fn(&str) -> Option<bool>>,
     pub(crate) x86_float_abi_ok: Option<bool>,
@@ -265,8 +269,11 @@ impl Config {
             memory_guaranteed_dense_image_size: 16 << 20,
             force_memory_init_memfd: false,
             wmemcheck: false,
+            #[cfg(any(feature = "cranelift", feature = "winch", feature = "wmemcheck"))]
+            wmemcheck_allocator: WmemcheckAllocator::default(),
             #[cfg(feature = "coredump")]
             coredump_on_trap: false,
+            tier_up_threshold: 1000,
             macos_use_mach_ports: !cfg!(miri),
             #[cfg(feature = "std")]
             detect_host_feature: Some(detect_host_feature),
@@ -1220,6 +1227,23 @@ impl Config {
         self
     }
 
+    /// Configures how often a function must run before it's recompiled with
+    /// Cranelift when using [`Strategy::Tiered`].
+    ///
+    /// Each instance counts, per function, the number of times the function
+    /// is called plus the number of iterations of loops within it. Once this
+    /// count reaches `threshold` the function is considered hot and is
+    /// recompiled in the background. Subsequent calls to the function from
+    /// within the instance then use the optimized code.
+    ///
+    /// This option has no effect with other compilation strategies. The
+    /// default value for this is 1000 and it must not be zero.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn tier_up_threshold(&mut self, threshold: u32) -> &mut Self {
+        self.tier_up_threshold = threshold;
+        self
+    }
+
     /// Configures which garbage collector will be used for Wasm modules.
     ///
     /// This method can be used to configure which garbage collector
@@ -1993,9 +2017,44 @@ impl Config {
         self
     }
 
+    /// Configures whether compiled code preserves the values of Wasm locals
+    /// and the operand stack so they can be included in core dumps.
+    ///
+    /// By default core dumps generated with [`Config::coredump_on_trap`] only
+    /// contain the stack's frames, not the values of locals or the operand
+    /// stack in each frame. When this option is enabled, compiled code keeps a
+    /// copy of each local in its stack frame and saves the operand stack before
+    /// each instruction which may trap or call another function. These values
+    /// are then available through [`WasmCoreDump::frame_locals`] and
+    /// [`WasmCoreDump::frame_operand_stack`] and are included in serialized
+    /// core dumps.
+    ///
+    /// Only `i32`, `i64`, `f32`, and `f64` values are preserved; others are
+    /// recorded as missing. This instrumentation makes compiled code slower
+    /// and larger, so it's intended for debugging. Enabling this option
+    /// disables [`Config::compiler_inlining`], and it requires
+    /// [`Config::generate_address_map`]. It's only supported by Cranelift.
+    ///
+    /// This option is disabled by default.
+    ///
+    /// [`WasmCoreDump::frame_locals`]: crate::WasmCoreDump::frame_locals
+    /// [`WasmCoreDump::frame_operand_stack`]: crate::WasmCoreDump::frame_operand_stack
+    #[cfg(feature = "coredump")]
+    pub fn coredump_frame_state(&mut self, enable: bool) -> &mut Self {
+        self.tunables.coredump_frame_state = Some(enable);
+        self
+    }
+
     /// Enables memory error checking for wasm programs.
     ///
+    /// Allocations made by the guest's allocator functions, as configured with
+    /// [`Config::wmemcheck_allocator`], are tracked and loads and stores to
+    /// memory which isn't allocated or initialized become traps. Allocations
+    /// which are never freed can be listed with [`Store::wmemcheck_leaks`].
+    ///
     /// This option is disabled by default.
+    ///
+    /// [`Store::wmemcheck_leaks`]: crate::Store::wmemcheck_leaks
     #[cfg(any(feature = "cranelift", feature = "winch"))]
     pub fn wmemcheck(&mut self, enable: bool) -> &mut Self {
         self.wmemcheck = enable;
@@ -2003,6 +2062,19 @@ impl Config {
         self
     }
 
+    /// Configures which guest functions [`Config::wmemcheck`] treats as the
+    /// guest's allocator, and which linear memory that allocator manages.
+    ///
+    /// By default functions named `malloc`, `free`, `realloc`, `calloc` and
+    /// `aligned_alloc` managing memory 0 are recognized. Guests using a
+    /// different allocator, such as `dlmalloc`, can list their own functions
+    /// here.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn wmemcheck_allocator(&mut self, allocator: WmemcheckAllocator) -> &mut Self {
+        self.wmemcheck_allocator = allocator;
+        self
+    }
+
     /// Configures the "guaranteed dense image size" for copy-on-write
     /// initialized memories.
     ///
@@ -2117,7 +2189,7 @@ impl Config {
                 }
                 unsupported
             }
-            Some(Strategy::Winch) => {
+            Some(Strategy::Winch | Strategy::Tiered) => {
                 let mut unsupported = WasmFeatures::GC
                     | WasmFeatures::FUNCTION_REFERENCES
                     | WasmFeatures::RELAXED_SIMD
@@ -2243,6 +2315,9 @@ impl Config {
         if self.max_wasm_stack == 0 {
             bail!("max_wasm_stack size cannot be zero");
         }
+        if self.tier_up_threshold == 0 {
+            bail!("tier_up_threshold cannot be zero");
+        }
         if !cfg!(feature = "wmemcheck") && self.wmemcheck {
             bail!("wmemcheck (memory checker) was requested but is not enabled in this build");
         }
@@ -2284,10 +2359,25 @@ impl Config {
 
         self.tunables.configure(&mut tunables);
 
-        // If we're going to compile with winch, we must use the winch calling convention.
+        if tunables.coredump_frame_state {
+            if !tunables.generate_address_map {
+                bail!("core dump frame state requires address maps to be generated");
+            }
+            // Frame state is recorded per function, so inlined callees would
+            // otherwise be missing from core dumps.
+            tunables.inlining = false;
+        }
+
+        // If we're going to compile with winch, we must use the winch calling
+        // convention. This includes tiered compilation where the code which
+        // Cranelift produces must be callable from Winch-compiled code.
         #[cfg(any(feature = "cranelift", feature = "winch"))]
         {
-            tunables.winch_callable = self.compiler_config.strategy == Some(Strategy::Winch);
+            tunables.winch_callable = matches!(
+                self.compiler_config.strategy,
+                Some(Strategy::Winch | Strategy::Tiered)
+            );
+            tunables.tier_up = self.compiler_config.strategy == Some(Strategy::Tiered);
         }
 
         tunables.collector = if features.gc_types() {
@@ -2297,6 +2387,7 @@ impl Config {
                 Some(match self.collector.try_not_auto()? {
                     Collector::DeferredReferenceCounting => EnvCollector::DeferredReferenceCounting,
                     Collector::Null => EnvCollector::Null,
+                    Collector::Copying => EnvCollector::Copying,
                     Collector::Auto => unreachable!(),
                 })
             }
@@ -2358,7 +2449,7 @@ impl Config {
 
         #[cfg(feature = "gc")]
         #[cfg_attr(
-            not(any(feature = "gc-null", feature = "gc-drc")),
+            not(any(feature = "gc-null", feature = "gc-drc", feature = "gc-copying")),
             expect(unreachable_code, reason = "definitions known to be dummy")
         )]
         {
@@ -2377,6 +2468,13 @@ impl Config {
                 #[cfg(not(feature = "gc-null"))]
                 Collector::Null => unreachable!(),
 
+                #[cfg(feature = "gc-copying")]
+                Collector::Copying => {
+                    Arc::new(crate::runtime::vm::CopyingCollector::default()) as Arc<dyn GcRuntime>
+                }
+                #[cfg(not(feature = "gc-copying"))]
+                Collector::Copying => unreachable!(),
+
                 Collector::Auto => unreachable!(),
             }))
         }
@@ -2420,9 +2518,11 @@ impl Config {
             #[cfg(not(feature = "cranelift"))]
             Some(Strategy::Cranelift) => bail!("cranelift support not compiled in"),
             #[cfg(feature = "winch")]
-            Some(Strategy::Winch) => wasmtime_winch::builder(target_for_builder)?,
+            Some(Strategy::Winch | Strategy::Tiered) => {
+                wasmtime_winch::builder(target_for_builder)?
+            }
             #[cfg(not(feature = "winch"))]
-            Some(Strategy::Winch) => bail!("winch support not compiled in"),
+            Some(Strategy::Winch | Strategy::Tiered) => bail!("winch support not compiled in"),
 
             None | Some(Strategy::Auto) => unreachable!(),
         };
@@ -2542,6 +2642,7 @@ impl Config {
         }
 
         compiler.wmemcheck(self.compiler_config.wmemcheck);
+        compiler.wmemcheck_allocator(self.wmemcheck_allocator.clone());
 
         Ok((self, compiler.build()?))
     }
@@ -2831,6 +2932,32 @@ pub enum Strategy {
     /// A baseline compiler for WebAssembly, currently under active development and not ready for
     /// production applications.
     Winch,
+
+    /// Tiered compilation: modules are first compiled quickly with Winch, and
+    /// functions which turn out to be hot at runtime are recompiled with
+    /// Cranelift in the background.
+    ///
+    /// Code compiled with Winch counts how often each function is called and
+    /// how many loop iterations it runs, and once that reaches
+    /// [`Config::tier_up_threshold`] the module is compiled with Cranelift in
+    /// the background, on the thread pool used for
+    /// [`Config::parallel_compilation`], or on the calling thread if that's
+    /// disabled. When that's finished, calls to the hot function are
+    /// redirected to the optimized code through the function's `funcref`. A
+    /// function that's already running, for example in a long-running loop,
+    /// keeps running the Winch-compiled code until it returns.
+    ///
+    /// The whole module is recompiled at once, including functions which are
+    /// still cold, so a single hot function incurs the full cost of compiling
+    /// the module with Cranelift. This happens at most once per module.
+    ///
+    /// Tiering up is done per instance and only for modules created with
+    /// [`Module::new`](crate::Module::new) or similar APIs which have access
+    /// to the original WebAssembly binary. Modules that were deserialized from
+    /// a precompiled artifact, and modules within components, keep running
+    /// the Winch-compiled code. This strategy supports the same set of
+    /// WebAssembly features as [`Strategy::Winch`].
+    Tiered,
 }
 
 #[cfg(any(feature = "winch", feature = "cranelift"))]
@@ -2862,6 +2989,7 @@ impl Strategy {
 /// |-----------------------------|----------------------|-------------|----------------|----------------------|----------------------|
 /// | `DeferredReferenceCounting` | Yes, but not cycles  | 🙂         | 🙁             | 😐                   | 😐                  |
 /// | `Null`                      | No                   | 🙂         | 🙂             | 🙂                   | 🙂                  |
+/// | `Copying`                   | Yes                  | 🙁         | 🙂             | 🙂                   | 😐                  |
 ///
 /// [^1]: Whether or not the collector is capable of collecting garbage and cyclic garbage.
 ///
@@ -2926,6 +3054,17 @@ pub enum Collector {
     /// collectors, as this collector imposes as close to zero throughput and
     /// latency overhead as possible.
     Null,
+
+    /// The copying collector.
+    ///
+    /// A tracing collector that bump allocates objects and, when it runs out
+    /// of space, copies every object that is reachable from the GC roots to the
+    /// start of the GC heap, reclaiming everything else at once. Unlike the
+    /// deferred reference-counting collector, it can collect cycles, and it
+    /// requires no GC barriers. Collection pauses are proportional to the
+    /// amount of live data, and the collector temporarily needs additional host
+    /// memory of the same size during a collection.
+    Copying,
 }
 
 impl Default for Collector {
@@ -2943,6 +3082,8 @@ impl Collector {
                     Some(Collector::DeferredReferenceCounting)
                 } else if cfg!(feature = "gc-null") {
                     Some(Collector::Null)
+                } else if cfg!(feature = "gc-copying") {
+                    Some(Collector::Copying)
                 } else {
                     None
                 }
@@ -2970,12 +3111,20 @@ impl Collector {
                  the `gc-null` feature was not enabled at compile time",
             ),
 
+            #[cfg(feature = "gc-copying")]
+            Some(c @ Collector::Copying) => Ok(c),
+            #[cfg(not(feature = "gc-copying"))]
+            Some(Collector::Copying) => bail!(
+                "cannot create an engine using the copying collector because \
+                 the `gc-copying` feature was not enabled at compile time",
+            ),
+
             Some(Collector::Auto) => unreachable!(),
 
             None => bail!(
                 "cannot create an engine with GC support when none of the \
                  collectors are available; enable one of the following \
-                 features: `gc-drc`, `gc-null`",
+                 features: `gc-drc`, `gc-null`, `gc-copying`",
             ),
         }
     }
@@ -226,6 +226,22 @@ impl Engine {
         input.into_iter().map(|a| f(a)).collect::<Result<(), E>>()
     }
 
+    /// Runs `f` in the background on the thread pool used for parallel
+    /// compilation, or on the current thread before returning if parallel
+    /// compilation is disabled.
+    #[cfg(feature = "winch")]
+    pub(crate) fn spawn_maybe_parallel(&self, f: impl FnOnce() + Send + 'static) {
+        if self.config().parallel_compilation {
+            #[cfg(feature = "parallel-compilation")]
+            {
+                rayon::spawn(f);
+                return;
+            }
+        }
+
+        f()
+    }
+
     /// Take a weak reference to this engine.
     pub fn weak(&self) -> EngineWeak {
         EngineWeak {
@@ -286,6 +286,7 @@ impl Metadata<'_> {
             table_lazy_init,
             relaxed_simd_deterministic,
             winch_callable,
+            tier_up,
             signals_based_traps,
             memory_init_cow,
             inlining,
@@ -302,6 +303,11 @@ impl Metadata<'_> {
             // whether it's present or not)
             generate_address_map: _,
 
+            // Similarly to the address map this only adds information to
+            // compiled modules for core dumps, and modules compiled with or
+            // without it work the same in either engine.
+            coredump_frame_state: _,
+
             // Just a debugging aid, doesn't affect functionality at all.
             debug_adapter_modules: _,
         } = self.tunables;
@@ -350,6 +356,7 @@ impl Metadata<'_> {
             other.winch_callable,
             "Winch calling convention",
         )?;
+        Self::check_bool(tier_up, other.tier_up, "tiered compilation")?;
         Self::check_bool(
             signals_based_traps,
             other.signals_based_traps,
@@ -232,6 +232,11 @@
 //!   a core dump when a trap happens. This can be configured via
 //!   [`Config::coredump_on_trap`].
 //!
+//! * `snapshot` - Enabled by default, this provides support for
+//!   pre-initializing modules and components by snapshotting the state of an
+//!   instance into a new WebAssembly binary. See [`Instance::snapshot`] and
+//!   [`Preinitializer`].
+//!
 //! * `addr2line` - Enabled by default, this feature configures whether traps
 //!   will attempt to parse DWARF debug information and convert WebAssembly
 //!   addresses to source filenames and line numbers.
@@ -106,6 +106,11 @@ mod profiling;
 #[cfg(feature = "profiling")]
 pub use profiling::GuestProfiler;
 
+#[cfg(feature = "wmemcheck")]
+mod wmemcheck;
+#[cfg(feature = "wmemcheck")]
+pub use wmemcheck::WmemcheckLeak;
+
 #[cfg(feature = "async")]
 pub(crate) mod stack;
 #[cfg(feature = "async")]
@@ -116,6 +121,11 @@ mod coredump;
 #[cfg(feature = "coredump")]
 pub use coredump::*;
 
+#[cfg(feature = "snapshot")]
+mod snapshot;
+#[cfg(feature = "snapshot")]
+pub use snapshot::*;
+
 #[cfg(feature = "wave")]
 mod wave;
 
@@ -35,6 +35,7 @@ pub struct CodeMemory {
     wasm_data: Range<usize>,
     address_map_data: Range<usize>,
     stack_map_data: Range<usize>,
+    frame_state_data: Range<usize>,
     exception_data: Range<usize>,
     func_name_data: Range<usize>,
     info_data: Range<usize>,
@@ -125,6 +126,7 @@ impl CodeMemory {
         let mut wasm_data = 0..0;
         let mut address_map_data = 0..0;
         let mut stack_map_data = 0..0;
+        let mut frame_state_data = 0..0;
         let mut func_name_data = 0..0;
         let mut info_data = 0..0;
         let mut wasm_dwarf = 0..0;
@@ -170,6 +172,7 @@ impl CodeMemory {
                 obj::ELF_WASM_DATA => wasm_data = range,
                 obj::ELF_WASMTIME_ADDRMAP => address_map_data = range,
                 obj::ELF_WASMTIME_STACK_MAP => stack_map_data = range,
+                obj::ELF_WASMTIME_FRAME_STATE => frame_state_data = range,
                 obj::ELF_WASMTIME_TRAPS => trap_data = range,
                 obj::ELF_WASMTIME_EXCEPTIONS => exception_data = range,
                 obj::ELF_NAME_DATA => func_name_data = range,
@@ -215,6 +218,7 @@ impl CodeMemory {
             trap_data,
             address_map_data,
             stack_map_data,
+            frame_state_data,
             exception_data,
             func_name_data,
             wasm_dwarf,
@@ -271,6 +275,15 @@ impl CodeMemory {
         &self.mmap[self.stack_map_data.clone()]
     }
 
+    /// Returns the encoded frame state section used to pass to
+    /// `wasmtime_environ::FrameState::lookup`.
+    ///
+    /// This is empty unless the code was compiled with
+    /// `Config::coredump_frame_state` enabled.
+    pub fn frame_state_data(&self) -> &[u8] {
+        &self.mmap[self.frame_state_data.clone()]
+    }
+
     /// Returns the encoded exception-tables section to pass to
     /// `wasmtime_unwinder::ExceptionTable::parse`.
     pub fn exception_tables(&self) -> &[u8] {
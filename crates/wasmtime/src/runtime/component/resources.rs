@@ -58,6 +58,23 @@ impl ResourceType {
         }
     }
 
+    /// Creates a new host resource type which is identified by the integer
+    /// `ty` rather than a Rust type.
+    ///
+    /// This is intended for embedders, such as the C API, which can't define
+    /// a Rust type per resource type. Two types created with this function are
+    /// the same if they have the same `ty`, and they're never the same as a
+    /// type created with [`ResourceType::host`].
+    ///
+    /// A resource type of type `ResourceType::host_dynamic(ty)` will match the
+    /// type of the value produced by `ResourceDynamic::new_{own,borrow}` with
+    /// the same `ty`.
+    pub fn host_dynamic(ty: u32) -> ResourceType {
+        ResourceType {
+            kind: ResourceTypeKind::HostDynamic(ty),
+        }
+    }
+
     pub(crate) fn guest(
         store: StoreId,
         instance: &ComponentInstance,
@@ -85,6 +102,7 @@ impl ResourceType {
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum ResourceTypeKind {
     Host(TypeId),
+    HostDynamic(u32),
     Guest {
         store: StoreId,
         // For now this is the `*mut ComponentInstance` pointer within the store
@@ -795,26 +813,12 @@ where
         resource: ResourceAny,
         mut store: impl AsContextMut,
     ) -> Result<Self> {
-        let store = store.as_context_mut();
-        let mut tables = HostResourceTables::new_host(store.0);
-        let ResourceAny { idx, ty, owned } = resource;
-        ensure!(ty == ResourceType::host::<T>(), "resource type mismatch");
-        let (state, rep) = if owned {
-            let rep = tables.host_resource_lift_own(idx)?;
-            (AtomicResourceState::NOT_IN_TABLE, rep)
+        let (owned, rep) =
+            resource.take_host(ResourceType::host::<T>(), store.as_context_mut().0)?;
+        let state = if owned {
+            AtomicResourceState::NOT_IN_TABLE
         } else {
-            // For borrowed handles, first acquire the `rep` via lifting the
-            // borrow. Afterwards though remove any dynamic state associated
-            // with this borrow. `Resource<T>` doesn't participate in dynamic
-            // state tracking and it's assumed embedders know what they're
-            // doing, so the drop call will clear out that a borrow is active
-            //
-            // Note that the result of `drop` should always be `None` as it's a
-            // borrowed handle, so assert so.
-            let rep = tables.host_resource_lift_borrow(idx)?;
-            let res = tables.host_resource_drop(idx)?;
-            assert!(res.is_none());
-            (AtomicResourceState::BORROW, rep)
+            AtomicResourceState::BORROW
         };
         Ok(Resource {
             state,
@@ -905,6 +909,109 @@ impl<T> fmt::Debug for Resource<T> {
     }
 }
 
+/// A host-defined resource whose type is identified by an integer rather than a
+/// Rust type.
+///
+/// This is the counterpart of [`Resource`] for resource types created with
+/// [`ResourceType::host_dynamic`], intended for embedders such as the C API
+/// which can't define a Rust type for each resource type. Like [`Resource`]
+/// this is a 32-bit representation of a resource which is either owned or
+/// borrowed, and it has no destructor of its own.
+///
+/// Unlike [`Resource`] this type can't be passed to typed functions directly
+/// and is instead converted to and from a [`ResourceAny`], for example to be
+/// used as a [`Val::Resource`](crate::component::Val::Resource).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ResourceDynamic {
+    rep: u32,
+    ty: u32,
+    owned: bool,
+}
+
+impl ResourceDynamic {
+    /// Creates a new owned resource of type `ResourceType::host_dynamic(ty)`
+    /// with the `rep` specified.
+    pub fn new_own(rep: u32, ty: u32) -> ResourceDynamic {
+        ResourceDynamic {
+            rep,
+            ty,
+            owned: true,
+        }
+    }
+
+    /// Creates a new borrowed resource of type
+    /// `ResourceType::host_dynamic(ty)` with the `rep` specified.
+    ///
+    /// See [`Resource::new_borrow`] for more information.
+    pub fn new_borrow(rep: u32, ty: u32) -> ResourceDynamic {
+        ResourceDynamic {
+            rep,
+            ty,
+            owned: false,
+        }
+    }
+
+    /// Returns the underlying 32-bit representation used to originally create
+    /// this resource.
+    pub fn rep(&self) -> u32 {
+        self.rep
+    }
+
+    /// Returns the `ty` that this resource's type was created with, see
+    /// [`ResourceType::host_dynamic`].
+    pub fn ty(&self) -> u32 {
+        self.ty
+    }
+
+    /// Returns whether this is an owned resource or not.
+    pub fn owned(&self) -> bool {
+        self.owned
+    }
+
+    /// Attempts to convert a [`ResourceAny`] into a [`ResourceDynamic`] of
+    /// type `ResourceType::host_dynamic(ty)`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `resource` does not have type
+    /// `ResourceType::host_dynamic(ty)`. This function may also return an
+    /// error if `resource` is no longer valid, for example it was previously
+    /// converted.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `resource` does not belong to the `store`
+    /// specified.
+    pub fn try_from_resource_any(
+        resource: ResourceAny,
+        ty: u32,
+        mut store: impl AsContextMut,
+    ) -> Result<Self> {
+        let (owned, rep) =
+            resource.take_host(ResourceType::host_dynamic(ty), store.as_context_mut().0)?;
+        Ok(ResourceDynamic { rep, ty, owned })
+    }
+
+    /// Converts this resource into a [`ResourceAny`] which lives in `store`.
+    ///
+    /// Like [`ResourceAny::try_from_resource`] the returned value has no
+    /// destructor attached to it, and borrowed resources may only be
+    /// converted during a host function call.
+    pub fn try_into_resource_any(self, mut store: impl AsContextMut) -> Result<ResourceAny> {
+        let state = if self.owned {
+            ResourceState::NotInTable
+        } else {
+            ResourceState::Borrow
+        };
+        ResourceAny::from_host(
+            self.rep,
+            state,
+            ResourceType::host_dynamic(self.ty),
+            store.as_context_mut().0,
+        )
+    }
+}
+
 /// Representation of a resource in the component model, either a guest-defined
 /// or a host-defined resource.
 ///
@@ -957,10 +1064,24 @@ impl ResourceAny {
         mut store: impl AsContextMut,
     ) -> Result<Self> {
         let Resource { rep, state, .. } = resource;
-        let store = store.as_context_mut();
+        ResourceAny::from_host(
+            rep,
+            state.get(),
+            ResourceType::host::<T>(),
+            store.as_context_mut().0,
+        )
+    }
 
-        let mut tables = HostResourceTables::new_host(store.0);
-        let (idx, owned) = match state.get() {
+    /// Inserts the host resource `rep` of type `ty` into the host table of
+    /// `store` for use as a `ResourceAny`.
+    fn from_host(
+        rep: u32,
+        state: ResourceState,
+        ty: ResourceType,
+        store: &mut StoreOpaque,
+    ) -> Result<Self> {
+        let mut tables = HostResourceTables::new_host(store);
+        let (idx, owned) = match state {
             ResourceState::Borrow => (tables.host_resource_lower_borrow(rep)?, false),
             ResourceState::NotInTable => {
                 let idx = tables.host_resource_lower_own(rep, None, None)?;
@@ -969,11 +1090,31 @@ impl ResourceAny {
             ResourceState::Taken => bail!("host resource already consumed"),
             ResourceState::Index(idx) => (idx, true),
         };
-        Ok(Self {
-            idx,
-            ty: ResourceType::host::<T>(),
-            owned,
-        })
+        Ok(Self { idx, ty, owned })
+    }
+
+    /// Removes this host resource of type `ty` from the host table of `store`,
+    /// returning whether it's owned and its `rep`.
+    fn take_host(self, ty: ResourceType, store: &mut StoreOpaque) -> Result<(bool, u32)> {
+        let mut tables = HostResourceTables::new_host(store);
+        ensure!(self.ty == ty, "resource type mismatch");
+        if self.owned {
+            let rep = tables.host_resource_lift_own(self.idx)?;
+            Ok((true, rep))
+        } else {
+            // For borrowed handles, first acquire the `rep` via lifting the
+            // borrow. Afterwards though remove any dynamic state associated
+            // with this borrow. Host resources don't participate in dynamic
+            // state tracking and it's assumed embedders know what they're
+            // doing, so the drop call will clear out that a borrow is active
+            //
+            // Note that the result of `drop` should always be `None` as it's a
+            // borrowed handle, so assert so.
+            let rep = tables.host_resource_lift_borrow(self.idx)?;
+            let res = tables.host_resource_drop(self.idx)?;
+            assert!(res.is_none());
+            Ok((false, rep))
+        }
     }
 
     /// See [`Resource::try_from_resource_any`]
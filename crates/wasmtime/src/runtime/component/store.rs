@@ -2,7 +2,7 @@
 use crate::runtime::vm::VMStore;
 use crate::runtime::vm::component::{ComponentInstance, OwnedComponentInstance};
 use crate::store::{StoreData, StoreId, StoreOpaque};
-#[cfg(feature = "component-model-async")]
+#[cfg(any(feature = "component-model-async", feature = "coredump"))]
 use alloc::vec::Vec;
 use core::pin::Pin;
 use wasmtime_environ::PrimaryMap;
@@ -73,6 +73,18 @@ impl StoreOpaque {
     pub(crate) fn component_instance(&self, id: ComponentInstanceId) -> &ComponentInstance {
         self.store_data().component_instance(id)
     }
+
+    /// Returns the ids of all component instances within this store.
+    #[cfg(feature = "coredump")]
+    pub(crate) fn all_component_instances(&self) -> Vec<ComponentInstanceId> {
+        self.store_data()
+            .components
+            .instances
+            .iter()
+            .filter(|(_, instance)| instance.is_some())
+            .map(|(id, _)| id)
+            .collect()
+    }
 }
 
 /// A type used to represent an allocated `ComponentInstance` located within a
@@ -5,6 +5,7 @@ use crate::{
     ValType, WasmBacktrace, store::StoreOpaque,
 };
 use std::fmt;
+use wasm_encoder::{CoreDumpValue, Ieee32, Ieee64};
 
 /// Representation of a core dump of a WebAssembly module
 ///
@@ -16,13 +17,18 @@ use std::fmt;
 /// error returned this will get printed along with the rest of the error when
 /// the error is logged.
 ///
-/// Note that some state, such as Wasm locals or values on the operand stack,
-/// may be optimized away by the compiler or otherwise not recovered in the
-/// coredump.
+/// Note that Wasm locals and values on the operand stack are only recovered
+/// when [`Config::coredump_frame_state`][crate::Config::coredump_frame_state]
+/// is enabled. Even then, values of some types, such as `v128` and references,
+/// are not preserved and are reported as missing.
 ///
 /// Capturing of wasm coredumps can be configured through the
 /// [`Config::coredump_on_trap`][crate::Config::coredump_on_trap] method.
 ///
+/// When a trap happens within a component the core dump additionally records
+/// the store's component instances, see
+/// [`WasmCoreDump::component_instances`].
+///
 /// For more information about errors in wasmtime see the documentation of the
 /// [`Trap`][crate::Trap] type.
 ///
@@ -35,10 +41,20 @@ pub struct WasmCoreDump {
     memories: Vec<Memory>,
     globals: Vec<Global>,
     backtrace: WasmBacktrace,
+    frame_instances: Vec<usize>,
+    locals: Vec<Vec<Option<Val>>>,
+    operand_stacks: Vec<Vec<Option<Val>>>,
+    #[cfg(feature = "component-model")]
+    component_instances: Vec<CoreDumpComponentInstance>,
 }
 
 impl WasmCoreDump {
-    pub(crate) fn new(store: &mut StoreOpaque, backtrace: WasmBacktrace) -> WasmCoreDump {
+    pub(crate) fn new(
+        store: &mut StoreOpaque,
+        backtrace: WasmBacktrace,
+        frames: Vec<crate::runtime::vm::CoreDumpFrame>,
+    ) -> WasmCoreDump {
+        debug_assert_eq!(frames.len(), backtrace.frames().len());
         let modules: Vec<_> = store.modules().all_modules().cloned().collect();
         let instances: Vec<Instance> = store.all_instances().collect();
         let store_memories: Vec<Memory> = store.all_memories().collect();
@@ -46,6 +62,44 @@ impl WasmCoreDump {
         let mut store_globals: Vec<Global> = vec![];
         store.for_each_global(|_store, global| store_globals.push(global));
 
+        // Frames which preserved their state record exactly which instance
+        // they belong to. Otherwise we can only recover the frame's module
+        // from its pc, so if there are multiple instances of the same module
+        // we do a best effort job and choose the last one.
+        let frame_instances = backtrace
+            .frames()
+            .iter()
+            .zip(&frames)
+            .map(|(info, frame)| {
+                frame
+                    .vmctx
+                    .and_then(|vmctx| {
+                        instances
+                            .iter()
+                            .position(|i| store.instance(i.id()).vmctx().as_ptr() as usize == vmctx)
+                    })
+                    .or_else(|| {
+                        instances
+                            .iter()
+                            .rposition(|i| i._module(store).id() == info.module().id())
+                    })
+                    .expect("frames' modules should always have an instance in the store")
+            })
+            .collect();
+
+        #[cfg(feature = "component-model")]
+        let component_instances = CoreDumpComponentInstance::all(store);
+
+        let (locals, operand_stacks) = frames
+            .into_iter()
+            .map(|frame| {
+                (
+                    values_from_core_dump(frame.locals),
+                    values_from_core_dump(frame.operand_stack),
+                )
+            })
+            .unzip();
+
         WasmCoreDump {
             name: String::from("store_name"),
             modules,
@@ -53,6 +107,11 @@ impl WasmCoreDump {
             memories: store_memories,
             globals: store_globals,
             backtrace,
+            frame_instances,
+            locals,
+            operand_stacks,
+            #[cfg(feature = "component-model")]
+            component_instances,
         }
     }
 
@@ -64,6 +123,49 @@ impl WasmCoreDump {
         self.backtrace.frames()
     }
 
+    /// The instance that the `frame`th entry in [`WasmCoreDump::frames`]
+    /// belongs to.
+    ///
+    /// This is exact when the module was compiled with
+    /// [`Config::coredump_frame_state`][crate::Config::coredump_frame_state]
+    /// enabled. Otherwise, if the store contains multiple instances of the
+    /// frame's module, the most recently created one is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame` is out of bounds.
+    pub fn frame_instance(&self, frame: usize) -> Instance {
+        self.instances[self.frame_instances[frame]]
+    }
+
+    /// The values of the locals, including parameters, of the `frame`th entry
+    /// in [`WasmCoreDump::frames`].
+    ///
+    /// This is empty unless the module was compiled with
+    /// [`Config::coredump_frame_state`][crate::Config::coredump_frame_state]
+    /// enabled. Locals whose value was not preserved are `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame` is out of bounds.
+    pub fn frame_locals(&self, frame: usize) -> &[Option<Val>] {
+        &self.locals[frame]
+    }
+
+    /// The values on the operand stack of the `frame`th entry in
+    /// [`WasmCoreDump::frames`], from the bottom of the stack to the top.
+    ///
+    /// This is empty unless the module was compiled with
+    /// [`Config::coredump_frame_state`][crate::Config::coredump_frame_state]
+    /// enabled. Values which were not preserved are `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame` is out of bounds.
+    pub fn frame_operand_stack(&self, frame: usize) -> &[Option<Val>] {
+        &self.operand_stacks[frame]
+    }
+
     /// All modules instantiated inside the store when the core dump was
     /// created.
     pub fn modules(&self) -> &[Module] {
@@ -75,6 +177,29 @@ impl WasmCoreDump {
         self.instances.as_ref()
     }
 
+    /// All component instances within the store when the core dump was
+    /// created.
+    #[cfg(feature = "component-model")]
+    pub fn component_instances(&self) -> &[CoreDumpComponentInstance] {
+        self.component_instances.as_ref()
+    }
+
+    /// The name of the component-level function that the `frame`th entry in
+    /// [`WasmCoreDump::frames`] was executing, if the frame's core function
+    /// is exported from a component as a lifted function.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame` is out of bounds.
+    #[cfg(feature = "component-model")]
+    pub fn frame_component_function(&self, frame: usize) -> Option<&str> {
+        let instance = self.frame_instance(frame);
+        let func_index = self.frames()[frame].func_index();
+        self.component_instances
+            .iter()
+            .find_map(|c| c.function_name(&instance, func_index))
+    }
+
     /// All globals, instance- or host-defined, within the store when the core
     /// dump was created.
     pub fn globals(&self) -> &[Global] {
@@ -97,7 +222,12 @@ impl WasmCoreDump {
     /// network, or pass it to other debugging tools that consume Wasm core
     /// dumps.
     ///
+    /// If the store contains component instances then they're recorded in an
+    /// additional `componentinstances` custom section, which is an extension
+    /// of the standard format documented in [Wasmtime's book][book].
+    ///
     /// [spec]: https://github.com/WebAssembly/tool-conventions/blob/main/Coredump.md
+    /// [book]: https://docs.wasmtime.dev/examples-debugging-core-dumps.html
     pub fn serialize(&self, mut store: impl AsContextMut, name: &str) -> Vec<u8> {
         let store = store.as_context_mut();
         self._serialize(store, name)
@@ -248,20 +378,10 @@ impl WasmCoreDump {
             core_dump.section(&modules);
         }
 
-        // TODO: We can't currently recover instances from stack frames. We can
-        // recover module via the frame's PC, but if there are multiple
-        // instances of the same module, we don't know which instance the frame
-        // is associated with. Therefore, we do a best effort job: remember the
-        // last instance of each module and always choose that one. We record
-        // that information here.
-        let mut module_to_instance = HashMap::new();
-
         {
             let mut instances = wasm_encoder::CoreDumpInstancesSection::new();
             for instance in self.instances() {
                 let module = instance.module(&store);
-                module_to_instance.insert(module.id(), instances.len());
-
                 let module_index = module_to_index[&module.id()];
 
                 let memories = instance
@@ -286,11 +406,8 @@ impl WasmCoreDump {
         {
             let thread_name = "main";
             let mut stack = wasm_encoder::CoreDumpStackSection::new(thread_name);
-            for frame in self.frames() {
-                // This isn't necessarily the right instance if there are
-                // multiple instances of the same module. See comment above
-                // `module_to_instance` for details.
-                let instance = module_to_instance[&frame.module().id()];
+            for (i, frame) in self.frames().iter().enumerate() {
+                let instance = u32::try_from(self.frame_instances[i]).unwrap();
 
                 let func = frame.func_index();
 
@@ -299,20 +416,187 @@ impl WasmCoreDump {
                     .and_then(|o| u32::try_from(o).ok())
                     .unwrap_or(0);
 
-                // We can't currently recover locals and the operand stack. We
-                // should eventually be able to do that with Winch though.
-                let locals = [];
-                let operand_stack = [];
+                let locals = self.locals[i].iter().map(value_to_core_dump);
+                let operand_stack = self.operand_stacks[i].iter().map(value_to_core_dump);
 
                 stack.frame(instance, func, offset, locals, operand_stack);
             }
             core_dump.section(&stack);
         }
 
+        #[cfg(feature = "component-model")]
+        if !self.component_instances.is_empty() {
+            core_dump.section(&self.component_instances_section());
+        }
+
         core_dump.finish()
     }
 }
 
+#[cfg(feature = "component-model")]
+impl WasmCoreDump {
+    /// Encodes the `componentinstances` custom section, see
+    /// `docs/examples-debugging-core-dumps.md` for its format.
+    fn component_instances_section(&self) -> wasm_encoder::CustomSection<'static> {
+        use wasm_encoder::Encode;
+
+        let instance_index = |instance: &Instance| {
+            let index = self
+                .instances
+                .iter()
+                .position(|i| i.id() == instance.id())
+                .unwrap();
+            u32::try_from(index).unwrap()
+        };
+
+        let mut data = Vec::new();
+        self.component_instances.len().encode(&mut data);
+        for component in self.component_instances.iter() {
+            data.push(0x00);
+            component.core_instances.len().encode(&mut data);
+            for instance in component.core_instances.iter() {
+                instance_index(instance).encode(&mut data);
+            }
+            component.functions.len().encode(&mut data);
+            for (name, instance, func_index) in component.functions.iter() {
+                name.encode(&mut data);
+                instance_index(instance).encode(&mut data);
+                func_index.encode(&mut data);
+            }
+        }
+        wasm_encoder::CustomSection {
+            name: "componentinstances".into(),
+            data: data.into(),
+        }
+    }
+}
+
+/// A component instance recorded in a [`WasmCoreDump`].
+///
+/// This describes which core instances make up the component instance, and
+/// which of their functions are exported from the component as lifted
+/// functions.
+#[cfg(feature = "component-model")]
+pub struct CoreDumpComponentInstance {
+    instance: crate::component::Instance,
+    core_instances: Vec<Instance>,
+    functions: Vec<(String, Instance, u32)>,
+}
+
+#[cfg(feature = "component-model")]
+impl CoreDumpComponentInstance {
+    fn all(store: &mut StoreOpaque) -> Vec<CoreDumpComponentInstance> {
+        store
+            .all_component_instances()
+            .into_iter()
+            .map(|id| Self::new(store, id))
+            .collect()
+    }
+
+    fn new(
+        store: &mut StoreOpaque,
+        id: crate::component::store::ComponentInstanceId,
+    ) -> CoreDumpComponentInstance {
+        use wasmtime_environ::component::{CoreDef, Export, ExportItem};
+        use wasmtime_environ::{EntityIndex, FuncIndex};
+
+        let data = store.component_instance(id);
+        let core_instance_ids = data.instance_ids().map(|(_, id)| id).collect::<Vec<_>>();
+
+        // Walk the component's exports, recursing into exported instances, to
+        // find the core function behind each lifted function.
+        let env_component = data.component().env_component();
+        let mut functions = Vec::new();
+        let mut worklist = env_component
+            .exports
+            .raw_iter()
+            .map(|(name, index)| (name.clone(), *index))
+            .collect::<Vec<_>>();
+        while let Some((name, index)) = worklist.pop() {
+            match &env_component.export_items[index] {
+                Export::LiftedFunction {
+                    func: CoreDef::Export(export),
+                    ..
+                } => {
+                    let Some(instance) = data.get_instance(export.instance) else {
+                        continue;
+                    };
+                    let func = match &export.item {
+                        ExportItem::Index(EntityIndex::Function(func)) => Some(*func),
+                        ExportItem::Name(item) => {
+                            match store.instance(instance).env_module().exports.get(item) {
+                                Some(EntityIndex::Function(func)) => Some(*func),
+                                _ => None,
+                            }
+                        }
+                        ExportItem::Index(_) => None,
+                    };
+                    if let Some(func) = func {
+                        functions.push((name, instance, func));
+                    }
+                }
+                Export::Instance { exports, .. } => {
+                    worklist.extend(
+                        exports
+                            .raw_iter()
+                            .map(|(item, index)| (format!("{name}#{item}"), *index)),
+                    );
+                }
+                _ => {}
+            }
+        }
+        functions.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let functions = functions
+            .into_iter()
+            .map(|(name, instance, func): (String, _, FuncIndex)| {
+                let instance = Instance::from_wasmtime(instance, store);
+                (name, instance, func.as_u32())
+            })
+            .collect();
+        CoreDumpComponentInstance {
+            instance: crate::component::Instance::from_wasmtime(store, id),
+            core_instances: core_instance_ids
+                .into_iter()
+                .map(|id| Instance::from_wasmtime(id, store))
+                .collect(),
+            functions,
+        }
+    }
+
+    /// The component instance itself.
+    pub fn instance(&self) -> crate::component::Instance {
+        self.instance
+    }
+
+    /// The core instances created within this component instance, in the
+    /// order they were instantiated.
+    pub fn core_instances(&self) -> &[Instance] {
+        &self.core_instances
+    }
+
+    /// The functions exported from this component instance which lift a core
+    /// function.
+    ///
+    /// Each item is the name of the exported function, using `#` to separate
+    /// the names of exported instances from the names of their functions, along
+    /// with the core instance and function index being lifted.
+    pub fn functions(&self) -> impl ExactSizeIterator<Item = (&str, Instance, u32)> + '_ {
+        self.functions
+            .iter()
+            .map(|(name, instance, func)| (name.as_str(), *instance, *func))
+    }
+
+    /// Returns the name under which the function `func_index` of `instance` is
+    /// exported from this component instance, if any.
+    pub fn function_name(&self, instance: &Instance, func_index: u32) -> Option<&str> {
+        self.functions
+            .iter()
+            .find(|(_, i, f)| i.id() == instance.id() && *f == func_index)
+            .map(|(name, _, _)| name.as_str())
+    }
+}
+
 impl fmt::Display for WasmCoreDump {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "wasm coredump generated while executing {}:", self.name)?;
@@ -326,6 +610,20 @@ impl fmt::Display for WasmCoreDump {
             writeln!(f, "  {instance:?}")?;
         }
 
+        #[cfg(feature = "component-model")]
+        if !self.component_instances.is_empty() {
+            writeln!(f, "component instances:")?;
+            for component in self.component_instances.iter() {
+                writeln!(f, "  {:?}", component.instance)?;
+                for instance in component.core_instances.iter() {
+                    writeln!(f, "    {instance:?}")?;
+                }
+                for (name, _, _) in component.functions.iter() {
+                    writeln!(f, "    func {name}")?;
+                }
+            }
+        }
+
         writeln!(f, "memories:")?;
         for memory in self.memories.iter() {
             writeln!(f, "  {memory:?}")?;
@@ -348,3 +646,26 @@ impl fmt::Debug for WasmCoreDump {
         write!(f, "<wasm core dump>")
     }
 }
+
+fn values_from_core_dump(values: Vec<CoreDumpValue>) -> Vec<Option<Val>> {
+    values
+        .into_iter()
+        .map(|value| match value {
+            CoreDumpValue::Missing => None,
+            CoreDumpValue::I32(x) => Some(Val::I32(x)),
+            CoreDumpValue::I64(x) => Some(Val::I64(x)),
+            CoreDumpValue::F32(x) => Some(Val::F32(x.bits())),
+            CoreDumpValue::F64(x) => Some(Val::F64(x.bits())),
+        })
+        .collect()
+}
+
+fn value_to_core_dump(value: &Option<Val>) -> CoreDumpValue {
+    match value {
+        Some(Val::I32(x)) => CoreDumpValue::I32(*x),
+        Some(Val::I64(x)) => CoreDumpValue::I64(*x),
+        Some(Val::F32(x)) => CoreDumpValue::F32(Ieee32::new(*x)),
+        Some(Val::F64(x)) => CoreDumpValue::F64(Ieee64::new(*x)),
+        _ => CoreDumpValue::Missing,
+    }
+}
@@ -8,6 +8,7 @@ mod exnref;
 mod externref;
 mod i31;
 mod rooting;
+mod stats;
 mod structref;
 
 pub use anyref::*;
@@ -17,4 +18,5 @@ pub use exnref::*;
 pub use externref::*;
 pub use i31::*;
 pub use rooting::*;
+pub use stats::*;
 pub use structref::*;
@@ -0,0 +1,480 @@
+//! Statistics about, and snapshots of, the contents of a store's GC heap.
+
+use crate::hash_map::HashMap;
+use crate::runtime::vm::{GcCollectionStats, GcStore, VMGcRef};
+use crate::{ArrayType, Engine, ExnType, StructType, prelude::*};
+use core::fmt::Write;
+use core::time::Duration;
+use wasmtime_environ::{GcLayout, VMSharedTypeIndex};
+
+/// The concrete type of an object in a GC heap.
+///
+/// See [`GcTypeStats::ty`].
+#[derive(Clone, Debug)]
+pub enum GcObjectType {
+    /// An `externref`.
+    ExternRef,
+
+    /// An instance of the given struct type.
+    Struct(StructType),
+
+    /// An instance of the given array type.
+    Array(ArrayType),
+
+    /// An instance of the given exception type.
+    Exn(ExnType),
+}
+
+impl GcObjectType {
+    fn new(engine: &Engine, ty: Option<VMSharedTypeIndex>, layout: Option<&GcLayout>) -> Self {
+        match (ty, layout) {
+            (None, _) => GcObjectType::ExternRef,
+            (Some(ty), Some(GcLayout::Array(_))) => {
+                GcObjectType::Array(ArrayType::from_shared_type_index(engine, ty))
+            }
+            (Some(ty), Some(GcLayout::Struct(s))) if s.is_exception => {
+                GcObjectType::Exn(ExnType::from_shared_type_index(engine, ty))
+            }
+            (Some(ty), Some(GcLayout::Struct(_))) => {
+                GcObjectType::Struct(StructType::from_shared_type_index(engine, ty))
+            }
+            (Some(ty), None) => panic!("should have a GC layout for {ty:?}"),
+        }
+    }
+}
+
+/// Live object counts and bytes for a single concrete type in a GC heap.
+///
+/// See [`GcHeapStats::types`].
+#[derive(Clone, Debug)]
+pub struct GcTypeStats {
+    ty: GcObjectType,
+    objects: usize,
+    bytes: usize,
+}
+
+impl GcTypeStats {
+    /// The concrete type of these objects.
+    pub fn ty(&self) -> &GcObjectType {
+        &self.ty
+    }
+
+    /// The number of live objects of this type.
+    pub fn objects(&self) -> usize {
+        self.objects
+    }
+
+    /// The number of bytes that live objects of this type occupy in the GC
+    /// heap, including their object headers.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+/// Statistics about a store's GC heap.
+///
+/// Returned by [`Store::gc_heap_stats`](crate::Store::gc_heap_stats).
+///
+/// An object is considered live when it is reachable from the store's GC
+/// roots, which is exactly the set of objects that a full, precise collection
+/// would retain. Unreachable objects that have not been collected yet are not
+/// included.
+#[derive(Clone, Debug, Default)]
+pub struct GcHeapStats {
+    capacity: usize,
+    live_objects: usize,
+    live_bytes: usize,
+    types: Vec<GcTypeStats>,
+    collections: GcCollectionStats,
+}
+
+impl GcHeapStats {
+    /// The size of the GC heap, in bytes.
+    ///
+    /// This is zero if the store's GC heap has not been allocated yet.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of live objects in the GC heap.
+    pub fn live_objects(&self) -> usize {
+        self.live_objects
+    }
+
+    /// The number of bytes that live objects occupy in the GC heap.
+    pub fn live_bytes(&self) -> usize {
+        self.live_bytes
+    }
+
+    /// Live object counts and bytes grouped by concrete type, sorted by
+    /// descending bytes.
+    pub fn types(&self) -> &[GcTypeStats] {
+        &self.types
+    }
+
+    /// The number of garbage collections performed in this store so far.
+    pub fn collections(&self) -> u64 {
+        self.collections.collections
+    }
+
+    /// The wall-clock time taken by the most recent garbage collection.
+    ///
+    /// Pause times are only measured when the `std` Cargo feature is enabled,
+    /// and are otherwise always zero.
+    pub fn last_pause(&self) -> Duration {
+        self.collections.last_pause
+    }
+
+    /// The wall-clock time taken by the longest garbage collection.
+    pub fn max_pause(&self) -> Duration {
+        self.collections.max_pause
+    }
+
+    /// The total wall-clock time taken by all garbage collections.
+    pub fn total_pause(&self) -> Duration {
+        self.collections.total_pause
+    }
+}
+
+/// A snapshot of the object graph in a store's GC heap.
+///
+/// Returned by [`Store::gc_heap_snapshot`](crate::Store::gc_heap_snapshot).
+///
+/// The snapshot contains every live object, the references between them, and
+/// which of them are referenced by the store's GC roots. It can be exported
+/// with [`GcHeapSnapshot::to_heapsnapshot_json`] for analysis in external
+/// tools.
+#[derive(Clone, Debug, Default)]
+pub struct GcHeapSnapshot {
+    pub(crate) stats: GcHeapStats,
+    objects: Vec<SnapshotObject>,
+    edges: Vec<SnapshotEdge>,
+    roots: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+struct SnapshotObject {
+    /// The object's GC heap index.
+    id: u32,
+    /// Index into `GcHeapStats::types`.
+    ty: usize,
+    size: usize,
+    /// The range of this object's outgoing edges in `GcHeapSnapshot::edges`.
+    edges: core::ops::Range<usize>,
+}
+
+#[derive(Clone, Debug)]
+struct SnapshotEdge {
+    /// The struct field or array element index holding this reference.
+    index: u32,
+    /// Index into `GcHeapSnapshot::objects`.
+    to: usize,
+}
+
+impl GcHeapSnapshot {
+    /// Statistics about the GC heap at the time of this snapshot.
+    pub fn stats(&self) -> &GcHeapStats {
+        &self.stats
+    }
+
+    /// Export this snapshot as JSON in the `.heapsnapshot` format.
+    ///
+    /// This is the format produced by V8, and can be loaded into heap-analysis
+    /// tools that support it, such as the Memory panel of Chrome DevTools.
+    /// Objects are named after their kind and an engine-wide type index, so
+    /// that objects of the same concrete type are grouped together, and the
+    /// store's GC roots are represented by a synthetic `(GC roots)` object.
+    pub fn to_heapsnapshot_json(&self) -> String {
+        // Node types and edge types, as indices into the `node_types` and
+        // `edge_types` arrays in the metadata below.
+        const NODE_ARRAY: u32 = 1;
+        const NODE_OBJECT: u32 = 3;
+        const NODE_NATIVE: u32 = 8;
+        const NODE_SYNTHETIC: u32 = 9;
+        const EDGE_ELEMENT: u32 = 1;
+        const EDGE_PROPERTY: u32 = 2;
+        const NODE_FIELDS: usize = 6;
+
+        let mut strings = vec![String::new()];
+        let mut string_ids = HashMap::new();
+        let mut intern = |s: String| -> usize {
+            *string_ids.entry(s.clone()).or_insert_with(|| {
+                strings.push(s);
+                strings.len() - 1
+            })
+        };
+
+        let type_names: Vec<usize> = self
+            .stats
+            .types
+            .iter()
+            .map(|t| {
+                intern(match &t.ty {
+                    GcObjectType::ExternRef => "externref".to_string(),
+                    GcObjectType::Struct(s) => format!("struct #{}", s.type_index().bits()),
+                    GcObjectType::Array(a) => format!("array #{}", a.type_index().bits()),
+                    GcObjectType::Exn(e) => format!("exn #{}", e.type_index().bits()),
+                })
+            })
+            .collect();
+
+        // Node 0 is the synthetic root; object `i` is node `i + 1`.
+        let mut nodes = String::new();
+        let mut edges = String::new();
+        let root_name = intern("(GC roots)".to_string());
+        write!(
+            nodes,
+            "{NODE_SYNTHETIC},{root_name},0,0,{},0",
+            self.roots.len()
+        )
+        .unwrap();
+        for (i, root) in self.roots.iter().enumerate() {
+            let sep = if edges.is_empty() { "" } else { "," };
+            let to = (root + 1) * NODE_FIELDS;
+            write!(edges, "{sep}{EDGE_ELEMENT},{i},{to}").unwrap();
+        }
+
+        for object in &self.objects {
+            let ty = &self.stats.types[object.ty].ty;
+            let node_type = match ty {
+                GcObjectType::ExternRef => NODE_NATIVE,
+                GcObjectType::Array(_) => NODE_ARRAY,
+                GcObjectType::Struct(_) | GcObjectType::Exn(_) => NODE_OBJECT,
+            };
+            write!(
+                nodes,
+                ",{node_type},{},{},{},{},0",
+                type_names[object.ty],
+                // V8 uses odd IDs for heap objects.
+                u64::from(object.id) * 2 + 1,
+                object.size,
+                object.edges.len(),
+            )
+            .unwrap();
+
+            for edge in &self.edges[object.edges.clone()] {
+                let to = (edge.to + 1) * NODE_FIELDS;
+                let sep = if edges.is_empty() { "" } else { "," };
+                match ty {
+                    GcObjectType::Array(_) => {
+                        write!(edges, "{sep}{EDGE_ELEMENT},{},{to}", edge.index).unwrap()
+                    }
+                    _ => {
+                        let name = intern(edge.index.to_string());
+                        write!(edges, "{sep}{EDGE_PROPERTY},{name},{to}").unwrap()
+                    }
+                }
+            }
+        }
+
+        let edge_count = self.roots.len() + self.edges.len();
+        let mut json = String::new();
+        write!(
+            json,
+            concat!(
+                "{{\"snapshot\":{{\"meta\":{{",
+                "\"node_fields\":[\"type\",\"name\",\"id\",\"self_size\",\"edge_count\",",
+                "\"trace_node_id\"],",
+                "\"node_types\":[[\"hidden\",\"array\",\"string\",\"object\",\"code\",",
+                "\"closure\",\"regexp\",\"number\",\"native\",\"synthetic\",",
+                "\"concatenated string\",\"sliced string\",\"symbol\",\"bigint\"],",
+                "\"string\",\"number\",\"number\",\"number\",\"number\"],",
+                "\"edge_fields\":[\"type\",\"name_or_index\",\"to_node\"],",
+                "\"edge_types\":[[\"context\",\"element\",\"property\",\"internal\",",
+                "\"hidden\",\"shortcut\",\"weak\"],\"string_or_number\",\"node\"],",
+                "\"trace_function_info_fields\":[],\"trace_node_fields\":[],",
+                "\"sample_fields\":[],\"location_fields\":[]}},",
+                "\"node_count\":{},\"edge_count\":{},\"trace_function_count\":0}},",
+                "\"nodes\":[{}],\"edges\":[{}],\"trace_function_infos\":[],",
+                "\"trace_tree\":[],\"samples\":[],\"locations\":[],\"strings\":["
+            ),
+            self.objects.len() + 1,
+            edge_count,
+            nodes,
+            edges,
+        )
+        .unwrap();
+        for (i, s) in strings.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            json.push('"');
+            for c in s.chars() {
+                match c {
+                    '"' => json.push_str("\\\""),
+                    '\\' => json.push_str("\\\\"),
+                    c if u32::from(c) < 0x20 => write!(json, "\\u{:04x}", u32::from(c)).unwrap(),
+                    c => json.push(c),
+                }
+            }
+            json.push('"');
+        }
+        json.push_str("]}");
+        json
+    }
+}
+
+/// Builds a `GcHeapSnapshot` by tracing the object graph from a set of roots.
+pub(crate) struct GcHeapSnapshotBuilder<'a> {
+    engine: &'a Engine,
+    gc_store: &'a GcStore,
+    record_edges: bool,
+    snapshot: GcHeapSnapshot,
+    object_indices: HashMap<u32, usize>,
+    type_indices: HashMap<Option<VMSharedTypeIndex>, usize>,
+    layouts: Vec<Option<GcLayout>>,
+}
+
+impl<'a> GcHeapSnapshotBuilder<'a> {
+    /// Create a new builder for the given GC heap.
+    ///
+    /// If `record_edges` is false then only statistics are collected, and the
+    /// resulting snapshot has no references between objects.
+    pub(crate) fn new(engine: &'a Engine, gc_store: &'a GcStore, record_edges: bool) -> Self {
+        GcHeapSnapshotBuilder {
+            engine,
+            gc_store,
+            record_edges,
+            snapshot: GcHeapSnapshot::default(),
+            object_indices: HashMap::new(),
+            type_indices: HashMap::new(),
+            layouts: Vec::new(),
+        }
+    }
+
+    /// Add a GC root.
+    pub(crate) fn add_root(&mut self, gc_ref: &VMGcRef) {
+        if gc_ref.is_i31() {
+            return;
+        }
+        let index = self.visit(gc_ref);
+        if self.record_edges {
+            self.snapshot.roots.push(index);
+        }
+    }
+
+    /// Trace every object reachable from the roots added so far, and return
+    /// the resulting snapshot.
+    pub(crate) fn finish(mut self) -> GcHeapSnapshot {
+        let mut i = 0;
+        while i < self.snapshot.objects.len() {
+            self.trace(i);
+            i += 1;
+        }
+
+        let stats = &mut self.snapshot.stats;
+        stats.capacity = self.gc_store.vmmemory_definition().current_length();
+        stats.collections = self.gc_store.collection_stats;
+        stats.live_objects = self.snapshot.objects.len();
+        stats.live_bytes = stats.types.iter().map(|t| t.bytes).sum();
+
+        // Sort the types by descending size, remapping objects' type indices
+        // to match.
+        let mut order: Vec<usize> = (0..stats.types.len()).collect();
+        order.sort_by_key(|i| core::cmp::Reverse(stats.types[*i].bytes));
+        let mut new_index = vec![0; order.len()];
+        for (new, old) in order.iter().enumerate() {
+            new_index[*old] = new;
+        }
+        let types = core::mem::take(&mut stats.types);
+        let mut types: Vec<_> = types.into_iter().map(Some).collect();
+        stats.types = order.iter().map(|i| types[*i].take().unwrap()).collect();
+        for object in &mut self.snapshot.objects {
+            object.ty = new_index[object.ty];
+        }
+
+        self.snapshot
+    }
+
+    /// Get the index of the given object in the snapshot, adding it if this is
+    /// the first time we've seen it.
+    fn visit(&mut self, gc_ref: &VMGcRef) -> usize {
+        debug_assert!(!gc_ref.is_i31());
+        let id = gc_ref.as_raw_u32();
+        if let Some(index) = self.object_indices.get(&id) {
+            return *index;
+        }
+
+        let ty = self.gc_store.header(gc_ref).ty();
+        let type_index = match self.type_indices.get(&ty) {
+            Some(i) => *i,
+            None => {
+                let layout = ty.map(|ty| {
+                    self.engine
+                        .signatures()
+                        .layout(ty)
+                        .unwrap_or_else(|| panic!("should have a GC layout for {ty:?}"))
+                });
+                self.snapshot.stats.types.push(GcTypeStats {
+                    ty: GcObjectType::new(self.engine, ty, layout.as_ref()),
+                    objects: 0,
+                    bytes: 0,
+                });
+                self.layouts.push(layout);
+                let i = self.snapshot.stats.types.len() - 1;
+                self.type_indices.insert(ty, i);
+                i
+            }
+        };
+
+        let size = self.gc_store.gc_heap.object_size(gc_ref);
+        let type_stats = &mut self.snapshot.stats.types[type_index];
+        type_stats.objects += 1;
+        type_stats.bytes += size;
+
+        let index = self.snapshot.objects.len();
+        self.snapshot.objects.push(SnapshotObject {
+            id,
+            ty: type_index,
+            size,
+            edges: 0..0,
+        });
+        self.object_indices.insert(id, index);
+        index
+    }
+
+    /// Visit every object referenced by the given object.
+    fn trace(&mut self, index: usize) {
+        let gc_ref = VMGcRef::from_raw_u32(self.snapshot.objects[index].id).unwrap();
+        let offsets: Vec<(u32, u32)> = match &self.layouts[self.snapshot.objects[index].ty] {
+            None => return,
+            Some(GcLayout::Struct(s)) => s
+                .fields
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| f.is_gc_ref)
+                .map(|(i, f)| (u32::try_from(i).unwrap(), f.offset))
+                .collect(),
+            Some(GcLayout::Array(a)) => {
+                if !a.elems_are_gc_refs {
+                    return;
+                }
+                let len = self
+                    .gc_store
+                    .gc_heap
+                    .array_len(gc_ref.as_arrayref_unchecked());
+                (0..len).map(|i| (i, a.elem_offset(i))).collect()
+            }
+        };
+
+        let start = self.snapshot.edges.len();
+        for (field, offset) in offsets {
+            let raw = self
+                .gc_store
+                .gc_heap
+                .gc_object_data(&gc_ref)
+                .read_u32(offset);
+            let Some(to) = VMGcRef::from_raw_u32(raw) else {
+                continue;
+            };
+            if to.is_i31() {
+                continue;
+            }
+            let to = self.visit(&to);
+            if self.record_edges {
+                self.snapshot.edges.push(SnapshotEdge { index: field, to });
+            }
+        }
+        self.snapshot.objects[index].edges = start..self.snapshot.edges.len();
+    }
+}
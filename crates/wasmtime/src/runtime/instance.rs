@@ -390,7 +390,7 @@ impl Instance {
         self._module(store.into().0)
     }
 
-    fn _module<'a>(&self, store: &'a StoreOpaque) -> &'a Module {
+    pub(crate) fn _module<'a>(&self, store: &'a StoreOpaque) -> &'a Module {
         store.module_for_instance(self.id).unwrap()
     }
 
@@ -475,7 +475,7 @@ impl Instance {
         Some(self._get_export(store, export.entity))
     }
 
-    fn _get_export(&self, store: &mut StoreOpaque, entity: EntityIndex) -> Extern {
+    pub(crate) fn _get_export(&self, store: &mut StoreOpaque, entity: EntityIndex) -> Extern {
         let id = store.id();
         // SAFETY: the store `id` owns this instance and all exports contained
         // within.
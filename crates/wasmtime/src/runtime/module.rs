@@ -25,8 +25,12 @@ use wasmtime_environ::{
 #[cfg(feature = "gc")]
 use wasmtime_unwinder::ExceptionTable;
 mod registry;
+#[cfg(feature = "winch")]
+mod tier_up;
 
 pub use registry::*;
+#[cfg(feature = "winch")]
+pub(crate) use tier_up::TierUpStatus;
 
 /// A compiled WebAssembly module, ready to be instantiated.
 ///
@@ -159,6 +163,11 @@ struct ModuleInner {
 
     /// Runtime offset information for `VMContext`.
     offsets: VMOffsets<HostPtr>,
+
+    /// State for recompiling this module with an optimizing compiler, if
+    /// tiered compilation is enabled.
+    #[cfg(feature = "winch")]
+    tier_up: Option<tier_up::TierUp>,
 }
 
 impl fmt::Debug for Module {
@@ -554,10 +563,47 @@ impl Module {
                 #[cfg(any(feature = "cranelift", feature = "winch"))]
                 serializable,
                 offsets,
+                #[cfg(feature = "winch")]
+                tier_up: None,
             }),
         })
     }
 
+    /// Enables tiered compilation for this freshly-compiled module, keeping
+    /// a copy of `wasm` around to recompile it later on.
+    ///
+    /// This does nothing if tiered compilation isn't enabled in the engine.
+    #[cfg(feature = "winch")]
+    pub(crate) fn with_tier_up(mut self, wasm: &[u8]) -> Self {
+        if self.inner.engine.tunables().tier_up {
+            Arc::get_mut(&mut self.inner)
+                .expect("module should not be shared yet")
+                .tier_up = Some(tier_up::TierUp::new(wasm));
+        }
+        self
+    }
+
+    /// Returns the status of the optimized version of this module when using
+    /// tiered compilation, starting its compilation if necessary.
+    #[cfg(feature = "winch")]
+    pub(crate) fn tier_up_status(&self) -> TierUpStatus {
+        match self.tier_up() {
+            Some(tier_up) => tier_up.poll(self),
+            None => TierUpStatus::Unavailable,
+        }
+    }
+
+    #[cfg(feature = "winch")]
+    fn tier_up(&self) -> Option<&tier_up::TierUp> {
+        self.inner.tier_up.as_ref()
+    }
+
+    /// Returns whether this module can be tiered up at runtime.
+    #[cfg(feature = "winch")]
+    pub(crate) fn has_tier_up(&self) -> bool {
+        self.inner.tier_up.is_some()
+    }
+
     /// Validates `binary` input data as a WebAssembly binary given the
     /// configuration in `engine`.
     ///
@@ -653,7 +699,11 @@ impl Module {
         self.inner.code.module_types()
     }
 
-    #[cfg(any(feature = "component-model", feature = "gc-drc"))]
+    #[cfg(any(
+        feature = "component-model",
+        feature = "gc-drc",
+        feature = "gc-copying"
+    ))]
     pub(crate) fn signatures(&self) -> &crate::type_registry::TypeCollection {
         self.inner.code.signatures()
     }
@@ -0,0 +1,144 @@
+//! Support for tiered compilation of modules.
+//!
+//! With [`Strategy::Tiered`](crate::Strategy::Tiered) modules are compiled
+//! with Winch and each instance counts how often its functions run. Once a
+//! function is hot the whole module is recompiled with Cranelift in the
+//! background, and when that's done instances patch the `VMFuncRef` of hot
+//! functions to point to the optimized code. This module manages the
+//! state of that background compilation for a single module.
+
+use crate::Module;
+use crate::prelude::*;
+use std::sync::Mutex;
+
+/// Tiered-compilation state attached to a Winch-compiled [`Module`].
+pub(crate) struct TierUp {
+    /// The original WebAssembly binary, used to recompile the module.
+    wasm: Box<[u8]>,
+    state: Mutex<State>,
+}
+
+enum State {
+    /// No function has requested to be tiered up yet.
+    Idle,
+    /// The module is being compiled in the background.
+    Compiling,
+    /// The optimized module is available.
+    Ready(Module),
+    /// Compilation failed, so functions stay on their baseline code.
+    Failed,
+}
+
+/// The result of [`TierUp::poll`].
+pub(crate) enum TierUpStatus {
+    /// The optimized module isn't available yet.
+    Pending,
+    /// The optimized module is ready to be used.
+    Ready(Module),
+    /// The module will never be tiered up.
+    Unavailable,
+}
+
+impl TierUp {
+    pub(crate) fn new(wasm: &[u8]) -> TierUp {
+        TierUp {
+            wasm: wasm.into(),
+            state: Mutex::new(State::Idle),
+        }
+    }
+
+    /// Returns the status of the optimized version of `module`, starting its
+    /// compilation with [`Engine::spawn_maybe_parallel`] if it hasn't been
+    /// started yet.
+    ///
+    /// [`Engine::spawn_maybe_parallel`]: crate::Engine::spawn_maybe_parallel
+    ///
+    /// `module` must be the module this `TierUp` belongs to.
+    pub(crate) fn poll(&self, module: &Module) -> TierUpStatus {
+        let start = {
+            let mut state = self.state.lock().unwrap();
+            let idle = matches!(*state, State::Idle);
+            if idle {
+                *state = State::Compiling;
+            }
+            idle
+        };
+
+        if start {
+            let engine = module.engine().clone();
+            let module = module.clone();
+            // The lock isn't held here as this may run to completion before
+            // returning.
+            engine.spawn_maybe_parallel(move || {
+                let tier_up = module.tier_up().unwrap();
+                let result = crate::compile::compile_tier_up_module(module.engine(), &tier_up.wasm);
+                *tier_up.state.lock().unwrap() = match result {
+                    Ok(optimized) => State::Ready(optimized),
+                    Err(e) => {
+                        log::warn!("failed to tier up module: {e:?}");
+                        State::Failed
+                    }
+                };
+            });
+        }
+
+        match &*self.state.lock().unwrap() {
+            State::Idle | State::Compiling => TierUpStatus::Pending,
+            State::Ready(optimized) => TierUpStatus::Ready(optimized.clone()),
+            State::Failed => TierUpStatus::Unavailable,
+        }
+    }
+}
+
+#[cfg(all(test, any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod tests {
+    use super::TierUpStatus;
+    use crate::{AsContext, Config, Engine, Instance, Module, Store, Strategy};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn hot_function_is_patched() -> crate::Result<()> {
+        let mut config = Config::new();
+        config.strategy(Strategy::Tiered).tier_up_threshold(1);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(
+            &engine,
+            r#"
+                (module
+                    (func (export "f") (param i32) (result i32)
+                        local.get 0
+                        i32.const 1
+                        i32.add))
+            "#,
+        )?;
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let func = instance.get_func(&mut store, "f").unwrap();
+        let f = func.typed::<i32, i32>(&store)?;
+
+        let start = Instant::now();
+        loop {
+            assert_eq!(f.call(&mut store, 41)?, 42);
+
+            if let TierUpStatus::Ready(optimized) = module.tier_up_status() {
+                let func_ref = func.vm_func_ref(store.as_context().0);
+                // SAFETY: the funcref is owned by the instance which is alive.
+                let wasm_call = unsafe { func_ref.as_ref().wasm_call.unwrap().as_ptr() };
+                if optimized
+                    .text()
+                    .as_ptr_range()
+                    .contains(&wasm_call.cast_const().cast())
+                {
+                    return Ok(());
+                }
+            }
+
+            assert!(
+                start.elapsed() < Duration::from_secs(60),
+                "function was never tiered up"
+            );
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
@@ -0,0 +1,840 @@
+//! Pre-initialization of WebAssembly modules and components.
+//!
+//! A snapshot captures the memories, tables and globals of an instance after
+//! it has been initialized and writes them back into the original WebAssembly
+//! binary as data segments, element segments and constant global
+//! initializers. Instantiating the resulting binary then skips straight to the
+//! initialized state, which pairs well with copy-on-write memory
+//! initialization.
+
+use crate::hash_map::HashMap;
+use crate::prelude::*;
+use crate::{AsContextMut, Extern, Func, Instance, Memory, Ref, StoreContextMut, Val};
+use alloc::borrow::Cow;
+use wasm_encoder::reencode::{Reencode, RoundtripReencoder};
+use wasmparser::{Parser, Payload};
+use wasmtime_environ::EntityIndex;
+
+/// Size of the chunks that memories are split into when writing them out as
+/// data segments, see `MemorySnapshot::capture`.
+const CHUNK_SIZE: usize = 4096;
+
+impl Instance {
+    /// Snapshots the current state of this instance into a new WebAssembly
+    /// module.
+    ///
+    /// The `wasm` argument must be the WebAssembly binary that this instance's
+    /// [`Module`](crate::Module) was created from. The returned binary is the
+    /// same module except that:
+    ///
+    /// * the initial contents of its memories are the current contents of this
+    ///   instance's memories,
+    /// * the initial contents of its tables are the current contents of this
+    ///   instance's tables,
+    /// * its globals are initialized to their current values,
+    /// * element and data segments that have already been applied or dropped
+    ///   are emptied, and
+    /// * its start function, if any, is removed since it has already run.
+    ///
+    /// This is typically used after calling an initialization function of the
+    /// instance to produce a module that doesn't need to run that function
+    /// again when it's instantiated, see [`Preinitializer`] for a higher-level
+    /// interface to this.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state of this instance can't be represented in
+    /// a WebAssembly module, for example:
+    ///
+    /// * the module imports memories or tables, whose state lives outside of
+    ///   this instance,
+    /// * the module defines shared memories,
+    /// * a global or table holds a non-null reference which isn't a function
+    ///   of this instance, such as an `externref` or a GC object.
+    ///
+    /// An error is also returned if `wasm` doesn't match this instance's
+    /// module.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this instance.
+    pub fn snapshot(&self, mut store: impl AsContextMut, wasm: &[u8]) -> Result<Vec<u8>> {
+        let mut store = store.as_context_mut();
+        let snapshot = CoreSnapshot::capture(&mut store, self, false)?;
+        snapshot.rewrite(wasm, None)
+    }
+}
+
+#[cfg(feature = "component-model")]
+impl crate::component::Instance {
+    /// Snapshots the current state of this component instance into a new
+    /// WebAssembly component.
+    ///
+    /// The `wasm` argument must be the WebAssembly binary that this instance's
+    /// [`Component`](crate::component::Component) was created from. Each core
+    /// module within the component which was instantiated is rewritten as
+    /// described in [`Instance::snapshot`](crate::Instance::snapshot), while
+    /// everything else in the component is left as-is.
+    ///
+    /// # Errors
+    ///
+    /// In addition to the errors of
+    /// [`Instance::snapshot`](crate::Instance::snapshot), an error is
+    /// returned if a core module within the component is instantiated more
+    /// than once, as there's no single state to write into it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this instance.
+    pub fn snapshot(&self, mut store: impl AsContextMut, wasm: &[u8]) -> Result<Vec<u8>> {
+        use wasmtime_environ::component::{
+            GlobalInitializer, InstantiateModule, RuntimeInstanceIndex, StaticModuleIndex,
+        };
+
+        let mut store = store.as_context_mut();
+
+        // Core instances are created in the order of the `InstantiateModule`
+        // initializers, so walk those to learn which instance belongs to which
+        // static module.
+        let data = self.id().get(store.0);
+        let num_static_modules = data.component().static_modules().count();
+        let mut instances = Vec::new();
+        let mut runtime_instances = 0;
+        for init in data.component().env_component().initializers.iter() {
+            let GlobalInitializer::InstantiateModule(init) = init else {
+                continue;
+            };
+            let index = RuntimeInstanceIndex::from_u32(runtime_instances);
+            runtime_instances += 1;
+            if let InstantiateModule::Static(module, _) = init {
+                instances.push((*module, data.instance(index)));
+            }
+        }
+
+        let mut snapshots = HashMap::new();
+        for (module, id) in instances {
+            let instance = Instance::from_wasmtime(id, store.0);
+            let snapshot = CoreSnapshot::capture(&mut store, &instance, true)?;
+            if snapshots.insert(module, snapshot).is_some() {
+                bail!(
+                    "cannot snapshot a component which instantiates the same core module \
+                     more than once"
+                );
+            }
+        }
+
+        let mut next_module = 0;
+        let result = rewrite_component(wasm, &mut |module| {
+            let index = StaticModuleIndex::from_u32(next_module);
+            next_module += 1;
+            snapshots
+                .get(&index)
+                .map(|snapshot| snapshot.rewrite(module, None))
+                .transpose()
+        })?;
+        if usize::try_from(next_module).unwrap() != num_static_modules {
+            bail!("wasm binary does not match the component of the instance");
+        }
+        Ok(result)
+    }
+}
+
+/// Pre-initializes WebAssembly modules and components by running their
+/// initialization function and snapshotting the result.
+///
+/// This is similar to the [Wizer] tool: a module is instantiated, an exported
+/// initialization function is called, and the resulting state of the instance
+/// is written out as a new module with [`Instance::snapshot`]. Instantiating
+/// the new module is then equivalent to instantiating the original one and
+/// calling its initialization function, without the cost of running it.
+///
+/// The initialization function must take no parameters and return no
+/// results, and it's named `wizer.initialize` by default.
+///
+/// ```
+/// # use wasmtime::*;
+/// # fn main() -> Result<()> {
+/// let engine = Engine::default();
+/// let wasm = wat::parse_str(r#"
+///     (module
+///         (global $g (mut i32) (i32.const 0))
+///         (func (export "wizer.initialize")
+///             i32.const 42
+///             global.set $g)
+///         (func (export "get") (result i32)
+///             global.get $g))
+/// "#)?;
+///
+/// let mut store = Store::new(&engine, ());
+/// let linker = Linker::new(&engine);
+/// let initialized = Preinitializer::new().run(&mut store, &linker, &wasm)?;
+///
+/// let module = Module::new(&engine, &initialized)?;
+/// let instance = Instance::new(&mut store, &module, &[])?;
+/// let get = instance.get_typed_func::<(), i32>(&mut store, "get")?;
+/// assert_eq!(get.call(&mut store, ())?, 42);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [Wizer]: https://github.com/bytecodealliance/wizer
+#[derive(Debug, Clone)]
+pub struct Preinitializer {
+    init_func: String,
+    keep_init_func: bool,
+}
+
+impl Default for Preinitializer {
+    fn default() -> Preinitializer {
+        Preinitializer::new()
+    }
+}
+
+impl Preinitializer {
+    /// Creates a new pre-initializer with the default configuration.
+    pub fn new() -> Preinitializer {
+        Preinitializer {
+            init_func: "wizer.initialize".to_string(),
+            keep_init_func: false,
+        }
+    }
+
+    /// Configures the name of the exported initialization function.
+    ///
+    /// Defaults to `wizer.initialize`.
+    pub fn init_func(&mut self, name: &str) -> &mut Self {
+        self.init_func = name.to_string();
+        self
+    }
+
+    /// Configures whether the initialization function stays exported from
+    /// pre-initialized modules.
+    ///
+    /// By default the export is removed since calling the function again
+    /// would usually initialize the module twice. Components always keep
+    /// their exports.
+    pub fn keep_init_func(&mut self, keep: bool) -> &mut Self {
+        self.keep_init_func = keep;
+        self
+    }
+
+    /// Pre-initializes the WebAssembly module `wasm`.
+    ///
+    /// The module is instantiated within `store` using `linker`, its
+    /// initialization function is called, and the snapshot of the resulting
+    /// instance is returned.
+    ///
+    /// Note that the instance stays alive within `store`, so a fresh store
+    /// is typically used for pre-initialization.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `wasm` fails to compile or instantiate, if the
+    /// initialization function is missing or traps, or if the instance can't
+    /// be snapshotted, see [`Instance::snapshot`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` is configured for async, or if `store` and `linker`
+    /// belong to different engines.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn run<T: 'static>(
+        &self,
+        mut store: impl AsContextMut<Data = T>,
+        linker: &crate::Linker<T>,
+        wasm: &[u8],
+    ) -> Result<Vec<u8>> {
+        #[cfg(feature = "wat")]
+        let wasm = wat::parse_bytes(wasm)?;
+        let module = crate::Module::new(store.as_context().engine(), &wasm[..])?;
+        let instance = linker.instantiate(&mut store, &module)?;
+        instance
+            .get_typed_func::<(), ()>(&mut store, &self.init_func)?
+            .call(&mut store, ())
+            .with_context(|| format!("failed to call `{}`", self.init_func))?;
+
+        let mut store = store.as_context_mut();
+        let snapshot = CoreSnapshot::capture(&mut store, &instance, false)?;
+        let remove_export = if self.keep_init_func {
+            None
+        } else {
+            Some(self.init_func.as_str())
+        };
+        snapshot.rewrite(&wasm, remove_export)
+    }
+
+    /// Pre-initializes the WebAssembly component `wasm`.
+    ///
+    /// This is the same as [`Preinitializer::run`] except for components, and
+    /// the snapshot is taken with
+    /// [`component::Instance::snapshot`](crate::component::Instance::snapshot).
+    #[cfg(all(
+        feature = "component-model",
+        any(feature = "cranelift", feature = "winch")
+    ))]
+    pub fn run_component<T: 'static>(
+        &self,
+        mut store: impl AsContextMut<Data = T>,
+        linker: &crate::component::Linker<T>,
+        wasm: &[u8],
+    ) -> Result<Vec<u8>> {
+        #[cfg(feature = "wat")]
+        let wasm = wat::parse_bytes(wasm)?;
+        let component = crate::component::Component::new(store.as_context().engine(), &wasm[..])?;
+        let instance = linker.instantiate(&mut store, &component)?;
+        let func = instance.get_typed_func::<(), ()>(&mut store, self.init_func.as_str())?;
+        func.call(&mut store, ())
+            .with_context(|| format!("failed to call `{}`", self.init_func))?;
+        func.post_return(&mut store)?;
+
+        instance.snapshot(&mut store, &wasm)
+    }
+}
+
+/// The state of a core instance which is written into its module.
+struct CoreSnapshot {
+    num_imported_memories: usize,
+    num_imported_tables: usize,
+    /// The state of each defined memory.
+    memories: Vec<MemorySnapshot>,
+    /// The contents of each defined table.
+    tables: Vec<TableSnapshot>,
+    /// The value of each defined global.
+    globals: Vec<GlobalSnapshot>,
+    /// The indices of the passive element segments that weren't dropped.
+    live_elements: Vec<u32>,
+    /// The indices of the passive data segments that weren't dropped.
+    live_data: Vec<u32>,
+    /// The functions which can be referenced by `ref.func` instructions.
+    escaping_funcs: Vec<u32>,
+}
+
+struct MemorySnapshot {
+    /// The current size of the memory, in pages.
+    pages: u64,
+    is_64: bool,
+    /// Non-zero chunks of the memory's contents and their offsets.
+    segments: Vec<(u64, Vec<u8>)>,
+}
+
+struct TableSnapshot {
+    /// The function index of each element of the table, `None` for nulls.
+    elements: Vec<Option<u32>>,
+}
+
+enum GlobalSnapshot {
+    Const(wasm_encoder::ConstExpr),
+    RefNull,
+    RefFunc(u32),
+}
+
+/// Identifies a function by its funcref's contents rather than its address,
+/// since imported functions have a copy of the exporter's funcref.
+type FuncKey = (usize, Option<usize>, usize);
+
+fn func_key<T: 'static>(store: &StoreContextMut<'_, T>, func: &Func) -> FuncKey {
+    let func_ref = func.vm_func_ref(store.0);
+    // SAFETY: the funcref is owned by `store`, which keeps it alive.
+    let func_ref = unsafe { func_ref.as_ref() };
+    (
+        func_ref.array_call.as_ptr().addr(),
+        func_ref.wasm_call.map(|f| f.as_ptr().addr()),
+        func_ref.vmctx.as_ptr().addr(),
+    )
+}
+
+impl CoreSnapshot {
+    /// Captures the state of `instance`.
+    ///
+    /// When `allow_imported_state` is set imported memories and tables are
+    /// skipped instead of rejected, for when their defining instance is
+    /// snapshotted as well.
+    fn capture<T: 'static>(
+        store: &mut StoreContextMut<'_, T>,
+        instance: &Instance,
+        allow_imported_state: bool,
+    ) -> Result<CoreSnapshot> {
+        let module = instance._module(store.0).env_module().clone();
+        if !allow_imported_state
+            && (module.num_imported_memories > 0 || module.num_imported_tables > 0)
+        {
+            bail!("cannot snapshot an instance which imports memories or tables");
+        }
+
+        let mut funcs = HashMap::new();
+        let mut escaping_funcs = Vec::new();
+        for (index, func) in module.functions.iter() {
+            if !func.is_escaping() {
+                continue;
+            }
+            let Extern::Func(f) = instance._get_export(store.0, EntityIndex::Function(index))
+            else {
+                unreachable!()
+            };
+            funcs.entry(func_key(store, &f)).or_insert(index.as_u32());
+            escaping_funcs.push(index.as_u32());
+        }
+        let func_index = |store: &StoreContextMut<'_, T>, f: &Func| -> Result<u32> {
+            match funcs.get(&func_key(store, f)) {
+                Some(index) => Ok(*index),
+                None => bail!("cannot snapshot a reference to a function of another instance"),
+            }
+        };
+
+        let mut memories = Vec::new();
+        for index in module.memories.keys().skip(module.num_imported_memories) {
+            let memory = match instance._get_export(store.0, EntityIndex::Memory(index)) {
+                Extern::Memory(memory) => memory,
+                Extern::SharedMemory(_) => bail!("cannot snapshot shared memories"),
+                _ => unreachable!(),
+            };
+            memories.push(MemorySnapshot::capture(store, memory));
+        }
+
+        let mut tables = Vec::new();
+        for index in module.tables.keys().skip(module.num_imported_tables) {
+            let Extern::Table(table) = instance._get_export(store.0, EntityIndex::Table(index))
+            else {
+                unreachable!()
+            };
+            let size = table.size(&mut *store);
+            let mut elements = Vec::new();
+            for i in 0..size {
+                let element = match table.get(&mut *store, i).unwrap() {
+                    Ref::Func(Some(f)) => Some(func_index(store, &f)?),
+                    r if r.is_null() => None,
+                    _ => bail!("cannot snapshot tables containing non-function references"),
+                };
+                elements.push(element);
+            }
+            tables.push(TableSnapshot { elements });
+        }
+
+        let mut globals = Vec::new();
+        for index in module.globals.keys().skip(module.num_imported_globals) {
+            let Extern::Global(global) = instance._get_export(store.0, EntityIndex::Global(index))
+            else {
+                unreachable!()
+            };
+            let global = match global.get(&mut *store) {
+                Val::I32(x) => GlobalSnapshot::Const(wasm_encoder::ConstExpr::i32_const(x)),
+                Val::I64(x) => GlobalSnapshot::Const(wasm_encoder::ConstExpr::i64_const(x)),
+                Val::F32(x) => GlobalSnapshot::Const(wasm_encoder::ConstExpr::f32_const(
+                    f32::from_bits(x).into(),
+                )),
+                Val::F64(x) => GlobalSnapshot::Const(wasm_encoder::ConstExpr::f64_const(
+                    f64::from_bits(x).into(),
+                )),
+                Val::V128(x) => {
+                    GlobalSnapshot::Const(wasm_encoder::ConstExpr::v128_const(x.as_u128() as i128))
+                }
+                Val::FuncRef(Some(f)) => GlobalSnapshot::RefFunc(func_index(store, &f)?),
+                Val::FuncRef(None)
+                | Val::ExternRef(None)
+                | Val::AnyRef(None)
+                | Val::ExnRef(None)
+                | Val::ContRef(None) => GlobalSnapshot::RefNull,
+                _ => bail!("cannot snapshot globals containing non-function references"),
+            };
+            globals.push(global);
+        }
+
+        let vm_instance = store.0.instance(instance.id());
+        let live_elements = module
+            .passive_elements_map
+            .keys()
+            .filter(|i| vm_instance.has_passive_element(**i))
+            .map(|i| i.as_u32())
+            .collect();
+        let live_data = module
+            .passive_data_map
+            .keys()
+            .filter(|i| vm_instance.has_passive_data(**i))
+            .map(|i| i.as_u32())
+            .collect();
+
+        Ok(CoreSnapshot {
+            num_imported_memories: module.num_imported_memories,
+            num_imported_tables: module.num_imported_tables,
+            memories,
+            tables,
+            globals,
+            live_elements,
+            live_data,
+            escaping_funcs,
+        })
+    }
+
+    /// Rewrites the module `wasm` to start out in this snapshot's state,
+    /// removing the export named `remove_export` if given.
+    fn rewrite(&self, wasm: &[u8], remove_export: Option<&str>) -> Result<Vec<u8>> {
+        let mut module = wasm_encoder::Module::new();
+
+        let mut tables = Vec::new();
+        let mut num_globals = 0;
+        let mut num_memories = 0;
+        let mut wrote_elements = false;
+        let mut wrote_data = false;
+        let mut has_data_count = false;
+        let num_data_segments = self
+            .memories
+            .iter()
+            .map(|m| m.segments.len())
+            .sum::<usize>();
+
+        for payload in Parser::new(0).parse_all(wasm) {
+            let payload = payload?;
+
+            if !wrote_elements
+                && matches!(
+                    payload,
+                    Payload::DataCountSection { .. }
+                        | Payload::CodeSectionStart { .. }
+                        | Payload::DataSection(_)
+                        | Payload::End(_)
+                )
+            {
+                let mut elements = wasm_encoder::ElementSection::new();
+                self.append_elements(&mut elements, &tables)?;
+                if !elements.is_empty() {
+                    module.section(&elements);
+                }
+                wrote_elements = true;
+            }
+
+            match payload {
+                Payload::Version { encoding, .. } => {
+                    if encoding != wasmparser::Encoding::Module {
+                        bail!("expected a WebAssembly module");
+                    }
+                }
+
+                Payload::MemorySection(reader) => {
+                    let mut memories = wasm_encoder::MemorySection::new();
+                    for memory in reader {
+                        let mut ty = wasm_encoder::MemoryType::from(memory?);
+                        let Some(snapshot) = self.memories.get(num_memories) else {
+                            bail!("wasm binary does not match the module of the instance");
+                        };
+                        ty.minimum = snapshot.pages;
+                        memories.memory(ty);
+                        num_memories += 1;
+                    }
+                    module.section(&memories);
+                }
+
+                Payload::TableSection(reader) => {
+                    let mut section = wasm_encoder::TableSection::new();
+                    for table in reader {
+                        let table = table?;
+                        let mut ty = wasm_encoder::TableType::try_from(table.ty)?;
+                        let Some(snapshot) = self.tables.get(tables.len()) else {
+                            bail!("wasm binary does not match the module of the instance");
+                        };
+                        ty.minimum = u64::try_from(snapshot.elements.len()).unwrap();
+                        let has_init = match table.init {
+                            wasmparser::TableInit::RefNull => {
+                                section.table(ty);
+                                false
+                            }
+                            wasmparser::TableInit::Expr(expr) => {
+                                section.table_with_init(ty, &RoundtripReencoder.const_expr(expr)?);
+                                true
+                            }
+                        };
+                        tables.push((ty.element_type, ty.table64, has_init));
+                    }
+                    module.section(&section);
+                }
+
+                Payload::GlobalSection(reader) => {
+                    let mut section = wasm_encoder::GlobalSection::new();
+                    for global in reader {
+                        let ty = global?.ty;
+                        let Some(snapshot) = self.globals.get(num_globals) else {
+                            bail!("wasm binary does not match the module of the instance");
+                        };
+                        let init = match snapshot {
+                            GlobalSnapshot::Const(expr) => expr.clone(),
+                            GlobalSnapshot::RefFunc(f) => wasm_encoder::ConstExpr::ref_func(*f),
+                            GlobalSnapshot::RefNull => {
+                                let wasmparser::ValType::Ref(r) = ty.content_type else {
+                                    bail!("wasm binary does not match the module of the instance");
+                                };
+                                wasm_encoder::ConstExpr::ref_null(r.heap_type().try_into()?)
+                            }
+                        };
+                        section.global(ty.try_into()?, &init);
+                        num_globals += 1;
+                    }
+                    module.section(&section);
+                }
+
+                Payload::ExportSection(reader) => {
+                    let mut exports = wasm_encoder::ExportSection::new();
+                    for export in reader {
+                        let export = export?;
+                        if Some(export.name) == remove_export {
+                            continue;
+                        }
+                        exports.export(export.name, export.kind.into(), export.index);
+                    }
+                    module.section(&exports);
+                }
+
+                // The start function has already run.
+                Payload::StartSection { .. } => {}
+
+                Payload::ElementSection(reader) => {
+                    let mut elements = wasm_encoder::ElementSection::new();
+                    for (i, element) in reader.into_iter().enumerate() {
+                        let element = element?;
+                        let items = RoundtripReencoder.element_items(element.items)?;
+                        let live = matches!(element.kind, wasmparser::ElementKind::Passive)
+                            && self.live_elements.contains(&u32::try_from(i).unwrap());
+                        // Segments which were applied or dropped are turned
+                        // into declarative segments, which behave as if
+                        // they're dropped but still declare the functions
+                        // they reference for `ref.func`.
+                        if live {
+                            elements.passive(items);
+                        } else {
+                            elements.declared(items);
+                        }
+                    }
+                    self.append_elements(&mut elements, &tables)?;
+                    module.section(&elements);
+                    wrote_elements = true;
+                }
+
+                Payload::DataCountSection { count, .. } => {
+                    has_data_count = true;
+                    module.section(&wasm_encoder::DataCountSection {
+                        count: count + u32::try_from(num_data_segments).unwrap(),
+                    });
+                }
+
+                Payload::DataSection(reader) => {
+                    let mut data = wasm_encoder::DataSection::new();
+                    // Without a data count section the segments can't be
+                    // referenced from code, so they're all dropped.
+                    // Otherwise their indices are preserved and all segments
+                    // which were applied or dropped are emptied.
+                    if has_data_count {
+                        for (i, datum) in reader.into_iter().enumerate() {
+                            let datum = datum?;
+                            let live = matches!(datum.kind, wasmparser::DataKind::Passive)
+                                && self.live_data.contains(&u32::try_from(i).unwrap());
+                            if live {
+                                data.passive(datum.data.iter().copied());
+                            } else {
+                                data.passive([]);
+                            }
+                        }
+                    }
+                    self.append_data(&mut data);
+                    module.section(&data);
+                    wrote_data = true;
+                }
+
+                Payload::CodeSectionEntry(_) => {}
+
+                Payload::End(_) => {
+                    if !wrote_data && num_data_segments > 0 {
+                        let mut data = wasm_encoder::DataSection::new();
+                        self.append_data(&mut data);
+                        module.section(&data);
+                    }
+                }
+
+                other => match other.as_section() {
+                    Some((id, range)) => {
+                        module.section(&wasm_encoder::RawSection {
+                            id,
+                            data: &wasm[range],
+                        });
+                    }
+                    None => bail!("wasm binary does not match the module of the instance"),
+                },
+            }
+        }
+
+        if num_memories != self.memories.len()
+            || tables.len() != self.tables.len()
+            || num_globals != self.globals.len()
+        {
+            bail!("wasm binary does not match the module of the instance");
+        }
+
+        Ok(module.finish())
+    }
+
+    /// Appends the segments which initialize tables, along with a declaration
+    /// of all functions which may be referenced by `ref.func`.
+    ///
+    /// `tables` is the element type of each defined table, whether it's a
+    /// 64-bit table and whether it has an initializer expression.
+    fn append_elements(
+        &self,
+        elements: &mut wasm_encoder::ElementSection,
+        tables: &[(wasm_encoder::RefType, bool, bool)],
+    ) -> Result<()> {
+        if !self.escaping_funcs.is_empty() {
+            elements.declared(wasm_encoder::Elements::Functions(Cow::Borrowed(
+                &self.escaping_funcs,
+            )));
+        }
+
+        for (i, (snapshot, (ty, table64, has_init))) in self.tables.iter().zip(tables).enumerate() {
+            let table_index = u32::try_from(self.num_imported_tables + i).unwrap();
+            let offset = |i: usize| {
+                if *table64 {
+                    wasm_encoder::ConstExpr::i64_const(i64::try_from(i).unwrap())
+                } else {
+                    wasm_encoder::ConstExpr::i32_const(i32::try_from(i).unwrap())
+                }
+            };
+
+            // Tables with an initializer expression are written in full since
+            // nulls would otherwise be replaced by the initializer. All other
+            // tables start out null, so only the runs of functions are
+            // written.
+            let runs = if *has_init {
+                vec![(0, snapshot.elements.len())]
+            } else {
+                let mut runs = Vec::new();
+                let mut start = None;
+                for (i, element) in snapshot.elements.iter().enumerate() {
+                    match (element, start) {
+                        (Some(_), None) => start = Some(i),
+                        (None, Some(s)) => {
+                            runs.push((s, i));
+                            start = None;
+                        }
+                        _ => {}
+                    }
+                }
+                if let Some(s) = start {
+                    runs.push((s, snapshot.elements.len()));
+                }
+                runs
+            };
+
+            for (start, end) in runs {
+                let items = snapshot.elements[start..end]
+                    .iter()
+                    .map(|element| match element {
+                        Some(f) => wasm_encoder::ConstExpr::ref_func(*f),
+                        None => wasm_encoder::ConstExpr::ref_null(ty.heap_type),
+                    })
+                    .collect::<Vec<_>>();
+                elements.active(
+                    Some(table_index),
+                    &offset(start),
+                    wasm_encoder::Elements::Expressions(*ty, Cow::Owned(items)),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends the segments which initialize memories.
+    fn append_data(&self, data: &mut wasm_encoder::DataSection) {
+        for (i, memory) in self.memories.iter().enumerate() {
+            let memory_index = u32::try_from(self.num_imported_memories + i).unwrap();
+            for (offset, bytes) in &memory.segments {
+                let offset = if memory.is_64 {
+                    wasm_encoder::ConstExpr::i64_const(*offset as i64)
+                } else {
+                    wasm_encoder::ConstExpr::i32_const(u32::try_from(*offset).unwrap() as i32)
+                };
+                data.active(memory_index, &offset, bytes.iter().copied());
+            }
+        }
+    }
+}
+
+impl MemorySnapshot {
+    fn capture<T: 'static>(store: &StoreContextMut<'_, T>, memory: Memory) -> MemorySnapshot {
+        // Like core dumps, balance the number of data segments and the binary
+        // size by splitting the memory into chunks and trimming runs of zeroes
+        // from the start and end of each chunk.
+        let mut segments = Vec::new();
+        for (i, chunk) in memory.data(store).chunks(CHUNK_SIZE).enumerate() {
+            if let Some(start) = chunk.iter().position(|byte| *byte != 0) {
+                let end = chunk.iter().rposition(|byte| *byte != 0).unwrap() + 1;
+                let offset = u64::try_from(i * CHUNK_SIZE + start).unwrap();
+                segments.push((offset, chunk[start..end].to_vec()));
+            }
+        }
+        MemorySnapshot {
+            pages: memory.size(store),
+            is_64: memory.ty(store).is_64(),
+            segments,
+        }
+    }
+}
+
+/// Rewrites the component `wasm`, replacing each core module within it, in
+/// the depth-first order they appear in, with the result of `rewrite_module`
+/// if it returns `Some`.
+#[cfg(feature = "component-model")]
+fn rewrite_component(
+    wasm: &[u8],
+    rewrite_module: &mut dyn FnMut(&[u8]) -> Result<Option<Vec<u8>>>,
+) -> Result<Vec<u8>> {
+    use wasm_encoder::ComponentSectionId;
+    use wasmparser::Chunk;
+
+    let mut component = wasm_encoder::Component::new();
+    let mut parser = Parser::new(0);
+    let mut offset = 0;
+    loop {
+        let payload = match parser.parse(&wasm[offset..], true)? {
+            Chunk::Parsed { consumed, payload } => {
+                offset += consumed;
+                payload
+            }
+            Chunk::NeedMoreData(_) => unreachable!(),
+        };
+        match payload {
+            Payload::Version { encoding, .. } => {
+                if encoding != wasmparser::Encoding::Component {
+                    bail!("expected a WebAssembly component");
+                }
+            }
+            Payload::ModuleSection {
+                unchecked_range, ..
+            } => {
+                let module = &wasm[unchecked_range.clone()];
+                let rewritten = rewrite_module(module)?;
+                component.section(&wasm_encoder::RawSection {
+                    id: ComponentSectionId::CoreModule.into(),
+                    data: rewritten.as_deref().unwrap_or(module),
+                });
+                offset = unchecked_range.end;
+            }
+            Payload::ComponentSection {
+                unchecked_range, ..
+            } => {
+                let nested = rewrite_component(&wasm[unchecked_range.clone()], rewrite_module)?;
+                component.section(&wasm_encoder::RawSection {
+                    id: ComponentSectionId::Component.into(),
+                    data: &nested,
+                });
+                offset = unchecked_range.end;
+            }
+            Payload::End(_) => break,
+            other => match other.as_section() {
+                Some((id, range)) => {
+                    component.section(&wasm_encoder::RawSection {
+                        id,
+                        data: &wasm[range],
+                    });
+                }
+                None => bail!("unexpected payload in component"),
+            },
+        }
+    }
+    Ok(component.finish())
+}
@@ -928,6 +928,49 @@ impl<T> Store<T> {
         StoreContextMut(&mut self.inner).gc(why)
     }
 
+    /// Get statistics about this store's GC heap.
+    ///
+    /// This reports the GC heap's capacity, live object counts and bytes
+    /// grouped by concrete type, and how many garbage collections have been
+    /// performed and how long they took. Live objects are found by tracing the
+    /// object graph from the GC roots, which takes time proportional to the
+    /// number of live objects, but does not collect any garbage.
+    ///
+    /// This method is only available when the `gc` Cargo feature is enabled.
+    #[cfg(feature = "gc")]
+    pub fn gc_heap_stats(&mut self) -> crate::GcHeapStats {
+        StoreContextMut(&mut self.inner).gc_heap_stats()
+    }
+
+    /// Take a snapshot of the object graph in this store's GC heap.
+    ///
+    /// The snapshot can be exported for analysis in heap-analysis tools with
+    /// [`GcHeapSnapshot::to_heapsnapshot_json`][crate::GcHeapSnapshot::to_heapsnapshot_json].
+    /// Like [`Store::gc_heap_stats`], this traces the object graph from the GC
+    /// roots but does not collect any garbage.
+    ///
+    /// This method is only available when the `gc` Cargo feature is enabled.
+    #[cfg(feature = "gc")]
+    pub fn gc_heap_snapshot(&mut self) -> crate::GcHeapSnapshot {
+        StoreContextMut(&mut self.inner).gc_heap_snapshot()
+    }
+
+    /// Returns the allocations made by guests' allocators which haven't been
+    /// freed, along with where they were allocated.
+    ///
+    /// This requires [`Config::wmemcheck`] to be enabled and otherwise
+    /// returns an empty list. Allocations are listed for every instance in
+    /// this store, sorted by address within each instance.
+    ///
+    /// This method is only available when the `wmemcheck` Cargo feature is
+    /// enabled.
+    ///
+    /// [`Config::wmemcheck`]: crate::Config::wmemcheck
+    #[cfg(feature = "wmemcheck")]
+    pub fn wmemcheck_leaks(&self) -> Vec<crate::WmemcheckLeak> {
+        self.inner.wmemcheck_leaks()
+    }
+
     /// Returns the amount fuel in this [`Store`]. When fuel is enabled, it must
     /// be configured via [`Store::set_fuel`].
     ///
@@ -1193,6 +1236,15 @@ impl<'a, T> StoreContext<'a, T> {
     pub fn get_fuel(&self) -> Result<u64> {
         self.0.get_fuel()
     }
+
+    /// Returns the allocations made by guests' allocators which haven't been
+    /// freed.
+    ///
+    /// For more information see [`Store::wmemcheck_leaks`].
+    #[cfg(feature = "wmemcheck")]
+    pub fn wmemcheck_leaks(&self) -> Vec<crate::WmemcheckLeak> {
+        self.0.wmemcheck_leaks()
+    }
 }
 
 impl<'a, T> StoreContextMut<'a, T> {
@@ -1227,6 +1279,26 @@ impl<'a, T> StoreContextMut<'a, T> {
         vm::assert_ready(store.gc(limiter.as_mut(), None, why.map(|e| e.bytes_needed())));
     }
 
+    /// Get statistics about this store's GC heap.
+    ///
+    /// Same as [`Store::gc_heap_stats`].
+    ///
+    /// This method is only available when the `gc` Cargo feature is enabled.
+    #[cfg(feature = "gc")]
+    pub fn gc_heap_stats(&mut self) -> crate::GcHeapStats {
+        self.0.gc_heap_snapshot(false).stats
+    }
+
+    /// Take a snapshot of the object graph in this store's GC heap.
+    ///
+    /// Same as [`Store::gc_heap_snapshot`].
+    ///
+    /// This method is only available when the `gc` Cargo feature is enabled.
+    #[cfg(feature = "gc")]
+    pub fn gc_heap_snapshot(&mut self) -> crate::GcHeapSnapshot {
+        self.0.gc_heap_snapshot(true)
+    }
+
     /// Returns remaining fuel in this store.
     ///
     /// For more information see [`Store::get_fuel`]
@@ -1552,6 +1624,25 @@ impl StoreOpaque {
         (self.gc_store.as_mut(), self.instances[id].handle.get_mut())
     }
 
+    #[cfg(feature = "wmemcheck")]
+    pub(crate) fn wmemcheck_leaks(&self) -> Vec<crate::WmemcheckLeak> {
+        let mut leaks = Vec::new();
+        for (_, instance) in self.instances.iter() {
+            let Some(state) = instance.handle.get().wmemcheck_state() else {
+                continue;
+            };
+            for (addr, size, site) in state.leaks() {
+                let site = site.cloned().unwrap_or_else(vm::Backtrace::empty);
+                leaks.push(crate::WmemcheckLeak {
+                    addr: u64::try_from(addr).unwrap(),
+                    size: u64::try_from(size).unwrap(),
+                    backtrace: crate::WasmBacktrace::from_captured(self, site, None),
+                });
+            }
+        }
+        leaks
+    }
+
     /// Get all instances (ignoring dummy instances) within this store.
     pub fn all_instances<'a>(&'a mut self) -> impl ExactSizeIterator<Item = Instance> + 'a {
         let instances = self
@@ -1815,12 +1906,15 @@ impl StoreOpaque {
 
         log::trace!("============ Begin GC ===========");
 
+        #[cfg(feature = "std")]
+        let start = std::time::Instant::now();
+
         // Take the GC roots out of `self` so we can borrow it mutably but still
         // call mutable methods on `self`.
         let mut roots = core::mem::take(&mut self.gc_roots_list);
 
-        self.trace_roots(&mut roots).await;
         let async_yield = self.async_support();
+        self.trace_roots(&mut roots, async_yield).await;
         self.unwrap_gc_store_mut()
             .gc(async_yield, unsafe { roots.iter() })
             .await;
@@ -1829,32 +1923,40 @@ impl StoreOpaque {
         roots.clear();
         self.gc_roots_list = roots;
 
+        #[cfg(feature = "std")]
+        let pause = start.elapsed();
+        #[cfg(not(feature = "std"))]
+        let pause = core::time::Duration::ZERO;
+        self.unwrap_gc_store_mut().collection_stats.record(pause);
+
         log::trace!("============ End GC ===========");
     }
 
     #[cfg(feature = "gc")]
-    async fn trace_roots(&mut self, gc_roots_list: &mut GcRootsList) {
+    async fn trace_roots(&mut self, gc_roots_list: &mut GcRootsList, async_yield: bool) {
         log::trace!("Begin trace GC roots");
+        #[cfg(not(feature = "async"))]
+        let _ = async_yield;
 
         // We shouldn't have any leftover, stale GC roots.
         assert!(gc_roots_list.is_empty());
 
         self.trace_wasm_stack_roots(gc_roots_list);
         #[cfg(feature = "async")]
-        if self.async_support() {
+        if async_yield {
             vm::Yield::new().await;
         }
         #[cfg(feature = "stack-switching")]
         {
             self.trace_wasm_continuation_roots(gc_roots_list);
             #[cfg(feature = "async")]
-            if self.async_support() {
+            if async_yield {
                 vm::Yield::new().await;
             }
         }
         self.trace_vmctx_roots(gc_roots_list);
         #[cfg(feature = "async")]
-        if self.async_support() {
+        if async_yield {
             vm::Yield::new().await;
         }
         self.trace_user_roots(gc_roots_list);
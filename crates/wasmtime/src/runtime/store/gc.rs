@@ -37,6 +37,38 @@ impl StoreOpaque {
         })
     }
 
+    /// Trace the GC heap's object graph from the current GC roots, without
+    /// collecting any garbage.
+    ///
+    /// If `record_edges` is false, then only statistics are gathered and the
+    /// resulting snapshot does not contain references between objects.
+    pub(crate) fn gc_heap_snapshot(&mut self, record_edges: bool) -> crate::GcHeapSnapshot {
+        if self.gc_store.is_none() {
+            return crate::GcHeapSnapshot::default();
+        }
+
+        self.trim_gc_liveness_flags(true);
+
+        // Take the GC roots out of `self` so we can borrow it mutably but still
+        // call mutable methods on `self`.
+        let mut roots = core::mem::take(&mut self.gc_roots_list);
+        vm::assert_ready(self.trace_roots(&mut roots, false));
+
+        let engine = self.engine().clone();
+        let mut builder =
+            crate::GcHeapSnapshotBuilder::new(&engine, self.unwrap_gc_store(), record_edges);
+        for root in unsafe { roots.iter() } {
+            builder.add_root(&root.get());
+        }
+        let snapshot = builder.finish();
+
+        // Restore the GC roots for the next GC.
+        roots.clear();
+        self.gc_roots_list = roots;
+
+        snapshot
+    }
+
     // This lives on the Store because it must simultaneously borrow
     // `gc_store` and `gc_roots`, and is invoked from other modules to
     // which we do not want to expose the raw fields for piecewise
@@ -126,8 +126,12 @@ pub(crate) fn from_runtime_box(
     let _ = &coredumpstack;
     #[cfg(feature = "coredump")]
     if let Some(coredump) = coredumpstack {
-        let bt = WasmBacktrace::from_captured(store, coredump.bt, pc);
-        let cd = WasmCoreDump::new(store, bt);
+        let crate::runtime::vm::CoreDumpStack { bt, mut frames } = coredump;
+        let mut kept_frames = Vec::new();
+        let bt = WasmBacktrace::from_captured_with(store, bt, pc, |i| {
+            kept_frames.push(core::mem::take(&mut frames[i]));
+        });
+        let cd = WasmCoreDump::new(store, bt, kept_frames);
         error = error.context(cd);
     }
 
@@ -263,17 +267,29 @@ impl WasmBacktrace {
         Self::from_captured(store.0, crate::runtime::vm::Backtrace::new(store.0), None)
     }
 
-    fn from_captured(
+    pub(crate) fn from_captured(
         store: &StoreOpaque,
         runtime_trace: crate::runtime::vm::Backtrace,
         trap_pc: Option<usize>,
+    ) -> Self {
+        Self::from_captured_with(store, runtime_trace, trap_pc, |_| {})
+    }
+
+    /// Same as `from_captured`, but additionally invokes `kept_frame` with the
+    /// index, within `runtime_trace`, of each frame that is kept in the
+    /// resulting backtrace.
+    fn from_captured_with(
+        store: &StoreOpaque,
+        runtime_trace: crate::runtime::vm::Backtrace,
+        trap_pc: Option<usize>,
+        mut kept_frame: impl FnMut(usize),
     ) -> Self {
         let mut wasm_trace = Vec::<FrameInfo>::with_capacity(runtime_trace.frames().len());
         let mut hint_wasm_backtrace_details_env = false;
         let wasm_backtrace_details_env_used =
             store.engine().config().wasm_backtrace_details_env_used;
 
-        for frame in runtime_trace.frames() {
+        for (i, frame) in runtime_trace.frames().enumerate() {
             debug_assert!(frame.pc() != 0);
 
             // Note that we need to be careful about the pc we pass in
@@ -318,6 +334,7 @@ impl WasmBacktrace {
             // this store's module registry.
             if let Some((info, module)) = store.modules().lookup_frame_info(pc_to_lookup) {
                 wasm_trace.push(info);
+                kept_frame(i);
 
                 // If this frame has unparsed debug information and the
                 // store's configuration indicates that we were
@@ -352,7 +369,22 @@ impl WasmBacktrace {
 impl fmt::Display for WasmBacktrace {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "error while executing at wasm backtrace:")?;
+        self.fmt_frames(f)?;
+        if self.hint_wasm_backtrace_details_env {
+            write!(
+                f,
+                "\nnote: using the `WASMTIME_BACKTRACE_DETAILS=1` \
+                 environment variable may show more debugging information"
+            )?;
+        }
+        Ok(())
+    }
+}
 
+impl WasmBacktrace {
+    /// Writes one line per frame of this backtrace, without a trailing
+    /// newline.
+    pub(crate) fn fmt_frames(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut needs_newline = false;
         for (i, frame) in self.wasm_trace.iter().enumerate() {
             // Avoid putting a trailing newline on the output
@@ -404,13 +436,6 @@ impl fmt::Display for WasmBacktrace {
                 }
             }
         }
-        if self.hint_wasm_backtrace_details_env {
-            write!(
-                f,
-                "\nnote: using the `WASMTIME_BACKTRACE_DETAILS=1` \
-                 environment variable may show more debugging information"
-            )?;
-        }
         Ok(())
     }
 }
@@ -99,6 +99,8 @@ pub use crate::runtime::vm::always_mut::*;
 pub use crate::runtime::vm::export::*;
 pub use crate::runtime::vm::gc::*;
 pub use crate::runtime::vm::imports::Imports;
+#[cfg(feature = "wmemcheck")]
+pub use crate::runtime::vm::instance::WmemcheckState;
 pub use crate::runtime::vm::instance::{
     GcHeapAllocationIndex, Instance, InstanceAllocationRequest, InstanceAllocator, InstanceHandle,
     MemoryAllocationIndex, OnDemandInstanceAllocator, TableAllocationIndex, initialize_instance,
@@ -153,9 +155,9 @@ mod async_yield;
 #[cfg(feature = "async")]
 pub use crate::runtime::vm::async_yield::*;
 
-#[cfg(feature = "gc-null")]
+#[cfg(any(feature = "gc-null", feature = "gc-copying"))]
 mod send_sync_unsafe_cell;
-#[cfg(feature = "gc-null")]
+#[cfg(any(feature = "gc-null", feature = "gc-copying"))]
 pub use send_sync_unsafe_cell::SendSyncUnsafeCell;
 
 cfg_if::cfg_if! {
@@ -804,6 +804,20 @@ impl ComponentInstance {
         self.instances[idx]
     }
 
+    /// Returns the [`InstanceId`] of every core instance which has been
+    /// instantiated so far within this component instance.
+    pub fn instance_ids(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (RuntimeInstanceIndex, InstanceId)> + '_ {
+        self.instances.iter().map(|(idx, id)| (idx, *id))
+    }
+
+    /// Same as [`Self::instance`], but returns `None` if `idx` hasn't been
+    /// initialized yet.
+    pub fn get_instance(&self, idx: RuntimeInstanceIndex) -> Option<InstanceId> {
+        self.instances.get(idx).copied()
+    }
+
     fn instances_mut(self: Pin<&mut Self>) -> &mut PrimaryMap<RuntimeInstanceIndex, InstanceId> {
         // SAFETY: we've chosen the `Pin` guarantee of `Self` to not apply to
         // the map returned.
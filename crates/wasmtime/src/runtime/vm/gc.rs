@@ -24,6 +24,7 @@ use crate::prelude::*;
 use crate::runtime::vm::{GcHeapAllocationIndex, VMMemoryDefinition};
 use core::any::Any;
 use core::mem::MaybeUninit;
+use core::time::Duration;
 use core::{alloc::Layout, num::NonZeroU32};
 use wasmtime_environ::{GcArrayLayout, GcStructLayout, VMGcKind, VMSharedTypeIndex};
 
@@ -47,6 +48,35 @@ pub struct GcStore {
 
     /// The function-references table for this GC heap.
     pub func_ref_table: FuncRefTable,
+
+    /// Statistics about the collections performed in this GC heap.
+    pub collection_stats: GcCollectionStats,
+}
+
+/// Statistics about the collections performed in a `GcStore`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GcCollectionStats {
+    /// The number of collections performed.
+    pub collections: u64,
+
+    /// How long the most recent collection took.
+    pub last_pause: Duration,
+
+    /// How long the longest collection took.
+    pub max_pause: Duration,
+
+    /// How long all collections took in total.
+    pub total_pause: Duration,
+}
+
+impl GcCollectionStats {
+    /// Record a collection that took `pause` to complete.
+    pub fn record(&mut self, pause: Duration) {
+        self.collections += 1;
+        self.last_pause = pause;
+        self.max_pause = self.max_pause.max(pause);
+        self.total_pause = self.total_pause.saturating_add(pause);
+    }
 }
 
 impl GcStore {
@@ -59,6 +89,7 @@ impl GcStore {
             gc_heap,
             host_data_table,
             func_ref_table,
+            collection_stats: GcCollectionStats::default(),
         }
     }
 
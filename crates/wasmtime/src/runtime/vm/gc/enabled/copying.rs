@@ -0,0 +1,722 @@
+//! The copying collector.
+//!
+//! The copying collector bump allocates objects until it runs out of space, at
+//! which point it performs a Cheney-style copying collection: every object that
+//! is reachable from the GC roots is evacuated into a to-space, the GC roots
+//! and the GC references inside evacuated objects are updated to point at the
+//! objects' new locations, and then the to-space is compacted back into the
+//! start of the GC heap. Everything that was not evacuated is garbage,
+//! including cycles, and its space is reclaimed all at once by resetting the
+//! bump pointer to the end of the evacuated objects.
+//!
+//! When an object is evacuated, its old header is overwritten with a
+//! forwarding header: the header's reserved bits, which otherwise hold the
+//! object's (always non-zero) size, are zeroed and its type index is replaced
+//! with the object's new GC heap index. Any other GC reference to the same
+//! object that is encountered later in the collection is redirected to the new
+//! index, so that sharing is preserved.
+//!
+//! Every object is aligned to, and has a size that is a multiple of,
+//! `OBJECT_ALIGN`. This means that evacuating objects in a different order than
+//! they were allocated in never needs more space than they originally occupied,
+//! and that the evacuated objects always fit back into the GC heap.
+//!
+//! Like the null collector, the copying collector does not require any GC
+//! barriers.
+
+use super::*;
+use crate::hash_map::HashMap;
+use crate::{
+    Engine, EngineWeak,
+    prelude::*,
+    vm::{
+        ExternRefHostDataId, ExternRefHostDataTable, GarbageCollection, GcHeap, GcHeapObject,
+        GcProgress, GcRootsIter, GcRuntime, SendSyncUnsafeCell, TypedGcRef, VMGcHeader, VMGcRef,
+        VMMemoryDefinition,
+    },
+};
+use core::ops::Range;
+use core::ptr::NonNull;
+use core::{alloc::Layout, any::Any, mem, num::NonZeroU32};
+use wasmtime_environ::copying::{ARRAY_LENGTH_OFFSET, CopyingTypeLayouts, OBJECT_ALIGN};
+use wasmtime_environ::{
+    GcArrayLayout, GcLayout, GcStructLayout, GcTypeLayouts, VMGcKind, VMSharedTypeIndex,
+};
+
+#[expect(clippy::cast_possible_truncation, reason = "known to not overflow")]
+const GC_REF_ARRAY_ELEMS_OFFSET: u32 = ARRAY_LENGTH_OFFSET + (mem::size_of::<u32>() as u32);
+
+/// The copying collector.
+#[derive(Default)]
+pub struct CopyingCollector {
+    layouts: CopyingTypeLayouts,
+}
+
+unsafe impl GcRuntime for CopyingCollector {
+    fn layouts(&self) -> &dyn GcTypeLayouts {
+        &self.layouts
+    }
+
+    fn new_gc_heap(&self, engine: &Engine) -> Result<Box<dyn GcHeap>> {
+        let heap = CopyingHeap::new(engine)?;
+        Ok(Box::new(heap) as _)
+    }
+}
+
+/// How to trace a GC object.
+enum TraceInfo {
+    /// How to trace an array.
+    Array {
+        /// Whether this array type's elements are GC references, and need
+        /// tracing.
+        gc_ref_elems: bool,
+    },
+
+    /// How to trace a struct.
+    Struct {
+        /// The offsets of each GC reference field that needs tracing in
+        /// instances of this struct type.
+        gc_ref_offsets: Box<[u32]>,
+    },
+}
+
+/// A GC heap for the copying collector.
+#[repr(C)]
+struct CopyingHeap {
+    /// Bump-allocation finger indexing within `OBJECT_ALIGN..self.heap.len()`.
+    ///
+    /// NB: this is an `UnsafeCell` because it is written to by compiled Wasm
+    /// code.
+    next: SendSyncUnsafeCell<NonZeroU32>,
+
+    engine: EngineWeak,
+
+    /// For every type that we have traced in this heap, how do we trace it?
+    ///
+    /// Note that this is populated lazily during collection, rather than upon
+    /// allocation, because compiled Wasm code bump allocates objects inline
+    /// without calling into the runtime.
+    trace_infos: HashMap<VMSharedTypeIndex, TraceInfo>,
+
+    /// Every `externref` currently allocated in this heap.
+    ///
+    /// Used to find the `externref`s that did not survive a collection, so
+    /// that their host data can be deallocated.
+    externrefs: Vec<VMGcRef>,
+
+    /// Reusable storage for the to-space during collection.
+    to_space: Vec<u8>,
+
+    /// The number of active no-gc scopes at the current moment.
+    no_gc_count: usize,
+
+    /// The actual storage for the GC heap.
+    memory: Option<crate::vm::Memory>,
+}
+
+/// The common header for all arrays in the copying collector.
+#[repr(C)]
+struct VMCopyingArrayHeader {
+    header: VMGcHeader,
+    length: u32,
+}
+
+unsafe impl GcHeapObject for VMCopyingArrayHeader {
+    #[inline]
+    fn is(header: &VMGcHeader) -> bool {
+        header.kind() == VMGcKind::ArrayRef
+    }
+}
+
+impl VMCopyingArrayHeader {
+    fn typed_ref<'a>(
+        gc_heap: &CopyingHeap,
+        array: &'a VMArrayRef,
+    ) -> &'a TypedGcRef<VMCopyingArrayHeader> {
+        let gc_ref = array.as_gc_ref();
+        debug_assert!(gc_ref.is_typed::<VMCopyingArrayHeader>(gc_heap));
+        gc_ref.as_typed_unchecked()
+    }
+}
+
+/// The representation of an `externref` in the copying collector.
+#[repr(C)]
+struct VMCopyingExternRef {
+    header: VMGcHeader,
+    host_data: ExternRefHostDataId,
+}
+
+unsafe impl GcHeapObject for VMCopyingExternRef {
+    #[inline]
+    fn is(header: &VMGcHeader) -> bool {
+        header.kind() == VMGcKind::ExternRef
+    }
+}
+
+impl VMCopyingExternRef {
+    /// Convert a generic `externref` to a typed reference to our concrete
+    /// `externref` type.
+    fn typed_ref<'a>(
+        gc_heap: &CopyingHeap,
+        externref: &'a VMExternRef,
+    ) -> &'a TypedGcRef<VMCopyingExternRef> {
+        let gc_ref = externref.as_gc_ref();
+        debug_assert!(gc_ref.is_typed::<VMCopyingExternRef>(gc_heap));
+        gc_ref.as_typed_unchecked()
+    }
+}
+
+/// An object in from-space that has already been evacuated to to-space during
+/// the current collection.
+///
+/// This overlays the object's original `VMGcHeader`.
+#[repr(C)]
+struct VMCopyingForwardedObject {
+    /// The original object's `VMGcKind`, with all reserved bits zeroed.
+    kind: u32,
+
+    /// The GC heap index that the object was evacuated to.
+    forwarded_to: u32,
+}
+
+unsafe impl GcHeapObject for VMCopyingForwardedObject {
+    #[inline]
+    fn is(header: &VMGcHeader) -> bool {
+        header.reserved_u26() == 0
+    }
+}
+
+impl CopyingHeap {
+    /// Construct a new, default heap for the copying collector.
+    fn new(engine: &Engine) -> Result<Self> {
+        log::trace!("allocating new copying heap");
+        Ok(Self {
+            next: SendSyncUnsafeCell::new(NonZeroU32::new(u32::MAX).unwrap()),
+            engine: engine.weak(),
+            trace_infos: HashMap::with_capacity(1),
+            externrefs: vec![],
+            to_space: vec![],
+            no_gc_count: 0,
+            memory: None,
+        })
+    }
+
+    fn engine(&self) -> Engine {
+        self.engine.upgrade().unwrap()
+    }
+
+    /// Attempt to bump-allocate an object with the given layout and
+    /// header.
+    ///
+    /// Returns `Ok(Ok(r))` on success, `Ok(Err(bytes_needed))` when we don't
+    /// have enough heap space but growing the GC heap or collecting garbage
+    /// could make it allocatable, and `Err(_)` when the object is too large to
+    /// ever be allocated.
+    fn alloc(&mut self, mut header: VMGcHeader, layout: Layout) -> Result<Result<VMGcRef, u64>> {
+        debug_assert!(layout.size() >= core::mem::size_of::<VMGcHeader>());
+        debug_assert!(layout.align() >= core::mem::align_of::<VMGcHeader>());
+        debug_assert!(layout.align() <= usize::try_from(OBJECT_ALIGN).unwrap());
+
+        // Round the requested size up to our object granularity and make sure
+        // that it fits in the GC header's unused bits.
+        let size = match u32::try_from(layout.size())
+            .ok()
+            .and_then(|size| size.checked_next_multiple_of(OBJECT_ALIGN))
+            .filter(|size| VMGcKind::value_fits_in_unused_bits(*size))
+        {
+            Some(size) => size,
+            None => return Err(crate::Trap::AllocationTooLarge.into()),
+        };
+
+        // The bump pointer is always kept aligned to `OBJECT_ALIGN`, so we
+        // only need to check whether the allocation fits in the heap space we
+        // have left.
+        let next = *self.next.get_mut();
+        debug_assert_eq!(next.get() % OBJECT_ALIGN, 0);
+        let end_of_object = match next.get().checked_add(size) {
+            Some(end) => end,
+            None => return Err(crate::Trap::AllocationTooLarge.into()),
+        };
+        let len = self.memory.as_ref().unwrap().byte_size();
+        let len = u32::try_from(len).unwrap_or(u32::MAX);
+        if end_of_object > len {
+            return Ok(Err(u64::from(size)));
+        }
+
+        // Update the bump pointer, write the header, and return the GC ref.
+        *self.next.get_mut() = NonZeroU32::new(end_of_object).unwrap();
+
+        let gc_ref = VMGcRef::from_heap_index(next).unwrap();
+
+        debug_assert_eq!(header.reserved_u26(), 0);
+        header.set_reserved_u26(size);
+        *self.header_mut(&gc_ref) = header;
+
+        Ok(Ok(gc_ref))
+    }
+
+    /// Ensure that we have tracing information for the given type.
+    fn ensure_trace_info(&mut self, ty: VMSharedTypeIndex) {
+        if self.trace_infos.contains_key(&ty) {
+            return;
+        }
+
+        let engine = self.engine();
+        let gc_layout = engine
+            .signatures()
+            .layout(ty)
+            .unwrap_or_else(|| panic!("should have a GC layout for {ty:?}"));
+
+        let info = match gc_layout {
+            GcLayout::Array(l) => {
+                if l.elems_are_gc_refs {
+                    debug_assert_eq!(l.elem_offset(0), GC_REF_ARRAY_ELEMS_OFFSET);
+                }
+                TraceInfo::Array {
+                    gc_ref_elems: l.elems_are_gc_refs,
+                }
+            }
+            GcLayout::Struct(l) => TraceInfo::Struct {
+                gc_ref_offsets: l
+                    .fields
+                    .iter()
+                    .filter_map(|f| if f.is_gc_ref { Some(f.offset) } else { None })
+                    .collect(),
+            },
+        };
+
+        let old_entry = self.trace_infos.insert(ty, info);
+        debug_assert!(old_entry.is_none());
+    }
+
+    /// Perform a full collection: evacuate everything reachable from `roots`,
+    /// deallocate the host data of every dead `externref`, and then move the
+    /// evacuated objects back to the start of the heap.
+    fn collect(
+        &mut self,
+        roots: &mut GcRootsIter<'_>,
+        host_data_table: &mut ExternRefHostDataTable,
+    ) {
+        let mut to_space = mem::take(&mut self.to_space);
+        debug_assert!(to_space.is_empty());
+
+        // The to-space offset of each evacuated object, along with its
+        // original header and, for arrays, its length.
+        let mut evacuated = vec![];
+
+        for mut root in roots {
+            let gc_ref = root.get();
+            if gc_ref.is_i31() {
+                continue;
+            }
+            let new_ref = self.forward(&gc_ref, &mut to_space, &mut evacuated);
+            root.set(new_ref);
+        }
+
+        // Scan the evacuated objects in order, which evacuates the objects
+        // they reference in turn, until we reach a fixed point.
+        let mut scan = 0;
+        while let Some(&(offset, header, array_len)) = evacuated.get(scan) {
+            scan += 1;
+            self.scan(offset, header, array_len, &mut to_space, &mut evacuated);
+        }
+
+        // Any `externref` that was not evacuated is garbage; free its host
+        // data. This must happen before we overwrite from-space below.
+        let mut externrefs = mem::take(&mut self.externrefs);
+        externrefs.retain_mut(|externref| match self.forwarded(externref) {
+            Some(new_ref) => {
+                *externref = new_ref;
+                true
+            }
+            None => {
+                let typed_ref = externref.as_typed_unchecked::<VMCopyingExternRef>();
+                let host_data = self.index(typed_ref).host_data;
+                host_data_table.dealloc(host_data);
+                false
+            }
+        });
+        self.externrefs = externrefs;
+
+        // Move the evacuated objects back into the GC heap, zero the space
+        // that was freed, and reset the bump pointer.
+        let start = usize::try_from(OBJECT_ALIGN).unwrap();
+        let old_next = usize::try_from(self.next.get_mut().get()).unwrap();
+        let new_next = start + to_space.len();
+        assert!(new_next <= old_next);
+        let heap = self.heap_slice_mut();
+        heap[start..new_next].copy_from_slice(&to_space);
+        heap[new_next..old_next].fill(0);
+        *self.next.get_mut() = NonZeroU32::new(u32::try_from(new_next).unwrap()).unwrap();
+
+        log::trace!(
+            "copying collection evacuated {} objects ({} bytes), freed {} bytes",
+            evacuated.len(),
+            to_space.len(),
+            old_next - new_next,
+        );
+
+        to_space.clear();
+        self.to_space = to_space;
+    }
+
+    /// If the given from-space object has already been evacuated, get its new
+    /// GC reference.
+    fn forwarded(&self, gc_ref: &VMGcRef) -> Option<VMGcRef> {
+        debug_assert!(!gc_ref.is_i31());
+        if gc_ref.is_typed::<VMCopyingForwardedObject>(self) {
+            let forwarded = self.index::<VMCopyingForwardedObject>(gc_ref.as_typed_unchecked());
+            Some(VMGcRef::from_raw_u32(forwarded.forwarded_to).expect("non-null"))
+        } else {
+            None
+        }
+    }
+
+    /// Evacuate the given from-space object to to-space, if it hasn't been
+    /// already, and return its new GC reference.
+    fn forward(
+        &mut self,
+        gc_ref: &VMGcRef,
+        to_space: &mut Vec<u8>,
+        evacuated: &mut Vec<(usize, VMGcHeader, u32)>,
+    ) -> VMGcRef {
+        debug_assert!(!gc_ref.is_i31());
+        let index = gc_ref.as_heap_index().unwrap().get();
+        assert_eq!(
+            index % OBJECT_ALIGN,
+            0,
+            "misaligned GC reference: {gc_ref:?}"
+        );
+
+        if let Some(new_ref) = self.forwarded(gc_ref) {
+            return new_ref;
+        }
+
+        let header = *self.header(gc_ref);
+        let array_len = if header.kind() == VMGcKind::ArrayRef {
+            self.array_len(gc_ref.as_arrayref_unchecked())
+        } else {
+            0
+        };
+
+        let range = self.object_range(gc_ref);
+        assert_eq!(range.len() % usize::try_from(OBJECT_ALIGN).unwrap(), 0);
+        let offset = to_space.len();
+        to_space.extend_from_slice(&self.heap_slice()[range]);
+        evacuated.push((offset, header, array_len));
+
+        let new_index = u32::try_from(offset)
+            .ok()
+            .and_then(|offset| offset.checked_add(OBJECT_ALIGN))
+            .and_then(NonZeroU32::new)
+            .unwrap();
+        let new_ref = VMGcRef::from_heap_index(new_index).unwrap();
+
+        *self.index_mut::<VMCopyingForwardedObject>(gc_ref.as_typed_unchecked()) =
+            VMCopyingForwardedObject {
+                kind: header.kind().as_u32(),
+                forwarded_to: new_ref.as_raw_u32(),
+            };
+
+        new_ref
+    }
+
+    /// Update every outgoing GC reference of the evacuated object at the given
+    /// to-space offset, evacuating their referents as necessary.
+    fn scan(
+        &mut self,
+        offset: usize,
+        header: VMGcHeader,
+        array_len: u32,
+        to_space: &mut Vec<u8>,
+        evacuated: &mut Vec<(usize, VMGcHeader, u32)>,
+    ) {
+        let Some(ty) = header.ty() else {
+            debug_assert!(header.kind().matches(VMGcKind::ExternRef));
+            return;
+        };
+        self.ensure_trace_info(ty);
+
+        let object = offset..offset + usize::try_from(header.reserved_u26()).unwrap();
+
+        // Temporarily take the trace infos out of `self` so that we can borrow
+        // them while evacuating referents.
+        let trace_infos = mem::take(&mut self.trace_infos);
+        match &trace_infos[&ty] {
+            TraceInfo::Struct { gc_ref_offsets } => {
+                for field in gc_ref_offsets {
+                    self.scan_field(object.clone(), *field, to_space, evacuated);
+                }
+            }
+            TraceInfo::Array { gc_ref_elems } => {
+                if *gc_ref_elems {
+                    for i in 0..array_len {
+                        let field = GC_REF_ARRAY_ELEMS_OFFSET
+                            + i * u32::try_from(mem::size_of::<u32>()).unwrap();
+                        self.scan_field(object.clone(), field, to_space, evacuated);
+                    }
+                }
+            }
+        }
+        self.trace_infos = trace_infos;
+    }
+
+    /// Update the GC reference in the given field of the given evacuated
+    /// object, evacuating its referent as necessary.
+    fn scan_field(
+        &mut self,
+        object: Range<usize>,
+        field: u32,
+        to_space: &mut Vec<u8>,
+        evacuated: &mut Vec<(usize, VMGcHeader, u32)>,
+    ) {
+        let data: &VMGcObjectData = to_space[object.clone()].into();
+        let Some(gc_ref) = VMGcRef::from_raw_u32(data.read_u32(field)) else {
+            return;
+        };
+        if gc_ref.is_i31() {
+            return;
+        }
+        let new_ref = self.forward(&gc_ref, to_space, evacuated);
+        let data: &mut VMGcObjectData = (&mut to_space[object]).into();
+        data.write_u32(field, new_ref.as_raw_u32());
+    }
+}
+
+unsafe impl GcHeap for CopyingHeap {
+    fn is_attached(&self) -> bool {
+        self.memory.is_some()
+    }
+
+    fn attach(&mut self, memory: crate::vm::Memory) {
+        assert!(!self.is_attached());
+        self.memory = Some(memory);
+        self.next = SendSyncUnsafeCell::new(NonZeroU32::new(OBJECT_ALIGN).unwrap());
+    }
+
+    fn detach(&mut self) -> crate::vm::Memory {
+        assert!(self.is_attached());
+
+        let CopyingHeap {
+            next,
+            engine: _,
+            trace_infos: _,
+            externrefs,
+            to_space,
+            no_gc_count,
+            memory,
+        } = self;
+
+        externrefs.clear();
+        to_space.clear();
+        *no_gc_count = 0;
+        *next.get_mut() = NonZeroU32::new(u32::MAX).unwrap();
+
+        memory.take().unwrap()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self as _
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self as _
+    }
+
+    fn enter_no_gc_scope(&mut self) {
+        self.no_gc_count += 1;
+    }
+
+    fn exit_no_gc_scope(&mut self) {
+        self.no_gc_count -= 1;
+    }
+
+    fn take_memory(&mut self) -> crate::vm::Memory {
+        debug_assert!(self.is_attached());
+        self.memory.take().unwrap()
+    }
+
+    unsafe fn replace_memory(&mut self, memory: crate::vm::Memory, _delta_bytes_grown: u64) {
+        debug_assert!(self.memory.is_none());
+        self.memory = Some(memory);
+    }
+
+    fn vmmemory(&self) -> VMMemoryDefinition {
+        debug_assert!(self.is_attached());
+        self.memory.as_ref().unwrap().vmmemory()
+    }
+
+    fn clone_gc_ref(&mut self, gc_ref: &VMGcRef) -> VMGcRef {
+        gc_ref.unchecked_copy()
+    }
+
+    fn write_gc_ref(
+        &mut self,
+        _host_data_table: &mut ExternRefHostDataTable,
+        destination: &mut Option<VMGcRef>,
+        source: Option<&VMGcRef>,
+    ) {
+        *destination = source.map(|s| s.unchecked_copy());
+    }
+
+    fn expose_gc_ref_to_wasm(&mut self, _gc_ref: VMGcRef) {
+        // Don't need to do anything special here.
+    }
+
+    fn alloc_externref(
+        &mut self,
+        host_data: ExternRefHostDataId,
+    ) -> Result<Result<VMExternRef, u64>> {
+        let gc_ref =
+            match self.alloc(VMGcHeader::externref(), Layout::new::<VMCopyingExternRef>())? {
+                Ok(r) => r,
+                Err(bytes_needed) => return Ok(Err(bytes_needed)),
+            };
+        self.index_mut::<VMCopyingExternRef>(gc_ref.as_typed_unchecked())
+            .host_data = host_data;
+        self.externrefs.push(gc_ref.unchecked_copy());
+        Ok(Ok(gc_ref.into_externref_unchecked()))
+    }
+
+    fn externref_host_data(&self, externref: &VMExternRef) -> ExternRefHostDataId {
+        let typed_ref = VMCopyingExternRef::typed_ref(self, externref);
+        self.index(typed_ref).host_data
+    }
+
+    fn object_size(&self, gc_ref: &VMGcRef) -> usize {
+        let size = self.header(gc_ref).reserved_u26();
+        usize::try_from(size).unwrap()
+    }
+
+    fn header(&self, gc_ref: &VMGcRef) -> &VMGcHeader {
+        self.index(gc_ref.as_typed_unchecked())
+    }
+
+    fn header_mut(&mut self, gc_ref: &VMGcRef) -> &mut VMGcHeader {
+        self.index_mut(gc_ref.as_typed_unchecked())
+    }
+
+    fn alloc_raw(&mut self, header: VMGcHeader, layout: Layout) -> Result<Result<VMGcRef, u64>> {
+        self.alloc(header, layout)
+    }
+
+    fn alloc_uninit_struct_or_exn(
+        &mut self,
+        ty: VMSharedTypeIndex,
+        layout: &GcStructLayout,
+    ) -> Result<Result<VMGcRef, u64>> {
+        let kind = if layout.is_exception {
+            VMGcKind::ExnRef
+        } else {
+            VMGcKind::StructRef
+        };
+        self.alloc(VMGcHeader::from_kind_and_index(kind, ty), layout.layout())
+    }
+
+    fn dealloc_uninit_struct_or_exn(&mut self, _struct_ref: VMGcRef) {}
+
+    fn alloc_uninit_array(
+        &mut self,
+        ty: VMSharedTypeIndex,
+        length: u32,
+        layout: &GcArrayLayout,
+    ) -> Result<Result<VMArrayRef, u64>> {
+        self.alloc(
+            VMGcHeader::from_kind_and_index(VMGcKind::ArrayRef, ty),
+            layout.layout(length),
+        )
+        .map(|r| {
+            r.map(|r| {
+                self.index_mut::<VMCopyingArrayHeader>(r.as_typed_unchecked())
+                    .length = length;
+                r.into_arrayref_unchecked()
+            })
+        })
+    }
+
+    fn dealloc_uninit_array(&mut self, _array_ref: VMArrayRef) {}
+
+    fn array_len(&self, arrayref: &VMArrayRef) -> u32 {
+        let arrayref = VMCopyingArrayHeader::typed_ref(self, arrayref);
+        self.index(arrayref).length
+    }
+
+    fn gc<'a>(
+        &'a mut self,
+        roots: GcRootsIter<'a>,
+        host_data_table: &'a mut ExternRefHostDataTable,
+    ) -> Box<dyn GarbageCollection<'a> + 'a> {
+        assert_eq!(self.no_gc_count, 0, "Cannot GC inside a no-GC scope!");
+        Box::new(CopyingCollection {
+            roots,
+            host_data_table,
+            heap: self,
+            done: false,
+        })
+    }
+
+    unsafe fn vmctx_gc_heap_data(&self) -> NonNull<u8> {
+        let ptr_to_next: *mut NonZeroU32 = unsafe { self.next.get() };
+        NonNull::new(ptr_to_next).unwrap().cast()
+    }
+}
+
+struct CopyingCollection<'a> {
+    roots: GcRootsIter<'a>,
+    host_data_table: &'a mut ExternRefHostDataTable,
+    heap: &'a mut CopyingHeap,
+    done: bool,
+}
+
+impl<'a> GarbageCollection<'a> for CopyingCollection<'a> {
+    fn collect_increment(&mut self) -> GcProgress {
+        if !self.done {
+            log::trace!("Begin copying collection");
+            self.heap.collect(&mut self.roots, self.host_data_table);
+            log::trace!("End copying collection");
+            self.done = true;
+        }
+        GcProgress::Complete
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vm_gc_copying_header_size_align() {
+        assert_eq!(
+            (wasmtime_environ::copying::HEADER_SIZE as usize),
+            core::mem::size_of::<VMGcHeader>()
+        );
+        assert_eq!(
+            (wasmtime_environ::copying::HEADER_ALIGN as usize),
+            core::mem::align_of::<VMGcHeader>()
+        );
+    }
+
+    #[test]
+    fn vm_copying_array_header_length_offset() {
+        assert_eq!(
+            wasmtime_environ::copying::ARRAY_LENGTH_OFFSET,
+            u32::try_from(core::mem::offset_of!(VMCopyingArrayHeader, length)).unwrap(),
+        );
+    }
+
+    #[test]
+    fn vm_copying_forwarded_object_overlays_header() {
+        assert_eq!(
+            core::mem::size_of::<VMCopyingForwardedObject>(),
+            core::mem::size_of::<VMGcHeader>()
+        );
+        assert_eq!(
+            u32::try_from(core::mem::offset_of!(
+                VMCopyingForwardedObject,
+                forwarded_to
+            ))
+            .unwrap(),
+            wasmtime_environ::VM_GC_HEADER_TYPE_INDEX_OFFSET,
+        );
+    }
+}
@@ -1060,6 +1060,7 @@ mod tests {
             num_defined_globals: 0,
             num_defined_tags: 0,
             num_escaped_funcs: 0,
+            num_tier_up_counters: 0,
         });
 
         assert_eq!(
@@ -30,16 +30,18 @@ use core::{mem, ptr};
 #[cfg(feature = "gc")]
 use wasmtime_environ::ModuleInternedTypeIndex;
 use wasmtime_environ::{
-    DataIndex, DefinedGlobalIndex, DefinedMemoryIndex, DefinedTableIndex, DefinedTagIndex,
-    ElemIndex, EntityIndex, EntityRef, EntitySet, FuncIndex, GlobalIndex, HostPtr, MemoryIndex,
-    Module, PrimaryMap, PtrSize, TableIndex, TableInitialValue, TableSegmentElements, TagIndex,
-    Trap, VMCONTEXT_MAGIC, VMOffsets, VMSharedTypeIndex, packed_option::ReservedValue,
+    DataIndex, DefinedFuncIndex, DefinedGlobalIndex, DefinedMemoryIndex, DefinedTableIndex,
+    DefinedTagIndex, ElemIndex, EntityIndex, EntityRef, EntitySet, FuncIndex, GlobalIndex, HostPtr,
+    MemoryIndex, Module, PrimaryMap, PtrSize, TableIndex, TableInitialValue, TableSegmentElements,
+    TagIndex, Trap, VMCONTEXT_MAGIC, VMOffsets, VMSharedTypeIndex, packed_option::ReservedValue,
 };
-#[cfg(feature = "wmemcheck")]
-use wasmtime_wmemcheck::Wmemcheck;
 
 mod allocator;
 pub use allocator::*;
+#[cfg(feature = "wmemcheck")]
+mod wmemcheck;
+#[cfg(feature = "wmemcheck")]
+pub use wmemcheck::WmemcheckState;
 
 /// A type that roughly corresponds to a WebAssembly instance, but is also used
 /// for host-defined objects.
@@ -124,10 +126,10 @@ pub struct Instance {
     /// If the index is present in the set, the segment has been dropped.
     dropped_data: EntitySet<DataIndex>,
 
-    // TODO: add support for multiple memories; `wmemcheck_state` corresponds to
-    // memory 0.
+    /// Memory checker state for the memory managed by the guest's allocator,
+    /// see `Config::wmemcheck_allocator`.
     #[cfg(feature = "wmemcheck")]
-    pub(crate) wmemcheck_state: Option<Wmemcheck>,
+    pub(crate) wmemcheck_state: Option<WmemcheckState>,
 
     /// Self-pointer back to `Store<T>` and its functions. Not present for
     /// the brief time that `Store<T>` is itself being created. Also not
@@ -174,15 +176,13 @@ impl Instance {
             dropped_data,
             #[cfg(feature = "wmemcheck")]
             wmemcheck_state: {
-                if req.store.engine().config().wmemcheck {
+                let config = req.store.engine().config();
+                if config.wmemcheck {
                     let size = memory_tys
-                        .iter()
-                        .next()
-                        .map(|memory| memory.1.limits.min)
-                        .unwrap_or(0)
-                        * 64
-                        * 1024;
-                    Some(Wmemcheck::new(size.try_into().unwrap()))
+                        .get(MemoryIndex::from_u32(config.wmemcheck_allocator.memory))
+                        .map(|memory| memory.minimum_byte_size().unwrap())
+                        .unwrap_or(0);
+                    Some(WmemcheckState::new(size.try_into().unwrap()))
                 } else {
                     None
                 }
@@ -346,7 +346,7 @@ impl Instance {
         self.runtime_info.env_module()
     }
 
-    #[cfg(feature = "gc")]
+    #[cfg(any(feature = "gc", feature = "winch"))]
     pub(crate) fn runtime_module(&self) -> Option<&crate::Module> {
         match &self.runtime_info {
             ModuleRuntimeInfo::Module(m) => Some(m),
@@ -830,6 +830,14 @@ impl Instance {
             self.vmctx_plus_offset_raw::<VMFuncRef>(self.offsets().vmctx_func_ref(func.func_ref))
         };
 
+        // With tiered compilation the funcrefs of defined functions are
+        // constructed up front in `initialize_tier_up` and may have been
+        // patched since then, so they must not be reconstructed here.
+        if self.offsets().num_tier_up_counters > 0 && !self.env_module().is_imported_function(index)
+        {
+            return Some(func_ref);
+        }
+
         // SAFETY: the `func_ref` ptr should be valid as it's within our
         // `VMContext` area.
         unsafe {
@@ -839,6 +847,92 @@ impl Instance {
         Some(func_ref)
     }
 
+    /// Eagerly constructs the funcrefs of all defined functions and
+    /// initializes their tier-up counters, for modules using tiered
+    /// compilation.
+    fn initialize_tier_up(mut self: Pin<&mut Self>, store: &StoreOpaque) {
+        #[cfg(feature = "winch")]
+        let counter = match self.runtime_module() {
+            Some(module) if module.has_tier_up() => store.engine().config().tier_up_threshold,
+            _ => u32::MAX,
+        };
+        #[cfg(not(feature = "winch"))]
+        let counter = {
+            let _ = store;
+            u32::MAX
+        };
+
+        let module = self.env_module().clone();
+        for i in 0..module.num_defined_funcs() {
+            let def_index = DefinedFuncIndex::new(i);
+            let index = module.func_index(def_index);
+            let func = &module.functions[index];
+            let sig = func.signature.unwrap_engine_type_index();
+
+            // SAFETY: the offset calculated here should be correct with
+            // `self.offsets`, and the `func_ref` ptr is valid as it's within
+            // our `VMContext` area.
+            unsafe {
+                let func_ref = self.vmctx_plus_offset_raw::<VMFuncRef>(
+                    self.offsets().vmctx_func_ref(func.func_ref),
+                );
+                self.as_mut()
+                    .construct_func_ref(index, sig, func_ref.as_ptr());
+            }
+            self.as_mut().set_tier_up_counter(def_index, counter);
+        }
+    }
+
+    /// Sets the tier-up counter of the defined function `index`.
+    pub(crate) fn set_tier_up_counter(self: Pin<&mut Self>, index: DefinedFuncIndex, value: u32) {
+        // SAFETY: the offset calculated here should be correct with
+        // `self.offsets`, and the counter is a `u32` within our `VMContext`
+        // area.
+        unsafe {
+            self.vmctx_plus_offset_raw::<u32>(self.offsets().vmctx_tier_up_counter(index))
+                .write(value);
+        }
+    }
+
+    /// Redirects calls to the defined function `index` through its funcref to
+    /// the code for the same function within `optimized`.
+    ///
+    /// `optimized` must be a recompilation of this instance's module with the
+    /// same calling convention. If its `VMContext` layout differs the function
+    /// instead keeps running its baseline code.
+    #[cfg(feature = "winch")]
+    pub(crate) fn tier_up_func(
+        mut self: Pin<&mut Self>,
+        index: DefinedFuncIndex,
+        optimized: &crate::Module,
+    ) {
+        // The optimized code accesses this instance's `VMContext`, so it must
+        // agree on where everything lives within it.
+        if self.offsets() != optimized.offsets() {
+            log::warn!("not tiering up function: optimized module has a different vmctx layout");
+            self.as_mut().set_tier_up_counter(index, u32::MAX);
+            return;
+        }
+        let optimized = ModuleRuntimeInfo::Module(optimized.clone());
+        let func_ref = self.env_module().functions[self.env_module().func_index(index)].func_ref;
+
+        // SAFETY: the offset calculated here should be correct with
+        // `self.offsets`, and the `func_ref` ptr is valid as it's within our
+        // `VMContext` area. It was initialized in `initialize_tier_up`.
+        unsafe {
+            let func_ref = self
+                .vmctx_plus_offset_raw::<VMFuncRef>(self.offsets().vmctx_func_ref(func_ref))
+                .as_ptr();
+            (*func_ref).array_call = optimized
+                .array_to_wasm_trampoline(index)
+                .expect("should have array-to-Wasm trampoline for escaping function")
+                .into();
+            (*func_ref).wasm_call = Some(optimized.function(index).into());
+        }
+
+        self.as_mut().set_tier_up_counter(index, u32::MAX);
+    }
+
     /// Get the passive elements segment at the given index.
     ///
     /// Returns an empty segment if the index is out of bounds or if the segment
@@ -1076,6 +1170,24 @@ impl Instance {
         Ok(())
     }
 
+    /// Returns whether `elem_index` is a passive element segment which hasn't
+    /// been dropped yet.
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn has_passive_element(&self, elem_index: ElemIndex) -> bool {
+        self.env_module()
+            .passive_elements_map
+            .contains_key(&elem_index)
+            && !self.dropped_elements.contains(elem_index)
+    }
+
+    /// Returns whether `data_index` is a passive data segment which hasn't
+    /// been dropped yet.
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn has_passive_data(&self, data_index: DataIndex) -> bool {
+        self.env_module().passive_data_map.contains_key(&data_index)
+            && !self.dropped_data.contains(data_index)
+    }
+
     /// Get the internal storage range of a particular Wasm data segment.
     pub(crate) fn wasm_data_range(&self, index: DataIndex) -> Range<u32> {
         match self.env_module().passive_data_map.get(&index) {
@@ -1339,7 +1451,12 @@ impl Instance {
         // N.B.: there is no need to initialize the funcrefs array because we
         // eagerly construct each element in it whenever asked for a reference
         // to that element. In other words, there is no state needed to track
-        // the lazy-init, so we don't need to initialize any state now.
+        // the lazy-init, so we don't need to initialize any state now. The
+        // exception is tiered compilation, where funcrefs are patched at
+        // runtime and hence are initialized once here.
+        if offsets.num_tier_up_counters > 0 {
+            self.as_mut().initialize_tier_up(store);
+        }
 
         // Initialize the defined tables
         //
@@ -1543,7 +1660,12 @@ impl Instance {
     }
 
     #[cfg(feature = "wmemcheck")]
-    pub(super) fn wmemcheck_state_mut(self: Pin<&mut Self>) -> &mut Option<Wmemcheck> {
+    pub(crate) fn wmemcheck_state(&self) -> Option<&WmemcheckState> {
+        self.wmemcheck_state.as_ref()
+    }
+
+    #[cfg(feature = "wmemcheck")]
+    pub(super) fn wmemcheck_state_mut(self: Pin<&mut Self>) -> &mut Option<WmemcheckState> {
         // SAFETY: see `store_mut` above.
         unsafe { &mut self.get_unchecked_mut().wmemcheck_state }
     }
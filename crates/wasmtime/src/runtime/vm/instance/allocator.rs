@@ -774,12 +774,14 @@ async fn initialize_globals(
         let mut instance = store.instance_mut(context.instance);
 
         #[cfg(feature = "wmemcheck")]
-        if index.as_u32() == 0
-            && module.globals[index].wasm_ty == wasmtime_environ::WasmValType::I32
-        {
-            if let Some(wmemcheck) = instance.as_mut().wmemcheck_state_mut() {
-                let size = usize::try_from(val.unwrap_i32()).unwrap();
-                wmemcheck.set_stack_size(size);
+        if index.as_u32() == 0 {
+            let size = match module.globals[index].wasm_ty {
+                wasmtime_environ::WasmValType::I32 => Some(i64::from(val.unwrap_i32())),
+                wasmtime_environ::WasmValType::I64 => Some(val.unwrap_i64()),
+                _ => None,
+            };
+            if let (Some(size), Some(wmemcheck)) = (size, instance.as_mut().wmemcheck_state_mut()) {
+                wmemcheck.set_stack_size(usize::try_from(size).unwrap());
             }
         }
 
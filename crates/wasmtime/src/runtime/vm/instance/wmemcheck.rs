@@ -0,0 +1,57 @@
+//! Per-instance state of the wmemcheck memory checker.
+
+use crate::hash_map::HashMap;
+use crate::runtime::vm::Backtrace;
+use core::ops::{Deref, DerefMut};
+use wasmtime_wmemcheck::Wmemcheck;
+
+/// The wmemcheck checker for an instance's allocator memory, along with where
+/// each of its live allocations was made.
+pub struct WmemcheckState {
+    checker: Wmemcheck,
+    alloc_sites: HashMap<usize, Backtrace>,
+}
+
+impl WmemcheckState {
+    pub fn new(mem_size: usize) -> WmemcheckState {
+        WmemcheckState {
+            checker: Wmemcheck::new(mem_size),
+            alloc_sites: HashMap::new(),
+        }
+    }
+
+    /// Records that the allocation at `addr` was made at `site`.
+    pub fn record_alloc_site(&mut self, addr: usize, site: Backtrace) {
+        if addr != 0 {
+            self.alloc_sites.insert(addr, site);
+        }
+    }
+
+    /// Forgets the allocation site of `addr`, which was freed.
+    pub fn remove_alloc_site(&mut self, addr: usize) {
+        self.alloc_sites.remove(&addr);
+    }
+
+    /// Returns the address, length and allocation site of every allocation
+    /// which hasn't been freed, sorted by address.
+    pub fn leaks(&self) -> impl Iterator<Item = (usize, usize, Option<&Backtrace>)> + '_ {
+        self.checker
+            .allocations()
+            .into_iter()
+            .map(|(addr, len)| (addr, len, self.alloc_sites.get(&addr)))
+    }
+}
+
+impl Deref for WmemcheckState {
+    type Target = Wmemcheck;
+
+    fn deref(&self) -> &Wmemcheck {
+        &self.checker
+    }
+}
+
+impl DerefMut for WmemcheckState {
+    fn deref_mut(&mut self) -> &mut Wmemcheck {
+        &mut self.checker
+    }
+}
@@ -373,6 +373,7 @@ impl InterpreterRef<'_> {
         macro_rules! core {
             (
                 $(
+                    $( #[allow($($lint:tt)*)] )?
                     $( #[cfg($attr:meta)] )?
                     $name:ident($($pname:ident: $param:ident ),* ) $(-> $result:ident)?;
                 )*
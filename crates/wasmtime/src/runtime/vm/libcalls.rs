@@ -60,6 +60,8 @@ use crate::prelude::*;
 use crate::runtime::store::{InstanceId, StoreInstanceId, StoreOpaque};
 #[cfg(feature = "gc")]
 use crate::runtime::vm::VMGcRef;
+#[cfg(feature = "wmemcheck")]
+use crate::runtime::vm::WmemcheckState;
 use crate::runtime::vm::table::TableElementType;
 use crate::runtime::vm::vmcontext::VMFuncRef;
 use crate::runtime::vm::{
@@ -70,8 +72,8 @@ use core::ptr::NonNull;
 #[cfg(feature = "threads")]
 use core::time::Duration;
 use wasmtime_environ::{
-    DataIndex, DefinedMemoryIndex, DefinedTableIndex, ElemIndex, FuncIndex, MemoryIndex,
-    TableIndex, Trap,
+    DataIndex, DefinedFuncIndex, DefinedMemoryIndex, DefinedTableIndex, ElemIndex, FuncIndex,
+    MemoryIndex, TableIndex, Trap,
 };
 #[cfg(feature = "wmemcheck")]
 use wasmtime_wmemcheck::AccessError::{
@@ -102,6 +104,7 @@ pub mod raw {
     macro_rules! libcall {
         (
             $(
+                $( #[allow($($lint:tt)*)] )?
                 $( #[cfg($attr:meta)] )?
                 $name:ident( vmctx: vmctx $(, $pname:ident: $param:ident )* ) $(-> $result:ident)?;
             )*
@@ -646,7 +649,7 @@ fn grow_gc_heap(store: &mut dyn VMStore, _instance: InstanceId, bytes_needed: u6
 /// Allocate a raw, unininitialized GC object for Wasm code.
 ///
 /// The Wasm code is responsible for initializing the object.
-#[cfg(feature = "gc-drc")]
+#[cfg(any(feature = "gc-drc", feature = "gc-copying"))]
 fn gc_alloc_raw(
     store: &mut dyn VMStore,
     instance: InstanceId,
@@ -1250,6 +1253,42 @@ fn out_of_gas(store: &mut dyn VMStore, _instance: InstanceId) -> Result<()> {
     })?
 }
 
+// Hook for when the tier-up counter of a function reaches zero.
+fn tier_up(store: &mut dyn VMStore, instance: InstanceId, func: u32) {
+    let func = DefinedFuncIndex::from_u32(func);
+
+    #[cfg(feature = "winch")]
+    {
+        use crate::module::TierUpStatus;
+
+        let status = match store.instance(instance).runtime_module() {
+            Some(module) => module.tier_up_status(),
+            None => TierUpStatus::Unavailable,
+        };
+        let counter = match status {
+            TierUpStatus::Ready(optimized) => {
+                // Register the optimized module so that traps, backtraces,
+                // etc, work with its code.
+                store.modules_mut().register_module(&optimized);
+                store.instance_mut(instance).tier_up_func(func, &optimized);
+                return;
+            }
+            // Check again after running the function `tier_up_threshold` more
+            // times.
+            TierUpStatus::Pending => store.engine().config().tier_up_threshold,
+            TierUpStatus::Unavailable => u32::MAX,
+        };
+        store
+            .instance_mut(instance)
+            .set_tier_up_counter(func, counter);
+    }
+
+    #[cfg(not(feature = "winch"))]
+    store
+        .instance_mut(instance)
+        .set_tier_up_counter(func, u32::MAX);
+}
+
 // Hook for when an instance observes that the epoch has changed.
 #[cfg(target_has_atomic = "64")]
 fn new_epoch(store: &mut dyn VMStore, _instance: InstanceId) -> Result<NextEpoch> {
@@ -1305,15 +1344,46 @@ unsafe impl HostResultHasUnwindSentinel for NextEpoch {
     }
 }
 
+// Converts an address or length from wasm to the host's `usize`; values which
+// don't fit are out of bounds regardless.
+#[cfg(feature = "wmemcheck")]
+fn wmemcheck_usize(value: u64) -> usize {
+    usize::try_from(value).unwrap_or(usize::MAX)
+}
+
+// Finishes a call to one of the guest's allocator functions. If it was the
+// outermost allocator call then `f` records its effect in wmemcheck_state and
+// returns the address it allocated, if any, whose allocation site is then
+// recorded for leak reports.
+#[cfg(feature = "wmemcheck")]
+fn allocator_exit(
+    store: &mut dyn VMStore,
+    instance: InstanceId,
+    f: impl FnOnce(&mut WmemcheckState) -> Result<Option<usize>>,
+) -> Result<()> {
+    let Some(wmemcheck_state) = store.instance_mut(instance).wmemcheck_state_mut() else {
+        return Ok(());
+    };
+    if !wmemcheck_state.allocator_exit() {
+        return Ok(());
+    }
+    let addr = f(wmemcheck_state)?;
+    if let Some(addr) = addr.filter(|_| store.engine().config().wasm_backtrace) {
+        let site = crate::runtime::vm::Backtrace::new(store.store_opaque());
+        if let Some(wmemcheck_state) = store.instance_mut(instance).wmemcheck_state_mut() {
+            wmemcheck_state.record_alloc_site(addr, site);
+        }
+    }
+    Ok(())
+}
+
 // Hook for validating malloc using wmemcheck_state.
 #[cfg(feature = "wmemcheck")]
-fn check_malloc(store: &mut dyn VMStore, instance: InstanceId, addr: u32, len: u32) -> Result<()> {
-    let instance = store.instance_mut(instance);
-    if let Some(wmemcheck_state) = instance.wmemcheck_state_mut() {
-        let result = wmemcheck_state.malloc(addr as usize, len as usize);
-        wmemcheck_state.memcheck_on();
-        match result {
-            Ok(()) => {}
+fn check_malloc(store: &mut dyn VMStore, instance: InstanceId, addr: u64, len: u64) -> Result<()> {
+    allocator_exit(store, instance, |wmemcheck_state| {
+        let addr = wmemcheck_usize(addr);
+        match wmemcheck_state.malloc(addr, wmemcheck_usize(len)) {
+            Ok(()) => Ok(Some(addr)),
             Err(DoubleMalloc { addr, len }) => {
                 bail!("Double malloc at addr {:#x} of size {}", addr, len)
             }
@@ -1324,19 +1394,80 @@ fn check_malloc(store: &mut dyn VMStore, instance: InstanceId, addr: u32, len: u
                 panic!("unreachable")
             }
         }
-    }
-    Ok(())
+    })
+}
+
+// Hook for validating calloc using wmemcheck_state.
+#[cfg(feature = "wmemcheck")]
+fn check_calloc(
+    store: &mut dyn VMStore,
+    instance: InstanceId,
+    addr: u64,
+    count: u64,
+    size: u64,
+) -> Result<()> {
+    allocator_exit(store, instance, |wmemcheck_state| {
+        let addr = wmemcheck_usize(addr);
+        match wmemcheck_state.calloc(addr, wmemcheck_usize(count.saturating_mul(size))) {
+            Ok(()) => Ok(Some(addr)),
+            Err(DoubleMalloc { addr, len }) => {
+                bail!("Double calloc at addr {:#x} of size {}", addr, len)
+            }
+            Err(OutOfBounds { addr, len }) => {
+                bail!("Calloc out of bounds at addr {:#x} of size {}", addr, len);
+            }
+            _ => {
+                panic!("unreachable")
+            }
+        }
+    })
+}
+
+// Hook for validating realloc using wmemcheck_state.
+#[cfg(feature = "wmemcheck")]
+fn check_realloc(
+    store: &mut dyn VMStore,
+    instance: InstanceId,
+    old_addr: u64,
+    new_addr: u64,
+    len: u64,
+) -> Result<()> {
+    allocator_exit(store, instance, |wmemcheck_state| {
+        let old_addr = wmemcheck_usize(old_addr);
+        let new_addr = wmemcheck_usize(new_addr);
+        match wmemcheck_state.realloc(old_addr, new_addr, wmemcheck_usize(len)) {
+            Ok(()) => {
+                if new_addr != 0 || len == 0 {
+                    wmemcheck_state.remove_alloc_site(old_addr);
+                }
+                Ok(Some(new_addr))
+            }
+            Err(InvalidFree { addr }) => {
+                bail!("Invalid realloc at addr {:#x}", addr)
+            }
+            Err(DoubleMalloc { addr, len }) => {
+                bail!("Double malloc at addr {:#x} of size {}", addr, len)
+            }
+            Err(OutOfBounds { addr, len }) => {
+                bail!("Realloc out of bounds at addr {:#x} of size {}", addr, len);
+            }
+            _ => {
+                panic!("unreachable")
+            }
+        }
+    })
 }
 
 // Hook for validating free using wmemcheck_state.
 #[cfg(feature = "wmemcheck")]
-fn check_free(store: &mut dyn VMStore, instance: InstanceId, addr: u32) -> Result<()> {
-    let instance = store.instance_mut(instance);
-    if let Some(wmemcheck_state) = instance.wmemcheck_state_mut() {
-        let result = wmemcheck_state.free(addr as usize);
-        wmemcheck_state.memcheck_on();
-        match result {
-            Ok(()) => {}
+fn check_free(store: &mut dyn VMStore, instance: InstanceId, addr: u64) -> Result<()> {
+    allocator_exit(store, instance, |wmemcheck_state| {
+        let addr = wmemcheck_usize(addr);
+        match wmemcheck_state.free(addr) {
+            Ok(()) => {
+                wmemcheck_state.remove_alloc_site(addr);
+                Ok(None)
+            }
             Err(InvalidFree { addr }) => {
                 bail!("Invalid free at addr {:#x}", addr)
             }
@@ -1344,8 +1475,7 @@ fn check_free(store: &mut dyn VMStore, instance: InstanceId, addr: u32) -> Resul
                 panic!("unreachable")
             }
         }
-    }
-    Ok(())
+    })
 }
 
 // Hook for validating load using wmemcheck_state.
@@ -1354,12 +1484,13 @@ fn check_load(
     store: &mut dyn VMStore,
     instance: InstanceId,
     num_bytes: u32,
-    addr: u32,
-    offset: u32,
+    addr: u64,
+    offset: u64,
 ) -> Result<()> {
     let instance = store.instance_mut(instance);
     if let Some(wmemcheck_state) = instance.wmemcheck_state_mut() {
-        let result = wmemcheck_state.read(addr as usize + offset as usize, num_bytes as usize);
+        let addr = wmemcheck_usize(addr.saturating_add(offset));
+        let result = wmemcheck_state.read(addr, num_bytes as usize);
         match result {
             Ok(()) => {}
             Err(InvalidRead { addr, len }) => {
@@ -1382,12 +1513,13 @@ fn check_store(
     store: &mut dyn VMStore,
     instance: InstanceId,
     num_bytes: u32,
-    addr: u32,
-    offset: u32,
+    addr: u64,
+    offset: u64,
 ) -> Result<()> {
     let instance = store.instance_mut(instance);
     if let Some(wmemcheck_state) = instance.wmemcheck_state_mut() {
-        let result = wmemcheck_state.write(addr as usize + offset as usize, num_bytes as usize);
+        let addr = wmemcheck_usize(addr.saturating_add(offset));
+        let result = wmemcheck_state.write(addr, num_bytes as usize);
         match result {
             Ok(()) => {}
             Err(InvalidWrite { addr, len }) => {
@@ -1404,27 +1536,19 @@ fn check_store(
     Ok(())
 }
 
-// Hook for turning wmemcheck load/store validation off when entering a malloc function.
-#[cfg(feature = "wmemcheck")]
-fn malloc_start(store: &mut dyn VMStore, instance: InstanceId) {
-    let instance = store.instance_mut(instance);
-    if let Some(wmemcheck_state) = instance.wmemcheck_state_mut() {
-        wmemcheck_state.memcheck_off();
-    }
-}
-
-// Hook for turning wmemcheck load/store validation off when entering a free function.
+// Hook for turning wmemcheck load/store validation off when entering one of
+// the allocator functions.
 #[cfg(feature = "wmemcheck")]
-fn free_start(store: &mut dyn VMStore, instance: InstanceId) {
+fn allocator_start(store: &mut dyn VMStore, instance: InstanceId) {
     let instance = store.instance_mut(instance);
     if let Some(wmemcheck_state) = instance.wmemcheck_state_mut() {
-        wmemcheck_state.memcheck_off();
+        wmemcheck_state.allocator_enter();
     }
 }
 
 // Hook for tracking wasm stack updates using wmemcheck_state.
 #[cfg(feature = "wmemcheck")]
-fn update_stack_pointer(_store: &mut dyn VMStore, _instance: InstanceId, _value: u32) {
+fn update_stack_pointer(_store: &mut dyn VMStore, _instance: InstanceId, _value: u64) {
     // TODO: stack-tracing has yet to be finalized. All memory below
     // the address of the top of the stack is marked as valid for
     // loads and stores.
@@ -1435,11 +1559,12 @@ fn update_stack_pointer(_store: &mut dyn VMStore, _instance: InstanceId, _value:
 
 // Hook updating wmemcheck_state memory state vector every time memory.grow is called.
 #[cfg(feature = "wmemcheck")]
-fn update_mem_size(store: &mut dyn VMStore, instance: InstanceId, num_pages: u32) {
-    let instance = store.instance_mut(instance);
-    if let Some(wmemcheck_state) = instance.wmemcheck_state_mut() {
-        const KIB: usize = 1024;
-        let num_bytes = num_pages as usize * 64 * KIB;
+fn update_mem_size(store: &mut dyn VMStore, instance: InstanceId, num_pages: u64) {
+    let memory = MemoryIndex::from_u32(store.engine().config().wmemcheck_allocator.memory);
+    let mut instance = store.instance_mut(instance);
+    let page_size = instance.env_module().memories[memory].page_size();
+    if let Some(wmemcheck_state) = instance.as_mut().wmemcheck_state_mut() {
+        let num_bytes = wmemcheck_usize(num_pages.saturating_mul(page_size));
         wmemcheck_state.update_mem_size(num_bytes);
     }
 }
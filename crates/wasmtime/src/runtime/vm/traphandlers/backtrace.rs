@@ -34,7 +34,7 @@ use core::ops::ControlFlow;
 use wasmtime_unwinder::Frame;
 
 /// A WebAssembly stack trace.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Backtrace(Vec<Frame>);
 
 impl Backtrace {
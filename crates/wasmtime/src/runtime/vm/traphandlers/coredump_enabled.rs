@@ -1,7 +1,10 @@
 use super::CallThreadState;
 use crate::prelude::*;
+use crate::runtime::module::lookup_code;
 use crate::runtime::vm::{Backtrace, VMStoreContext};
-use wasm_encoder::CoreDumpValue;
+use wasm_encoder::{CoreDumpValue, Ieee32, Ieee64};
+use wasmtime_environ::{FrameState, FrameValueType};
+use wasmtime_unwinder::Frame;
 
 /// A WebAssembly Coredump
 #[derive(Debug)]
@@ -9,17 +12,25 @@ pub struct CoreDumpStack {
     /// The backtrace containing the stack frames for the CoreDump
     pub bt: Backtrace,
 
-    /// The locals for each frame in the backtrace.
-    ///
-    /// This is not currently implemented.
-    #[expect(dead_code, reason = "not implemented yet")]
-    pub locals: Vec<Vec<CoreDumpValue>>,
-
-    /// The operands for each stack frame
-    ///
-    /// This is not currently implemented.
-    #[expect(dead_code, reason = "not implemented yet")]
-    pub operand_stack: Vec<Vec<CoreDumpValue>>,
+    /// The state recovered for each frame in the backtrace.
+    pub frames: Vec<CoreDumpFrame>,
+}
+
+/// The state of a single frame in a [`CoreDumpStack`].
+///
+/// This is only recovered for code compiled with
+/// `Config::coredump_frame_state` enabled, and is otherwise empty.
+#[derive(Debug, Default)]
+pub struct CoreDumpFrame {
+    /// The address of this frame's `VMContext`, identifying the instance that
+    /// the frame belongs to.
+    pub vmctx: Option<usize>,
+
+    /// The locals of this frame.
+    pub locals: Vec<CoreDumpValue>,
+
+    /// The operands of this frame, from the bottom of the stack to the top.
+    pub operand_stack: Vec<CoreDumpValue>,
 }
 
 impl CallThreadState {
@@ -35,10 +46,74 @@ impl CallThreadState {
             Backtrace::new_with_trap_state(vm_store_context, self.unwinder, self, trap_pc_and_fp)
         };
 
-        Some(CoreDumpStack {
-            bt,
-            locals: vec![],
-            operand_stack: vec![],
-        })
+        let trap_pc = trap_pc_and_fp.map(|(pc, _)| pc);
+        let frames = bt
+            .frames()
+            .map(|frame| unsafe { read_frame_state(frame, Some(frame.pc()) == trap_pc) })
+            .collect();
+
+        Some(CoreDumpStack { bt, frames })
+    }
+}
+
+/// Reads the state preserved in `frame`, if any.
+///
+/// # Safety
+///
+/// The `frame` must be a live Wasm frame on the stack.
+unsafe fn read_frame_state(frame: &Frame, is_trap_frame: bool) -> CoreDumpFrame {
+    let Some((code, text_offset)) = lookup_code(frame.pc()) else {
+        return CoreDumpFrame::default();
+    };
+    if code.frame_state_data().is_empty() {
+        return CoreDumpFrame::default();
+    }
+
+    // Like symbolication of backtraces, use the pc of the call instruction
+    // itself rather than the return address for frames which aren't the one
+    // that trapped.
+    let text_offset = if is_trap_frame {
+        text_offset
+    } else {
+        text_offset - 1
+    };
+    let state = wasmtime_environ::lookup_file_pos(code.address_map_data(), text_offset)
+        .and_then(|pos| pos.file_offset())
+        .and_then(|wasm_offset| {
+            FrameState::lookup(
+                code.frame_state_data(),
+                u32::try_from(text_offset).ok()?,
+                wasm_offset,
+            )
+        });
+    let Some(state) = state else {
+        return CoreDumpFrame::default();
+    };
+
+    let addr = |offset: u32| frame.fp() - usize::try_from(offset).unwrap();
+    let read = |(ty, offset): (FrameValueType, u32)| {
+        let ptr = addr(offset);
+        // SAFETY: the frame state section describes where the compiled code
+        // stored each value within this live frame.
+        unsafe {
+            match ty {
+                FrameValueType::I32 => CoreDumpValue::I32((ptr as *const i32).read_unaligned()),
+                FrameValueType::I64 => CoreDumpValue::I64((ptr as *const i64).read_unaligned()),
+                FrameValueType::F32 => {
+                    CoreDumpValue::F32(Ieee32::new((ptr as *const u32).read_unaligned()))
+                }
+                FrameValueType::F64 => {
+                    CoreDumpValue::F64(Ieee64::new((ptr as *const u64).read_unaligned()))
+                }
+                FrameValueType::Unknown => CoreDumpValue::Missing,
+            }
+        }
+    };
+    // SAFETY: same as above, the `VMContext` is stored on function entry.
+    let vmctx = unsafe { (addr(state.vmctx_offset()) as *const usize).read_unaligned() };
+    CoreDumpFrame {
+        vmctx: Some(vmctx),
+        locals: state.locals().map(read).collect(),
+        operand_stack: state.operands().map(read).collect(),
     }
 }
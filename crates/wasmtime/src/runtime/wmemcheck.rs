@@ -0,0 +1,49 @@
+use crate::WasmBacktrace;
+use core::fmt;
+
+/// An allocation made by a guest's allocator which was never freed, as found
+/// by [`Config::wmemcheck`](crate::Config::wmemcheck).
+///
+/// Leaks are listed with [`Store::wmemcheck_leaks`](crate::Store::wmemcheck_leaks).
+#[derive(Debug)]
+pub struct WmemcheckLeak {
+    pub(crate) addr: u64,
+    pub(crate) size: u64,
+    pub(crate) backtrace: WasmBacktrace,
+}
+
+impl WmemcheckLeak {
+    /// Returns the address of this allocation in the allocator's linear
+    /// memory.
+    pub fn addr(&self) -> u64 {
+        self.addr
+    }
+
+    /// Returns the size of this allocation, in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the WebAssembly backtrace of where this allocation was made.
+    ///
+    /// This is empty if [`Config::wasm_backtrace`](crate::Config::wasm_backtrace)
+    /// is disabled.
+    pub fn backtrace(&self) -> &WasmBacktrace {
+        &self.backtrace
+    }
+}
+
+impl fmt::Display for WmemcheckLeak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} bytes at addr {:#x} allocated at wasm backtrace:",
+            self.size, self.addr
+        )?;
+        if !self.backtrace.frames().is_empty() {
+            writeln!(f)?;
+            self.backtrace.fmt_frames(f)?;
+        }
+        Ok(())
+    }
+}
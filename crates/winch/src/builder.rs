@@ -67,6 +67,10 @@ impl CompilerBuilder for Builder {
             bail!("Winch does not currently support generating native debug information");
         }
 
+        if tunables.coredump_frame_state {
+            bail!("Winch does not currently support preserving frame state for core dumps");
+        }
+
         self.tunables = Some(tunables.clone());
         self.cranelift.set_tunables(tunables)?;
         Ok(())
@@ -109,6 +109,13 @@ impl wasmtime_environ::Compiler for Compiler {
         None
     }
 
+    fn tier_up_compiler(&self) -> Option<&dyn wasmtime_environ::Compiler> {
+        // The compiler used for trampolines is Cranelift configured to use
+        // the Winch calling convention, so its code can be swapped in for
+        // functions compiled by Winch.
+        Some(&self.trampolines)
+    }
+
     fn compile_function(
         &self,
         translation: &ModuleTranslation<'_>,
@@ -135,6 +142,7 @@ impl wasmtime_environ::Compiler for Compiler {
         let func = self
             .isa
             .compile_function(
+                def_func_index,
                 ty,
                 &body,
                 translation,
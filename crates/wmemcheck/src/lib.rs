@@ -15,6 +15,7 @@ pub struct Wmemcheck {
     pub stack_pointer: usize,
     max_stack_size: usize,
     pub flag: bool,
+    allocator_depth: usize,
 }
 
 /// Error types for memory checker.
@@ -54,11 +55,17 @@ impl Wmemcheck {
             stack_pointer: 0,
             max_stack_size: 0,
             flag: true,
+            allocator_depth: 0,
         }
     }
 
     /// Updates memory checker memory state metadata when malloc is called.
+    ///
+    /// A null `addr` is a failed allocation and is ignored.
     pub fn malloc(&mut self, addr: usize, len: usize) -> Result<(), AccessError> {
+        if addr == 0 {
+            return Ok(());
+        }
         if !self.is_in_bounds_heap(addr, len) {
             return Err(AccessError::OutOfBounds { addr, len });
         }
@@ -121,8 +128,57 @@ impl Wmemcheck {
         Ok(())
     }
 
+    /// Updates memory checker memory state metadata when calloc is called.
+    ///
+    /// Unlike `malloc`, the allocated memory is zeroed and therefore defined.
+    pub fn calloc(&mut self, addr: usize, len: usize) -> Result<(), AccessError> {
+        self.malloc(addr, len)?;
+        if addr != 0 {
+            for i in addr..addr + len {
+                self.metadata[i] = MemState::ValidToReadWrite;
+            }
+        }
+        Ok(())
+    }
+
+    /// Updates memory checker memory state metadata when realloc is called.
+    ///
+    /// The contents of the old allocation that fit in the new one keep their
+    /// state, the rest of the new allocation is undefined.
+    pub fn realloc(
+        &mut self,
+        old_addr: usize,
+        new_addr: usize,
+        len: usize,
+    ) -> Result<(), AccessError> {
+        if old_addr == 0 {
+            return self.malloc(new_addr, len);
+        }
+        let Some(&old_len) = self.mallocs.get(&old_addr) else {
+            return Err(AccessError::InvalidFree { addr: old_addr });
+        };
+        if new_addr == 0 {
+            // A zero-sized request may free the old allocation, any other
+            // failure leaves it untouched.
+            if len == 0 {
+                self.free(old_addr)?;
+            }
+            return Ok(());
+        }
+        let kept = self.metadata[old_addr..old_addr + min(old_len, len)].to_vec();
+        self.free(old_addr)?;
+        self.malloc(new_addr, len)?;
+        self.metadata[new_addr..new_addr + kept.len()].clone_from_slice(&kept);
+        Ok(())
+    }
+
     /// Updates memory checker memory state metadata when free is called.
+    ///
+    /// Freeing a null `addr` does nothing.
     pub fn free(&mut self, addr: usize) -> Result<(), AccessError> {
+        if addr == 0 {
+            return Ok(());
+        }
         if !self.mallocs.contains_key(&addr) {
             return Err(AccessError::InvalidFree { addr });
         }
@@ -140,11 +196,17 @@ impl Wmemcheck {
     }
 
     fn is_in_bounds_heap(&self, addr: usize, len: usize) -> bool {
-        self.max_stack_size <= addr && addr + len <= self.metadata.len()
+        self.max_stack_size <= addr
+            && addr
+                .checked_add(len)
+                .is_some_and(|end| end <= self.metadata.len())
     }
 
     fn is_in_bounds_stack(&self, addr: usize, len: usize) -> bool {
-        self.stack_pointer <= addr && addr + len < self.max_stack_size
+        self.stack_pointer <= addr
+            && addr
+                .checked_add(len)
+                .is_some_and(|end| end < self.max_stack_size)
     }
 
     /// Updates memory checker metadata when stack pointer is updated.
@@ -177,6 +239,38 @@ impl Wmemcheck {
         self.flag = false;
     }
 
+    /// Records entry into one of the guest's allocator functions.
+    ///
+    /// Loads and stores aren't checked while inside the allocator.
+    pub fn allocator_enter(&mut self) {
+        self.allocator_depth += 1;
+        self.memcheck_off();
+    }
+
+    /// Records exit from one of the guest's allocator functions.
+    ///
+    /// Returns whether this was the outermost allocator call, in which case
+    /// its effect on the heap should be recorded. Allocator functions that are
+    /// called by other allocator functions, such as a `malloc` called by
+    /// `realloc`, are only implementation details of the outer call.
+    pub fn allocator_exit(&mut self) -> bool {
+        self.allocator_depth = self.allocator_depth.saturating_sub(1);
+        if self.allocator_depth == 0 {
+            self.memcheck_on();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the address and length of every live allocation, sorted by
+    /// address.
+    pub fn allocations(&self) -> Vec<(usize, usize)> {
+        let mut allocations: Vec<_> = self.mallocs.iter().map(|(a, l)| (*a, *l)).collect();
+        allocations.sort();
+        allocations
+    }
+
     /// Initializes stack and stack pointer in memory checker metadata.
     pub fn set_stack_size(&mut self, stack_size: usize) {
         self.max_stack_size = stack_size + 1;
@@ -385,3 +479,69 @@ fn from_test_program() {
     assert!(wmemcheck_state.write(70832, 1).is_ok());
     assert!(wmemcheck_state.read(1138, 1).is_ok());
 }
+
+#[test]
+fn null_malloc_and_free() {
+    let mut wmemcheck_state = Wmemcheck::new(640 * 1024);
+
+    assert!(wmemcheck_state.malloc(0, 32).is_ok());
+    assert!(wmemcheck_state.free(0).is_ok());
+    assert!(wmemcheck_state.mallocs.is_empty());
+}
+
+#[test]
+fn calloc_is_defined() {
+    let mut wmemcheck_state = Wmemcheck::new(640 * 1024);
+
+    assert!(wmemcheck_state.calloc(0x1000, 32).is_ok());
+    assert!(wmemcheck_state.read(0x1000, 32).is_ok());
+    assert!(wmemcheck_state.free(0x1000).is_ok());
+}
+
+#[test]
+fn realloc_keeps_contents() {
+    let mut wmemcheck_state = Wmemcheck::new(640 * 1024);
+
+    assert!(wmemcheck_state.malloc(0x1000, 8).is_ok());
+    assert!(wmemcheck_state.write(0x1000, 4).is_ok());
+    assert!(wmemcheck_state.realloc(0x1000, 0x2000, 16).is_ok());
+    assert_eq!(wmemcheck_state.mallocs, HashMap::from([(0x2000, 16)]));
+    assert!(wmemcheck_state.read(0x2000, 4).is_ok());
+    assert_eq!(
+        wmemcheck_state.read(0x2004, 4),
+        Err(AccessError::InvalidRead {
+            addr: 0x2004,
+            len: 4
+        })
+    );
+    assert_eq!(
+        wmemcheck_state.read(0x1000, 4),
+        Err(AccessError::InvalidRead {
+            addr: 0x1000,
+            len: 4
+        })
+    );
+
+    // Growing in place.
+    assert!(wmemcheck_state.realloc(0x2000, 0x2000, 32).is_ok());
+    assert!(wmemcheck_state.read(0x2000, 4).is_ok());
+    assert_eq!(
+        wmemcheck_state.realloc(0x3000, 0x4000, 32),
+        Err(AccessError::InvalidFree { addr: 0x3000 })
+    );
+    assert!(wmemcheck_state.realloc(0, 0x4000, 32).is_ok());
+    assert_eq!(wmemcheck_state.allocations(), [(0x2000, 32), (0x4000, 32)]);
+}
+
+#[test]
+fn nested_allocator_calls() {
+    let mut wmemcheck_state = Wmemcheck::new(640 * 1024);
+
+    wmemcheck_state.allocator_enter();
+    wmemcheck_state.allocator_enter();
+    assert!(!wmemcheck_state.flag);
+    assert!(!wmemcheck_state.allocator_exit());
+    assert!(!wmemcheck_state.flag);
+    assert!(wmemcheck_state.allocator_exit());
+    assert!(wmemcheck_state.flag);
+}
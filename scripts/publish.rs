@@ -44,6 +44,9 @@ const CRATES_TO_PUBLISH: &[&str] = &[
     "wasmtime-internal-jit-icache-coherence",
     // Wasmtime unwinder, used by both `cranelift-jit` (optionally) and filetests, and by Wasmtime.
     "wasmtime-internal-unwinder",
+    // Wasmtime JIT debug interfaces, used by both `cranelift-jit` (optionally) and Wasmtime.
+    "wasmtime-internal-versioned-export-macros",
+    "wasmtime-internal-jit-debug",
     // Cranelift crates that use Wasmtime unwinder.
     "cranelift-jit",
     "cranelift",
@@ -53,12 +56,10 @@ const CRATES_TO_PUBLISH: &[&str] = &[
     // winch
     "winch",
     // wasmtime
-    "wasmtime-internal-versioned-export-macros",
     "wasmtime-internal-slab",
     "wasmtime-internal-component-util",
     "wasmtime-internal-wit-bindgen",
     "wasmtime-internal-component-macro",
-    "wasmtime-internal-jit-debug",
     "wasmtime-internal-fiber",
     "wasmtime-environ",
     "wasmtime-internal-wmemcheck",
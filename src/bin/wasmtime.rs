@@ -58,6 +58,10 @@ enum Subcommand {
     #[cfg(feature = "run")]
     Run(wasmtime_cli::commands::RunCommand),
 
+    /// Inspects and manages the compilation cache
+    #[cfg(feature = "cache")]
+    Cache(wasmtime_cli::commands::CacheCommand),
+
     /// Controls Wasmtime configuration settings
     #[cfg(feature = "cache")]
     Config(wasmtime_cli::commands::ConfigCommand),
@@ -74,6 +78,10 @@ enum Subcommand {
     #[cfg(feature = "serve")]
     Serve(wasmtime_cli::commands::ServeCommand),
 
+    /// Pre-initializes a WebAssembly module or component by snapshotting it
+    #[cfg(feature = "snapshot")]
+    Snapshot(wasmtime_cli::commands::SnapshotCommand),
+
     /// Displays available Cranelift settings for a target.
     #[cfg(feature = "cranelift")]
     Settings(wasmtime_cli::commands::SettingsCommand),
@@ -103,6 +111,9 @@ impl Wasmtime {
             #[cfg(feature = "run")]
             Subcommand::Run(c) => c.execute(),
 
+            #[cfg(feature = "cache")]
+            Subcommand::Cache(c) => c.execute(),
+
             #[cfg(feature = "cache")]
             Subcommand::Config(c) => c.execute(),
 
@@ -115,6 +126,9 @@ impl Wasmtime {
             #[cfg(feature = "serve")]
             Subcommand::Serve(c) => c.execute(),
 
+            #[cfg(feature = "snapshot")]
+            Subcommand::Snapshot(c) => c.execute(),
+
             #[cfg(feature = "cranelift")]
             Subcommand::Settings(c) => c.execute(),
 
@@ -20,6 +20,11 @@ mod wast;
 #[cfg(feature = "wast")]
 pub use self::wast::*;
 
+#[cfg(feature = "cache")]
+mod cache;
+#[cfg(feature = "cache")]
+pub use self::cache::*;
+
 #[cfg(feature = "cache")]
 mod config;
 #[cfg(feature = "cache")]
@@ -30,6 +35,11 @@ mod compile;
 #[cfg(feature = "compile")]
 pub use self::compile::*;
 
+#[cfg(feature = "snapshot")]
+mod snapshot;
+#[cfg(feature = "snapshot")]
+pub use self::snapshot::*;
+
 #[cfg(feature = "cranelift")]
 mod settings;
 #[cfg(feature = "cranelift")]
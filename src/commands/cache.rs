@@ -0,0 +1,83 @@
+//! The module that implements the `wasmtime cache` command.
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use wasmtime_cache::Cache;
+
+/// Inspects and manages the compilation cache
+#[derive(Parser, PartialEq)]
+pub struct CacheCommand {
+    /// The cache configuration file to use, rather than the system default
+    #[arg(long, value_name = "FILE_PATH", global = true)]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    subcommand: CacheSubcommand,
+}
+
+#[derive(Subcommand, PartialEq)]
+enum CacheSubcommand {
+    /// Summarizes the contents of the cache
+    Stats,
+    /// Lists the compiled modules in the cache
+    List,
+    /// Deletes unrecognized files and, if the soft limits are exceeded, the
+    /// least recently used modules
+    Prune,
+    /// Deletes everything in the cache
+    Clear,
+}
+
+impl CacheCommand {
+    /// Executes the command.
+    pub fn execute(self) -> Result<()> {
+        let cache = Cache::from_file(self.config.as_deref())?;
+        match self.subcommand {
+            CacheSubcommand::Stats => {
+                let stats = cache.stats();
+                println!("directory:   {}", cache.directory().display());
+                println!(
+                    "total size:  {} (soft limit {})",
+                    stats.modules_size,
+                    cache.files_total_size_soft_limit()
+                );
+                println!(
+                    "modules:     {} (soft limit {})",
+                    stats.modules,
+                    cache.file_count_soft_limit()
+                );
+                println!("other files: {}", stats.other_files);
+                println!("hits:        {}", stats.hits);
+                println!("misses:      {}", stats.misses);
+            }
+            CacheSubcommand::List => {
+                for module in cache.modules() {
+                    println!(
+                        "{}\t{}\t{}\t{}",
+                        module.compiler(),
+                        module.hash(),
+                        module.size(),
+                        module.usages()
+                    );
+                }
+            }
+            CacheSubcommand::Prune => {
+                let before = cache.stats();
+                cache.prune()?;
+                let after = cache.stats();
+                println!(
+                    "Removed {} modules ({} bytes) and {} other files.",
+                    before.modules.saturating_sub(after.modules),
+                    before.modules_size.saturating_sub(after.modules_size),
+                    before.other_files.saturating_sub(after.other_files),
+                );
+            }
+            CacheSubcommand::Clear => {
+                cache.clear()?;
+                println!("Cleared '{}'.", cache.directory().display());
+            }
+        }
+        Ok(())
+    }
+}
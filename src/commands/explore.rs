@@ -7,13 +7,13 @@ use tempfile::tempdir;
 use wasmtime::Strategy;
 use wasmtime_cli_flags::CommonOptions;
 
-/// Explore the compilation of a WebAssembly module to native code.
+/// Explore the compilation of a WebAssembly module or component to native code.
 #[derive(Parser)]
 pub struct ExploreCommand {
     #[command(flatten)]
     common: CommonOptions,
 
-    /// The path of the WebAssembly module to compile
+    /// The path of the WebAssembly module or component to compile
     #[arg(required = true, value_name = "MODULE")]
     module: PathBuf,
 
@@ -21,11 +21,25 @@ pub struct ExploreCommand {
     /// provided)
     #[arg(short, long)]
     output: Option<PathBuf>,
+
+    /// Additional options, such as `-Ccompiler=winch` or `--target
+    /// aarch64-unknown-linux-gnu`, to compile the module with a second time
+    /// and show the assembly of both compilations side by side
+    #[arg(long, value_name = "OPTIONS", allow_hyphen_values = true)]
+    diff: Option<String>,
 }
 
 impl ExploreCommand {
     /// Executes the command.
     pub fn execute(mut self) -> Result<()> {
+        let diff = match &self.diff {
+            Some(opts) => {
+                let mut common = self.common.clone();
+                common.push_args(opts.split_whitespace())?;
+                Some((opts, common))
+            }
+            None => None,
+        };
         self.common.init_logging()?;
 
         let mut config = self.common.config(None)?;
@@ -48,6 +62,28 @@ impl ExploreCommand {
             .with_context(|| format!("failed to create file: {}", output.display()))?;
         let mut output_file = std::io::BufWriter::new(output_file);
 
+        if let Some((opts, mut common)) = diff {
+            let diff_config = common.config(None)?;
+            wasmtime_explorer::generate_diff(
+                [
+                    wasmtime_explorer::DiffSide {
+                        label: "baseline",
+                        config: &config,
+                        target: self.common.target.as_deref(),
+                    },
+                    wasmtime_explorer::DiffSide {
+                        label: opts,
+                        config: &diff_config,
+                        target: common.target.as_deref(),
+                    },
+                ],
+                &bytes,
+                &mut output_file,
+            )?;
+            println!("Exploration written to {}", output.display());
+            return Ok(());
+        }
+
         let clif_dir = if let Some(Strategy::Cranelift) | None = self.common.codegen.compiler {
             let clif_dir = tempdir()?;
             config.emit_clif(clif_dir.path());
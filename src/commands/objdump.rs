@@ -9,6 +9,7 @@ use object::read::elf::ElfFile64;
 use object::{Architecture, Endianness, FileFlags, Object, ObjectSection, ObjectSymbol};
 use pulley_interpreter::decode::{Decoder, DecodingError, OpVisitor};
 use pulley_interpreter::disas::Disassembler;
+use serde_derive::Serialize;
 use std::io::{IsTerminal, Read, Write};
 use std::iter::{self, Peekable};
 use std::path::{Path, PathBuf};
@@ -70,6 +71,14 @@ pub struct ObjdumpCommand {
     /// Whether or not to show information about exception tables.
     #[arg(long, require_equals = true, value_name = "true|false")]
     exception_tables: Option<Option<bool>>,
+
+    /// The format to print the disassembly in.
+    ///
+    /// The `json` format prints a single document with every function and its
+    /// instructions along with their annotations, such as Wasm offsets and
+    /// trap codes. Address maps are included by default in this format.
+    #[arg(long, default_value = "text", value_name = "FORMAT")]
+    format: Format,
 }
 
 fn optional_flag_with_default(flag: Option<Option<bool>>, default: bool) -> bool {
@@ -82,7 +91,7 @@ fn optional_flag_with_default(flag: Option<Option<bool>>, default: bool) -> bool
 
 impl ObjdumpCommand {
     fn addrmap(&self) -> bool {
-        optional_flag_with_default(self.addrmap, false)
+        optional_flag_with_default(self.addrmap, self.format == Format::Json)
     }
 
     fn traps(&self) -> bool {
@@ -156,6 +165,7 @@ impl ObjdumpCommand {
         // Iterate over all symbols which will be functions for a cwasm and
         // we'll disassemble them all.
         let mut first = true;
+        let mut json_functions = Vec::new();
         for sym in elf.symbols() {
             let name = match sym.name() {
                 Ok(name) => name,
@@ -195,6 +205,31 @@ impl ObjdumpCommand {
                 }
             }
 
+            if self.format == Format::Json {
+                let instructions = self
+                    .disas(&elf, bytes, sym.address())?
+                    .into_iter()
+                    .map(|inst| {
+                        let mut decorations = Decorations::default();
+                        decorator.decorate(inst.address, &mut decorations);
+                        JsonInst {
+                            address: inst.address,
+                            bytes: inst.bytes.iter().map(|b| format!("{b:02x}")).collect(),
+                            disassembly: inst.disassembly,
+                            decorations,
+                        }
+                    })
+                    .collect();
+                json_functions.push(JsonFunction {
+                    name,
+                    kind,
+                    address: sym.address(),
+                    size: sym.size(),
+                    instructions,
+                });
+                continue;
+            }
+
             // Place a blank line between functions.
             if first {
                 first = false;
@@ -247,9 +282,9 @@ impl ObjdumpCommand {
                 // instruction: for example, exception handler entries
                 // logically occur at the return point after a call,
                 // so "before" the instruction following the call.
-                let mut pre_decorations = Vec::new();
-                let mut post_decorations = Vec::new();
-                decorator.decorate(address, &mut pre_decorations, &mut post_decorations);
+                let mut decorations = Decorations::default();
+                decorator.decorate(address, &mut decorations);
+                let (pre_decorations, post_decorations) = decorations.render();
 
                 let print_whitespace_to_decoration = |stdout: &mut StandardStream| -> Result<()> {
                     write!(stdout, "{:width$}  ", "")?;
@@ -362,6 +397,17 @@ impl ObjdumpCommand {
                 print_decorations(&mut stdout, post_decorations)?;
             }
         }
+
+        if self.format == Format::Json {
+            let mut stdout = std::io::stdout().lock();
+            serde_json::to_writer_pretty(
+                &mut stdout,
+                &JsonOutput {
+                    functions: json_functions,
+                },
+            )?;
+            writeln!(stdout)?;
+        }
         Ok(())
     }
 
@@ -512,7 +558,8 @@ struct Inst {
     bytes: Vec<u8>,
 }
 
-#[derive(clap::ValueEnum, Clone, Copy, PartialEq, Eq)]
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 enum Func {
     All,
     Wasm,
@@ -521,6 +568,115 @@ enum Func {
     Libcall,
 }
 
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+}
+
+/// The document printed by `--format json`.
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    functions: Vec<JsonFunction<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonFunction<'a> {
+    name: &'a str,
+    kind: Func,
+    address: u64,
+    size: u64,
+    instructions: Vec<JsonInst>,
+}
+
+#[derive(Serialize)]
+struct JsonInst {
+    address: u64,
+    /// The instruction's bytes as a hexadecimal string.
+    bytes: String,
+    disassembly: String,
+    #[serde(flatten)]
+    decorations: Decorations,
+}
+
+/// Annotations of a single instruction, gathered by the `Decorator`.
+#[derive(Default, Serialize)]
+struct Decorations {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    wasm_offsets: Vec<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    traps: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stack_maps: Vec<StackMapEntry>,
+    /// Exception table entries, which logically apply to the return point of
+    /// the preceding call and so are printed before this instruction.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    exception_tables: Vec<ExceptionTableEntry>,
+}
+
+#[derive(Serialize)]
+struct StackMapEntry {
+    frame_size: u32,
+    frame_offsets: Vec<u32>,
+}
+
+#[derive(Serialize)]
+struct ExceptionTableEntry {
+    frame_offset: Option<u32>,
+    handlers: Vec<ExceptionHandlerEntry>,
+}
+
+#[derive(Serialize)]
+struct ExceptionHandlerEntry {
+    tag: Option<u32>,
+    context_sp_offset: Option<u32>,
+    handler_offset: u32,
+}
+
+impl Decorations {
+    /// Renders these decorations as text, returning the ones printed before
+    /// the instruction and the ones printed after it.
+    fn render(self) -> (Vec<String>, Vec<String>) {
+        let mut pre = Vec::new();
+        for entry in self.exception_tables {
+            if let Some(frame_offset) = entry.frame_offset {
+                pre.push(format!(
+                    "exception frame offset: SP = FP - 0x{frame_offset:x}",
+                ));
+            }
+            for handler in entry.handlers {
+                let tag = match handler.tag {
+                    Some(tag) => format!("tag={tag}"),
+                    None => "default handler".to_string(),
+                };
+                let context = match handler.context_sp_offset {
+                    Some(offset) => format!("context at [SP+0x{offset:x}]"),
+                    None => "no dynamic context".to_string(),
+                };
+                pre.push(format!(
+                    "exception handler: {tag}, {context}, handler=0x{:x}",
+                    handler.handler_offset
+                ));
+            }
+        }
+
+        let mut post = Vec::new();
+        for offset in self.wasm_offsets {
+            post.push(format!("addrmap: {offset:#x}"));
+        }
+        for trap in self.traps {
+            post.push(format!("trap: {trap}"));
+        }
+        for stack_map in self.stack_maps {
+            post.push(format!(
+                "stack_map: frame_size={}, frame_offsets={:?}",
+                stack_map.frame_size, stack_map.frame_offsets
+            ));
+        }
+        (pre, post)
+    }
+}
+
 struct Decorator<'a> {
     objdump: &'a ObjdumpCommand,
     addrmap: Option<Peekable<Box<dyn Iterator<Item = (u32, FilePos)> + 'a>>>,
@@ -531,14 +687,14 @@ struct Decorator<'a> {
 }
 
 impl Decorator<'_> {
-    fn decorate(&mut self, address: u64, pre_list: &mut Vec<String>, post_list: &mut Vec<String>) {
-        self.addrmap(address, post_list);
-        self.traps(address, post_list);
-        self.stack_maps(address, post_list);
-        self.exception_table(address, pre_list);
+    fn decorate(&mut self, address: u64, decorations: &mut Decorations) {
+        self.addrmap(address, decorations);
+        self.traps(address, decorations);
+        self.stack_maps(address, decorations);
+        self.exception_table(address, decorations);
     }
 
-    fn addrmap(&mut self, address: u64, list: &mut Vec<String>) {
+    fn addrmap(&mut self, address: u64, decorations: &mut Decorations) {
         if !self.objdump.addrmap() {
             return;
         }
@@ -550,12 +706,12 @@ impl Decorator<'_> {
                 continue;
             }
             if let Some(offset) = pos.file_offset() {
-                list.push(format!("addrmap: {offset:#x}"));
+                decorations.wasm_offsets.push(offset);
             }
         }
     }
 
-    fn traps(&mut self, address: u64, list: &mut Vec<String>) {
+    fn traps(&mut self, address: u64, decorations: &mut Decorations) {
         if !self.objdump.traps() {
             return;
         }
@@ -566,11 +722,11 @@ impl Decorator<'_> {
             if u64::from(addr) != address {
                 continue;
             }
-            list.push(format!("trap: {trap:?}"));
+            decorations.traps.push(format!("{trap:?}"));
         }
     }
 
-    fn stack_maps(&mut self, address: u64, list: &mut Vec<String>) {
+    fn stack_maps(&mut self, address: u64, decorations: &mut Decorations) {
         if !self.objdump.stack_maps() {
             return;
         }
@@ -583,15 +739,14 @@ impl Decorator<'_> {
             if u64::from(addr) != address {
                 continue;
             }
-            list.push(format!(
-                "stack_map: frame_size={}, frame_offsets={:?}",
-                stack_map.frame_size(),
-                stack_map.offsets().collect::<Vec<_>>()
-            ));
+            decorations.stack_maps.push(StackMapEntry {
+                frame_size: stack_map.frame_size(),
+                frame_offsets: stack_map.offsets().collect(),
+            });
         }
     }
 
-    fn exception_table(&mut self, address: u64, list: &mut Vec<String>) {
+    fn exception_table(&mut self, address: u64, decorations: &mut Decorations) {
         if !self.objdump.exception_tables() {
             return;
         }
@@ -604,25 +759,17 @@ impl Decorator<'_> {
             if u64::from(addr) != address {
                 continue;
             }
-            if let Some(frame_offset) = frame_offset {
-                list.push(format!(
-                    "exception frame offset: SP = FP - 0x{frame_offset:x}",
-                ));
-            }
-            for handler in &handlers {
-                let tag = match handler.tag {
-                    Some(tag) => format!("tag={tag}"),
-                    None => "default handler".to_string(),
-                };
-                let context = match handler.context_sp_offset {
-                    Some(offset) => format!("context at [SP+0x{offset:x}]"),
-                    None => "no dynamic context".to_string(),
-                };
-                list.push(format!(
-                    "exception handler: {tag}, {context}, handler=0x{:x}",
-                    handler.handler_offset
-                ));
-            }
+            decorations.exception_tables.push(ExceptionTableEntry {
+                frame_offset,
+                handlers: handlers
+                    .iter()
+                    .map(|handler| ExceptionHandlerEntry {
+                        tag: handler.tag,
+                        context_sp_offset: handler.context_sp_offset,
+                        handler_offset: handler.handler_offset,
+                    })
+                    .collect(),
+            });
         }
     }
 }
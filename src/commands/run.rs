@@ -5,7 +5,7 @@
     allow(irrefutable_let_patterns, unreachable_patterns)
 )]
 
-use crate::common::{Profile, RunCommon, RunTarget};
+use crate::common::{DirSource, Profile, RunCommon, RunTarget};
 use anyhow::{Context as _, Error, Result, anyhow, bail};
 use clap::Parser;
 use std::ffi::OsString;
@@ -23,7 +23,7 @@ use wasmtime_wasi_http::{
     DEFAULT_OUTGOING_BODY_BUFFER_CHUNKS, DEFAULT_OUTGOING_BODY_CHUNK_SIZE, WasiHttpCtx,
 };
 #[cfg(feature = "wasi-keyvalue")]
-use wasmtime_wasi_keyvalue::{WasiKeyValue, WasiKeyValueCtx, WasiKeyValueCtxBuilder};
+use wasmtime_wasi_keyvalue::{WasiKeyValue, WasiKeyValueCtx};
 #[cfg(feature = "wasi-nn")]
 use wasmtime_wasi_nn::wit::WasiNnView;
 #[cfg(feature = "wasi-threads")]
@@ -231,6 +231,11 @@ impl RunCommand {
             .await
         });
 
+        #[cfg(feature = "wmemcheck")]
+        if self.run.common.wasm.wmemcheck == Some(true) {
+            print_wmemcheck_leaks(&store);
+        }
+
         // Load the main wasm module.
         match result.unwrap_or_else(|elapsed| {
             Err(anyhow::Error::from(wasmtime::Trap::Interrupt))
@@ -910,16 +915,8 @@ impl RunCommand {
                         bail!("Cannot enable wasi-keyvalue for core wasm modules");
                     }
                     CliLinker::Component(linker) => {
-                        let ctx = WasiKeyValueCtxBuilder::new()
-                            .in_memory_data(
-                                self.run
-                                    .common
-                                    .wasi
-                                    .keyvalue_in_memory_data
-                                    .iter()
-                                    .map(|v| (v.key.clone(), v.value.clone())),
-                            )
-                            .build();
+                        let stores = self.run.wasi_keyvalue_stores()?;
+                        let ctx = self.run.wasi_keyvalue_ctx(&stores);
 
                         wasmtime_wasi_keyvalue::add_to_linker(linker, |h| {
                             let ctx = h.wasip1_ctx.as_mut().expect("wasip2 is not configured");
@@ -983,7 +980,7 @@ impl RunCommand {
                     }
                 }
 
-                store.data_mut().wasi_http = Some(Arc::new(WasiHttpCtx::new()));
+                store.data_mut().wasi_http = Some(Arc::new(self.run.wasi_http_ctx()?));
             }
         }
 
@@ -1057,7 +1054,12 @@ impl RunCommand {
             num_fd += 1;
         }
 
-        for (host, guest) in self.run.dirs.iter() {
+        for (source, guest) in self.run.dirs.iter() {
+            let DirSource::Host(host) = source else {
+                bail!(
+                    "in-memory and overlay directories require `-Spreview2` and are incompatible with `-Sthreads`"
+                );
+            };
             let dir = Dir::open_ambient_dir(host, ambient_authority())
                 .with_context(|| format!("failed to open directory '{host}'"))?;
             builder.preopened_dir(dir, guest)?;
@@ -1142,8 +1144,6 @@ struct Host {
     wasi_http_outgoing_body_buffer_chunks: Option<usize>,
     #[cfg(feature = "wasi-http")]
     wasi_http_outgoing_body_chunk_size: Option<usize>,
-    #[cfg(all(feature = "wasi-http", feature = "component-model-async"))]
-    p3_http: crate::common::DefaultP3Ctx,
     limits: StoreLimits,
     #[cfg(feature = "profiling")]
     guest_profiler: Option<Arc<wasmtime::GuestProfiler>>,
@@ -1203,7 +1203,8 @@ impl wasmtime_wasi_http::p3::WasiHttpView for Host {
     fn http(&mut self) -> wasmtime_wasi_http::p3::WasiHttpCtxView<'_> {
         wasmtime_wasi_http::p3::WasiHttpCtxView {
             table: WasiView::ctx(unwrap_singlethread_context(&mut self.wasip1_ctx)).table,
-            ctx: &mut self.p3_http,
+            ctx: Arc::get_mut(self.wasi_http.as_mut().unwrap())
+                .expect("wasmtime_wasi is not compatible with threads"),
         }
     }
 }
@@ -1260,3 +1261,20 @@ fn write_core_dump(
         .with_context(|| format!("failed to write core dump file at `{path}`"))?;
     Ok(())
 }
+
+/// Prints the allocations which the guest never freed to stderr.
+#[cfg(feature = "wmemcheck")]
+fn print_wmemcheck_leaks(store: &Store<Host>) {
+    let leaks = store.wmemcheck_leaks();
+    if leaks.is_empty() {
+        return;
+    }
+    let bytes = leaks.iter().map(|leak| leak.size()).sum::<u64>();
+    eprintln!(
+        "wmemcheck: {bytes} bytes in {} allocations were never freed",
+        leaks.len()
+    );
+    for leak in leaks {
+        eprintln!("{leak}");
+    }
+}
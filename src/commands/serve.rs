@@ -1,12 +1,14 @@
 use crate::common::{Profile, RunCommon, RunTarget};
-use anyhow::{Result, bail};
+use anyhow::{Context as _, Result, bail};
 use bytes::Bytes;
 use clap::Parser;
 use http::{Response, StatusCode};
 use http_body_util::BodyExt as _;
 use http_body_util::combinators::BoxBody;
+use std::collections::BTreeMap;
 use std::convert::Infallible;
 use std::net::SocketAddr;
+use std::num::NonZeroUsize;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use std::time::Instant;
@@ -19,9 +21,9 @@ use std::{
     time::Duration,
 };
 use tokio::io::{self, AsyncWrite};
-use tokio::sync::Notify;
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
 use wasmtime::component::{Component, Linker, ResourceTable};
-use wasmtime::{Engine, Store, StoreLimits, UpdateDeadline};
+use wasmtime::{Engine, ResourceLimiter, Store, StoreLimits, UpdateDeadline};
 use wasmtime_wasi::p2::{StreamError, StreamResult};
 use wasmtime_wasi::{WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView};
 use wasmtime_wasi_http::bindings as p2;
@@ -34,7 +36,7 @@ use wasmtime_wasi_http::{
 #[cfg(feature = "wasi-config")]
 use wasmtime_wasi_config::{WasiConfig, WasiConfigVariables};
 #[cfg(feature = "wasi-keyvalue")]
-use wasmtime_wasi_keyvalue::{WasiKeyValue, WasiKeyValueCtx, WasiKeyValueCtxBuilder};
+use wasmtime_wasi_keyvalue::{KeyValueStore, WasiKeyValue, WasiKeyValueCtx};
 #[cfg(feature = "wasi-nn")]
 use wasmtime_wasi_nn::wit::WasiNnCtx;
 
@@ -45,10 +47,7 @@ struct Host {
     http_outgoing_body_buffer_chunks: Option<usize>,
     http_outgoing_body_chunk_size: Option<usize>,
 
-    #[cfg(feature = "component-model-async")]
-    p3_http: crate::common::DefaultP3Ctx,
-
-    limits: StoreLimits,
+    limiter: UsageLimiter,
 
     #[cfg(feature = "wasi-nn")]
     nn: Option<WasiNnCtx>,
@@ -96,11 +95,81 @@ impl wasmtime_wasi_http::p3::WasiHttpView for Host {
     fn http(&mut self) -> wasmtime_wasi_http::p3::WasiHttpCtxView<'_> {
         wasmtime_wasi_http::p3::WasiHttpCtxView {
             table: &mut self.table,
-            ctx: &mut self.p3_http,
+            ctx: &mut self.http,
         }
     }
 }
 
+/// A [`ResourceLimiter`] which enforces the configured [`StoreLimits`] while
+/// also tracking the peak amount of linear memory used by an instance.
+struct UsageLimiter {
+    limits: StoreLimits,
+    /// The total size, in bytes, of all linear memories in the store.
+    memory: usize,
+    /// The largest value `memory` has had so far.
+    peak_memory: usize,
+    /// The most recently approved growth, undone if the growth then fails.
+    pending_growth: usize,
+}
+
+impl UsageLimiter {
+    fn new(limits: StoreLimits) -> UsageLimiter {
+        UsageLimiter {
+            limits,
+            memory: 0,
+            peak_memory: 0,
+            pending_growth: 0,
+        }
+    }
+}
+
+impl ResourceLimiter for UsageLimiter {
+    fn memory_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        maximum: Option<usize>,
+    ) -> Result<bool> {
+        let allow = self.limits.memory_growing(current, desired, maximum)?;
+        if allow {
+            self.pending_growth = desired.saturating_sub(current);
+            self.memory = self.memory.saturating_add(self.pending_growth);
+            self.peak_memory = self.peak_memory.max(self.memory);
+        }
+        Ok(allow)
+    }
+
+    fn memory_grow_failed(&mut self, error: anyhow::Error) -> Result<()> {
+        self.memory -= std::mem::take(&mut self.pending_growth);
+        self.limits.memory_grow_failed(error)
+    }
+
+    fn table_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        maximum: Option<usize>,
+    ) -> Result<bool> {
+        self.limits.table_growing(current, desired, maximum)
+    }
+
+    fn table_grow_failed(&mut self, error: anyhow::Error) -> Result<()> {
+        self.limits.table_grow_failed(error)
+    }
+
+    fn instances(&self) -> usize {
+        self.limits.instances()
+    }
+
+    fn tables(&self) -> usize {
+        self.limits.tables()
+    }
+
+    fn memories(&self) -> usize {
+        self.limits.memories()
+    }
+}
+
 const DEFAULT_ADDR: std::net::SocketAddr = std::net::SocketAddr::new(
     std::net::IpAddr::V4(std::net::Ipv4Addr::new(0, 0, 0, 0)),
     8080,
@@ -123,6 +192,29 @@ pub struct ServeCommand {
     #[arg(long, value_name = "SOCKADDR")]
     shutdown_addr: Option<SocketAddr>,
 
+    /// Socket address to serve metrics on, in the Prometheus text format.
+    ///
+    /// Any HTTP request to this address returns the aggregate resource usage
+    /// of all requests handled so far.
+    #[arg(long, value_name = "SOCKADDR")]
+    metrics_addr: Option<SocketAddr>,
+
+    /// Write an access-log line for each request to this file, or to stderr
+    /// if `-` is given.
+    ///
+    /// Lines are made of `key=value` fields and include the resource usage of
+    /// the request's instance: wall time, fuel consumed (with `-W fuel`), peak
+    /// linear memory and the number of outgoing HTTP requests.
+    #[arg(long, value_name = "PATH")]
+    access_log: Option<PathBuf>,
+
+    /// Maximum number of instances which may run at the same time.
+    ///
+    /// Requests arriving while this many instances are running are rejected
+    /// with a 503 Service Unavailable response.
+    #[arg(long, value_name = "N")]
+    max_concurrent_instances: Option<NonZeroUsize>,
+
     /// Disable log prefixes of wasi-http handlers.
     /// if unspecified, logs will be prefixed with 'stdout|stderr [{req_id}] :: '
     #[arg(long)]
@@ -131,6 +223,12 @@ pub struct ServeCommand {
     /// The WebAssembly component to run.
     #[arg(value_name = "WASM", required = true)]
     component: PathBuf,
+
+    /// File-backed key-value stores, opened when the server starts and shared
+    /// by all requests. In-Memory stores are instead created for each request.
+    #[cfg(feature = "wasi-keyvalue")]
+    #[arg(skip)]
+    wasi_keyvalue_stores: Vec<(String, Arc<dyn KeyValueStore>)>,
 }
 
 impl ServeCommand {
@@ -192,11 +290,11 @@ impl ServeCommand {
         let mut host = Host {
             table: wasmtime::component::ResourceTable::new(),
             ctx: builder.build(),
-            http: WasiHttpCtx::new(),
+            http: self.run.wasi_http_ctx()?,
             http_outgoing_body_buffer_chunks: self.run.common.wasi.http_outgoing_body_buffer_chunks,
             http_outgoing_body_chunk_size: self.run.common.wasi.http_outgoing_body_chunk_size,
 
-            limits: StoreLimits::default(),
+            limiter: UsageLimiter::new(self.run.store_limits()),
 
             #[cfg(feature = "wasi-nn")]
             nn: None,
@@ -206,8 +304,6 @@ impl ServeCommand {
             wasi_keyvalue: None,
             #[cfg(feature = "profiling")]
             guest_profiler: None,
-            #[cfg(feature = "component-model-async")]
-            p3_http: crate::common::DefaultP3Ctx,
         };
 
         if self.run.common.wasi.nn == Some(true) {
@@ -244,24 +340,14 @@ impl ServeCommand {
         if self.run.common.wasi.keyvalue == Some(true) {
             #[cfg(feature = "wasi-keyvalue")]
             {
-                let ctx = WasiKeyValueCtxBuilder::new()
-                    .in_memory_data(
-                        self.run
-                            .common
-                            .wasi
-                            .keyvalue_in_memory_data
-                            .iter()
-                            .map(|v| (v.key.clone(), v.value.clone())),
-                    )
-                    .build();
+                let ctx = self.run.wasi_keyvalue_ctx(&self.wasi_keyvalue_stores);
                 host.wasi_keyvalue.replace(ctx);
             }
         }
 
         let mut store = Store::new(engine, host);
 
-        store.data_mut().limits = self.run.store_limits();
-        store.limiter(|t| &mut t.limits);
+        store.limiter(|t| &mut t.limiter);
 
         // If fuel has been configured, we want to add the configured
         // fuel amount to this store.
@@ -272,7 +358,7 @@ impl ServeCommand {
         Ok(store)
     }
 
-    fn add_to_linker(&self, linker: &mut Linker<Host>) -> Result<()> {
+    fn add_to_linker(&mut self, linker: &mut Linker<Host>) -> Result<()> {
         self.run.validate_p3_option()?;
         let cli = self.run.validate_cli_enabled()?;
 
@@ -336,6 +422,7 @@ impl ServeCommand {
             }
             #[cfg(feature = "wasi-keyvalue")]
             {
+                self.wasi_keyvalue_stores = self.run.wasi_keyvalue_stores()?;
                 wasmtime_wasi_keyvalue::add_to_linker(linker, |h: &mut Host| {
                     WasiKeyValue::new(h.wasi_keyvalue.as_ref().unwrap(), &mut h.table)
                 })?;
@@ -350,6 +437,10 @@ impl ServeCommand {
             bail!("support for wasi-http must be enabled for `serve` subcommand");
         }
 
+        // Stores are created per-request, so check the outgoing request policy
+        // options here to report errors before serving any requests.
+        self.run.wasi_http_ctx()?;
+
         Ok(())
     }
 
@@ -440,7 +531,32 @@ impl ServeCommand {
 
         log::info!("Listening on {}", self.addr);
 
-        let handler = ProxyHandler::new(self, engine, instance);
+        let metrics_listener = match self.metrics_addr {
+            Some(addr) => {
+                let listener = tokio::net::TcpListener::bind(addr).await?;
+                eprintln!("Serving metrics on http://{}/", listener.local_addr()?);
+                Some(listener)
+            }
+            None => None,
+        };
+
+        let access_log: Option<Box<dyn std::io::Write + Send>> = match &self.access_log {
+            Some(path) if path.as_os_str() == "-" => Some(Box::new(std::io::stderr())),
+            Some(path) => Some(Box::new(
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .with_context(|| format!("failed to open access log `{}`", path.display()))?,
+            )),
+            None => None,
+        };
+
+        let handler = ProxyHandler::new(self, engine, instance, access_log);
+
+        if let Some(listener) = metrics_listener {
+            tokio::task::spawn(serve_metrics(listener, handler.clone()));
+        }
 
         loop {
             // Wait for a socket, but also "race" against shutdown to break out
@@ -729,6 +845,9 @@ struct ProxyHandlerInner {
     engine: Engine,
     instance_pre: ProxyPre,
     next_id: AtomicU64,
+    admission: Option<Arc<Semaphore>>,
+    access_log: Option<Mutex<Box<dyn std::io::Write + Send>>>,
+    metrics: Metrics,
 }
 
 enum ProxyPre {
@@ -773,16 +892,307 @@ impl ProxyHandlerInner {
 struct ProxyHandler(Arc<ProxyHandlerInner>);
 
 impl ProxyHandler {
-    fn new(cmd: ServeCommand, engine: Engine, instance_pre: ProxyPre) -> Self {
+    fn new(
+        cmd: ServeCommand,
+        engine: Engine,
+        instance_pre: ProxyPre,
+        access_log: Option<Box<dyn std::io::Write + Send>>,
+    ) -> Self {
+        let admission = cmd
+            .max_concurrent_instances
+            .map(|max| Arc::new(Semaphore::new(max.get())));
         Self(Arc::new(ProxyHandlerInner {
             cmd,
             engine,
             instance_pre,
             next_id: AtomicU64::from(0),
+            admission,
+            access_log: access_log.map(Mutex::new),
+            metrics: Metrics::default(),
         }))
     }
 }
 
+/// Resource usage of the instance which handled a request.
+struct InstanceUsage {
+    /// Only available when fuel is enabled with `-W fuel`.
+    fuel_consumed: Option<u64>,
+    peak_memory: usize,
+    outgoing_requests: u64,
+}
+
+impl InstanceUsage {
+    fn new(cmd: &ServeCommand, store: &Store<Host>) -> InstanceUsage {
+        let host = store.data();
+        InstanceUsage {
+            fuel_consumed: cmd
+                .run
+                .common
+                .wasm
+                .fuel
+                .map(|fuel| fuel.saturating_sub(store.get_fuel().unwrap_or(fuel))),
+            peak_memory: host.limiter.peak_memory,
+            outgoing_requests: host.http.outgoing_requests(),
+        }
+    }
+}
+
+/// Accounting for a single request.
+///
+/// A record is shared between the task producing the response and the task
+/// running the request's instance, which keeps going while the response body
+/// is streamed. Once both are done the record is written to the access log
+/// and added to the aggregate metrics.
+struct RequestRecord {
+    handler: Arc<ProxyHandlerInner>,
+    req_id: u64,
+    method: http::Method,
+    uri: http::Uri,
+    start: Instant,
+    /// Held for as long as the instance runs, if admission is limited.
+    permit: Option<OwnedSemaphorePermit>,
+    admitted: bool,
+    status: Mutex<Option<StatusCode>>,
+    usage: Mutex<Option<InstanceUsage>>,
+}
+
+impl RequestRecord {
+    fn new(handler: &Arc<ProxyHandlerInner>, req_id: u64, req: &Request) -> RequestRecord {
+        RequestRecord {
+            handler: handler.clone(),
+            req_id,
+            method: req.method().clone(),
+            uri: req.uri().clone(),
+            start: Instant::now(),
+            permit: None,
+            admitted: false,
+            status: Mutex::new(None),
+            usage: Mutex::new(None),
+        }
+    }
+
+    /// Attempts to admit this request to run an instance, returning whether
+    /// `--max-concurrent-instances` allows it.
+    fn admit(&mut self) -> bool {
+        if let Some(admission) = &self.handler.admission {
+            match admission.clone().try_acquire_owned() {
+                Ok(permit) => self.permit = Some(permit),
+                Err(_) => return false,
+            }
+        }
+        self.admitted = true;
+        self.handler
+            .metrics
+            .instances_active
+            .fetch_add(1, Ordering::Relaxed);
+        true
+    }
+
+    fn set_status(&self, status: StatusCode) {
+        *self.status.lock().unwrap() = Some(status);
+    }
+
+    fn set_usage(&self, store: &Store<Host>) {
+        *self.usage.lock().unwrap() = Some(InstanceUsage::new(&self.handler.cmd, store));
+    }
+}
+
+impl Drop for RequestRecord {
+    fn drop(&mut self) {
+        use std::fmt::Write;
+
+        let wall_time = self.start.elapsed();
+        // Requests which failed before a response was produced are turned
+        // into a 500 response by the accept loop.
+        let status = self
+            .status
+            .get_mut()
+            .unwrap()
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let usage = self.usage.get_mut().unwrap().take();
+
+        let metrics = &self.handler.metrics;
+        metrics.record(status, wall_time, usage.as_ref());
+        if self.admitted {
+            metrics.instances_active.fetch_sub(1, Ordering::Relaxed);
+        } else {
+            metrics.rejected.fetch_add(1, Ordering::Relaxed);
+        }
+        self.permit.take();
+
+        let Some(access_log) = &self.handler.access_log else {
+            return;
+        };
+        let mut line = format!(
+            "req_id={} method={} uri={} status={} wall_time_us={}",
+            self.req_id,
+            self.method,
+            self.uri,
+            status.as_u16(),
+            wall_time.as_micros(),
+        );
+        if !self.admitted {
+            line.push_str(" rejected=true");
+        }
+        if let Some(usage) = usage {
+            if let Some(fuel) = usage.fuel_consumed {
+                let _ = write!(line, " fuel_consumed={fuel}");
+            }
+            let _ = write!(
+                line,
+                " peak_memory_bytes={} outgoing_requests={}",
+                usage.peak_memory, usage.outgoing_requests,
+            );
+        }
+        line.push('\n');
+        if let Err(e) = access_log.lock().unwrap().write_all(line.as_bytes()) {
+            log::warn!("failed to write access log: {e}");
+        }
+    }
+}
+
+/// Aggregate resource usage of all requests, served at `--metrics-addr`.
+#[derive(Default)]
+struct Metrics {
+    requests: AtomicU64,
+    rejected: AtomicU64,
+    instances_active: AtomicU64,
+    wall_time_us: AtomicU64,
+    fuel_consumed: AtomicU64,
+    outgoing_requests: AtomicU64,
+    peak_memory: AtomicU64,
+    responses: Mutex<BTreeMap<u16, u64>>,
+}
+
+impl Metrics {
+    fn record(&self, status: StatusCode, wall_time: Duration, usage: Option<&InstanceUsage>) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        *self
+            .responses
+            .lock()
+            .unwrap()
+            .entry(status.as_u16())
+            .or_default() += 1;
+        self.wall_time_us.fetch_add(
+            u64::try_from(wall_time.as_micros()).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+        if let Some(usage) = usage {
+            self.fuel_consumed
+                .fetch_add(usage.fuel_consumed.unwrap_or(0), Ordering::Relaxed);
+            self.outgoing_requests
+                .fetch_add(usage.outgoing_requests, Ordering::Relaxed);
+            self.peak_memory
+                .fetch_max(usage.peak_memory as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Renders these metrics in the Prometheus text exposition format.
+    fn render(&self) -> String {
+        use std::fmt::Write;
+
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: &dyn std::fmt::Display| {
+            let _ = writeln!(out, "# HELP wasmtime_serve_{name} {help}");
+            let _ = writeln!(out, "# TYPE wasmtime_serve_{name} {kind}");
+            let _ = writeln!(out, "wasmtime_serve_{name} {value}");
+        };
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+
+        metric(
+            "requests_total",
+            "counter",
+            "Requests handled, including rejected ones.",
+            &load(&self.requests),
+        );
+        metric(
+            "requests_rejected_total",
+            "counter",
+            "Requests rejected because of --max-concurrent-instances.",
+            &load(&self.rejected),
+        );
+        metric(
+            "instances_active",
+            "gauge",
+            "Instances currently running.",
+            &load(&self.instances_active),
+        );
+        metric(
+            "wall_time_seconds_total",
+            "counter",
+            "Wall time spent handling requests.",
+            &(load(&self.wall_time_us) as f64 / 1e6),
+        );
+        metric(
+            "fuel_consumed_total",
+            "counter",
+            "Fuel consumed by instances, when fuel is enabled.",
+            &load(&self.fuel_consumed),
+        );
+        metric(
+            "outgoing_requests_total",
+            "counter",
+            "Outgoing HTTP requests sent by instances.",
+            &load(&self.outgoing_requests),
+        );
+        metric(
+            "instance_peak_memory_bytes",
+            "gauge",
+            "Largest peak linear memory of any instance.",
+            &load(&self.peak_memory),
+        );
+
+        let _ = writeln!(
+            out,
+            "# HELP wasmtime_serve_responses_total Responses sent, by status code."
+        );
+        let _ = writeln!(out, "# TYPE wasmtime_serve_responses_total counter");
+        for (status, count) in self.responses.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "wasmtime_serve_responses_total{{status=\"{status}\"}} {count}"
+            );
+        }
+        out
+    }
+}
+
+/// Serves the aggregate metrics of `handler` to all connections on `listener`.
+async fn serve_metrics(listener: tokio::net::TcpListener, handler: ProxyHandler) {
+    use http_body_util::Full;
+    use hyper::server::conn::http1;
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => TokioIo::new(stream),
+            Err(e) => {
+                eprintln!("error: failed to accept metrics connection: {e:?}");
+                continue;
+            }
+        };
+        let h = handler.clone();
+        tokio::task::spawn(async move {
+            let service = hyper::service::service_fn(move |_req| {
+                let body = h.0.metrics.render();
+                async move {
+                    Ok::<_, Infallible>(
+                        Response::builder()
+                            .header("Content-Type", "text/plain; version=0.0.4")
+                            .body(Full::new(Bytes::from(body)))
+                            .unwrap(),
+                    )
+                }
+            });
+            if let Err(e) = http1::Builder::new()
+                .serve_connection(stream, service)
+                .await
+            {
+                eprintln!("error: {e:?}");
+            }
+        });
+    }
+}
+
 type Request = hyper::Request<hyper::body::Incoming>;
 
 async fn handle_request(
@@ -800,6 +1210,22 @@ async fn handle_request(
         req.uri()
     );
 
+    let mut record = RequestRecord::new(&inner, req_id, &req);
+    if !record.admit() {
+        log::info!("Request {req_id} rejected, too many concurrent instances");
+        record.set_status(StatusCode::SERVICE_UNAVAILABLE);
+        return Ok(Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .header("Content-Type", "text/plain; charset=UTF-8")
+            .body(
+                http_body_util::Full::new(Bytes::from("too many concurrent instances\n"))
+                    .map_err(|_| unreachable!())
+                    .boxed(),
+            )
+            .unwrap());
+    }
+    let record = Arc::new(record);
+
     let mut store = inner.cmd.new_store(&inner.engine, req_id)?;
 
     let (write_profile, epoch_thread) =
@@ -811,12 +1237,14 @@ async fn handle_request(
                 .data_mut()
                 .new_incoming_request(p2::http::types::Scheme::Http, req)?;
             let out = store.data_mut().new_response_outparam(sender)?;
+            let task_record = record.clone();
             let task = tokio::task::spawn(async move {
-                if let Err(e) = proxy
+                let result = proxy
                     .wasi_http_incoming_handler()
                     .call_handle(&mut store, req, out)
-                    .await
-                {
+                    .await;
+                task_record.set_usage(&store);
+                if let Err(e) = result {
                     log::error!("[{req_id}] :: {e:?}");
                     return Err(e);
                 }
@@ -828,7 +1256,10 @@ async fn handle_request(
             });
 
             let result = match receiver.await {
-                Ok(Ok(resp)) => resp,
+                Ok(Ok(resp)) => {
+                    record.set_status(resp.status());
+                    resp
+                }
                 Ok(Err(e)) => bail!(e),
                 Err(_) => {
                     // An error in the receiver (`RecvError`) only indicates that the
@@ -857,6 +1288,7 @@ async fn handle_request(
 
             let (tx, rx) = tokio::sync::oneshot::channel();
 
+            let task_record = record.clone();
             tokio::task::spawn(async move {
                 let guest_result = instance
                     .run_concurrent(&mut store, async move |store| {
@@ -873,8 +1305,9 @@ async fn handle_request(
                         task.block(store).await;
                         anyhow::Ok(())
                     })
-                    .await?;
-                if let Err(e) = guest_result {
+                    .await;
+                task_record.set_usage(&store);
+                if let Err(e) = guest_result? {
                     log::error!("[{req_id}] :: {e:?}");
                     return Err(e);
                 }
@@ -884,7 +1317,9 @@ async fn handle_request(
 
                 anyhow::Ok(())
             });
-            Ok(rx.await?.map(|body| body.map_err(|err| err.into()).boxed()))
+            let res = rx.await?;
+            record.set_status(res.status());
+            Ok(res.map(|body| body.map_err(|err| err.into()).boxed()))
         }
     }
 }
@@ -0,0 +1,157 @@
+//! The module that implements the `wasmtime snapshot` command.
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use std::fs;
+use std::path::PathBuf;
+use wasmtime::{CodeBuilder, CodeHint, Engine, Linker, Preinitializer, Store};
+use wasmtime_cli_flags::CommonOptions;
+use wasmtime_wasi::WasiCtxBuilder;
+use wasmtime_wasi::p1::WasiP1Ctx;
+
+const AFTER_HELP: &str = "The input is instantiated, its initialization function is called, and the\n\
+        resulting state of its memories, tables and globals is written out as a\n\
+        new WebAssembly binary which starts out already initialized.\n\
+        \n\
+        Usage examples:\n\
+        \n\
+        Pre-initializing a module with the default `wizer.initialize` function:\n\
+        \n  \
+        wasmtime snapshot -o initialized.wasm input.wasm\n\
+        \n\
+        Using a different initialization function with WASI available:\n\
+        \n  \
+        wasmtime snapshot --init-func _initialize --allow-wasi -o initialized.wasm input.wasm\n";
+
+/// Pre-initializes a WebAssembly module or component by snapshotting it.
+#[derive(Parser)]
+#[command(
+    version,
+    after_help = AFTER_HELP,
+)]
+pub struct SnapshotCommand {
+    #[command(flatten)]
+    #[expect(missing_docs, reason = "don't want to mess with clap doc-strings")]
+    pub common: CommonOptions,
+
+    /// The name of the exported initialization function to call
+    #[arg(long, value_name = "NAME", default_value = "wizer.initialize")]
+    pub init_func: String,
+
+    /// Keep the initialization function exported from the snapshot
+    #[arg(long)]
+    pub keep_init_func: bool,
+
+    /// Make WASI available to the initialization function, with stdio
+    /// inherited from the host
+    #[arg(long)]
+    pub allow_wasi: bool,
+
+    /// The path of the output WebAssembly binary
+    #[arg(short = 'o', long, value_name = "OUTPUT")]
+    pub output: PathBuf,
+
+    /// The path of the WebAssembly to pre-initialize
+    #[arg(index = 1, value_name = "MODULE")]
+    pub module: PathBuf,
+}
+
+impl SnapshotCommand {
+    /// Executes the command.
+    pub fn execute(mut self) -> Result<()> {
+        self.common.init_logging()?;
+
+        let config = self.common.config(None)?;
+        let engine = Engine::new(&config)?;
+
+        let mut code = CodeBuilder::new(&engine);
+        code.wasm_binary_or_text_file(&self.module)?;
+        let hint = code.hint();
+        let wasm = fs::read(&self.module)
+            .with_context(|| format!("failed to read input: {}", self.module.display()))?;
+
+        let mut preinitializer = Preinitializer::new();
+        preinitializer
+            .init_func(&self.init_func)
+            .keep_init_func(self.keep_init_func);
+
+        let ctx = WasiCtxBuilder::new().inherit_stdio().build_p1();
+        let mut store = Store::new(&engine, ctx);
+
+        let output = match hint {
+            #[cfg(feature = "component-model")]
+            Some(CodeHint::Component) => {
+                let mut linker = wasmtime::component::Linker::new(&engine);
+                if self.allow_wasi {
+                    wasmtime_wasi::p2::add_to_linker_sync(&mut linker)?;
+                }
+                preinitializer.run_component(&mut store, &linker, &wasm)?
+            }
+            #[cfg(not(feature = "component-model"))]
+            Some(CodeHint::Component) => {
+                anyhow::bail!("component model support was disabled at compile time")
+            }
+            Some(CodeHint::Module) | None => {
+                let mut linker = Linker::new(&engine);
+                if self.allow_wasi {
+                    wasmtime_wasi::p1::add_to_linker_sync(&mut linker, |ctx: &mut WasiP1Ctx| ctx)?;
+                }
+                preinitializer.run(&mut store, &linker, &wasm)?
+            }
+        };
+        fs::write(&self.output, output)
+            .with_context(|| format!("failed to write output: {}", self.output.display()))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, not(miri)))]
+mod test {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+    use wasmtime::{Instance, Module};
+
+    #[test]
+    fn test_successful_snapshot() -> Result<()> {
+        let (mut input, input_path) = NamedTempFile::new()?.into_parts();
+        input.write_all(
+            r#"
+                (module
+                    (global $g (mut i32) (i32.const 0))
+                    (func (export "init")
+                        i32.const 1234
+                        global.set $g)
+                    (func (export "f") (result i32)
+                        global.get $g))
+            "#
+            .as_bytes(),
+        )?;
+        drop(input);
+
+        let output_path = NamedTempFile::new()?.into_temp_path();
+
+        let command = SnapshotCommand::try_parse_from(vec![
+            "snapshot",
+            "-Dlogging=n",
+            "--init-func",
+            "init",
+            "-o",
+            output_path.to_str().unwrap(),
+            input_path.to_str().unwrap(),
+        ])?;
+
+        command.execute()?;
+
+        let engine = Engine::default();
+        let module = Module::from_file(&engine, &output_path)?;
+        assert!(module.get_export("init").is_none());
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let f = instance.get_typed_func::<(), i32>(&mut store, "f")?;
+        assert_eq!(f.call(&mut store, ()).unwrap(), 1234);
+
+        Ok(())
+    }
+}
@@ -7,6 +7,7 @@ use std::{fs::File, path::Path, time::Duration};
 use wasmtime::{Engine, Module, Precompiled, StoreLimits, StoreLimitsBuilder};
 use wasmtime_cli_flags::{CommonOptions, opt::WasmtimeOptionValue};
 use wasmtime_wasi::WasiCtxBuilder;
+use wasmtime_wasi::filesystem::MemoryDir;
 
 #[cfg(feature = "component-model")]
 use wasmtime::component::Component;
@@ -85,8 +86,15 @@ pub struct RunCommon {
     /// host is made available within the guest. If specified as `HOST::GUEST`
     /// then the `HOST` directory is opened and made available as the name
     /// `GUEST` in the guest.
+    ///
+    /// Directories which only exist in memory can be provided with
+    /// `mem::GUEST`, or `mem:SIZE::GUEST` to limit their contents to `SIZE`
+    /// bytes. With `overlay:HOST::GUEST` the guest may modify the `HOST`
+    /// directory, but its changes are only recorded in memory and the host
+    /// directory itself is left untouched. In both cases everything written
+    /// by the guest is discarded when it exits.
     #[arg(long = "dir", value_name = "HOST_DIR[::GUEST_DIR]", value_parser = parse_dirs)]
-    pub dirs: Vec<(String, String)>,
+    pub dirs: Vec<(DirSource, String)>,
 
     /// Pass an environment variable to the program.
     ///
@@ -107,14 +115,44 @@ fn parse_env_var(s: &str) -> Result<(String, Option<String>)> {
     ))
 }
 
-fn parse_dirs(s: &str) -> Result<(String, String)> {
+/// Where the contents of a directory passed with `--dir` come from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DirSource {
+    /// A directory on the host.
+    Host(String),
+    /// An empty in-memory directory, optionally limited to a number of bytes.
+    Memory(Option<u64>),
+    /// An in-memory copy-on-write layer on top of a directory on the host.
+    Overlay(String),
+}
+
+fn parse_dirs(s: &str) -> Result<(DirSource, String)> {
     let mut parts = s.split("::");
     let host = parts.next().unwrap();
-    let guest = match parts.next() {
-        Some(guest) => guest,
-        None => host,
+    let guest = parts.next();
+
+    // A plain `--dir mem` still refers to a host directory named `mem`.
+    let memory_size = match host.strip_prefix("mem") {
+        Some("") if guest.is_some() => Some(None),
+        Some(size) => match size.strip_prefix(':') {
+            Some(size) => Some(Some(<u64 as WasmtimeOptionValue>::parse(Some(size))?)),
+            None => None,
+        },
+        None => None,
     };
-    Ok((host.into(), guest.into()))
+    if let Some(size) = memory_size {
+        let Some(guest) = guest else {
+            bail!("in-memory directories must be given a guest path, for example `mem::/tmp`");
+        };
+        return Ok((DirSource::Memory(size), guest.into()));
+    }
+
+    let (source, host) = match host.strip_prefix("overlay:") {
+        Some(host) => (DirSource::Overlay(host.into()), host),
+        None => (DirSource::Host(host.into()), host),
+    };
+    let guest = guest.unwrap_or(host);
+    Ok((source, guest.into()))
 }
 
 impl RunCommon {
@@ -295,13 +333,32 @@ impl RunCommon {
             builder.env(key, &value);
         }
 
-        for (host, guest) in self.dirs.iter() {
-            builder.preopened_dir(
-                host,
-                guest,
-                wasmtime_wasi::DirPerms::all(),
-                wasmtime_wasi::FilePerms::all(),
-            )?;
+        for (source, guest) in self.dirs.iter() {
+            let dir_perms = wasmtime_wasi::DirPerms::all();
+            let file_perms = wasmtime_wasi::FilePerms::all();
+            match source {
+                DirSource::Host(host) => {
+                    builder.preopened_dir(host, guest, dir_perms, file_perms)?;
+                }
+                DirSource::Memory(size) => {
+                    let mut dir = MemoryDir::new();
+                    if let Some(size) = size {
+                        dir = dir.size_limit(*size);
+                    }
+                    builder.preopened_virtual_dir(dir, guest, dir_perms, file_perms);
+                }
+                DirSource::Overlay(host) => {
+                    let lower =
+                        cap_std::fs::Dir::open_ambient_dir(host, cap_std::ambient_authority())
+                            .with_context(|| format!("failed to open directory '{host}'"))?;
+                    builder.preopened_virtual_dir(
+                        MemoryDir::overlay(lower),
+                        guest,
+                        dir_perms,
+                        file_perms,
+                    );
+                }
+            }
         }
 
         if self.common.wasi.listenfd == Some(true) {
@@ -327,6 +384,103 @@ impl RunCommon {
         Ok(())
     }
 
+    /// Opens the key-value stores persisted to files with `-Skeyvalue-file`.
+    #[cfg(feature = "wasi-keyvalue")]
+    pub fn wasi_keyvalue_stores(
+        &self,
+    ) -> Result<
+        Vec<(
+            String,
+            std::sync::Arc<dyn wasmtime_wasi_keyvalue::KeyValueStore>,
+        )>,
+    > {
+        use wasmtime_wasi_keyvalue::FileStore;
+
+        let wasi = &self.common.wasi;
+        let mut stores = Vec::new();
+        for store in &wasi.keyvalue_file {
+            if store.key.is_empty() && !wasi.keyvalue_in_memory_data.is_empty() {
+                bail!(
+                    "`-Skeyvalue-in-memory-data` cannot be used when the default store is a file"
+                );
+            }
+            let file = FileStore::open(&store.value)
+                .with_context(|| format!("failed to open key-value store `{}`", store.value))?;
+            stores.push((store.key.clone(), std::sync::Arc::new(file) as _));
+        }
+        Ok(stores)
+    }
+
+    /// Creates a key-value context using the file-backed `stores`, along with
+    /// a new In-Memory default store unless `stores` replaces it.
+    #[cfg(feature = "wasi-keyvalue")]
+    pub fn wasi_keyvalue_ctx(
+        &self,
+        stores: &[(
+            String,
+            std::sync::Arc<dyn wasmtime_wasi_keyvalue::KeyValueStore>,
+        )],
+    ) -> wasmtime_wasi_keyvalue::WasiKeyValueCtx {
+        use wasmtime_wasi_keyvalue::WasiKeyValueCtxBuilder;
+
+        let mut builder = WasiKeyValueCtxBuilder::new().in_memory_data(
+            self.common
+                .wasi
+                .keyvalue_in_memory_data
+                .iter()
+                .map(|v| (v.key.clone(), v.value.clone())),
+        );
+        for (name, store) in stores {
+            builder = builder.shared_store(name, store.clone());
+        }
+        builder.build()
+    }
+
+    #[cfg(feature = "wasi-http")]
+    pub fn wasi_http_ctx(&self) -> Result<wasmtime_wasi_http::WasiHttpCtx> {
+        use wasmtime_wasi_http::OutgoingPolicy;
+
+        let wasi = &self.common.wasi;
+        let mut policy = OutgoingPolicy::new();
+        for pattern in &wasi.http_outgoing_allow {
+            policy.allow(pattern)?;
+        }
+        for pattern in &wasi.http_outgoing_deny {
+            policy.deny(pattern)?;
+        }
+        for header in &wasi.http_outgoing_header {
+            policy.inject_header(
+                header
+                    .key
+                    .parse()
+                    .with_context(|| format!("invalid header name `{}`", header.key))?,
+                header
+                    .value
+                    .parse()
+                    .with_context(|| format!("invalid value for header `{}`", header.key))?,
+            );
+        }
+        for name in &wasi.http_outgoing_strip_header {
+            policy.strip_header(
+                name.parse()
+                    .with_context(|| format!("invalid header name `{name}`"))?,
+            );
+        }
+        if let Some(max) = wasi.http_outgoing_max_body_size {
+            policy.max_body_size(max);
+        }
+        if let Some(max) = wasi.http_outgoing_max_requests {
+            policy.max_requests(max);
+        }
+        if let Some(max) = wasi.http_outgoing_max_redirects {
+            policy.max_redirects(max);
+        }
+
+        let mut ctx = wasmtime_wasi_http::WasiHttpCtx::new();
+        ctx.set_outgoing_policy(policy);
+        Ok(ctx)
+    }
+
     pub fn compute_preopen_sockets(&self) -> Result<Vec<TcpListener>> {
         let mut listeners = vec![];
 
@@ -429,9 +583,3 @@ impl Profile {
         }
     }
 }
-
-#[derive(Default, Clone)]
-#[cfg(all(feature = "wasi-http", feature = "component-model-async"))]
-pub struct DefaultP3Ctx;
-#[cfg(all(feature = "wasi-http", feature = "component-model-async"))]
-impl wasmtime_wasi_http::p3::WasiHttpCtx for DefaultP3Ctx {}
@@ -157,6 +157,42 @@ fn run_wasmtime_unreachable_wat() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn cache_subcommands() -> Result<()> {
+    let td = TempDir::new()?;
+    let config = td.path().join("config.toml");
+    std::fs::write(
+        &config,
+        format!(
+            "[cache]\ndirectory = '{}'\n",
+            td.path().join("cache").display()
+        ),
+    )?;
+    let config = config.to_str().unwrap();
+    let wasm = build_wasm("tests/all/cli_tests/simple.wat")?;
+    run_wasmtime(&[
+        "run",
+        "--invoke",
+        "get_f32",
+        &format!("-Ccache-config={config}"),
+        wasm.path().to_str().unwrap(),
+    ])?;
+
+    let list = run_wasmtime(&["cache", "list", "--config", config])?;
+    assert_eq!(list.lines().count(), 1, "{list}");
+    let stats = run_wasmtime(&["cache", "stats", "--config", config])?;
+    assert!(stats.contains("misses:      1"), "{stats}");
+
+    run_wasmtime(&["cache", "prune", "--config", config])?;
+    let list = run_wasmtime(&["cache", "list", "--config", config])?;
+    assert_eq!(list.lines().count(), 1, "{list}");
+
+    run_wasmtime(&["cache", "clear", "--config", config])?;
+    let list = run_wasmtime(&["cache", "list", "--config", config])?;
+    assert_eq!(list, "");
+    Ok(())
+}
+
 fn assert_trap_code(status: &ExitStatus) {
     let code = status
         .code()
@@ -1288,6 +1324,46 @@ mod test_programs {
         Ok(())
     }
 
+    #[test]
+    fn cli_file_read_overlay() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        std::fs::write(dir.path().join("bar.txt"), b"And stood awhile in thought")?;
+
+        run_wasmtime(&[
+            "run",
+            "-Wcomponent-model",
+            &format!("--dir=overlay:{}::/", dir.path().to_str().unwrap()),
+            CLI_FILE_READ_COMPONENT,
+        ])?;
+        Ok(())
+    }
+
+    #[test]
+    fn cli_file_append_overlay() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        std::fs::write(
+            dir.path().join("bar.txt"),
+            b"'Twas brillig, and the slithy toves.\n",
+        )?;
+
+        run_wasmtime(&[
+            "run",
+            "-Wcomponent-model",
+            &format!("--dir=overlay:{}::/", dir.path().to_str().unwrap()),
+            CLI_FILE_APPEND_COMPONENT,
+        ])?;
+
+        // The guest's writes only went to the in-memory layer.
+        let contents = std::fs::read(dir.path().join("bar.txt"))?;
+        assert_eq!(
+            std::str::from_utf8(&contents).unwrap(),
+            "'Twas brillig, and the slithy toves.\n"
+        );
+        Ok(())
+    }
+
     #[test]
     fn cli_exit_success() -> Result<()> {
         run_wasmtime(&["run", "-Wcomponent-model", CLI_EXIT_SUCCESS_COMPONENT])?;
@@ -1512,6 +1588,7 @@ mod test_programs {
         stderr: Option<JoinHandle<io::Result<Vec<u8>>>>,
         addr: SocketAddr,
         shutdown_addr: SocketAddr,
+        metrics_addr: Option<SocketAddr>,
     }
 
     impl WasmtimeServe {
@@ -1534,13 +1611,16 @@ mod test_programs {
             cmd.stdin(Stdio::null());
             cmd.stdout(Stdio::piped());
             cmd.stderr(Stdio::piped());
+            let serves_metrics = cmd
+                .get_args()
+                .any(|arg| arg.to_string_lossy().starts_with("--metrics-addr"));
             let mut child = cmd.spawn()?;
 
             // Read the first few lines of stderr which will say which address
             // it's listening on. The first line is the shutdown line (with
-            // `--shutdown-addr`) and the second is what `--addr` was bound to.
-            // This is done to figure out what `:0` was bound to in the child
-            // process.
+            // `--shutdown-addr`) and the second is what `--addr` was bound to,
+            // followed by `--metrics-addr` if it's used. This is done to
+            // figure out what `:0` was bound to in the child process.
             let mut line = String::new();
             let mut stderr = BufReader::new(child.stderr.take().unwrap());
             let mut read_addr_from_line = |prefix: &str| -> Result<SocketAddr> {
@@ -1563,11 +1643,14 @@ mod test_programs {
             };
             let shutdown_addr = read_addr_from_line("Listening for shutdown");
             let addr = read_addr_from_line("Serving HTTP on");
-            let (shutdown_addr, addr) = match (shutdown_addr, addr) {
-                (Ok(a), Ok(b)) => (a, b),
-                // If either failed kill the child and otherwise try to shepherd
+            let metrics_addr = serves_metrics
+                .then(|| read_addr_from_line("Serving metrics on"))
+                .transpose();
+            let (shutdown_addr, addr, metrics_addr) = match (shutdown_addr, addr, metrics_addr) {
+                (Ok(a), Ok(b), Ok(c)) => (a, b, c),
+                // If any failed kill the child and otherwise try to shepherd
                 // along any contextual information we have.
-                (Err(a), _) | (_, Err(a)) => {
+                (Err(a), _, _) | (_, Err(a), _) | (_, _, Err(a)) => {
                     child.kill()?;
                     child.wait()?;
                     stderr.read_to_string(&mut line)?;
@@ -1589,6 +1672,7 @@ mod test_programs {
                 child: Some(child),
                 addr,
                 shutdown_addr,
+                metrics_addr,
             })
         }
 
@@ -1647,13 +1731,36 @@ mod test_programs {
             Ok(http::Response::from_parts(parts, body))
         }
 
+        /// Fetch the body served at `--metrics-addr`.
+        async fn metrics(&self) -> Result<String> {
+            let addr = self.metrics_addr.context("server doesn't serve metrics")?;
+            let (mut send, conn_task) = Self::connect(addr).await?;
+            let response = send
+                .send_request(http::Request::new(String::new()))
+                .await
+                .context("error sending metrics request")?;
+            drop(send);
+            let body = response.into_body().collect().await?.to_bytes();
+            conn_task.await??;
+            Ok(std::str::from_utf8(&body)?.to_string())
+        }
+
         async fn start_requests(
             &self,
         ) -> Result<(
             hyper::client::conn::http1::SendRequest<String>,
             tokio::task::JoinHandle<hyper::Result<()>>,
         )> {
-            let tcp = TcpStream::connect(&self.addr)
+            Self::connect(self.addr).await
+        }
+
+        async fn connect(
+            addr: SocketAddr,
+        ) -> Result<(
+            hyper::client::conn::http1::SendRequest<String>,
+            tokio::task::JoinHandle<hyper::Result<()>>,
+        )> {
+            let tcp = TcpStream::connect(&addr)
                 .await
                 .context("failed to connect")?;
             let tcp = wasmtime_wasi_http::io::TokioIo::new(tcp);
@@ -1759,6 +1866,45 @@ mod test_programs {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn cli_serve_outgoing_policy_config() -> Result<()> {
+        let server = WasmtimeServe::new(CLI_SERVE_ECHO_ENV_COMPONENT, |cmd| {
+            cmd.arg("-Scli");
+            cmd.arg("-Shttp-outgoing-allow=https://*.example.com");
+            cmd.arg("-Shttp-outgoing-deny=http://*");
+            cmd.arg("-Shttp-outgoing-header=x-tenant=acme");
+            cmd.arg("-Shttp-outgoing-strip-header=cookie");
+            cmd.arg("-Shttp-outgoing-max-body-size=1024");
+            cmd.arg("-Shttp-outgoing-max-requests=10");
+            cmd.arg("-Shttp-outgoing-max-redirects=3");
+        })?;
+
+        let resp = server
+            .send_request(
+                hyper::Request::builder()
+                    .uri("http://localhost/")
+                    .header("env", "FOO")
+                    .body(String::new())
+                    .context("failed to make request")?,
+            )
+            .await?;
+        assert!(resp.status().is_success());
+        server.finish()?;
+
+        // Invalid policies are reported before serving any requests.
+        let err = WasmtimeServe::new(CLI_SERVE_ECHO_ENV_COMPONENT, |cmd| {
+            cmd.arg("-Scli");
+            cmd.arg("-Shttp-outgoing-allow=ftp://example.com");
+        })
+        .err()
+        .context("server should have failed to start")?;
+        assert!(
+            format!("{err:?}").contains("unsupported scheme `ftp`"),
+            "{err:?}"
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     #[ignore] // TODO: printing stderr in the child and killing the child at the
     // end of this test race so the stderr may be present or not. Need
@@ -1840,6 +1986,54 @@ mod test_programs {
     // server while the request is still processing. The port is then rebound
     // in the next process while it technically could be stolen by another
     // process.
+    #[tokio::test]
+    async fn cli_serve_accounting() -> Result<()> {
+        let server = WasmtimeServe::new(CLI_SERVE_ECHO_ENV_COMPONENT, |cmd| {
+            cmd.arg("-Scli");
+            cmd.arg("-Wfuel=1000000000");
+            cmd.arg("--env=FOO=bar");
+            cmd.arg("--access-log=-");
+            cmd.arg("--metrics-addr=127.0.0.1:0");
+        })?;
+
+        let resp = server
+            .send_request(
+                hyper::Request::builder()
+                    .uri("http://localhost/")
+                    .header("env", "FOO")
+                    .body(String::new())
+                    .context("failed to make request")?,
+            )
+            .await?;
+        assert!(resp.status().is_success());
+
+        // The instance may still be finishing up after the response is sent,
+        // so wait for the request to be accounted for.
+        let metrics = loop {
+            let metrics = server.metrics().await?;
+            if metrics.contains("wasmtime_serve_requests_total 1\n") {
+                break metrics;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        };
+        assert!(metrics.contains("wasmtime_serve_responses_total{status=\"200\"} 1\n"));
+        assert!(metrics.contains("wasmtime_serve_instances_active 0\n"));
+        assert!(metrics.contains("wasmtime_serve_outgoing_requests_total 0\n"));
+        assert!(!metrics.contains("wasmtime_serve_fuel_consumed_total 0\n"));
+
+        let (_, stderr) = server.finish()?;
+        let line = stderr
+            .lines()
+            .find(|line| line.starts_with("req_id=0 "))
+            .context("no access log line")?;
+        assert!(line.contains(" method=GET "), "{line}");
+        assert!(line.contains(" status=200 "), "{line}");
+        assert!(line.contains(" fuel_consumed="), "{line}");
+        assert!(line.contains(" outgoing_requests=0"), "{line}");
+        assert!(!line.contains(" peak_memory_bytes=0 "), "{line}");
+        Ok(())
+    }
+
     #[tokio::test]
     async fn cli_serve_quick_rebind_allowed() -> Result<()> {
         let wasm = CLI_SERVE_ECHO_ENV_COMPONENT;
@@ -2090,6 +2284,36 @@ start a print 1234
         Ok(())
     }
 
+    #[test]
+    fn cli_keyvalue_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("store");
+        let file = format!("-Skeyvalue-file=other={}", path.display());
+        run_wasmtime(&[
+            "run",
+            "-Skeyvalue",
+            "-Skeyvalue-in-memory-data=atomics_key=5",
+            &file,
+            KEYVALUE_MAIN_COMPONENT,
+        ])?;
+        assert!(std::fs::read(&path)?.starts_with(b"wkvlog"));
+
+        let file = format!("-Skeyvalue-file=={}", path.display());
+        let err = run_wasmtime(&[
+            "run",
+            "-Skeyvalue",
+            "-Skeyvalue-in-memory-data=atomics_key=5",
+            &file,
+            KEYVALUE_MAIN_COMPONENT,
+        ])
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("default store is a file"),
+            "{err:?}"
+        );
+        Ok(())
+    }
+
     #[test]
     fn cli_multiple_preopens() -> Result<()> {
         run_wasmtime(&[
@@ -2102,6 +2326,18 @@ start a print 1234
         Ok(())
     }
 
+    #[test]
+    fn cli_memory_preopens() -> Result<()> {
+        run_wasmtime(&[
+            "run",
+            "--dir=mem::/a",
+            "--dir=mem:0x10000::/b",
+            "--dir=overlay:/::/c",
+            CLI_MULTIPLE_PREOPENS_COMPONENT,
+        ])?;
+        Ok(())
+    }
+
     async fn cli_serve_guest_never_invoked_set(wasm: &str) -> Result<()> {
         let server = WasmtimeServe::new(wasm, |cmd| {
             cmd.arg("-Scli");
@@ -2271,6 +2507,124 @@ fn settings_command() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[cfg_attr(not(all(feature = "explore", feature = "component-model")), ignore)]
+fn explore_component() -> Result<()> {
+    // Skip this test on platforms that Cranelift doesn't support.
+    if cranelift_native::builder().is_err() {
+        return Ok(());
+    }
+    let dir = tempfile::tempdir()?;
+    let output = dir.path().join("component.html");
+    run_wasmtime(&[
+        "explore",
+        "tests/all/cli_tests/component-adapter.wat",
+        "-o",
+        output.to_str().unwrap(),
+    ])?;
+    let html = std::fs::read_to_string(&output)?;
+
+    // The nested core modules, the fused adapter between the two nested
+    // components and the lowered host import are all linked to WIT.
+    for expected in [
+        r#""name":"wasm[0]::function[0]","demangled_name":null,"kind":"function","wit_name":"foo:bar/baz#add""#,
+        r#""name":"wasm[1]::function[2]","demangled_name":null,"kind":"function","wit_name":"run""#,
+        r#""name":"wasm[2]::function[1]","demangled_name":null,"kind":"adapter","wit_name":"foo:bar/baz#add""#,
+        r#""name":"component-lower-import[0]_wasm_call","demangled_name":null,"kind":"trampoline","wit_name":"host:log/log#log""#,
+        ";; fused adapters generated by Wasmtime in wasm[2]",
+    ] {
+        assert!(html.contains(expected), "missing {expected}");
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(not(feature = "explore"), ignore)]
+fn explore_diff() -> Result<()> {
+    // Skip this test on platforms that Cranelift doesn't support.
+    if cranelift_native::builder().is_err() {
+        return Ok(());
+    }
+    let dir = tempfile::tempdir()?;
+    let output = dir.path().join("diff.html");
+    run_wasmtime(&[
+        "explore",
+        "tests/all/cli_tests/simple.wat",
+        "--diff=-Oopt-level=0 -Ccache=n",
+        "-o",
+        output.to_str().unwrap(),
+    ])?;
+    let html = std::fs::read_to_string(&output)?;
+    assert!(html.contains("window.DIFF = "));
+    assert!(html.contains(r#""labels":["baseline","-Oopt-level=0 -Ccache=n"]"#));
+    assert!(html.contains(r#""func_index":0"#));
+    // Both compilations have instructions for the same Wasm offset.
+    assert!(html.contains(r#""instructions":[[{"wasm_offset":"#));
+    assert!(html.contains(r#"}],[{"wasm_offset":"#));
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(not(feature = "objdump"), ignore)]
+fn objdump_json() -> Result<()> {
+    // Skip this test on platforms that Cranelift doesn't support.
+    if cranelift_native::builder().is_err() {
+        return Ok(());
+    }
+    let td = TempDir::new()?;
+    let wat = td.path().join("load.wat");
+    std::fs::write(
+        &wat,
+        r#"
+            (module
+                (memory 1)
+                (func (export "load") (param i32) (result i32)
+                    (i32.load (local.get 0))))
+        "#,
+    )?;
+    let cwasm = td.path().join("load.cwasm");
+    run_wasmtime(&[
+        "compile",
+        wat.to_str().unwrap(),
+        "-o",
+        cwasm.to_str().unwrap(),
+    ])?;
+    let stdout = run_wasmtime(&["objdump", "--format", "json", cwasm.to_str().unwrap()])?;
+    let json: serde_json::Value = serde_json::from_str(&stdout)?;
+
+    let functions = json["functions"].as_array().unwrap();
+    assert_eq!(functions.len(), 1);
+    let func = &functions[0];
+    assert_eq!(func["name"], "wasm[0]::function[0]");
+    assert_eq!(func["kind"], "wasm");
+
+    // The instructions cover the whole function.
+    let instructions = func["instructions"].as_array().unwrap();
+    let size = instructions
+        .iter()
+        .map(|inst| inst["bytes"].as_str().unwrap().len() / 2)
+        .sum::<usize>();
+    assert_eq!(func["size"].as_u64(), Some(size as u64));
+    assert!(
+        instructions
+            .iter()
+            .all(|inst| inst["disassembly"].is_string())
+    );
+
+    // The load is annotated with its Wasm offset and can trap.
+    assert!(
+        instructions
+            .iter()
+            .any(|inst| inst["wasm_offsets"].is_array())
+    );
+    assert!(
+        instructions
+            .iter()
+            .any(|inst| inst["traps"][0] == "MemoryOutOfBounds")
+    );
+    Ok(())
+}
+
 #[cfg(target_arch = "x86_64")]
 #[test]
 fn profile_with_vtune() -> Result<()> {
@@ -0,0 +1,47 @@
+(component
+  (import "host:log/log" (instance $log
+    (export "log" (func (param "msg" u32)))
+  ))
+  (component $A
+    (core module $m
+      (func (export "foo:bar/baz#add") (param i32 i32) (result i32)
+        local.get 0
+        local.get 1
+        i32.add)
+    )
+    (core instance $i (instantiate $m))
+    (func $add (param "a" u32) (param "b" u32) (result u32)
+      (canon lift (core func $i "foo:bar/baz#add")))
+    (instance $baz (export "add" (func $add)))
+    (export "foo:bar/baz" (instance $baz))
+  )
+  (component $B
+    (import "foo:bar/baz" (instance $baz
+      (export "add" (func (param "a" u32) (param "b" u32) (result u32)))
+    ))
+    (import "host:log/log" (instance $log
+      (export "log" (func (param "msg" u32)))
+    ))
+    (core func $add (canon lower (func $baz "add")))
+    (core func $log (canon lower (func $log "log")))
+    (core module $n
+      (import "foo:bar/baz" "add" (func $add (param i32 i32) (result i32)))
+      (import "host:log/log" "log" (func $log (param i32)))
+      (func (export "run") (result i32)
+        (call $log (i32.const 1))
+        (call $add (i32.const 1) (i32.const 2)))
+    )
+    (core instance $j (instantiate $n
+      (with "foo:bar/baz" (instance (export "add" (func $add))))
+      (with "host:log/log" (instance (export "log" (func $log))))
+    ))
+    (func $run (result u32) (canon lift (core func $j "run")))
+    (export "run" (func $run))
+  )
+  (instance $a (instantiate $A))
+  (instance $b (instantiate $B
+    (with "foo:bar/baz" (instance $a "foo:bar/baz"))
+    (with "host:log/log" (instance $log))
+  ))
+  (export "run" (func $b "run"))
+)
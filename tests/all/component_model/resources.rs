@@ -688,6 +688,102 @@ fn dynamic_val() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn host_dynamic_resources() -> Result<()> {
+    let engine = super::engine();
+    let c = Component::new(
+        &engine,
+        r#"
+            (component
+                (import "t" (type $t (sub resource)))
+                (import "u" (type $u (sub resource)))
+                (core func $drop (canon resource.drop $t))
+
+                (core module $m
+                    (import "" "drop" (func $drop (param i32)))
+                    (func (export "pass") (param i32) (result i32)
+                        (local.get 0))
+                    (func (export "drop") (param i32)
+                        (call $drop (local.get 0))))
+                (core instance $i (instantiate $m
+                    (with "" (instance (export "drop" (func $drop))))
+                ))
+
+                (func (export "a") (param "x" (own $t)) (result (own $t))
+                    (canon lift (core func $i "pass")))
+                (func (export "drop") (param "x" (own $t))
+                    (canon lift (core func $i "drop")))
+                (export "t" (type $t))
+                (export "u" (type $u))
+            )
+        "#,
+    )?;
+
+    struct MyType;
+    assert_eq!(ResourceType::host_dynamic(1), ResourceType::host_dynamic(1));
+    assert!(ResourceType::host_dynamic(1) != ResourceType::host_dynamic(2));
+    assert!(ResourceType::host_dynamic(0) != ResourceType::host::<MyType>());
+
+    let mut store = Store::new(&engine, Vec::new());
+    let mut linker = Linker::<Vec<u32>>::new(&engine);
+    linker
+        .root()
+        .resource("t", ResourceType::host_dynamic(1), |mut cx, rep| {
+            cx.data_mut().push(rep);
+            Ok(())
+        })?;
+    linker
+        .root()
+        .resource("u", ResourceType::host_dynamic(2), |_, _| Ok(()))?;
+    let i = linker.instantiate(&mut store, &c)?;
+    assert_eq!(
+        i.get_resource(&mut store, "t"),
+        Some(ResourceType::host_dynamic(1))
+    );
+    assert_eq!(
+        i.get_resource(&mut store, "u"),
+        Some(ResourceType::host_dynamic(2))
+    );
+
+    let a = i.get_func(&mut store, "a").unwrap();
+    let drop = i.get_func(&mut store, "drop").unwrap();
+
+    // Round-trip an owned resource through the guest.
+    let t = ResourceDynamic::new_own(100, 1).try_into_resource_any(&mut store)?;
+    let mut results = [Val::Bool(false)];
+    a.call(&mut store, &[Val::Resource(t)], &mut results)?;
+    a.post_return(&mut store)?;
+    let Val::Resource(t) = results[0] else {
+        unreachable!()
+    };
+    assert_eq!(t.ty(), ResourceType::host_dynamic(1));
+    assert!(t.owned());
+    let t = ResourceDynamic::try_from_resource_any(t, 1, &mut store)?;
+    assert_eq!(t, ResourceDynamic::new_own(100, 1));
+
+    // The guest dropping an owned resource runs the linker's destructor.
+    let t = t.try_into_resource_any(&mut store)?;
+    drop.call(&mut store, &[Val::Resource(t)], &mut [])?;
+    drop.post_return(&mut store)?;
+    assert_eq!(store.data(), &[100]);
+
+    // Resources of the wrong type are rejected, which poisons the instance.
+    let u = ResourceDynamic::new_own(200, 2).try_into_resource_any(&mut store)?;
+    let err = a
+        .call(&mut store, &[Val::Resource(u)], &mut results)
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("mismatched resource types"),
+        "{err:?}"
+    );
+    assert!(ResourceDynamic::try_from_resource_any(u, 1, &mut store).is_err());
+    let u = ResourceDynamic::try_from_resource_any(u, 2, &mut store)?;
+    assert_eq!(u.rep(), 200);
+    assert_eq!(u.ty(), 2);
+
+    Ok(())
+}
+
 #[test]
 fn cannot_reenter_during_import() -> Result<()> {
     let engine = super::engine();
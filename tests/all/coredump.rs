@@ -71,6 +71,107 @@ fn coredump_has_stack() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[cfg_attr(miri, ignore)]
+fn coredump_has_frame_state() -> Result<()> {
+    let _ = env_logger::try_init();
+    let mut config = Config::default();
+    config.coredump_on_trap(true).coredump_frame_state(true);
+    let engine = Engine::new(&config).unwrap();
+    let mut store = Store::<()>::new(&engine, ());
+
+    let wat = r#"
+      (module
+          (func $a (export "a") (param i32)
+              (local f64)
+              f64.const 1.5
+              local.set 1
+              i64.const 7
+              local.get 0
+              call $b
+              drop
+              drop
+          )
+          (func $b (param i32) (result i64)
+              (local i64 v128)
+              i64.const 42
+              local.set 1
+              f32.const 2.5
+              i32.const 10
+              local.get 0
+              i32.div_u
+              drop
+              drop
+              local.get 1
+          )
+      )
+    "#;
+
+    let module = Module::new(store.engine(), wat)?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let a_func = instance.get_typed_func::<i32, ()>(&mut store, "a")?;
+
+    let e = a_func.call(&mut store, 0).unwrap_err();
+    let cd = e.downcast_ref::<WasmCoreDump>().unwrap();
+    assert_eq!(cd.frames().len(), 2);
+
+    let vals = |vals: &[Option<Val>]| {
+        vals.iter()
+            .map(|v| match v {
+                Some(Val::I32(x)) => Some(i64::from(*x)),
+                Some(Val::I64(x)) => Some(*x),
+                Some(Val::F32(x)) => Some(i64::from(f32::from_bits(*x) as i32)),
+                Some(Val::F64(x)) => Some(f64::from_bits(*x) as i64),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // The trapping frame has its operands as of the `i32.div_u`, and the
+    // `v128` local isn't preserved.
+    assert_eq!(vals(cd.frame_locals(0)), [Some(0), Some(42), None]);
+    assert_eq!(
+        vals(cd.frame_operand_stack(0)),
+        [Some(2), Some(10), Some(0)]
+    );
+
+    // The calling frame has its operands as of the `call`.
+    assert_eq!(vals(cd.frame_locals(1)), [Some(0), Some(1)]);
+    assert_eq!(vals(cd.frame_operand_stack(1)), [Some(7), Some(0)]);
+
+    let _ = cd.serialize(&mut store, "frame_state");
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn coredump_frame_state_disabled_by_default() -> Result<()> {
+    let mut config = Config::default();
+    config.coredump_on_trap(true);
+    let engine = Engine::new(&config).unwrap();
+    let mut store = Store::<()>::new(&engine, ());
+
+    let wat = r#"
+      (module
+          (func (export "a") (param i32)
+              i32.const 1
+              unreachable
+          )
+      )
+    "#;
+
+    let module = Module::new(store.engine(), wat)?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let a_func = instance.get_typed_func::<i32, ()>(&mut store, "a")?;
+
+    let e = a_func.call(&mut store, 0).unwrap_err();
+    let cd = e.downcast_ref::<WasmCoreDump>().unwrap();
+    assert_eq!(cd.frames().len(), 1);
+    assert!(cd.frame_locals(0).is_empty());
+    assert!(cd.frame_operand_stack(0).is_empty());
+    Ok(())
+}
+
 #[test]
 #[cfg_attr(miri, ignore)]
 fn coredump_has_modules_and_instances() -> Result<()> {
@@ -259,3 +360,55 @@ fn multiple_globals_memories_and_instances() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn coredump_has_component_instances() -> Result<()> {
+    use wasmtime::component::{Component, Linker};
+
+    let mut config = Config::default();
+    config.coredump_on_trap(true);
+    let engine = Engine::new(&config)?;
+    let mut store = Store::<()>::new(&engine, ());
+
+    let component = Component::new(
+        &engine,
+        r#"
+            (component
+                (core module $m
+                    (memory (export "memory") 1)
+                    (func (export "run")
+                        unreachable
+                    )
+                )
+                (core instance $i (instantiate $m))
+                (func (export "run")
+                    (canon lift (core func $i "run"))
+                )
+            )
+        "#,
+    )?;
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    let err = run.call(&mut store, ()).unwrap_err();
+    let core_dump = err.downcast_ref::<WasmCoreDump>().unwrap();
+
+    assert_eq!(core_dump.frames().len(), 1);
+    assert_eq!(core_dump.instances().len(), 1);
+    assert_eq!(core_dump.component_instances().len(), 1);
+    let component_instance = &core_dump.component_instances()[0];
+    assert_eq!(component_instance.core_instances().len(), 1);
+    assert_eq!(component_instance.functions().len(), 1);
+    assert_eq!(core_dump.frame_component_function(0), Some("run"));
+
+    let bytes = core_dump.serialize(&mut store, "component");
+    let mut found = false;
+    for payload in wasmparser::Parser::new(0).parse_all(&bytes) {
+        if let wasmparser::Payload::CustomSection(section) = payload? {
+            found |= section.name() == "componentinstances";
+        }
+    }
+    assert!(found);
+
+    Ok(())
+}
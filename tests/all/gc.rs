@@ -1802,3 +1802,359 @@ fn array_init_elem_oom() -> Result<()> {
 
     Ok(())
 }
+
+fn copying_config() -> Config {
+    let mut config = Config::new();
+    config.wasm_function_references(true);
+    config.wasm_gc(true);
+    config.collector(Collector::Copying);
+    config
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn copying_collects_cycles() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let engine = Engine::new(&copying_config())?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $node (struct (field externref) (field (mut (ref null $node)))))
+                (func (export "make-cycle") (param externref)
+                    (local $a (ref $node))
+                    (local $b (ref $node))
+                    (local.set $a (struct.new $node (local.get 0) (ref.null $node)))
+                    (local.set $b (struct.new $node (local.get 0) (local.get $a)))
+                    (struct.set $node 1 (local.get $a) (local.get $b))
+                )
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let make_cycle =
+        instance.get_typed_func::<Option<Rooted<ExternRef>>, ()>(&mut store, "make-cycle")?;
+
+    let num_refs_dropped = Arc::new(AtomicUsize::new(0));
+    let len = 10;
+    for _ in 0..len {
+        let mut scope = RootScope::new(&mut store);
+        let externref = ExternRef::new(&mut scope, CountDrops(num_refs_dropped.clone()))?;
+        make_cycle.call(&mut scope, Some(externref))?;
+    }
+    assert_eq!(num_refs_dropped.load(SeqCst), 0);
+
+    // Unlike the DRC collector, the copying collector reclaims the cycles and
+    // the `externref`s they reference.
+    store.gc(None);
+    assert_eq!(num_refs_dropped.load(SeqCst), len);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn copying_live_objects_survive_collections_triggered_by_wasm() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let mut config = copying_config();
+    config.memory_reservation(1 << 16);
+    config.memory_reservation_for_growth(0);
+    config.memory_guard_size(0);
+    config.memory_may_move(false);
+
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $cons (struct (field i32) (field (ref null $cons))))
+                (type $garbage (array i64))
+                (global $list (mut (ref null $cons)) (ref.null $cons))
+
+                ;; Build two lists of `n` elements, one held in a global and
+                ;; one in a local, while allocating lots of garbage in between
+                ;; so that the GC heap fills up and collections happen.
+                (func (export "run") (param $n i32) (result i32)
+                    (local $i i32)
+                    (local $local (ref null $cons))
+                    (local $sum i32)
+                    (loop $build
+                        (drop (array.new_default $garbage (i32.const 100)))
+                        (global.set $list (struct.new $cons (local.get $i) (global.get $list)))
+                        (drop (array.new_default $garbage (i32.const 100)))
+                        (local.set $local (struct.new $cons (local.get $i) (local.get $local)))
+                        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                        (br_if $build (i32.lt_u (local.get $i) (local.get $n)))
+                    )
+
+                    ;; Sum up both lists.
+                    (block $done
+                        (loop $sum_global
+                            (br_if $done (ref.is_null (global.get $list)))
+                            (local.set $sum
+                                (i32.add (local.get $sum)
+                                    (struct.get $cons 0 (global.get $list))))
+                            (global.set $list (struct.get $cons 1 (global.get $list)))
+                            (br $sum_global)
+                        )
+                    )
+                    (block $done
+                        (loop $sum_local
+                            (br_if $done (ref.is_null (local.get $local)))
+                            (local.set $sum
+                                (i32.add (local.get $sum)
+                                    (struct.get $cons 0 (local.get $local))))
+                            (local.set $local (struct.get $cons 1 (local.get $local)))
+                            (br $sum_local)
+                        )
+                    )
+                    (local.get $sum)
+                )
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<i32, i32>(&mut store, "run")?;
+
+    // The garbage allocated in total is many times larger than the GC heap, so
+    // this only succeeds if collections free it and preserve the lists.
+    let n = 200;
+    assert_eq!(run.call(&mut store, n)?, n * (n - 1));
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn copying_host_roots_are_updated() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let engine = Engine::new(&copying_config())?;
+    let mut store = Store::new(&engine, ());
+
+    let struct_ty = StructType::new(
+        &engine,
+        [
+            FieldType::new(Mutability::Const, StorageType::I8),
+            FieldType::new(Mutability::Const, ValType::EXTERNREF.into()),
+        ],
+    )?;
+    let pre = StructRefPre::new(&mut store, struct_ty);
+
+    // Allocate garbage before `live`, so that collecting moves `live` and its
+    // `externref` to the start of the GC heap, and its roots must follow.
+    for _ in 0..100 {
+        let mut scope = RootScope::new(&mut store);
+        StructRef::new(&mut scope, &pre, &[Val::I32(0), Val::null_extern_ref()])?;
+    }
+
+    let dropped = Arc::new(AtomicBool::new(false));
+    let live = {
+        let mut scope = RootScope::new(&mut store);
+        let externref = ExternRef::new(&mut scope, SetFlagOnDrop(dropped.clone()))?;
+        let live = StructRef::new(&mut scope, &pre, &[Val::I32(42), externref.into()])?;
+        live.to_owned_rooted(&mut scope)?
+    };
+
+    store.gc(None);
+    store.gc(None);
+
+    assert!(!dropped.load(SeqCst));
+    assert_eq!(live.field(&mut store, 0)?.unwrap_i32(), 42);
+    {
+        let mut scope = RootScope::new(&mut store);
+        let externref = live.field(&mut scope, 1)?;
+        let externref = externref.unwrap_externref().unwrap();
+        assert!(
+            externref
+                .data(&scope)?
+                .unwrap()
+                .downcast_ref::<SetFlagOnDrop>()
+                .is_some()
+        );
+    }
+
+    drop(live);
+    store.gc(None);
+    assert!(dropped.load(SeqCst));
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn gc_heap_stats() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let mut config = Config::new();
+    config.wasm_function_references(true);
+    config.wasm_gc(true);
+    let engine = Engine::new(&config)?;
+
+    let mut store = Store::new(&engine, ());
+    let stats = store.gc_heap_stats();
+    assert_eq!(stats.capacity(), 0);
+    assert_eq!(stats.live_objects(), 0);
+    assert_eq!(stats.collections(), 0);
+
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $point (struct (field i32) (field i32)))
+                (type $points (array (mut (ref null $point))))
+                (global $g (mut (ref null $points)) (ref.null $points))
+                (func (export "run") (param $n i32)
+                    (local $i i32)
+                    (global.set $g (array.new_default $points (local.get $n)))
+                    (loop $fill
+                        (array.set $points (global.get $g) (local.get $i)
+                            (struct.new $point (local.get $i) (local.get $i)))
+                        ;; Unreachable garbage.
+                        (drop (struct.new $point (i32.const 0) (i32.const 0)))
+                        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                        (br_if $fill (i32.lt_u (local.get $i) (local.get $n)))
+                    )
+                )
+            )
+        "#,
+    )?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<i32, ()>(&mut store, "run")?;
+    run.call(&mut store, 10)?;
+
+    // Only the objects reachable from the global are live.
+    let stats = store.gc_heap_stats();
+    assert!(stats.capacity() > 0);
+    assert_eq!(stats.live_objects(), 11);
+    assert_eq!(stats.types().len(), 2);
+    assert_eq!(
+        stats.types().iter().map(|t| t.bytes()).sum::<usize>(),
+        stats.live_bytes()
+    );
+    let points = stats
+        .types()
+        .iter()
+        .find(|t| matches!(t.ty(), GcObjectType::Struct(_)))
+        .unwrap();
+    assert_eq!(points.objects(), 10);
+    assert!(points.bytes() >= 10 * 8);
+    let array = stats
+        .types()
+        .iter()
+        .find(|t| matches!(t.ty(), GcObjectType::Array(_)))
+        .unwrap();
+    assert_eq!(array.objects(), 1);
+
+    let collections = stats.collections();
+    store.gc(None);
+    store.gc(None);
+    let stats = store.gc_heap_stats();
+    assert_eq!(stats.collections(), collections + 2);
+    assert!(stats.total_pause() >= stats.max_pause());
+    assert!(stats.max_pause() >= stats.last_pause());
+    assert_eq!(stats.live_objects(), 11);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn gc_heap_snapshot() -> Result<()> {
+    let _ = env_logger::try_init();
+
+    let mut config = Config::new();
+    config.wasm_function_references(true);
+    config.wasm_gc(true);
+    let engine = Engine::new(&config)?;
+
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $cons (struct (field externref) (field (ref null $cons))))
+                (global (export "g") (mut (ref null $cons)) (ref.null $cons))
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let global = instance.get_global(&mut store, "g").unwrap();
+    let struct_ty = global.ty(&store).content().unwrap_ref().heap_type().clone();
+    let struct_ty = struct_ty.unwrap_concrete_struct();
+    let pre = StructRefPre::new(&mut store, struct_ty.clone());
+
+    // Build a three-element list, held alive by the global.
+    let mut list = None;
+    for i in 0..3 {
+        let externref = ExternRef::new(&mut store, i)?;
+        list = Some(StructRef::new(
+            &mut store,
+            &pre,
+            &[externref.into(), list.into()],
+        )?);
+    }
+    global.set(&mut store, list.into())?;
+
+    let snapshot = store.gc_heap_snapshot();
+    assert_eq!(snapshot.stats().live_objects(), 6);
+
+    let json: serde_json::Value = serde_json::from_str(&snapshot.to_heapsnapshot_json())?;
+    let meta = &json["snapshot"]["meta"];
+    let node_fields = meta["node_fields"].as_array().unwrap().len();
+    let edge_fields = meta["edge_fields"].as_array().unwrap().len();
+    let nodes = json["nodes"].as_array().unwrap();
+    let edges = json["edges"].as_array().unwrap();
+    let strings = json["strings"].as_array().unwrap();
+
+    // The synthetic root plus our six objects.
+    assert_eq!(json["snapshot"]["node_count"], 7);
+    assert_eq!(nodes.len(), 7 * node_fields);
+    assert_eq!(
+        json["snapshot"]["edge_count"].as_u64().unwrap() as usize * edge_fields,
+        edges.len()
+    );
+
+    // Every edge points at the start of a node, and the nodes' edge counts add
+    // up to the total number of edges.
+    let edge_count_sum: u64 = nodes
+        .chunks(node_fields)
+        .map(|n| n[4].as_u64().unwrap())
+        .sum();
+    assert_eq!(edge_count_sum as usize * edge_fields, edges.len());
+    for edge in edges.chunks(edge_fields) {
+        let to = edge[2].as_u64().unwrap() as usize;
+        assert_eq!(to % node_fields, 0);
+        assert!(to < nodes.len());
+    }
+
+    // Each cons cell references its `externref` and the next cell, except for
+    // the last one.
+    let name = |node: &[serde_json::Value]| strings[node[1].as_u64().unwrap() as usize].clone();
+    assert_eq!(name(&nodes[..node_fields]), "(GC roots)");
+    let structs = nodes
+        .chunks(node_fields)
+        .filter(|n| name(n).as_str().unwrap().starts_with("struct"))
+        .collect::<Vec<_>>();
+    assert_eq!(structs.len(), 3);
+    let mut edge_counts = structs
+        .iter()
+        .map(|n| n[4].as_u64().unwrap())
+        .collect::<Vec<_>>();
+    edge_counts.sort();
+    assert_eq!(edge_counts, [1, 2, 2]);
+    let externrefs = nodes
+        .chunks(node_fields)
+        .filter(|n| name(n) == "externref")
+        .count();
+    assert_eq!(externrefs, 3);
+
+    Ok(())
+}
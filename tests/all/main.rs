@@ -40,6 +40,7 @@ mod piped_tests;
 mod pooling_allocator;
 mod pulley;
 mod relocs;
+mod snapshot;
 mod stack_creator;
 mod stack_overflow;
 mod store;
@@ -48,10 +49,13 @@ mod table;
 #[cfg(all(feature = "stack-switching", unix, target_arch = "x86_64"))]
 mod tags;
 mod threads;
+mod tiered;
 mod traps;
 mod types;
 mod wait_notify;
 mod winch_engine_features;
+#[cfg(feature = "wmemcheck")]
+mod wmemcheck;
 
 /// A helper to compile a module in a new store with reference types enabled.
 pub(crate) fn ref_types_module(
@@ -0,0 +1,228 @@
+use wasmtime::*;
+
+fn preinitialize(wat: &str) -> Result<Vec<u8>> {
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, ());
+    let linker = Linker::new(&engine);
+    Preinitializer::new().run(&mut store, &linker, wat.as_bytes())
+}
+
+fn instantiate(wasm: &[u8]) -> Result<(Store<()>, Instance)> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, wasm)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    Ok((store, instance))
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn memories_and_globals() -> Result<()> {
+    let wasm = preinitialize(
+        r#"
+            (module
+                (memory (export "memory") 1)
+                (global $g (export "g") (mut i64) (i64.const 0))
+                (func (export "wizer.initialize")
+                    (memory.grow (i32.const 2))
+                    drop
+                    (i32.store (i32.const 100) (i32.const 0x01020304))
+                    (i32.store8 (i32.const 70000) (i32.const 5))
+                    (global.set $g (i64.const 42)))
+            )
+        "#,
+    )?;
+
+    let (mut store, instance) = instantiate(&wasm)?;
+    assert!(instance.get_func(&mut store, "wizer.initialize").is_none());
+
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+    assert_eq!(memory.size(&store), 3);
+    assert_eq!(memory.data(&store)[100..104], [4, 3, 2, 1]);
+    assert_eq!(memory.data(&store)[70000], 5);
+    assert!(memory.data(&store)[104..70000].iter().all(|b| *b == 0));
+
+    let g = instance.get_global(&mut store, "g").unwrap();
+    assert_eq!(g.get(&mut store).unwrap_i64(), 42);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn start_function_is_not_rerun() -> Result<()> {
+    let wasm = preinitialize(
+        r#"
+            (module
+                (global $count (export "count") (mut i32) (i32.const 0))
+                (func $start
+                    (global.set $count (i32.add (global.get $count) (i32.const 1))))
+                (start $start)
+                (func (export "wizer.initialize")
+                    (global.set $count (i32.add (global.get $count) (i32.const 10))))
+            )
+        "#,
+    )?;
+
+    let (mut store, instance) = instantiate(&wasm)?;
+    let count = instance.get_global(&mut store, "count").unwrap();
+    assert_eq!(count.get(&mut store).unwrap_i32(), 11);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tables_and_segments() -> Result<()> {
+    let wasm = preinitialize(
+        r#"
+            (module
+                (table $t (export "t") 2 funcref)
+                (global $f (export "f") (mut funcref) (ref.null func))
+                (memory 1)
+                (data $live "live")
+                (data $dropped "dropped")
+                (elem $passive func $a $b)
+                (elem (i32.const 0) func $a)
+                (func $a (result i32) i32.const 1)
+                (func $b (result i32) i32.const 2)
+                (func (export "wizer.initialize")
+                    (drop (table.grow $t (ref.null func) (i32.const 2)))
+                    (table.init $t $passive (i32.const 2) (i32.const 0) (i32.const 2))
+                    (global.set $f (ref.func $b))
+                    (data.drop $dropped))
+                (func (export "call") (param i32) (result i32)
+                    (call_indirect $t (result i32) (local.get 0)))
+                (func (export "load-live") (result i32)
+                    (memory.init $live (i32.const 0) (i32.const 0) (i32.const 4))
+                    (i32.load (i32.const 0)))
+                (func (export "load-dropped")
+                    (memory.init $dropped (i32.const 0) (i32.const 0) (i32.const 1)))
+            )
+        "#,
+    )?;
+
+    let (mut store, instance) = instantiate(&wasm)?;
+    let table = instance.get_table(&mut store, "t").unwrap();
+    assert_eq!(table.size(&store), 4);
+    assert!(table.get(&mut store, 1).unwrap().is_null());
+
+    let call = instance.get_typed_func::<i32, i32>(&mut store, "call")?;
+    assert_eq!(call.call(&mut store, 0)?, 1);
+    assert_eq!(call.call(&mut store, 2)?, 1);
+    assert_eq!(call.call(&mut store, 3)?, 2);
+    assert!(call.call(&mut store, 1).is_err());
+
+    let f = instance.get_global(&mut store, "f").unwrap();
+    let f = *f.get(&mut store).unwrap_funcref().unwrap();
+    let f = f.typed::<(), i32>(&store)?;
+    assert_eq!(f.call(&mut store, ())?, 2);
+
+    let load_live = instance.get_typed_func::<(), i32>(&mut store, "load-live")?;
+    assert_eq!(
+        load_live.call(&mut store, ())?,
+        i32::from_le_bytes(*b"live")
+    );
+    let load_dropped = instance.get_typed_func::<(), ()>(&mut store, "load-dropped")?;
+    assert!(load_dropped.call(&mut store, ()).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn keep_init_func() -> Result<()> {
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, ());
+    let linker = Linker::new(&engine);
+    let wasm = Preinitializer::new()
+        .init_func("init")
+        .keep_init_func(true)
+        .run(&mut store, &linker, br#"(module (func (export "init")))"#)?;
+
+    let (mut store, instance) = instantiate(&wasm)?;
+    assert!(instance.get_func(&mut store, "init").is_some());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn imported_memories_are_rejected() -> Result<()> {
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    let memory = Memory::new(&mut store, MemoryType::new(1, None))?;
+    linker.define(&store, "", "memory", memory)?;
+    let err = Preinitializer::new()
+        .run(
+            &mut store,
+            &linker,
+            br#"(module (import "" "memory" (memory 1)) (func (export "wizer.initialize")))"#,
+        )
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("imports memories or tables"),
+        "bad error: {err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn externrefs_are_rejected() -> Result<()> {
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    linker.func_wrap("", "make", |mut caller: Caller<'_, ()>| {
+        Ok(Some(ExternRef::new(&mut caller, 1)?))
+    })?;
+    let err = Preinitializer::new()
+        .run(
+            &mut store,
+            &linker,
+            br#"
+                (module
+                    (import "" "make" (func $make (result externref)))
+                    (global $g (mut externref) (ref.null extern))
+                    (func (export "wizer.initialize")
+                        (global.set $g (call $make)))
+                )
+            "#,
+        )
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("non-function references"),
+        "bad error: {err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn component() -> Result<()> {
+    use wasmtime::component::{Component, Linker};
+
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, ());
+    let linker = Linker::new(&engine);
+    let wasm = Preinitializer::new().init_func("init").run_component(
+        &mut store,
+        &linker,
+        br#"
+            (component
+                (core module $m
+                    (global $g (mut i32) (i32.const 0))
+                    (func (export "init") (global.set $g (i32.const 7)))
+                    (func (export "get") (result i32) global.get $g)
+                )
+                (core instance $i (instantiate $m))
+                (func (export "init") (canon lift (core func $i "init")))
+                (func (export "get") (result u32) (canon lift (core func $i "get")))
+            )
+        "#,
+    )?;
+
+    let component = Component::new(&engine, &wasm)?;
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &component)?;
+    let get = instance.get_typed_func::<(), (u32,)>(&mut store, "get")?;
+    assert_eq!(get.call(&mut store, ())?, (7,));
+    Ok(())
+}
@@ -0,0 +1,144 @@
+// Tiered compilation starts out with Winch which only supports these
+// architectures.
+#![cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+
+use std::time::{Duration, Instant};
+use wasmtime::*;
+
+fn tiered_engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.strategy(Strategy::Tiered).tier_up_threshold(1);
+    Engine::new(&config)
+}
+
+/// Repeatedly calls `f` for long enough that the module should have been
+/// recompiled in the background.
+fn run_hot<T>(mut f: impl FnMut() -> Result<T>) -> Result<T> {
+    let start = Instant::now();
+    loop {
+        let result = f()?;
+        if start.elapsed() > Duration::from_millis(500) {
+            return Ok(result);
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn results_are_consistent_across_tiers() -> Result<()> {
+    let engine = tiered_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func $fib (export "fib") (param i32) (result i32)
+                    local.get 0
+                    i32.const 2
+                    i32.lt_u
+                    if (result i32)
+                        local.get 0
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        call $fib
+                        local.get 0
+                        i32.const 2
+                        i32.sub
+                        call $fib
+                        i32.add
+                    end)
+
+                (func (export "sum") (param i32) (result i64)
+                    (local i64)
+                    loop
+                        local.get 1
+                        local.get 0
+                        i64.extend_i32_u
+                        i64.add
+                        local.set 1
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        local.tee 0
+                        br_if 0
+                    end
+                    local.get 1)
+
+                (table 1 funcref)
+                (elem (i32.const 0) $fib)
+                (type $t (func (param i32) (result i32)))
+                (func (export "call-fib-indirect") (param i32) (result i32)
+                    local.get 0
+                    i32.const 0
+                    call_indirect (type $t))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let fib = instance.get_typed_func::<i32, i32>(&mut store, "fib")?;
+    let sum = instance.get_typed_func::<i32, i64>(&mut store, "sum")?;
+    let call_fib_indirect = instance.get_typed_func::<i32, i32>(&mut store, "call-fib-indirect")?;
+
+    run_hot(|| {
+        assert_eq!(fib.call(&mut store, 15)?, 610);
+        assert_eq!(sum.call(&mut store, 1000)?, 500500);
+        assert_eq!(call_fib_indirect.call(&mut store, 10)?, 55);
+        Ok(())
+    })?;
+
+    // A fresh instance of the same module starts out on the baseline tier and
+    // produces the same results.
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let fib = instance.get_typed_func::<i32, i32>(&mut store, "fib")?;
+    assert_eq!(fib.call(&mut store, 15)?, 610);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn traps_after_tier_up() -> Result<()> {
+    let engine = tiered_engine()?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func $div (export "div") (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.div_u)
+                (func (export "call-div") (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    call $div)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let call_div = instance.get_typed_func::<(i32, i32), i32>(&mut store, "call-div")?;
+
+    run_hot(|| {
+        assert_eq!(call_div.call(&mut store, (10, 2))?, 5);
+        Ok(())
+    })?;
+
+    let err = call_div.call(&mut store, (1, 0)).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<Trap>(),
+        Some(&Trap::IntegerDivisionByZero)
+    );
+    let trace = err.downcast_ref::<WasmBacktrace>().unwrap();
+    assert_eq!(trace.frames().len(), 2);
+    assert_eq!(trace.frames()[0].func_name(), Some("div"));
+    Ok(())
+}
+
+#[test]
+fn tier_up_threshold_cannot_be_zero() {
+    let mut config = Config::new();
+    config.strategy(Strategy::Tiered).tier_up_threshold(0);
+    assert!(Engine::new(&config).is_err());
+}
@@ -0,0 +1,220 @@
+use wasmtime::*;
+
+fn wmemcheck_engine(allocator: WmemcheckAllocator) -> Result<Engine> {
+    let mut config = Config::new();
+    config.strategy(Strategy::Cranelift);
+    config.wmemcheck(true).wmemcheck_allocator(allocator);
+    Engine::new(&config)
+}
+
+fn custom_allocator() -> WmemcheckAllocator {
+    WmemcheckAllocator {
+        malloc: vec!["dlmalloc".to_string()],
+        free: vec!["dlfree".to_string()],
+        realloc: vec!["dlrealloc".to_string()],
+        calloc: vec!["dlcalloc".to_string()],
+        ..WmemcheckAllocator::default()
+    }
+}
+
+// A bump allocator whose functions are only recognized by their export names.
+// Global 0 is the stack pointer, so the first 1024 bytes are the stack.
+const BUMP_ALLOCATOR: &str = r#"
+    (global (mut i32) (i32.const 1024))
+    (global $next (mut i32) (i32.const 4096))
+    (func $alloc (export "dlmalloc") (param $size i32) (result i32)
+        (local $p i32)
+        (local.set $p (global.get $next))
+        (global.set $next (i32.add (global.get $next) (local.get $size)))
+        (local.get $p))
+    (func $dealloc (export "dlfree") (param i32))
+    (func (export "dlcalloc") (param $n i32) (param $size i32) (result i32)
+        (local $p i32)
+        (local.set $p (call $alloc (i32.mul (local.get $n) (local.get $size))))
+        (memory.fill (local.get $p) (i32.const 0) (i32.mul (local.get $n) (local.get $size)))
+        (local.get $p))
+    (func (export "dlrealloc") (param $old i32) (param $size i32) (result i32)
+        (local $p i32)
+        (local.set $p (call $alloc (local.get $size)))
+        (memory.copy (local.get $p) (local.get $old) (local.get $size))
+        (call $dealloc (local.get $old))
+        (local.get $p))
+"#;
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn custom_allocator_use_after_free() -> Result<()> {
+    let engine = wmemcheck_engine(custom_allocator())?;
+    let module = Module::new(
+        &engine,
+        format!(
+            r#"
+                (module
+                    (memory 1)
+                    {BUMP_ALLOCATOR}
+                    (func (export "run") (param $use_after_free i32)
+                        (local $p i32)
+                        (local.set $p (call $alloc (i32.const 8)))
+                        (i32.store (local.get $p) (i32.const 1))
+                        (drop (i32.load (local.get $p)))
+                        (call $dealloc (local.get $p))
+                        (if (local.get $use_after_free)
+                            (then (drop (i32.load (local.get $p))))))
+                )
+            "#
+        ),
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<i32, ()>(&mut store, "run")?;
+
+    run.call(&mut store, 0)?;
+    let err = run.call(&mut store, 1).unwrap_err();
+    assert!(
+        format!("{err:?}").contains("Invalid load at addr 0x1008 of size 4"),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn nested_allocator_calls() -> Result<()> {
+    let engine = wmemcheck_engine(custom_allocator())?;
+    let module = Module::new(
+        &engine,
+        format!(
+            r#"
+                (module
+                    (memory 1)
+                    {BUMP_ALLOCATOR}
+                    (func (export "run") (result i32)
+                        (local $p i32)
+                        (local.set $p (call 2 (i32.const 2) (i32.const 4)))
+                        ;; calloc'd memory is defined.
+                        (drop (i32.load offset=4 (local.get $p)))
+                        (i32.store (local.get $p) (i32.const 42))
+                        ;; The `dlmalloc` and `dlfree` calls made by `dlrealloc`
+                        ;; aren't tracked on their own.
+                        (local.set $p (call 3 (local.get $p) (i32.const 16)))
+                        (i32.load (local.get $p)))
+                )
+            "#
+        ),
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(), i32>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, ())?, 42);
+
+    let leaks = store.wmemcheck_leaks();
+    assert_eq!(leaks.len(), 1);
+    assert_eq!(leaks[0].addr(), 4096 + 8);
+    assert_eq!(leaks[0].size(), 16);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn leak_report() -> Result<()> {
+    let engine = wmemcheck_engine(WmemcheckAllocator::default())?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (memory 1)
+                (global (mut i32) (i32.const 1024))
+                (global $next (mut i32) (i32.const 4096))
+                (func $malloc (param $size i32) (result i32)
+                    (local $p i32)
+                    (local.set $p (global.get $next))
+                    (global.set $next (i32.add (global.get $next) (local.get $size)))
+                    (local.get $p))
+                (func $free (param i32))
+                (func $leaky (result i32)
+                    (call $malloc (i32.const 24)))
+                (func (export "run")
+                    (drop (call $leaky))
+                    (call $free (call $malloc (i32.const 8)))
+                    (drop (call $leaky)))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    assert!(store.wmemcheck_leaks().is_empty());
+    run.call(&mut store, ())?;
+
+    let leaks = store.wmemcheck_leaks();
+    assert_eq!(
+        leaks
+            .iter()
+            .map(|l| (l.addr(), l.size()))
+            .collect::<Vec<_>>(),
+        [(4096, 24), (4096 + 24 + 8, 24)]
+    );
+    for leak in &leaks {
+        let names = leak
+            .backtrace()
+            .frames()
+            .iter()
+            .map(|f| f.func_name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, [Some("malloc"), Some("leaky"), None]);
+    }
+    let report = leaks[0].to_string();
+    assert!(
+        report.starts_with("24 bytes at addr 0x1000 allocated at wasm backtrace:"),
+        "{report}"
+    );
+    assert!(report.contains("!malloc"), "{report}");
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn memory64_and_multi_memory() -> Result<()> {
+    let engine = wmemcheck_engine(WmemcheckAllocator {
+        memory: 1,
+        ..WmemcheckAllocator::default()
+    })?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (memory $scratch 1)
+                (memory $heap i64 1)
+                (global (mut i64) (i64.const 1024))
+                (global $next (mut i64) (i64.const 4096))
+                (func $malloc (param $size i64) (result i64)
+                    (local $p i64)
+                    (local.set $p (global.get $next))
+                    (global.set $next (i64.add (global.get $next) (local.get $size)))
+                    (local.get $p))
+                (func $free (param i64))
+                (func (export "run") (param $uninit i32) (result i32)
+                    (local $p i64)
+                    ;; Memory 0 isn't managed by the allocator and isn't checked.
+                    (i32.store $scratch (i32.const 8192) (i32.const 1))
+                    (drop (i32.load $scratch (i32.const 9000)))
+
+                    (local.set $p (call $malloc (i64.const 8)))
+                    (i32.store $heap (local.get $p) (i32.const 7))
+                    (if (local.get $uninit)
+                        (then (drop (i32.load $heap offset=4 (local.get $p)))))
+                    (i32.load $heap (local.get $p)))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<i32, i32>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, 0)?, 7);
+    let err = run.call(&mut store, 1).unwrap_err();
+    assert!(
+        format!("{err:?}").contains("Invalid load at addr 0x100c of size 4"),
+        "{err:?}"
+    );
+    Ok(())
+}
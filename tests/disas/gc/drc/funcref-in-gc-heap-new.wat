@@ -19,8 +19,8 @@
 ;;     gv5 = load.i64 notrap aligned readonly can_move gv4+24
 ;;     sig0 = (i64 vmctx, i32, i32, i32, i32) -> i32 tail
 ;;     sig1 = (i64 vmctx, i64) -> i64 tail
-;;     fn0 = colocated u1610612736:27 sig0
-;;     fn1 = colocated u1610612736:28 sig1
+;;     fn0 = colocated u1610612736:29 sig0
+;;     fn1 = colocated u1610612736:30 sig1
 ;;     stack_limit = gv2
 ;;
 ;;                                 block0(v0: i64, v1: i64, v2: i64):
@@ -45,7 +45,7 @@
 ;;       ret
 ;;       mv      a1, s1
 ;;       ld      a2, 0x10(a1)
-;;       ld      a2, 0x198(a2)
+;;       ld      a2, 0x1a8(a2)
 ;;       mv      a0, a1
 ;;       jalr    a2
 ;;       .byte   0x00, 0x00, 0x00, 0x00
@@ -30,7 +30,7 @@
 ;;
 ;; block1 cold:
 ;;     v13 = load.i64 notrap aligned readonly v1+16
-;;     v14 = load.i64 notrap aligned readonly v13+408
+;;     v14 = load.i64 notrap aligned readonly v13+424
 ;;     call_indirect sig1, v14(v1)
 ;;     trap user1
 ;;
@@ -153,7 +153,7 @@
 ;;     sig0 = (i64 vmctx, i32) -> i64 tail
 ;;     sig1 = (i64 vmctx, i64, i32, i32) -> i64 tail
 ;;     fn0 = colocated u1610612736:7 sig0
-;;     fn1 = colocated u1610612736:52 sig1
+;;     fn1 = colocated u1610612736:54 sig1
 ;;     stack_limit = gv2
 ;;
 ;;                                 block0(v0: i64, v1: i64):
@@ -70,7 +70,7 @@ use crate::{
     stack::Val,
 };
 use anyhow::{Result, ensure};
-use wasmtime_environ::{DefinedFuncIndex, FuncIndex, PtrSize, VMOffsets};
+use wasmtime_environ::{DefinedFuncIndex, FuncIndex, FuncRefIndex, PtrSize, VMOffsets};
 
 /// All the information needed to emit a function call.
 #[derive(Copy, Clone)]
@@ -148,6 +148,11 @@ impl FnCall {
             Callee::FuncRef(_) => {
                 Self::lower_funcref(env.callee_sig::<M::ABI>(callee)?, ptr, context, masm)
             }
+            Callee::Local(i) if vmoffsets.num_tier_up_counters > 0 => {
+                let func_ref = env.translation.module.functions[*i].func_ref;
+                let sig = env.callee_sig::<M::ABI>(callee)?;
+                Self::lower_tiered_local(func_ref, sig, context, masm, vmoffsets)
+            }
             Callee::Local(i) => {
                 let f = env.translation.module.defined_func_index(*i).unwrap();
                 Ok(Self::lower_local(env, f))
@@ -188,6 +193,30 @@ impl FnCall {
         )
     }
 
+    /// Lowers a local function when tiered compilation is enabled.
+    ///
+    /// Local functions may be replaced by an optimized version at runtime, so
+    /// instead of a direct call the callee is loaded from its `VMFuncRef`,
+    /// which is patched when the function tiers up.
+    fn lower_tiered_local<M: MacroAssembler>(
+        func_ref: FuncRefIndex,
+        sig: &ABISig,
+        context: &mut CodeGenContext<Emission>,
+        masm: &mut M,
+        vmoffsets: &VMOffsets<u8>,
+    ) -> Result<(CalleeKind, ContextArgs)> {
+        let callee = context
+            .without::<Result<Reg>, M, _>(&sig.regs, masm, |cx, masm| cx.any_gpr(masm))??;
+        let offset =
+            vmoffsets.vmctx_func_ref(func_ref) + u32::from(vmoffsets.ptr.vm_func_ref_wasm_call());
+        masm.load_ptr(masm.address_at_vmctx(offset)?, writable!(callee))?;
+
+        Ok((
+            CalleeKind::indirect(callee),
+            ContextArgs::pinned_callee_and_caller_vmctx(),
+        ))
+    }
+
     /// Lowers a function import by loading its address to the next available
     /// register.
     fn lower_import<M: MacroAssembler, P: PtrSize>(
@@ -22,8 +22,8 @@ use wasmparser::{
 };
 use wasmtime_cranelift::{TRAP_BAD_SIGNATURE, TRAP_HEAP_MISALIGNED, TRAP_TABLE_OUT_OF_BOUNDS};
 use wasmtime_environ::{
-    FUNCREF_MASK, GlobalIndex, MemoryIndex, PtrSize, TableIndex, Tunables, TypeIndex, WasmHeapType,
-    WasmValType,
+    DefinedFuncIndex, FUNCREF_MASK, GlobalIndex, MemoryIndex, PtrSize, TableIndex, Tunables,
+    TypeIndex, WasmHeapType, WasmValType,
 };
 
 mod context;
@@ -113,6 +113,9 @@ where
     /// Compilation settings for code generation.
     pub tunables: &'a Tunables,
 
+    /// The index of the function being compiled.
+    pub func_index: DefinedFuncIndex,
+
     /// Local counter to track fuel consumption.
     pub fuel_consumed: i64,
     phase: PhantomData<P>,
@@ -128,6 +131,7 @@ where
         context: CodeGenContext<'a, Prologue>,
         env: FuncEnv<'a, 'translation, 'data, M::Ptr>,
         sig: ABISig,
+        func_index: DefinedFuncIndex,
     ) -> CodeGen<'a, 'translation, 'data, M, Prologue> {
         Self {
             sig,
@@ -135,6 +139,7 @@ where
             masm,
             env,
             tunables,
+            func_index,
             source_location: Default::default(),
             control_frames: Default::default(),
             // Empty functions should consume at least 1 fuel unit.
@@ -196,6 +201,7 @@ where
             masm: self.masm,
             env: self.env,
             tunables: self.tunables,
+            func_index: self.func_index,
             source_location: self.source_location,
             control_frames: self.control_frames,
             fuel_consumed: self.fuel_consumed,
@@ -319,6 +325,8 @@ where
 
         self.maybe_emit_epoch_check()?;
 
+        self.maybe_emit_tier_up_check()?;
+
         // Once we have emitted the epilogue and reserved stack space for the locals, we push the
         // base control flow block.
         self.control_frames.push(ControlStackFrame::block(
@@ -1155,6 +1163,69 @@ where
         Ok(())
     }
 
+    /// Checks if tiered compilation is enabled and emits a series of
+    /// instructions that decrement this function's tier-up counter, calling
+    /// into the runtime once it reaches zero.
+    pub fn maybe_emit_tier_up_check(&mut self) -> Result<()> {
+        if !self.tunables.tier_up {
+            return Ok(());
+        }
+
+        let tier_up = self.env.builtins.tier_up::<M::ABI, M::Ptr>()?;
+        let counter_reg = self.context.without::<Result<Reg>, M, _>(
+            &tier_up.sig().regs,
+            self.masm,
+            |cx, masm| cx.any_gpr(masm),
+        )??;
+        let counter_offset = self.env.vmoffsets.vmctx_tier_up_counter(self.func_index);
+
+        self.masm.load(
+            self.masm.address_at_vmctx(counter_offset)?,
+            writable!(counter_reg),
+            OperandSize::S32,
+        )?;
+        self.masm.sub(
+            writable!(counter_reg),
+            counter_reg,
+            RegImm::i32(1),
+            OperandSize::S32,
+        )?;
+        self.masm.store(
+            counter_reg.into(),
+            self.masm.address_at_vmctx(counter_offset)?,
+            OperandSize::S32,
+        )?;
+
+        // The continuation label if the counter hasn't reached zero yet.
+        let continuation = self.masm.get_label()?;
+
+        // Spill locals and registers to avoid conflicts at the control flow
+        // merge below.
+        self.context.spill(self.masm)?;
+        self.masm.branch(
+            IntCmpKind::Ne,
+            counter_reg,
+            RegImm::i32(0),
+            continuation,
+            OperandSize::S32,
+        )?;
+        // Hot function branch.
+        self.context
+            .stack
+            .extend([self.func_index.as_u32().try_into()?]);
+        FnCall::emit::<M>(
+            &mut self.env,
+            self.masm,
+            &mut self.context,
+            Callee::Builtin(tier_up.clone()),
+        )?;
+
+        self.masm.bind(continuation)?;
+        self.context.free_reg(counter_reg);
+
+        Ok(())
+    }
+
     /// Emits a series of instructions that load the `fuel_consumed` field from
     /// `VMStoreContext`.
     fn emit_load_fuel_consumed(&mut self, fuel_reg: Reg) -> Result<()> {
@@ -12,7 +12,9 @@ use std::{
 use target_lexicon::{Architecture, Triple};
 use wasmparser::{FuncValidator, FunctionBody, ValidatorResources};
 use wasmtime_cranelift::CompiledFunction;
-use wasmtime_environ::{ModuleTranslation, ModuleTypesBuilder, Tunables, WasmFuncType};
+use wasmtime_environ::{
+    DefinedFuncIndex, ModuleTranslation, ModuleTypesBuilder, Tunables, WasmFuncType,
+};
 
 #[cfg(feature = "x64")]
 pub(crate) mod x64;
@@ -165,9 +167,10 @@ pub trait TargetIsa: Send + Sync {
         false
     }
 
-    /// Compile a function.
+    /// Compile the function `index`.
     fn compile_function(
         &self,
+        index: DefinedFuncIndex,
         sig: &WasmFuncType,
         body: &FunctionBody,
         translation: &ModuleTranslation,
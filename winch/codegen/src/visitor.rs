@@ -1943,7 +1943,8 @@ where
         )?);
 
         self.maybe_emit_epoch_check()?;
-        self.maybe_emit_fuel_check()
+        self.maybe_emit_fuel_check()?;
+        self.maybe_emit_tier_up_check()
     }
 
     fn visit_br(&mut self, depth: u32) -> Self::Output {